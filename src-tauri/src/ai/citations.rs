@@ -0,0 +1,477 @@
+// src-tauri/src/ai/citations.rs
+// Chunk citations for retrieval-augmented answers and post-hoc grounding checks
+
+use crate::ai::context_builder::ContextChunk;
+use crate::ai::response::DocumentReferenceContext;
+use crate::vector::SearchResult;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Matches citation markers such as `[S1]`, `[S2, S4]` or `[S3; S5]`
+static CITATION_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\[\s*(S\d+(?:\s*[,;]\s*S\d+)*)\s*\]").unwrap());
+
+/// Sentence terminator plus any citation markers that directly follow it
+static SENTENCE_BOUNDARY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[.!?]+(?:\s*\[\s*S\d+(?:\s*[,;]\s*S\d+)*\s*\])*(?:\s+|$)").unwrap());
+
+/// Minimum share of a sentence's content words found in a cited chunk
+const CITED_SUPPORT_THRESHOLD: f32 = 0.2;
+
+/// Minimum share of content words for an uncited sentence to count as supported
+const UNCITED_SUPPORT_THRESHOLD: f32 = 0.5;
+
+/// Sentences shorter than this (in content words) are not checked
+const MIN_CHECKED_WORDS: usize = 4;
+
+/// A retrieved chunk offered to the model under a citation ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CitedChunk {
+    /// Citation ID used in the prompt, e.g. `S1`
+    pub citation_id: String,
+    pub chunk_id: String,
+    pub document_id: String,
+    pub title: String,
+    pub section: Option<String>,
+    pub path: Option<String>,
+    pub start_char: usize,
+    pub end_char: usize,
+    pub similarity: f32,
+    pub content: String,
+}
+
+/// Structured reference parsed from a citation in the answer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkCitation {
+    pub citation_id: String,
+    pub chunk_id: String,
+    pub document_id: String,
+    pub title: String,
+    pub section: Option<String>,
+    pub path: Option<String>,
+    pub start_char: usize,
+    pub end_char: usize,
+    /// How many times the answer cited this chunk
+    pub cited_count: usize,
+}
+
+/// Support check for one answer sentence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentenceSupport {
+    pub sentence: String,
+    /// Valid citation IDs attached to the sentence
+    pub cited_ids: Vec<String>,
+    /// Citation IDs of chunks that support the sentence
+    pub supporting_ids: Vec<String>,
+    /// Best lexical overlap with a supporting chunk (0.0-1.0)
+    pub overlap: f32,
+    pub supported: bool,
+}
+
+/// Result of checking an answer against the chunks it was given
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GroundingReport {
+    pub citations: Vec<ChunkCitation>,
+    pub sentences: Vec<SentenceSupport>,
+    /// Sentences with no supporting chunk
+    pub unsupported_sentences: Vec<String>,
+    /// Citation IDs in the answer that were never offered in the prompt
+    pub invalid_citation_ids: Vec<String>,
+    /// Share of checked sentences that are supported (1.0 when nothing was checked)
+    pub grounding_score: f32,
+}
+
+/// The set of chunks offered to the model for one answer
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CitationSet {
+    pub chunks: Vec<CitedChunk>,
+}
+
+impl CitationSet {
+    /// Assign citation IDs (`S1`, `S2`, ...) to search results in ranking order
+    pub fn from_search_results(results: &[SearchResult]) -> Self {
+        let chunks = results
+            .iter()
+            .enumerate()
+            .map(|(index, result)| {
+                let chunk = &result.chunk;
+                let metadata = &chunk.metadata;
+                CitedChunk {
+                    citation_id: format!("S{}", index + 1),
+                    chunk_id: chunk.id.clone(),
+                    document_id: chunk.document_id.clone(),
+                    title: metadata
+                        .get("title")
+                        .or_else(|| metadata.get("file_name"))
+                        .cloned()
+                        .unwrap_or_else(|| chunk.document_id.clone()),
+                    section: metadata
+                        .get("section")
+                        .cloned()
+                        .or_else(|| heading_in(&chunk.content)),
+                    path: metadata.get("path").cloned(),
+                    start_char: chunk.start_char,
+                    end_char: chunk.end_char,
+                    similarity: result.similarity,
+                    content: chunk.content.clone(),
+                }
+            })
+            .collect();
+
+        Self { chunks }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    pub fn get(&self, citation_id: &str) -> Option<&CitedChunk> {
+        self.chunks.iter().find(|c| c.citation_id == citation_id)
+    }
+
+    /// Chunks as prompt sources, each prefixed with its citation ID
+    pub fn to_context_chunks(&self) -> Vec<ContextChunk> {
+        self.chunks
+            .iter()
+            .map(|chunk| {
+                let mut header = format!("[{}] {}", chunk.citation_id, chunk.title);
+                if let Some(section) = &chunk.section {
+                    header.push_str(&format!(" - {}", section));
+                }
+                ContextChunk {
+                    id: chunk.citation_id.clone(),
+                    label: header.clone(),
+                    content: format!("{}\n{}", header, chunk.content.trim()),
+                    relevance: chunk.similarity,
+                }
+            })
+            .collect()
+    }
+
+    /// The chunks that were actually sent, as cut down by the context budget
+    ///
+    /// Chunks dropped from the prompt are removed, and truncated chunks keep
+    /// only the content the model saw.
+    pub fn restricted_to(&self, sent: &[ContextChunk]) -> Self {
+        let chunks = self
+            .chunks
+            .iter()
+            .filter_map(|chunk| {
+                let sent = sent.iter().find(|s| s.id == chunk.citation_id)?;
+                // Sent content is the citation header line followed by the chunk text
+                let content = sent
+                    .content
+                    .split_once('\n')
+                    .map(|(_, body)| body.to_string())
+                    .unwrap_or_default();
+                Some(CitedChunk {
+                    content,
+                    ..chunk.clone()
+                })
+            })
+            .collect();
+
+        Self { chunks }
+    }
+
+    /// Parse citations out of an answer and check every sentence for support
+    pub fn verify_answer(&self, answer: &str) -> GroundingReport {
+        let mut report = GroundingReport::default();
+        let mut cited_counts: HashMap<String, usize> = HashMap::new();
+        let mut invalid = HashSet::new();
+
+        for sentence in split_sentences(answer) {
+            let cited_ids = citation_ids_in(&sentence);
+            let mut valid_ids = Vec::new();
+            for id in cited_ids {
+                if self.get(&id).is_some() {
+                    *cited_counts.entry(id.clone()).or_default() += 1;
+                    if !valid_ids.contains(&id) {
+                        valid_ids.push(id);
+                    }
+                } else {
+                    invalid.insert(id);
+                }
+            }
+
+            let text = CITATION_PATTERN
+                .replace_all(&sentence, "")
+                .trim()
+                .to_string();
+            let words = content_words(&text);
+            if words.len() < MIN_CHECKED_WORDS {
+                continue;
+            }
+
+            // Cited chunks are checked first; otherwise any offered chunk may support it
+            let (candidates, threshold): (Vec<&CitedChunk>, f32) = if valid_ids.is_empty() {
+                (self.chunks.iter().collect(), UNCITED_SUPPORT_THRESHOLD)
+            } else {
+                (
+                    valid_ids.iter().filter_map(|id| self.get(id)).collect(),
+                    CITED_SUPPORT_THRESHOLD,
+                )
+            };
+
+            let mut supporting_ids = Vec::new();
+            let mut best_overlap: f32 = 0.0;
+            for chunk in candidates {
+                let overlap = overlap_ratio(&words, &chunk.content);
+                best_overlap = best_overlap.max(overlap);
+                if overlap >= threshold {
+                    supporting_ids.push(chunk.citation_id.clone());
+                }
+            }
+
+            let supported = !supporting_ids.is_empty();
+            if !supported {
+                report.unsupported_sentences.push(text.clone());
+            }
+            report.sentences.push(SentenceSupport {
+                sentence: text,
+                cited_ids: valid_ids,
+                supporting_ids,
+                overlap: best_overlap,
+                supported,
+            });
+        }
+
+        report.citations = self
+            .chunks
+            .iter()
+            .filter_map(|chunk| {
+                cited_counts
+                    .get(&chunk.citation_id)
+                    .map(|count| ChunkCitation {
+                        citation_id: chunk.citation_id.clone(),
+                        chunk_id: chunk.chunk_id.clone(),
+                        document_id: chunk.document_id.clone(),
+                        title: chunk.title.clone(),
+                        section: chunk.section.clone(),
+                        path: chunk.path.clone(),
+                        start_char: chunk.start_char,
+                        end_char: chunk.end_char,
+                        cited_count: *count,
+                    })
+            })
+            .collect();
+
+        let mut invalid: Vec<String> = invalid.into_iter().collect();
+        invalid.sort();
+        report.invalid_citation_ids = invalid;
+
+        let checked = report.sentences.len();
+        report.grounding_score = if checked == 0 {
+            1.0
+        } else {
+            report.sentences.iter().filter(|s| s.supported).count() as f32 / checked as f32
+        };
+
+        report
+    }
+}
+
+impl GroundingReport {
+    /// One document reference per cited document, listing the cited sections
+    pub fn document_references(&self) -> Vec<DocumentReferenceContext> {
+        let mut references: Vec<DocumentReferenceContext> = Vec::new();
+
+        for citation in &self.citations {
+            let section = citation
+                .section
+                .clone()
+                .unwrap_or_else(|| format!("chars {}-{}", citation.start_char, citation.end_char));
+            match references
+                .iter_mut()
+                .find(|r| r.document_id == citation.document_id)
+            {
+                Some(existing) => {
+                    if !existing.specific_sections.contains(&section) {
+                        existing.specific_sections.push(section);
+                    }
+                    existing.relevance_score += citation.cited_count as f32;
+                }
+                None => references.push(DocumentReferenceContext {
+                    document_id: citation.document_id.clone(),
+                    title: citation.title.clone(),
+                    relevance_score: citation.cited_count as f32,
+                    specific_sections: vec![section],
+                    reference_reason: format!("Cited as [{}]", citation.citation_id),
+                    path: citation.path.clone(),
+                }),
+            }
+        }
+
+        // Normalise citation counts to a 0-1 relevance score
+        let max = references
+            .iter()
+            .map(|r| r.relevance_score)
+            .fold(0.0_f32, f32::max);
+        if max > 0.0 {
+            for reference in &mut references {
+                reference.relevance_score /= max;
+            }
+        }
+
+        references
+    }
+}
+
+/// First Markdown heading in a chunk, used as its section name
+fn heading_in(content: &str) -> Option<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with('#'))
+        .map(|line| line.trim_start_matches('#').trim().to_string())
+        .filter(|heading| !heading.is_empty())
+}
+
+fn citation_ids_in(text: &str) -> Vec<String> {
+    CITATION_PATTERN
+        .captures_iter(text)
+        .flat_map(|cap| {
+            cap[1]
+                .split([',', ';'])
+                .map(|id| id.trim().to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Split an answer into sentences, keeping trailing citation markers with
+/// the sentence they follow (e.g. "Text. [S1]")
+fn split_sentences(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+
+    for line in text.lines() {
+        let mut start = 0;
+        for boundary in SENTENCE_BOUNDARY.find_iter(line) {
+            let sentence = line[start..boundary.end()].trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence.to_string());
+            }
+            start = boundary.end();
+        }
+        let rest = line[start..].trim();
+        if !rest.is_empty() {
+            sentences.push(rest.to_string());
+        }
+    }
+
+    sentences
+}
+
+const STOP_WORDS: &[&str] = &[
+    "the", "a", "an", "and", "or", "but", "of", "to", "in", "on", "for", "with", "is", "are",
+    "was", "were", "be", "been", "it", "this", "that", "these", "those", "as", "at", "by", "from",
+    "can", "should", "will", "you", "your", "their", "its", "into", "than", "then", "there",
+];
+
+fn content_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.len() > 1)
+        .map(|w| w.to_lowercase())
+        .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+        .collect()
+}
+
+fn overlap_ratio(words: &[String], chunk_content: &str) -> f32 {
+    if words.is_empty() {
+        return 0.0;
+    }
+    let chunk_words: HashSet<String> = content_words(chunk_content).into_iter().collect();
+    let found = words.iter().filter(|w| chunk_words.contains(*w)).count();
+    found as f32 / words.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::DocumentChunk;
+
+    fn result(id: &str, document_id: &str, content: &str) -> SearchResult {
+        SearchResult {
+            chunk: DocumentChunk {
+                id: id.to_string(),
+                document_id: document_id.to_string(),
+                content: content.to_string(),
+                chunk_index: 0,
+                start_char: 100,
+                end_char: 100 + content.len(),
+                metadata: HashMap::new(),
+            },
+            similarity: 0.9,
+            explanation: String::new(),
+        }
+    }
+
+    fn citation_set() -> CitationSet {
+        CitationSet::from_search_results(&[
+            result(
+                "safety:0",
+                "safety.md",
+                "## Lockout\nIsolate the machine from all power sources before maintenance.",
+            ),
+            result(
+                "hr:3",
+                "handbook.md",
+                "Employees accrue twenty vacation days per calendar year.",
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_citation_ids_and_sections() {
+        let set = citation_set();
+        assert_eq!(set.chunks[0].citation_id, "S1");
+        assert_eq!(set.chunks[0].section.as_deref(), Some("Lockout"));
+        assert!(set.to_context_chunks()[1].content.starts_with("[S2]"));
+    }
+
+    #[test]
+    fn test_verify_answer_parses_citations() {
+        let set = citation_set();
+        let answer = "Isolate the machine from all power sources before maintenance [S1]. \
+                      Employees accrue twenty vacation days each year. [S2, S7]";
+        let report = set.verify_answer(answer);
+
+        assert_eq!(report.citations.len(), 2);
+        assert_eq!(report.citations[0].start_char, 100);
+        assert_eq!(report.invalid_citation_ids, vec!["S7".to_string()]);
+        assert!(report.unsupported_sentences.is_empty());
+        assert_eq!(report.document_references().len(), 2);
+    }
+
+    #[test]
+    fn test_verify_answer_ignores_chunks_not_sent() {
+        let set = citation_set();
+        let sent: Vec<ContextChunk> = set
+            .to_context_chunks()
+            .into_iter()
+            .filter(|chunk| chunk.id == "S1")
+            .collect();
+        let sent_set = set.restricted_to(&sent);
+        assert_eq!(sent_set.chunks.len(), 1);
+
+        let answer = "Employees accrue twenty vacation days per calendar year. [S2]";
+        let report = sent_set.verify_answer(answer);
+        assert!(report.citations.is_empty());
+        assert_eq!(report.invalid_citation_ids, vec!["S2".to_string()]);
+        assert_eq!(report.unsupported_sentences.len(), 1);
+    }
+
+    #[test]
+    fn test_verify_answer_flags_unsupported_sentences() {
+        let set = citation_set();
+        let answer = "Isolate the machine before maintenance [S1]. \
+                      Forklift operators must renew certification every three years.";
+        let report = set.verify_answer(answer);
+
+        assert_eq!(report.unsupported_sentences.len(), 1);
+        assert!(report.unsupported_sentences[0].starts_with("Forklift"));
+        assert!(report.grounding_score < 1.0);
+    }
+}
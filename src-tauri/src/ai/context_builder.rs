@@ -0,0 +1,683 @@
+// src-tauri/src/ai/context_builder.rs
+// Token-budgeted assembly of prompt context (system prompt, history, retrieved chunks, question)
+
+use crate::ai::prompts::ConversationTurn;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Context window assumed for local Ollama models when none is configured.
+/// Ollama silently truncates prompts beyond its `num_ctx`, so stay conservative.
+pub const DEFAULT_LOCAL_CONTEXT_WINDOW: usize = 4096;
+
+/// Share of the remaining budget given to retrieved chunks before history
+const CHUNK_BUDGET_SHARE: f32 = 0.7;
+
+/// Maximum share of the history budget spent on the summary of older turns
+const SUMMARY_BUDGET_SHARE: f32 = 0.3;
+
+/// Separator placed between kept chunks
+const CHUNK_SEPARATOR: &str = "\n\n";
+
+/// Counts tokens for a target model
+#[derive(Clone)]
+pub enum TokenCounter {
+    /// Heuristic estimate for providers whose tokenizer is not available locally
+    Estimate { chars_per_token: f32 },
+    /// Exact counts from a HuggingFace `tokenizer.json`
+    Tokenizer(Arc<tokenizers::Tokenizer>),
+}
+
+impl std::fmt::Debug for TokenCounter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenCounter::Estimate { chars_per_token } => f
+                .debug_struct("Estimate")
+                .field("chars_per_token", chars_per_token)
+                .finish(),
+            TokenCounter::Tokenizer(_) => f.write_str("Tokenizer"),
+        }
+    }
+}
+
+impl TokenCounter {
+    /// Per-provider estimator
+    pub fn for_provider(provider: &str) -> Self {
+        let chars_per_token = match provider {
+            "anthropic" => 3.5,
+            "openrouter" => 3.8,
+            // Llama-family tokenizers used by local models are less efficient on
+            // technical text; underestimating here is what overflows num_ctx
+            _ => 3.3,
+        };
+        TokenCounter::Estimate { chars_per_token }
+    }
+
+    /// Load an exact tokenizer from a `tokenizer.json` file
+    pub fn from_tokenizer_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let tokenizer = tokenizers::Tokenizer::from_file(path.as_ref()).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to load tokenizer {}: {}",
+                path.as_ref().display(),
+                e
+            )
+        })?;
+        Ok(TokenCounter::Tokenizer(Arc::new(tokenizer)))
+    }
+
+    /// Count the tokens in a piece of text
+    pub fn count(&self, text: &str) -> usize {
+        if text.is_empty() {
+            return 0;
+        }
+
+        match self {
+            TokenCounter::Estimate { chars_per_token } => {
+                let by_chars = (text.chars().count() as f32 / chars_per_token).ceil() as usize;
+                // Word count guards against short-word text that packs poorly
+                let by_words = (text.split_whitespace().count() as f32 * 1.3).ceil() as usize;
+                by_chars.max(by_words)
+            }
+            TokenCounter::Tokenizer(tokenizer) => match tokenizer.encode(text, false) {
+                Ok(encoding) => encoding.len(),
+                Err(e) => {
+                    tracing::warn!("Tokenizer failed, falling back to estimate: {}", e);
+                    TokenCounter::for_provider("").count(text)
+                }
+            },
+        }
+    }
+
+    /// Truncate text to at most `max_tokens`, preferring a word boundary
+    pub fn truncate(&self, text: &str, max_tokens: usize) -> String {
+        if self.count(text) <= max_tokens {
+            return text.to_string();
+        }
+        if max_tokens == 0 {
+            return String::new();
+        }
+
+        // Binary search the longest character prefix that fits
+        let boundaries: Vec<usize> = text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(text.len()))
+            .collect();
+        let (mut low, mut high) = (0, boundaries.len() - 1);
+        while low < high {
+            let mid = (low + high).div_ceil(2);
+            if self.count(&text[..boundaries[mid]]) <= max_tokens {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        let prefix = &text[..boundaries[low]];
+        match prefix.rfind(char::is_whitespace) {
+            Some(cut) if cut > prefix.len() / 2 => prefix[..cut].trim_end().to_string(),
+            _ => prefix.to_string(),
+        }
+    }
+}
+
+/// Context window, in tokens, of a provider/model combination
+pub fn context_window_for(provider: &str, model: &str) -> usize {
+    let model = model.to_lowercase();
+    match provider {
+        "anthropic" => 200_000,
+        "openrouter" => {
+            if model.contains("claude") {
+                200_000
+            } else if model.contains("gpt-4o")
+                || model.contains("gpt-4.1")
+                || model.contains("llama-3.1")
+                || model.contains("llama-3.2")
+            {
+                128_000
+            } else if model.contains("deepseek") {
+                64_000
+            } else if model.contains("mistral") || model.contains("mixtral") {
+                32_000
+            } else {
+                8_192
+            }
+        }
+        _ => DEFAULT_LOCAL_CONTEXT_WINDOW,
+    }
+}
+
+/// A retrieved piece of content competing for prompt space
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextChunk {
+    /// Stable identifier (chunk ID or section index)
+    pub id: String,
+    /// Human-readable label used in reports
+    pub label: String,
+    pub content: String,
+    /// Retrieval relevance; higher-scoring chunks are kept first
+    pub relevance: f32,
+}
+
+impl ContextChunk {
+    /// Split a pre-formatted context string into chunks on blank lines,
+    /// keeping the original order as the priority
+    ///
+    /// A heading on its own (e.g. `=== DOCUMENT SEARCH RESULTS ===`) is kept
+    /// with the section after it, so it is never kept or dropped apart from
+    /// the content it introduces.
+    pub fn from_sections(context: &str) -> Vec<Self> {
+        let mut sections: Vec<String> = Vec::new();
+        let mut pending: Vec<&str> = Vec::new();
+        for section in context.split("\n\n").map(str::trim) {
+            if section.is_empty() {
+                continue;
+            }
+            pending.push(section);
+            if !is_heading(section) {
+                sections.push(pending.join("\n\n"));
+                pending.clear();
+            }
+        }
+        let total = sections.len().max(1) as f32;
+
+        sections
+            .into_iter()
+            .enumerate()
+            .map(|(index, section)| ContextChunk {
+                id: format!("section_{}", index + 1),
+                label: section
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .chars()
+                    .take(60)
+                    .collect(),
+                content: section,
+                relevance: 1.0 - index as f32 / total,
+            })
+            .collect()
+    }
+}
+
+/// Whether a section is only a heading line such as `=== TITLE ===` or `# Title`
+fn is_heading(section: &str) -> bool {
+    !section.contains('\n')
+        && ((section.starts_with("===") && section.ends_with("===")) || section.starts_with('#'))
+}
+
+/// Token budget for one prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextBudget {
+    /// Model context window in tokens
+    pub context_window: usize,
+    /// Tokens kept free for the model's answer
+    pub reserved_for_response: usize,
+}
+
+impl ContextBudget {
+    /// Budget for a model, reserving at most a quarter of the window for the answer
+    pub fn new(context_window: usize, max_response_tokens: Option<u32>) -> Self {
+        let requested = max_response_tokens.unwrap_or(1024) as usize;
+        Self {
+            context_window,
+            reserved_for_response: requested.min(context_window / 4),
+        }
+    }
+
+    /// Tokens available for the prompt itself
+    pub fn prompt_tokens(&self) -> usize {
+        self.context_window
+            .saturating_sub(self.reserved_for_response)
+    }
+}
+
+/// An item that did not make it into the prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DroppedItem {
+    /// "chunk" or "turn"
+    pub kind: String,
+    pub id: String,
+    pub label: String,
+    pub tokens: usize,
+}
+
+/// Report of how the budget was spent and what was dropped
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContextBudgetReport {
+    pub context_window: usize,
+    pub reserved_for_response: usize,
+    pub system_tokens: usize,
+    pub question_tokens: usize,
+    pub history_tokens: usize,
+    pub summary_tokens: usize,
+    pub chunk_tokens: usize,
+    pub total_prompt_tokens: usize,
+    /// Number of older turns folded into the summary
+    pub summarized_turns: usize,
+    /// Chunks cut short to fit the budget
+    pub truncated_chunks: Vec<String>,
+    pub dropped: Vec<DroppedItem>,
+    pub question_truncated: bool,
+}
+
+impl ContextBudgetReport {
+    /// Whether anything was dropped, truncated or summarised
+    pub fn was_trimmed(&self) -> bool {
+        !self.dropped.is_empty()
+            || !self.truncated_chunks.is_empty()
+            || self.summarized_turns > 0
+            || self.question_truncated
+    }
+}
+
+/// Prompt inputs that fit the budget
+#[derive(Debug, Clone)]
+pub struct AssembledContext {
+    /// Retrieved content joined in priority order
+    pub document_content: String,
+    /// Chunks that made it into the prompt, cut short where the budget required
+    pub chunks: Vec<ContextChunk>,
+    /// Kept turns, preceded by a summary turn when older turns were folded
+    pub conversation_history: Vec<ConversationTurn>,
+    pub user_query: String,
+    pub report: ContextBudgetReport,
+}
+
+/// Builds prompt context within a token budget
+///
+/// The question and the system prompt are always kept (the question is
+/// truncated only if it alone exceeds the budget). The remainder is split
+/// between retrieved chunks and history, with any unused share flowing to
+/// the other side. When history does not fit, the oldest turns are folded
+/// into an extractive summary.
+#[derive(Debug, Clone)]
+pub struct ContextBuilder {
+    counter: TokenCounter,
+    budget: ContextBudget,
+    system_prompt: String,
+    history: Vec<ConversationTurn>,
+    chunks: Vec<ContextChunk>,
+    question: String,
+}
+
+impl ContextBuilder {
+    pub fn new(counter: TokenCounter, budget: ContextBudget) -> Self {
+        Self {
+            counter,
+            budget,
+            system_prompt: String::new(),
+            history: Vec::new(),
+            chunks: Vec::new(),
+            question: String::new(),
+        }
+    }
+
+    /// Fixed prompt text (template skeleton and instructions)
+    pub fn system_prompt(mut self, system_prompt: &str) -> Self {
+        self.system_prompt = system_prompt.to_string();
+        self
+    }
+
+    /// Conversation history, oldest first
+    pub fn history(mut self, history: Vec<ConversationTurn>) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Retrieved chunks; kept in descending relevance
+    pub fn chunks(mut self, chunks: Vec<ContextChunk>) -> Self {
+        self.chunks = chunks;
+        self
+    }
+
+    pub fn question(mut self, question: &str) -> Self {
+        self.question = question.to_string();
+        self
+    }
+
+    /// Assemble the context and report what was kept and dropped
+    pub fn build(self) -> AssembledContext {
+        let mut report = ContextBudgetReport {
+            context_window: self.budget.context_window,
+            reserved_for_response: self.budget.reserved_for_response,
+            system_tokens: self.counter.count(&self.system_prompt),
+            ..Default::default()
+        };
+
+        let mut remaining = self
+            .budget
+            .prompt_tokens()
+            .saturating_sub(report.system_tokens);
+
+        // The question always goes in, truncated only as a last resort
+        let mut user_query = self.question.clone();
+        report.question_tokens = self.counter.count(&user_query);
+        if report.question_tokens > remaining {
+            user_query = self.counter.truncate(&user_query, remaining);
+            report.question_tokens = self.counter.count(&user_query);
+            report.question_truncated = true;
+        }
+        remaining = remaining.saturating_sub(report.question_tokens);
+
+        // Split the rest between chunks and history, letting unused share flow over
+        let history_needed: usize = self.history.iter().map(|t| self.turn_tokens(t)).sum();
+        let chunk_share = (remaining as f32 * CHUNK_BUDGET_SHARE) as usize;
+        let chunk_budget = chunk_share.max(remaining.saturating_sub(history_needed));
+
+        let chunks = self.fit_chunks(chunk_budget, &mut report);
+        let document_content = chunks
+            .iter()
+            .map(|chunk| chunk.content.as_str())
+            .collect::<Vec<_>>()
+            .join(CHUNK_SEPARATOR);
+        let history_budget = remaining.saturating_sub(report.chunk_tokens);
+        let conversation_history = self.fit_history(history_budget, &mut report);
+
+        report.total_prompt_tokens = report.system_tokens
+            + report.question_tokens
+            + report.chunk_tokens
+            + report.history_tokens
+            + report.summary_tokens;
+
+        if report.was_trimmed() {
+            tracing::info!(
+                "Context trimmed to {} of {} prompt tokens: {} items dropped, {} turns summarised",
+                report.total_prompt_tokens,
+                self.budget.prompt_tokens(),
+                report.dropped.len(),
+                report.summarized_turns
+            );
+        }
+
+        AssembledContext {
+            document_content,
+            chunks,
+            conversation_history,
+            user_query,
+            report,
+        }
+    }
+
+    fn turn_tokens(&self, turn: &ConversationTurn) -> usize {
+        // Role label and separator as rendered by the prompt templates
+        self.counter.count(&turn.content) + 2
+    }
+
+    fn fit_chunks(&self, budget: usize, report: &mut ContextBudgetReport) -> Vec<ContextChunk> {
+        let mut ordered: Vec<&ContextChunk> = self.chunks.iter().collect();
+        ordered.sort_by(|a, b| {
+            b.relevance
+                .partial_cmp(&a.relevance)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let separator_tokens = self.counter.count(CHUNK_SEPARATOR);
+        let mut kept = Vec::new();
+        let mut used = 0;
+        for chunk in ordered {
+            let tokens = self.counter.count(&chunk.content);
+            // Every chunk after the first is preceded by a separator
+            let separator = if kept.is_empty() { 0 } else { separator_tokens };
+            let available = budget.saturating_sub(used + separator);
+            if tokens <= available {
+                kept.push(chunk.clone());
+                used += separator + tokens;
+            } else if available >= 64 && kept.is_empty() {
+                // Better a truncated top chunk than no grounding at all
+                let truncated = self.counter.truncate(&chunk.content, available);
+                used += self.counter.count(&truncated);
+                kept.push(ContextChunk {
+                    content: truncated,
+                    ..chunk.clone()
+                });
+                report.truncated_chunks.push(chunk.id.clone());
+            } else {
+                report.dropped.push(DroppedItem {
+                    kind: "chunk".to_string(),
+                    id: chunk.id.clone(),
+                    label: chunk.label.clone(),
+                    tokens,
+                });
+            }
+        }
+
+        report.chunk_tokens = used;
+        kept
+    }
+
+    fn fit_history(
+        &self,
+        budget: usize,
+        report: &mut ContextBudgetReport,
+    ) -> Vec<ConversationTurn> {
+        let total: usize = self.history.iter().map(|t| self.turn_tokens(t)).sum();
+        if total <= budget {
+            report.history_tokens = total;
+            return self.history.clone();
+        }
+
+        // Keep the most recent turns that fit beside a summary of the rest
+        let summary_budget = (budget as f32 * SUMMARY_BUDGET_SHARE) as usize;
+        let recent_budget = budget - summary_budget;
+        let mut split = self.history.len();
+        let mut used = 0;
+        while split > 0 {
+            let tokens = self.turn_tokens(&self.history[split - 1]);
+            if used + tokens > recent_budget {
+                break;
+            }
+            used += tokens;
+            split -= 1;
+        }
+
+        let (older, recent) = self.history.split_at(split);
+        let mut history = Vec::new();
+        let summary = self.summarize_turns(older, summary_budget.saturating_sub(2));
+        if summary.is_empty() {
+            for (index, turn) in older.iter().enumerate() {
+                report.dropped.push(DroppedItem {
+                    kind: "turn".to_string(),
+                    id: format!("turn_{}", index + 1),
+                    label: format!("{} turn", turn.role),
+                    tokens: self.turn_tokens(turn),
+                });
+            }
+        } else {
+            report.summary_tokens = self.counter.count(&summary) + 2;
+            report.summarized_turns = older.len();
+            history.push(ConversationTurn {
+                role: "summary".to_string(),
+                content: summary,
+            });
+        }
+
+        report.history_tokens = used;
+        history.extend(recent.iter().cloned());
+        history
+    }
+
+    /// Extractive summary: the opening sentence of each older turn, newest
+    /// turns preferred when space runs out
+    fn summarize_turns(&self, turns: &[ConversationTurn], budget: usize) -> String {
+        if turns.is_empty() || budget < 16 {
+            return String::new();
+        }
+
+        let header = format!("Earlier conversation ({} turns):", turns.len());
+        let mut used = self.counter.count(&header);
+        let mut lines = Vec::new();
+
+        for turn in turns.iter().rev() {
+            let first_sentence = turn
+                .content
+                .split_inclusive(['.', '?', '!', '\n'])
+                .next()
+                .unwrap_or_default()
+                .trim();
+            let line = format!(
+                "- {}: {}",
+                turn.role,
+                self.counter.truncate(first_sentence, 40)
+            );
+            let tokens = self.counter.count(&line);
+            if used + tokens > budget {
+                break;
+            }
+            used += tokens;
+            lines.push(line);
+        }
+
+        if lines.is_empty() {
+            return String::new();
+        }
+        lines.reverse();
+        format!("{}\n{}", header, lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(role: &str, content: &str) -> ConversationTurn {
+        ConversationTurn {
+            role: role.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_everything_fits_in_large_window() {
+        let counter = TokenCounter::for_provider("anthropic");
+        let assembled = ContextBuilder::new(counter, ContextBudget::new(200_000, Some(4096)))
+            .system_prompt("You are helpful.")
+            .history(vec![turn("user", "Hi"), turn("assistant", "Hello!")])
+            .chunks(ContextChunk::from_sections("First chunk.\n\nSecond chunk."))
+            .question("What does the guide say?")
+            .build();
+
+        assert!(!assembled.report.was_trimmed());
+        assert_eq!(assembled.conversation_history.len(), 2);
+        assert!(assembled.document_content.contains("Second chunk."));
+    }
+
+    #[test]
+    fn test_headings_stay_with_the_following_section() {
+        let chunks = ContextChunk::from_sections(
+            "=== CONVERSATION CONTEXT ===\n\nSession: s1\n\n=== DOCUMENT SEARCH RESULTS ===\n\nDocument 1: Guide\nContent: lockout",
+        );
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(
+            chunks[0].content,
+            "=== CONVERSATION CONTEXT ===\n\nSession: s1"
+        );
+        assert_eq!(chunks[1].label, "=== DOCUMENT SEARCH RESULTS ===");
+        assert!(chunks[1].content.ends_with("Content: lockout"));
+        assert!(chunks[0].relevance > chunks[1].relevance);
+    }
+
+    #[test]
+    fn test_small_window_drops_chunks_and_summarizes_history() {
+        let counter = TokenCounter::for_provider("local");
+        let long_chunk = "Safety procedure step. ".repeat(400);
+        let chunks = vec![
+            ContextChunk {
+                id: "a".to_string(),
+                label: "A".to_string(),
+                content: "Most relevant paragraph about lockout.".to_string(),
+                relevance: 0.9,
+            },
+            ContextChunk {
+                id: "b".to_string(),
+                label: "B".to_string(),
+                content: long_chunk,
+                relevance: 0.5,
+            },
+        ];
+        let history: Vec<_> = (0..120)
+            .map(|i| {
+                turn(
+                    "user",
+                    &format!("Question number {} about the manual. More.", i),
+                )
+            })
+            .collect();
+
+        let budget = ContextBudget::new(1024, Some(256));
+        let assembled = ContextBuilder::new(counter.clone(), budget.clone())
+            .system_prompt("System instructions.")
+            .history(history)
+            .chunks(chunks)
+            .question("What is lockout?")
+            .build();
+
+        let report = &assembled.report;
+        assert!(report.total_prompt_tokens <= budget.prompt_tokens());
+        assert!(assembled.document_content.contains("lockout"));
+        assert!(report.dropped.iter().any(|d| d.id == "b"));
+        assert!(report.summarized_turns > 0);
+        assert_eq!(assembled.conversation_history[0].role, "summary");
+        assert!(assembled
+            .conversation_history
+            .last()
+            .unwrap()
+            .content
+            .contains("119"));
+    }
+
+    #[test]
+    fn test_chunk_separators_count_against_budget() {
+        let counter = TokenCounter::for_provider("local");
+        let chunks: Vec<ContextChunk> = ["alpha beta", "gamma delta", "epsilon zeta"]
+            .iter()
+            .enumerate()
+            .map(|(index, content)| ContextChunk {
+                id: format!("c{}", index),
+                label: content.to_string(),
+                content: content.to_string(),
+                relevance: 1.0 - index as f32 * 0.1,
+            })
+            .collect();
+        let content_tokens: usize = chunks.iter().map(|c| counter.count(&c.content)).sum();
+        let separator_tokens = counter.count(CHUNK_SEPARATOR);
+
+        let builder =
+            ContextBuilder::new(counter.clone(), ContextBudget::new(4096, None)).chunks(chunks);
+        let mut report = ContextBudgetReport::default();
+        let kept = builder.fit_chunks(content_tokens + 2 * separator_tokens, &mut report);
+        assert_eq!(report.chunk_tokens, content_tokens + 2 * separator_tokens);
+        assert!(report.dropped.is_empty());
+        assert_eq!(kept.len(), 3);
+
+        // Without room for the separators the last chunk no longer fits
+        let mut report = ContextBudgetReport::default();
+        let kept = builder.fit_chunks(content_tokens, &mut report);
+        assert_eq!(kept.len(), 2);
+        assert_eq!(report.dropped[0].id, "c2");
+    }
+
+    #[test]
+    fn test_truncate_respects_budget() {
+        let counter = TokenCounter::for_provider("openrouter");
+        let text = "word ".repeat(500);
+        let truncated = counter.truncate(&text, 50);
+        assert!(counter.count(&truncated) <= 50);
+        assert!(!truncated.is_empty());
+    }
+
+    #[test]
+    fn test_context_windows() {
+        assert_eq!(context_window_for("anthropic", "claude-3-haiku"), 200_000);
+        assert_eq!(
+            context_window_for("openrouter", "openai/gpt-4o-mini"),
+            128_000
+        );
+        assert_eq!(
+            context_window_for("local", "llama3.2-3b"),
+            DEFAULT_LOCAL_CONTEXT_WINDOW
+        );
+    }
+}
@@ -2,6 +2,9 @@
 // Enhanced conversation context management system for multi-turn conversations
 
 use crate::ai::context::WorkspaceIntelligenceContext;
+use crate::ai::conversation_store::{
+    ConversationHistoryStore, HistorySearchHit, HistorySearchQuery, RetentionReport,
+};
 use anyhow::Result;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -13,6 +16,8 @@ use std::collections::HashMap;
 pub struct ConversationContextManager {
     /// Active conversation sessions
     sessions: HashMap<String, ConversationSession>,
+    /// Workspace history store, when persistence is enabled
+    history_store: Option<ConversationHistoryStore>,
 }
 
 /// A complete conversation session with enhanced context tracking
@@ -293,6 +298,115 @@ impl ConversationContextManager {
     pub fn new() -> Self {
         Self {
             sessions: HashMap::new(),
+            history_store: None,
+        }
+    }
+
+    /// Attach a workspace history store and resume its persisted sessions
+    ///
+    /// Sessions already in memory take precedence over persisted copies.
+    /// Switching to another workspace's store drops the previous workspace's
+    /// sessions first. Returns the number of sessions resumed from disk.
+    pub fn attach_history_store(&mut self, store: ConversationHistoryStore) -> Result<usize> {
+        if self
+            .history_store
+            .as_ref()
+            .is_some_and(|current| current.root() != store.root())
+        {
+            self.sessions.clear();
+        }
+
+        let mut resumed = 0;
+        for session in store.load_all_sessions()? {
+            if !self.sessions.contains_key(&session.session_id) {
+                self.sessions.insert(session.session_id.clone(), session);
+                resumed += 1;
+            }
+        }
+
+        self.history_store = Some(store);
+        Ok(resumed)
+    }
+
+    /// Detach the history store; sessions stay in memory only
+    pub fn detach_history_store(&mut self) -> Option<ConversationHistoryStore> {
+        self.history_store.take()
+    }
+
+    /// Currently attached history store
+    pub fn history_store(&self) -> Option<&ConversationHistoryStore> {
+        self.history_store.as_ref()
+    }
+
+    /// Search persisted conversation history
+    pub fn search_history(&self, query: &HistorySearchQuery) -> Result<Vec<HistorySearchHit>> {
+        match &self.history_store {
+            Some(store) => store.search(query),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Delete conversation history from memory and disk
+    ///
+    /// With `session_id` only that session is deleted, otherwise all history
+    /// is removed. Returns the number of sessions deleted.
+    pub fn delete_history(&mut self, session_id: Option<&str>) -> Result<usize> {
+        match session_id {
+            Some(session_id) => {
+                let in_memory = self.sessions.remove(session_id).is_some();
+                let on_disk = match &self.history_store {
+                    Some(store) => store.delete_session(session_id)?,
+                    None => false,
+                };
+                Ok(usize::from(in_memory || on_disk))
+            }
+            None => {
+                let in_memory = self.sessions.len();
+                self.sessions.clear();
+                let on_disk = match &self.history_store {
+                    Some(store) => store.delete_all()?,
+                    None => 0,
+                };
+                Ok(in_memory.max(on_disk))
+            }
+        }
+    }
+
+    /// Apply the store's retention policy to persisted history, forgetting
+    /// the deleted sessions in memory as well
+    pub fn apply_history_retention(&mut self) -> Result<RetentionReport> {
+        let Some(store) = &self.history_store else {
+            return Ok(RetentionReport::default());
+        };
+        let report = store.apply_retention()?;
+        for session_id in &report.removed_session_ids {
+            self.sessions.remove(session_id);
+        }
+        Ok(report)
+    }
+
+    /// Persist the session snapshot, and optionally its newest turn
+    ///
+    /// Persistence failures are logged rather than propagated so that a
+    /// read-only workspace never blocks the conversation itself.
+    fn persist_session(&self, session_id: &str, append_last_turn: bool) {
+        let (Some(store), Some(session)) = (&self.history_store, self.sessions.get(session_id))
+        else {
+            return;
+        };
+        if !session.metadata.user_preferences.persist_history {
+            return;
+        }
+
+        if append_last_turn {
+            if let Some(turn) = session.conversation_history.last() {
+                if let Err(e) = store.append_turn(session_id, turn) {
+                    tracing::warn!("Failed to persist conversation turn: {}", e);
+                }
+            }
+        }
+        if let Err(e) = store.save_session_snapshot(session) {
+            tracing::warn!("Failed to persist conversation session: {}", e);
         }
     }
 
@@ -505,6 +619,8 @@ impl ConversationContextManager {
         // Update last activity
         session.metadata.last_activity = chrono::Utc::now();
 
+        self.persist_session(session_id, true);
+
         Ok(turn_id)
     }
 
@@ -1554,6 +1670,8 @@ impl ConversationContextManager {
             }
         }
 
+        self.persist_session(session_id, false);
+
         Ok(())
     }
 
@@ -1586,6 +1704,8 @@ impl ConversationContextManager {
         session.conversation_state.current_task = Some(task);
         session.conversation_state.flow_state = ConversationFlowState::ExecutingTask;
 
+        self.persist_session(session_id, false);
+
         Ok(task_id)
     }
 
@@ -1640,6 +1760,13 @@ impl ConversationContextManager {
     pub fn import_session(&mut self, session_data: &str) -> Result<String> {
         let session: ConversationSession = serde_json::from_str(session_data)?;
         let session_id = session.session_id.clone();
+
+        if let Some(store) = &self.history_store {
+            if session.metadata.user_preferences.persist_history {
+                store.replace_session(&session)?;
+            }
+        }
+
         self.sessions.insert(session_id.clone(), session);
         Ok(session_id)
     }
@@ -0,0 +1,672 @@
+// src-tauri/src/ai/conversation_store.rs
+// Persistent conversation history store backed by per-workspace append-only turn logs
+
+use crate::ai::conversation_context::{ConversationSession, ConversationTurn, ReferenceType};
+use crate::filesystem::atomic_write::write_atomically;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// Workspace-relative directory reserved for conversation history
+pub const CONVERSATIONS_DIR: &str = "intelligence/conversations";
+
+/// Suffix of the session snapshot file (state and metadata without turns)
+const SESSION_FILE_SUFFIX: &str = ".session.json";
+
+/// Suffix of the append-only turn log (one JSON turn per line)
+const TURN_LOG_SUFFIX: &str = ".turns.jsonl";
+
+/// Retention limits applied to persisted conversation history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRetentionPolicy {
+    /// Maximum number of sessions kept on disk
+    pub max_sessions: usize,
+    /// Sessions inactive for longer than this are deleted
+    pub max_age_days: i64,
+    /// Maximum number of turns kept in a single session log
+    pub max_turns_per_session: usize,
+}
+
+impl Default for HistoryRetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_sessions: 200,
+            max_age_days: 90,
+            max_turns_per_session: 1000,
+        }
+    }
+}
+
+/// Query over persisted conversation history
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistorySearchQuery {
+    /// Free text that must appear in the turn content (all terms, case-insensitive)
+    pub text: Option<String>,
+    /// Document name, alias or path referenced by the turn
+    pub document: Option<String>,
+    /// Restrict the search to a single session
+    pub session_id: Option<String>,
+    /// Maximum number of hits to return
+    pub limit: Option<usize>,
+}
+
+/// A single turn matching a history search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistorySearchHit {
+    pub session_id: String,
+    pub turn_id: String,
+    pub role: String,
+    pub timestamp: DateTime<Utc>,
+    /// Excerpt of the turn content around the first match
+    pub snippet: String,
+    /// Documents referenced by the turn that matched the query
+    pub matched_documents: Vec<String>,
+    /// Relevance score (higher is better)
+    pub score: f32,
+}
+
+/// Outcome of applying the retention policy
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub sessions_removed: usize,
+    /// IDs of the sessions deleted as expired or in excess
+    #[serde(default)]
+    pub removed_session_ids: Vec<String>,
+    pub turns_removed: usize,
+    pub sessions_compacted: usize,
+}
+
+/// File-based conversation history store for a single workspace
+///
+/// Each session is stored as two files: a snapshot of the session state
+/// (`<id>.session.json`) rewritten atomically, and an append-only turn log
+/// (`<id>.turns.jsonl`) that only grows until retention compacts it.
+#[derive(Debug, Clone)]
+pub struct ConversationHistoryStore {
+    /// Directory holding the session files
+    root: PathBuf,
+    /// Retention limits for persisted sessions
+    retention: HistoryRetentionPolicy,
+}
+
+impl ConversationHistoryStore {
+    /// Create a store rooted at an arbitrary directory
+    pub fn new<P: AsRef<Path>>(root: P) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(&root).with_context(|| {
+            format!(
+                "Failed to create conversation history directory: {}",
+                root.display()
+            )
+        })?;
+
+        Ok(Self {
+            root,
+            retention: HistoryRetentionPolicy::default(),
+        })
+    }
+
+    /// Create a store in the workspace's reserved conversations directory
+    pub fn for_workspace<P: AsRef<Path>>(workspace_path: P) -> Result<Self> {
+        Self::new(workspace_path.as_ref().join(CONVERSATIONS_DIR))
+    }
+
+    /// Replace the retention policy
+    pub fn with_retention(mut self, retention: HistoryRetentionPolicy) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Directory holding the session files
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Current retention policy
+    pub fn retention(&self) -> &HistoryRetentionPolicy {
+        &self.retention
+    }
+
+    /// Append a single turn to the session's turn log
+    pub fn append_turn(&self, session_id: &str, turn: &ConversationTurn) -> Result<()> {
+        let path = self.turn_log_path(session_id);
+        let mut line =
+            serde_json::to_string(turn).with_context(|| "Failed to serialize conversation turn")?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open turn log: {}", path.display()))?;
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("Failed to append to turn log: {}", path.display()))?;
+
+        Ok(())
+    }
+
+    /// Write the session snapshot (everything except the turn history)
+    pub fn save_session_snapshot(&self, session: &ConversationSession) -> Result<()> {
+        let mut snapshot = session.clone();
+        snapshot.conversation_history.clear();
+
+        let json = serde_json::to_string_pretty(&snapshot)
+            .with_context(|| "Failed to serialize conversation session")?;
+        write_atomically(&self.session_path(&session.session_id), json.as_bytes())
+    }
+
+    /// Replace both the snapshot and the turn log of a session
+    pub fn replace_session(&self, session: &ConversationSession) -> Result<()> {
+        self.save_session_snapshot(session)?;
+        self.rewrite_turn_log(&session.session_id, &session.conversation_history)
+    }
+
+    /// Load a session and its most recent turns
+    ///
+    /// Only the last `max_history_turns` (from the session's preferences) are
+    /// kept in memory; the full log stays on disk for search.
+    pub fn load_session(&self, session_id: &str) -> Result<Option<ConversationSession>> {
+        let session_path = self.session_path(session_id);
+        if !session_path.exists() {
+            return Ok(None);
+        }
+
+        let mut session = self.read_snapshot(&session_path)?;
+        let turns = self.read_turns(session_id)?;
+
+        let max_turns = session.metadata.user_preferences.max_history_turns;
+        let skip = turns.len().saturating_sub(max_turns);
+        session.conversation_history = turns.into_iter().skip(skip).collect();
+
+        Ok(Some(session))
+    }
+
+    /// Load every persisted session, most recently active first
+    pub fn load_all_sessions(&self) -> Result<Vec<ConversationSession>> {
+        let mut sessions = Vec::new();
+
+        for session_id in self.list_session_ids()? {
+            match self.load_session(&session_id) {
+                Ok(Some(session)) => sessions.push(session),
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::warn!(
+                        "Skipping unreadable conversation session {}: {}",
+                        session_id,
+                        e
+                    )
+                }
+            }
+        }
+
+        sessions.sort_by(|a, b| b.metadata.last_activity.cmp(&a.metadata.last_activity));
+        Ok(sessions)
+    }
+
+    /// List the identifiers of all persisted sessions
+    pub fn list_session_ids(&self) -> Result<Vec<String>> {
+        let mut ids = Vec::new();
+
+        for path in self.session_files()? {
+            match self.read_snapshot(&path) {
+                Ok(session) => ids.push(session.session_id),
+                Err(e) => tracing::warn!(
+                    "Skipping unreadable session snapshot {}: {}",
+                    path.display(),
+                    e
+                ),
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Search the full persisted history by text and/or referenced document
+    pub fn search(&self, query: &HistorySearchQuery) -> Result<Vec<HistorySearchHit>> {
+        let terms: Vec<String> = query
+            .text
+            .as_deref()
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(|t| t.to_lowercase())
+            .collect();
+        let document = query
+            .document
+            .as_deref()
+            .map(|d| d.trim().to_lowercase())
+            .filter(|d| !d.is_empty());
+
+        let session_ids = match &query.session_id {
+            Some(id) => vec![id.clone()],
+            None => self.list_session_ids()?,
+        };
+
+        let mut hits = Vec::new();
+        for session_id in session_ids {
+            for turn in self.read_turns(&session_id)? {
+                if let Some(hit) = match_turn(&session_id, &turn, &terms, document.as_deref()) {
+                    hits.push(hit);
+                }
+            }
+        }
+
+        hits.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.timestamp.cmp(&a.timestamp))
+        });
+        if let Some(limit) = query.limit {
+            hits.truncate(limit);
+        }
+
+        Ok(hits)
+    }
+
+    /// Permanently delete a session's snapshot and turn log
+    pub fn delete_session(&self, session_id: &str) -> Result<bool> {
+        let mut removed = false;
+
+        for path in [
+            self.session_path(session_id),
+            self.turn_log_path(session_id),
+        ] {
+            if path.exists() {
+                fs::remove_file(&path)
+                    .with_context(|| format!("Failed to delete {}", path.display()))?;
+                removed = true;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Permanently delete every persisted session in this workspace
+    pub fn delete_all(&self) -> Result<usize> {
+        let mut removed = 0;
+
+        for entry in fs::read_dir(&self.root)
+            .with_context(|| format!("Failed to read {}", self.root.display()))?
+        {
+            let path = entry?.path();
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            if name.ends_with(SESSION_FILE_SUFFIX) {
+                removed += 1;
+            }
+            if name.ends_with(SESSION_FILE_SUFFIX) || name.ends_with(TURN_LOG_SUFFIX) {
+                fs::remove_file(&path)
+                    .with_context(|| format!("Failed to delete {}", path.display()))?;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Apply the retention policy: drop expired or excess sessions and
+    /// compact oversized turn logs
+    pub fn apply_retention(&self) -> Result<RetentionReport> {
+        let mut report = RetentionReport::default();
+        let cutoff = Utc::now() - chrono::Duration::days(self.retention.max_age_days);
+
+        let mut sessions = Vec::new();
+        for path in self.session_files()? {
+            if let Ok(session) = self.read_snapshot(&path) {
+                sessions.push(session);
+            }
+        }
+        sessions.sort_by(|a, b| b.metadata.last_activity.cmp(&a.metadata.last_activity));
+
+        for (index, session) in sessions.iter().enumerate() {
+            if index >= self.retention.max_sessions || session.metadata.last_activity < cutoff {
+                if self.delete_session(&session.session_id)? {
+                    report.sessions_removed += 1;
+                    report.removed_session_ids.push(session.session_id.clone());
+                }
+                continue;
+            }
+
+            let turns = self.read_turns(&session.session_id)?;
+            if turns.len() > self.retention.max_turns_per_session {
+                let excess = turns.len() - self.retention.max_turns_per_session;
+                self.rewrite_turn_log(&session.session_id, &turns[excess..])?;
+                report.turns_removed += excess;
+                report.sessions_compacted += 1;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Read all turns of a session, skipping lines that fail to parse
+    /// (e.g. a partially written last line after a crash)
+    fn read_turns(&self, session_id: &str) -> Result<Vec<ConversationTurn>> {
+        let path = self.turn_log_path(session_id);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let file =
+            File::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut turns = Vec::new();
+
+        for (line_number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<ConversationTurn>(&line) {
+                Ok(turn) => turns.push(turn),
+                Err(e) => tracing::warn!(
+                    "Skipping corrupt turn at {}:{}: {}",
+                    path.display(),
+                    line_number + 1,
+                    e
+                ),
+            }
+        }
+
+        Ok(turns)
+    }
+
+    fn rewrite_turn_log(&self, session_id: &str, turns: &[ConversationTurn]) -> Result<()> {
+        let mut data = String::new();
+        for turn in turns {
+            data.push_str(&serde_json::to_string(turn)?);
+            data.push('\n');
+        }
+        write_atomically(&self.turn_log_path(session_id), data.as_bytes())
+    }
+
+    fn read_snapshot(&self, path: &Path) -> Result<ConversationSession> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("Failed to parse session snapshot {}", path.display()))
+    }
+
+    fn session_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        if !self.root.exists() {
+            return Ok(files);
+        }
+
+        for entry in fs::read_dir(&self.root)
+            .with_context(|| format!("Failed to read {}", self.root.display()))?
+        {
+            let path = entry?.path();
+            if path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.ends_with(SESSION_FILE_SUFFIX))
+            {
+                files.push(path);
+            }
+        }
+
+        Ok(files)
+    }
+
+    fn session_path(&self, session_id: &str) -> PathBuf {
+        self.root.join(format!(
+            "{}{}",
+            file_stem_for(session_id),
+            SESSION_FILE_SUFFIX
+        ))
+    }
+
+    fn turn_log_path(&self, session_id: &str) -> PathBuf {
+        self.root
+            .join(format!("{}{}", file_stem_for(session_id), TURN_LOG_SUFFIX))
+    }
+}
+
+/// Map a session ID to a filesystem-safe file stem
+fn file_stem_for(session_id: &str) -> String {
+    let sanitized: String = session_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if sanitized == session_id && !sanitized.is_empty() {
+        sanitized
+    } else {
+        // Keep distinct IDs distinct after sanitizing
+        use sha2::{Digest, Sha256};
+        let digest = Sha256::digest(session_id.as_bytes());
+        format!("{}-{}", sanitized, &hex::encode(digest)[..12])
+    }
+}
+
+/// Documents referenced by a turn (explicit mentions and resolved references)
+fn referenced_documents(turn: &ConversationTurn) -> Vec<String> {
+    let mut documents = turn.context_contributions.document_references.clone();
+    for reference in &turn.resolved_references {
+        if matches!(reference.reference_type, ReferenceType::Document)
+            && !documents.contains(&reference.resolved_to)
+        {
+            documents.push(reference.resolved_to.clone());
+        }
+    }
+    documents
+}
+
+fn match_turn(
+    session_id: &str,
+    turn: &ConversationTurn,
+    terms: &[String],
+    document: Option<&str>,
+) -> Option<HistorySearchHit> {
+    let matched_documents: Vec<String> = match document {
+        Some(document) => {
+            let matched: Vec<String> = referenced_documents(turn)
+                .into_iter()
+                .filter(|d| d.to_lowercase().contains(document))
+                .collect();
+            if matched.is_empty() {
+                return None;
+            }
+            matched
+        }
+        None => Vec::new(),
+    };
+
+    let content_lower = turn.content.to_lowercase();
+    let mut occurrences = 0;
+    for term in terms {
+        let count = content_lower.matches(term.as_str()).count();
+        if count == 0 {
+            return None;
+        }
+        occurrences += count;
+    }
+
+    if terms.is_empty() && matched_documents.is_empty() {
+        return None;
+    }
+
+    let score = occurrences as f32 + matched_documents.len() as f32;
+    let first_match = terms
+        .iter()
+        .filter_map(|t| content_lower.find(t.as_str()))
+        .min()
+        .unwrap_or(0);
+
+    Some(HistorySearchHit {
+        session_id: session_id.to_string(),
+        turn_id: turn.turn_id.clone(),
+        role: turn.role.clone(),
+        timestamp: turn.timestamp,
+        snippet: snippet_around(&turn.content, &content_lower, first_match),
+        matched_documents,
+        score,
+    })
+}
+
+/// Extract roughly 160 characters of context around a byte offset in the
+/// lowercased content, mapped back onto the original text
+fn snippet_around(content: &str, content_lower: &str, byte_offset: usize) -> String {
+    const CONTEXT_CHARS: usize = 80;
+
+    // Lowercasing can change byte lengths, so work in character positions
+    let char_offset = content_lower
+        .char_indices()
+        .take_while(|(i, _)| *i < byte_offset)
+        .count();
+    let start = char_offset.saturating_sub(CONTEXT_CHARS);
+    let total = content.chars().count();
+    let end = (char_offset + CONTEXT_CHARS).min(total);
+
+    let mut snippet: String = content.chars().skip(start).take(end - start).collect();
+    if start > 0 {
+        snippet.insert_str(0, "...");
+    }
+    if end < total {
+        snippet.push_str("...");
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::conversation_context::ConversationContextManager;
+    use tempfile::TempDir;
+
+    fn manager_with_store(dir: &TempDir) -> ConversationContextManager {
+        let mut manager = ConversationContextManager::new();
+        manager
+            .attach_history_store(ConversationHistoryStore::for_workspace(dir.path()).unwrap())
+            .unwrap();
+        manager
+    }
+
+    #[test]
+    fn test_turns_are_persisted_and_resumed() {
+        let dir = TempDir::new().unwrap();
+        let mut manager = manager_with_store(&dir);
+        manager
+            .add_conversation_turn("session-a", "user", "Summarise user_guide.pdf", None)
+            .unwrap();
+        manager
+            .add_conversation_turn("session-a", "assistant", "The guide covers setup.", None)
+            .unwrap();
+
+        let resumed = manager_with_store(&dir);
+        let session = resumed.get_session_info("session-a").unwrap();
+        assert_eq!(session.conversation_history.len(), 2);
+        assert_eq!(session.conversation_history[1].role, "assistant");
+    }
+
+    #[test]
+    fn test_switching_workspace_drops_previous_sessions() {
+        let first = TempDir::new().unwrap();
+        let second = TempDir::new().unwrap();
+        let mut manager = manager_with_store(&first);
+        manager
+            .add_conversation_turn("session-a", "user", "Summarise user_guide.pdf", None)
+            .unwrap();
+
+        manager
+            .attach_history_store(ConversationHistoryStore::for_workspace(second.path()).unwrap())
+            .unwrap();
+        assert!(manager.get_session_info("session-a").is_none());
+
+        let resumed = manager
+            .attach_history_store(ConversationHistoryStore::for_workspace(first.path()).unwrap())
+            .unwrap();
+        assert_eq!(resumed, 1);
+        assert!(manager.get_session_info("session-a").is_some());
+    }
+
+    #[test]
+    fn test_search_by_text_and_document() {
+        let dir = TempDir::new().unwrap();
+        let mut manager = manager_with_store(&dir);
+        manager
+            .add_conversation_turn("s1", "user", "Please analyze the user_guide.pdf", None)
+            .unwrap();
+        manager
+            .add_conversation_turn("s2", "user", "What is the refund policy?", None)
+            .unwrap();
+
+        let store = ConversationHistoryStore::for_workspace(dir.path()).unwrap();
+        let by_text = store
+            .search(&HistorySearchQuery {
+                text: Some("REFUND policy".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(by_text.len(), 1);
+        assert_eq!(by_text[0].session_id, "s2");
+
+        let by_document = store
+            .search(&HistorySearchQuery {
+                document: Some("user_guide".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(by_document.len(), 1);
+        assert_eq!(by_document[0].session_id, "s1");
+    }
+
+    #[test]
+    fn test_retention_compacts_and_deletes() {
+        let dir = TempDir::new().unwrap();
+        let store = ConversationHistoryStore::for_workspace(dir.path())
+            .unwrap()
+            .with_retention(HistoryRetentionPolicy {
+                max_sessions: 1,
+                max_age_days: 30,
+                max_turns_per_session: 2,
+            });
+        let mut manager = ConversationContextManager::new();
+        manager.attach_history_store(store.clone()).unwrap();
+
+        manager
+            .add_conversation_turn("old", "user", "first", None)
+            .unwrap();
+        for i in 0..4 {
+            manager
+                .add_conversation_turn("new", "user", &format!("message {}", i), None)
+                .unwrap();
+        }
+
+        let report = manager.apply_history_retention().unwrap();
+        assert_eq!(report.sessions_removed, 1);
+        assert_eq!(report.removed_session_ids, vec!["old".to_string()]);
+        assert_eq!(report.turns_removed, 2);
+        assert_eq!(store.list_session_ids().unwrap(), vec!["new".to_string()]);
+
+        // Expired sessions are dropped from memory too, so they are not
+        // written back on the next change
+        assert!(manager.get_session_info("old").is_none());
+        assert!(manager.get_session_info("new").is_some());
+    }
+
+    #[test]
+    fn test_delete_history() {
+        let dir = TempDir::new().unwrap();
+        let mut manager = manager_with_store(&dir);
+        manager
+            .add_conversation_turn("session/with:odd chars", "user", "hello", None)
+            .unwrap();
+
+        assert_eq!(manager.delete_history(None).unwrap(), 1);
+        assert!(manager.get_session_info("session/with:odd chars").is_none());
+        assert!(manager_with_store(&dir)
+            .get_session_info("session/with:odd chars")
+            .is_none());
+    }
+}
@@ -64,6 +64,9 @@ pub struct IntentConfidence {
     pub confidence: f32,
     pub reasoning: String,
     pub alternative_intents: Vec<(Intent, f32)>,
+    /// Details pulled from the request, such as document names or formats
+    #[serde(default)]
+    pub slots: HashMap<String, String>,
 }
 
 pub struct IntentClassifier {
@@ -628,6 +631,7 @@ impl IntentClassifier {
             confidence,
             reasoning,
             alternative_intents: alternatives,
+            slots: HashMap::new(),
         })
     }
 
@@ -0,0 +1,668 @@
+// src-tauri/src/ai/intent_stage.rs
+// Optional LLM and embedding classification stages in front of the pattern classifier
+
+use super::intent::{Intent, IntentClassifier, IntentConfidence};
+use crate::vector::EmbeddingEngine;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+
+/// Stage results below this confidence fall back to the pattern classifier
+pub const DEFAULT_MIN_STAGE_CONFIDENCE: f32 = 0.55;
+
+/// How long the LLM stage may take before the pattern classifier is used instead
+pub const LLM_STAGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Number of neighbours that vote in the embedding stage
+const NEAREST_NEIGHBOURS: usize = 5;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntentClassificationMode {
+    /// Regex patterns and keyword heuristics only
+    #[default]
+    Patterns,
+    /// Ask the configured LLM for structured JSON
+    Llm,
+    /// Nearest neighbour over embedded labelled examples
+    Embedding,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelledExample {
+    pub text: String,
+    pub intent: Intent,
+}
+
+impl LabelledExample {
+    pub fn new(text: impl Into<String>, intent: Intent) -> Self {
+        Self {
+            text: text.into(),
+            intent,
+        }
+    }
+}
+
+/// Reference examples used for few-shot prompting and nearest-neighbour lookup
+const REFERENCE_EXAMPLES: &[(&str, Intent)] = &[
+    (
+        "Compare these two versions of the guide",
+        Intent::CompareDocuments,
+    ),
+    ("What changed between the drafts?", Intent::CompareDocuments),
+    (
+        "Give me a summary of this report",
+        Intent::SummarizeDocument,
+    ),
+    (
+        "TL;DR of the meeting notes please",
+        Intent::SummarizeDocument,
+    ),
+    (
+        "Analyze the structure of this document",
+        Intent::AnalyzeDocument,
+    ),
+    ("Is this contract well organised?", Intent::AnalyzeDocument),
+    ("Rewrite the introduction section", Intent::UpdateContent),
+    ("Fix the typos in paragraph two", Intent::UpdateContent),
+    ("Review the edits made yesterday", Intent::ReviewChanges),
+    (
+        "Pull out all dates and names from the file",
+        Intent::ExtractInformation,
+    ),
+    (
+        "Which email addresses appear in the invoice?",
+        Intent::ExtractInformation,
+    ),
+    (
+        "Search for documents about onboarding",
+        Intent::SearchDocuments,
+    ),
+    (
+        "Where do we mention the refund policy?",
+        Intent::SearchDocuments,
+    ),
+    (
+        "Find the quarterly budget spreadsheet",
+        Intent::FindDocuments,
+    ),
+    ("Only show PDFs from last month", Intent::FilterDocuments),
+    (
+        "What topics are covered in my workspace?",
+        Intent::DiscoverContent,
+    ),
+    (
+        "Show me documents like this one",
+        Intent::FindSimilarContent,
+    ),
+    (
+        "What are the main concepts in these notes?",
+        Intent::ExtractConcepts,
+    ),
+    (
+        "How are these documents connected?",
+        Intent::AnalyzeRelationships,
+    ),
+    ("Write a new project proposal", Intent::CreateDocument),
+    (
+        "Draft a welcome letter for new hires",
+        Intent::CreateDocument,
+    ),
+    (
+        "Adapt this guide for a beginner audience",
+        Intent::AdaptContent,
+    ),
+    (
+        "Convert this markdown file to Word",
+        Intent::TransformFormat,
+    ),
+    (
+        "Produce a PDF export of the final report",
+        Intent::GenerateOutput,
+    ),
+    (
+        "Make this text shorter and clearer",
+        Intent::OptimizeContent,
+    ),
+    (
+        "Tidy up the folders in my workspace",
+        Intent::OrganizeWorkspace,
+    ),
+    ("How healthy is my workspace?", Intent::ReviewWorkspace),
+    ("Export every approved document", Intent::ExportDocuments),
+    ("Change the workspace settings", Intent::ConfigureWorkspace),
+    ("Rename and move these files", Intent::ManageFiles),
+    (
+        "Create a template from this document",
+        Intent::ManageTemplates,
+    ),
+    ("Match the style of our brand guide", Intent::AdaptStyle),
+    ("What tone is this written in?", Intent::AnalyzeStyle),
+    (
+        "Apply the corporate style to this draft",
+        Intent::ApplyStyle,
+    ),
+    ("Is the system working?", Intent::CheckStatus),
+    ("What can you do?", Intent::GetHelp),
+    ("Which models are available?", Intent::ListModels),
+    (
+        "Switch the AI provider to Anthropic",
+        Intent::ConfigureSystem,
+    ),
+    ("What do you mean by that?", Intent::ClarifyQuestion),
+    (
+        "Show me an example of a good summary",
+        Intent::ProvideExample,
+    ),
+    ("Walk me through how indexing works", Intent::ExplainProcess),
+];
+
+/// Held-out paraphrases, including non-English requests, for evaluation
+const EVALUATION_EXAMPLES: &[(&str, Intent)] = &[
+    (
+        "How does the new handbook differ from the old one?",
+        Intent::CompareDocuments,
+    ),
+    ("Compara estos dos documentos", Intent::CompareDocuments),
+    (
+        "Boil this report down to a few bullet points",
+        Intent::SummarizeDocument,
+    ),
+    ("Fasse dieses Dokument zusammen", Intent::SummarizeDocument),
+    ("Résume ce document", Intent::SummarizeDocument),
+    (
+        "Take a close look at how this paper is structured",
+        Intent::AnalyzeDocument,
+    ),
+    (
+        "Grab every phone number in this file",
+        Intent::ExtractInformation,
+    ),
+    (
+        "Is there anything in my files about parental leave?",
+        Intent::SearchDocuments,
+    ),
+    (
+        "Busca documentos sobre facturación",
+        Intent::SearchDocuments,
+    ),
+    (
+        "Where did I put the vendor contract?",
+        Intent::FindDocuments,
+    ),
+    (
+        "Anything else resembling this memo?",
+        Intent::FindSimilarContent,
+    ),
+    (
+        "Put together a fresh onboarding checklist",
+        Intent::CreateDocument,
+    ),
+    ("Erstelle ein neues Angebot", Intent::CreateDocument),
+    ("Turn this into a spreadsheet", Intent::TransformFormat),
+    (
+        "My workspace is a mess, sort it out",
+        Intent::OrganizeWorkspace,
+    ),
+    ("Make it sound like our marketing copy", Intent::AdaptStyle),
+    ("Is everything running okay?", Intent::CheckStatus),
+    ("I'm lost, how do I use this?", Intent::GetHelp),
+    ("Qué modelos tengo disponibles?", Intent::ListModels),
+    (
+        "Step me through what happens when I import a file",
+        Intent::ExplainProcess,
+    ),
+];
+
+/// Built-in labelled examples for nearest-neighbour lookup and few-shot prompts
+pub fn reference_examples() -> Vec<LabelledExample> {
+    REFERENCE_EXAMPLES
+        .iter()
+        .map(|(text, intent)| LabelledExample::new(*text, intent.clone()))
+        .collect()
+}
+
+/// Built-in labelled test set for the evaluation harness
+pub fn evaluation_examples() -> Vec<LabelledExample> {
+    EVALUATION_EXAMPLES
+        .iter()
+        .map(|(text, intent)| LabelledExample::new(*text, intent.clone()))
+        .collect()
+}
+
+/// Shape the LLM is asked to produce
+#[derive(Debug, Deserialize)]
+struct LlmClassification {
+    intent: String,
+    #[serde(default)]
+    slots: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    confidence: Option<f32>,
+    #[serde(default)]
+    reasoning: Option<String>,
+}
+
+/// Classifies intent by asking the configured LLM for structured JSON
+#[derive(Debug, Clone)]
+pub struct LlmIntentStage {
+    intents: Vec<Intent>,
+    examples: Vec<LabelledExample>,
+}
+
+impl LlmIntentStage {
+    pub fn new(intents: Vec<Intent>, examples: Vec<LabelledExample>) -> Self {
+        let mut intents = intents;
+        intents.sort_by_key(|intent| format!("{:?}", intent));
+        Self { intents, examples }
+    }
+
+    /// Stage covering every intent the pattern classifier knows about
+    pub fn for_classifier(classifier: &IntentClassifier) -> Self {
+        Self::new(classifier.get_supported_intents(), reference_examples())
+    }
+
+    pub fn build_prompt(&self, input: &str) -> String {
+        let intent_list = self
+            .intents
+            .iter()
+            .map(|intent| format!("{:?}", intent))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let examples = self
+            .examples
+            .iter()
+            .map(|example| format!("\"{}\" -> {:?}", example.text, example.intent))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        format!(
+            "Classify the user's request into exactly one intent for a document assistant.\n\
+             The request may be in any language.\n\n\
+             Allowed intents: {intent_list}, Unknown\n\n\
+             Examples:\n{examples}\n\n\
+             Respond with JSON only, no prose:\n\
+             {{\"intent\": \"<intent>\", \"slots\": {{\"<name>\": \"<value>\"}}, \"confidence\": <0.0-1.0>, \"reasoning\": \"<short reason>\"}}\n\
+             Slots capture details such as document names, formats, topics or styles.\n\n\
+             Request: {input}"
+        )
+    }
+
+    /// Parse the model output, tolerating code fences and surrounding text
+    pub fn parse_response(&self, raw: &str) -> Result<IntentConfidence> {
+        let start = raw
+            .find('{')
+            .ok_or_else(|| anyhow!("No JSON object in intent classification response"))?;
+        let end = raw
+            .rfind('}')
+            .filter(|end| *end > start)
+            .ok_or_else(|| anyhow!("Unterminated JSON in intent classification response"))?;
+        let parsed: LlmClassification = serde_json::from_str(&raw[start..=end])
+            .context("Failed to parse intent classification JSON")?;
+
+        let intent = self
+            .resolve_intent(&parsed.intent)
+            .ok_or_else(|| anyhow!("LLM returned unknown intent '{}'", parsed.intent))?;
+        let slots = parsed
+            .slots
+            .into_iter()
+            .filter_map(|(name, value)| match value {
+                serde_json::Value::Null => None,
+                serde_json::Value::String(text) if text.trim().is_empty() => None,
+                serde_json::Value::String(text) => Some((name, text)),
+                other => Some((name, other.to_string())),
+            })
+            .collect();
+
+        Ok(IntentConfidence {
+            intent,
+            confidence: parsed.confidence.unwrap_or(0.5).clamp(0.0, 1.0),
+            reasoning: parsed
+                .reasoning
+                .unwrap_or_else(|| "LLM classification".to_string()),
+            alternative_intents: Vec::new(),
+            slots,
+        })
+    }
+
+    /// Match an intent name case-insensitively, ignoring separators
+    fn resolve_intent(&self, name: &str) -> Option<Intent> {
+        let normalise = |s: &str| {
+            s.chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase()
+        };
+        let wanted = normalise(name);
+        if wanted == "unknown" {
+            return Some(Intent::Unknown);
+        }
+        self.intents
+            .iter()
+            .find(|intent| normalise(&format!("{:?}", intent)) == wanted)
+            .cloned()
+    }
+}
+
+/// Embedded labelled examples searched by cosine similarity
+#[derive(Debug, Clone, Default)]
+pub struct ExampleIndex {
+    entries: Vec<(Intent, Vec<f32>)>,
+}
+
+impl ExampleIndex {
+    pub fn new(entries: Vec<(Intent, Vec<f32>)>) -> Self {
+        Self { entries }
+    }
+
+    /// Similarity-weighted vote among the nearest examples
+    pub fn nearest(&self, query: &[f32]) -> IntentConfidence {
+        let mut neighbours: Vec<(&Intent, f32)> = self
+            .entries
+            .iter()
+            .map(|(intent, vector)| (intent, cosine_similarity(query, vector)))
+            .filter(|(_, similarity)| *similarity > 0.0)
+            .collect();
+        neighbours.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        neighbours.truncate(NEAREST_NEIGHBOURS);
+
+        let total: f32 = neighbours.iter().map(|(_, similarity)| similarity).sum();
+        let mut votes: HashMap<&Intent, (f32, f32)> = HashMap::new();
+        for (intent, similarity) in &neighbours {
+            let entry = votes.entry(intent).or_insert((0.0, 0.0));
+            entry.0 += similarity;
+            entry.1 = entry.1.max(*similarity);
+        }
+
+        let mut ranked: Vec<(Intent, f32)> = votes
+            .into_iter()
+            .map(|(intent, (weight, best))| (intent.clone(), (weight / total) * best))
+            .collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        if ranked.is_empty() {
+            return IntentConfidence {
+                intent: Intent::Unknown,
+                confidence: 0.0,
+                reasoning: "No similar labelled examples".to_string(),
+                alternative_intents: Vec::new(),
+                slots: HashMap::new(),
+            };
+        }
+
+        let (intent, confidence) = ranked.remove(0);
+        ranked.truncate(3);
+        IntentConfidence {
+            reasoning: format!(
+                "Nearest labelled examples vote for {:?} ({} neighbours)",
+                intent,
+                neighbours.len()
+            ),
+            intent,
+            confidence: confidence.clamp(0.0, 1.0),
+            alternative_intents: ranked,
+            slots: HashMap::new(),
+        }
+    }
+}
+
+/// Classifies intent by nearest neighbour over embedded labelled examples
+#[derive(Clone)]
+pub struct EmbeddingIntentStage {
+    engine: EmbeddingEngine,
+    index: ExampleIndex,
+}
+
+impl EmbeddingIntentStage {
+    pub async fn build(engine: EmbeddingEngine, examples: &[LabelledExample]) -> Result<Self> {
+        let mut entries = Vec::with_capacity(examples.len());
+        for example in examples {
+            let vector = engine
+                .embed_text(&example.text)
+                .await
+                .with_context(|| format!("Failed to embed example '{}'", example.text))?;
+            entries.push((example.intent.clone(), vector));
+        }
+        tracing::info!("Embedded {} labelled intent examples", entries.len());
+
+        Ok(Self {
+            engine,
+            index: ExampleIndex::new(entries),
+        })
+    }
+
+    pub async fn classify(&self, input: &str) -> Result<IntentConfidence> {
+        let query = self.engine.embed_text(input).await?;
+        Ok(self.index.nearest(&query))
+    }
+}
+
+/// Use a stage result when it is confident enough, otherwise run the patterns
+pub async fn classify_with_fallback(
+    patterns: &IntentClassifier,
+    input: &str,
+    staged: Option<Result<IntentConfidence>>,
+    min_confidence: f32,
+) -> Result<IntentConfidence> {
+    let reason = match staged {
+        None => return patterns.classify(input).await,
+        Some(Ok(result))
+            if result.intent != Intent::Unknown && result.confidence >= min_confidence =>
+        {
+            return Ok(result)
+        }
+        Some(Ok(result)) => format!(
+            "stage returned {:?} at confidence {:.2}",
+            result.intent, result.confidence
+        ),
+        Some(Err(e)) => format!("stage failed: {}", e),
+    };
+
+    tracing::debug!("Falling back to pattern intent classification: {}", reason);
+    let mut result = patterns.classify(input).await?;
+    result.reasoning = format!("{} (pattern fallback: {})", result.reasoning, reason);
+    Ok(result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntentAccuracy {
+    pub intent: Intent,
+    pub total: usize,
+    pub correct: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Misclassification {
+    pub text: String,
+    pub expected: Intent,
+    pub predicted: Intent,
+    pub confidence: f32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntentEvaluationReport {
+    pub total: usize,
+    pub correct: usize,
+    pub accuracy: f32,
+    /// Cases where classification returned an error
+    pub errors: usize,
+    pub per_intent: Vec<IntentAccuracy>,
+    pub misclassified: Vec<Misclassification>,
+}
+
+/// Run a classifier over a labelled test set and report accuracy
+pub async fn evaluate_intents<F, Fut>(
+    cases: &[LabelledExample],
+    mut classify: F,
+) -> IntentEvaluationReport
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<IntentConfidence>>,
+{
+    let mut report = IntentEvaluationReport::default();
+    let mut per_intent: HashMap<Intent, IntentAccuracy> = HashMap::new();
+
+    for case in cases {
+        let (predicted, confidence) = match classify(case.text.clone()).await {
+            Ok(result) => (result.intent, result.confidence),
+            Err(e) => {
+                tracing::warn!("Intent evaluation failed for '{}': {}", case.text, e);
+                report.errors += 1;
+                (Intent::Unknown, 0.0)
+            }
+        };
+
+        let stats = per_intent
+            .entry(case.intent.clone())
+            .or_insert_with(|| IntentAccuracy {
+                intent: case.intent.clone(),
+                total: 0,
+                correct: 0,
+            });
+        stats.total += 1;
+        report.total += 1;
+
+        if predicted == case.intent {
+            stats.correct += 1;
+            report.correct += 1;
+        } else {
+            report.misclassified.push(Misclassification {
+                text: case.text.clone(),
+                expected: case.intent.clone(),
+                predicted,
+                confidence,
+            });
+        }
+    }
+
+    report.accuracy = if report.total > 0 {
+        report.correct as f32 / report.total as f32
+    } else {
+        0.0
+    };
+    report.per_intent = per_intent.into_values().collect();
+    report
+        .per_intent
+        .sort_by_key(|stats| format!("{:?}", stats.intent));
+    report
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_llm_response_with_slots() {
+        let stage = LlmIntentStage::for_classifier(&IntentClassifier::new());
+        let raw = "Sure:\n```json\n{\"intent\": \"summarize_document\", \"slots\": {\"document\": \"handbook.pdf\", \"length\": 3, \"style\": null}, \"confidence\": 0.92}\n```";
+
+        let result = stage.parse_response(raw).unwrap();
+        assert_eq!(result.intent, Intent::SummarizeDocument);
+        assert!((result.confidence - 0.92).abs() < f32::EPSILON);
+        assert_eq!(result.slots.get("document").unwrap(), "handbook.pdf");
+        assert_eq!(result.slots.get("length").unwrap(), "3");
+        assert!(!result.slots.contains_key("style"));
+
+        assert!(stage.parse_response("no json here").is_err());
+        assert!(stage
+            .parse_response("{\"intent\": \"BookFlight\", \"confidence\": 0.9}")
+            .is_err());
+    }
+
+    #[test]
+    fn test_nearest_neighbour_vote() {
+        let index = ExampleIndex::new(vec![
+            (Intent::SummarizeDocument, vec![1.0, 0.0, 0.0]),
+            (Intent::SummarizeDocument, vec![0.9, 0.1, 0.0]),
+            (Intent::SearchDocuments, vec![0.0, 1.0, 0.0]),
+            (Intent::CreateDocument, vec![0.0, 0.0, 1.0]),
+        ]);
+
+        let result = index.nearest(&[0.95, 0.05, 0.0]);
+        assert_eq!(result.intent, Intent::SummarizeDocument);
+        assert!(result.confidence > 0.8);
+
+        let empty = ExampleIndex::default().nearest(&[1.0, 0.0, 0.0]);
+        assert_eq!(empty.intent, Intent::Unknown);
+    }
+
+    #[tokio::test]
+    async fn test_low_confidence_stage_falls_back_to_patterns() {
+        let patterns = IntentClassifier::new();
+        let input = "Summarize this document for me";
+
+        let weak = IntentConfidence {
+            intent: Intent::CreateDocument,
+            confidence: 0.2,
+            reasoning: "guess".to_string(),
+            alternative_intents: Vec::new(),
+            slots: HashMap::new(),
+        };
+        let result = classify_with_fallback(&patterns, input, Some(Ok(weak)), 0.55)
+            .await
+            .unwrap();
+        assert_eq!(result.intent, Intent::SummarizeDocument);
+        assert!(result.reasoning.contains("pattern fallback"));
+
+        let offline = Some(Err(anyhow!("connection refused")));
+        let result = classify_with_fallback(&patterns, input, offline, 0.55)
+            .await
+            .unwrap();
+        assert_eq!(result.intent, Intent::SummarizeDocument);
+
+        let strong = IntentConfidence {
+            intent: Intent::CreateDocument,
+            confidence: 0.9,
+            reasoning: "llm".to_string(),
+            alternative_intents: Vec::new(),
+            slots: HashMap::new(),
+        };
+        let result = classify_with_fallback(&patterns, input, Some(Ok(strong)), 0.55)
+            .await
+            .unwrap();
+        assert_eq!(result.intent, Intent::CreateDocument);
+    }
+
+    #[tokio::test]
+    async fn test_evaluation_report() {
+        let cases = vec![
+            LabelledExample::new("Summarize this document for me", Intent::SummarizeDocument),
+            LabelledExample::new(
+                "Compare the old guide with the new version",
+                Intent::CompareDocuments,
+            ),
+            LabelledExample::new("zzz", Intent::GetHelp),
+        ];
+        let classifier = IntentClassifier::new();
+
+        let report = evaluate_intents(&cases, |text| {
+            let classifier = &classifier;
+            async move { classifier.classify(&text).await }
+        })
+        .await;
+
+        assert_eq!(report.total, 3);
+        assert_eq!(report.correct, 2);
+        assert!((report.accuracy - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(report.misclassified.len(), 1);
+        assert_eq!(report.misclassified[0].expected, Intent::GetHelp);
+        assert_eq!(report.per_intent.len(), 3);
+
+        assert!(!evaluation_examples().is_empty());
+        assert!(reference_examples()
+            .iter()
+            .all(|example| example.intent != Intent::Unknown));
+    }
+}
@@ -2,31 +2,50 @@
 
 pub mod actions;
 pub mod anthropic;
+pub mod citations;
 pub mod context;
+pub mod context_builder;
 pub mod conversation_context;
+pub mod conversation_store;
 pub mod document_commands;
 pub mod intent;
+pub mod intent_stage;
 pub mod nl_operations;
 pub mod ollama;
 pub mod openrouter;
+pub mod prompt_library;
 pub mod prompts;
 pub mod response;
 pub mod text_operations;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
 // Re-export key types
 pub use anthropic::{AnthropicClient, AnthropicConfig};
+#[allow(unused_imports)]
+pub use citations::{CitationSet, GroundingReport};
 pub use context::{DocumentContextManager, DocumentRef, UserPreferences};
+#[allow(unused_imports)]
+pub use context_builder::{ContextBudgetReport, ContextBuilder, TokenCounter};
 pub use conversation_context::ConversationContextManager;
 #[allow(unused_imports)]
 pub use conversation_context::{EnrichedConversationContext, TaskStatus};
+#[allow(unused_imports)]
+pub use conversation_store::{
+    ConversationHistoryStore, HistoryRetentionPolicy, HistorySearchQuery,
+};
 pub use intent::IntentClassifier;
+#[allow(unused_imports)]
+pub use intent_stage::{IntentClassificationMode, IntentEvaluationReport, LabelledExample};
 pub use ollama::{OllamaClient, OllamaConfig};
 pub use openrouter::{OpenRouterClient, OpenRouterConfig};
+pub use prompt_library::PromptLibrary;
+#[allow(unused_imports)]
+pub use prompt_library::{PromptTemplateVersion, TemplateLoadError};
 #[allow(unused_imports)]
 pub use prompts::{PromptContext, PromptTemplates};
 pub use response::{AIResponse, ResponseGenerator};
@@ -40,6 +59,18 @@ pub struct AIConfig {
     pub default_model: String,
     pub temperature: f32,
     pub max_tokens: Option<u32>,
+    /// Override for the model context window in tokens
+    #[serde(default)]
+    pub context_window: Option<usize>,
+    /// Optional HuggingFace `tokenizer.json` for exact token counts
+    #[serde(default)]
+    pub tokenizer_path: Option<String>,
+    /// Optional stage tried before the pattern classifier
+    #[serde(default)]
+    pub intent_classification: IntentClassificationMode,
+    /// Active workspace, whose prompt templates override the global ones
+    #[serde(default)]
+    pub workspace_path: Option<PathBuf>,
 }
 
 impl Default for AIConfig {
@@ -52,6 +83,10 @@ impl Default for AIConfig {
             default_model: "llama3.2-3b".to_string(),
             temperature: 0.7,
             max_tokens: Some(4096),
+            context_window: None,
+            tokenizer_path: None,
+            intent_classification: IntentClassificationMode::default(),
+            workspace_path: None,
         }
     }
 }
@@ -61,10 +96,14 @@ pub struct AIOrchestrator {
     openrouter_client: Option<OpenRouterClient>,
     anthropic_client: Option<AnthropicClient>,
     intent_classifier: IntentClassifier,
+    llm_intent_stage: intent_stage::LlmIntentStage,
+    embedding_intent_stage: Option<intent_stage::EmbeddingIntentStage>,
     #[allow(dead_code)]
     response_generator: ResponseGenerator,
     context_manager: Arc<Mutex<DocumentContextManager>>,
     config: AIConfig,
+    token_counter: TokenCounter,
+    prompt_library: PromptLibrary,
     #[allow(dead_code)]
     vector_search_enabled: bool,
 }
@@ -72,6 +111,7 @@ pub struct AIOrchestrator {
 impl AIOrchestrator {
     pub async fn new(config: AIConfig) -> Result<Self> {
         let intent_classifier = IntentClassifier::new();
+        let llm_intent_stage = intent_stage::LlmIntentStage::for_classifier(&intent_classifier);
         let response_generator = ResponseGenerator::new();
 
         // Initialize clients based on provider
@@ -124,14 +164,28 @@ impl AIOrchestrator {
             }
         }
 
+        let token_counter = match &config.tokenizer_path {
+            Some(path) => TokenCounter::from_tokenizer_file(path).unwrap_or_else(|e| {
+                tracing::warn!("{}; using token estimates", e);
+                TokenCounter::for_provider(&config.provider)
+            }),
+            None => TokenCounter::for_provider(&config.provider),
+        };
+
+        let prompt_library = PromptLibrary::load(config.workspace_path.as_deref());
+
         Ok(Self {
             ollama_client,
             openrouter_client,
             anthropic_client,
             intent_classifier,
+            llm_intent_stage,
+            embedding_intent_stage: None,
             response_generator,
             context_manager: Arc::new(Mutex::new(DocumentContextManager::new())),
             config,
+            token_counter,
+            prompt_library,
             vector_search_enabled: true, // Enable vector search for document-aware AI
         })
     }
@@ -141,21 +195,28 @@ impl AIOrchestrator {
         input: &str,
         context: Option<&str>,
     ) -> Result<AIResponse> {
-        self.process_conversation_with_session(input, context, "default")
+        self.process_conversation_with_session(input, context, "default", None)
             .await
     }
 
     /// Process conversation with session-specific context management
+    ///
+    /// `classified_intent` is used when the caller has already classified the
+    /// input, so the configured stage does not run a second time.
     pub async fn process_conversation_with_session(
         &self,
         input: &str,
         context: Option<&str>,
         session_id: &str,
+        classified_intent: Option<intent::IntentConfidence>,
     ) -> Result<AIResponse> {
         use prompts::{PromptContext, PromptTemplates};
 
         // Step 1: Classify intent
-        let intent = self.intent_classifier.classify(input).await?;
+        let intent = match classified_intent {
+            Some(intent) => intent,
+            None => self.classify_intent(input).await?,
+        };
 
         // Step 2: Get enhanced context from context manager and existing context
         let enhanced_context = if let Some(ctx) = context {
@@ -179,12 +240,26 @@ impl AIOrchestrator {
         // Step 3: Detect appropriate prompt type and build context-aware prompt
         let prompt_type = PromptTemplates::detect_prompt_type(input);
 
-        let enhanced_input = if enhanced_context.contains("No document context available")
+        let budget = context_builder::ContextBudget::new(
+            self.config.context_window.unwrap_or_else(|| {
+                context_builder::context_window_for(
+                    &self.config.provider,
+                    &self.config.default_model,
+                )
+            }),
+            self.config.max_tokens,
+        );
+
+        let (enhanced_input, budget_report, prompt_template) = if enhanced_context
+            .contains("No document context available")
             || enhanced_context.starts_with("No relevant documents")
             || enhanced_context.starts_with("Vector search unavailable")
         {
             // Use original input when no document context is available
-            input.to_string()
+            let assembled = ContextBuilder::new(self.token_counter.clone(), budget)
+                .question(input)
+                .build();
+            (assembled.user_query, assembled.report, None)
         } else {
             // Extract conversation history from context manager
             let conversation_history = {
@@ -195,84 +270,45 @@ impl AIOrchestrator {
                     .map(|ctx| ctx.conversation_history.clone())
                     .unwrap_or_default()
             };
+            let document_metadata = {
+                let context_manager = self.context_manager.lock().await;
+                context_manager.extract_document_metadata(session_id)
+            };
+
+            // Measure the template itself so the budget only covers variable content
+            let (skeleton, _) = self.render_prompt(
+                &prompt_type,
+                &PromptContext {
+                    document_content: Some(String::new()),
+                    document_metadata: document_metadata.clone(),
+                    user_query: String::new(),
+                    conversation_history: Vec::new(),
+                },
+            );
+
+            let assembled = ContextBuilder::new(self.token_counter.clone(), budget)
+                .system_prompt(&skeleton)
+                .history(conversation_history)
+                .chunks(context_builder::ContextChunk::from_sections(
+                    &enhanced_context,
+                ))
+                .question(input)
+                .build();
 
             // Build document-aware prompt using templates
             let prompt_context = PromptContext {
-                document_content: Some(enhanced_context),
-                document_metadata: {
-                    let context_manager = self.context_manager.lock().await;
-                    context_manager.extract_document_metadata(session_id)
-                },
-                user_query: input.to_string(),
-                conversation_history,
+                document_content: Some(assembled.document_content),
+                document_metadata,
+                user_query: assembled.user_query,
+                conversation_history: assembled.conversation_history,
             };
 
-            match prompt_type {
-                prompts::PromptType::DocumentAnalysis => {
-                    PromptTemplates::build_document_analysis_prompt(&prompt_context)
-                }
-                prompts::PromptType::QuestionAnswering => {
-                    PromptTemplates::build_qa_prompt(&prompt_context)
-                }
-                prompts::PromptType::StructureAnalysis => {
-                    PromptTemplates::build_structure_analysis_prompt(
-                        &prompt_context.document_content.unwrap_or_default(),
-                        input,
-                    )
-                }
-                prompts::PromptType::ProcedureAnalysis => {
-                    PromptTemplates::build_procedure_analysis_prompt(
-                        &prompt_context.document_content.unwrap_or_default(),
-                        input,
-                    )
-                }
-                prompts::PromptType::StyleAnalysis => PromptTemplates::build_style_analysis_prompt(
-                    &prompt_context.document_content.unwrap_or_default(),
-                    input,
-                ),
-                _ => {
-                    // Default to question-answering for other types
-                    PromptTemplates::build_qa_prompt(&prompt_context)
-                }
-            }
+            let (prompt, template) = self.render_prompt(&prompt_type, &prompt_context);
+            (prompt, assembled.report, Some(template))
         };
 
         // Step 4: Generate response based on provider
-        let response_content = match self.config.provider.as_str() {
-            "local" => {
-                if let Some(client) = &self.ollama_client {
-                    client
-                        .simple_chat(&self.config.default_model, &enhanced_input)
-                        .await?
-                } else {
-                    return Err(anyhow::anyhow!("Ollama client not available"));
-                }
-            }
-            "openrouter" => {
-                if let Some(client) = &self.openrouter_client {
-                    client
-                        .simple_chat(&self.config.default_model, &enhanced_input)
-                        .await?
-                } else {
-                    return Err(anyhow::anyhow!("OpenRouter client not available"));
-                }
-            }
-            "anthropic" => {
-                if let Some(client) = &self.anthropic_client {
-                    client
-                        .simple_chat(&self.config.default_model, &enhanced_input)
-                        .await?
-                } else {
-                    return Err(anyhow::anyhow!("Anthropic client not available"));
-                }
-            }
-            _ => {
-                return Err(anyhow::anyhow!(
-                    "Unknown provider: {}",
-                    self.config.provider
-                ));
-            }
-        };
+        let response_content = self.generate(&enhanced_input).await?;
 
         // Step 5: Record conversation turn
         let _ = self.add_conversation_turn(session_id, "user", input).await;
@@ -292,10 +328,11 @@ impl AIOrchestrator {
             document_references: vec![],
             action_items: vec![],
             style_guidance: None,
+            grounding: None,
             metadata: response::ResponseMetadata {
                 processing_time_ms: 0,
                 model_used: self.config.default_model.clone(),
-                tokens_used: None,
+                tokens_used: Some(budget_report.total_prompt_tokens as u32),
                 confidence_explanation:
                     "Document-aware response using context manager and prompt templates".to_string(),
                 context_used: true,
@@ -306,12 +343,282 @@ impl AIOrchestrator {
                     "Used document-aware processing".to_string(),
                     "Applied conversation context".to_string(),
                 ],
+                context_budget: Some(budget_report),
+                prompt_template,
             },
         };
 
         Ok(response)
     }
 
+    /// Answer a question from retrieved chunks, requiring chunk citations
+    ///
+    /// Every search result is offered to the model under a citation ID. The
+    /// answer's citations are parsed back into structured references and each
+    /// sentence is checked for a supporting chunk.
+    pub async fn process_grounded_question(
+        &self,
+        input: &str,
+        search_results: &[crate::vector::SearchResult],
+        supplementary_context: Option<&str>,
+        session_id: &str,
+        classified_intent: Option<intent::IntentConfidence>,
+    ) -> Result<AIResponse> {
+        let start_time = std::time::Instant::now();
+        let intent = match classified_intent {
+            Some(intent) => intent,
+            None => self.classify_intent(input).await?,
+        };
+        let citation_set = CitationSet::from_search_results(search_results);
+
+        let conversation_history = {
+            let context_manager = self.context_manager.lock().await;
+            context_manager
+                .contexts
+                .get(session_id)
+                .map(|ctx| ctx.conversation_history.clone())
+                .unwrap_or_default()
+        };
+
+        let budget = context_builder::ContextBudget::new(
+            self.config.context_window.unwrap_or_else(|| {
+                context_builder::context_window_for(
+                    &self.config.provider,
+                    &self.config.default_model,
+                )
+            }),
+            self.config.max_tokens,
+        );
+        let (skeleton, _) = self.render_prompt(
+            &prompts::PromptType::GroundedQuestionAnswering,
+            &PromptContext {
+                document_content: Some(String::new()),
+                document_metadata: None,
+                user_query: String::new(),
+                conversation_history: Vec::new(),
+            },
+        );
+
+        // Supplementary context is useful background but is never citable
+        let mut sources = citation_set.to_context_chunks();
+        if let Some(extra) = supplementary_context.filter(|c| !c.trim().is_empty()) {
+            sources.push(context_builder::ContextChunk {
+                id: "background".to_string(),
+                label: "Background (not citable)".to_string(),
+                content: format!("BACKGROUND (do not cite):\n{}", extra),
+                relevance: 0.0,
+            });
+        }
+
+        let assembled = ContextBuilder::new(self.token_counter.clone(), budget)
+            .system_prompt(&skeleton)
+            .history(conversation_history)
+            .chunks(sources)
+            .question(input)
+            .build();
+        let (prompt, prompt_template) = self.render_prompt(
+            &prompts::PromptType::GroundedQuestionAnswering,
+            &PromptContext {
+                document_content: Some(assembled.document_content),
+                document_metadata: None,
+                user_query: assembled.user_query,
+                conversation_history: assembled.conversation_history,
+            },
+        );
+
+        let response_content = self.generate(&prompt).await?;
+        // Only chunks that survived the budget could have grounded the answer
+        let citation_set = citation_set.restricted_to(&assembled.chunks);
+        let grounding = citation_set.verify_answer(&response_content);
+
+        let _ = self.add_conversation_turn(session_id, "user", input).await;
+        let _ = self
+            .add_conversation_turn(session_id, "assistant", &response_content)
+            .await;
+
+        let mut reasoning_chain = vec![format!(
+            "Offered {} cited chunks, {} cited in the answer",
+            citation_set.chunks.len(),
+            grounding.citations.len()
+        )];
+        if !grounding.unsupported_sentences.is_empty() {
+            reasoning_chain.push(format!(
+                "{} sentence(s) have no supporting chunk",
+                grounding.unsupported_sentences.len()
+            ));
+        }
+        if !grounding.invalid_citation_ids.is_empty() {
+            reasoning_chain.push(format!(
+                "Ignored unknown citations: {}",
+                grounding.invalid_citation_ids.join(", ")
+            ));
+        }
+
+        let confidence = grounding.grounding_score;
+        Ok(AIResponse {
+            response_type: response::ResponseType::Information,
+            content: response_content,
+            intent: intent.intent.clone(),
+            confidence,
+            confidence_level: response::ConfidenceLevel::from_score(confidence),
+            suggested_actions: vec![],
+            follow_up_questions: vec![],
+            document_references: grounding.document_references(),
+            action_items: vec![],
+            style_guidance: None,
+            metadata: response::ResponseMetadata {
+                processing_time_ms: start_time.elapsed().as_millis() as u64,
+                model_used: self.config.default_model.clone(),
+                tokens_used: Some(assembled.report.total_prompt_tokens as u32),
+                confidence_explanation: format!(
+                    "{:.0}% of checked sentences are supported by a source chunk",
+                    confidence * 100.0
+                ),
+                context_used: true,
+                documents_analyzed: citation_set.chunks.len(),
+                session_id: Some(session_id.to_string()),
+                turn_id: None,
+                reasoning_chain,
+                context_budget: Some(assembled.report),
+                prompt_template: Some(prompt_template),
+            },
+            grounding: Some(grounding),
+        })
+    }
+
+    /// Embed the labelled intent examples so the embedding stage can run
+    pub async fn enable_embedding_intent_stage(
+        &mut self,
+        engine: crate::vector::EmbeddingEngine,
+    ) -> Result<()> {
+        let stage =
+            intent_stage::EmbeddingIntentStage::build(engine, &intent_stage::reference_examples())
+                .await?;
+        self.embedding_intent_stage = Some(stage);
+        Ok(())
+    }
+
+    /// Classify intent with the configured stage, falling back to patterns
+    pub async fn classify_intent(&self, input: &str) -> Result<intent::IntentConfidence> {
+        let staged = match self.config.intent_classification {
+            IntentClassificationMode::Patterns => None,
+            IntentClassificationMode::Llm => {
+                let prompt = self.llm_intent_stage.build_prompt(input);
+                let generated =
+                    tokio::time::timeout(intent_stage::LLM_STAGE_TIMEOUT, self.generate(&prompt))
+                        .await
+                        .unwrap_or_else(|_| {
+                            Err(anyhow::anyhow!(
+                                "LLM intent classification timed out after {:?}",
+                                intent_stage::LLM_STAGE_TIMEOUT
+                            ))
+                        });
+                Some(generated.and_then(|raw| self.llm_intent_stage.parse_response(&raw)))
+            }
+            IntentClassificationMode::Embedding => match &self.embedding_intent_stage {
+                Some(stage) => Some(stage.classify(input).await),
+                None => None,
+            },
+        };
+
+        intent_stage::classify_with_fallback(
+            &self.intent_classifier,
+            input,
+            staged,
+            intent_stage::DEFAULT_MIN_STAGE_CONFIDENCE,
+        )
+        .await
+    }
+
+    /// Report classification accuracy over a labelled test set
+    pub async fn evaluate_intent_classification(
+        &self,
+        cases: &[LabelledExample],
+    ) -> IntentEvaluationReport {
+        intent_stage::evaluate_intents(
+            cases,
+            |text| async move { self.classify_intent(&text).await },
+        )
+        .await
+    }
+
+    /// Send a prompt to the configured provider
+    async fn generate(&self, prompt: &str) -> Result<String> {
+        match self.config.provider.as_str() {
+            "local" => match &self.ollama_client {
+                Some(client) => client.simple_chat(&self.config.default_model, prompt).await,
+                None => Err(anyhow::anyhow!("Ollama client not available")),
+            },
+            "openrouter" => match &self.openrouter_client {
+                Some(client) => client.simple_chat(&self.config.default_model, prompt).await,
+                None => Err(anyhow::anyhow!("OpenRouter client not available")),
+            },
+            "anthropic" => match &self.anthropic_client {
+                Some(client) => client.simple_chat(&self.config.default_model, prompt).await,
+                None => Err(anyhow::anyhow!("Anthropic client not available")),
+            },
+            _ => Err(anyhow::anyhow!(
+                "Unknown provider: {}",
+                self.config.provider
+            )),
+        }
+    }
+
+    /// Replace the loaded prompt templates, e.g. after switching workspace
+    pub fn set_prompt_library(&mut self, library: PromptLibrary) {
+        self.prompt_library = library;
+    }
+
+    /// Render a prompt from the loaded templates, falling back to the built-in one
+    fn render_prompt(
+        &self,
+        prompt_type: &prompts::PromptType,
+        context: &PromptContext,
+    ) -> (String, PromptTemplateVersion) {
+        let name = prompt_type.template_name();
+        self.prompt_library
+            .render(name, &prompt_library::prompt_variables(context))
+            .unwrap_or_else(|| {
+                (
+                    Self::render_builtin_prompt(prompt_type, context),
+                    PromptTemplateVersion::builtin(name),
+                )
+            })
+    }
+
+    /// Render the built-in prompt template for a prompt type
+    fn render_builtin_prompt(prompt_type: &prompts::PromptType, context: &PromptContext) -> String {
+        let document_content = context.document_content.as_deref().unwrap_or_default();
+        match prompt_type {
+            prompts::PromptType::DocumentAnalysis => {
+                PromptTemplates::build_document_analysis_prompt(context)
+            }
+            prompts::PromptType::QuestionAnswering => PromptTemplates::build_qa_prompt(context),
+            prompts::PromptType::GroundedQuestionAnswering => {
+                PromptTemplates::build_grounded_qa_prompt(context)
+            }
+            prompts::PromptType::StructureAnalysis => {
+                PromptTemplates::build_structure_analysis_prompt(
+                    document_content,
+                    &context.user_query,
+                )
+            }
+            prompts::PromptType::ProcedureAnalysis => {
+                PromptTemplates::build_procedure_analysis_prompt(
+                    document_content,
+                    &context.user_query,
+                )
+            }
+            prompts::PromptType::StyleAnalysis => {
+                PromptTemplates::build_style_analysis_prompt(document_content, &context.user_query)
+            }
+            _ => {
+                // Default to question-answering for other types
+                PromptTemplates::build_qa_prompt(context)
+            }
+        }
+    }
+
     pub async fn is_available(&self) -> bool {
         match self.config.provider.as_str() {
             "local" => {
@@ -0,0 +1,602 @@
+// src-tauri/src/ai/prompt_library.rs
+// Prompt templates loaded from global and per-workspace directories
+
+use super::prompts::PromptContext;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Template directory inside a workspace
+pub const WORKSPACE_PROMPTS_DIR: &str = ".fiovana/prompts";
+
+/// File extension for prompt templates
+pub const PROMPT_FILE_EXTENSION: &str = "prompt";
+
+/// Version reported when the compiled-in template is used
+pub const BUILTIN_VERSION: &str = "builtin";
+
+/// Variables a template may reference
+pub const PROMPT_VARIABLES: &[&str] = &[
+    "document_content",
+    "user_query",
+    "conversation_history",
+    "document_title",
+    "document_type",
+    "sections",
+    "key_concepts",
+];
+
+/// Nested includes deeper than this are rejected
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// Identifies which template and version produced a prompt
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptTemplateVersion {
+    pub name: String,
+    pub version: String,
+    /// File the template was loaded from, or "builtin"
+    pub source: String,
+}
+
+impl PromptTemplateVersion {
+    pub fn builtin(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: BUILTIN_VERSION.to_string(),
+            source: BUILTIN_VERSION.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Variable(String),
+    If {
+        variable: String,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Include(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct FrontMatter {
+    #[serde(default)]
+    name: Option<String>,
+    version: serde_yaml::Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct PromptTemplate {
+    pub version: PromptTemplateVersion,
+    nodes: Vec<Node>,
+}
+
+impl PromptTemplate {
+    /// Parse a template file: YAML front matter between `---` lines, then the body
+    pub fn parse(content: &str, default_name: &str, source: &str) -> Result<Self> {
+        let content = content.trim_start_matches('\u{feff}');
+        let rest = content
+            .strip_prefix("---")
+            .ok_or_else(|| anyhow!("Template must start with '---' front matter"))?;
+        let end = rest
+            .find("\n---")
+            .ok_or_else(|| anyhow!("Unterminated front matter"))?;
+        let front: FrontMatter = serde_yaml::from_str(&rest[..end])
+            .map_err(|e| anyhow!("Invalid front matter: {}", e))?;
+        let body = rest[end + 4..]
+            .strip_prefix('\n')
+            .or_else(|| rest[end + 4..].strip_prefix("\r\n"))
+            .unwrap_or(&rest[end + 4..]);
+
+        let version = match front.version {
+            serde_yaml::Value::String(s) => s,
+            serde_yaml::Value::Number(n) => n.to_string(),
+            _ => bail!("Template version must be a string or number"),
+        };
+        if version.trim().is_empty() {
+            bail!("Template version must not be empty");
+        }
+
+        Ok(Self {
+            version: PromptTemplateVersion {
+                name: front.name.unwrap_or_else(|| default_name.to_string()),
+                version,
+                source: source.to_string(),
+            },
+            nodes: parse_body(body)?,
+        })
+    }
+
+    fn includes(&self) -> Vec<&str> {
+        let mut found = Vec::new();
+        collect_includes(&self.nodes, &mut found);
+        found
+    }
+
+    fn unknown_variables(&self) -> Vec<String> {
+        let mut found = Vec::new();
+        collect_variables(&self.nodes, &mut found);
+        found
+            .into_iter()
+            .filter(|name| !PROMPT_VARIABLES.contains(name))
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+fn parse_body(body: &str) -> Result<Vec<Node>> {
+    // Stack of open `#if` blocks: (variable, then-branch, else-branch, in_else)
+    let mut stack: Vec<(String, Vec<Node>, Vec<Node>, bool)> = Vec::new();
+    let mut root = Vec::new();
+    let mut rest = body;
+
+    fn push(stack: &mut [(String, Vec<Node>, Vec<Node>, bool)], root: &mut Vec<Node>, node: Node) {
+        match stack.last_mut() {
+            Some((_, then, _, false)) => then.push(node),
+            Some((_, _, otherwise, true)) => otherwise.push(node),
+            None => root.push(node),
+        }
+    }
+
+    while let Some(open) = rest.find("{{") {
+        if open > 0 {
+            push(&mut stack, &mut root, Node::Text(rest[..open].to_string()));
+        }
+        let after = &rest[open + 2..];
+        let close = after
+            .find("}}")
+            .ok_or_else(|| anyhow!("Unclosed '{{{{' tag"))?;
+        let tag = after[..close].trim();
+        rest = &after[close + 2..];
+
+        if tag.starts_with('!') {
+            continue;
+        } else if let Some(variable) = tag.strip_prefix("#if ") {
+            stack.push((valid_name(variable)?, Vec::new(), Vec::new(), false));
+        } else if tag == "else" {
+            match stack.last_mut() {
+                Some((_, _, _, in_else @ false)) => *in_else = true,
+                Some(_) => bail!("Duplicate '{{{{else}}}}' in '#if' block"),
+                None => bail!("'{{{{else}}}}' outside of an '#if' block"),
+            }
+        } else if tag == "/if" {
+            let (variable, then, otherwise, _) = stack
+                .pop()
+                .ok_or_else(|| anyhow!("'{{{{/if}}}}' without a matching '#if'"))?;
+            push(
+                &mut stack,
+                &mut root,
+                Node::If {
+                    variable,
+                    then,
+                    otherwise,
+                },
+            );
+        } else if let Some(name) = tag.strip_prefix('>') {
+            push(&mut stack, &mut root, Node::Include(valid_name(name)?));
+        } else {
+            push(&mut stack, &mut root, Node::Variable(valid_name(tag)?));
+        }
+    }
+
+    if let Some((variable, ..)) = stack.last() {
+        bail!("Unclosed '{{{{#if {}}}}}' block", variable);
+    }
+    if !rest.is_empty() {
+        root.push(Node::Text(rest.to_string()));
+    }
+    Ok(root)
+}
+
+fn valid_name(raw: &str) -> Result<String> {
+    let name = raw.trim();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        bail!("Invalid name '{}' in template tag", name);
+    }
+    Ok(name.to_string())
+}
+
+fn collect_includes<'a>(nodes: &'a [Node], found: &mut Vec<&'a str>) {
+    for node in nodes {
+        match node {
+            Node::Include(name) => found.push(name),
+            Node::If {
+                then, otherwise, ..
+            } => {
+                collect_includes(then, found);
+                collect_includes(otherwise, found);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn collect_variables<'a>(nodes: &'a [Node], found: &mut Vec<&'a str>) {
+    for node in nodes {
+        match node {
+            Node::Variable(name) => found.push(name),
+            Node::If {
+                variable,
+                then,
+                otherwise,
+            } => {
+                found.push(variable);
+                collect_variables(then, found);
+                collect_variables(otherwise, found);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A template file that failed validation and was skipped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateLoadError {
+    pub path: String,
+    pub error: String,
+}
+
+/// Loaded templates and partials; anything missing falls back to the built-ins
+#[derive(Debug, Clone, Default)]
+pub struct PromptLibrary {
+    templates: HashMap<String, PromptTemplate>,
+    errors: Vec<TemplateLoadError>,
+}
+
+impl PromptLibrary {
+    /// Global template directory under the user's config dir
+    pub fn global_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("fiovana").join("prompts"))
+    }
+
+    /// Load the global directory, then the workspace directory over it
+    pub fn load(workspace_path: Option<&Path>) -> Self {
+        let mut dirs = Vec::new();
+        if let Some(global) = Self::global_dir() {
+            dirs.push(global);
+        }
+        if let Some(workspace) = workspace_path {
+            dirs.push(workspace.join(WORKSPACE_PROMPTS_DIR));
+        }
+        Self::load_from_dirs(&dirs)
+    }
+
+    /// Load templates from directories in increasing order of precedence
+    pub fn load_from_dirs(dirs: &[PathBuf]) -> Self {
+        let mut library = Self::default();
+
+        for dir in dirs {
+            if !dir.is_dir() {
+                continue;
+            }
+            let entries = match fs::read_dir(dir) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    library.record_error(dir, e.into());
+                    continue;
+                }
+            };
+
+            let mut paths: Vec<PathBuf> = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.is_file()
+                        && path.extension().and_then(|ext| ext.to_str())
+                            == Some(PROMPT_FILE_EXTENSION)
+                })
+                .collect();
+            paths.sort();
+
+            for path in paths {
+                match Self::load_file(&path) {
+                    Ok(template) => {
+                        library
+                            .templates
+                            .insert(template.version.name.clone(), template);
+                    }
+                    Err(e) => library.record_error(&path, e),
+                }
+            }
+        }
+
+        library.validate_references();
+        tracing::info!(
+            "Loaded {} prompt templates ({} rejected)",
+            library.templates.len(),
+            library.errors.len()
+        );
+        library
+    }
+
+    fn load_file(path: &Path) -> Result<PromptTemplate> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read prompt template {}", path.display()))?;
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default();
+        let template = PromptTemplate::parse(&content, stem, &path.to_string_lossy())?;
+
+        let unknown = template.unknown_variables();
+        if !unknown.is_empty() {
+            bail!(
+                "Unknown variable(s): {} (allowed: {})",
+                unknown.join(", "),
+                PROMPT_VARIABLES.join(", ")
+            );
+        }
+        Ok(template)
+    }
+
+    /// Drop templates whose includes are missing, cyclic or too deep
+    fn validate_references(&mut self) {
+        loop {
+            let broken: Vec<(String, String)> = self
+                .templates
+                .keys()
+                .filter_map(|name| {
+                    self.check_includes(name, &mut Vec::new())
+                        .err()
+                        .map(|e| (name.clone(), e.to_string()))
+                })
+                .collect();
+            if broken.is_empty() {
+                break;
+            }
+            for (name, error) in broken {
+                if let Some(template) = self.templates.remove(&name) {
+                    self.errors.push(TemplateLoadError {
+                        path: template.version.source,
+                        error,
+                    });
+                }
+            }
+        }
+    }
+
+    fn check_includes(&self, name: &str, chain: &mut Vec<String>) -> Result<()> {
+        if chain.iter().any(|seen| seen == name) {
+            bail!("Include cycle: {} -> {}", chain.join(" -> "), name);
+        }
+        if chain.len() >= MAX_INCLUDE_DEPTH {
+            bail!("Includes nested deeper than {}", MAX_INCLUDE_DEPTH);
+        }
+        let template = self
+            .templates
+            .get(name)
+            .ok_or_else(|| anyhow!("Missing include '{}'", name))?;
+
+        chain.push(name.to_string());
+        for include in template.includes() {
+            self.check_includes(include, chain)?;
+        }
+        chain.pop();
+        Ok(())
+    }
+
+    fn record_error(&mut self, path: &Path, error: anyhow::Error) {
+        tracing::warn!("Skipping prompt template {}: {}", path.display(), error);
+        self.errors.push(TemplateLoadError {
+            path: path.to_string_lossy().to_string(),
+            error: error.to_string(),
+        });
+    }
+
+    pub fn errors(&self) -> &[TemplateLoadError] {
+        &self.errors
+    }
+
+    /// Versions of every loaded template, sorted by name
+    pub fn versions(&self) -> Vec<PromptTemplateVersion> {
+        let mut versions: Vec<_> = self
+            .templates
+            .values()
+            .map(|template| template.version.clone())
+            .collect();
+        versions.sort_by(|a, b| a.name.cmp(&b.name));
+        versions
+    }
+
+    /// Render a loaded template, or `None` when the built-in should be used
+    pub fn render(
+        &self,
+        name: &str,
+        variables: &HashMap<String, String>,
+    ) -> Option<(String, PromptTemplateVersion)> {
+        let template = self.templates.get(name)?;
+        let mut output = String::new();
+        self.render_nodes(&template.nodes, variables, &mut output, 0);
+        Some((output, template.version.clone()))
+    }
+
+    fn render_nodes(
+        &self,
+        nodes: &[Node],
+        variables: &HashMap<String, String>,
+        output: &mut String,
+        depth: usize,
+    ) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => output.push_str(text),
+                Node::Variable(name) => {
+                    if let Some(value) = variables.get(name) {
+                        output.push_str(value);
+                    }
+                }
+                Node::If {
+                    variable,
+                    then,
+                    otherwise,
+                } => {
+                    let truthy = variables
+                        .get(variable)
+                        .is_some_and(|value| !value.trim().is_empty());
+                    let branch = if truthy { then } else { otherwise };
+                    self.render_nodes(branch, variables, output, depth);
+                }
+                Node::Include(name) => {
+                    // References were validated at load time
+                    if let Some(partial) = self.templates.get(name) {
+                        if depth < MAX_INCLUDE_DEPTH {
+                            self.render_nodes(&partial.nodes, variables, output, depth + 1);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Template variables for a prompt context; empty values are omitted so
+/// `{{#if}}` blocks can test for them
+pub fn prompt_variables(context: &PromptContext) -> HashMap<String, String> {
+    let mut variables = HashMap::new();
+    let mut set = |name: &str, value: String| {
+        if !value.trim().is_empty() {
+            variables.insert(name.to_string(), value);
+        }
+    };
+
+    set("user_query", context.user_query.clone());
+    set(
+        "document_content",
+        context.document_content.clone().unwrap_or_default(),
+    );
+    set(
+        "conversation_history",
+        context
+            .conversation_history
+            .iter()
+            .map(|turn| format!("{}: {}", turn.role.to_uppercase(), turn.content))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    if let Some(metadata) = &context.document_metadata {
+        set("document_title", metadata.title.clone().unwrap_or_default());
+        set(
+            "document_type",
+            metadata.document_type.clone().unwrap_or_default(),
+        );
+        set("sections", metadata.sections.join(", "));
+        set("key_concepts", metadata.key_concepts.join(", "));
+    }
+    variables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_render_variables_conditionals_and_includes() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("guidelines.prompt"),
+            "---\nversion: 1\n---\nAlways cite sections.",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("question_answering.prompt"),
+            "---\nversion: \"2.1\"\ndescription: Domain QA\n---\n{{! internal note }}Q: {{ user_query }}\n{{#if document_content}}DOCS:\n{{document_content}}{{else}}No documents.{{/if}}\n{{> guidelines}}",
+        )
+        .unwrap();
+
+        let library = PromptLibrary::load_from_dirs(&[dir.path().to_path_buf()]);
+        assert!(library.errors().is_empty(), "{:?}", library.errors());
+
+        let (text, version) = library
+            .render(
+                "question_answering",
+                &vars(&[
+                    ("user_query", "What is RPO?"),
+                    ("document_content", "RPO is 4h"),
+                ]),
+            )
+            .unwrap();
+        assert_eq!(
+            text,
+            "Q: What is RPO?\nDOCS:\nRPO is 4h\nAlways cite sections."
+        );
+        assert_eq!(version.version, "2.1");
+
+        let (text, _) = library
+            .render("question_answering", &vars(&[("user_query", "Hi")]))
+            .unwrap();
+        assert!(text.contains("No documents."));
+        assert!(library.render("style_analysis", &HashMap::new()).is_none());
+    }
+
+    #[test]
+    fn test_invalid_templates_are_rejected_at_load() {
+        let dir = TempDir::new().unwrap();
+        let write = |name: &str, content: &str| fs::write(dir.path().join(name), content).unwrap();
+        write("no_version.prompt", "---\nname: x\n---\nbody");
+        write("unknown_var.prompt", "---\nversion: 1\n---\n{{secret}}");
+        write(
+            "unbalanced.prompt",
+            "---\nversion: 1\n---\n{{#if user_query}}open",
+        );
+        write(
+            "missing_include.prompt",
+            "---\nversion: 1\n---\n{{> nowhere}}",
+        );
+        write("cycle_a.prompt", "---\nversion: 1\n---\n{{> cycle_b}}");
+        write("cycle_b.prompt", "---\nversion: 1\n---\n{{> cycle_a}}");
+        write("uses_cycle.prompt", "---\nversion: 1\n---\n{{> cycle_a}}");
+        write("notes.txt", "ignored");
+
+        let library = PromptLibrary::load_from_dirs(&[dir.path().to_path_buf()]);
+        assert!(library.versions().is_empty());
+        assert_eq!(library.errors().len(), 7);
+    }
+
+    #[test]
+    fn test_workspace_templates_override_global() {
+        let global = TempDir::new().unwrap();
+        let workspace = TempDir::new().unwrap();
+        fs::write(
+            global.path().join("style_analysis.prompt"),
+            "---\nversion: 1\n---\nglobal {{user_query}}",
+        )
+        .unwrap();
+        fs::write(
+            global.path().join("comparison.prompt"),
+            "---\nversion: 1\n---\nglobal comparison",
+        )
+        .unwrap();
+        fs::write(
+            workspace.path().join("style_analysis.prompt"),
+            "---\nversion: 3\n---\nworkspace {{user_query}}",
+        )
+        .unwrap();
+
+        let library = PromptLibrary::load_from_dirs(&[
+            global.path().to_path_buf(),
+            workspace.path().to_path_buf(),
+        ]);
+        let (text, version) = library
+            .render("style_analysis", &vars(&[("user_query", "tone?")]))
+            .unwrap();
+        assert_eq!(text, "workspace tone?");
+        assert_eq!(version.version, "3");
+        assert!(library.render("comparison", &HashMap::new()).is_some());
+        assert_eq!(library.versions().len(), 2);
+    }
+}
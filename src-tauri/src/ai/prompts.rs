@@ -94,6 +94,27 @@ Guidelines:
 - Explain concepts clearly if they appear complex
 - Suggest related topics the user might find helpful
 - If the answer isn't in the documents, say so clearly
+"#;
+
+    /// Template for answers grounded in numbered source chunks
+    pub const GROUNDED_QUESTION_ANSWERING: &'static str = r#"
+You are a knowledgeable assistant answering questions strictly from the user's documents. Each source below starts with a citation ID in square brackets, such as [S1].
+
+SOURCES:
+{document_content}
+
+CONVERSATION HISTORY:
+{conversation_history}
+
+USER QUESTION: {user_query}
+
+Answer the question using only the sources above.
+
+Citation rules:
+- End every sentence that states a fact from the sources with the citation ID(s) it relies on, e.g. "Backups run nightly [S2]." or "[S1, S3]".
+- Only cite IDs that appear in SOURCES; never invent citation IDs.
+- If the sources do not contain the answer, say so plainly instead of guessing.
+- Do not add facts that are not supported by a cited source.
 "#;
 
     /// Template for content generation based on existing documents
@@ -283,6 +304,32 @@ impl PromptTemplates {
         prompt
     }
 
+    /// Build a grounded question-answering prompt; `document_content` holds
+    /// the citation-tagged sources
+    pub fn build_grounded_qa_prompt(context: &PromptContext) -> String {
+        let history = if context.conversation_history.is_empty() {
+            "No previous conversation".to_string()
+        } else {
+            context
+                .conversation_history
+                .iter()
+                .map(|turn| format!("{}: {}", turn.role.to_uppercase(), turn.content))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        Self::GROUNDED_QUESTION_ANSWERING
+            .replace(
+                "{document_content}",
+                context
+                    .document_content
+                    .as_deref()
+                    .unwrap_or("No sources available"),
+            )
+            .replace("{conversation_history}", &history)
+            .replace("{user_query}", &context.user_query)
+    }
+
     /// Build a content generation prompt
     #[allow(dead_code)]
     pub fn build_generation_prompt(
@@ -383,6 +430,7 @@ pub enum PromptType {
     DocumentAnalysis,
     Comparison,
     QuestionAnswering,
+    GroundedQuestionAnswering,
     Generation,
     StructureAnalysis,
     ProcedureAnalysis,
@@ -390,6 +438,23 @@ pub enum PromptType {
     TrainingAdaptation,
 }
 
+impl PromptType {
+    /// File stem used to override this prompt type
+    pub fn template_name(&self) -> &'static str {
+        match self {
+            PromptType::DocumentAnalysis => "document_analysis",
+            PromptType::Comparison => "comparison",
+            PromptType::QuestionAnswering => "question_answering",
+            PromptType::GroundedQuestionAnswering => "grounded_question_answering",
+            PromptType::Generation => "generation",
+            PromptType::StructureAnalysis => "structure_analysis",
+            PromptType::ProcedureAnalysis => "procedure_analysis",
+            PromptType::StyleAnalysis => "style_analysis",
+            PromptType::TrainingAdaptation => "training_adaptation",
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
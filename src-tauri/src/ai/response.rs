@@ -5,9 +5,12 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::citations::GroundingReport;
+use super::context_builder::ContextBudgetReport;
 use super::conversation_context::ConversationSession;
 use super::intent::{Intent, IntentConfidence};
 use super::ollama::OllamaClient;
+use super::prompt_library::PromptTemplateVersion;
 use super::AIConfig;
 use crate::document::StyleAnalyzer;
 
@@ -92,6 +95,9 @@ pub struct AIResponse {
     pub action_items: Vec<ActionItem>,
     pub metadata: ResponseMetadata,
     pub style_guidance: Option<StyleGuidance>,
+    /// Chunk citations and per-sentence support for retrieval-augmented answers
+    #[serde(default)]
+    pub grounding: Option<GroundingReport>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -168,6 +174,12 @@ pub struct ResponseMetadata {
     pub session_id: Option<String>,
     pub turn_id: Option<String>,
     pub reasoning_chain: Vec<String>,
+    /// How the prompt's token budget was spent and what was dropped
+    #[serde(default)]
+    pub context_budget: Option<ContextBudgetReport>,
+    /// Prompt template and version used to build the request
+    #[serde(default)]
+    pub prompt_template: Option<PromptTemplateVersion>,
 }
 
 #[allow(dead_code)]
@@ -320,6 +332,7 @@ impl ResponseGenerator {
             document_references,
             action_items,
             style_guidance,
+            grounding: None,
             metadata: ResponseMetadata {
                 processing_time_ms: processing_time,
                 model_used: config.default_model.clone(),
@@ -330,6 +343,8 @@ impl ResponseGenerator {
                 session_id: None,
                 turn_id: None, // Will be set by conversation manager
                 reasoning_chain,
+                context_budget: None,
+                prompt_template: None,
             },
         })
     }
@@ -1115,6 +1130,7 @@ mod tests {
             confidence: 0.8,
             reasoning: "Test".to_string(),
             alternative_intents: vec![],
+            slots: HashMap::new(),
         };
 
         let response = generator.generate_fallback_response(&intent_result);
@@ -1187,6 +1203,7 @@ mod tests {
             confidence: 0.8,
             reasoning: "High keyword match".to_string(),
             alternative_intents: vec![(Intent::SearchDocuments, 0.3)],
+            slots: HashMap::new(),
         };
 
         let reasoning = generator.build_reasoning_chain(&intent_result, true, false);
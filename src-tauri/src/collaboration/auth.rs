@@ -0,0 +1,253 @@
+// src-tauri/src/collaboration/auth.rs
+// Token authentication and per-document permissions for collaboration clients
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use rand::rngs::ThreadRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Query parameter carrying the access token for clients that cannot set
+/// headers on the WebSocket handshake (browsers)
+pub const TOKEN_QUERY_PARAM: &str = "token";
+
+/// What a collaborator may do in a document, from least to most privileged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollaborationRole {
+    Viewer,
+    Commenter,
+    Editor,
+}
+
+impl CollaborationRole {
+    /// Whether the role may send document updates
+    pub fn can_edit(self) -> bool {
+        self == CollaborationRole::Editor
+    }
+
+    /// Whether the role may comment and suggest changes
+    pub fn can_comment(self) -> bool {
+        self >= CollaborationRole::Commenter
+    }
+}
+
+/// Identity a connection authenticated as during the WebSocket handshake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollaboratorIdentity {
+    pub user_id: String,
+    pub user_name: String,
+}
+
+/// Token handed to a collaborator; only its hash is kept by the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuedToken {
+    pub token: String,
+    pub user_id: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone)]
+struct Collaborator {
+    user_name: String,
+    /// Role for documents without an explicit permission
+    default_role: Option<CollaborationRole>,
+    documents: HashMap<String, CollaborationRole>,
+}
+
+#[derive(Debug, Clone)]
+struct TokenGrant {
+    user_id: String,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// Access tokens and per-document roles of collaboration clients
+///
+/// Tokens are kept in memory only, so they have to be reissued whenever the
+/// collaboration server is restarted.
+#[derive(Debug, Default)]
+pub struct AccessControl {
+    tokens: DashMap<String, TokenGrant>, // token hash -> grant
+    collaborators: DashMap<String, Collaborator>, // user_id -> collaborator
+}
+
+impl AccessControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Issue a new token for a collaborator, registering or renaming them.
+    /// `default_role` applies to every document without an explicit role.
+    pub fn issue_token(
+        &self,
+        user_id: &str,
+        user_name: &str,
+        default_role: Option<CollaborationRole>,
+        ttl: Option<Duration>,
+    ) -> IssuedToken {
+        self.collaborators
+            .entry(user_id.to_string())
+            .and_modify(|collaborator| {
+                collaborator.user_name = user_name.to_string();
+                collaborator.default_role = default_role;
+            })
+            .or_insert_with(|| Collaborator {
+                user_name: user_name.to_string(),
+                default_role,
+                documents: HashMap::new(),
+            });
+
+        let mut bytes = [0u8; 32];
+        ThreadRng::default().fill_bytes(&mut bytes);
+        let token = hex::encode(bytes);
+        let expires_at = ttl.map(|ttl| Utc::now() + ttl);
+
+        self.tokens.insert(
+            hash_token(&token),
+            TokenGrant {
+                user_id: user_id.to_string(),
+                expires_at,
+            },
+        );
+
+        IssuedToken {
+            token,
+            user_id: user_id.to_string(),
+            expires_at,
+        }
+    }
+
+    /// Invalidate a token; returns whether it existed
+    pub fn revoke_token(&self, token: &str) -> bool {
+        self.tokens.remove(&hash_token(token)).is_some()
+    }
+
+    /// Grant a role on a single document, or remove the explicit grant with `None`
+    pub fn set_document_role(
+        &self,
+        user_id: &str,
+        document_id: &str,
+        role: Option<CollaborationRole>,
+    ) -> Result<()> {
+        let mut collaborator = self
+            .collaborators
+            .get_mut(user_id)
+            .ok_or_else(|| anyhow!("Unknown collaborator: {}", user_id))?;
+        match role {
+            Some(role) => {
+                collaborator.documents.insert(document_id.to_string(), role);
+            }
+            None => {
+                collaborator.documents.remove(document_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve a token to the collaborator it was issued to, dropping it if expired
+    pub fn authenticate(&self, token: &str) -> Option<CollaboratorIdentity> {
+        let key = hash_token(token);
+        let grant = self.tokens.get(&key).map(|grant| grant.clone())?;
+        if grant
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+        {
+            self.tokens.remove(&key);
+            return None;
+        }
+
+        self.collaborators
+            .get(&grant.user_id)
+            .map(|collaborator| CollaboratorIdentity {
+                user_id: grant.user_id.clone(),
+                user_name: collaborator.user_name.clone(),
+            })
+    }
+
+    /// Role of a collaborator in a document, if they have access at all
+    pub fn role_for(&self, user_id: &str, document_id: &str) -> Option<CollaborationRole> {
+        let collaborator = self.collaborators.get(user_id)?;
+        collaborator
+            .documents
+            .get(document_id)
+            .copied()
+            .or(collaborator.default_role)
+    }
+}
+
+/// Pick the access token out of a handshake's `Authorization: Bearer` header
+/// or, failing that, its `token` query parameter
+pub fn extract_token(authorization: Option<&str>, query: Option<&str>) -> Option<String> {
+    if let Some(token) = authorization
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+    {
+        return Some(token.to_string());
+    }
+
+    query?
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == TOKEN_QUERY_PARAM)
+        .map(|(_, value)| value.to_string())
+        .filter(|token| !token.is_empty())
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_and_document_roles() {
+        let access = AccessControl::new();
+        let issued = access.issue_token("alice", "Alice", Some(CollaborationRole::Viewer), None);
+
+        let identity = access.authenticate(&issued.token).unwrap();
+        assert_eq!(identity.user_id, "alice");
+        assert_eq!(identity.user_name, "Alice");
+        assert!(access.authenticate("not-a-token").is_none());
+
+        assert_eq!(
+            access.role_for("alice", "doc-1"),
+            Some(CollaborationRole::Viewer)
+        );
+        access
+            .set_document_role("alice", "doc-1", Some(CollaborationRole::Editor))
+            .unwrap();
+        assert!(access.role_for("alice", "doc-1").unwrap().can_edit());
+        assert!(!access.role_for("alice", "doc-2").unwrap().can_edit());
+        assert!(!access.role_for("alice", "doc-2").unwrap().can_comment());
+        assert!(CollaborationRole::Commenter.can_comment());
+        assert!(access.role_for("bob", "doc-1").is_none());
+        assert!(access.set_document_role("bob", "doc-1", None).is_err());
+
+        assert!(access.revoke_token(&issued.token));
+        assert!(access.authenticate(&issued.token).is_none());
+
+        let expired = access.issue_token("alice", "Alice", None, Some(Duration::seconds(-1)));
+        assert!(access.authenticate(&expired.token).is_none());
+        assert!(access.role_for("alice", "doc-2").is_none());
+    }
+
+    #[test]
+    fn test_extract_token() {
+        assert_eq!(
+            extract_token(Some("Bearer abc"), Some("token=xyz")),
+            Some("abc".to_string())
+        );
+        assert_eq!(
+            extract_token(None, Some("room=doc-1&token=xyz")),
+            Some("xyz".to_string())
+        );
+        assert_eq!(extract_token(Some("Basic abc"), Some("token=")), None);
+        assert_eq!(extract_token(None, None), None);
+    }
+}
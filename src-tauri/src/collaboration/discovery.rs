@@ -0,0 +1,240 @@
+// src-tauri/src/collaboration/discovery.rs
+// mDNS/DNS-SD advertisement and discovery of collaboration hosts on the LAN
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use mdns_sd::{ServiceDaemon, ServiceEvent, ServiceInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// DNS-SD service type collaboration hosts register under
+pub const SERVICE_TYPE: &str = "_fiovana-collab._tcp.local.";
+
+/// A collaboration host found on the local network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveredPeer {
+    /// Full DNS-SD instance name, unique per host and workspace
+    pub instance_name: String,
+    pub host_name: String,
+    pub addresses: Vec<String>,
+    pub port: u16,
+    /// Name of the shared workspace
+    pub workspace_name: Option<String>,
+    /// Whether the host serves wss://
+    pub secure: bool,
+    /// SHA-256 fingerprint of the host certificate, for pinning
+    pub certificate_fingerprint: Option<String>,
+    pub last_seen: DateTime<Utc>,
+}
+
+impl DiscoveredPeer {
+    fn from_service(info: &ServiceInfo) -> Self {
+        let mut addresses: Vec<String> = info
+            .get_addresses()
+            .iter()
+            .map(|address| address.to_string())
+            .collect();
+        addresses.sort();
+
+        Self {
+            instance_name: info.get_fullname().to_string(),
+            host_name: info.get_hostname().to_string(),
+            addresses,
+            port: info.get_port(),
+            workspace_name: info.get_property_val_str("workspace").map(str::to_string),
+            secure: info.get_property_val_str("secure") == Some("true"),
+            certificate_fingerprint: info.get_property_val_str("fingerprint").map(str::to_string),
+            last_seen: Utc::now(),
+        }
+    }
+}
+
+/// What a host advertises about its collaboration server
+#[derive(Debug, Clone)]
+pub struct Advertisement {
+    pub port: u16,
+    pub workspace_name: Option<String>,
+    pub certificate_fingerprint: Option<String>,
+}
+
+/// Browses for collaboration hosts and advertises the local one
+pub struct LanDiscovery {
+    daemon: ServiceDaemon,
+    peers: Arc<DashMap<String, DiscoveredPeer>>, // instance name -> peer
+    advertised: Option<String>,
+}
+
+impl LanDiscovery {
+    /// Start the mDNS daemon and browse for collaboration hosts
+    pub fn start() -> Result<Self> {
+        let daemon = ServiceDaemon::new().map_err(|e| anyhow!("Failed to start mDNS: {}", e))?;
+        let receiver = daemon
+            .browse(SERVICE_TYPE)
+            .map_err(|e| anyhow!("Failed to browse for collaboration hosts: {}", e))?;
+
+        let peers = Arc::new(DashMap::new());
+        let discovered = Arc::clone(&peers);
+        tokio::spawn(async move {
+            while let Ok(event) = receiver.recv_async().await {
+                match event {
+                    ServiceEvent::ServiceResolved(info) => {
+                        let peer = DiscoveredPeer::from_service(&info);
+                        info!(
+                            "Discovered collaboration host {} at {:?}",
+                            peer.instance_name, peer.addresses
+                        );
+                        discovered.insert(peer.instance_name.clone(), peer);
+                    }
+                    ServiceEvent::ServiceRemoved(_, instance_name) => {
+                        discovered.remove(&instance_name);
+                    }
+                    ServiceEvent::SearchStopped(_) => break,
+                    _ => {}
+                }
+            }
+        });
+
+        Ok(Self {
+            daemon,
+            peers,
+            advertised: None,
+        })
+    }
+
+    /// Announce the local collaboration server, replacing any earlier announcement
+    pub fn advertise(&mut self, advertisement: &Advertisement) -> Result<()> {
+        self.withdraw();
+
+        let host = local_hostname();
+        let instance = match &advertisement.workspace_name {
+            Some(workspace) => format!("{} ({})", workspace, host),
+            None => host.clone(),
+        };
+
+        let mut properties = HashMap::new();
+        properties.insert("version".to_string(), "1".to_string());
+        properties.insert(
+            "secure".to_string(),
+            advertisement.certificate_fingerprint.is_some().to_string(),
+        );
+        if let Some(workspace) = &advertisement.workspace_name {
+            properties.insert("workspace".to_string(), workspace.clone());
+        }
+        if let Some(fingerprint) = &advertisement.certificate_fingerprint {
+            properties.insert("fingerprint".to_string(), fingerprint.clone());
+        }
+
+        let service = ServiceInfo::new(
+            SERVICE_TYPE,
+            &instance,
+            &format!("{}.local.", host),
+            "",
+            advertisement.port,
+            properties,
+        )
+        .map_err(|e| anyhow!("Invalid collaboration service record: {}", e))?
+        .enable_addr_auto();
+
+        let fullname = service.get_fullname().to_string();
+        self.daemon
+            .register(service)
+            .map_err(|e| anyhow!("Failed to advertise collaboration server: {}", e))?;
+        info!("Advertising collaboration server as {}", fullname);
+        self.advertised = Some(fullname);
+        Ok(())
+    }
+
+    /// Stop announcing the local collaboration server
+    pub fn withdraw(&mut self) {
+        if let Some(fullname) = self.advertised.take() {
+            if let Err(e) = self.daemon.unregister(&fullname) {
+                warn!("Failed to withdraw {}: {}", fullname, e);
+            }
+        }
+    }
+
+    /// Hosts currently visible on the LAN, excluding this one
+    pub fn peers(&self) -> Vec<DiscoveredPeer> {
+        let mut peers: Vec<DiscoveredPeer> = self
+            .peers
+            .iter()
+            .filter(|entry| Some(entry.key()) != self.advertised.as_ref())
+            .map(|entry| entry.value().clone())
+            .collect();
+        peers.sort_by(|a, b| a.instance_name.cmp(&b.instance_name));
+        peers
+    }
+}
+
+impl Drop for LanDiscovery {
+    fn drop(&mut self) {
+        self.withdraw();
+        let _ = self.daemon.shutdown();
+    }
+}
+
+/// Host name used for the mDNS record, reduced to a valid DNS label
+pub fn local_hostname() -> String {
+    let raw = std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| {
+            std::fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|name| name.trim().to_string())
+        })
+        .unwrap_or_default();
+
+    let label: String = raw
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let label = label.trim_matches('-');
+    if label.is_empty() {
+        "fiovana".to_string()
+    } else {
+        label.to_ascii_lowercase()
+    }
+}
+
+/// Whether a bind address only accepts connections from this machine, in
+/// which case advertising it on the LAN would point peers at nothing
+pub fn is_loopback_host(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|address| address.is_loopback())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loopback_hosts() {
+        assert!(is_loopback_host("127.0.0.1"));
+        assert!(is_loopback_host("127.0.1.1"));
+        assert!(is_loopback_host("::1"));
+        assert!(is_loopback_host("[::1]"));
+        assert!(is_loopback_host("LocalHost"));
+        assert!(!is_loopback_host("0.0.0.0"));
+        assert!(!is_loopback_host("192.168.1.20"));
+    }
+
+    #[test]
+    fn test_local_hostname_is_a_dns_label() {
+        let host = local_hostname();
+        assert!(!host.is_empty());
+        assert!(!host.starts_with('-') && !host.ends_with('-'));
+        assert!(host
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'));
+    }
+}
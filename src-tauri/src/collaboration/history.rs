@@ -0,0 +1,440 @@
+// src-tauri/src/collaboration/history.rs
+// Attributed timeline of every change applied to a collaboration room
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use yrs::updates::decoder::Decode;
+use yrs::{Doc, GetString, ReadTxn, StateVector, Transact, Update};
+
+use super::auth::CollaboratorIdentity;
+use super::room_manager::{changed_span, SHARED_TEXT_NAME};
+
+/// Inactivity after which a user's next change starts a new session
+pub const SESSION_GAP_MINUTES: i64 = 10;
+
+/// Latest changes kept individually when a history is compacted; older ones
+/// are merged into a single baseline entry
+pub const RETAINED_CHANGES: usize = 1000;
+
+/// Author of changes that no collaborator made directly, such as `"filesystem"`
+pub fn system_author(source: &str) -> CollaboratorIdentity {
+    CollaboratorIdentity {
+        user_id: format!("system:{}", source),
+        user_name: source.to_string(),
+    }
+}
+
+/// How a change reached the room
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChangeOrigin {
+    /// Edited live by a connected client
+    Edit,
+    /// Merged from the bound file after an edit outside the editor
+    FileSync,
+    /// Suggestion by the change's author, accepted by an editor
+    Suggestion {
+        suggestion_id: String,
+        accepted_by: String,
+    },
+    /// The document was reset to its state after change `seq`
+    Restore { seq: u64 },
+    /// State of a room that predates its history
+    Baseline,
+}
+
+/// One Yjs update and who made it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Position in the room's history, starting at 1
+    pub seq: u64,
+    pub user_id: String,
+    pub user_name: String,
+    pub timestamp: DateTime<Utc>,
+    pub origin: ChangeOrigin,
+    /// Yjs v1 update
+    #[serde(with = "update_encoding")]
+    pub update: Vec<u8>,
+}
+
+/// Stores update bytes as base64 rather than as an array of numbers
+mod update_encoding {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(update: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(update))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(encoded).map_err(de::Error::custom)
+    }
+}
+
+/// Run of changes by one user without a long pause
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditSession {
+    pub user_id: String,
+    pub user_name: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub first_seq: u64,
+    pub last_seq: u64,
+    pub change_count: usize,
+}
+
+/// Text edit made by a single history entry. Entries touching several places
+/// are reported as one span covering all of them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextChange {
+    pub seq: u64,
+    pub user_id: String,
+    pub user_name: String,
+    pub timestamp: DateTime<Utc>,
+    pub origin: ChangeOrigin,
+    /// Byte offset of the edit in the text before it
+    pub offset: usize,
+    pub removed: String,
+    pub inserted: String,
+}
+
+/// Ordered, append-only history of a room
+#[derive(Debug, Clone, Default)]
+pub struct DocumentHistory {
+    entries: Vec<HistoryEntry>,
+}
+
+impl DocumentHistory {
+    pub fn new(mut entries: Vec<HistoryEntry>) -> Self {
+        entries.sort_by_key(|entry| entry.seq);
+        Self { entries }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Earliest change whose resulting text can still be rebuilt; later than
+    /// 0 once older changes have been merged into a baseline
+    pub fn earliest_seq(&self) -> u64 {
+        match self.entries.first() {
+            Some(entry) if entry.seq > 1 => entry.seq,
+            _ => 0,
+        }
+    }
+
+    /// Whether enough changes have accumulated to be worth compacting
+    pub fn needs_compaction(&self) -> bool {
+        self.entries.len() > 2 * RETAINED_CHANGES
+    }
+
+    /// Merge all but the latest `retain` changes into one baseline entry that
+    /// keeps the sequence number and timestamp of the last change it replaces.
+    /// Returns whether anything was merged.
+    pub fn compact(&mut self, retain: usize) -> Result<bool> {
+        let merged = self.entries.len().saturating_sub(retain);
+        if merged < 2 {
+            return Ok(false);
+        }
+
+        let doc = Doc::new();
+        for entry in &self.entries[..merged] {
+            apply(&doc, entry)?;
+        }
+        let last = &self.entries[merged - 1];
+        let author = system_author("history");
+        let baseline = HistoryEntry {
+            seq: last.seq,
+            user_id: author.user_id,
+            user_name: author.user_name,
+            timestamp: last.timestamp,
+            origin: ChangeOrigin::Baseline,
+            update: doc
+                .transact()
+                .encode_state_as_update_v1(&StateVector::default()),
+        };
+        self.entries.splice(..merged, [baseline]);
+        Ok(true)
+    }
+
+    /// Sequence number of the latest change, 0 for an empty history
+    pub fn latest_seq(&self) -> u64 {
+        self.entries.last().map(|entry| entry.seq).unwrap_or(0)
+    }
+
+    /// Append an update made by `author` and return the recorded entry
+    pub fn record(
+        &mut self,
+        author: &CollaboratorIdentity,
+        origin: ChangeOrigin,
+        update: Vec<u8>,
+    ) -> HistoryEntry {
+        let entry = HistoryEntry {
+            seq: self.latest_seq() + 1,
+            user_id: author.user_id.clone(),
+            user_name: author.user_name.clone(),
+            timestamp: Utc::now(),
+            origin,
+            update,
+        };
+        self.entries.push(entry.clone());
+        entry
+    }
+
+    /// Group each user's changes into sessions separated by `gap` of inactivity,
+    /// ordered by start time
+    pub fn sessions(&self, gap: Duration) -> Vec<EditSession> {
+        let mut open: Vec<EditSession> = Vec::new();
+        let mut closed = Vec::new();
+
+        for entry in &self.entries {
+            match open
+                .iter_mut()
+                .find(|session| session.user_id == entry.user_id)
+            {
+                Some(session) if entry.timestamp - session.ended_at <= gap => {
+                    session.ended_at = entry.timestamp;
+                    session.last_seq = entry.seq;
+                    session.change_count += 1;
+                    continue;
+                }
+                Some(session) => {
+                    let previous = std::mem::replace(session, EditSession::starting_at(entry));
+                    closed.push(previous);
+                }
+                None => open.push(EditSession::starting_at(entry)),
+            }
+        }
+
+        closed.extend(open);
+        closed.sort_by_key(|session| (session.started_at, session.first_seq));
+        closed
+    }
+
+    /// Document text right after change `seq`; 0 is the empty document
+    pub fn text_at(&self, seq: u64) -> Result<String> {
+        self.check_seq(seq)?;
+        let doc = Doc::new();
+        for entry in self.entries.iter().take_while(|entry| entry.seq <= seq) {
+            apply(&doc, entry)?;
+        }
+        Ok(text_of(&doc))
+    }
+
+    /// Attributed edits made by changes `from_seq + 1 ..= to_seq`
+    pub fn changes(&self, from_seq: u64, to_seq: u64) -> Result<Vec<TextChange>> {
+        self.check_seq(from_seq)?;
+        self.check_seq(to_seq)?;
+        if from_seq > to_seq {
+            return Err(anyhow!(
+                "History range {}..{} is reversed",
+                from_seq,
+                to_seq
+            ));
+        }
+
+        let doc = Doc::new();
+        let mut entries = self
+            .entries
+            .iter()
+            .take_while(|entry| entry.seq <= to_seq)
+            .peekable();
+        while let Some(entry) = entries.next_if(|entry| entry.seq <= from_seq) {
+            apply(&doc, entry)?;
+        }
+
+        let mut before = text_of(&doc);
+        let mut changes = Vec::new();
+        for entry in entries {
+            apply(&doc, entry)?;
+            let after = text_of(&doc);
+            if after != before {
+                let (start, old_end, new_end) = changed_span(&before, &after);
+                changes.push(TextChange {
+                    seq: entry.seq,
+                    user_id: entry.user_id.clone(),
+                    user_name: entry.user_name.clone(),
+                    timestamp: entry.timestamp,
+                    origin: entry.origin.clone(),
+                    offset: start,
+                    removed: before[start..old_end].to_string(),
+                    inserted: after[start..new_end].to_string(),
+                });
+            }
+            before = after;
+        }
+        Ok(changes)
+    }
+
+    fn check_seq(&self, seq: u64) -> Result<()> {
+        if seq > self.latest_seq() {
+            return Err(anyhow!(
+                "Change {} is beyond the latest change {}",
+                seq,
+                self.latest_seq()
+            ));
+        }
+        if seq < self.earliest_seq() {
+            return Err(anyhow!(
+                "Change {} was merged into the baseline at change {}",
+                seq,
+                self.earliest_seq()
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl EditSession {
+    fn starting_at(entry: &HistoryEntry) -> Self {
+        Self {
+            user_id: entry.user_id.clone(),
+            user_name: entry.user_name.clone(),
+            started_at: entry.timestamp,
+            ended_at: entry.timestamp,
+            first_seq: entry.seq,
+            last_seq: entry.seq,
+            change_count: 1,
+        }
+    }
+}
+
+fn apply(doc: &Doc, entry: &HistoryEntry) -> Result<()> {
+    let update = Update::decode_v1(&entry.update)
+        .map_err(|e| anyhow!("Invalid update in change {}: {}", entry.seq, e))?;
+    doc.transact_mut()
+        .apply_update(update)
+        .map_err(|e| anyhow!("Failed to replay change {}: {}", entry.seq, e))
+}
+
+fn text_of(doc: &Doc) -> String {
+    let text = doc.get_or_insert_text(SHARED_TEXT_NAME);
+    let txn = doc.transact();
+    text.get_string(&txn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yrs::Text;
+
+    fn author(user_id: &str) -> CollaboratorIdentity {
+        CollaboratorIdentity {
+            user_id: user_id.to_string(),
+            user_name: user_id.to_uppercase(),
+        }
+    }
+
+    fn edit(doc: &Doc, index: u32, chunk: &str) -> Vec<u8> {
+        let text = doc.get_or_insert_text(SHARED_TEXT_NAME);
+        let before = doc.transact().state_vector();
+        text.insert(&mut doc.transact_mut(), index, chunk);
+        doc.transact().encode_state_as_update_v1(&before)
+    }
+
+    #[test]
+    fn test_replay_attribution_and_sessions() {
+        let alice = Doc::with_client_id(1);
+        let mut history = DocumentHistory::default();
+        let first = history.record(
+            &author("alice"),
+            ChangeOrigin::Edit,
+            edit(&alice, 0, "Step 1"),
+        );
+        let bob = Doc::with_client_id(2);
+        bob.transact_mut()
+            .apply_update(Update::decode_v1(&first.update).unwrap())
+            .unwrap();
+        history.record(
+            &author("bob"),
+            ChangeOrigin::FileSync,
+            edit(&bob, 6, ": isolate"),
+        );
+        history.record(&author("alice"), ChangeOrigin::Edit, edit(&alice, 0, "# "));
+
+        assert_eq!(history.latest_seq(), 3);
+        assert_eq!(history.text_at(0).unwrap(), "");
+        assert_eq!(history.text_at(1).unwrap(), "Step 1");
+        assert_eq!(history.text_at(3).unwrap(), "# Step 1: isolate");
+        assert!(history.text_at(4).is_err());
+
+        let changes = history.changes(1, 3).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].user_id, "bob");
+        assert_eq!(changes[0].inserted, ": isolate");
+        assert_eq!(changes[0].offset, 6);
+        assert_eq!(changes[1].user_id, "alice");
+        assert_eq!(changes[1].inserted, "# ");
+        assert!(history.changes(3, 1).is_err());
+
+        // A long pause splits Alice's changes into two sessions
+        history.entries[2].timestamp =
+            history.entries[0].timestamp + Duration::minutes(SESSION_GAP_MINUTES + 1);
+        let sessions = history.sessions(Duration::minutes(SESSION_GAP_MINUTES));
+        let owners: Vec<(&str, u64, u64)> = sessions
+            .iter()
+            .map(|session| {
+                (
+                    session.user_id.as_str(),
+                    session.first_seq,
+                    session.last_seq,
+                )
+            })
+            .collect();
+        assert_eq!(
+            owners,
+            vec![("alice", 1, 1), ("bob", 2, 2), ("alice", 3, 3)]
+        );
+    }
+
+    #[test]
+    fn test_compaction_merges_oldest_changes_into_baseline() {
+        let doc = Doc::with_client_id(1);
+        let mut history = DocumentHistory::default();
+        for (index, word) in ["one ", "two ", "three ", "four "].iter().enumerate() {
+            let offset = history.text_at(index as u64).unwrap().len() as u32;
+            history.record(
+                &author("alice"),
+                ChangeOrigin::Edit,
+                edit(&doc, offset, word),
+            );
+        }
+
+        assert!(history.compact(2).unwrap());
+        assert_eq!(history.entries().len(), 3);
+        assert_eq!(history.entries()[0].origin, ChangeOrigin::Baseline);
+        assert_eq!(history.earliest_seq(), 2);
+        assert_eq!(history.latest_seq(), 4);
+        assert_eq!(history.text_at(2).unwrap(), "one two ");
+        assert_eq!(history.text_at(4).unwrap(), "one two three four ");
+        assert!(history.text_at(1).is_err());
+        assert_eq!(history.changes(2, 4).unwrap()[0].inserted, "three ");
+        assert!(!history.compact(2).unwrap());
+
+        // The next recorded change continues the numbering
+        let next = history.record(&author("bob"), ChangeOrigin::Edit, edit(&doc, 0, "# "));
+        assert_eq!(next.seq, 5);
+    }
+
+    #[test]
+    fn test_updates_are_stored_as_base64() {
+        let entry = HistoryEntry {
+            seq: 1,
+            user_id: "alice".to_string(),
+            user_name: "Alice".to_string(),
+            timestamp: Utc::now(),
+            origin: ChangeOrigin::Edit,
+            update: vec![1, 2, 3, 250],
+        };
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["update"], "AQID+g==");
+        let decoded: HistoryEntry = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.update, entry.update);
+    }
+}
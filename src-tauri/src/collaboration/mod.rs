@@ -1,9 +1,17 @@
 // Collaboration module for real-time document editing
 // Implements WebSocket server with Yjs protocol support
 
+pub mod auth;
+pub mod discovery;
+pub mod history;
 pub mod operational_transforms;
+pub mod persistence;
 pub mod presence;
+pub mod review;
 pub mod room_manager;
+pub mod sync_agent;
+pub mod text_merge;
+pub mod tls;
 pub mod websocket_server;
 
 pub use websocket_server::{CollaborationServer, ServerConfig};
@@ -2,6 +2,8 @@
 // Implements Yjs-compatible update handling
 
 use serde::{Deserialize, Serialize};
+use yrs::updates::decoder::Decode;
+use yrs::{Doc, ReadTxn, StateVector, Transact, Update};
 
 /// Yjs update message containing document changes
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -123,24 +125,22 @@ pub struct OperationalTransform;
 
 #[allow(dead_code)]
 impl OperationalTransform {
-    /// Apply Yjs update to document state
-    /// Note: Actual Yjs update application is handled by the Yjs library
-    /// This is a placeholder for future custom transform logic
-    pub fn apply_update(_current_state: &[u8], _update: &YjsUpdate) -> Result<Vec<u8>, String> {
-        // In a real implementation, this would:
-        // 1. Parse the Yjs update
-        // 2. Apply it to the current state
-        // 3. Return the new state
-        //
-        // For now, we rely on Yjs library to handle this
-        Ok(vec![])
-    }
-
-    /// Merge multiple concurrent updates
-    pub fn merge_updates(_updates: &[YjsUpdate]) -> Result<Vec<u8>, String> {
-        // Yjs handles automatic merging via CRDT properties
-        // This is a placeholder for any custom merge logic
-        Ok(vec![])
+    /// Apply a Yjs v1 update to an encoded document state and return the new state.
+    /// An empty `current_state` is treated as an empty document.
+    pub fn apply_update(current_state: &[u8], update: &YjsUpdate) -> Result<Vec<u8>, String> {
+        let doc = Doc::new();
+        Self::apply_bytes(&doc, current_state)?;
+        Self::apply_bytes(&doc, &update.update)?;
+        Ok(Self::encode_state(&doc))
+    }
+
+    /// Merge multiple concurrent updates into a single equivalent update
+    pub fn merge_updates(updates: &[YjsUpdate]) -> Result<Vec<u8>, String> {
+        let doc = Doc::new();
+        for update in updates {
+            Self::apply_bytes(&doc, &update.update)?;
+        }
+        Ok(Self::encode_state(&doc))
     }
 
     /// Check if two updates conflict
@@ -149,6 +149,21 @@ impl OperationalTransform {
         // This always returns false as Yjs handles conflicts automatically
         false
     }
+
+    fn apply_bytes(doc: &Doc, bytes: &[u8]) -> Result<(), String> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let update = Update::decode_v1(bytes).map_err(|e| format!("Invalid Yjs update: {}", e))?;
+        doc.transact_mut()
+            .apply_update(update)
+            .map_err(|e| format!("Failed to apply Yjs update: {}", e))
+    }
+
+    fn encode_state(doc: &Doc) -> Vec<u8> {
+        doc.transact()
+            .encode_state_as_update_v1(&StateVector::default())
+    }
 }
 
 #[cfg(test)]
@@ -185,6 +200,43 @@ mod tests {
         assert_eq!(msg3.user_id(), Some("user-2"));
     }
 
+    #[test]
+    fn test_merge_and_apply_updates() {
+        use yrs::{GetString, Text};
+
+        let edit = |client_id: u64, chunk: &str| {
+            let doc = Doc::with_client_id(client_id);
+            let text = doc.get_or_insert_text("content");
+            text.insert(&mut doc.transact_mut(), 0, chunk);
+            let update = doc
+                .transact()
+                .encode_state_as_update_v1(&StateVector::default());
+            YjsUpdate::new(update, "doc-1".to_string(), format!("user-{}", client_id))
+        };
+        let first = edit(1, "alpha");
+        let second = edit(2, "beta");
+
+        let merged = OperationalTransform::merge_updates(&[first.clone(), second.clone()]).unwrap();
+        let applied = OperationalTransform::apply_update(
+            &OperationalTransform::apply_update(&[], &first).unwrap(),
+            &second,
+        )
+        .unwrap();
+
+        let read = |state: &[u8]| {
+            let doc = Doc::new();
+            OperationalTransform::apply_bytes(&doc, state).unwrap();
+            let text = doc.get_or_insert_text("content");
+            let txn = doc.transact();
+            text.get_string(&txn)
+        };
+        assert_eq!(read(&merged), read(&applied));
+        assert_eq!(read(&merged).len(), "alphabeta".len());
+
+        let bad = YjsUpdate::new(vec![0xff; 4], "doc-1".to_string(), "user-3".to_string());
+        assert!(OperationalTransform::merge_updates(&[bad]).is_err());
+    }
+
     #[test]
     fn test_no_conflicts() {
         let update1 = YjsUpdate::new(vec![1, 2, 3], "doc-1".to_string(), "user-1".to_string());
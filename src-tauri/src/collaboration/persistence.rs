@@ -0,0 +1,472 @@
+// src-tauri/src/collaboration/persistence.rs
+// On-disk storage for collaboration rooms: Yjs snapshots, update logs, file
+// bindings, review state and change history
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use tracing::warn;
+
+use super::history::HistoryEntry;
+use super::review::ReviewState;
+use crate::filesystem::atomic_write::write_atomically;
+
+/// Workspace-relative directory holding persisted collaboration rooms
+pub const ROOMS_DIR: &str = ".fiovana/collaboration/rooms";
+
+/// Full Yjs v1 document state as of the last compaction
+const SNAPSHOT_FILE: &str = "snapshot.bin";
+
+/// Length-prefixed Yjs v1 updates applied since the snapshot
+const UPDATE_LOG_FILE: &str = "updates.log";
+
+/// Update log set aside while a snapshot is taken; removed once it is written
+const COMPACTING_LOG_FILE: &str = "updates.compacting";
+
+/// Room metadata and the file the room is materialised to
+const ROOM_FILE: &str = "room.json";
+
+/// Comment threads and suggestions
+const REVIEW_FILE: &str = "review.json";
+
+/// Attributed changes, one JSON entry per line; rewritten when the history is compacted
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// Link between a room and the workspace file it is materialised to
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoomBinding {
+    pub document_id: String,
+    /// Markdown or text file the room content is written back to
+    pub file_path: Option<PathBuf>,
+    /// SHA-256 of the content last written to or merged from `file_path`
+    pub last_synced_hash: Option<String>,
+    /// That content itself, the base external edits are merged against
+    #[serde(default)]
+    pub last_synced_text: Option<String>,
+    pub last_synced_at: Option<DateTime<Utc>>,
+}
+
+impl RoomBinding {
+    pub fn new(document_id: impl Into<String>) -> Self {
+        Self {
+            document_id: document_id.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Room state read back from disk
+#[derive(Debug, Clone)]
+pub struct PersistedRoom {
+    pub binding: RoomBinding,
+    /// Empty when the room has never been compacted
+    pub snapshot: Vec<u8>,
+    pub updates: Vec<Vec<u8>>,
+    pub review: ReviewState,
+    pub history: Vec<HistoryEntry>,
+}
+
+/// File-based store for collaboration rooms of a single workspace
+///
+/// Each room lives in its own directory named after a hash of the document ID,
+/// so arbitrary IDs are safe to use. The snapshot is rewritten atomically on
+/// compaction, which also drops the updates it already contains.
+#[derive(Debug, Clone)]
+pub struct RoomStore {
+    root: PathBuf,
+    /// Serialises compactions so an older snapshot never replaces a newer one
+    compaction: Arc<Mutex<()>>,
+}
+
+impl RoomStore {
+    /// Open the room store kept under `root`, creating the directory
+    pub fn new<P: AsRef<Path>>(root: P) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(&root).with_context(|| {
+            format!(
+                "Failed to create collaboration rooms directory: {}",
+                root.display()
+            )
+        })?;
+        Ok(Self {
+            root,
+            compaction: Arc::new(Mutex::new(())),
+        })
+    }
+
+    /// Create a store in the workspace's reserved collaboration directory
+    pub fn for_workspace<P: AsRef<Path>>(workspace_path: P) -> Result<Self> {
+        Self::new(workspace_path.as_ref().join(ROOMS_DIR))
+    }
+
+    fn room_dir(&self, document_id: &str) -> PathBuf {
+        let digest = Sha256::digest(document_id.as_bytes());
+        self.root.join(hex::encode(&digest[..16]))
+    }
+
+    fn ensure_room_dir(&self, document_id: &str) -> Result<PathBuf> {
+        let dir = self.room_dir(document_id);
+        if !dir.exists() {
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("Failed to create room directory: {}", dir.display()))?;
+            self.save_binding(&RoomBinding::new(document_id))?;
+        }
+        Ok(dir)
+    }
+
+    /// Append a single update to the room's log
+    pub fn append_update(&self, document_id: &str, update: &[u8]) -> Result<()> {
+        let path = self.ensure_room_dir(document_id)?.join(UPDATE_LOG_FILE);
+        let mut record = Vec::with_capacity(update.len() + 4);
+        record.extend_from_slice(&(update.len() as u32).to_le_bytes());
+        record.extend_from_slice(update);
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open update log: {}", path.display()))?;
+        file.write_all(&record)
+            .with_context(|| format!("Failed to append to update log: {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Replace the room's snapshot with the one `take_snapshot` returns and
+    /// drop the updates it already contains
+    ///
+    /// The update log is set aside before the snapshot is taken, so an update
+    /// applied and appended meanwhile lands either in the snapshot or in the
+    /// fresh log, never only in the discarded one. Until the snapshot is
+    /// written the set-aside log is still read on load.
+    pub fn compact(
+        &self,
+        document_id: &str,
+        take_snapshot: impl FnOnce() -> Vec<u8>,
+    ) -> Result<()> {
+        let _compacting = self
+            .compaction
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let dir = self.ensure_room_dir(document_id)?;
+        let log_path = dir.join(UPDATE_LOG_FILE);
+        let compacting_path = dir.join(COMPACTING_LOG_FILE);
+
+        if compacting_path.exists() {
+            // Left over from an interrupted compaction; keep its updates ahead of the new ones
+            if log_path.exists() {
+                let pending = fs::read(&log_path)
+                    .with_context(|| format!("Failed to read {}", log_path.display()))?;
+                let mut file = OpenOptions::new()
+                    .append(true)
+                    .open(&compacting_path)
+                    .with_context(|| format!("Failed to open {}", compacting_path.display()))?;
+                file.write_all(&pending).with_context(|| {
+                    format!("Failed to append to {}", compacting_path.display())
+                })?;
+                fs::remove_file(&log_path)
+                    .with_context(|| format!("Failed to remove {}", log_path.display()))?;
+            }
+        } else if log_path.exists() {
+            fs::rename(&log_path, &compacting_path)
+                .with_context(|| format!("Failed to set aside {}", log_path.display()))?;
+        }
+
+        write_atomically(&dir.join(SNAPSHOT_FILE), &take_snapshot())?;
+        if compacting_path.exists() {
+            fs::remove_file(&compacting_path)
+                .with_context(|| format!("Failed to remove {}", compacting_path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Persist the room's metadata
+    pub fn save_binding(&self, binding: &RoomBinding) -> Result<()> {
+        let dir = self.room_dir(&binding.document_id);
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create room directory: {}", dir.display()))?;
+        let json = serde_json::to_vec_pretty(binding)?;
+        write_atomically(&dir.join(ROOM_FILE), &json)
+    }
+
+    /// Persist the room's comment threads and suggestions
+    pub fn save_review(&self, document_id: &str, review: &ReviewState) -> Result<()> {
+        let dir = self.ensure_room_dir(document_id)?;
+        let json = serde_json::to_vec_pretty(review)?;
+        write_atomically(&dir.join(REVIEW_FILE), &json)
+    }
+
+    /// Append an entry to the room's change history
+    pub fn append_history(&self, document_id: &str, entry: &HistoryEntry) -> Result<()> {
+        let path = self.ensure_room_dir(document_id)?.join(HISTORY_FILE);
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open history: {}", path.display()))?;
+        file.write_all(&line)
+            .with_context(|| format!("Failed to append to history: {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Replace the room's change history, e.g. after compacting it
+    pub fn replace_history(&self, document_id: &str, entries: &[HistoryEntry]) -> Result<()> {
+        let dir = self.ensure_room_dir(document_id)?;
+        let mut data = Vec::new();
+        for entry in entries {
+            serde_json::to_writer(&mut data, entry)?;
+            data.push(b'\n');
+        }
+        write_atomically(&dir.join(HISTORY_FILE), &data)
+    }
+
+    /// Load every persisted room, skipping (and logging) unreadable ones
+    pub fn load_all(&self) -> Result<Vec<PersistedRoom>> {
+        let entries = fs::read_dir(&self.root)
+            .with_context(|| format!("Failed to read {}", self.root.display()))?;
+
+        let mut rooms = Vec::new();
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            if !dir.is_dir() {
+                continue;
+            }
+            match self.load_dir(&dir) {
+                Ok(room) => rooms.push(room),
+                Err(e) => warn!("Skipping unreadable room {}: {}", dir.display(), e),
+            }
+        }
+        Ok(rooms)
+    }
+
+    fn load_dir(&self, dir: &Path) -> Result<PersistedRoom> {
+        let room_path = dir.join(ROOM_FILE);
+        let binding: RoomBinding = serde_json::from_slice(
+            &fs::read(&room_path)
+                .with_context(|| format!("Failed to read {}", room_path.display()))?,
+        )
+        .with_context(|| format!("Failed to parse {}", room_path.display()))?;
+
+        let snapshot_path = dir.join(SNAPSHOT_FILE);
+        let snapshot = if snapshot_path.exists() {
+            fs::read(&snapshot_path)
+                .with_context(|| format!("Failed to read {}", snapshot_path.display()))?
+        } else {
+            Vec::new()
+        };
+
+        let mut updates = Vec::new();
+        for log_path in [dir.join(COMPACTING_LOG_FILE), dir.join(UPDATE_LOG_FILE)] {
+            if log_path.exists() {
+                let data = fs::read(&log_path)
+                    .with_context(|| format!("Failed to read {}", log_path.display()))?;
+                updates.extend(decode_update_log(&data, &log_path));
+            }
+        }
+
+        let review_path = dir.join(REVIEW_FILE);
+        let review = if review_path.exists() {
+            serde_json::from_slice(
+                &fs::read(&review_path)
+                    .with_context(|| format!("Failed to read {}", review_path.display()))?,
+            )
+            .with_context(|| format!("Failed to parse {}", review_path.display()))?
+        } else {
+            ReviewState::default()
+        };
+
+        let history_path = dir.join(HISTORY_FILE);
+        let history = if history_path.exists() {
+            let data = fs::read_to_string(&history_path)
+                .with_context(|| format!("Failed to read {}", history_path.display()))?;
+            decode_history(&data, &history_path)
+        } else {
+            Vec::new()
+        };
+
+        Ok(PersistedRoom {
+            binding,
+            snapshot,
+            updates,
+            review,
+            history,
+        })
+    }
+}
+
+/// Split a length-prefixed update log, dropping a torn trailing record
+pub(super) fn decode_update_log(data: &[u8], path: &Path) -> Vec<Vec<u8>> {
+    let mut updates = Vec::new();
+    let mut offset = 0;
+    while offset + 4 <= data.len() {
+        let len = u32::from_le_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ]) as usize;
+        let start = offset + 4;
+        if start + len > data.len() {
+            break;
+        }
+        updates.push(data[start..start + len].to_vec());
+        offset = start + len;
+    }
+    if offset != data.len() {
+        warn!(
+            "Ignoring {} trailing bytes of incomplete update in {}",
+            data.len() - offset,
+            path.display()
+        );
+    }
+    updates
+}
+
+/// Parse the history log, dropping a torn trailing entry
+fn decode_history(data: &str, path: &Path) -> Vec<HistoryEntry> {
+    let mut entries = Vec::new();
+    for (index, line) in data.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                warn!(
+                    "Ignoring unreadable history entry {} in {}: {}",
+                    index + 1,
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collaboration::auth::CollaboratorIdentity;
+    use crate::collaboration::history::ChangeOrigin;
+    use crate::collaboration::review::TextAnchor;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_round_trip_snapshot_and_update_log() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = RoomStore::for_workspace(temp_dir.path()).unwrap();
+        assert!(store.load_all().unwrap().is_empty());
+
+        store.append_update("doc/1", &[1, 2, 3]).unwrap();
+        store.append_update("doc/1", &[4]).unwrap();
+        let room = store.load_all().unwrap().remove(0);
+        assert_eq!(room.binding.document_id, "doc/1");
+        assert!(room.snapshot.is_empty());
+        assert_eq!(room.updates, vec![vec![1, 2, 3], vec![4]]);
+        assert!(room.review.threads.is_empty());
+
+        store.compact("doc/1", || vec![9, 9]).unwrap();
+        store.append_update("doc/1", &[5]).unwrap();
+        let mut binding = RoomBinding::new("doc/1");
+        binding.file_path = Some(temp_dir.path().join("notes.md"));
+        store.save_binding(&binding).unwrap();
+        let mut review = ReviewState::default();
+        review
+            .propose(
+                TextAnchor {
+                    start: vec![1],
+                    end: vec![2],
+                },
+                "teh".to_string(),
+                "the".to_string(),
+                &CollaboratorIdentity {
+                    user_id: "sme".to_string(),
+                    user_name: "SME".to_string(),
+                },
+            )
+            .unwrap();
+        store.save_review("doc/1", &review).unwrap();
+        let entry = HistoryEntry {
+            seq: 1,
+            user_id: "sme".to_string(),
+            user_name: "SME".to_string(),
+            timestamp: Utc::now(),
+            origin: ChangeOrigin::Edit,
+            update: vec![5],
+        };
+        store.append_history("doc/1", &entry).unwrap();
+
+        let rooms = RoomStore::for_workspace(temp_dir.path())
+            .unwrap()
+            .load_all()
+            .unwrap();
+        assert_eq!(rooms.len(), 1);
+        assert_eq!(rooms[0].snapshot, vec![9, 9]);
+        assert_eq!(rooms[0].updates, vec![vec![5]]);
+        assert_eq!(rooms[0].binding.file_path, binding.file_path);
+        assert_eq!(rooms[0].review.suggestions.len(), 1);
+        assert_eq!(rooms[0].history.len(), 1);
+        assert_eq!(rooms[0].history[0].origin, ChangeOrigin::Edit);
+    }
+
+    #[test]
+    fn test_updates_appended_during_compaction_are_kept() {
+        use crate::collaboration::room_manager::Room;
+        use yrs::{Doc, ReadTxn, Text, Transact};
+
+        let temp_dir = TempDir::new().unwrap();
+        let store = RoomStore::for_workspace(temp_dir.path()).unwrap();
+        let room = Arc::new(Room::new("doc/1".to_string()));
+
+        let writers: Vec<_> = (0..4u64)
+            .map(|writer| {
+                let store = store.clone();
+                let room = room.clone();
+                std::thread::spawn(move || {
+                    let doc = Doc::with_client_id(writer + 1);
+                    let text = doc.get_or_insert_text("content");
+                    for _ in 0..50 {
+                        let before = doc.transact().state_vector();
+                        text.insert(&mut doc.transact_mut(), 0, "x");
+                        let update = doc.transact().encode_state_as_update_v1(&before);
+                        room.apply_update(update.clone()).unwrap();
+                        store.append_update("doc/1", &update).unwrap();
+                    }
+                })
+            })
+            .collect();
+
+        while writers.iter().any(|writer| !writer.is_finished()) {
+            store.compact("doc/1", || room.compact()).unwrap();
+        }
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let persisted = store.load_all().unwrap().remove(0);
+        let restored =
+            Room::restore("doc/1".to_string(), &persisted.snapshot, &persisted.updates).unwrap();
+        assert_eq!(restored.text_content().len(), 200);
+        assert_eq!(restored.text_content(), room.text_content());
+    }
+
+    #[test]
+    fn test_torn_update_record_is_ignored() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&[7, 8]);
+        data.extend_from_slice(&10u32.to_le_bytes());
+        data.extend_from_slice(&[1, 2]);
+
+        let updates = decode_update_log(&data, Path::new("updates.log"));
+        assert_eq!(updates, vec![vec![7, 8]]);
+    }
+}
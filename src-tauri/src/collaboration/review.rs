@@ -0,0 +1,293 @@
+// src-tauri/src/collaboration/review.rs
+// Comment threads and suggested edits attached to collaborative documents
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::auth::CollaboratorIdentity;
+
+/// Range of the shared text pinned to CRDT positions rather than offsets, so it
+/// follows the text it was created on through concurrent edits. Both ends are
+/// Yjs v1 encoded relative positions (`Y.encodeRelativePosition`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextAnchor {
+    pub start: Vec<u8>,
+    pub end: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadStatus {
+    Open,
+    Resolved,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comment {
+    pub id: String,
+    pub author_id: String,
+    pub author_name: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Discussion anchored to a range of the document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentThread {
+    pub id: String,
+    pub anchor: TextAnchor,
+    /// Text the anchor covered when the thread was started
+    pub quoted_text: String,
+    pub status: ThreadStatus,
+    pub comments: Vec<Comment>,
+    pub created_at: DateTime<Utc>,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionStatus {
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+/// Proposed replacement of an anchored range, applied only once accepted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub id: String,
+    pub anchor: TextAnchor,
+    /// Text the anchor covered when the suggestion was made
+    pub original_text: String,
+    pub replacement: String,
+    pub author_id: String,
+    pub author_name: String,
+    pub status: SuggestionStatus,
+    pub created_at: DateTime<Utc>,
+    pub decided_by: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+}
+
+/// Comment threads and suggestions of one room
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReviewState {
+    pub threads: Vec<CommentThread>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl ReviewState {
+    /// Open a thread on `anchor` with its first comment
+    pub fn start_thread(
+        &mut self,
+        anchor: TextAnchor,
+        quoted_text: String,
+        author: &CollaboratorIdentity,
+        body: &str,
+    ) -> Result<CommentThread> {
+        let comment = new_comment(author, body)?;
+        let thread = CommentThread {
+            id: uuid::Uuid::new_v4().to_string(),
+            anchor,
+            quoted_text,
+            status: ThreadStatus::Open,
+            created_at: comment.created_at,
+            comments: vec![comment],
+            resolved_by: None,
+            resolved_at: None,
+        };
+        self.threads.push(thread.clone());
+        Ok(thread)
+    }
+
+    /// Add a comment to an existing thread
+    pub fn reply(
+        &mut self,
+        thread_id: &str,
+        author: &CollaboratorIdentity,
+        body: &str,
+    ) -> Result<CommentThread> {
+        let comment = new_comment(author, body)?;
+        let thread = self.thread_mut(thread_id)?;
+        thread.comments.push(comment);
+        Ok(thread.clone())
+    }
+
+    /// Resolve a thread, or reopen it with `resolved: false`
+    pub fn set_resolved(
+        &mut self,
+        thread_id: &str,
+        resolved: bool,
+        user_id: &str,
+    ) -> Result<CommentThread> {
+        let thread = self.thread_mut(thread_id)?;
+        if resolved {
+            thread.status = ThreadStatus::Resolved;
+            thread.resolved_by = Some(user_id.to_string());
+            thread.resolved_at = Some(Utc::now());
+        } else {
+            thread.status = ThreadStatus::Open;
+            thread.resolved_by = None;
+            thread.resolved_at = None;
+        }
+        Ok(thread.clone())
+    }
+
+    /// Record a proposed replacement of the anchored text
+    pub fn propose(
+        &mut self,
+        anchor: TextAnchor,
+        original_text: String,
+        replacement: String,
+        author: &CollaboratorIdentity,
+    ) -> Result<Suggestion> {
+        if original_text == replacement {
+            return Err(anyhow!("Suggestion does not change the text"));
+        }
+
+        let suggestion = Suggestion {
+            id: uuid::Uuid::new_v4().to_string(),
+            anchor,
+            original_text,
+            replacement,
+            author_id: author.user_id.clone(),
+            author_name: author.user_name.clone(),
+            status: SuggestionStatus::Pending,
+            created_at: Utc::now(),
+            decided_by: None,
+            decided_at: None,
+        };
+        self.suggestions.push(suggestion.clone());
+        Ok(suggestion)
+    }
+
+    /// A suggestion that is still awaiting a decision
+    pub fn pending_suggestion(&self, suggestion_id: &str) -> Result<&Suggestion> {
+        let suggestion = self
+            .suggestions
+            .iter()
+            .find(|suggestion| suggestion.id == suggestion_id)
+            .ok_or_else(|| anyhow!("Unknown suggestion: {}", suggestion_id))?;
+        if suggestion.status != SuggestionStatus::Pending {
+            return Err(anyhow!("Suggestion {} was already decided", suggestion_id));
+        }
+        Ok(suggestion)
+    }
+
+    /// Mark a pending suggestion accepted or rejected
+    pub fn decide(
+        &mut self,
+        suggestion_id: &str,
+        accept: bool,
+        user_id: &str,
+    ) -> Result<Suggestion> {
+        self.pending_suggestion(suggestion_id)?;
+        let suggestion = self
+            .suggestions
+            .iter_mut()
+            .find(|suggestion| suggestion.id == suggestion_id)
+            .ok_or_else(|| anyhow!("Unknown suggestion: {}", suggestion_id))?;
+        suggestion.status = if accept {
+            SuggestionStatus::Accepted
+        } else {
+            SuggestionStatus::Rejected
+        };
+        suggestion.decided_by = Some(user_id.to_string());
+        suggestion.decided_at = Some(Utc::now());
+        Ok(suggestion.clone())
+    }
+
+    fn thread_mut(&mut self, thread_id: &str) -> Result<&mut CommentThread> {
+        self.threads
+            .iter_mut()
+            .find(|thread| thread.id == thread_id)
+            .ok_or_else(|| anyhow!("Unknown comment thread: {}", thread_id))
+    }
+}
+
+fn new_comment(author: &CollaboratorIdentity, body: &str) -> Result<Comment> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(anyhow!("Comment is empty"));
+    }
+    Ok(Comment {
+        id: uuid::Uuid::new_v4().to_string(),
+        author_id: author.user_id.clone(),
+        author_name: author.user_name.clone(),
+        body: body.to_string(),
+        created_at: Utc::now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(user_id: &str) -> CollaboratorIdentity {
+        CollaboratorIdentity {
+            user_id: user_id.to_string(),
+            user_name: user_id.to_uppercase(),
+        }
+    }
+
+    fn anchor() -> TextAnchor {
+        TextAnchor {
+            start: vec![1],
+            end: vec![2],
+        }
+    }
+
+    #[test]
+    fn test_threads_resolve_and_reopen() {
+        let mut review = ReviewState::default();
+        let thread = review
+            .start_thread(anchor(), "Hello".to_string(), &identity("sme"), "Typo?")
+            .unwrap();
+        assert!(review
+            .start_thread(anchor(), "Hello".to_string(), &identity("sme"), "  ")
+            .is_err());
+
+        let thread = review
+            .reply(&thread.id, &identity("author"), "Fixed")
+            .unwrap();
+        assert_eq!(thread.comments.len(), 2);
+
+        let resolved = review.set_resolved(&thread.id, true, "sme").unwrap();
+        assert_eq!(resolved.status, ThreadStatus::Resolved);
+        assert_eq!(resolved.resolved_by.as_deref(), Some("sme"));
+
+        let reopened = review.set_resolved(&thread.id, false, "author").unwrap();
+        assert_eq!(reopened.status, ThreadStatus::Open);
+        assert!(reopened.resolved_at.is_none());
+        assert!(review.reply("missing", &identity("sme"), "?").is_err());
+    }
+
+    #[test]
+    fn test_suggestions_are_decided_once() {
+        let mut review = ReviewState::default();
+        assert!(review
+            .propose(
+                anchor(),
+                "same".to_string(),
+                "same".to_string(),
+                &identity("sme")
+            )
+            .is_err());
+
+        let suggestion = review
+            .propose(
+                anchor(),
+                "teh".to_string(),
+                "the".to_string(),
+                &identity("sme"),
+            )
+            .unwrap();
+        assert!(review.pending_suggestion(&suggestion.id).is_ok());
+
+        let decided = review.decide(&suggestion.id, true, "editor").unwrap();
+        assert_eq!(decided.status, SuggestionStatus::Accepted);
+        assert!(review.pending_suggestion(&suggestion.id).is_err());
+        assert!(review.decide(&suggestion.id, false, "editor").is_err());
+    }
+}
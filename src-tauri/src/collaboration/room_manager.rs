@@ -1,11 +1,183 @@
 // Room management for collaborative editing sessions
 // Each room represents a single document being edited
 
+use anyhow::{anyhow, Result};
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
+use yrs::updates::decoder::Decode;
+use yrs::updates::encoder::Encode;
+use yrs::{Doc, GetString, ReadTxn, StateVector, StickyIndex, Text, Transact, Update};
 
+use super::history::DocumentHistory;
 use super::presence::UserPresence;
+use super::review::{ReviewState, TextAnchor};
+use super::text_merge::merge_text;
+
+/// Compact the update log into a snapshot once it holds this many updates
+pub const COMPACTION_THRESHOLD: usize = 200;
+
+/// Name of the shared Y.Text holding the document body
+pub const SHARED_TEXT_NAME: &str = "content";
+
+/// Merged Yjs document plus the updates applied since the last compaction
+struct RoomDocument {
+    doc: Doc,
+    /// Yjs v1 updates applied since the last compaction, in arrival order
+    update_log: Vec<Vec<u8>>,
+}
+
+impl RoomDocument {
+    fn new() -> Self {
+        Self {
+            doc: Doc::new(),
+            update_log: Vec::new(),
+        }
+    }
+
+    fn apply(&mut self, update: &[u8]) -> Result<()> {
+        let decoded =
+            Update::decode_v1(update).map_err(|e| anyhow!("Invalid Yjs update: {}", e))?;
+        self.doc
+            .transact_mut()
+            .apply_update(decoded)
+            .map_err(|e| anyhow!("Failed to apply Yjs update: {}", e))?;
+        self.update_log.push(update.to_vec());
+        Ok(())
+    }
+
+    fn state_vector(&self) -> Vec<u8> {
+        self.doc.transact().state_vector().encode_v1()
+    }
+
+    fn diff(&self, client_state_vector: &[u8]) -> Result<Vec<u8>> {
+        let state_vector = if client_state_vector.is_empty() {
+            StateVector::default()
+        } else {
+            StateVector::decode_v1(client_state_vector)
+                .map_err(|e| anyhow!("Invalid Yjs state vector: {}", e))?
+        };
+        Ok(self.doc.transact().encode_state_as_update_v1(&state_vector))
+    }
+
+    fn text(&self) -> String {
+        let text = self.doc.get_or_insert_text(SHARED_TEXT_NAME);
+        let txn = self.doc.transact();
+        text.get_string(&txn)
+    }
+
+    /// Rewrite the shared text to `new_text` by replacing only the span that
+    /// differs, so concurrent edits outside that span survive. Returns the
+    /// resulting update, or `None` if the text was already identical.
+    fn replace_text(&mut self, new_text: &str) -> Option<Vec<u8>> {
+        let current = self.text();
+        if current == new_text {
+            return None;
+        }
+
+        let (start, old_end, new_end) = changed_span(&current, new_text);
+        Some(self.splice(start as u32, old_end as u32, &new_text[start..new_end]))
+    }
+
+    /// Replace the byte range `start..end` of the shared text and return the update
+    fn splice(&mut self, start: u32, end: u32, replacement: &str) -> Vec<u8> {
+        let text = self.doc.get_or_insert_text(SHARED_TEXT_NAME);
+        let before = self.doc.transact().state_vector();
+        {
+            let mut txn = self.doc.transact_mut();
+            if end > start {
+                text.remove_range(&mut txn, start, end - start);
+            }
+            if !replacement.is_empty() {
+                text.insert(&mut txn, start, replacement);
+            }
+        }
+
+        let update = self.doc.transact().encode_state_as_update_v1(&before);
+        self.update_log.push(update.clone());
+        update
+    }
+
+    /// Pin the byte range `start..end` of the shared text to CRDT positions.
+    /// Text inserted at either edge later stays outside the anchor.
+    #[cfg(test)]
+    fn anchor(&self, start: u32, end: u32) -> Result<TextAnchor> {
+        use yrs::{Assoc, IndexedSequence};
+
+        let text = self.doc.get_or_insert_text(SHARED_TEXT_NAME);
+        let mut txn = self.doc.transact_mut();
+        if start > end || end > text.len(&txn) {
+            return Err(anyhow!("Range {}..{} is outside the document", start, end));
+        }
+        let start = text
+            .sticky_index(&mut txn, start, Assoc::After)
+            .ok_or_else(|| anyhow!("Cannot anchor offset {}", start))?;
+        let end = text
+            .sticky_index(&mut txn, end, Assoc::Before)
+            .ok_or_else(|| anyhow!("Cannot anchor offset {}", end))?;
+        Ok(TextAnchor {
+            start: start.encode_v1(),
+            end: end.encode_v1(),
+        })
+    }
+
+    /// Current byte range of the shared text an anchor covers
+    fn resolve(&self, anchor: &TextAnchor) -> Result<(u32, u32)> {
+        let start = StickyIndex::decode_v1(&anchor.start)
+            .map_err(|e| anyhow!("Invalid anchor start: {}", e))?;
+        let end = StickyIndex::decode_v1(&anchor.end)
+            .map_err(|e| anyhow!("Invalid anchor end: {}", e))?;
+
+        let txn = self.doc.transact();
+        let start = start
+            .get_offset(&txn)
+            .ok_or_else(|| anyhow!("Anchor start no longer resolves"))?
+            .index;
+        let end = end
+            .get_offset(&txn)
+            .ok_or_else(|| anyhow!("Anchor end no longer resolves"))?
+            .index;
+        // Deleting the anchored text collapses the range
+        Ok((start, end.max(start)))
+    }
+
+    fn anchored_text(&self, anchor: &TextAnchor) -> Result<String> {
+        let (start, end) = self.resolve(anchor)?;
+        self.text()
+            .get(start as usize..end as usize)
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("Anchor does not fall on character boundaries"))
+    }
+
+    /// Drop the update log and return the merged state as a single update
+    fn compact(&mut self) -> Vec<u8> {
+        self.update_log.clear();
+        self.doc
+            .transact()
+            .encode_state_as_update_v1(&StateVector::default())
+    }
+}
+
+/// Byte span `start..old_end` of `old` that must become `start..new_end` of
+/// `new`, trimmed to the longest common prefix and suffix on char boundaries
+pub fn changed_span(old: &str, new: &str) -> (usize, usize, usize) {
+    let prefix = old
+        .char_indices()
+        .zip(new.chars())
+        .find(|((_, a), b)| a != b)
+        .map(|((index, _), _)| index)
+        .unwrap_or_else(|| old.len().min(new.len()));
+
+    let suffix = old[prefix..]
+        .chars()
+        .rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum::<usize>();
+
+    (prefix, old.len() - suffix, new.len() - suffix)
+}
 
 /// Information about a collaboration room
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,9 +196,12 @@ pub struct Room {
     #[allow(dead_code)]
     created_at: chrono::DateTime<chrono::Utc>,
     last_activity: Arc<tokio::sync::RwLock<chrono::DateTime<chrono::Utc>>>,
-    // Yjs document state storage
-    updates: Arc<tokio::sync::RwLock<Vec<Vec<u8>>>>, // Stored Yjs updates
-    state_vector: Arc<tokio::sync::RwLock<Vec<u8>>>, // Current state vector
+    // Merged Yjs document state
+    document: Arc<Mutex<RoomDocument>>,
+    // Comment threads and suggestions
+    review: Mutex<ReviewState>,
+    // Attributed changes since the room was created
+    history: Mutex<DocumentHistory>,
 }
 
 impl Room {
@@ -37,9 +212,26 @@ impl Room {
             users: Arc::new(DashMap::new()),
             created_at: chrono::Utc::now(),
             last_activity: Arc::new(tokio::sync::RwLock::new(chrono::Utc::now())),
-            updates: Arc::new(tokio::sync::RwLock::new(Vec::new())),
-            state_vector: Arc::new(tokio::sync::RwLock::new(Vec::new())),
+            document: Arc::new(Mutex::new(RoomDocument::new())),
+            review: Mutex::new(ReviewState::default()),
+            history: Mutex::new(DocumentHistory::default()),
+        }
+    }
+
+    /// Rebuild a room from a persisted snapshot and the updates logged after it
+    pub fn restore(document_id: String, snapshot: &[u8], updates: &[Vec<u8>]) -> Result<Self> {
+        let room = Self::new(document_id);
+        {
+            let mut document = room.document.lock().unwrap_or_else(PoisonError::into_inner);
+            if !snapshot.is_empty() {
+                document.apply(snapshot)?;
+                document.update_log.clear();
+            }
+            for update in updates {
+                document.apply(update)?;
+            }
         }
+        Ok(room)
     }
 
     /// Add a user to the room
@@ -49,13 +241,13 @@ impl Room {
     }
 
     /// Remove a user by connection ID
-    #[allow(dead_code)]
     pub fn remove_user(&self, connection_id: &str) {
         self.users.remove(connection_id);
         self.update_activity();
     }
 
     /// Remove a user by user ID (may have multiple connections)
+    #[allow(dead_code)]
     pub fn remove_user_by_id(&self, user_id: &str) {
         self.users.retain(|_, presence| presence.user_id != user_id);
         self.update_activity();
@@ -100,66 +292,168 @@ impl Room {
         }
     }
 
-    /// Get the current state vector
+    /// Get the encoded state vector of the merged document
     pub fn get_state_vector(&self) -> Vec<u8> {
-        // Try to read the state vector, return empty if locked
-        if let Ok(state_vector) = self.state_vector.try_read() {
-            state_vector.clone()
-        } else {
-            Vec::new()
+        self.document
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .state_vector()
+    }
+
+    /// Encode everything the client is missing, given its encoded state vector.
+    /// An empty state vector yields the full document state.
+    pub fn get_updates_since(&self, client_state_vector: &[u8]) -> Result<Vec<u8>> {
+        self.document
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .diff(client_state_vector)
+    }
+
+    /// Decode a Yjs v1 update and merge it into the document
+    pub fn apply_update(&self, update: Vec<u8>) -> Result<()> {
+        self.document
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .apply(&update)?;
+
+        self.update_activity();
+        Ok(())
+    }
+
+    /// Current plain-text content of the shared document body
+    pub fn text_content(&self) -> String {
+        self.document
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .text()
+    }
+
+    /// Merge externally edited content into the document body, returning the
+    /// update to broadcast if anything changed
+    pub fn replace_text(&self, new_text: &str) -> Option<Vec<u8>> {
+        let update = self
+            .document
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .replace_text(new_text);
+        if update.is_some() {
+            self.update_activity();
         }
+        update
     }
 
-    /// Get updates since a given state vector
-    pub fn get_updates_since(&self, client_state_vector: &[u8]) -> Vec<u8> {
-        // Parse client state vector to determine which updates they need
-        // For a simplified implementation, we check if client has any state
-        if let Ok(updates) = self.updates.try_read() {
-            if client_state_vector.is_empty() {
-                // Client has no state, send all updates
-                updates
-                    .iter()
-                    .flat_map(|update| update.iter())
-                    .copied()
-                    .collect()
-            } else {
-                // Client has some state, send recent updates
-                // In a full implementation, we would parse the state vector properly
-                // For now, we send the last few updates that might be missing
-                let recent_count = std::cmp::min(10, updates.len());
-                if recent_count > 0 {
-                    updates[updates.len() - recent_count..]
-                        .iter()
-                        .flat_map(|update| update.iter())
-                        .copied()
-                        .collect()
-                } else {
-                    Vec::new()
-                }
-            }
-        } else {
-            Vec::new()
+    /// Three-way merge a file edited outside the editor into the document body:
+    /// the file's changes since `base` are applied on top of the current text.
+    /// Returns the update to broadcast, if anything changed, and how many file
+    /// changes were dropped because they overlap edits made in the room.
+    pub fn merge_text(&self, base: &str, file_text: &str) -> (Option<Vec<u8>>, usize) {
+        let mut document = self.document.lock().unwrap_or_else(PoisonError::into_inner);
+        let merged = merge_text(base, &document.text(), file_text);
+        let update = document.replace_text(&merged.text);
+        drop(document);
+        if update.is_some() {
+            self.update_activity();
         }
+        (update, merged.conflicts)
     }
 
-    /// Apply a new update to the document
-    pub fn apply_update(&self, update: Vec<u8>) {
-        // Store the update
-        if let Ok(mut updates) = self.updates.try_write() {
-            updates.push(update.clone());
+    /// Anchor a byte range of the document body, as a client does with
+    /// `Y.createRelativePositionFromTypeIndex`
+    #[cfg(test)]
+    pub fn anchor_range(&self, start: u32, end: u32) -> Result<TextAnchor> {
+        self.document
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .anchor(start, end)
+    }
 
-            // Update the state vector
-            // In a full implementation, this would properly compute the new state vector
-            // For now, we'll use a simplified approach
-            drop(updates);
+    /// Text an anchor currently covers
+    pub fn anchored_text(&self, anchor: &TextAnchor) -> Result<String> {
+        self.document
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .anchored_text(anchor)
+    }
 
-            if let Ok(mut state_vector) = self.state_vector.try_write() {
-                // Simplified: append update length as state
-                state_vector.extend_from_slice(&(update.len() as u32).to_le_bytes());
-            }
+    /// Replace the text an anchor covers, provided it is still `expected`.
+    /// Returns the update to broadcast.
+    pub fn replace_anchored(
+        &self,
+        anchor: &TextAnchor,
+        expected: &str,
+        replacement: &str,
+    ) -> Result<Vec<u8>> {
+        let mut document = self.document.lock().unwrap_or_else(PoisonError::into_inner);
+        let current = document.anchored_text(anchor)?;
+        if current != expected {
+            return Err(anyhow!(
+                "Anchored text changed since the suggestion was made"
+            ));
         }
+        let (start, end) = document.resolve(anchor)?;
+        let update = document.splice(start, end, replacement);
+        drop(document);
+
+        self.update_activity();
+        Ok(update)
+    }
+
+    /// Snapshot of the room's comment threads and suggestions
+    pub fn review_state(&self) -> ReviewState {
+        self.review
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replace the review state, e.g. with one restored from disk
+    pub fn set_review_state(&self, review: ReviewState) {
+        *self.review.lock().unwrap_or_else(PoisonError::into_inner) = review;
+    }
 
+    /// Modify the review state under its lock
+    pub fn update_review<R>(&self, update: impl FnOnce(&mut ReviewState) -> R) -> R {
+        let result = update(&mut self.review.lock().unwrap_or_else(PoisonError::into_inner));
         self.update_activity();
+        result
+    }
+
+    /// Change the room's history under its lock. Persisting inside `update`
+    /// keeps appended entries from racing a rewrite of the history on disk.
+    pub fn update_history<R>(&self, update: impl FnOnce(&mut DocumentHistory) -> R) -> R {
+        update(&mut self.history.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Replace the history, e.g. with one restored from disk
+    pub fn set_history(&self, history: DocumentHistory) {
+        *self.history.lock().unwrap_or_else(PoisonError::into_inner) = history;
+    }
+
+    /// Read the room's history under its lock
+    pub fn with_history<R>(&self, read: impl FnOnce(&DocumentHistory) -> R) -> R {
+        read(&self.history.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Whether the update log has grown enough to be folded into a snapshot
+    pub fn needs_compaction(&self) -> bool {
+        self.pending_update_count() >= COMPACTION_THRESHOLD
+    }
+
+    /// Fold the update log into a snapshot of the merged document
+    pub fn compact(&self) -> Vec<u8> {
+        self.document
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .compact()
+    }
+
+    /// Number of updates applied since the last snapshot
+    pub fn pending_update_count(&self) -> usize {
+        self.document
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .update_log
+            .len()
     }
 }
 
@@ -189,6 +483,13 @@ impl RoomManager {
         self.rooms.get(document_id).map(|entry| entry.clone())
     }
 
+    /// Register a room restored from disk, replacing any room with the same ID
+    pub fn insert_room(&self, document_id: String, room: Room) -> Arc<Room> {
+        let room = Arc::new(room);
+        self.rooms.insert(document_id, room.clone());
+        room
+    }
+
     /// Remove a room (e.g., when empty)
     #[allow(dead_code)]
     pub fn remove_room(&self, document_id: &str) {
@@ -216,6 +517,15 @@ impl RoomManager {
         self.rooms.len()
     }
 
+    /// Rooms with updates applied since their last snapshot, keyed by document ID
+    pub fn rooms_pending_compaction(&self) -> Vec<(String, Arc<Room>)> {
+        self.rooms
+            .iter()
+            .filter(|entry| entry.pending_update_count() > 0)
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect()
+    }
+
     /// Get total user count across all rooms
     #[allow(dead_code)]
     pub fn total_user_count(&self) -> usize {
@@ -233,6 +543,152 @@ impl Default for RoomManager {
 mod tests {
     use super::*;
 
+    fn text_of(doc: &Doc) -> String {
+        let text = doc.get_or_insert_text("content");
+        let txn = doc.transact();
+        text.get_string(&txn)
+    }
+
+    fn edit(doc: &Doc, index: u32, chunk: &str) -> Vec<u8> {
+        let text = doc.get_or_insert_text("content");
+        let before = doc.transact().state_vector();
+        text.insert(&mut doc.transact_mut(), index, chunk);
+        doc.transact().encode_state_as_update_v1(&before)
+    }
+
+    #[test]
+    fn test_room_merges_concurrent_updates_for_late_joiners() {
+        let room = Room::new("doc-1".to_string());
+        let alice = Doc::with_client_id(1);
+        let bob = Doc::with_client_id(2);
+
+        room.apply_update(edit(&alice, 0, "Hello")).unwrap();
+        room.apply_update(edit(&bob, 0, "World")).unwrap();
+        room.apply_update(edit(&alice, 5, "!")).unwrap();
+
+        // A late joiner with no state receives the whole merged document
+        let late = Doc::with_client_id(3);
+        let full = room.get_updates_since(&[]).unwrap();
+        late.transact_mut()
+            .apply_update(Update::decode_v1(&full).unwrap())
+            .unwrap();
+
+        // Alice only receives Bob's edit, and converges with the late joiner
+        let alice_state = alice.transact().state_vector().encode_v1();
+        let missing = room.get_updates_since(&alice_state).unwrap();
+        assert!(missing.len() < full.len());
+        alice
+            .transact_mut()
+            .apply_update(Update::decode_v1(&missing).unwrap())
+            .unwrap();
+
+        assert_eq!(text_of(&alice), text_of(&late));
+        assert!(text_of(&late).contains("Hello!"));
+        assert!(text_of(&late).contains("World"));
+        assert_eq!(
+            room.get_state_vector(),
+            late.transact().state_vector().encode_v1()
+        );
+    }
+
+    #[test]
+    fn test_room_rejects_invalid_updates_and_compacts() {
+        let room = Room::new("doc-1".to_string());
+        assert!(room.apply_update(vec![0xff, 0xff, 0xff]).is_err());
+        assert!(room.get_updates_since(&[0xff, 0xff]).is_err());
+
+        let doc = Doc::with_client_id(7);
+        room.apply_update(edit(&doc, 0, "draft")).unwrap();
+        room.apply_update(edit(&doc, 5, " two")).unwrap();
+        assert_eq!(room.pending_update_count(), 2);
+
+        let manager = RoomManager::new();
+        manager.rooms.insert("doc-1".to_string(), Arc::new(room));
+        let pending = manager.rooms_pending_compaction();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0, "doc-1");
+
+        let room = manager.get_room("doc-1").unwrap();
+        room.compact();
+        assert_eq!(room.pending_update_count(), 0);
+        assert!(manager.rooms_pending_compaction().is_empty());
+        let restored = Doc::new();
+        restored
+            .transact_mut()
+            .apply_update(Update::decode_v1(&room.get_updates_since(&[]).unwrap()).unwrap())
+            .unwrap();
+        assert_eq!(text_of(&restored), "draft two");
+    }
+
+    #[test]
+    fn test_restore_and_merge_external_text() {
+        let room = Room::new("doc-1".to_string());
+        let doc = Doc::with_client_id(1);
+        room.apply_update(edit(&doc, 0, "# Title\n\nfirst"))
+            .unwrap();
+        let snapshot = room.compact();
+        let draft = edit(&doc, 14, " draft");
+        room.apply_update(draft.clone()).unwrap();
+
+        let restored = Room::restore(
+            "doc-1".to_string(),
+            &snapshot,
+            &[draft, edit(&doc, 20, " ✓")],
+        )
+        .unwrap();
+        assert_eq!(restored.pending_update_count(), 2);
+        assert_eq!(restored.text_content(), "# Title\n\nfirst draft ✓");
+
+        // An external edit in the middle keeps the surrounding items intact
+        let update = restored
+            .replace_text("# Title\n\nsecond draft ✓")
+            .expect("text changed");
+        assert!(restored.replace_text("# Title\n\nsecond draft ✓").is_none());
+        assert_eq!(restored.text_content(), "# Title\n\nsecond draft ✓");
+
+        // Connected clients converge by applying the broadcast update
+        doc.transact_mut()
+            .apply_update(Update::decode_v1(&update).unwrap())
+            .unwrap();
+        assert_eq!(text_of(&doc), "# Title\n\nsecond draft ✓");
+    }
+
+    #[test]
+    fn test_changed_span_respects_char_boundaries() {
+        assert_eq!(changed_span("abc", "abc"), (3, 3, 3));
+        assert_eq!(changed_span("héllo", "hallo"), (1, 3, 2));
+        assert_eq!(changed_span("aaa", "aa"), (2, 3, 2));
+        assert_eq!(changed_span("", "new"), (0, 0, 3));
+    }
+
+    #[test]
+    fn test_anchors_follow_concurrent_edits() {
+        let room = Room::new("doc-1".to_string());
+        room.replace_text("Teh quick fox");
+        let anchor = room.anchor_range(0, 3).unwrap();
+        assert!(room.anchor_range(5, 40).is_err());
+
+        // A client inserts before and at the edge of the anchored word
+        let client = Doc::with_client_id(7);
+        client
+            .transact_mut()
+            .apply_update(Update::decode_v1(&room.get_updates_since(&[]).unwrap()).unwrap())
+            .unwrap();
+        room.apply_update(edit(&client, 0, "> ")).unwrap();
+        assert_eq!(room.anchored_text(&anchor).unwrap(), "Teh");
+
+        let update = room.replace_anchored(&anchor, "Teh", "The").unwrap();
+        assert_eq!(room.text_content(), "> The quick fox");
+        client
+            .transact_mut()
+            .apply_update(Update::decode_v1(&update).unwrap())
+            .unwrap();
+        assert_eq!(text_of(&client), "> The quick fox");
+
+        // Once the text has moved on, a stale replacement is refused
+        assert!(room.replace_anchored(&anchor, "Teh", "THE").is_err());
+    }
+
     #[tokio::test]
     async fn test_room_creation() {
         let room = Room::new("doc-123".to_string());
@@ -0,0 +1,618 @@
+// src-tauri/src/collaboration/sync_agent.rs
+// Client-side replica of a collaboration room that keeps accepting edits while
+// the connection is down and merges both ways when it comes back
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_tungstenite::client_async;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{info, warn};
+use url::Url;
+use yrs::updates::decoder::Decode;
+use yrs::updates::encoder::Encode;
+use yrs::{Doc, GetString, ReadTxn, StateVector, Text, Transact, Update};
+
+use super::auth::TOKEN_QUERY_PARAM;
+use super::persistence::decode_update_log;
+use super::room_manager::{changed_span, SHARED_TEXT_NAME};
+use super::tls::pinned_connector;
+use super::websocket_server::WsMessage;
+use crate::filesystem::atomic_write::write_atomically;
+
+// Binary frame types, as understood by `CollaborationServer`
+const SYNC_STEP1: u8 = 0x00;
+const SYNC_STEP2: u8 = 0x01;
+const UPDATE: u8 = 0x02;
+
+/// Where and as whom a sync agent connects
+#[derive(Debug, Clone)]
+pub struct SyncAgentConfig {
+    /// ws:// or wss:// address of the collaboration server
+    pub server_url: String,
+    /// Access token issued by the server
+    pub token: String,
+    pub document_id: String,
+    /// Certificate fingerprint to pin, required for wss://
+    pub certificate_fingerprint: Option<String>,
+    /// Pause between reconnection attempts
+    pub reconnect_delay: Duration,
+    /// Directory edits made offline are kept in until sent, so they survive a
+    /// restart; kept in memory only when unset
+    pub queue_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    Offline,
+    Connecting,
+    /// Connected and caught up with the server
+    Online,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncStatus {
+    pub document_id: String,
+    pub state: ConnectionState,
+    /// Local updates made while offline, sent on the next reconnect
+    pub pending_updates: usize,
+    /// Updates received from the server so far
+    pub remote_updates: u64,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+/// Local CRDT replica and the connection edits currently go out on
+struct Replica {
+    doc: Doc,
+    pending: Vec<Vec<u8>>,
+    /// Live connection, set once the reconnect handshake has completed
+    outgoing: Option<mpsc::UnboundedSender<Message>>,
+}
+
+struct Shared {
+    config: SyncAgentConfig,
+    /// File holding the replica and its queued updates while any are pending
+    queue_path: Option<PathBuf>,
+    replica: Mutex<Replica>,
+    status: watch::Sender<SyncStatus>,
+    /// Whether the agent should be connected
+    online: watch::Sender<bool>,
+}
+
+/// Keeps a room replica in sync with a collaboration server
+///
+/// Edits are applied to the replica immediately. While connected they are
+/// sent as they happen; otherwise they are queued, and on reconnect the agent
+/// exchanges state vectors with the server (`SyncStep1`/`SyncStep2`) so each
+/// side receives exactly what it is missing. With a queue directory configured
+/// the queue is also written to disk, and an agent started again for the same
+/// server and room picks it up.
+pub struct SyncAgent {
+    shared: Arc<Shared>,
+    task: JoinHandle<()>,
+}
+
+impl SyncAgent {
+    /// Start the agent, restoring edits queued by an earlier agent for the same
+    /// room, and connect in the background
+    pub fn start(config: SyncAgentConfig) -> Self {
+        let queue_path = config
+            .queue_dir
+            .as_deref()
+            .map(|dir| queue_path(dir, &config.server_url, &config.document_id));
+        let replica = match queue_path.as_deref().map(load_queue) {
+            Some(Ok(Some(replica))) => {
+                info!(
+                    "Restored {} queued updates for {}",
+                    replica.pending.len(),
+                    config.document_id
+                );
+                replica
+            }
+            Some(Err(e)) => {
+                warn!(
+                    "Failed to restore queued updates for {}: {:#}",
+                    config.document_id, e
+                );
+                Replica::empty()
+            }
+            _ => Replica::empty(),
+        };
+
+        let (status, _) = watch::channel(SyncStatus {
+            document_id: config.document_id.clone(),
+            state: ConnectionState::Offline,
+            pending_updates: replica.pending.len(),
+            remote_updates: 0,
+            last_synced_at: None,
+            last_error: None,
+        });
+        let (online, _) = watch::channel(true);
+        let shared = Arc::new(Shared {
+            config,
+            queue_path,
+            replica: Mutex::new(replica),
+            status,
+            online,
+        });
+
+        let task = tokio::spawn(Arc::clone(&shared).run());
+        Self { shared, task }
+    }
+
+    pub fn document_id(&self) -> &str {
+        &self.shared.config.document_id
+    }
+
+    pub fn status(&self) -> SyncStatus {
+        self.shared.status.borrow().clone()
+    }
+
+    /// Watch status changes, including each update received from the server
+    pub fn subscribe(&self) -> watch::Receiver<SyncStatus> {
+        self.shared.status.subscribe()
+    }
+
+    /// Disconnect and keep editing locally, or reconnect
+    pub fn set_online(&self, online: bool) {
+        self.shared.online.send_replace(online);
+    }
+
+    /// Current text of the replica
+    pub fn text(&self) -> String {
+        let replica = self.shared.replica();
+        let text = replica.doc.get_or_insert_text(SHARED_TEXT_NAME);
+        let txn = replica.doc.transact();
+        text.get_string(&txn)
+    }
+
+    /// Replace the text, touching only the span that differs. Returns whether
+    /// anything changed.
+    pub fn replace_text(&self, new_text: &str) -> bool {
+        let mut replica = self.shared.replica();
+        let text = replica.doc.get_or_insert_text(SHARED_TEXT_NAME);
+        let current = text.get_string(&replica.doc.transact());
+        if current == new_text {
+            return false;
+        }
+
+        let (start, old_end, new_end) = changed_span(&current, new_text);
+        let before = replica.doc.transact().state_vector();
+        {
+            let mut txn = replica.doc.transact_mut();
+            if old_end > start {
+                text.remove_range(&mut txn, start as u32, (old_end - start) as u32);
+            }
+            if new_end > start {
+                text.insert(&mut txn, start as u32, &new_text[start..new_end]);
+            }
+        }
+        let update = replica.doc.transact().encode_state_as_update_v1(&before);
+        self.shared.commit_local(&mut replica, update);
+        true
+    }
+
+    /// Merge an update made by a local editor, such as the frontend's Yjs document
+    pub fn apply_local_update(&self, update: &[u8]) -> Result<()> {
+        let decoded =
+            Update::decode_v1(update).map_err(|e| anyhow!("Invalid Yjs update: {}", e))?;
+        let mut replica = self.shared.replica();
+        replica
+            .doc
+            .transact_mut()
+            .apply_update(decoded)
+            .map_err(|e| anyhow!("Failed to apply Yjs update: {}", e))?;
+        self.shared.commit_local(&mut replica, update.to_vec());
+        Ok(())
+    }
+
+    /// Drop edits that were never sent, including their copy on disk
+    pub fn discard_queue(&self) {
+        let mut replica = self.shared.replica();
+        replica.pending.clear();
+        self.shared.save_queue(&replica);
+        self.shared
+            .update_status(|status| status.pending_updates = 0);
+    }
+}
+
+impl Replica {
+    fn empty() -> Self {
+        Self {
+            doc: Doc::new(),
+            pending: Vec::new(),
+            outgoing: None,
+        }
+    }
+}
+
+impl Drop for SyncAgent {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl Shared {
+    fn replica(&self) -> MutexGuard<'_, Replica> {
+        self.replica.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn update_status(&self, update: impl FnOnce(&mut SyncStatus)) {
+        self.status.send_modify(update);
+    }
+
+    /// Send a local update now if connected, otherwise queue it
+    fn commit_local(&self, replica: &mut Replica, update: Vec<u8>) {
+        let document_id = &self.config.document_id;
+        let sent = replica
+            .outgoing
+            .as_ref()
+            .is_some_and(|sender| sender.send(frame(UPDATE, document_id, &update)).is_ok());
+        if !sent {
+            replica.outgoing = None;
+            replica.pending.push(update);
+            self.save_queue(replica);
+            let pending = replica.pending.len();
+            self.update_status(|status| status.pending_updates = pending);
+        }
+    }
+
+    /// Write the replica and its queued updates to disk, or remove the file
+    /// once nothing is queued. The whole replica is kept because queued edits
+    /// build on content received from the server before the restart.
+    fn save_queue(&self, replica: &Replica) {
+        let Some(path) = &self.queue_path else {
+            return;
+        };
+        let result = if replica.pending.is_empty() {
+            match fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            }
+        } else {
+            let state = replica
+                .doc
+                .transact()
+                .encode_state_as_update_v1(&StateVector::default());
+            let mut data = Vec::new();
+            for record in std::iter::once(&state).chain(&replica.pending) {
+                data.extend_from_slice(&(record.len() as u32).to_le_bytes());
+                data.extend_from_slice(record);
+            }
+            fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))
+                .map_err(anyhow::Error::from)
+                .and_then(|_| write_atomically(path, &data))
+        };
+        if let Err(e) = result {
+            warn!(
+                "Failed to save queued updates for {}: {:#}",
+                self.config.document_id, e
+            );
+        }
+    }
+
+    /// Connect whenever the agent is online, retrying after failures
+    async fn run(self: Arc<Self>) {
+        let mut online = self.online.subscribe();
+        loop {
+            if !wait_for(&mut online, true).await {
+                return;
+            }
+
+            self.update_status(|status| status.state = ConnectionState::Connecting);
+            let result = tokio::select! {
+                result = self.connect() => result,
+                _ = wait_for(&mut online, false) => Ok(()),
+            };
+            self.replica().outgoing = None;
+            self.update_status(|status| {
+                status.state = ConnectionState::Offline;
+                if let Err(e) = &result {
+                    status.last_error = Some(e.to_string());
+                }
+            });
+            if let Err(e) = result {
+                warn!("Sync of {} interrupted: {:#}", self.config.document_id, e);
+            }
+
+            if *online.borrow() {
+                tokio::time::sleep(self.config.reconnect_delay).await;
+            }
+        }
+    }
+
+    async fn connect(&self) -> Result<()> {
+        let mut url =
+            Url::parse(&self.config.server_url).context("Invalid collaboration server URL")?;
+        url.query_pairs_mut()
+            .append_pair(TOKEN_QUERY_PARAM, &self.config.token);
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow!("Collaboration server URL has no host"))?
+            .to_string();
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| anyhow!("Collaboration server URL has no port"))?;
+
+        let stream = TcpStream::connect((host.as_str(), port))
+            .await
+            .with_context(|| format!("Failed to connect to {}:{}", host, port))?;
+        match url.scheme() {
+            "ws" => self.session(&url, stream).await,
+            "wss" => {
+                let fingerprint = self
+                    .config
+                    .certificate_fingerprint
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("wss:// needs the server's certificate fingerprint"))?;
+                let name = ServerName::try_from(host.clone())
+                    .map_err(|e| anyhow!("Invalid server name {}: {}", host, e))?;
+                let stream = pinned_connector(fingerprint)?
+                    .connect(name, stream)
+                    .await
+                    .context("TLS handshake with collaboration server failed")?;
+                self.session(&url, stream).await
+            }
+            scheme => Err(anyhow!("Unsupported collaboration URL scheme: {}", scheme)),
+        }
+    }
+
+    /// Join the room, reconcile with the server and relay updates until the
+    /// connection closes
+    async fn session<S>(&self, url: &Url, stream: S) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let (ws_stream, _) = client_async(url.as_str(), stream)
+            .await
+            .context("WebSocket handshake with collaboration server failed")?;
+        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+        let document_id = &self.config.document_id;
+
+        // The server takes the identity from the token, not from the message
+        let join = WsMessage::Join {
+            document_id: document_id.clone(),
+            user_id: String::new(),
+            user_name: String::new(),
+        };
+        ws_sender
+            .send(Message::Text(serde_json::to_string(&join)?))
+            .await?;
+
+        // Ask for the server's state vector, and send ours so the server
+        // answers with everything missing locally
+        let state_vector = self.replica().doc.transact().state_vector().encode_v1();
+        ws_sender.send(frame(SYNC_STEP1, document_id, &[])).await?;
+        ws_sender
+            .send(frame(SYNC_STEP2, document_id, &state_vector))
+            .await?;
+
+        let (sender, mut outgoing) = mpsc::unbounded_channel();
+        let writer = async {
+            while let Some(message) = outgoing.recv().await {
+                ws_sender.send(message).await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        };
+        let reader = async {
+            while let Some(message) = ws_receiver.next().await {
+                match message? {
+                    Message::Binary(data) => self.handle_frame(&data, &sender)?,
+                    Message::Text(text) => self.handle_text(&text),
+                    Message::Close(_) => break,
+                    _ => {}
+                }
+            }
+            Ok::<_, anyhow::Error>(())
+        };
+
+        tokio::select! {
+            result = reader => result,
+            result = writer => result,
+        }
+    }
+
+    fn handle_frame(&self, data: &[u8], sender: &mpsc::UnboundedSender<Message>) -> Result<()> {
+        let Some((kind, document_id, payload)) = parse_frame(data) else {
+            warn!("Ignoring malformed frame from collaboration server");
+            return Ok(());
+        };
+        if document_id != self.config.document_id {
+            return Ok(());
+        }
+
+        match kind {
+            SYNC_STEP1 => self.send_missing(payload, sender),
+            SYNC_STEP2 | UPDATE => {
+                let update = Update::decode_v1(payload)
+                    .map_err(|e| anyhow!("Invalid update from server: {}", e))?;
+                self.replica()
+                    .doc
+                    .transact_mut()
+                    .apply_update(update)
+                    .map_err(|e| anyhow!("Failed to apply update from server: {}", e))?;
+                self.update_status(|status| status.remote_updates += 1);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Answer the server's state vector with the local changes it lacks, then
+    /// send further edits live. Besides the queue this covers edits that were
+    /// sent just before a connection dropped and never reached the server.
+    fn send_missing(
+        &self,
+        server_state: &[u8],
+        sender: &mpsc::UnboundedSender<Message>,
+    ) -> Result<()> {
+        let server_state = StateVector::decode_v1(server_state)
+            .map_err(|e| anyhow!("Invalid state vector from server: {}", e))?;
+        let document_id = &self.config.document_id;
+
+        let mut replica = self.replica();
+        let local_state = replica.doc.transact().state_vector();
+        let server_behind = local_state
+            .iter()
+            .any(|(client, clock)| server_state.get(client) < *clock);
+        if !replica.pending.is_empty() || server_behind {
+            let update = replica
+                .doc
+                .transact()
+                .encode_state_as_update_v1(&server_state);
+            sender
+                .send(frame(UPDATE, document_id, &update))
+                .map_err(|_| anyhow!("Connection closed during sync"))?;
+            info!(
+                "Sent {} queued updates for {}",
+                replica.pending.len(),
+                document_id
+            );
+            replica.pending.clear();
+            self.save_queue(&replica);
+        }
+        replica.outgoing = Some(sender.clone());
+        drop(replica);
+
+        self.update_status(|status| {
+            status.state = ConnectionState::Online;
+            status.pending_updates = 0;
+            status.last_synced_at = Some(Utc::now());
+            status.last_error = None;
+        });
+        Ok(())
+    }
+
+    fn handle_text(&self, text: &str) {
+        if let Ok(WsMessage::Error { code, message, .. }) = serde_json::from_str(text) {
+            warn!(
+                "Collaboration server rejected a request for {}: {} ({})",
+                self.config.document_id, message, code
+            );
+            self.update_status(|status| status.last_error = Some(message));
+        }
+    }
+}
+
+/// Queue file of the agent syncing `document_id` from `server_url`
+fn queue_path(dir: &Path, server_url: &str, document_id: &str) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(server_url.as_bytes());
+    hasher.update([0]);
+    hasher.update(document_id.as_bytes());
+    dir.join(format!("{}.queue", hex::encode(&hasher.finalize()[..16])))
+}
+
+/// Replica saved by `Shared::save_queue`: its full state followed by the
+/// queued updates, as length-prefixed records
+fn load_queue(path: &Path) -> Result<Option<Replica>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let data = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut records = decode_update_log(&data, path).into_iter();
+    let Some(state) = records.next() else {
+        return Ok(None);
+    };
+
+    let replica = Replica {
+        pending: records.collect(),
+        ..Replica::empty()
+    };
+    let state = Update::decode_v1(&state).map_err(|e| anyhow!("Invalid queued state: {}", e))?;
+    replica
+        .doc
+        .transact_mut()
+        .apply_update(state)
+        .map_err(|e| anyhow!("Failed to apply queued state: {}", e))?;
+    Ok(Some(replica))
+}
+
+/// Wait until `online` equals `wanted`; false once the agent is gone
+async fn wait_for(online: &mut watch::Receiver<bool>, wanted: bool) -> bool {
+    loop {
+        if *online.borrow_and_update() == wanted {
+            return true;
+        }
+        if online.changed().await.is_err() {
+            return false;
+        }
+    }
+}
+
+/// Format: [kind, ...document_id, 0x00, ...payload]
+fn frame(kind: u8, document_id: &str, payload: &[u8]) -> Message {
+    let mut data = Vec::with_capacity(document_id.len() + payload.len() + 2);
+    data.push(kind);
+    data.extend_from_slice(document_id.as_bytes());
+    data.push(0x00);
+    data.extend_from_slice(payload);
+    Message::Binary(data)
+}
+
+fn parse_frame(data: &[u8]) -> Option<(u8, &str, &[u8])> {
+    let (&kind, rest) = data.split_first()?;
+    let end = rest.iter().position(|&b| b == 0x00)?;
+    let document_id = std::str::from_utf8(&rest[..end]).ok()?;
+    Some((kind, document_id, &rest[end + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config(queue_dir: &Path) -> SyncAgentConfig {
+        SyncAgentConfig {
+            server_url: "ws://127.0.0.1:9".to_string(),
+            token: "token".to_string(),
+            document_id: "doc-1".to_string(),
+            certificate_fingerprint: None,
+            reconnect_delay: Duration::from_secs(60),
+            queue_dir: Some(queue_dir.to_path_buf()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_offline_edits_survive_a_restart() {
+        let temp_dir = TempDir::new().unwrap();
+        let agent = SyncAgent::start(config(temp_dir.path()));
+        agent.set_online(false);
+        assert!(agent.replace_text("Draft"));
+        assert!(agent.replace_text("Draft, revised offline"));
+        assert_eq!(agent.status().pending_updates, 2);
+        drop(agent);
+
+        let restarted = SyncAgent::start(config(temp_dir.path()));
+        restarted.set_online(false);
+        assert_eq!(restarted.text(), "Draft, revised offline");
+        assert_eq!(restarted.status().pending_updates, 2);
+
+        // Another room on the same server has its own queue
+        let other = SyncAgent::start(SyncAgentConfig {
+            document_id: "doc-2".to_string(),
+            ..config(temp_dir.path())
+        });
+        assert_eq!(other.text(), "");
+        assert_eq!(other.status().pending_updates, 0);
+
+        restarted.discard_queue();
+        drop(restarted);
+        let emptied = SyncAgent::start(config(temp_dir.path()));
+        assert_eq!(emptied.text(), "");
+        assert_eq!(emptied.status().pending_updates, 0);
+    }
+}
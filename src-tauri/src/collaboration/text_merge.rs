@@ -0,0 +1,176 @@
+// src-tauri/src/collaboration/text_merge.rs
+// Line-based three-way merge of a room's text with its file on disk
+
+/// Largest number of line pairs compared exactly; past it the differing
+/// middle of the two texts is treated as one change
+const MAX_COMPARED_LINE_PAIRS: usize = 4_000_000;
+
+/// Result of merging two edited copies of the same base text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedText {
+    pub text: String,
+    /// Changes on the incoming side dropped because they overlap local edits
+    pub conflicts: usize,
+}
+
+/// Lines `start..end` of the base replaced by `lines`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Hunk<'a> {
+    start: usize,
+    end: usize,
+    lines: Vec<&'a str>,
+}
+
+impl Hunk<'_> {
+    /// Whether both hunks touch the same base lines or insert at the same point
+    fn overlaps(&self, other: &Hunk) -> bool {
+        self.start == other.start || (self.start < other.end && other.start < self.end)
+    }
+}
+
+/// Apply the changes made from `base` to `incoming` on top of `local`
+///
+/// Changes on either side that touch different lines are both kept. Where an
+/// incoming change overlaps a local one, the local edit wins and the incoming
+/// change is counted as a conflict.
+pub fn merge_text(base: &str, local: &str, incoming: &str) -> MergedText {
+    let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+    let local_hunks = diff_lines(
+        &base_lines,
+        &local.split_inclusive('\n').collect::<Vec<_>>(),
+    );
+    let incoming_hunks = diff_lines(
+        &base_lines,
+        &incoming.split_inclusive('\n').collect::<Vec<_>>(),
+    );
+
+    let mut conflicts = 0;
+    let mut hunks = local_hunks.clone();
+    for hunk in incoming_hunks {
+        match local_hunks.iter().find(|local| local.overlaps(&hunk)) {
+            None => hunks.push(hunk),
+            // Both sides made the same change
+            Some(local) if *local == hunk => {}
+            Some(_) => conflicts += 1,
+        }
+    }
+    hunks.sort_by_key(|hunk| (hunk.start, hunk.end));
+
+    let mut text = String::with_capacity(local.len().max(incoming.len()));
+    let mut cursor = 0;
+    for hunk in hunks {
+        text.extend(base_lines[cursor..hunk.start].iter().copied());
+        text.extend(hunk.lines);
+        cursor = hunk.end;
+    }
+    text.extend(base_lines[cursor..].iter().copied());
+    MergedText { text, conflicts }
+}
+
+/// Changes turning `base` into `other`, from a longest common subsequence of lines
+fn diff_lines<'a>(base: &[&'a str], other: &[&'a str]) -> Vec<Hunk<'a>> {
+    let prefix = base.iter().zip(other).take_while(|(a, b)| a == b).count();
+    let suffix = base[prefix..]
+        .iter()
+        .rev()
+        .zip(other[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let base_middle = &base[prefix..base.len() - suffix];
+    let other_middle = &other[prefix..other.len() - suffix];
+    if base_middle.is_empty() && other_middle.is_empty() {
+        return Vec::new();
+    }
+
+    let whole = || {
+        vec![Hunk {
+            start: prefix,
+            end: prefix + base_middle.len(),
+            lines: other_middle.to_vec(),
+        }]
+    };
+    let (rows, columns) = (base_middle.len(), other_middle.len());
+    if rows == 0 || columns == 0 || rows.saturating_mul(columns) > MAX_COMPARED_LINE_PAIRS {
+        return whole();
+    }
+
+    // common[i][j]: length of the longest common subsequence of the suffixes
+    // starting at base line i and other line j
+    let width = columns + 1;
+    let mut common = vec![0u32; (rows + 1) * width];
+    for i in (0..rows).rev() {
+        for j in (0..columns).rev() {
+            common[i * width + j] = if base_middle[i] == other_middle[j] {
+                common[(i + 1) * width + j + 1] + 1
+            } else {
+                common[(i + 1) * width + j].max(common[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let mut open: Option<Hunk> = None;
+    let (mut i, mut j) = (0, 0);
+    while i < rows || j < columns {
+        if i < rows && j < columns && base_middle[i] == other_middle[j] {
+            hunks.extend(open.take());
+            i += 1;
+            j += 1;
+            continue;
+        }
+        let hunk = open.get_or_insert_with(|| Hunk {
+            start: prefix + i,
+            end: prefix + i,
+            lines: Vec::new(),
+        });
+        if j == columns || (i < rows && common[(i + 1) * width + j] >= common[i * width + j + 1]) {
+            i += 1;
+            hunk.end = prefix + i;
+        } else {
+            hunk.lines.push(other_middle[j]);
+            j += 1;
+        }
+    }
+    hunks.extend(open);
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edits_to_different_lines_are_both_kept() {
+        let base = "# Procedure\nStep 1: isolate\nStep 2: drain\nStep 3: refill\n";
+        let local = "# Procedure\nStep 1: isolate the pump\nStep 2: drain\nStep 3: refill\n";
+        let incoming = "# Procedure\nStep 1: isolate\nStep 2: drain\nStep 3: refill and test\nStep 4: sign off\n";
+
+        let merged = merge_text(base, local, incoming);
+        assert_eq!(
+            merged.text,
+            "# Procedure\nStep 1: isolate the pump\nStep 2: drain\nStep 3: refill and test\nStep 4: sign off\n"
+        );
+        assert_eq!(merged.conflicts, 0);
+
+        // Without local edits the incoming text is taken as-is
+        assert_eq!(merge_text(base, base, incoming).text, incoming);
+        assert_eq!(merge_text(base, local, base).text, local);
+    }
+
+    #[test]
+    fn test_overlapping_edits_keep_the_local_change() {
+        let base = "intro\nbody\noutro\n";
+        let merged = merge_text(
+            base,
+            "intro\nlocal body\noutro\n",
+            "intro\ndisk body\noutro\n",
+        );
+        assert_eq!(merged.text, "intro\nlocal body\noutro\n");
+        assert_eq!(merged.conflicts, 1);
+
+        // The same change made on both sides is not a conflict
+        let merged = merge_text(base, "intro\nnew body\noutro\n", "intro\nnew body\noutro\n");
+        assert_eq!(merged.text, "intro\nnew body\noutro\n");
+        assert_eq!(merged.conflicts, 0);
+    }
+}
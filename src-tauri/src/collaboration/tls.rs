@@ -0,0 +1,269 @@
+// src-tauri/src/collaboration/tls.rs
+// Self-signed TLS identity for serving collaboration over wss://, and the
+// pinned client side used to connect to such a server
+
+use anyhow::{anyhow, Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::crypto::{self, ring, CryptoProvider};
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{self, DigitallySignedStruct, SignatureScheme};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use tracing::info;
+
+const CERT_FILE: &str = "cert.pem";
+const KEY_FILE: &str = "key.pem";
+
+/// SHA-256 fingerprint recorded when the certificate was generated
+const PIN_FILE: &str = "fingerprint.sha256";
+
+/// Certificate and key the collaboration server presents to clients
+///
+/// The certificate is generated once and its fingerprint pinned next to it.
+/// Later runs refuse a certificate that no longer matches the pin, so a
+/// swapped key cannot silently impersonate the host; clients pin the same
+/// fingerprint, which is advertised with the service.
+pub struct TlsIdentity {
+    cert: CertificateDer<'static>,
+    key: PrivateKeyDer<'static>,
+    fingerprint: String,
+}
+
+impl TlsIdentity {
+    /// Default directory for the server certificate
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("fiovana").join("collaboration").join("tls"))
+    }
+
+    /// Load the pinned certificate from `dir`, generating one for
+    /// `hostnames` on first run
+    pub fn load_or_generate(dir: &Path, hostnames: &[String]) -> Result<Self> {
+        let cert_path = dir.join(CERT_FILE);
+        let key_path = dir.join(KEY_FILE);
+        let pin_path = dir.join(PIN_FILE);
+
+        if !cert_path.exists() || !key_path.exists() {
+            Self::generate(dir, hostnames)?;
+        }
+
+        let cert = CertificateDer::from_pem_file(&cert_path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", cert_path.display(), e))?;
+        let key = PrivateKeyDer::from_pem_file(&key_path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", key_path.display(), e))?;
+        let fingerprint = certificate_fingerprint(&cert);
+
+        let pinned = fs::read_to_string(&pin_path)
+            .with_context(|| format!("Failed to read {}", pin_path.display()))?;
+        if pinned.trim() != fingerprint {
+            return Err(anyhow!(
+                "Collaboration certificate in {} does not match its pinned fingerprint",
+                dir.display()
+            ));
+        }
+
+        Ok(Self {
+            cert,
+            key,
+            fingerprint,
+        })
+    }
+
+    fn generate(dir: &Path, hostnames: &[String]) -> Result<()> {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let mut names = vec!["localhost".to_string()];
+        names.extend(hostnames.iter().cloned());
+        names.dedup();
+        let certified = rcgen::generate_simple_self_signed(names)
+            .map_err(|e| anyhow!("Failed to generate certificate: {}", e))?;
+
+        let key_path = dir.join(KEY_FILE);
+        fs::write(&key_path, certified.key_pair.serialize_pem())
+            .with_context(|| format!("Failed to write {}", key_path.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&key_path, fs::Permissions::from_mode(0o600))
+                .with_context(|| format!("Failed to restrict {}", key_path.display()))?;
+        }
+
+        let cert_path = dir.join(CERT_FILE);
+        fs::write(&cert_path, certified.cert.pem())
+            .with_context(|| format!("Failed to write {}", cert_path.display()))?;
+
+        let fingerprint = certificate_fingerprint(certified.cert.der());
+        fs::write(dir.join(PIN_FILE), &fingerprint)
+            .with_context(|| format!("Failed to pin certificate in {}", dir.display()))?;
+
+        info!(
+            "Generated collaboration certificate {} in {}",
+            fingerprint,
+            dir.display()
+        );
+        Ok(())
+    }
+
+    /// SHA-256 fingerprint of the certificate, as colon-separated hex pairs
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    /// Build the acceptor used to wrap incoming TCP connections
+    pub fn acceptor(&self) -> Result<TlsAcceptor> {
+        let config =
+            rustls::ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .context("Failed to select TLS protocol versions")?
+                .with_no_client_auth()
+                .with_single_cert(vec![self.cert.clone()], self.key.clone_key())
+                .context("Invalid collaboration certificate")?;
+
+        Ok(TlsAcceptor::from(Arc::new(config)))
+    }
+}
+
+/// Build a connector that trusts only the certificate with `fingerprint`, as
+/// advertised by the host, since collaboration certificates are self-signed
+pub fn pinned_connector(fingerprint: &str) -> Result<TlsConnector> {
+    let provider = Arc::new(ring::default_provider());
+    let verifier = PinnedCertificate {
+        fingerprint: fingerprint.trim().to_ascii_uppercase(),
+        provider: Arc::clone(&provider),
+    };
+    let config = rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .context("Failed to select TLS protocol versions")?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
+
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+/// Accepts the server certificate by fingerprint instead of by CA chain and
+/// host name; handshake signatures are still checked against it
+#[derive(Debug)]
+struct PinnedCertificate {
+    fingerprint: String,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedCertificate {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if certificate_fingerprint(end_entity) == self.fingerprint {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::General(
+                "Server certificate does not match the pinned fingerprint".to_string(),
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+fn certificate_fingerprint(cert: &CertificateDer<'_>) -> String {
+    Sha256::digest(cert.as_ref())
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_certificate_is_generated_once_and_pinned() {
+        let temp_dir = TempDir::new().unwrap();
+        let hostnames = vec!["fiovana-host.local".to_string()];
+
+        let first = TlsIdentity::load_or_generate(temp_dir.path(), &hostnames).unwrap();
+        assert_eq!(first.fingerprint().split(':').count(), 32);
+        assert!(first.acceptor().is_ok());
+
+        let second = TlsIdentity::load_or_generate(temp_dir.path(), &hostnames).unwrap();
+        assert_eq!(first.fingerprint(), second.fingerprint());
+
+        // A replaced certificate no longer matches the pin
+        let other = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        fs::write(temp_dir.path().join(CERT_FILE), other.cert.pem()).unwrap();
+        fs::write(
+            temp_dir.path().join(KEY_FILE),
+            other.key_pair.serialize_pem(),
+        )
+        .unwrap();
+        assert!(TlsIdentity::load_or_generate(temp_dir.path(), &hostnames).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pinned_connector_only_trusts_the_pinned_certificate() {
+        let temp_dir = TempDir::new().unwrap();
+        let identity = TlsIdentity::load_or_generate(temp_dir.path(), &[]).unwrap();
+        let acceptor = identity.acceptor().unwrap();
+
+        for (fingerprint, trusted) in [
+            (identity.fingerprint().to_lowercase(), true),
+            ("00:11".to_string(), false),
+        ] {
+            let (client, server) = tokio::io::duplex(16 * 1024);
+            let acceptor = acceptor.clone();
+            let accepted = tokio::spawn(async move { acceptor.accept(server).await.is_ok() });
+
+            let connector = pinned_connector(&fingerprint).unwrap();
+            let name = ServerName::try_from("localhost").unwrap();
+            let connected = connector.connect(name, client).await;
+            assert_eq!(connected.is_ok(), trusted);
+            if trusted {
+                assert!(accepted.await.unwrap());
+            }
+        }
+    }
+}
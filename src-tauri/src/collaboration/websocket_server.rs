@@ -4,18 +4,47 @@
 // - Message broadcasting
 // - User presence tracking
 // - Conflict-free sync using Yjs protocol
+// - Room persistence and write-back to workspace files
+// - Token authentication and per-document permissions
+// - Optional TLS (wss://) with a pinned self-signed certificate
+// - Heartbeats, idle eviction, connection caps and per-connection limits
+// - Anchored comment threads and suggested edits
+// - Attributed change history with diff and restore
 
+use anyhow::{anyhow, Context};
 use dashmap::DashMap;
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::mpsc;
-use tokio_tungstenite::{accept_async, tungstenite::Message};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, Notify};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::tungstenite::Error as WsError;
+use tokio_tungstenite::{accept_hdr_async_with_config, tungstenite::Message};
 use tracing::{error, info, warn};
 
+use super::auth::{extract_token, AccessControl, CollaborationRole, CollaboratorIdentity};
+use super::history::{system_author, ChangeOrigin, DocumentHistory, RETAINED_CHANGES};
+use super::persistence::{RoomBinding, RoomStore};
 use super::presence::UserPresence;
-use super::room_manager::RoomManager;
+use super::review::{CommentThread, ReviewState, Suggestion, TextAnchor};
+use super::room_manager::{Room, RoomManager};
+use super::tls::TlsIdentity;
+use crate::commands::document_editing_commands::{calculate_hash, write_document_version};
+use crate::filesystem::atomic_write::write_atomically;
+use crate::filesystem::security::audit_logger::SecurityAuditor;
+use crate::filesystem::watcher::FileEvent;
+
+/// File extensions a room can be materialised to
+const MATERIALIZABLE_EXTENSIONS: &[&str] = &["md", "markdown", "txt"];
 
 /// WebSocket message types for Yjs protocol
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +83,122 @@ pub enum WsMessage {
     /// Ping/Pong for connection health
     Ping,
     Pong,
+    /// Start a comment thread on `anchor`, or reply to `thread_id`
+    AddComment {
+        document_id: String,
+        thread_id: Option<String>,
+        anchor: Option<TextAnchor>,
+        body: String,
+    },
+    /// Resolve a comment thread, or reopen it with `resolved: false`
+    ResolveThread {
+        document_id: String,
+        thread_id: String,
+        resolved: bool,
+    },
+    /// Propose replacing the anchored text instead of editing it directly
+    Suggest {
+        document_id: String,
+        anchor: TextAnchor,
+        replacement: String,
+    },
+    /// Accept or reject a pending suggestion
+    ReviewSuggestion {
+        document_id: String,
+        suggestion_id: String,
+        accept: bool,
+    },
+    /// Every thread and suggestion of a room, sent on join
+    ReviewState {
+        document_id: String,
+        review: ReviewState,
+    },
+    /// A thread was started, replied to, resolved or reopened
+    ThreadUpdated {
+        document_id: String,
+        thread: CommentThread,
+    },
+    /// A suggestion was made, accepted or rejected
+    SuggestionUpdated {
+        document_id: String,
+        suggestion: Suggestion,
+    },
+    /// Request rejected by the server
+    Error {
+        document_id: Option<String>,
+        code: String,
+        message: String,
+    },
+}
+
+/// Authenticated connection and the rooms it has joined
+struct ConnectionSession {
+    identity: CollaboratorIdentity,
+    rooms: HashSet<String>,
+    /// Heartbeats sent since the connection was last heard from
+    missed_heartbeats: u32,
+    rate_limiter: RateLimiter,
+    /// Whether the connection is currently being held back by the rate limiter
+    throttled: bool,
+    /// Wakes the connection's reader so it stops once the connection is evicted
+    evicted: Arc<Notify>,
+}
+
+impl ConnectionSession {
+    fn new(identity: CollaboratorIdentity, max_messages_per_second: u32) -> Self {
+        Self {
+            identity,
+            rooms: HashSet::new(),
+            missed_heartbeats: 0,
+            rate_limiter: RateLimiter::new(max_messages_per_second),
+            throttled: false,
+            evicted: Arc::new(Notify::new()),
+        }
+    }
+}
+
+/// Who is reviewing, with which role, in which room
+type ReviewAccess = (CollaboratorIdentity, CollaborationRole, Arc<Room>);
+
+/// Token bucket holding up to one second's worth of messages
+struct RateLimiter {
+    per_second: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    fn new(per_second: u32) -> Self {
+        let per_second = f64::from(per_second.max(1));
+        Self {
+            per_second,
+            tokens: per_second,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Take a token for one message, borrowing it when the bucket is empty;
+    /// returns how long to wait before the message is within the limit
+    fn reserve(&mut self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.per_second).min(self.per_second);
+        self.last_refill = now;
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.per_second)
+        }
+    }
+}
+
+/// A reserved place under the server's connection cap, released on drop
+struct ConnectionSlot<'a>(&'a AtomicUsize);
+
+impl Drop for ConnectionSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 /// Configuration for the collaboration server
@@ -65,9 +210,23 @@ pub struct ServerConfig {
     pub host: String,
     /// Maximum connections per room
     pub max_connections_per_room: usize,
+    /// Maximum connections across all rooms
+    pub max_connections: usize,
     /// Heartbeat interval in seconds
-    #[allow(dead_code)]
     pub heartbeat_interval: u64,
+    /// Heartbeats a connection may leave unanswered before it is evicted
+    pub max_missed_heartbeats: u32,
+    /// Largest message a client may send, in bytes
+    pub max_message_size: usize,
+    /// Messages a client may send per second before further ones are dropped
+    pub max_messages_per_second: u32,
+    /// How often room update logs are compacted into snapshots, in seconds
+    pub compaction_interval: u64,
+    /// Workspace whose `.fiovana/` directory persists room state; rooms are
+    /// kept in memory only when unset
+    pub workspace_path: Option<PathBuf>,
+    /// How often bound rooms are written back to their files, in seconds
+    pub materialize_interval: u64,
 }
 
 impl Default for ServerConfig {
@@ -76,7 +235,14 @@ impl Default for ServerConfig {
             port: 1234,
             host: "127.0.0.1".to_string(),
             max_connections_per_room: 50,
+            max_connections: 200,
             heartbeat_interval: 30,
+            max_missed_heartbeats: 3,
+            max_message_size: 10 * 1024 * 1024,
+            max_messages_per_second: 100,
+            compaction_interval: 300,
+            workspace_path: None,
+            materialize_interval: 30,
         }
     }
 }
@@ -86,30 +252,269 @@ pub struct CollaborationServer {
     config: ServerConfig,
     room_manager: Arc<RoomManager>,
     connections: Arc<DashMap<String, mpsc::UnboundedSender<Message>>>,
+    store: Option<RoomStore>,
+    bindings: DashMap<String, RoomBinding>, // document_id -> RoomBinding
+    access_control: AccessControl,
+    sessions: DashMap<String, ConnectionSession>, // connection_id -> session
+    /// Connections holding a slot, from handshake until disconnect
+    connection_slots: AtomicUsize,
+    tls: Option<TlsIdentity>,
 }
 
 impl CollaborationServer {
-    /// Create a new collaboration server
+    /// Create a new collaboration server, restoring rooms persisted in the
+    /// configured workspace
     pub fn new(config: ServerConfig) -> Self {
-        Self {
+        let store = config.workspace_path.as_ref().and_then(|workspace| {
+            match RoomStore::for_workspace(workspace) {
+                Ok(store) => Some(store),
+                Err(e) => {
+                    warn!("Collaboration rooms will not be persisted: {}", e);
+                    None
+                }
+            }
+        });
+
+        let server = Self {
             config,
             room_manager: Arc::new(RoomManager::new()),
             connections: Arc::new(DashMap::new()),
+            store,
+            bindings: DashMap::new(),
+            access_control: AccessControl::new(),
+            sessions: DashMap::new(),
+            connection_slots: AtomicUsize::new(0),
+            tls: None,
+        };
+        server.restore_rooms();
+        server
+    }
+
+    /// Load persisted rooms and their file bindings into memory
+    fn restore_rooms(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
+
+        let persisted = match store.load_all() {
+            Ok(persisted) => persisted,
+            Err(e) => {
+                warn!("Failed to load persisted collaboration rooms: {}", e);
+                return;
+            }
+        };
+
+        for room in persisted {
+            let document_id = room.binding.document_id.clone();
+            match Room::restore(document_id.clone(), &room.snapshot, &room.updates) {
+                Ok(restored) => {
+                    restored.set_review_state(room.review);
+                    restored.set_history(DocumentHistory::new(room.history));
+                    self.record_baseline(store, &document_id, &restored);
+                    self.room_manager.insert_room(document_id.clone(), restored);
+                    self.bindings.insert(document_id, room.binding);
+                }
+                Err(e) => warn!("Failed to restore room {}: {}", document_id, e),
+            }
+        }
+
+        info!("Restored {} collaboration rooms", self.bindings.len());
+    }
+
+    /// Give content that predates the room's history a first entry, so the
+    /// timeline can be replayed from the empty document
+    fn record_baseline(&self, store: &RoomStore, document_id: &str, room: &Room) {
+        if !room.with_history(DocumentHistory::is_empty) || room.text_content().is_empty() {
+            return;
         }
+        let state = match room.get_updates_since(&[]) {
+            Ok(state) => state,
+            Err(e) => {
+                warn!("Failed to encode baseline of room {}: {}", document_id, e);
+                return;
+            }
+        };
+        room.update_history(|history| {
+            let entry = history.record(&system_author("system"), ChangeOrigin::Baseline, state);
+            if let Err(e) = store.append_history(document_id, &entry) {
+                warn!("Failed to persist history for room {}: {}", document_id, e);
+            }
+        });
+    }
+
+    /// Serve wss:// using the given certificate instead of plain ws://
+    pub fn with_tls(mut self, identity: TlsIdentity) -> Self {
+        self.tls = Some(identity);
+        self
+    }
+
+    /// Configuration the server was started with
+    pub fn config(&self) -> &ServerConfig {
+        &self.config
+    }
+
+    /// Fingerprint clients should pin, if the server uses TLS
+    pub fn tls_fingerprint(&self) -> Option<&str> {
+        self.tls.as_ref().map(TlsIdentity::fingerprint)
+    }
+
+    /// Number of rooms and of users connected across them
+    pub fn stats(&self) -> (usize, usize) {
+        (
+            self.room_manager.room_count(),
+            self.room_manager.total_user_count(),
+        )
+    }
+
+    /// Tokens and document permissions checked for every client
+    pub fn access_control(&self) -> &AccessControl {
+        &self.access_control
+    }
+
+    /// Read a room's change history
+    pub fn with_history<R>(
+        &self,
+        document_id: &str,
+        read: impl FnOnce(&DocumentHistory) -> R,
+    ) -> anyhow::Result<R> {
+        let room = self
+            .room_manager
+            .get_room(document_id)
+            .ok_or_else(|| anyhow!("Unknown collaboration room: {}", document_id))?;
+        Ok(room.with_history(read))
+    }
+
+    /// Reset a room to its text right after change `seq`. The restore is itself
+    /// recorded as a change, so it can be undone the same way. Returns whether
+    /// the text changed.
+    pub fn restore_document(
+        &self,
+        document_id: &str,
+        seq: u64,
+        restored_by: &CollaboratorIdentity,
+    ) -> anyhow::Result<bool> {
+        let room = self
+            .room_manager
+            .get_room(document_id)
+            .ok_or_else(|| anyhow!("Unknown collaboration room: {}", document_id))?;
+        let text = room.with_history(|history| history.text_at(seq))?;
+
+        let Some(update) = room.replace_text(&text) else {
+            return Ok(false);
+        };
+        info!(
+            "{} restored room {} to change {}",
+            restored_by.user_id, document_id, seq
+        );
+        self.record_change(
+            document_id,
+            &room,
+            restored_by,
+            ChangeOrigin::Restore { seq },
+            &update,
+        );
+        self.broadcast_update(&room, document_id, None, &update);
+        SecurityAuditor::log_collaboration_event(
+            "restore",
+            &restored_by.user_id,
+            Some(document_id),
+            None,
+            serde_json::json!({ "seq": seq }),
+        );
+        Ok(true)
     }
 
     /// Start the WebSocket server
     pub async fn start(self: Arc<Self>) -> Result<(), Box<dyn std::error::Error>> {
         let addr = format!("{}:{}", self.config.host, self.config.port);
         let listener = TcpListener::bind(&addr).await?;
+        let acceptor = match &self.tls {
+            Some(identity) => Some(identity.acceptor()?),
+            None => None,
+        };
+
+        info!(
+            "Collaboration server listening on {}://{}",
+            if acceptor.is_some() { "wss" } else { "ws" },
+            addr
+        );
 
-        info!("Collaboration server listening on {}", addr);
+        // Periodically fold room update logs into snapshots
+        let server = Arc::downgrade(&self);
+        let compaction_interval =
+            std::time::Duration::from_secs(self.config.compaction_interval.max(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(compaction_interval);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let Some(server) = server.upgrade() else {
+                    break;
+                };
+                match tokio::task::spawn_blocking(move || server.compact_rooms()).await {
+                    Ok(0) => {}
+                    Ok(compacted) => info!("Compacted update logs for {} rooms", compacted),
+                    Err(e) => warn!("Room compaction task failed: {}", e),
+                }
+            }
+        });
+
+        // Ping every connection and evict the ones that stopped answering
+        let server = Arc::downgrade(&self);
+        let heartbeat_interval =
+            std::time::Duration::from_secs(self.config.heartbeat_interval.max(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(heartbeat_interval);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let Some(server) = server.upgrade() else {
+                    break;
+                };
+                let evicted = server.check_heartbeats().await;
+                if evicted > 0 {
+                    info!("Evicted {} unresponsive connections", evicted);
+                }
+            }
+        });
+
+        // Periodically write bound rooms back to their workspace files
+        if self.store.is_some() {
+            let server = Arc::downgrade(&self);
+            let materialize_interval =
+                std::time::Duration::from_secs(self.config.materialize_interval.max(1));
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(materialize_interval);
+                interval.tick().await;
+                loop {
+                    interval.tick().await;
+                    let Some(server) = server.upgrade() else {
+                        break;
+                    };
+                    match tokio::task::spawn_blocking(move || server.materialize_rooms()).await {
+                        Ok(0) => {}
+                        Ok(written) => {
+                            info!("Materialised {} collaboration rooms to disk", written)
+                        }
+                        Err(e) => warn!("Room materialisation task failed: {}", e),
+                    }
+                }
+            });
+        }
 
         while let Ok((stream, peer_addr)) = listener.accept().await {
             info!("New connection from: {}", peer_addr);
             let server = Arc::clone(&self);
+            let acceptor = acceptor.clone();
             tokio::spawn(async move {
-                if let Err(e) = server.handle_connection(stream).await {
+                let result = match acceptor {
+                    Some(acceptor) => match acceptor.accept(stream).await {
+                        Ok(stream) => server.handle_connection(stream, peer_addr).await,
+                        Err(e) => Err(format!("TLS handshake failed: {}", e).into()),
+                    },
+                    None => server.handle_connection(stream, peer_addr).await,
+                };
+                if let Err(e) = result {
                     error!("Error handling connection from {}: {}", peer_addr, e);
                 }
             });
@@ -119,16 +524,74 @@ impl CollaborationServer {
     }
 
     /// Handle a single WebSocket connection
-    async fn handle_connection(&self, stream: TcpStream) -> Result<(), Box<dyn std::error::Error>> {
-        let ws_stream = accept_async(stream).await?;
+    async fn handle_connection<S>(
+        &self,
+        stream: S,
+        peer_addr: SocketAddr,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        // Authenticate the bearer token (header or query parameter) during the handshake
+        let mut identity = None;
+        // Held until this function returns, releasing the place under the cap
+        let mut slot = None;
+        let mut rejection = "Missing or invalid access token";
+        let handshake = accept_hdr_async_with_config(
+            stream,
+            |request: &Request, response: Response| {
+                slot = self.reserve_connection_slot();
+                if slot.is_none() {
+                    rejection = "Server is at capacity";
+                    return Err(Self::reject_handshake(
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        rejection,
+                    ));
+                }
+                let authorization = request
+                    .headers()
+                    .get("authorization")
+                    .and_then(|value| value.to_str().ok());
+                match extract_token(authorization, request.uri().query())
+                    .and_then(|token| self.access_control.authenticate(&token))
+                {
+                    Some(authenticated) => {
+                        identity = Some(authenticated);
+                        Ok(response)
+                    }
+                    None => Err(Self::reject_handshake(StatusCode::UNAUTHORIZED, rejection)),
+                }
+            },
+            Some(self.websocket_config()),
+        )
+        .await;
+
+        let (ws_stream, identity) = match (handshake, identity) {
+            (Ok(ws_stream), Some(identity)) => (ws_stream, identity),
+            (Err(e), None) => {
+                SecurityAuditor::log_collaboration_event(
+                    "connect",
+                    "anonymous",
+                    None,
+                    Some(rejection),
+                    serde_json::json!({ "peer": peer_addr.to_string() }),
+                );
+                return Err(e.into());
+            }
+            (Err(e), Some(_)) => return Err(e.into()),
+            (Ok(_), None) => return Err("Handshake completed without authentication".into()),
+        };
         let (mut ws_sender, mut ws_receiver) = ws_stream.split();
 
         // Create a channel for this connection
         let (tx, mut rx) = mpsc::unbounded_channel();
         let connection_id = uuid::Uuid::new_v4().to_string();
 
-        // Store connection sender
+        // Store connection sender and authenticated session
+        let session = ConnectionSession::new(identity, self.config.max_messages_per_second);
+        let evicted = Arc::clone(&session.evicted);
         self.connections.insert(connection_id.clone(), tx);
+        self.sessions.insert(connection_id.clone(), session);
 
         // Spawn task to forward messages from channel to WebSocket
         let connection_id_clone = connection_id.clone();
@@ -144,15 +607,32 @@ impl CollaborationServer {
             }
         });
 
-        // Handle incoming messages
-        while let Some(message) = ws_receiver.next().await {
+        // Handle incoming messages until the peer leaves or is evicted
+        loop {
+            let message = tokio::select! {
+                message = ws_receiver.next() => message,
+                _ = evicted.notified() => break,
+            };
+            let Some(message) = message else {
+                break;
+            };
+            if message.is_ok() {
+                self.mark_alive(&connection_id);
+            }
+
             match message {
                 Ok(Message::Text(text)) => {
+                    if !self.admit_message(&connection_id).await {
+                        continue;
+                    }
                     if let Err(e) = self.handle_text_message(&connection_id, text).await {
                         error!("Error handling text message: {}", e);
                     }
                 }
                 Ok(Message::Binary(data)) => {
+                    if !self.admit_message(&connection_id).await {
+                        continue;
+                    }
                     if let Err(e) = self.handle_binary_message(&connection_id, data).await {
                         error!("Error handling binary message: {}", e);
                     }
@@ -170,6 +650,18 @@ impl CollaborationServer {
                 Ok(Message::Pong(_)) => {
                     // Heartbeat received
                 }
+                Err(WsError::Capacity(e)) => {
+                    warn!("Closing connection {}: {}", connection_id, e);
+                    let _ = self
+                        .send_error(
+                            &connection_id,
+                            None,
+                            "message_too_large",
+                            "Message exceeds the server's size limit",
+                        )
+                        .await;
+                    break;
+                }
                 Err(e) => {
                     error!("WebSocket error on connection {}: {}", connection_id, e);
                     break;
@@ -179,10 +671,190 @@ impl CollaborationServer {
         }
 
         // Clean up connection
+        self.handle_disconnect(&connection_id, "disconnected").await;
         self.connections.remove(&connection_id);
         Ok(())
     }
 
+    /// Handshake rejection with the given status and reason
+    fn reject_handshake(status: StatusCode, reason: &str) -> ErrorResponse {
+        let mut response = ErrorResponse::new(Some(reason.to_string()));
+        *response.status_mut() = status;
+        response
+    }
+
+    /// WebSocket limits applied to every connection
+    fn websocket_config(&self) -> WebSocketConfig {
+        WebSocketConfig {
+            max_message_size: Some(self.config.max_message_size),
+            max_frame_size: Some(self.config.max_message_size),
+            ..Default::default()
+        }
+    }
+
+    /// Record that the connection is alive; any frame counts as a heartbeat
+    fn mark_alive(&self, connection_id: &str) {
+        if let Some(mut session) = self.sessions.get_mut(connection_id) {
+            session.missed_heartbeats = 0;
+        }
+    }
+
+    /// Take a place under the connection cap, or `None` when the server is full.
+    /// Checking and taking happen in one step so concurrent handshakes cannot
+    /// overshoot the cap.
+    fn reserve_connection_slot(&self) -> Option<ConnectionSlot<'_>> {
+        self.connection_slots
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |taken| {
+                (taken < self.config.max_connections).then_some(taken + 1)
+            })
+            .ok()?;
+        Some(ConnectionSlot(&self.connection_slots))
+    }
+
+    /// Apply the connection's rate limit by pausing its reader until the
+    /// message is within the limit; returns false once the connection is gone.
+    ///
+    /// Nothing is dropped: the client and server documents only converge if
+    /// every Yjs update is applied, and a paused reader pushes back on the
+    /// client through TCP flow control.
+    async fn admit_message(&self, connection_id: &str) -> bool {
+        let (delay, user_id, first_delay) = {
+            let Some(mut session) = self.sessions.get_mut(connection_id) else {
+                return false;
+            };
+            let delay = session.rate_limiter.reserve(Instant::now());
+            if delay.is_zero() {
+                session.throttled = false;
+                return true;
+            }
+            let first_delay = !session.throttled;
+            session.throttled = true;
+            (delay, session.identity.user_id.clone(), first_delay)
+        };
+
+        if first_delay {
+            warn!(
+                "Throttling connection {} of user {}",
+                connection_id, user_id
+            );
+        }
+        tokio::time::sleep(delay).await;
+        true
+    }
+
+    /// Ping every connection and evict those that left too many heartbeats
+    /// unanswered; returns how many were evicted
+    async fn check_heartbeats(&self) -> usize {
+        let mut unresponsive = Vec::new();
+        for mut session in self.sessions.iter_mut() {
+            if session.missed_heartbeats >= self.config.max_missed_heartbeats {
+                unresponsive.push((session.key().clone(), Arc::clone(&session.evicted)));
+                continue;
+            }
+            session.missed_heartbeats += 1;
+            if let Some(sender) = self.connections.get(session.key()) {
+                let _ = sender.send(Message::Ping(Vec::new()));
+            }
+        }
+
+        for (connection_id, evicted) in &unresponsive {
+            warn!("Evicting unresponsive connection {}", connection_id);
+            self.handle_disconnect(connection_id, "heartbeat_timeout")
+                .await;
+            if let Some((_, sender)) = self.connections.remove(connection_id) {
+                let _ = sender.send(Message::Close(None));
+            }
+            evicted.notify_one();
+        }
+        unresponsive.len()
+    }
+
+    /// Identity the connection authenticated as
+    fn identity_of(&self, connection_id: &str) -> Option<CollaboratorIdentity> {
+        self.sessions
+            .get(connection_id)
+            .map(|session| session.identity.clone())
+    }
+
+    /// Current role of the connection in a room it has joined. Permissions are
+    /// re-read on every call so revocations apply to open connections.
+    fn room_role(
+        &self,
+        connection_id: &str,
+        document_id: &str,
+    ) -> Option<(CollaboratorIdentity, CollaborationRole)> {
+        let session = self.sessions.get(connection_id)?;
+        if !session.rooms.contains(document_id) {
+            return None;
+        }
+        let role = self
+            .access_control
+            .role_for(&session.identity.user_id, document_id)?;
+        Some((session.identity.clone(), role))
+    }
+
+    /// Remove a closed connection from every room it joined
+    async fn handle_disconnect(&self, connection_id: &str, reason: &str) {
+        let Some((_, session)) = self.sessions.remove(connection_id) else {
+            return;
+        };
+
+        for document_id in &session.rooms {
+            self.leave_room(
+                connection_id,
+                &session.identity.user_id,
+                document_id,
+                reason,
+            )
+            .await;
+        }
+    }
+
+    /// Drop a connection's presence from a room, audit it, and tell the others
+    /// once the user has no connection left in the room
+    async fn leave_room(
+        &self,
+        connection_id: &str,
+        user_id: &str,
+        document_id: &str,
+        reason: &str,
+    ) {
+        info!("User {} leaving room {} ({})", user_id, document_id, reason);
+        SecurityAuditor::log_collaboration_event(
+            "leave",
+            user_id,
+            Some(document_id),
+            None,
+            serde_json::json!({ "connection_id": connection_id, "reason": reason }),
+        );
+
+        let Some(room) = self.room_manager.get_room(document_id) else {
+            return;
+        };
+        room.remove_user(connection_id);
+
+        // Other tabs of the same user keep their presence
+        let still_present = room
+            .get_users()
+            .iter()
+            .any(|(_, presence)| presence.user_id == user_id);
+        if !still_present {
+            if let Err(e) = self
+                .broadcast_to_room(
+                    document_id,
+                    None,
+                    WsMessage::Leave {
+                        document_id: document_id.to_string(),
+                        user_id: user_id.to_string(),
+                    },
+                )
+                .await
+            {
+                warn!("Failed to broadcast leave for room {}: {}", document_id, e);
+            }
+        }
+    }
+
     /// Handle text message (JSON protocol)
     async fn handle_text_message(
         &self,
@@ -192,22 +864,48 @@ impl CollaborationServer {
         let msg: WsMessage = serde_json::from_str(&text)?;
 
         match msg {
-            WsMessage::Join {
+            // The authenticated identity replaces the self-declared user
+            WsMessage::Join { document_id, .. } => {
+                self.handle_join(connection_id, &document_id).await?;
+            }
+            WsMessage::Leave { document_id, .. } => {
+                self.handle_leave(connection_id, &document_id).await?;
+            }
+            WsMessage::Ping => {
+                self.send_to_connection(connection_id, WsMessage::Pong)
+                    .await?;
+            }
+            WsMessage::AddComment {
                 document_id,
-                user_id,
-                user_name,
+                thread_id,
+                anchor,
+                body,
             } => {
-                self.handle_join(connection_id, &document_id, &user_id, &user_name)
+                self.handle_add_comment(connection_id, &document_id, thread_id, anchor, &body)
                     .await?;
             }
-            WsMessage::Leave {
+            WsMessage::ResolveThread {
                 document_id,
-                user_id,
+                thread_id,
+                resolved,
             } => {
-                self.handle_leave(&document_id, &user_id).await?;
+                self.handle_resolve_thread(connection_id, &document_id, &thread_id, resolved)
+                    .await?;
             }
-            WsMessage::Ping => {
-                self.send_to_connection(connection_id, WsMessage::Pong)
+            WsMessage::Suggest {
+                document_id,
+                anchor,
+                replacement,
+            } => {
+                self.handle_suggest(connection_id, &document_id, anchor, replacement)
+                    .await?;
+            }
+            WsMessage::ReviewSuggestion {
+                document_id,
+                suggestion_id,
+                accept,
+            } => {
+                self.handle_review_suggestion(connection_id, &document_id, &suggestion_id, accept)
                     .await?;
             }
             _ => {
@@ -249,6 +947,17 @@ impl CollaborationServer {
         let room_id = String::from_utf8_lossy(&data[1..room_id_end]).to_string();
         let message_data = &data[room_id_end + 1..];
 
+        let Some((identity, role)) = self.room_role(connection_id, &room_id) else {
+            self.send_error(
+                connection_id,
+                Some(&room_id),
+                "not_joined",
+                "Join the document before syncing it",
+            )
+            .await?;
+            return Ok(());
+        };
+
         match message_type {
             0 => {
                 // Sync Step 1: Client requests state vector
@@ -262,6 +971,26 @@ impl CollaborationServer {
                 self.handle_sync_step2(connection_id, &room_id, message_data)
                     .await?;
             }
+            2 if !role.can_edit() => {
+                warn!(
+                    "Rejected update from {} ({:?}) for room {}",
+                    identity.user_id, role, room_id
+                );
+                SecurityAuditor::log_collaboration_event(
+                    "write",
+                    &identity.user_id,
+                    Some(&room_id),
+                    Some("Role does not allow editing"),
+                    serde_json::json!({ "connection_id": connection_id, "role": role }),
+                );
+                self.send_error(
+                    connection_id,
+                    Some(&room_id),
+                    "read_only",
+                    "Your role does not allow editing this document",
+                )
+                .await?;
+            }
             2 => {
                 // Update: Client sends document changes
                 info!(
@@ -269,7 +998,7 @@ impl CollaborationServer {
                     room_id,
                     message_data.len()
                 );
-                self.handle_yjs_update(connection_id, &room_id, message_data)
+                self.handle_yjs_update(connection_id, &identity, &room_id, message_data)
                     .await?;
             }
             _ => {
@@ -316,8 +1045,8 @@ impl CollaborationServer {
         message_data: &[u8],
     ) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(room) = self.room_manager.get_room(room_id) {
-            // Get updates since client's state vector
-            let updates = room.get_updates_since(message_data);
+            // Diff the merged document against the client's state vector
+            let updates = room.get_updates_since(message_data)?;
 
             if !updates.is_empty() {
                 // Construct Sync Step 2 response with updates
@@ -341,99 +1070,723 @@ impl CollaborationServer {
     async fn handle_yjs_update(
         &self,
         connection_id: &str,
+        identity: &CollaboratorIdentity,
         room_id: &str,
         update_data: &[u8],
     ) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(room) = self.room_manager.get_room(room_id) {
-            // Store update in room
-            room.apply_update(update_data.to_vec());
-
-            // Broadcast to all other clients in room
-            // Format: [0x02, ...room_id, 0x00, ...update_data]
-            let mut message = vec![0x02];
-            message.extend_from_slice(room_id.as_bytes());
-            message.push(0x00);
-            message.extend_from_slice(update_data);
+            // Merge into the room document; malformed updates are not broadcast
+            room.apply_update(update_data.to_vec())?;
+            self.record_change(room_id, &room, identity, ChangeOrigin::Edit, update_data);
 
             // Broadcast to all connections in room except sender
-            for (conn_id, _) in room.get_users() {
-                if conn_id.as_str() != connection_id {
-                    if let Some(sender) = self.connections.get(&conn_id) {
-                        let _ = sender.send(Message::Binary(message.clone()));
-                    }
-                }
-            }
+            self.broadcast_update(&room, room_id, Some(connection_id), update_data);
         }
 
         Ok(())
     }
 
-    /// Handle user joining a room
-    async fn handle_join(
+    /// Send a Yjs update to every connection in the room except `exclude_connection`
+    fn broadcast_update(
         &self,
-        connection_id: &str,
-        document_id: &str,
-        user_id: &str,
-        user_name: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        info!(
-            "User {} ({}) joining room {}",
-            user_name, user_id, document_id
-        );
-
-        // Get or create room
-        let room = self
-            .room_manager
-            .get_or_create_room(document_id.to_string());
-
-        // Add user presence
-        let presence = UserPresence {
-            user_id: user_id.to_string(),
-            user_name: user_name.to_string(),
-            color: Self::generate_user_color(user_id),
-            cursor_position: None,
-            last_seen: chrono::Utc::now(),
-        };
-
-        room.add_user(connection_id.to_string(), presence);
-
-        // Broadcast to other users in the room
-        self.broadcast_to_room(
-            document_id,
-            Some(connection_id),
-            WsMessage::Awareness {
-                document_id: document_id.to_string(),
-                awareness: vec![], // Will be populated with actual awareness data
-            },
-        )
-        .await?;
+        room: &Room,
+        room_id: &str,
+        exclude_connection: Option<&str>,
+        update: &[u8],
+    ) {
+        // Format: [0x02, ...room_id, 0x00, ...update_data]
+        let mut message = vec![0x02];
+        message.extend_from_slice(room_id.as_bytes());
+        message.push(0x00);
+        message.extend_from_slice(update);
 
-        Ok(())
+        for (conn_id, _) in room.get_users() {
+            if Some(conn_id.as_str()) != exclude_connection {
+                if let Some(sender) = self.connections.get(&conn_id) {
+                    let _ = sender.send(Message::Binary(message.clone()));
+                }
+            }
+        }
     }
 
-    /// Handle user leaving a room
-    async fn handle_leave(
+    /// Persist an applied update and attribute it to `author` in the room's history
+    fn record_change(
         &self,
         document_id: &str,
-        user_id: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        info!("User {} leaving room {}", user_id, document_id);
-
-        if let Some(room) = self.room_manager.get_room(document_id) {
-            room.remove_user_by_id(user_id);
+        room: &Room,
+        author: &CollaboratorIdentity,
+        origin: ChangeOrigin,
+        update: &[u8],
+    ) {
+        self.persist_update(document_id, room, update);
+        room.update_history(|history| {
+            let entry = history.record(author, origin, update.to_vec());
+            if let Some(store) = &self.store {
+                if let Err(e) = store.append_history(document_id, &entry) {
+                    warn!("Failed to persist history for room {}: {}", document_id, e);
+                }
+            }
+        });
+    }
 
-            // Broadcast leave event
-            self.broadcast_to_room(
+    /// Append an applied update to the room's log on disk, compacting the
+    /// room once the log reaches the threshold
+    fn persist_update(&self, document_id: &str, room: &Room, update: &[u8]) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.append_update(document_id, update) {
+                warn!("Failed to persist update for room {}: {}", document_id, e);
+            }
+        }
+
+        if room.needs_compaction() {
+            self.compact_room(document_id, room);
+        }
+    }
+
+    /// Fold the room's update log into a snapshot, and its oldest history into
+    /// a baseline, on disk when the server has a workspace
+    fn compact_room(&self, document_id: &str, room: &Room) {
+        match &self.store {
+            Some(store) => {
+                if let Err(e) = store.compact(document_id, || room.compact()) {
+                    warn!("Failed to persist snapshot for room {}: {}", document_id, e);
+                }
+            }
+            None => {
+                room.compact();
+            }
+        }
+
+        room.update_history(|history| {
+            if !history.needs_compaction() {
+                return;
+            }
+            let compacted = history
+                .compact(RETAINED_CHANGES)
+                .and_then(|_| match &self.store {
+                    Some(store) => store.replace_history(document_id, history.entries()),
+                    None => Ok(()),
+                });
+            if let Err(e) = compacted {
+                warn!("Failed to compact history for room {}: {}", document_id, e);
+            }
+        });
+    }
+
+    /// Fold every room's update log into a persisted snapshot; returns how many were compacted
+    pub fn compact_rooms(&self) -> usize {
+        let pending = self.room_manager.rooms_pending_compaction();
+        for (document_id, room) in &pending {
+            self.compact_room(document_id, room);
+        }
+        pending.len()
+    }
+
+    /// Bind a room to the workspace file it is materialised to. A room with no
+    /// content yet is seeded from the file; a room bound before has the file's
+    /// changes since it was last synced merged in.
+    pub fn bind_document(&self, document_id: &str, file_path: &Path) -> anyhow::Result<()> {
+        if self.store.is_none() {
+            return Err(anyhow!(
+                "Collaboration server was started without a workspace"
+            ));
+        }
+        let extension = file_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase())
+            .unwrap_or_default();
+        if !MATERIALIZABLE_EXTENSIONS.contains(&extension.as_str()) {
+            return Err(anyhow!(
+                "Only Markdown and text documents can be bound to a room: {}",
+                file_path.display()
+            ));
+        }
+
+        let file_path = file_path
+            .canonicalize()
+            .with_context(|| format!("Failed to resolve {}", file_path.display()))?;
+        let room = self
+            .room_manager
+            .get_or_create_room(document_id.to_string());
+        let mut binding = self
+            .bindings
+            .get(document_id)
+            .map(|entry| entry.clone())
+            .unwrap_or_else(|| RoomBinding::new(document_id));
+        binding.file_path = Some(file_path.clone());
+
+        let content = std::fs::read_to_string(&file_path)
+            .with_context(|| format!("Failed to read {}", file_path.display()))?;
+        match binding.last_synced_text.clone() {
+            Some(base) => self.merge_into_room(document_id, &room, &file_path, &base, &content),
+            None if room.text_content().is_empty() => {
+                self.merge_into_room(document_id, &room, &file_path, "", &content)
+            }
+            // An existing room is authoritative and overwrites the file on the
+            // next materialisation; the file's current content is not an edit
+            None => {}
+        }
+        binding.last_synced_hash = Some(calculate_hash(&content));
+        binding.last_synced_text = Some(content);
+        binding.last_synced_at = Some(chrono::Utc::now());
+
+        self.save_binding(binding);
+        Ok(())
+    }
+
+    fn save_binding(&self, binding: RoomBinding) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save_binding(&binding) {
+                warn!(
+                    "Failed to persist binding for room {}: {}",
+                    binding.document_id, e
+                );
+            }
+        }
+        self.bindings.insert(binding.document_id.clone(), binding);
+    }
+
+    /// Write every bound room whose content changed back to its file, creating
+    /// a document version first; returns how many files were written
+    pub fn materialize_rooms(&self) -> usize {
+        let document_ids: Vec<String> = self
+            .bindings
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect();
+
+        let mut written = 0;
+        for document_id in document_ids {
+            match self.materialize_room(&document_id) {
+                Ok(true) => written += 1,
+                Ok(false) => {}
+                Err(e) => warn!("Failed to materialise room {}: {}", document_id, e),
+            }
+        }
+        written
+    }
+
+    fn materialize_room(&self, document_id: &str) -> anyhow::Result<bool> {
+        let Some(mut binding) = self.bindings.get(document_id).map(|entry| entry.clone()) else {
+            return Ok(false);
+        };
+        let (Some(file_path), Some(room)) = (
+            binding.file_path.clone(),
+            self.room_manager.get_room(document_id),
+        ) else {
+            return Ok(false);
+        };
+
+        // Fold in external edits the watcher has not reported yet, so they
+        // are not overwritten
+        if file_path.exists()
+            && self.merge_file_into_room(document_id, &room, &file_path, &mut binding)?
+        {
+            self.save_binding(binding.clone());
+        }
+
+        let content = room.text_content();
+        let hash = calculate_hash(&content);
+        if binding.last_synced_hash.as_deref() == Some(hash.as_str()) {
+            return Ok(false);
+        }
+
+        write_document_version(document_id, &file_path, &content).map_err(|e| anyhow!(e))?;
+        write_atomically(&file_path, content.as_bytes())?;
+
+        binding.last_synced_hash = Some(hash);
+        binding.last_synced_text = Some(content);
+        binding.last_synced_at = Some(chrono::Utc::now());
+        self.save_binding(binding);
+        Ok(true)
+    }
+
+    /// Merge a file changed outside the editor into the room bound to it
+    pub fn handle_file_event(&self, event: &FileEvent) {
+        if matches!(event, FileEvent::Deleted(_)) {
+            return;
+        }
+
+        let path = event
+            .path()
+            .canonicalize()
+            .unwrap_or_else(|_| event.path().to_path_buf());
+        let path = path.as_path();
+        let Some(document_id) = self
+            .bindings
+            .iter()
+            .find(|entry| entry.file_path.as_deref() == Some(path))
+            .map(|entry| entry.key().clone())
+        else {
+            return;
+        };
+        let (Some(mut binding), Some(room)) = (
+            self.bindings.get(&document_id).map(|entry| entry.clone()),
+            self.room_manager.get_room(&document_id),
+        ) else {
+            return;
+        };
+
+        match self.merge_file_into_room(&document_id, &room, path, &mut binding) {
+            Ok(true) => self.save_binding(binding),
+            Ok(false) => {}
+            Err(e) => warn!(
+                "Failed to merge external change to {} into room {}: {}",
+                path.display(),
                 document_id,
-                None,
-                WsMessage::Leave {
-                    document_id: document_id.to_string(),
-                    user_id: user_id.to_string(),
-                },
+                e
+            ),
+        }
+    }
+
+    /// Merge the file's changes since it was last synced into the room,
+    /// broadcasting the resulting update to connected clients. Returns whether
+    /// the binding's sync state changed.
+    fn merge_file_into_room(
+        &self,
+        document_id: &str,
+        room: &Room,
+        file_path: &Path,
+        binding: &mut RoomBinding,
+    ) -> anyhow::Result<bool> {
+        let content = std::fs::read_to_string(file_path)
+            .with_context(|| format!("Failed to read {}", file_path.display()))?;
+        let hash = calculate_hash(&content);
+        if binding.last_synced_hash.as_deref() == Some(hash.as_str()) {
+            return Ok(false);
+        }
+
+        // Bindings saved before the synced text was kept have no base; the
+        // file then replaces the room text as it always did
+        let base = binding
+            .last_synced_text
+            .clone()
+            .unwrap_or_else(|| room.text_content());
+        self.merge_into_room(document_id, room, file_path, &base, &content);
+        binding.last_synced_hash = Some(hash);
+        binding.last_synced_text = Some(content);
+        binding.last_synced_at = Some(chrono::Utc::now());
+        Ok(true)
+    }
+
+    /// Three-way merge file content into a room and broadcast the update
+    fn merge_into_room(
+        &self,
+        document_id: &str,
+        room: &Room,
+        file_path: &Path,
+        base: &str,
+        content: &str,
+    ) {
+        let (update, conflicts) = room.merge_text(base, content);
+        if conflicts > 0 {
+            warn!(
+                "{} external changes to {} overlap edits in room {} and were not merged",
+                conflicts,
+                file_path.display(),
+                document_id
+            );
+        }
+        if let Some(update) = update {
+            info!(
+                "Merged external change to {} into room {}",
+                file_path.display(),
+                document_id
+            );
+            let author = system_author("filesystem");
+            self.record_change(document_id, room, &author, ChangeOrigin::FileSync, &update);
+            self.broadcast_update(room, document_id, None, &update);
+        }
+    }
+
+    /// Snapshot every room and write bound rooms back to their files
+    pub fn flush(&self) {
+        self.compact_rooms();
+        self.materialize_rooms();
+    }
+
+    /// Handle user joining a room
+    async fn handle_join(
+        &self,
+        connection_id: &str,
+        document_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(identity) = self.identity_of(connection_id) else {
+            return Ok(());
+        };
+        let Some(role) = self.access_control.role_for(&identity.user_id, document_id) else {
+            SecurityAuditor::log_collaboration_event(
+                "join",
+                &identity.user_id,
+                Some(document_id),
+                Some("No permission for document"),
+                serde_json::json!({ "connection_id": connection_id }),
+            );
+            self.send_error(
+                connection_id,
+                Some(document_id),
+                "forbidden",
+                "You do not have access to this document",
             )
             .await?;
+            return Ok(());
+        };
+
+        // Get or create room
+        let room = self
+            .room_manager
+            .get_or_create_room(document_id.to_string());
+
+        let already_joined = room
+            .get_users()
+            .iter()
+            .any(|(conn_id, _)| conn_id == connection_id);
+        if !already_joined && room.user_count() >= self.config.max_connections_per_room {
+            warn!(
+                "Room {} is full, refusing {}",
+                document_id, identity.user_id
+            );
+            self.send_error(
+                connection_id,
+                Some(document_id),
+                "room_full",
+                "This document has reached its connection limit",
+            )
+            .await?;
+            return Ok(());
+        }
+
+        info!(
+            "User {} ({}) joining room {} as {:?}",
+            identity.user_name, identity.user_id, document_id, role
+        );
+        SecurityAuditor::log_collaboration_event(
+            "join",
+            &identity.user_id,
+            Some(document_id),
+            None,
+            serde_json::json!({ "connection_id": connection_id, "role": role }),
+        );
+        if let Some(mut session) = self.sessions.get_mut(connection_id) {
+            session.rooms.insert(document_id.to_string());
         }
 
+        // Add user presence
+        let presence = UserPresence {
+            user_id: identity.user_id.clone(),
+            user_name: identity.user_name.clone(),
+            color: Self::generate_user_color(&identity.user_id),
+            cursor_position: None,
+            last_seen: chrono::Utc::now(),
+        };
+
+        room.add_user(connection_id.to_string(), presence);
+
+        // Bring the joining client up to date with the room's review
+        self.send_to_connection(
+            connection_id,
+            WsMessage::ReviewState {
+                document_id: document_id.to_string(),
+                review: room.review_state(),
+            },
+        )
+        .await?;
+
+        // Broadcast to other users in the room
+        self.broadcast_to_room(
+            document_id,
+            Some(connection_id),
+            WsMessage::Awareness {
+                document_id: document_id.to_string(),
+                awareness: vec![], // Will be populated with actual awareness data
+            },
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Identity and room of a joined connection whose role allows commenting;
+    /// anything else is answered with an error
+    async fn commenter_in(
+        &self,
+        connection_id: &str,
+        document_id: &str,
+    ) -> Result<Option<ReviewAccess>, Box<dyn std::error::Error>> {
+        let Some((identity, role)) = self.room_role(connection_id, document_id) else {
+            self.send_error(
+                connection_id,
+                Some(document_id),
+                "not_joined",
+                "Join the document before reviewing it",
+            )
+            .await?;
+            return Ok(None);
+        };
+        if !role.can_comment() {
+            self.send_error(
+                connection_id,
+                Some(document_id),
+                "read_only",
+                "Your role does not allow commenting on this document",
+            )
+            .await?;
+            return Ok(None);
+        }
+        match self.room_manager.get_room(document_id) {
+            Some(room) => Ok(Some((identity, role, room))),
+            None => Ok(None),
+        }
+    }
+
+    /// Start a thread or reply to one
+    async fn handle_add_comment(
+        &self,
+        connection_id: &str,
+        document_id: &str,
+        thread_id: Option<String>,
+        anchor: Option<TextAnchor>,
+        body: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some((identity, _, room)) = self.commenter_in(connection_id, document_id).await? else {
+            return Ok(());
+        };
+
+        let thread = match (thread_id, anchor) {
+            (Some(thread_id), _) => {
+                room.update_review(|review| review.reply(&thread_id, &identity, body))
+            }
+            (None, Some(anchor)) => room.anchored_text(&anchor).and_then(|quoted_text| {
+                room.update_review(|review| {
+                    review.start_thread(anchor, quoted_text, &identity, body)
+                })
+            }),
+            (None, None) => Err(anyhow!("A new thread needs an anchor")),
+        };
+
+        match thread {
+            Ok(thread) => self.publish_thread(document_id, &room, thread).await,
+            Err(e) => {
+                self.send_error(
+                    connection_id,
+                    Some(document_id),
+                    "invalid_comment",
+                    &e.to_string(),
+                )
+                .await
+            }
+        }
+    }
+
+    async fn handle_resolve_thread(
+        &self,
+        connection_id: &str,
+        document_id: &str,
+        thread_id: &str,
+        resolved: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some((identity, _, room)) = self.commenter_in(connection_id, document_id).await? else {
+            return Ok(());
+        };
+
+        match room
+            .update_review(|review| review.set_resolved(thread_id, resolved, &identity.user_id))
+        {
+            Ok(thread) => self.publish_thread(document_id, &room, thread).await,
+            Err(e) => {
+                self.send_error(
+                    connection_id,
+                    Some(document_id),
+                    "not_found",
+                    &e.to_string(),
+                )
+                .await
+            }
+        }
+    }
+
+    /// Record a suggested edit; the text itself is left untouched
+    async fn handle_suggest(
+        &self,
+        connection_id: &str,
+        document_id: &str,
+        anchor: TextAnchor,
+        replacement: String,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some((identity, _, room)) = self.commenter_in(connection_id, document_id).await? else {
+            return Ok(());
+        };
+
+        let suggestion = room.anchored_text(&anchor).and_then(|original_text| {
+            room.update_review(|review| {
+                review.propose(anchor, original_text, replacement, &identity)
+            })
+        });
+        match suggestion {
+            Ok(suggestion) => {
+                self.publish_suggestion(document_id, &room, suggestion)
+                    .await
+            }
+            Err(e) => {
+                self.send_error(
+                    connection_id,
+                    Some(document_id),
+                    "invalid_suggestion",
+                    &e.to_string(),
+                )
+                .await
+            }
+        }
+    }
+
+    /// Accept (applying the replacement to the text) or reject a suggestion
+    async fn handle_review_suggestion(
+        &self,
+        connection_id: &str,
+        document_id: &str,
+        suggestion_id: &str,
+        accept: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some((identity, role, room)) = self.commenter_in(connection_id, document_id).await?
+        else {
+            return Ok(());
+        };
+        if !role.can_edit() {
+            self.send_error(
+                connection_id,
+                Some(document_id),
+                "read_only",
+                "Only editors can accept or reject suggestions",
+            )
+            .await?;
+            return Ok(());
+        }
+
+        // Decide under the review lock so a suggestion is never applied twice
+        let outcome = room.update_review(|review| {
+            let suggestion = review
+                .pending_suggestion(suggestion_id)
+                .map_err(|e| ("not_found", e))?
+                .clone();
+            let update = if accept {
+                let update = room
+                    .replace_anchored(
+                        &suggestion.anchor,
+                        &suggestion.original_text,
+                        &suggestion.replacement,
+                    )
+                    .map_err(|e| ("suggestion_conflict", e))?;
+                Some(update)
+            } else {
+                None
+            };
+            let decided = review
+                .decide(suggestion_id, accept, &identity.user_id)
+                .map_err(|e| ("not_found", e))?;
+            Ok::<_, (&str, anyhow::Error)>((decided, update))
+        });
+        let (decided, update) = match outcome {
+            Ok(outcome) => outcome,
+            Err((code, e)) => {
+                return self
+                    .send_error(connection_id, Some(document_id), code, &e.to_string())
+                    .await;
+            }
+        };
+
+        if let Some(update) = update {
+            let author = CollaboratorIdentity {
+                user_id: decided.author_id.clone(),
+                user_name: decided.author_name.clone(),
+            };
+            let origin = ChangeOrigin::Suggestion {
+                suggestion_id: suggestion_id.to_string(),
+                accepted_by: identity.user_id.clone(),
+            };
+            self.record_change(document_id, &room, &author, origin, &update);
+            self.broadcast_update(&room, document_id, None, &update);
+        }
+        SecurityAuditor::log_collaboration_event(
+            if accept {
+                "suggestion_accepted"
+            } else {
+                "suggestion_rejected"
+            },
+            &identity.user_id,
+            Some(document_id),
+            None,
+            serde_json::json!({
+                "suggestion_id": suggestion_id,
+                "author_id": decided.author_id,
+            }),
+        );
+        self.publish_suggestion(document_id, &room, decided).await
+    }
+
+    /// Persist the room's review state and send a changed thread to everyone in it
+    async fn publish_thread(
+        &self,
+        document_id: &str,
+        room: &Room,
+        thread: CommentThread,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.persist_review(document_id, room);
+        self.broadcast_to_room(
+            document_id,
+            None,
+            WsMessage::ThreadUpdated {
+                document_id: document_id.to_string(),
+                thread,
+            },
+        )
+        .await
+    }
+
+    /// Persist the room's review state and send a changed suggestion to everyone in it
+    async fn publish_suggestion(
+        &self,
+        document_id: &str,
+        room: &Room,
+        suggestion: Suggestion,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.persist_review(document_id, room);
+        self.broadcast_to_room(
+            document_id,
+            None,
+            WsMessage::SuggestionUpdated {
+                document_id: document_id.to_string(),
+                suggestion,
+            },
+        )
+        .await
+    }
+
+    fn persist_review(&self, document_id: &str, room: &Room) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save_review(document_id, &room.review_state()) {
+                warn!("Failed to persist review for room {}: {}", document_id, e);
+            }
+        }
+    }
+
+    /// Handle user leaving a room
+    async fn handle_leave(
+        &self,
+        connection_id: &str,
+        document_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(mut session) = self.sessions.get_mut(connection_id) else {
+            return Ok(());
+        };
+        if !session.rooms.remove(document_id) {
+            return Ok(());
+        }
+        let user_id = session.identity.user_id.clone();
+        drop(session);
+
+        self.leave_room(connection_id, &user_id, document_id, "left")
+            .await;
         Ok(())
     }
 
@@ -450,6 +1803,25 @@ impl CollaborationServer {
         Ok(())
     }
 
+    /// Send an error frame to a specific connection
+    async fn send_error(
+        &self,
+        connection_id: &str,
+        document_id: Option<&str>,
+        code: &str,
+        message: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.send_to_connection(
+            connection_id,
+            WsMessage::Error {
+                document_id: document_id.map(|id| id.to_string()),
+                code: code.to_string(),
+                message: message.to_string(),
+            },
+        )
+        .await
+    }
+
     /// Broadcast message to all users in a room (except sender if specified)
     async fn broadcast_to_room(
         &self,
@@ -487,6 +1859,7 @@ impl CollaborationServer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
     #[test]
     fn test_server_config_default() {
@@ -496,6 +1869,490 @@ mod tests {
         assert_eq!(config.max_connections_per_room, 50);
     }
 
+    #[test]
+    fn test_rooms_persist_materialise_and_merge_external_edits() {
+        use yrs::updates::decoder::Decode;
+        use yrs::{Doc, ReadTxn, Text, Transact, Update};
+
+        let workspace = tempfile::TempDir::new().unwrap();
+        let file_path = workspace.path().join("notes.md");
+        std::fs::write(&file_path, "Hello").unwrap();
+        let config = ServerConfig {
+            workspace_path: Some(workspace.path().to_path_buf()),
+            ..Default::default()
+        };
+
+        let server = CollaborationServer::new(config.clone());
+        server.bind_document("doc-1", &file_path).unwrap();
+        let room = server.room_manager.get_room("doc-1").unwrap();
+        assert_eq!(room.text_content(), "Hello");
+
+        // A client edit is persisted and written back with a version snapshot
+        let client = Doc::with_client_id(42);
+        client
+            .transact_mut()
+            .apply_update(Update::decode_v1(&room.get_updates_since(&[]).unwrap()).unwrap())
+            .unwrap();
+        let before = client.transact().state_vector();
+        client
+            .get_or_insert_text("content")
+            .insert(&mut client.transact_mut(), 5, " world");
+        let update = client.transact().encode_state_as_update_v1(&before);
+        room.apply_update(update.clone()).unwrap();
+        server.persist_update("doc-1", &room, &update);
+
+        assert_eq!(server.materialize_rooms(), 1);
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "Hello world");
+        assert_eq!(server.materialize_rooms(), 0);
+        let versions = std::fs::read_dir(workspace.path().join(".fiovana_versions/notes"))
+            .unwrap()
+            .count();
+        assert_eq!(versions, 1);
+
+        // An edit made outside the editor is merged into the room
+        std::fs::write(&file_path, "Hello brave world").unwrap();
+        server.handle_file_event(&FileEvent::Modified(file_path.clone()));
+        assert_eq!(room.text_content(), "Hello brave world");
+
+        // A restarted server restores the room and its binding
+        drop(server);
+        let restarted = CollaborationServer::new(config);
+        let room = restarted.room_manager.get_room("doc-1").unwrap();
+        assert_eq!(room.text_content(), "Hello brave world");
+        assert_eq!(restarted.materialize_rooms(), 0);
+
+        // Room edits not yet written back survive an external edit to other lines
+        std::fs::write(&file_path, "Title\nHello brave world\n").unwrap();
+        restarted.handle_file_event(&FileEvent::Modified(file_path.clone()));
+        assert_eq!(room.text_content(), "Title\nHello brave world\n");
+        room.replace_text("Title\nHello brave new world\n");
+        std::fs::write(&file_path, "Release notes\nHello brave world\n").unwrap();
+        restarted.handle_file_event(&FileEvent::Modified(file_path.clone()));
+        assert_eq!(
+            room.text_content(),
+            "Release notes\nHello brave new world\n"
+        );
+        assert_eq!(restarted.materialize_rooms(), 1);
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "Release notes\nHello brave new world\n"
+        );
+    }
+
+    fn next_error_code(rx: &mut mpsc::UnboundedReceiver<Message>) -> Option<String> {
+        match rx.try_recv().ok()? {
+            Message::Text(text) => match serde_json::from_str(&text).ok()? {
+                WsMessage::Error { code, .. } => Some(code),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_permissions_are_checked_on_join_and_update() {
+        let server = CollaborationServer::new(ServerConfig::default());
+        let access = server.access_control();
+        let viewer = access.issue_token("vera", "Vera", Some(CollaborationRole::Viewer), None);
+        let stranger = access.issue_token("sam", "Sam", None, None);
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        server.connections.insert("conn-1".to_string(), tx);
+        server.sessions.insert(
+            "conn-1".to_string(),
+            ConnectionSession::new(access.authenticate(&viewer.token).unwrap(), 100),
+        );
+
+        let mut update_frame = vec![0x02];
+        update_frame.extend_from_slice(b"doc-1");
+        update_frame.push(0x00);
+        update_frame.extend_from_slice(&[1, 2, 3]);
+
+        // Syncing a room that was never joined is refused
+        server
+            .handle_binary_message("conn-1", update_frame.clone())
+            .await
+            .unwrap();
+        assert_eq!(next_error_code(&mut rx).as_deref(), Some("not_joined"));
+
+        // The authenticated identity wins over the self-declared one
+        let join =
+            r#"{"type":"Join","document_id":"doc-1","user_id":"admin","user_name":"Mallory"}"#;
+        server
+            .handle_text_message("conn-1", join.to_string())
+            .await
+            .unwrap();
+        let room = server.room_manager.get_room("doc-1").unwrap();
+        assert_eq!(room.get_users()[0].1.user_id, "vera");
+
+        // Viewers cannot write
+        server
+            .handle_binary_message("conn-1", update_frame)
+            .await
+            .unwrap();
+        assert_eq!(next_error_code(&mut rx).as_deref(), Some("read_only"));
+        assert_eq!(room.pending_update_count(), 0);
+
+        // Collaborators without any role cannot join
+        server.sessions.insert(
+            "conn-2".to_string(),
+            ConnectionSession::new(access.authenticate(&stranger.token).unwrap(), 100),
+        );
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        server.connections.insert("conn-2".to_string(), tx);
+        server
+            .handle_text_message("conn-2", join.to_string())
+            .await
+            .unwrap();
+        assert_eq!(next_error_code(&mut rx).as_deref(), Some("forbidden"));
+        assert_eq!(room.user_count(), 1);
+
+        // Disconnecting removes the viewer's presence
+        server.handle_disconnect("conn-1", "disconnected").await;
+        assert!(room.is_empty());
+    }
+
+    fn join_message(document_id: &str) -> String {
+        format!(
+            r#"{{"type":"Join","document_id":"{}","user_id":"","user_name":""}}"#,
+            document_id
+        )
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_evicts_unresponsive_connections() {
+        let server = CollaborationServer::new(ServerConfig {
+            max_missed_heartbeats: 2,
+            ..Default::default()
+        });
+        let access = server.access_control();
+        let alice = access.issue_token("alice", "Alice", Some(CollaborationRole::Editor), None);
+        let bob = access.issue_token("bob", "Bob", Some(CollaborationRole::Editor), None);
+
+        let mut receivers = Vec::new();
+        for (connection_id, token) in [("conn-1", &alice.token), ("conn-2", &bob.token)] {
+            let (tx, rx) = mpsc::unbounded_channel();
+            server.connections.insert(connection_id.to_string(), tx);
+            server.sessions.insert(
+                connection_id.to_string(),
+                ConnectionSession::new(access.authenticate(token).unwrap(), 100),
+            );
+            server
+                .handle_text_message(connection_id, join_message("doc-1"))
+                .await
+                .unwrap();
+            receivers.push(rx);
+        }
+        let room = server.room_manager.get_room("doc-1").unwrap();
+        assert_eq!(room.user_count(), 2);
+        while receivers[1].try_recv().is_ok() {}
+
+        // Bob answers every ping, Alice never does
+        for _ in 0..2 {
+            assert_eq!(server.check_heartbeats().await, 0);
+            server.mark_alive("conn-2");
+        }
+        assert!(std::iter::from_fn(|| receivers[0].try_recv().ok())
+            .any(|message| matches!(message, Message::Ping(_))));
+        assert_eq!(server.check_heartbeats().await, 1);
+
+        assert!(!server.sessions.contains_key("conn-1"));
+        assert!(!server.connections.contains_key("conn-1"));
+        assert_eq!(room.user_count(), 1);
+
+        let mut saw_leave = false;
+        while let Ok(message) = receivers[1].try_recv() {
+            if let Message::Text(text) = message {
+                saw_leave |= matches!(
+                    serde_json::from_str::<WsMessage>(&text),
+                    Ok(WsMessage::Leave { user_id, .. }) if user_id == "alice"
+                );
+            }
+        }
+        assert!(saw_leave);
+    }
+
+    #[tokio::test]
+    async fn test_room_capacity_and_rate_limit() {
+        let server = CollaborationServer::new(ServerConfig {
+            max_connections_per_room: 1,
+            max_messages_per_second: 2,
+            ..Default::default()
+        });
+        let access = server.access_control();
+        let token = access.issue_token("alice", "Alice", Some(CollaborationRole::Editor), None);
+
+        let mut receivers = Vec::new();
+        for connection_id in ["conn-1", "conn-2"] {
+            let (tx, rx) = mpsc::unbounded_channel();
+            server.connections.insert(connection_id.to_string(), tx);
+            server.sessions.insert(
+                connection_id.to_string(),
+                ConnectionSession::new(access.authenticate(&token.token).unwrap(), 2),
+            );
+            server
+                .handle_text_message(connection_id, join_message("doc-1"))
+                .await
+                .unwrap();
+            receivers.push(rx);
+        }
+        assert_eq!(
+            server.room_manager.get_room("doc-1").unwrap().user_count(),
+            1
+        );
+        assert_eq!(
+            next_error_code(&mut receivers[1]).as_deref(),
+            Some("room_full")
+        );
+
+        // A burst beyond the limit is held back rather than dropped
+        let started = Instant::now();
+        assert!(server.admit_message("conn-1").await);
+        assert!(server.admit_message("conn-1").await);
+        assert!(started.elapsed() < Duration::from_millis(200));
+        assert!(server.admit_message("conn-1").await);
+        assert!(started.elapsed() >= Duration::from_millis(400));
+        assert!(receivers[0].try_recv().is_err());
+        assert!(!server.admit_message("gone").await);
+    }
+
+    #[test]
+    fn test_connection_cap_is_reserved_atomically() {
+        let server = CollaborationServer::new(ServerConfig {
+            max_connections: 2,
+            ..Default::default()
+        });
+        let first = server.reserve_connection_slot();
+        let second = server.reserve_connection_slot();
+        assert!(first.is_some() && second.is_some());
+        assert!(server.reserve_connection_slot().is_none());
+
+        drop(first);
+        assert!(server.reserve_connection_slot().is_some());
+    }
+
+    fn drain_messages(rx: &mut mpsc::UnboundedReceiver<Message>) -> Vec<WsMessage> {
+        std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|message| match message {
+                Message::Text(text) => serde_json::from_str(&text).ok(),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_comments_and_suggestions_respect_roles() {
+        let server = CollaborationServer::new(ServerConfig::default());
+        let access = server.access_control();
+
+        let mut receivers = HashMap::new();
+        for (user_id, role) in [
+            ("ed", CollaborationRole::Editor),
+            ("sme", CollaborationRole::Commenter),
+            ("vi", CollaborationRole::Viewer),
+        ] {
+            let token = access.issue_token(user_id, user_id, Some(role), None);
+            let (tx, rx) = mpsc::unbounded_channel();
+            server.connections.insert(user_id.to_string(), tx);
+            server.sessions.insert(
+                user_id.to_string(),
+                ConnectionSession::new(access.authenticate(&token.token).unwrap(), 100),
+            );
+            server
+                .handle_text_message(user_id, join_message("doc-1"))
+                .await
+                .unwrap();
+            receivers.insert(user_id, rx);
+        }
+        let room = server.room_manager.get_room("doc-1").unwrap();
+        room.replace_text("Teh course");
+        let anchor = room.anchor_range(0, 3).unwrap();
+        for rx in receivers.values_mut() {
+            drain_messages(rx);
+        }
+
+        let send = |connection_id: &'static str, message: WsMessage| {
+            let server = &server;
+            async move {
+                server
+                    .handle_text_message(connection_id, serde_json::to_string(&message).unwrap())
+                    .await
+                    .unwrap();
+            }
+        };
+
+        // Commenters open threads every member of the room sees
+        send(
+            "sme",
+            WsMessage::AddComment {
+                document_id: "doc-1".to_string(),
+                thread_id: None,
+                anchor: Some(anchor.clone()),
+                body: "Typo".to_string(),
+            },
+        )
+        .await;
+        let thread = match drain_messages(receivers.get_mut("vi").unwrap()).pop() {
+            Some(WsMessage::ThreadUpdated { thread, .. }) => thread,
+            other => panic!("expected a thread, got {:?}", other),
+        };
+        assert_eq!(thread.quoted_text, "Teh");
+
+        // Viewers can read but not comment
+        send(
+            "vi",
+            WsMessage::ResolveThread {
+                document_id: "doc-1".to_string(),
+                thread_id: thread.id.clone(),
+                resolved: true,
+            },
+        )
+        .await;
+        assert_eq!(
+            next_error_code(receivers.get_mut("vi").unwrap()).as_deref(),
+            Some("read_only")
+        );
+
+        // Suggestions leave the text alone until an editor accepts them
+        send(
+            "sme",
+            WsMessage::Suggest {
+                document_id: "doc-1".to_string(),
+                anchor,
+                replacement: "The".to_string(),
+            },
+        )
+        .await;
+        let suggestion = match drain_messages(receivers.get_mut("ed").unwrap()).pop() {
+            Some(WsMessage::SuggestionUpdated { suggestion, .. }) => suggestion,
+            other => panic!("expected a suggestion, got {:?}", other),
+        };
+        assert_eq!(room.text_content(), "Teh course");
+
+        let review = |accept| WsMessage::ReviewSuggestion {
+            document_id: "doc-1".to_string(),
+            suggestion_id: suggestion.id.clone(),
+            accept,
+        };
+        drain_messages(receivers.get_mut("sme").unwrap());
+        send("sme", review(true)).await;
+        assert_eq!(
+            next_error_code(receivers.get_mut("sme").unwrap()).as_deref(),
+            Some("read_only")
+        );
+
+        send("ed", review(true)).await;
+        assert_eq!(room.text_content(), "The course");
+        assert_eq!(
+            room.review_state().suggestions[0].decided_by.as_deref(),
+            Some("ed")
+        );
+        drain_messages(receivers.get_mut("ed").unwrap());
+        send("ed", review(false)).await;
+        assert_eq!(
+            next_error_code(receivers.get_mut("ed").unwrap()).as_deref(),
+            Some("not_found")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_history_attributes_changes_and_restores() {
+        use yrs::updates::decoder::Decode;
+        use yrs::{Doc, ReadTxn, Text, Transact, Update};
+
+        let workspace = tempfile::TempDir::new().unwrap();
+        let file_path = workspace.path().join("procedure.md");
+        std::fs::write(&file_path, "Step 1").unwrap();
+        let config = ServerConfig {
+            workspace_path: Some(workspace.path().to_path_buf()),
+            ..Default::default()
+        };
+
+        let server = CollaborationServer::new(config.clone());
+        server.bind_document("doc-1", &file_path).unwrap();
+        let access = server.access_control();
+        let token = access.issue_token("ed", "Ed", Some(CollaborationRole::Editor), None);
+        let (tx, _rx) = mpsc::unbounded_channel();
+        server.connections.insert("ed".to_string(), tx);
+        server.sessions.insert(
+            "ed".to_string(),
+            ConnectionSession::new(access.authenticate(&token.token).unwrap(), 100),
+        );
+        server
+            .handle_text_message("ed", join_message("doc-1"))
+            .await
+            .unwrap();
+
+        // An edit from a client is attributed to its authenticated user
+        let room = server.room_manager.get_room("doc-1").unwrap();
+        let client = Doc::with_client_id(42);
+        client
+            .transact_mut()
+            .apply_update(Update::decode_v1(&room.get_updates_since(&[]).unwrap()).unwrap())
+            .unwrap();
+        let before = client.transact().state_vector();
+        client.get_or_insert_text("content").insert(
+            &mut client.transact_mut(),
+            6,
+            ": isolate the pump",
+        );
+        let mut frame = vec![0x02];
+        frame.extend_from_slice(b"doc-1");
+        frame.push(0x00);
+        frame.extend_from_slice(&client.transact().encode_state_as_update_v1(&before));
+        server.handle_binary_message("ed", frame).await.unwrap();
+        assert_eq!(room.text_content(), "Step 1: isolate the pump");
+
+        let changes = server
+            .with_history("doc-1", |history| history.changes(0, 2))
+            .unwrap()
+            .unwrap();
+        assert_eq!(changes[0].user_id, "system:filesystem");
+        assert_eq!(changes[0].origin, ChangeOrigin::FileSync);
+        assert_eq!(changes[1].user_id, "ed");
+        assert_eq!(changes[1].inserted, ": isolate the pump");
+
+        // Restoring a past state is itself a recorded change
+        let host = system_author("host");
+        assert!(server.restore_document("doc-1", 1, &host).unwrap());
+        assert!(!server.restore_document("doc-1", 1, &host).unwrap());
+        assert!(server.restore_document("doc-1", 9, &host).is_err());
+        assert_eq!(room.text_content(), "Step 1");
+
+        // The timeline survives a restart
+        drop(server);
+        let restarted = CollaborationServer::new(config);
+        let (latest, restored) = restarted
+            .with_history("doc-1", |history| {
+                (history.latest_seq(), history.text_at(2))
+            })
+            .unwrap();
+        assert_eq!(latest, 3);
+        assert_eq!(restored.unwrap(), "Step 1: isolate the pump");
+        let sessions = restarted
+            .with_history("doc-1", |history| {
+                history.sessions(chrono::Duration::minutes(10))
+            })
+            .unwrap();
+        let authors: Vec<&str> = sessions.iter().map(|s| s.user_id.as_str()).collect();
+        assert_eq!(authors, vec!["system:filesystem", "ed", "system:host"]);
+    }
+
+    #[test]
+    fn test_rate_limiter_refills_over_time() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::new(10);
+        limiter.last_refill = start;
+        assert!((0..10).all(|_| limiter.reserve(start).is_zero()));
+        assert_eq!(limiter.reserve(start), Duration::from_millis(100));
+
+        // The borrowed token is repaid before new ones accumulate
+        let later = start + Duration::from_millis(350);
+        assert!(limiter.reserve(later).is_zero());
+        assert!(limiter.reserve(later).is_zero());
+        assert!(!limiter.reserve(later).is_zero());
+    }
+
     #[test]
     fn test_generate_user_color() {
         let color1 = CollaborationServer::generate_user_color("user1");
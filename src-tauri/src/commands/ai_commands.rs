@@ -1,12 +1,16 @@
 // src-tauri/src/commands/ai_commands.rs
 
-use crate::ai::{AIConfig, AIOrchestrator, AIResponse};
+use crate::ai::{
+    AIConfig, AIOrchestrator, AIResponse, IntentClassificationMode, IntentEvaluationReport,
+    LabelledExample, PromptLibrary, PromptTemplateVersion, TemplateLoadError,
+};
 use crate::commands::conversation_context_commands::ConversationContextState;
 use crate::commands::document_indexing_commands::{
     get_relevant_documents_for_context, DocumentIndexerState,
 };
 use crate::commands::vector_commands::VectorState;
 use crate::vector::{EmbeddingConfig, EmbeddingEngine, SearchResult};
+use crate::workspace::usage_log::{record_document_usage, UsageEventKind};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -36,13 +40,21 @@ pub struct AIStatusResponse {
     pub error: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplateStatus {
+    pub templates: Vec<PromptTemplateVersion>,
+    pub errors: Vec<TemplateLoadError>,
+}
+
 // AI state management
 pub type AIState = Arc<Mutex<Option<AIOrchestrator>>>;
 
 #[tauri::command]
 pub async fn init_ai_system(
     ai_state: State<'_, AIState>,
+    vector_state: State<'_, VectorState>,
     config: Option<serde_json::Value>,
+    workspace_path: Option<String>,
 ) -> Result<bool, String> {
     tracing::info!("Initializing AI system with config: {:?}", config);
 
@@ -57,7 +69,10 @@ pub async fn init_ai_system(
     };
 
     // Convert JSON settings to AIConfig
-    let ai_config = settings_to_ai_config(settings.clone())?;
+    let mut ai_config = settings_to_ai_config(settings.clone())?;
+    if let Some(workspace_path) = workspace_path.filter(|p| !p.is_empty()) {
+        ai_config.workspace_path = Some(std::path::PathBuf::from(workspace_path));
+    }
     tracing::info!(
         "Converted to AIConfig: provider={}, model={}, openrouter_key_present={}",
         ai_config.provider,
@@ -65,8 +80,24 @@ pub async fn init_ai_system(
         ai_config.openrouter_api_key.is_some()
     );
 
+    let intent_mode = ai_config.intent_classification;
     match AIOrchestrator::new(ai_config).await {
-        Ok(orchestrator) => {
+        Ok(mut orchestrator) => {
+            if intent_mode == IntentClassificationMode::Embedding {
+                let engine = vector_state.embedding_engine.lock().await.clone();
+                let engine = match engine {
+                    Some(engine) => Ok(engine),
+                    None => EmbeddingEngine::new(EmbeddingConfig::default()).await,
+                };
+                let enabled = match engine {
+                    Ok(engine) => orchestrator.enable_embedding_intent_stage(engine).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = enabled {
+                    warn!("Embedding intent stage unavailable, using patterns: {}", e);
+                }
+            }
+
             let mut state = ai_state.lock().await;
             *state = Some(orchestrator);
             tracing::info!("AI system initialized successfully");
@@ -108,31 +139,47 @@ pub async fn chat_with_ai(
         context_manager.get_enriched_context(&session_id, &request.message)
     };
 
-    // Check if conversational intelligence is available and enabled
-    let use_conversational_intelligence = {
-        let conv_state = conversational_state.lock().await;
-        conv_state.is_some()
-    };
+    // Use the conversational intelligence system for enhanced intent-based processing
+    // when it is available. The message is classified once here and the result is
+    // reused by the chat fallback below.
+    let mut classified_intent = None;
+    let conversation_result = {
+        let mut conv_state = conversational_state.lock().await;
+        match conv_state.as_mut() {
+            Some(system) => {
+                let conversation_request =
+                    crate::commands::conversational_intelligence_commands::ConversationRequest {
+                        user_input: request.message.clone(),
+                        session_id: None, // ChatRequest doesn't have session_id, we'll auto-generate
+                        context: request.context.as_ref().map(|ctx| {
+                            let mut context_map = std::collections::HashMap::new();
+                            context_map.insert("user_context".to_string(), ctx.clone());
+                            context_map
+                        }),
+                    };
 
-    if use_conversational_intelligence {
-        // Use the conversational intelligence system for enhanced intent-based processing
-        let conversation_request =
-            crate::commands::conversational_intelligence_commands::ConversationRequest {
-                user_input: request.message.clone(),
-                session_id: None, // ChatRequest doesn't have session_id, we'll auto-generate
-                context: request.context.as_ref().map(|ctx| {
-                    let mut context_map = std::collections::HashMap::new();
-                    context_map.insert("user_context".to_string(), ctx.clone());
-                    context_map
-                }),
-            };
+                let classified = {
+                    let ai = ai_state.lock().await;
+                    system.classify(&request.message, ai.as_ref()).await
+                };
+                match classified {
+                    Ok(intent_result) => {
+                        classified_intent = Some(intent_result.clone());
+                        Some(
+                            system
+                                .process_conversation(conversation_request, intent_result)
+                                .await,
+                        )
+                    }
+                    Err(e) => Some(Err(e)),
+                }
+            }
+            None => None,
+        }
+    };
 
-        match crate::commands::conversational_intelligence_commands::process_conversation(
-            conversational_state,
-            conversation_request,
-        )
-        .await
-        {
+    if let Some(conversation_result) = conversation_result {
+        match conversation_result {
             Ok(conv_response) => {
                 // If the action was executed successfully, return the result
                 if conv_response.action_executed {
@@ -178,6 +225,7 @@ pub async fn chat_with_ai(
                             document_references: vec![],
                             action_items: vec![],
                             style_guidance: None,
+                            grounding: None,
                             metadata: crate::ai::response::ResponseMetadata {
                                 processing_time_ms: action_result.execution_time_ms,
                                 model_used: "intent_classification".to_string(),
@@ -190,6 +238,8 @@ pub async fn chat_with_ai(
                                 reasoning_chain: vec![
                                     "Used conversational intelligence system".to_string()
                                 ],
+                                context_budget: None,
+                                prompt_template: None,
                             },
                         };
 
@@ -274,25 +324,67 @@ pub async fn chat_with_ai(
                 context_parts.push(context.to_string());
             }
 
-            // Add document search results
-            context_parts.push("=== DOCUMENT SEARCH RESULTS ===".to_string());
-            let document_context =
-                perform_enhanced_document_search(&vector_state, &indexer_state, &request.message)
-                    .await;
-            context_parts.push(document_context);
-
-            let enhanced_context = context_parts.join("\n\n");
-            debug!(
-                "Enhanced chat context length: {} characters",
-                enhanced_context.len()
-            );
+            // Prefer grounded answers with chunk citations when vector search finds chunks
+            let vector_results = search_vector_chunks(&vector_state, &request.message).await;
+            let result = if !vector_results.is_empty() {
+                info!(
+                    "Answering with {} cited chunks from vector search",
+                    vector_results.len()
+                );
+                let supplementary_context = context_parts.join("\n\n");
+                orchestrator
+                    .process_grounded_question(
+                        &request.message,
+                        &vector_results,
+                        Some(&supplementary_context),
+                        &session_id,
+                        classified_intent,
+                    )
+                    .await
+            } else {
+                // Fall back to keyword document search without citations
+                context_parts.push("=== DOCUMENT SEARCH RESULTS ===".to_string());
+                let keyword_results =
+                    perform_indexed_document_search(&indexer_state, &request.message).await;
+                let document_context = if keyword_results.is_empty() {
+                    // No results from either search
+                    tracing::warn!("Both vector and document search returned empty results");
+                    "No relevant documents found. Make sure you have uploaded documents in File Management and they have been successfully indexed and processed for vector search.".to_string()
+                } else {
+                    keyword_results
+                };
+                context_parts.push(document_context);
+
+                let enhanced_context = context_parts.join("\n\n");
+                debug!(
+                    "Enhanced chat context length: {} characters",
+                    enhanced_context.len()
+                );
 
-            // Process with AI orchestrator
-            match orchestrator
-                .process_conversation(&request.message, Some(&enhanced_context))
-                .await
-            {
+                // Process with AI orchestrator
+                orchestrator
+                    .process_conversation_with_session(
+                        &request.message,
+                        Some(&enhanced_context),
+                        "default",
+                        classified_intent,
+                    )
+                    .await
+            };
+
+            match result {
                 Ok(response) => {
+                    if let Some(grounding) = &response.grounding {
+                        let mut cited: Vec<&str> = grounding
+                            .citations
+                            .iter()
+                            .filter_map(|citation| citation.path.as_deref())
+                            .collect();
+                        cited.sort_unstable();
+                        cited.dedup();
+                        record_document_usage(&cited, UsageEventKind::Cited);
+                    }
+
                     // Add assistant response to conversation context
                     {
                         let mut context_manager = conversation_context_state.lock().await;
@@ -375,13 +467,53 @@ pub async fn shutdown_ai_system(ai_state: State<'_, AIState>) -> Result<bool, St
 #[tauri::command]
 pub async fn restart_ai_system(
     ai_state: State<'_, AIState>,
+    vector_state: State<'_, VectorState>,
     config: Option<serde_json::Value>,
+    workspace_path: Option<String>,
 ) -> Result<bool, String> {
     // Shutdown first
     let _ = shutdown_ai_system(ai_state.clone()).await;
 
     // Then reinitialize
-    init_ai_system(ai_state, config).await
+    init_ai_system(ai_state, vector_state, config, workspace_path).await
+}
+
+/// Reload prompt templates from the global and (optional) workspace template directories
+#[tauri::command]
+pub async fn reload_prompt_templates(
+    ai_state: State<'_, AIState>,
+    workspace_path: Option<String>,
+) -> Result<PromptTemplateStatus, String> {
+    let library = PromptLibrary::load(workspace_path.as_deref().map(std::path::Path::new));
+    let status = PromptTemplateStatus {
+        templates: library.versions(),
+        errors: library.errors().to_vec(),
+    };
+
+    let mut state = ai_state.lock().await;
+    let orchestrator = state.as_mut().ok_or("AI system not initialized")?;
+    orchestrator.set_prompt_library(library);
+    Ok(status)
+}
+
+/// Measure intent classification accuracy, using the built-in test set by default
+#[tauri::command]
+pub async fn evaluate_intent_classification(
+    ai_state: State<'_, AIState>,
+    cases: Option<Vec<LabelledExample>>,
+) -> Result<IntentEvaluationReport, String> {
+    let state = ai_state.lock().await;
+    let orchestrator = state.as_ref().ok_or("AI system not initialized")?;
+    let cases = cases.unwrap_or_else(crate::ai::intent_stage::evaluation_examples);
+
+    let report = orchestrator.evaluate_intent_classification(&cases).await;
+    info!(
+        "Intent classification accuracy: {}/{} ({:.1}%)",
+        report.correct,
+        report.total,
+        report.accuracy * 100.0
+    );
+    Ok(report)
 }
 
 #[tauri::command]
@@ -481,6 +613,25 @@ fn settings_to_ai_config(settings: serde_json::Value) -> Result<AIConfig, String
         temperature: 0.7,
         max_tokens: Some(4096),
         ollama: crate::ai::OllamaConfig::default(),
+        context_window: settings
+            .get("contextWindow")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize),
+        tokenizer_path: settings
+            .get("tokenizerPath")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string()),
+        intent_classification: settings
+            .get("intentClassification")
+            .cloned()
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default(),
+        workspace_path: settings
+            .get("workspacePath")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(std::path::PathBuf::from),
     })
 }
 
@@ -623,7 +774,7 @@ async fn perform_indexed_document_search(
             tracing::info!("Document search returned {} documents", documents.len());
             if documents.is_empty() {
                 tracing::warn!("No documents found in index for query: '{}'", query);
-                "No relevant documents found in index. Make sure you have uploaded documents in File Management and they have been successfully indexed.".to_string()
+                String::new()
             } else {
                 let mut context_parts = vec!["=== RELEVANT DOCUMENTS ===".to_string()];
 
@@ -659,45 +810,8 @@ async fn perform_indexed_document_search(
     }
 }
 
-/// Perform enhanced document search combining vector and keyword search
-async fn perform_enhanced_document_search(
-    vector_state: &VectorState,
-    indexer_state: &DocumentIndexerState,
-    query: &str,
-) -> String {
-    info!("AI performing enhanced search for query: '{}'", query);
-
-    // Vector-first approach: Try vector search first, only fall back to document search if needed
-    let vector_results = perform_vector_search(vector_state, query).await;
-
-    if !vector_results.is_empty() {
-        // Vector search found results - use only these (most precise and efficient)
-        tracing::info!(
-            "Using vector search results only - {} characters",
-            vector_results.len()
-        );
-        vector_results
-    } else {
-        // Vector search failed, fall back to document search
-        tracing::info!("Vector search empty, attempting document search fallback");
-        let keyword_results = perform_indexed_document_search(indexer_state, query).await;
-
-        if !keyword_results.is_empty() {
-            tracing::info!(
-                "Using document search fallback - {} characters",
-                keyword_results.len()
-            );
-            keyword_results
-        } else {
-            // No results from either search
-            tracing::warn!("Both vector and document search returned empty results");
-            "No relevant documents found. Make sure you have uploaded documents in File Management and they have been successfully indexed and processed for vector search.".to_string()
-        }
-    }
-}
-
-/// Perform vector search using embeddings
-async fn perform_vector_search(vector_state: &VectorState, query: &str) -> String {
+/// Retrieve the chunks most similar to the query
+async fn search_vector_chunks(vector_state: &VectorState, query: &str) -> Vec<SearchResult> {
     debug!("Performing vector search for query: '{}'", query);
 
     // Get the vector store from state
@@ -712,57 +826,28 @@ async fn perform_vector_search(vector_state: &VectorState, query: &str) -> Strin
                     // Perform vector search
                     match vector_store.search(&query_embedding, 5).await {
                         Ok(results) => {
-                            if results.is_empty() {
-                                debug!("Vector search returned no results");
-                                String::new()
-                            } else {
-                                debug!("Vector search returned {} results", results.len());
-                                format_vector_search_results(results)
-                            }
+                            debug!("Vector search returned {} results", results.len());
+                            results
                         }
                         Err(e) => {
                             warn!("Vector search error: {}", e);
-                            String::new()
+                            Vec::new()
                         }
                     }
                 }
                 Err(e) => {
                     warn!("Failed to generate query embedding: {}", e);
-                    String::new()
+                    Vec::new()
                 }
             }
         }
         Err(e) => {
             warn!("Failed to create embedding engine: {}", e);
-            String::new()
+            Vec::new()
         }
     }
 }
 
-/// Format vector search results for AI context
-fn format_vector_search_results(results: Vec<SearchResult>) -> String {
-    let mut context_parts = vec!["=== VECTOR SEARCH RESULTS ===".to_string()];
-
-    for (i, result) in results.iter().enumerate() {
-        let preview = if result.chunk.content.len() > 400 {
-            format!("{}...", &result.chunk.content[..400])
-        } else {
-            result.chunk.content.clone()
-        };
-
-        context_parts.push(format!(
-            "Result {}: Document {} (Similarity: {:.3})\nContent: {}\nExplanation: {}",
-            i + 1,
-            result.chunk.document_id,
-            result.similarity,
-            preview,
-            result.explanation
-        ));
-    }
-
-    context_parts.join("\n\n")
-}
-
 // Document operation command types and Tauri commands
 use crate::ai::document_commands::{
     CommandParser, CommandResult, DocumentCommand, DocumentCommandProcessor,
@@ -1,20 +1,67 @@
 // Tauri commands for collaboration server management
 
+use crate::collaboration::auth::{CollaborationRole, IssuedToken, TOKEN_QUERY_PARAM};
+use crate::collaboration::discovery::{
+    is_loopback_host, local_hostname, Advertisement, DiscoveredPeer, LanDiscovery,
+};
+use crate::collaboration::history::{
+    system_author, DocumentHistory, EditSession, TextChange, SESSION_GAP_MINUTES,
+};
+use crate::collaboration::sync_agent::{SyncAgent, SyncAgentConfig, SyncStatus};
+use crate::collaboration::tls::TlsIdentity;
 use crate::collaboration::{CollaborationServer, ServerConfig};
+use crate::commands::document_comparison_commands::DocumentComparisonAppState;
+use crate::commands::document_indexing_commands::DocumentIndexerState;
+use crate::document::{
+    ComparisonOptions, ComparisonType, DocumentComparisonRequest, DocumentComparisonResult,
+    DocumentForComparison, ParsedDocumentContent,
+};
+use crate::filesystem::watcher::FileEvent;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::{error, info};
+use std::time::Duration;
+use tauri::Emitter;
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tracing::{error, info, warn};
 
 /// State for collaboration server
 pub type CollaborationServerState = Arc<RwLock<Option<Arc<CollaborationServer>>>>;
 
+/// State for LAN discovery of collaboration hosts, started on first use
+pub type LanDiscoveryState = Arc<Mutex<Option<LanDiscovery>>>;
+
+/// Sync agents keeping local replicas of remote rooms, by document ID
+pub type SyncAgentState = Arc<Mutex<HashMap<String, SyncAgent>>>;
+
 /// Request to start collaboration server
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StartServerRequest {
     pub port: Option<u16>,
     pub host: Option<String>,
     pub max_connections_per_room: Option<usize>,
+    /// Maximum connections across all rooms
+    pub max_connections: Option<usize>,
+    /// Workspace whose `.fiovana/` directory persists rooms across restarts
+    pub workspace_path: Option<String>,
+    /// Serve wss:// with a self-signed certificate pinned on first run
+    pub tls: Option<bool>,
+    /// Announce the server to teammates on the local network
+    pub advertise: Option<bool>,
+}
+
+/// Request to issue an access token for a collaborator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTokenRequest {
+    pub user_id: String,
+    pub user_name: String,
+    /// Role for documents without an explicit permission; no access when unset
+    pub role: Option<CollaborationRole>,
+    /// Explicit per-document roles
+    pub documents: Option<HashMap<String, CollaborationRole>>,
+    /// Token lifetime; tokens never expire when unset
+    pub ttl_seconds: Option<i64>,
 }
 
 /// Response with server status
@@ -25,6 +72,38 @@ pub struct ServerStatus {
     pub host: String,
     pub active_rooms: usize,
     pub total_users: usize,
+    /// Whether clients connect over wss://
+    pub secure: bool,
+    /// Certificate fingerprint clients should pin when `secure`
+    pub certificate_fingerprint: Option<String>,
+}
+
+impl ServerStatus {
+    fn of(server: &CollaborationServer) -> Self {
+        let config = server.config();
+        let (active_rooms, total_users) = server.stats();
+        Self {
+            running: true,
+            port: config.port,
+            host: config.host.clone(),
+            active_rooms,
+            total_users,
+            secure: server.tls_fingerprint().is_some(),
+            certificate_fingerprint: server.tls_fingerprint().map(str::to_string),
+        }
+    }
+
+    fn stopped() -> Self {
+        Self {
+            running: false,
+            port: 0,
+            host: "".to_string(),
+            active_rooms: 0,
+            total_users: 0,
+            secure: false,
+            certificate_fingerprint: None,
+        }
+    }
 }
 
 /// Start the collaboration server
@@ -32,6 +111,7 @@ pub struct ServerStatus {
 pub async fn start_collaboration_server(
     request: StartServerRequest,
     state: tauri::State<'_, CollaborationServerState>,
+    discovery_state: tauri::State<'_, LanDiscoveryState>,
 ) -> Result<ServerStatus, String> {
     info!("Starting collaboration server with config: {:?}", request);
 
@@ -53,9 +133,31 @@ pub async fn start_collaboration_server(
     if let Some(max_connections) = request.max_connections_per_room {
         config.max_connections_per_room = max_connections;
     }
+    if let Some(max_connections) = request.max_connections {
+        config.max_connections = max_connections;
+    }
+    config.workspace_path = request.workspace_path.map(PathBuf::from);
 
     // Create and start server
-    let server = Arc::new(CollaborationServer::new(config.clone()));
+    let mut server = CollaborationServer::new(config.clone());
+    if request.tls.unwrap_or(false) {
+        let dir = TlsIdentity::default_dir()
+            .ok_or_else(|| "Could not determine the config directory".to_string())?;
+        let mut hostnames = vec![format!("{}.local", local_hostname())];
+        if !matches!(
+            config.host.as_str(),
+            "0.0.0.0" | "::" | "127.0.0.1" | "localhost"
+        ) {
+            hostnames.push(config.host.clone());
+        }
+        let identity =
+            tokio::task::spawn_blocking(move || TlsIdentity::load_or_generate(&dir, &hostnames))
+                .await
+                .map_err(|e| format!("Failed to load collaboration certificate: {}", e))?
+                .map_err(|e| format!("Failed to load collaboration certificate: {}", e))?;
+        server = server.with_tls(identity);
+    }
+    let server = Arc::new(server);
     let server_clone: Arc<CollaborationServer> = Arc::clone(&server);
 
     // Start server in background
@@ -65,33 +167,59 @@ pub async fn start_collaboration_server(
         }
     });
 
+    if request.advertise.unwrap_or(false) && is_loopback_host(&config.host) {
+        warn!(
+            "Not advertising collaboration server bound to loopback address {}",
+            config.host
+        );
+    } else if request.advertise.unwrap_or(false) {
+        let advertisement = Advertisement {
+            port: config.port,
+            workspace_name: config
+                .workspace_path
+                .as_ref()
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().to_string()),
+            certificate_fingerprint: server.tls_fingerprint().map(str::to_string),
+        };
+        let mut discovery = discovery_state.lock().await;
+        let discovery = ensure_discovery(&mut discovery)?;
+        if let Err(e) = discovery.advertise(&advertisement) {
+            error!("Collaboration server will not be discoverable: {}", e);
+        }
+    }
+
+    let status = ServerStatus::of(&server);
+
     // Store server reference
     *server_lock = Some(server);
 
-    Ok(ServerStatus {
-        running: true,
-        port: config.port,
-        host: config.host,
-        active_rooms: 0,
-        total_users: 0,
-    })
+    Ok(status)
 }
 
 /// Stop the collaboration server
 #[tauri::command]
 pub async fn stop_collaboration_server(
     state: tauri::State<'_, CollaborationServerState>,
+    discovery_state: tauri::State<'_, LanDiscoveryState>,
 ) -> Result<String, String> {
     info!("Stopping collaboration server");
 
     let mut server_lock = state.write().await;
 
-    if server_lock.is_none() {
+    let Some(server) = server_lock.take() else {
         return Err("Collaboration server is not running".to_string());
+    };
+
+    if let Some(discovery) = discovery_state.lock().await.as_mut() {
+        discovery.withdraw();
     }
 
-    // Drop the server (this will close all connections)
-    *server_lock = None;
+    // Persist room state and write bound rooms back before dropping the
+    // server (this will close all connections)
+    tokio::task::spawn_blocking(move || server.flush())
+        .await
+        .map_err(|e| format!("Failed to flush collaboration rooms: {}", e))?;
 
     Ok("Collaboration server stopped successfully".to_string())
 }
@@ -103,24 +231,390 @@ pub async fn get_collaboration_server_status(
 ) -> Result<ServerStatus, String> {
     let server_lock = state.read().await;
 
-    if let Some(_server) = server_lock.as_ref() {
-        // In a full implementation, we would query the server for stats
-        Ok(ServerStatus {
-            running: true,
-            port: 1234, // Default port
-            host: "127.0.0.1".to_string(),
-            active_rooms: 0, // Would query from room manager
-            total_users: 0,  // Would query from room manager
-        })
-    } else {
-        Ok(ServerStatus {
-            running: false,
-            port: 0,
-            host: "".to_string(),
-            active_rooms: 0,
-            total_users: 0,
-        })
+    Ok(server_lock
+        .as_ref()
+        .map(|server| ServerStatus::of(server))
+        .unwrap_or_else(ServerStatus::stopped))
+}
+
+/// Start browsing the LAN unless discovery is already running
+fn ensure_discovery(discovery: &mut Option<LanDiscovery>) -> Result<&mut LanDiscovery, String> {
+    if discovery.is_none() {
+        *discovery = Some(
+            LanDiscovery::start().map_err(|e| format!("Failed to start LAN discovery: {}", e))?,
+        );
+    }
+    Ok(discovery.as_mut().expect("discovery was just started"))
+}
+
+/// Collaboration hosts advertising a shared workspace on the local network.
+/// Browsing starts on the first call, so peers appear over the following seconds.
+#[tauri::command]
+pub async fn get_discovered_peers(
+    discovery_state: tauri::State<'_, LanDiscoveryState>,
+) -> Result<Vec<DiscoveredPeer>, String> {
+    let mut discovery = discovery_state.lock().await;
+    Ok(ensure_discovery(&mut discovery)?.peers())
+}
+
+/// Stop browsing the LAN and withdraw this host's advertisement
+#[tauri::command]
+pub async fn stop_lan_discovery(
+    discovery_state: tauri::State<'_, LanDiscoveryState>,
+) -> Result<(), String> {
+    discovery_state.lock().await.take();
+    Ok(())
+}
+
+/// The running collaboration server, or an error if it is stopped
+async fn running_server(
+    state: &CollaborationServerState,
+) -> Result<Arc<CollaborationServer>, String> {
+    state
+        .read()
+        .await
+        .clone()
+        .ok_or_else(|| "Collaboration server is not running".to_string())
+}
+
+/// Issue an access token clients present during the WebSocket handshake
+#[tauri::command]
+pub async fn create_collaboration_token(
+    request: CreateTokenRequest,
+    state: tauri::State<'_, CollaborationServerState>,
+) -> Result<IssuedToken, String> {
+    let server = running_server(&state).await?;
+    let access = server.access_control();
+
+    let issued = access.issue_token(
+        &request.user_id,
+        &request.user_name,
+        request.role,
+        request.ttl_seconds.map(chrono::Duration::seconds),
+    );
+    for (document_id, role) in request.documents.unwrap_or_default() {
+        access
+            .set_document_role(&request.user_id, &document_id, Some(role))
+            .map_err(|e| e.to_string())?;
+    }
+
+    info!("Issued collaboration token for {}", request.user_id);
+    Ok(issued)
+}
+
+/// Set or clear (with `role: null`) a collaborator's role on a document
+#[tauri::command]
+pub async fn set_collaboration_permission(
+    user_id: String,
+    document_id: String,
+    role: Option<CollaborationRole>,
+    state: tauri::State<'_, CollaborationServerState>,
+) -> Result<(), String> {
+    let server = running_server(&state).await?;
+    server
+        .access_control()
+        .set_document_role(&user_id, &document_id, role)
+        .map_err(|e| e.to_string())
+}
+
+/// Revoke an access token; connections already open are unaffected
+#[tauri::command]
+pub async fn revoke_collaboration_token(
+    token: String,
+    state: tauri::State<'_, CollaborationServerState>,
+) -> Result<bool, String> {
+    let server = running_server(&state).await?;
+    Ok(server.access_control().revoke_token(&token))
+}
+
+/// Bind a collaboration room to its document file so edits are written back
+/// to disk and external changes are merged into the room
+#[tauri::command]
+pub async fn bind_collaboration_document(
+    document_id: String,
+    state: tauri::State<'_, CollaborationServerState>,
+    indexer_state: tauri::State<'_, DocumentIndexerState>,
+) -> Result<(), String> {
+    let server = running_server(&state).await?;
+
+    let file_path = {
+        let indexer_guard = indexer_state.lock().await;
+        let indexer = indexer_guard
+            .as_ref()
+            .ok_or_else(|| "Document indexer not initialized".to_string())?;
+        let document = indexer
+            .get_document(&document_id)
+            .ok_or_else(|| format!("Document not found: {}", document_id))?;
+        PathBuf::from(&document.path)
+    };
+
+    let (room_id, path) = (document_id.clone(), file_path.clone());
+    tokio::task::spawn_blocking(move || server.bind_document(&room_id, &path))
+        .await
+        .map_err(|e| format!("Failed to bind document {}: {}", document_id, e))?
+        .map_err(|e| format!("Failed to bind document {}: {}", document_id, e))?;
+
+    info!(
+        "Bound collaboration room {} to {}",
+        document_id,
+        file_path.display()
+    );
+    Ok(())
+}
+
+/// Who changed a collaborative document, grouped into editing sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollaborationTimeline {
+    pub document_id: String,
+    pub latest_seq: u64,
+    pub sessions: Vec<EditSession>,
+}
+
+/// Run `read` against a room's history off the async runtime, since
+/// replaying a long history is CPU bound
+async fn replay_history<R: Send + 'static>(
+    state: &CollaborationServerState,
+    document_id: &str,
+    read: impl FnOnce(&DocumentHistory) -> anyhow::Result<R> + Send + 'static,
+) -> Result<R, String> {
+    let server = running_server(state).await?;
+    let document_id = document_id.to_string();
+    tokio::task::spawn_blocking(move || server.with_history(&document_id, read)?)
+        .await
+        .map_err(|e| format!("History task failed: {}", e))?
+        .map_err(|e| e.to_string())
+}
+
+/// Timeline of a collaborative document's editing sessions
+#[tauri::command]
+pub async fn get_collaboration_history(
+    document_id: String,
+    state: tauri::State<'_, CollaborationServerState>,
+) -> Result<CollaborationTimeline, String> {
+    let (latest_seq, sessions) = replay_history(&state, &document_id, |history| {
+        Ok((
+            history.latest_seq(),
+            history.sessions(chrono::Duration::minutes(SESSION_GAP_MINUTES)),
+        ))
+    })
+    .await?;
+
+    Ok(CollaborationTimeline {
+        document_id,
+        latest_seq,
+        sessions,
+    })
+}
+
+/// Attributed text changes between two points of a document's history, up to
+/// the latest change when `to_seq` is omitted
+#[tauri::command]
+pub async fn get_collaboration_changes(
+    document_id: String,
+    from_seq: u64,
+    to_seq: Option<u64>,
+    state: tauri::State<'_, CollaborationServerState>,
+) -> Result<Vec<TextChange>, String> {
+    replay_history(&state, &document_id, move |history| {
+        history.changes(from_seq, to_seq.unwrap_or_else(|| history.latest_seq()))
+    })
+    .await
+}
+
+/// Diff a document between two points of its history
+#[tauri::command]
+pub async fn diff_collaboration_history(
+    document_id: String,
+    from_seq: u64,
+    to_seq: Option<u64>,
+    state: tauri::State<'_, CollaborationServerState>,
+    comparison_state: tauri::State<'_, DocumentComparisonAppState>,
+) -> Result<DocumentComparisonResult, String> {
+    let (to_seq, before, after) = replay_history(&state, &document_id, move |history| {
+        let to_seq = to_seq.unwrap_or_else(|| history.latest_seq());
+        Ok((to_seq, history.text_at(from_seq)?, history.text_at(to_seq)?))
+    })
+    .await?;
+
+    let version = |seq: u64, content: String| DocumentForComparison {
+        file_path: format!("{}@{}", document_id, seq),
+        content: Some(ParsedDocumentContent::Text { content }),
+        metadata: HashMap::from([("seq".to_string(), seq.to_string())]),
+    };
+    let request = DocumentComparisonRequest {
+        document_a: version(from_seq, before),
+        document_b: version(to_seq, after),
+        comparison_type: ComparisonType::TextDiff,
+        options: ComparisonOptions::default(),
+    };
+
+    let comparator = comparison_state.comparator.lock().await;
+    comparator
+        .compare_documents(request)
+        .await
+        .map_err(|e| format!("Failed to diff {}: {}", document_id, e))
+}
+
+/// Restore a document to its state right after change `seq`; connected
+/// clients receive the restore as a regular update
+#[tauri::command]
+pub async fn restore_collaboration_document(
+    document_id: String,
+    seq: u64,
+    state: tauri::State<'_, CollaborationServerState>,
+) -> Result<bool, String> {
+    let server = running_server(&state).await?;
+    tokio::task::spawn_blocking(move || {
+        server.restore_document(&document_id, seq, &system_author("host"))
+    })
+    .await
+    .map_err(|e| format!("Restore task failed: {}", e))?
+    .map_err(|e| format!("Failed to restore document: {}", e))
+}
+
+/// Request to keep a local replica of a room on another host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartSyncRequest {
+    /// ws:// or wss:// address of the host
+    pub server_url: String,
+    pub token: String,
+    pub document_id: String,
+    /// Fingerprint advertised by the host, required for wss://
+    pub certificate_fingerprint: Option<String>,
+    pub reconnect_delay_ms: Option<u64>,
+}
+
+/// Start syncing a room from another host. Status changes are emitted as
+/// `collaboration-sync` events; edits made while offline are kept, across
+/// restarts too, and merged on reconnect.
+#[tauri::command]
+pub async fn start_collaboration_sync(
+    request: StartSyncRequest,
+    app_handle: tauri::AppHandle,
+    sync_state: tauri::State<'_, SyncAgentState>,
+) -> Result<SyncStatus, String> {
+    let mut agents = sync_state.lock().await;
+    if let Some(agent) = agents.get(&request.document_id) {
+        return Ok(agent.status());
     }
+
+    let agent = SyncAgent::start(SyncAgentConfig {
+        server_url: request.server_url,
+        token: request.token,
+        document_id: request.document_id.clone(),
+        certificate_fingerprint: request.certificate_fingerprint,
+        reconnect_delay: Duration::from_millis(request.reconnect_delay_ms.unwrap_or(2000)),
+        queue_dir: dirs::data_local_dir().map(|dir| dir.join("fiovana").join("sync")),
+    });
+
+    let mut status = agent.subscribe();
+    tokio::spawn(async move {
+        while status.changed().await.is_ok() {
+            let current = status.borrow_and_update().clone();
+            if let Err(e) = app_handle.emit("collaboration-sync", &current) {
+                error!("Failed to emit collaboration sync status: {}", e);
+            }
+        }
+    });
+
+    let current = agent.status();
+    agents.insert(request.document_id, agent);
+    Ok(current)
+}
+
+/// Status of a synced room, including edits still waiting to be sent
+#[tauri::command]
+pub async fn get_collaboration_sync_status(
+    document_id: String,
+    sync_state: tauri::State<'_, SyncAgentState>,
+) -> Result<SyncStatus, String> {
+    let agents = sync_state.lock().await;
+    Ok(sync_agent(&agents, &document_id)?.status())
+}
+
+/// Text of the local replica of a synced room
+#[tauri::command]
+pub async fn get_synced_document_text(
+    document_id: String,
+    sync_state: tauri::State<'_, SyncAgentState>,
+) -> Result<String, String> {
+    let agents = sync_state.lock().await;
+    Ok(sync_agent(&agents, &document_id)?.text())
+}
+
+/// Edit the local replica of a synced room, online or not
+#[tauri::command]
+pub async fn update_synced_document(
+    document_id: String,
+    content: String,
+    sync_state: tauri::State<'_, SyncAgentState>,
+) -> Result<SyncStatus, String> {
+    let agents = sync_state.lock().await;
+    let agent = sync_agent(&agents, &document_id)?;
+    agent.replace_text(&content);
+    Ok(agent.status())
+}
+
+/// Work offline on a synced room, or go back online and merge
+#[tauri::command]
+pub async fn set_collaboration_sync_online(
+    document_id: String,
+    online: bool,
+    sync_state: tauri::State<'_, SyncAgentState>,
+) -> Result<SyncStatus, String> {
+    let agents = sync_state.lock().await;
+    let agent = sync_agent(&agents, &document_id)?;
+    agent.set_online(online);
+    Ok(agent.status())
+}
+
+/// Stop syncing a room; edits that were never sent are discarded
+#[tauri::command]
+pub async fn stop_collaboration_sync(
+    document_id: String,
+    sync_state: tauri::State<'_, SyncAgentState>,
+) -> Result<bool, String> {
+    let mut agents = sync_state.lock().await;
+    let agent = agents.remove(&document_id);
+    if let Some(agent) = &agent {
+        agent.discard_queue();
+    }
+    Ok(agent.is_some())
+}
+
+fn sync_agent<'a>(
+    agents: &'a HashMap<String, SyncAgent>,
+    document_id: &str,
+) -> Result<&'a SyncAgent, String> {
+    agents
+        .get(document_id)
+        .ok_or_else(|| format!("Document {} is not being synced", document_id))
+}
+
+/// Pass file watcher events through the running collaboration server, so
+/// external edits to bound documents reach their rooms, and hand them on
+pub fn forward_file_events_to_collaboration(
+    mut receiver: mpsc::UnboundedReceiver<FileEvent>,
+    state: CollaborationServerState,
+) -> mpsc::UnboundedReceiver<FileEvent> {
+    let (sender, forwarded) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(event) = receiver.recv().await {
+            let server = state.read().await.clone();
+            if let Some(server) = server {
+                let event = event.clone();
+                if let Err(e) =
+                    tokio::task::spawn_blocking(move || server.handle_file_event(&event)).await
+                {
+                    error!("Failed to merge file event into collaboration room: {}", e);
+                }
+            }
+            if sender.send(event).is_err() {
+                break;
+            }
+        }
+    });
+    forwarded
 }
 
 /// Get available collaboration server info (for frontend configuration)
@@ -130,6 +624,10 @@ pub async fn get_collaboration_server_info() -> Result<serde_json::Value, String
         "default_port": 1234,
         "default_host": "127.0.0.1",
         "websocket_url": "ws://127.0.0.1:1234",
+        "supports_tls": true,
+        "discovery_service_type": crate::collaboration::discovery::SERVICE_TYPE,
+        "requires_token": true,
+        "token_query_param": TOKEN_QUERY_PARAM,
         "supports_webrtc_fallback": true,
         "max_document_size_mb": 10,
         "max_concurrent_users": 50,
@@ -11,6 +11,10 @@ use tokio::sync::Mutex;
 use crate::ai::conversation_context::{
     ConversationContextManager, EnrichedConversationContext, TaskStatus,
 };
+use crate::ai::conversation_store::{
+    ConversationHistoryStore, HistoryRetentionPolicy, HistorySearchHit, HistorySearchQuery,
+    RetentionReport,
+};
 
 /// State management for conversation context
 pub type ConversationContextState = Arc<Mutex<ConversationContextManager>>;
@@ -127,8 +131,16 @@ pub async fn initialize_conversation_context(
     let mut context_manager = state.lock().await;
 
     // Check if already initialized (manager is created but we can verify it's working)
+    let history_store = context_manager.detach_history_store();
     *context_manager = ConversationContextManager::new();
 
+    // Keep persisting to the same workspace and resume its sessions
+    if let Some(store) = history_store {
+        context_manager
+            .attach_history_store(store)
+            .map_err(|e| e.to_string())?;
+    }
+
     Ok("Conversation context manager initialized successfully".to_string())
 }
 
@@ -434,15 +446,70 @@ pub async fn import_conversation_session(
         .map_err(|e| e.to_string())
 }
 
+/// Open the persistent conversation history of a workspace and resume its sessions
+#[tauri::command]
+pub async fn open_conversation_history(
+    state: State<'_, ConversationContextState>,
+    workspace_path: String,
+    retention: Option<HistoryRetentionPolicy>,
+) -> Result<usize, String> {
+    let mut store =
+        ConversationHistoryStore::for_workspace(&workspace_path).map_err(|e| e.to_string())?;
+    if let Some(retention) = retention {
+        store = store.with_retention(retention);
+    }
+
+    let mut context_manager = state.lock().await;
+    context_manager
+        .attach_history_store(store)
+        .map_err(|e| e.to_string())
+}
+
+/// Search persisted conversation history by text and referenced document
+#[tauri::command]
+pub async fn search_conversation_history(
+    state: State<'_, ConversationContextState>,
+    query: HistorySearchQuery,
+) -> Result<Vec<HistorySearchHit>, String> {
+    let context_manager = state.lock().await;
+    context_manager
+        .search_history(&query)
+        .map_err(|e| e.to_string())
+}
+
+/// Delete conversation history for one session, or all history when no session is given
+#[tauri::command]
+pub async fn delete_conversation_history(
+    state: State<'_, ConversationContextState>,
+    session_id: Option<String>,
+) -> Result<usize, String> {
+    let mut context_manager = state.lock().await;
+    context_manager
+        .delete_history(session_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Apply the retention policy to persisted conversation history
+#[tauri::command]
+pub async fn apply_conversation_history_retention(
+    state: State<'_, ConversationContextState>,
+) -> Result<RetentionReport, String> {
+    let mut context_manager = state.lock().await;
+    context_manager
+        .apply_history_retention()
+        .map_err(|e| e.to_string())
+}
+
 /// Get conversation context manager status
 #[tauri::command]
 pub async fn get_conversation_context_status(
     state: State<'_, ConversationContextState>,
 ) -> Result<serde_json::Value, String> {
-    let _context_manager = state.lock().await;
+    let context_manager = state.lock().await;
 
     Ok(serde_json::json!({
         "initialized": true,
+        "history_persisted": context_manager.history_store().is_some(),
         "capabilities": [
             "conversation_history_tracking",
             "reference_resolution",
@@ -10,6 +10,8 @@ use tokio::sync::Mutex;
 
 use crate::ai::actions::ActionResult;
 use crate::ai::intent::{Intent, IntentClassifier, IntentConfidence};
+use crate::ai::AIOrchestrator;
+use crate::commands::ai_commands::AIState;
 use crate::vector::EmbeddingEngine;
 
 // State management for conversational intelligence
@@ -106,9 +108,11 @@ impl PlaceholderActionExecutor {
 }
 
 impl ConversationalIntelligenceSystem {
+    /// Act on a request whose input has already been classified
     pub async fn process_conversation(
         &mut self,
         request: ConversationRequest,
+        intent_result: IntentConfidence,
     ) -> Result<ConversationResponse> {
         // Generate or use provided session ID
         let session_id = request
@@ -118,9 +122,6 @@ impl ConversationalIntelligenceSystem {
         // Ensure session exists
         self.session_manager.ensure_session(&session_id);
 
-        // Classify intent
-        let intent_result = self.intent_classifier.classify(&request.user_input).await?;
-
         // Extract parameters for action execution
         let parameters = self
             .extract_parameters(&request.user_input, &intent_result.intent)
@@ -211,6 +212,21 @@ impl ConversationalIntelligenceSystem {
         })
     }
 
+    /// Classify with the AI system's configured stage when it is running
+    ///
+    /// The orchestrator falls back to the pattern classifier itself when the
+    /// LLM or embedding stage fails, times out or is not confident enough.
+    pub async fn classify(
+        &self,
+        user_input: &str,
+        ai: Option<&AIOrchestrator>,
+    ) -> Result<IntentConfidence> {
+        match ai {
+            Some(orchestrator) => orchestrator.classify_intent(user_input).await,
+            None => self.intent_classifier.classify(user_input).await,
+        }
+    }
+
     async fn extract_parameters(
         &self,
         user_input: &str,
@@ -554,13 +570,21 @@ pub async fn initialize_conversational_intelligence(
 #[tauri::command]
 pub async fn process_conversation(
     state: State<'_, ConversationalIntelligenceState>,
+    ai_state: State<'_, AIState>,
     request: ConversationRequest,
 ) -> Result<ConversationResponse, String> {
     let mut conversational_state = state.lock().await;
 
     if let Some(ref mut system) = *conversational_state {
+        let intent_result = {
+            let ai = ai_state.lock().await;
+            system
+                .classify(&request.user_input, ai.as_ref())
+                .await
+                .map_err(|e| e.to_string())?
+        };
         system
-            .process_conversation(request)
+            .process_conversation(request, intent_result)
             .await
             .map_err(|e| e.to_string())
     } else {
@@ -571,14 +595,15 @@ pub async fn process_conversation(
 #[tauri::command]
 pub async fn classify_user_intent(
     state: State<'_, ConversationalIntelligenceState>,
+    ai_state: State<'_, AIState>,
     user_input: String,
 ) -> Result<IntentConfidence, String> {
     let conversational_state = state.lock().await;
 
     if let Some(ref system) = *conversational_state {
+        let ai = ai_state.lock().await;
         system
-            .intent_classifier
-            .classify(&user_input)
+            .classify(&user_input, ai.as_ref())
             .await
             .map_err(|e| e.to_string())
     } else {
@@ -3,9 +3,11 @@
 
 #![allow(dead_code)]
 
+use crate::commands::workspace_backup_commands::WorkspaceBackupState;
 use crate::document::deduplication::{
     DeduplicationManager, DeduplicationResult, GarbageCollectionResult, StorageStats,
 };
+use crate::workspace::backup::RiskyOperation;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
@@ -75,14 +77,29 @@ pub async fn deduplicate_file(
         .map_err(|e| e.to_string())
 }
 
-/// Run garbage collection for unreferenced files
+/// Run garbage collection for unreferenced files, after snapshotting the
+/// workspace so deleted files can be rolled back
 #[tauri::command]
 pub async fn run_garbage_collection(
     workspace_path: String,
     state: State<'_, DeduplicationState>,
+    backups: State<'_, WorkspaceBackupState>,
 ) -> Result<GarbageCollectionResult, String> {
     let manager = state.get_manager(&workspace_path)?;
 
+    backups
+        .snapshot_before(
+            &PathBuf::from(&workspace_path),
+            RiskyOperation::GarbageCollection,
+        )
+        .await
+        .map_err(|e| {
+            format!(
+                "Failed to snapshot workspace before garbage collection: {}",
+                e
+            )
+        })?;
+
     manager
         .run_garbage_collection()
         .await
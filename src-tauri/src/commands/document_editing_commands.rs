@@ -136,57 +136,9 @@ pub async fn create_document_version(
         .ok_or_else(|| format!("Document not found: {}", document_id))?;
 
     let file_path = PathBuf::from(&document.path);
+    drop(indexer_guard);
 
-    // Create versions directory
-    let versions_dir = get_versions_directory(&file_path)?;
-    fs::create_dir_all(&versions_dir)
-        .map_err(|e| format!("Failed to create versions directory: {}", e))?;
-
-    // Generate version ID (timestamp-based)
-    let version_id = format!(
-        "v_{}_{}",
-        chrono::Utc::now().timestamp(),
-        &uuid::Uuid::new_v4().to_string()[..8]
-    );
-
-    // Calculate content hash
-    let hash = calculate_hash(&content);
-
-    // Save version file
-    let version_path = versions_dir.join(format!("{}.md", version_id));
-    fs::write(&version_path, &content)
-        .map_err(|e| format!("Failed to write version file: {}", e))?;
-
-    // Get version metadata
-    let metadata = fs::metadata(&version_path)
-        .map_err(|e| format!("Failed to get version metadata: {}", e))?;
-
-    let created_at = metadata
-        .created()
-        .or_else(|_| metadata.modified())
-        .map(|time| {
-            let datetime: chrono::DateTime<chrono::Utc> = time.into();
-            datetime.to_rfc3339()
-        })
-        .unwrap_or_else(|_| chrono::Utc::now().to_rfc3339());
-
-    tracing::info!(
-        "Version created: {} for document: {}",
-        version_id,
-        document_id
-    );
-
-    Ok(VersionInfo {
-        version_id: version_id.clone(),
-        document_id,
-        created_at,
-        size: metadata.len(),
-        hash,
-        description: format!(
-            "Version snapshot created at {}",
-            chrono::Utc::now().to_rfc3339()
-        ),
-    })
+    write_document_version(&document_id, &file_path, &content)
 }
 
 /// Get all versions of a document
@@ -332,6 +284,64 @@ pub async fn restore_document_version(
 
 // Helper functions
 
+/// Write a version snapshot of `content` next to the document at `file_path`
+pub fn write_document_version(
+    document_id: &str,
+    file_path: &Path,
+    content: &str,
+) -> Result<VersionInfo, String> {
+    // Create versions directory
+    let versions_dir = get_versions_directory(file_path)?;
+    fs::create_dir_all(&versions_dir)
+        .map_err(|e| format!("Failed to create versions directory: {}", e))?;
+
+    // Generate version ID (timestamp-based)
+    let version_id = format!(
+        "v_{}_{}",
+        chrono::Utc::now().timestamp(),
+        &uuid::Uuid::new_v4().to_string()[..8]
+    );
+
+    // Calculate content hash
+    let hash = calculate_hash(content);
+
+    // Save version file
+    let version_path = versions_dir.join(format!("{}.md", version_id));
+    fs::write(&version_path, content)
+        .map_err(|e| format!("Failed to write version file: {}", e))?;
+
+    // Get version metadata
+    let metadata = fs::metadata(&version_path)
+        .map_err(|e| format!("Failed to get version metadata: {}", e))?;
+
+    let created_at = metadata
+        .created()
+        .or_else(|_| metadata.modified())
+        .map(|time| {
+            let datetime: chrono::DateTime<chrono::Utc> = time.into();
+            datetime.to_rfc3339()
+        })
+        .unwrap_or_else(|_| chrono::Utc::now().to_rfc3339());
+
+    tracing::info!(
+        "Version created: {} for document: {}",
+        version_id,
+        document_id
+    );
+
+    Ok(VersionInfo {
+        version_id: version_id.clone(),
+        document_id: document_id.to_string(),
+        created_at,
+        size: metadata.len(),
+        hash,
+        description: format!(
+            "Version snapshot created at {}",
+            chrono::Utc::now().to_rfc3339()
+        ),
+    })
+}
+
 /// Create a backup of the file
 fn create_backup(file_path: &Path) -> Result<PathBuf, std::io::Error> {
     let backup_dir = file_path
@@ -367,7 +377,7 @@ fn get_versions_directory(file_path: &Path) -> Result<PathBuf, String> {
 }
 
 /// Calculate SHA256 hash of content
-fn calculate_hash(content: &str) -> String {
+pub fn calculate_hash(content: &str) -> String {
     use sha2::{Digest, Sha256};
     let mut hasher = Sha256::new();
     hasher.update(content.as_bytes());
@@ -1,14 +1,22 @@
 // src-tauri/src/commands/document_indexing_commands.rs
 // Commands for document indexing and search operations
 
-use crate::document::{DocumentIndexEntry, DocumentIndexer, SearchFilter, SearchResult};
+use crate::document::{
+    DocumentIndexEntry, DocumentIndexer, DocumentMetadataRecord, MetadataUpdate, SearchFilter,
+    SearchResult,
+};
+use crate::workspace::usage_log::{record_document_usage, UsageEventKind};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tauri::State;
 use tokio::sync::Mutex;
 
+/// Top search results recorded as document usage
+pub(crate) const SEARCH_USAGE_RESULTS: usize = 10;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexDocumentRequest {
     pub file_path: String,
@@ -134,6 +142,13 @@ pub async fn search_documents(
                     results.truncate(limit);
                 }
 
+                let shown: Vec<&PathBuf> = results
+                    .iter()
+                    .take(SEARCH_USAGE_RESULTS)
+                    .map(|result| &result.document.path)
+                    .collect();
+                record_document_usage(&shown, UsageEventKind::SearchResult);
+
                 let total_found = results.len();
                 Ok(DocumentSearchResponse {
                     success: true,
@@ -222,6 +237,7 @@ pub async fn get_document_details(
                 file_path,
                 doc.title
             );
+            record_document_usage(&[&doc.path], UsageEventKind::Opened);
             Ok(Some((*doc).clone()))
         } else {
             tracing::warn!("Document not found in index: {}", file_path);
@@ -275,6 +291,50 @@ pub async fn remove_document_from_indexer(
     }
 }
 
+/// Get the tags and custom metadata of a document
+#[tauri::command]
+pub async fn get_document_metadata(
+    indexer_state: State<'_, DocumentIndexerState>,
+    document_id: String,
+) -> Result<Option<DocumentMetadataRecord>, String> {
+    let state = indexer_state.lock().await;
+    if let Some(ref indexer) = *state {
+        Ok(indexer.get_metadata(&document_id).cloned())
+    } else {
+        Err("Document indexer not initialized".to_string())
+    }
+}
+
+/// Change the tags, custom fields, owners or review date of a document
+#[tauri::command]
+pub async fn update_document_metadata(
+    indexer_state: State<'_, DocumentIndexerState>,
+    document_id: String,
+    update: MetadataUpdate,
+) -> Result<DocumentMetadataRecord, String> {
+    let mut state = indexer_state.lock().await;
+    if let Some(ref mut indexer) = *state {
+        indexer
+            .update_metadata(&document_id, update)
+            .map_err(|e| format!("Failed to update document metadata: {}", e))
+    } else {
+        Err("Document indexer not initialized".to_string())
+    }
+}
+
+/// Get every tag in use with the number of documents carrying it
+#[tauri::command]
+pub async fn get_document_tags(
+    indexer_state: State<'_, DocumentIndexerState>,
+) -> Result<BTreeMap<String, usize>, String> {
+    let state = indexer_state.lock().await;
+    if let Some(ref indexer) = *state {
+        Ok(indexer.metadata_store().tag_counts())
+    } else {
+        Err("Document indexer not initialized".to_string())
+    }
+}
+
 /// Clear all documents from the index
 #[tauri::command]
 pub async fn clear_document_index(
@@ -0,0 +1,127 @@
+// src-tauri/src/commands/document_review_commands.rs
+// Commands for the controlled document review workflow
+
+use crate::commands::document_indexing_commands::DocumentIndexerState;
+use crate::document::review_workflow::{
+    ensure_publishable, is_publication, overdue_reviews, transition_status, DocumentStatus,
+    OverdueReview, ReviewReminders, StatusTransition,
+};
+use crate::document::DocumentMetadataRecord;
+use crate::filesystem::security::audit_logger::SecurityAuditor;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use tauri::State;
+
+/// How often overdue reviews are checked in the background
+pub const REVIEW_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentStatusChange {
+    pub record: DocumentMetadataRecord,
+    pub transition: StatusTransition,
+}
+
+/// Move a controlled document to a new review state
+///
+/// The change is always attributed to the signed-in user. A name supplied in
+/// `on_behalf_of` is only noted in the comment, never used as the actor.
+#[tauri::command]
+pub async fn transition_document_status(
+    indexer_state: State<'_, DocumentIndexerState>,
+    document_id: String,
+    status: DocumentStatus,
+    on_behalf_of: Option<String>,
+    comment: Option<String>,
+) -> Result<DocumentStatusChange, String> {
+    let actor = SecurityAuditor::get_current_user();
+    let comment = match on_behalf_of.filter(|name| !name.trim().is_empty()) {
+        Some(name) => Some(match comment {
+            Some(comment) => format!("On behalf of {}: {}", name.trim(), comment),
+            None => format!("On behalf of {}", name.trim()),
+        }),
+        None => comment,
+    };
+
+    let mut state = indexer_state.lock().await;
+    let indexer = state
+        .as_mut()
+        .ok_or_else(|| "Document indexer not initialized".to_string())?;
+
+    let (record, transition) =
+        transition_status(indexer, &document_id, status, &actor, comment.as_deref())
+            .map_err(|e| e.to_string())?;
+    tracing::info!(
+        "Document {} moved to {} by {}",
+        transition.path.display(),
+        status.as_str(),
+        actor
+    );
+
+    Ok(DocumentStatusChange { record, transition })
+}
+
+/// List controlled documents whose review date has passed
+#[tauri::command]
+pub async fn get_overdue_reviews(
+    indexer_state: State<'_, DocumentIndexerState>,
+) -> Result<Vec<OverdueReview>, String> {
+    let state = indexer_state.lock().await;
+    let indexer = state
+        .as_ref()
+        .ok_or_else(|| "Document indexer not initialized".to_string())?;
+    Ok(overdue_reviews(indexer.metadata_store(), Utc::now()))
+}
+
+/// Refuse to put `source` at `destination` when that publishes a document
+/// that is not approved
+pub(crate) async fn ensure_publication_allowed(
+    indexer_state: &DocumentIndexerState,
+    source: &Path,
+    destination: &Path,
+) -> Result<(), String> {
+    if !is_publication(destination) {
+        return Ok(());
+    }
+    let state = indexer_state.lock().await;
+    let indexer = state
+        .as_ref()
+        .ok_or_else(|| "Document indexer not initialized".to_string())?;
+    ensure_publishable(indexer, source).map_err(|e| e.to_string())
+}
+
+/// Raise a notification for each overdue review not yet raised today
+pub async fn notify_overdue_reviews(
+    indexer_state: &DocumentIndexerState,
+    reminders: &mut ReviewReminders,
+) -> usize {
+    let now = Utc::now();
+    let overdue = {
+        let state = indexer_state.lock().await;
+        match state.as_ref() {
+            Some(indexer) => overdue_reviews(indexer.metadata_store(), now),
+            None => return 0,
+        }
+    };
+
+    let due = reminders.due(overdue, now);
+    if let Some(emitter) = crate::notifications::get_global_emitter() {
+        for review in &due {
+            if let Err(e) = emitter.emit_review_overdue(
+                &review.document_id,
+                &review.path,
+                &review.owners,
+                review.review_date,
+                review.days_overdue,
+            ) {
+                tracing::warn!(
+                    "Failed to raise overdue review for {}: {}",
+                    review.path.display(),
+                    e
+                );
+            }
+        }
+    }
+    due.len()
+}
@@ -0,0 +1,164 @@
+// src-tauri/src/commands/federated_search_commands.rs
+// Commands for searching, relating and linking documents across workspaces
+
+use crate::commands::document_indexing_commands::SEARCH_USAGE_RESULTS;
+use crate::commands::relationship_commands::RelationshipState;
+use crate::document::{RelationshipAnalyzer, RelationshipConfig, SearchFilter};
+use crate::workspace::federation::{
+    workspace_link, CrossWorkspaceRelationship, FederatedSearchResult, Federation, ResolvedLink,
+    SkippedWorkspace, WorkspaceTag,
+};
+use crate::workspace::usage_log::{record_document_usage, UsageEventKind};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+const DEFAULT_FEDERATED_RESULTS: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederatedSearchRequest {
+    /// Workspace paths to search; every known workspace when empty
+    #[serde(default)]
+    pub workspaces: Vec<String>,
+    pub query: String,
+    pub filter: Option<SearchFilter>,
+    pub max_results: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederatedSearchResponse {
+    pub results: Vec<FederatedSearchResult>,
+    pub searched: Vec<WorkspaceTag>,
+    pub skipped: Vec<SkippedWorkspace>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossWorkspaceRelationshipsResponse {
+    pub relationships: Vec<CrossWorkspaceRelationship>,
+    pub searched: Vec<WorkspaceTag>,
+    pub skipped: Vec<SkippedWorkspace>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceLinkResolution {
+    pub link: String,
+    pub resolved: Option<ResolvedLink>,
+    pub error: Option<String>,
+}
+
+async fn open_federation(
+    state: &crate::AppState,
+    workspaces: &[String],
+) -> Result<Federation, String> {
+    let known = state
+        .workspace_manager
+        .list_workspaces()
+        .await
+        .map_err(|e| e.to_string())?;
+    let selected: Vec<PathBuf> = workspaces.iter().map(PathBuf::from).collect();
+    Ok(Federation::open(&known, &selected).await)
+}
+
+/// Search several workspaces at once, tagging each result with its workspace
+#[tauri::command]
+pub async fn federated_search(
+    state: State<'_, crate::AppState>,
+    request: FederatedSearchRequest,
+) -> Result<FederatedSearchResponse, String> {
+    let federation = open_federation(&state, &request.workspaces).await?;
+    let results = federation
+        .search(
+            &request.query,
+            request.filter,
+            request.max_results.unwrap_or(DEFAULT_FEDERATED_RESULTS),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let shown: Vec<&PathBuf> = results
+        .iter()
+        .take(SEARCH_USAGE_RESULTS)
+        .map(|found| &found.result.document.path)
+        .collect();
+    record_document_usage(&shown, UsageEventKind::SearchResult);
+
+    tracing::info!(
+        "Federated search for '{}' found {} results in {} workspaces",
+        request.query,
+        results.len(),
+        federation.members().len()
+    );
+
+    Ok(FederatedSearchResponse {
+        results,
+        searched: federation.members(),
+        skipped: federation.skipped().to_vec(),
+    })
+}
+
+/// Find related documents that live in different workspaces
+#[tauri::command]
+pub async fn discover_cross_workspace_relationships(
+    state: State<'_, crate::AppState>,
+    relationship_state: State<'_, RelationshipState>,
+    workspaces: Vec<String>,
+    max_results: Option<usize>,
+) -> Result<CrossWorkspaceRelationshipsResponse, String> {
+    let federation = open_federation(&state, &workspaces).await?;
+    let max_results = max_results.unwrap_or(DEFAULT_FEDERATED_RESULTS);
+
+    // Work on a copy so relationship commands are not held up by the comparison
+    let analyzer = relationship_state
+        .analyzer
+        .lock()
+        .await
+        .clone()
+        .unwrap_or_else(|| RelationshipAnalyzer::new(RelationshipConfig::default()));
+    let relationships = federation
+        .cross_workspace_relationships(&analyzer, max_results)
+        .await;
+
+    Ok(CrossWorkspaceRelationshipsResponse {
+        relationships,
+        searched: federation.members(),
+        skipped: federation.skipped().to_vec(),
+    })
+}
+
+/// Resolve `[[workspace:<name>/<path>]]` links against the known workspaces
+#[tauri::command]
+pub async fn resolve_workspace_links(
+    state: State<'_, crate::AppState>,
+    links: Vec<String>,
+) -> Result<Vec<WorkspaceLinkResolution>, String> {
+    let federation = open_federation(&state, &[]).await?;
+    Ok(links
+        .into_iter()
+        .map(|link| match federation.resolve_link(&link) {
+            Ok(resolved) => WorkspaceLinkResolution {
+                link,
+                resolved: Some(resolved),
+                error: None,
+            },
+            Err(e) => WorkspaceLinkResolution {
+                link,
+                resolved: None,
+                error: Some(e.to_string()),
+            },
+        })
+        .collect())
+}
+
+/// Create a link to a document that resolves from any other workspace
+#[tauri::command]
+pub async fn create_workspace_link(
+    state: State<'_, crate::AppState>,
+    workspace_path: String,
+    document_path: String,
+) -> Result<String, String> {
+    let workspace = state
+        .workspace_manager
+        .load_workspace(Path::new(&workspace_path))
+        .await
+        .map_err(|e| e.to_string())?;
+    workspace_link(&workspace, Path::new(&document_path)).map_err(|e| e.to_string())
+}
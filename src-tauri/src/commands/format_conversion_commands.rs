@@ -1,14 +1,17 @@
 // src-tauri/src/commands/format_conversion_commands.rs
 // Tauri commands for document format conversion functionality
 
+use crate::commands::document_indexing_commands::DocumentIndexerState;
+use crate::commands::document_review_commands::ensure_publication_allowed;
 use crate::document::{
     ConversionOptions, ConversionResult, DocumentFormat, FormatConverter, QualitySettings,
 };
+use crate::workspace::usage_log::{record_document_usage, UsageEventKind};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
-use tauri::command;
+use std::path::{Path, PathBuf};
+use tauri::{command, State};
 
 /// Request structure for document format conversion
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,7 +72,23 @@ pub struct ConversionCapabilityResponse {
 #[command]
 pub async fn convert_document_format(
     request: ConvertDocumentRequest,
+    indexer_state: State<'_, DocumentIndexerState>,
 ) -> Result<FormatConversionResponse, String> {
+    // A converted copy in the approved folder publishes the source document
+    if let Err(e) = ensure_publication_allowed(
+        &indexer_state,
+        Path::new(&request.input_path),
+        Path::new(&request.output_path),
+    )
+    .await
+    {
+        return Ok(FormatConversionResponse {
+            success: false,
+            result: None,
+            error: Some(e),
+        });
+    }
+
     async fn inner(request: ConvertDocumentRequest) -> Result<FormatConversionResponse> {
         // Create temporary directory for conversion operations
         let temp_dir = std::env::temp_dir().join("fiovana_conversions");
@@ -108,6 +127,7 @@ pub async fn convert_document_format(
                 conversion_options,
             )
             .await?;
+        record_document_usage(&[&request.input_path], UsageEventKind::Exported);
 
         Ok(FormatConversionResponse {
             success: true,
@@ -241,11 +261,12 @@ pub async fn check_conversion_capability(
 #[command]
 pub async fn batch_convert_documents(
     requests: Vec<ConvertDocumentRequest>,
+    indexer_state: State<'_, DocumentIndexerState>,
 ) -> Result<Vec<FormatConversionResponse>, String> {
     let mut results = Vec::new();
 
     for request in requests {
-        let result = convert_document_format(request).await?;
+        let result = convert_document_format(request, indexer_state.clone()).await?;
         results.push(result);
     }
 
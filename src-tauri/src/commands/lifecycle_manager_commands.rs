@@ -3,6 +3,7 @@
 
 use crate::document::indexer::DocumentIndexer;
 use crate::document::relationship_analyzer::{RelationshipAnalyzer, RelationshipConfig};
+use crate::workspace::usage_log::UsageEventKind;
 use crate::workspace::{
     ArchivalSuggestion, ConsolidationSuggestion, LifecycleAction, LifecycleAnalysis,
     LifecycleConfig, LifecycleManager, UpdateRecommendation, UsagePatternAnalysis,
@@ -442,6 +443,7 @@ pub async fn get_lifecycle_analysis_stats(
 pub async fn track_document_access(
     workspace_path: String,
     document_path: String,
+    kind: Option<UsageEventKind>,
 ) -> Result<JsonValue, String> {
     let workspace_pb = PathBuf::from(workspace_path);
     let mut lifecycle_manager = match create_lifecycle_manager(&workspace_pb).await {
@@ -450,7 +452,11 @@ pub async fn track_document_access(
     };
 
     match lifecycle_manager
-        .track_document_access(&document_path)
+        .track_document_access(
+            &workspace_pb,
+            &document_path,
+            kind.unwrap_or(UsageEventKind::Opened),
+        )
         .await
     {
         Ok(_) => Ok(serde_json::json!({
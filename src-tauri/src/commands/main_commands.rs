@@ -14,6 +14,7 @@ use crate::filesystem::security::backup_manager::BackupManager;
 use crate::filesystem::security::circuit_breaker::{CircuitBreakerConfig, CircuitBreakerManager};
 use crate::filesystem::security::emergency_procedures::EmergencyManager;
 use crate::filesystem::security::path_validator::PathValidator;
+use crate::filesystem::security::quarantine::{QuarantineManager, QuarantineReason};
 use crate::filesystem::security::safe_mode::SafeModeManager;
 use crate::filesystem::security::security_config::SecurityConfig;
 use once_cell::sync::Lazy;
@@ -107,6 +108,21 @@ fn backup_security_configurations() {
     }
 }
 
+/// Quarantine a file an import rejected as suspicious
+///
+/// Returns the error to report in place of the rejection when the file was moved.
+fn quarantine_rejected_import(
+    path: &Path,
+    reason: Option<QuarantineReason>,
+    operation: &str,
+) -> Option<SecurityError> {
+    let record =
+        QuarantineManager::global().quarantine_rejected_import(path, reason?, operation)?;
+    Some(SecurityError::FileQuarantined {
+        path: record.original_path.display().to_string(),
+    })
+}
+
 /// Get a reference to the global validation metrics
 #[allow(dead_code)]
 pub fn get_validation_metrics() -> &'static ValidationMetrics {
@@ -172,7 +188,7 @@ pub struct FileInfo {
 }
 
 /// Helper function to create a properly configured validator for production use
-fn create_default_validator() -> PathValidator {
+pub(crate) fn create_default_validator() -> PathValidator {
     let mut config = SecurityConfig::default();
     // Ensure common extensions are allowed, including .tmp for testing
     config.allowed_extensions.insert(".txt".to_string());
@@ -454,10 +470,13 @@ pub async fn import_file(path: PathBuf) -> Result<PathBuf, CommandError> {
     );
 
     let validator = create_default_validator();
+    let mut rejection = None;
     let result = breaker.call(|| {
-        validator
-            .validate_import_path(&path)
-            .map_err(|e| anyhow::anyhow!("{}", e))
+        validator.validate_import_path(&path).map_err(|e| {
+            let error = anyhow::anyhow!("{}", e);
+            rejection = Some(e);
+            error
+        })
     });
 
     // Convert result to expected type for audit logging
@@ -482,6 +501,18 @@ pub async fn import_file(path: PathBuf) -> Result<PathBuf, CommandError> {
         Err(_) => VALIDATION_METRICS.record_failure(duration),
     }
 
+    // Files rejected as suspicious are moved into quarantine
+    let quarantined = rejection.as_ref().and_then(|e| {
+        quarantine_rejected_import(
+            &path,
+            QuarantineReason::from_security_error(e),
+            "import_file",
+        )
+    });
+    if let Some(quarantined) = quarantined {
+        return Err(quarantined.into());
+    }
+
     let final_result = result.map_err(|e| CommandError::SecurityError {
         message: e.to_string(),
         code: "CIRCUIT_BREAKER".to_string(),
@@ -1260,13 +1291,28 @@ pub async fn process_dropped_files(
             // 1. Validate file path and security
             let validated_path = validator
                 .validate_import_path(Path::new(&file_path))
-                .map_err(|e| format!("Path validation failed: {}", e))?;
+                .map_err(|e| {
+                    let quarantined = quarantine_rejected_import(
+                        Path::new(&file_path),
+                        QuarantineReason::from_security_error(&e),
+                        "process_dropped_files",
+                    );
+                    format!("Path validation failed: {}", quarantined.unwrap_or(e))
+                })?;
 
             // 2. Comprehensive file validation
             let validation_result = FileProcessor::validate_file(&validated_path)
                 .map_err(|e| format!("File validation failed: {}", e))?;
 
             if !validation_result.is_valid {
+                validation_result.report_threat(&validated_path);
+                if let Some(quarantined) = quarantine_rejected_import(
+                    &validated_path,
+                    QuarantineReason::from_validation(&validation_result),
+                    "process_dropped_files",
+                ) {
+                    return Err(quarantined.to_string());
+                }
                 return Err(format!("File validation failed: {}", validation_result.message));
             }
 
@@ -1571,15 +1617,28 @@ pub async fn process_single_file_with_validation(
     // For now, replicate the logic
     let result = async {
         // Validate path security first
-        let validated_path = validator
-            .validate_import_path(path)
-            .map_err(|e| anyhow::anyhow!("Path validation failed: {}", e))?;
+        let validated_path = validator.validate_import_path(path).map_err(|e| {
+            let quarantined = quarantine_rejected_import(
+                path,
+                QuarantineReason::from_security_error(&e),
+                "process_single_file_with_validation",
+            );
+            anyhow::anyhow!("Path validation failed: {}", quarantined.unwrap_or(e))
+        })?;
 
         // Validate file integrity and structure
         let validation_result = FileProcessor::validate_file(&validated_path)
             .map_err(|e| anyhow::anyhow!("File validation failed: {}", e))?;
 
         if !validation_result.is_valid {
+            validation_result.report_threat(&validated_path);
+            if let Some(quarantined) = quarantine_rejected_import(
+                &validated_path,
+                QuarantineReason::from_validation(&validation_result),
+                "process_single_file_with_validation",
+            ) {
+                anyhow::bail!("{}", quarantined);
+            }
             anyhow::bail!("File validation failed: {}", validation_result.message);
         }
 
@@ -1661,6 +1720,11 @@ async fn start_file_watching_internal(
     workspace_path: String,
     app_handle: tauri::AppHandle<tauri::Wry>,
 ) -> Result<(), CommandError> {
+    use crate::commands::collaboration_commands::{
+        forward_file_events_to_collaboration, CollaborationServerState,
+    };
+    use tauri::Manager;
+
     let mut watcher_guard = FILE_WATCHER.lock().await;
     let mut receiver_guard = EVENT_RECEIVER.lock().await;
 
@@ -1672,8 +1736,17 @@ async fn start_file_watching_internal(
 
     // Create new watcher with default config
     let config = WatcherConfig::default();
+    let collaboration_state = app_handle
+        .try_state::<CollaborationServerState>()
+        .map(|state| state.inner().clone());
     let (mut watcher, receiver) = DocumentWatcher::new(config, app_handle);
 
+    // Let the collaboration server merge external edits into open rooms
+    let receiver = match collaboration_state {
+        Some(state) => forward_file_events_to_collaboration(receiver, state),
+        None => receiver,
+    };
+
     // Start the watcher
     watcher
         .start()
@@ -1896,6 +1969,7 @@ pub async fn validate_import_files(
                             status: crate::document::ValidationStatus::Invalid,
                             message: format!("Validation failed: {}", e),
                             corruption_check: None,
+                            content_scan: None,
                         });
                     }
                 }
@@ -1906,6 +1980,7 @@ pub async fn validate_import_files(
                     status: crate::document::ValidationStatus::Invalid,
                     message: format!("Path validation failed: {}", e),
                     corruption_check: None,
+                    content_scan: None,
                 });
             }
         }
@@ -1942,8 +2017,17 @@ async fn start_file_watching_test_internal<R: tauri::Runtime>(
 
     // Create new watcher with default config
     let config = WatcherConfig::default();
+    let collaboration_state = app_handle
+        .try_state::<crate::commands::collaboration_commands::CollaborationServerState>()
+        .map(|state| state.inner().clone());
     let (mut watcher, receiver) = DocumentWatcher::new(config, app_handle);
 
+    // Let the collaboration server merge external edits into open rooms
+    let receiver = match collaboration_state {
+        Some(state) => forward_file_events_to_collaboration(receiver, state),
+        None => receiver,
+    };
+
     // Start the watcher
     watcher
         .start()
@@ -14,8 +14,10 @@ pub mod document_comparison_commands;
 pub mod document_editing_commands;
 pub mod document_generation_commands;
 pub mod document_indexing_commands;
+pub mod document_review_commands;
 pub mod embedding_commands;
 pub mod embedding_settings_commands;
+pub mod federated_search_commands;
 pub mod format_conversion_commands;
 pub mod health_commands;
 pub mod knowledge_analyzer_commands;
@@ -23,6 +25,7 @@ pub mod lifecycle_manager_commands;
 pub mod main_commands;
 pub mod nl_operations_commands;
 pub mod progress_commands;
+pub mod quarantine_commands;
 pub mod relationship_commands;
 pub mod smart_organizer_commands;
 pub mod structure_commands;
@@ -33,6 +36,7 @@ pub mod template_commands;
 pub mod text_operation_commands;
 pub mod vector_commands;
 pub mod workspace_ai_commands;
+pub mod workspace_backup_commands;
 pub mod workspace_commands;
 pub mod workspace_intelligence_commands;
 pub mod workspace_performance_commands;
@@ -51,8 +55,10 @@ pub use document_comparison_commands::*;
 pub use document_editing_commands::*;
 pub use document_generation_commands::*;
 pub use document_indexing_commands::*;
+pub use document_review_commands::*;
 pub use embedding_commands::*;
 pub use embedding_settings_commands::*;
+pub use federated_search_commands::*;
 pub use format_conversion_commands::*;
 pub use health_commands::*;
 pub use knowledge_analyzer_commands::*;
@@ -60,6 +66,7 @@ pub use lifecycle_manager_commands::*;
 pub use main_commands::*;
 pub use nl_operations_commands::*;
 pub use progress_commands::*;
+pub use quarantine_commands::*;
 pub use relationship_commands::*;
 pub use smart_organizer_commands::*;
 pub use structure_commands::*;
@@ -70,5 +77,6 @@ pub use template_commands::*;
 pub use text_operation_commands::*;
 pub use vector_commands::*;
 pub use workspace_ai_commands::*;
+pub use workspace_backup_commands::*;
 pub use workspace_commands::*;
 pub use workspace_intelligence_commands::*;
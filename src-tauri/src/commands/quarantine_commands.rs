@@ -0,0 +1,61 @@
+// src-tauri/src/commands/quarantine_commands.rs
+// Commands for reviewing and resolving quarantined files
+
+use crate::commands::document_indexing_commands::DocumentIndexerState;
+use crate::commands::document_review_commands::ensure_publication_allowed;
+use crate::commands::main_commands::create_default_validator;
+use crate::filesystem::security::quarantine::{
+    QuarantineInspection, QuarantineManager, QuarantineRecord,
+};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// List quarantined files, newest first
+#[tauri::command]
+pub async fn list_quarantined_files() -> Result<Vec<QuarantineRecord>, String> {
+    QuarantineManager::global()
+        .list()
+        .map_err(|e| e.to_string())
+}
+
+/// Show a quarantined file's record and check its contents are unchanged
+#[tauri::command]
+pub async fn inspect_quarantined_file(id: String) -> Result<QuarantineInspection, String> {
+    QuarantineManager::global()
+        .inspect(&id)
+        .map_err(|e| e.to_string())
+}
+
+/// Release a quarantined file to its original location or a chosen destination
+#[tauri::command]
+pub async fn release_quarantined_file(
+    id: String,
+    destination: Option<String>,
+    indexer_state: State<'_, DocumentIndexerState>,
+) -> Result<PathBuf, String> {
+    let manager = QuarantineManager::global();
+    let original_path = manager
+        .record(&id)
+        .map_err(|e| e.to_string())?
+        .original_path;
+    // A chosen destination gets the same checks as an imported file's path
+    let destination = match destination {
+        Some(destination) => create_default_validator()
+            .validate_import_path(Path::new(&destination))
+            .map_err(|e| e.to_string())?,
+        None => original_path.clone(),
+    };
+    ensure_publication_allowed(&indexer_state, &original_path, &destination).await?;
+
+    manager
+        .release(&id, Some(&destination))
+        .map_err(|e| e.to_string())
+}
+
+/// Permanently delete a quarantined file
+#[tauri::command]
+pub async fn delete_quarantined_file(id: String) -> Result<QuarantineRecord, String> {
+    QuarantineManager::global()
+        .delete(&id)
+        .map_err(|e| e.to_string())
+}
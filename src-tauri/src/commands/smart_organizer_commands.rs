@@ -1,8 +1,16 @@
 // src-tauri/src/commands/smart_organizer_commands.rs
 //! Tauri commands for smart document organization functionality
 
+use crate::commands::document_indexing_commands::DocumentIndexerState;
+use crate::commands::vector_commands::VectorState;
+use crate::commands::workspace_backup_commands::WorkspaceBackupState;
 use crate::document::indexer::DocumentIndexer;
 use crate::document::relationship_analyzer::{RelationshipAnalyzer, RelationshipConfig};
+use crate::workspace::backup::RiskyOperation;
+use crate::workspace::organization_plan::{
+    apply_plan, list_plans, preview_plan, resume_plan, undo_plan, PlanIndexes, PlanJournal,
+    PlanPreview, PlanStatus,
+};
 use crate::workspace::{
     CategorizationSuggestion, DuplicateHandlingSuggestion, FolderStructureSuggestion,
     OrganizationAction, OrganizationAnalysis, OrganizationConfig, SemanticCluster, SmartOrganizer,
@@ -10,8 +18,9 @@ use crate::workspace::{
 };
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use tauri::State;
 use tokio::sync::Mutex;
 
 /// Request structure for organization analysis
@@ -99,6 +108,37 @@ pub struct ExecuteActionResponse {
     pub success: bool,
     pub executed: bool,
     pub message: String,
+    /// Snapshot taken before the action, for rolling it back
+    pub checkpoint_id: Option<String>,
+    /// Journal of the applied plan, for undoing just this action
+    pub plan_id: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Request to preview or apply a reorganization plan
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrganizationPlanRequest {
+    pub workspace_path: PathBuf,
+    pub actions: Vec<OrganizationAction>,
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+/// Response structure for reorganization plan previews
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrganizationPlanPreviewResponse {
+    pub success: bool,
+    pub preview: Option<PlanPreview>,
+    pub error: Option<String>,
+}
+
+/// Response structure for applying or undoing a reorganization plan
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrganizationPlanResponse {
+    pub success: bool,
+    pub journal: Option<PlanJournal>,
+    /// Snapshot taken before the plan was applied
+    pub checkpoint_id: Option<String>,
     pub error: Option<String>,
 }
 
@@ -454,30 +494,279 @@ pub async fn update_organization_config(
     })
 }
 
-/// Execute an organization action
+/// Execute an organization action. The action is looked up by its ID in a
+/// fresh analysis, so one whose suggestion no longer applies is not run.
 #[tauri::command]
 pub async fn execute_organization_action(
     request: ExecuteActionRequest,
+    backups: State<'_, WorkspaceBackupState>,
+    indexer_state: State<'_, DocumentIndexerState>,
+    vector_state: State<'_, VectorState>,
 ) -> Result<ExecuteActionResponse, String> {
     if !request.confirm {
         return Ok(ExecuteActionResponse {
             success: false,
             executed: false,
             message: "Action execution requires confirmation".to_string(),
+            checkpoint_id: None,
+            plan_id: None,
             error: None,
         });
     }
 
-    // For now, simulate execution
-    // In a full implementation, this would actually perform the organization action
-    Ok(ExecuteActionResponse {
-        success: true,
-        executed: true,
-        message: format!("Successfully executed action: {}", request.action_id),
+    let actions = get_organization_actions(OrganizationAnalysisRequest {
+        workspace_path: request.workspace_path.clone(),
+        config: None,
+    })
+    .await?;
+    let Some(action) = actions
+        .actions
+        .unwrap_or_default()
+        .into_iter()
+        .find(|action| action.action_id == request.action_id)
+    else {
+        return Ok(ExecuteActionResponse {
+            success: false,
+            executed: false,
+            message: "Action was not executed".to_string(),
+            checkpoint_id: None,
+            plan_id: None,
+            error: Some(
+                actions
+                    .error
+                    .unwrap_or_else(|| format!("Unknown action: {}", request.action_id)),
+            ),
+        });
+    };
+
+    let checkpoint = match backups
+        .snapshot_before(&request.workspace_path, RiskyOperation::Reorganization)
+        .await
+    {
+        Ok(checkpoint) => checkpoint,
+        Err(e) => {
+            return Ok(ExecuteActionResponse {
+                success: false,
+                executed: false,
+                message: "Action was not executed".to_string(),
+                checkpoint_id: None,
+                plan_id: None,
+                error: Some(format!("Failed to snapshot workspace: {}", e)),
+            });
+        }
+    };
+
+    match run_organization_plan(
+        &request.workspace_path,
+        &[action],
+        &indexer_state,
+        &vector_state,
+    )
+    .await
+    {
+        Ok(journal) => Ok(ExecuteActionResponse {
+            success: true,
+            executed: true,
+            message: format!("Successfully executed action: {}", request.action_id),
+            checkpoint_id: Some(checkpoint.backup_id),
+            plan_id: Some(journal.plan_id),
+            error: None,
+        }),
+        Err(e) => Ok(ExecuteActionResponse {
+            success: false,
+            executed: false,
+            message: "Action was not executed".to_string(),
+            checkpoint_id: Some(checkpoint.backup_id),
+            plan_id: None,
+            error: Some(e),
+        }),
+    }
+}
+
+/// Preview the file-tree changes a set of organization actions would make
+#[tauri::command]
+pub async fn preview_organization_plan(
+    request: OrganizationPlanRequest,
+) -> Result<OrganizationPlanPreviewResponse, String> {
+    let preview = preview_plan(&request.workspace_path, &request.actions);
+
+    Ok(OrganizationPlanPreviewResponse {
+        success: preview.conflicts.is_empty(),
+        preview: Some(preview),
         error: None,
     })
 }
 
+/// Apply a set of organization actions as one plan that can be undone
+#[tauri::command]
+pub async fn apply_organization_plan(
+    request: OrganizationPlanRequest,
+    backups: State<'_, WorkspaceBackupState>,
+    indexer_state: State<'_, DocumentIndexerState>,
+    vector_state: State<'_, VectorState>,
+) -> Result<OrganizationPlanResponse, String> {
+    if !request.confirm {
+        return Ok(OrganizationPlanResponse {
+            success: false,
+            journal: None,
+            checkpoint_id: None,
+            error: Some("Plan execution requires confirmation".to_string()),
+        });
+    }
+
+    let checkpoint = backups
+        .snapshot_before(&request.workspace_path, RiskyOperation::Reorganization)
+        .await
+        .map_err(|e| format!("Failed to snapshot workspace: {}", e))?;
+
+    match run_organization_plan(
+        &request.workspace_path,
+        &request.actions,
+        &indexer_state,
+        &vector_state,
+    )
+    .await
+    {
+        Ok(journal) => Ok(OrganizationPlanResponse {
+            success: true,
+            journal: Some(journal),
+            checkpoint_id: Some(checkpoint.backup_id),
+            error: None,
+        }),
+        Err(e) => Ok(OrganizationPlanResponse {
+            success: false,
+            journal: None,
+            checkpoint_id: Some(checkpoint.backup_id),
+            error: Some(e),
+        }),
+    }
+}
+
+/// Roll back every change an applied or interrupted organization plan made
+#[tauri::command]
+pub async fn undo_organization_plan(
+    workspace_path: PathBuf,
+    plan_id: String,
+    backups: State<'_, WorkspaceBackupState>,
+    indexer_state: State<'_, DocumentIndexerState>,
+    vector_state: State<'_, VectorState>,
+) -> Result<OrganizationPlanResponse, String> {
+    let checkpoint = backups
+        .snapshot_before(&workspace_path, RiskyOperation::Reorganization)
+        .await
+        .map_err(|e| format!("Failed to snapshot workspace: {}", e))?;
+
+    let mut state = indexer_state.lock().await;
+    let indexer = state
+        .as_mut()
+        .ok_or_else(|| "Document indexer not initialized".to_string())?;
+    let mut indexes = PlanIndexes {
+        indexer,
+        vector_store: Some(vector_state.vector_store.as_ref()),
+    };
+
+    match undo_plan(&workspace_path, &plan_id, &mut indexes).await {
+        Ok(journal) => Ok(OrganizationPlanResponse {
+            success: true,
+            journal: Some(journal),
+            checkpoint_id: Some(checkpoint.backup_id),
+            error: None,
+        }),
+        Err(e) => Ok(OrganizationPlanResponse {
+            success: false,
+            journal: None,
+            checkpoint_id: Some(checkpoint.backup_id),
+            error: Some(format!("{:#}", e)),
+        }),
+    }
+}
+
+/// Finish applying an organization plan that was interrupted, e.g. by a crash
+#[tauri::command]
+pub async fn resume_organization_plan(
+    workspace_path: PathBuf,
+    plan_id: String,
+    backups: State<'_, WorkspaceBackupState>,
+    indexer_state: State<'_, DocumentIndexerState>,
+    vector_state: State<'_, VectorState>,
+) -> Result<OrganizationPlanResponse, String> {
+    let checkpoint = backups
+        .snapshot_before(&workspace_path, RiskyOperation::Reorganization)
+        .await
+        .map_err(|e| format!("Failed to snapshot workspace: {}", e))?;
+
+    let mut state = indexer_state.lock().await;
+    let indexer = state
+        .as_mut()
+        .ok_or_else(|| "Document indexer not initialized".to_string())?;
+    let mut indexes = PlanIndexes {
+        indexer,
+        vector_store: Some(vector_state.vector_store.as_ref()),
+    };
+
+    match resume_plan(&workspace_path, &plan_id, &mut indexes).await {
+        Ok(journal) => Ok(OrganizationPlanResponse {
+            success: true,
+            journal: Some(journal),
+            checkpoint_id: Some(checkpoint.backup_id),
+            error: None,
+        }),
+        Err(e) => Ok(OrganizationPlanResponse {
+            success: false,
+            journal: None,
+            checkpoint_id: Some(checkpoint.backup_id),
+            error: Some(format!("{:#}", e)),
+        }),
+    }
+}
+
+/// List the organization plans applied to a workspace, newest first
+///
+/// Plans still marked as applying are reported as interrupted so they can be
+/// undone or resumed.
+#[tauri::command]
+pub async fn list_organization_plans(
+    workspace_path: PathBuf,
+    indexer_state: State<'_, DocumentIndexerState>,
+) -> Result<Vec<PlanJournal>, String> {
+    // Plans are applied while holding the indexer, so once it is free any plan
+    // still applying was cut short
+    let _indexer = indexer_state.lock().await;
+    let mut journals = list_plans(&workspace_path)
+        .map_err(|e| format!("Failed to list organization plans: {}", e))?;
+    for journal in &mut journals {
+        if journal.status == PlanStatus::Applying {
+            tracing::warn!(
+                "Organization plan {} was interrupted while applying",
+                journal.plan_id
+            );
+            journal.status = PlanStatus::Interrupted;
+        }
+    }
+    Ok(journals)
+}
+
+/// Apply actions against the shared document index, keeping vectors in step
+async fn run_organization_plan(
+    workspace_path: &Path,
+    actions: &[OrganizationAction],
+    indexer_state: &DocumentIndexerState,
+    vector_state: &VectorState,
+) -> Result<PlanJournal, String> {
+    let mut state = indexer_state.lock().await;
+    let indexer = state
+        .as_mut()
+        .ok_or_else(|| "Document indexer not initialized".to_string())?;
+    let mut indexes = PlanIndexes {
+        indexer,
+        vector_store: Some(vector_state.vector_store.as_ref()),
+    };
+
+    apply_plan(workspace_path, actions, &mut indexes)
+        .await
+        .map_err(|e| format!("{:#}", e))
+}
+
 /// Calculate organization score for a workspace
 #[tauri::command]
 pub async fn calculate_organization_score(
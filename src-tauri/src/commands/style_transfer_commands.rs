@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::sync::Mutex;
 use tauri::State;
 
+use crate::commands::workspace_backup_commands::WorkspaceBackupState;
 use crate::document::indexer::DocumentIndexer;
 use crate::document::style_analyzer::{ToneType, VocabularyComplexity, VoiceType};
 use crate::document::style_learner::{OrganizationalStyle, StyleLearner};
@@ -9,6 +11,7 @@ use crate::document::style_transfer::{
     StyleTransfer, StyleTransferConfig, StyleTransferMode, StyleTransferRequest,
     StyleTransferResult, StyleTransferTarget,
 };
+use crate::workspace::backup::RiskyOperation;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TransferStyleRequest {
@@ -25,6 +28,23 @@ pub struct TransferStyleResponse {
     pub error: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApplyStyleToDocumentRequest {
+    pub workspace_path: PathBuf,
+    pub document_path: PathBuf, // Document inside the workspace to rewrite
+    pub target_style_type: String,
+    pub target_style_data: serde_json::Value,
+    pub config: StyleTransferConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApplyStyleToDocumentResponse {
+    pub success: bool,
+    pub result: Option<StyleTransferResult>,
+    pub checkpoint_id: Option<String>, // Snapshot taken before the document was rewritten
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApplyOrganizationalStyleRequest {
     pub content: String,
@@ -135,6 +155,63 @@ pub async fn transfer_content_style(
     }
 }
 
+/// Transfer the style of a workspace document and write the result back to it,
+/// snapshotting the workspace first so the rewrite can be rolled back
+#[tauri::command]
+pub async fn apply_style_to_document(
+    request: ApplyStyleToDocumentRequest,
+    backups: State<'_, WorkspaceBackupState>,
+) -> Result<ApplyStyleToDocumentResponse, String> {
+    let failure = |error: String| ApplyStyleToDocumentResponse {
+        success: false,
+        result: None,
+        checkpoint_id: None,
+        error: Some(error),
+    };
+
+    let document_path =
+        match resolve_workspace_document(&request.workspace_path, &request.document_path) {
+            Ok(path) => path,
+            Err(e) => return Ok(failure(e)),
+        };
+    let content = match tokio::fs::read_to_string(&document_path).await {
+        Ok(content) => content,
+        Err(e) => return Ok(failure(format!("Failed to read document: {}", e))),
+    };
+    let target_style =
+        match parse_target_style(&request.target_style_type, &request.target_style_data) {
+            Ok(style) => style,
+            Err(e) => return Ok(failure(format!("Invalid target style: {}", e))),
+        };
+
+    let result = match StyleTransfer::new().transfer_style(StyleTransferRequest {
+        content,
+        target_style,
+        config: request.config,
+    }) {
+        Ok(result) => result,
+        Err(e) => return Ok(failure(e.to_string())),
+    };
+
+    let checkpoint = match backups
+        .snapshot_before(&request.workspace_path, RiskyOperation::StyleTransfer)
+        .await
+    {
+        Ok(checkpoint) => checkpoint,
+        Err(e) => return Ok(failure(format!("Failed to snapshot workspace: {}", e))),
+    };
+    if let Err(e) = tokio::fs::write(&document_path, &result.transferred_content).await {
+        return Ok(failure(format!("Failed to write document: {}", e)));
+    }
+
+    Ok(ApplyStyleToDocumentResponse {
+        success: true,
+        result: Some(result),
+        checkpoint_id: Some(checkpoint.backup_id),
+        error: None,
+    })
+}
+
 /// Apply organizational style to content by learning from document corpus
 #[tauri::command]
 pub async fn apply_organizational_style(
@@ -470,6 +547,24 @@ pub async fn get_style_transfer_capabilities() -> Result<serde_json::Value, Stri
     }))
 }
 
+/// Resolve a document path against its workspace, refusing paths outside it
+fn resolve_workspace_document(
+    workspace_path: &std::path::Path,
+    document_path: &std::path::Path,
+) -> Result<PathBuf, String> {
+    let workspace = workspace_path
+        .canonicalize()
+        .map_err(|e| format!("Invalid workspace path: {}", e))?;
+    let document = workspace
+        .join(document_path)
+        .canonicalize()
+        .map_err(|e| format!("Invalid document path: {}", e))?;
+    if !document.starts_with(&workspace) || !document.is_file() {
+        return Err("Document must be a file inside the workspace".to_string());
+    }
+    Ok(document)
+}
+
 // Helper functions for parsing style parameters
 fn parse_target_style(
     style_type: &str,
@@ -587,6 +682,22 @@ fn parse_vocabulary_complexity(complexity_str: &str) -> Result<VocabularyComplex
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_resolve_workspace_document() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        std::fs::write(workspace.path().join("notes.md"), "notes").unwrap();
+        let outside = tempfile::NamedTempFile::new().unwrap();
+
+        assert!(
+            resolve_workspace_document(workspace.path(), std::path::Path::new("notes.md")).is_ok()
+        );
+        assert!(resolve_workspace_document(workspace.path(), outside.path()).is_err());
+        assert!(
+            resolve_workspace_document(workspace.path(), std::path::Path::new("../notes.md"))
+                .is_err()
+        );
+    }
+
     #[tokio::test]
     async fn test_get_style_transfer_capabilities() {
         let result = get_style_transfer_capabilities().await;
@@ -1,12 +1,13 @@
 // src-tauri/src/commands/vector_commands.rs
 
 use crate::commands::document_indexing_commands::DocumentIndexerState;
+use crate::document::MetadataFilter;
 use crate::vector::{
     DocumentChunk, EmbeddingConfig, EmbeddingEngine, SearchResult, VectorStore, VectorStoreStats,
 };
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tauri::State;
 use tokio::sync::Mutex;
@@ -61,6 +62,8 @@ pub struct VectorSearchRequest {
     pub query: String,
     pub document_id: Option<String>,
     pub max_results: Option<usize>,
+    /// Only search documents whose tags and custom metadata match
+    pub metadata_filter: Option<MetadataFilter>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,6 +98,8 @@ pub struct HybridSearchRequest {
     pub vector_weight: Option<f32>,
     pub enable_vector_search: Option<bool>,
     pub enable_keyword_search: Option<bool>,
+    /// Only search documents whose tags and custom metadata match
+    pub metadata_filter: Option<MetadataFilter>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -272,10 +277,32 @@ pub async fn index_document_vector(
 #[tauri::command]
 pub async fn search_vectors(
     vector_state: State<'_, VectorState>,
+    indexer_state: State<'_, DocumentIndexerState>,
     request: VectorSearchRequest,
 ) -> Result<VectorSearchResponse, String> {
     let start_time = std::time::Instant::now();
 
+    let allowed_documents =
+        match documents_matching(&indexer_state, request.metadata_filter.as_ref()).await {
+            Ok(allowed) => allowed,
+            Err(e) => {
+                return Ok(VectorSearchResponse {
+                    success: false,
+                    results: Vec::new(),
+                    query_time_ms: 0,
+                    error: Some(e),
+                })
+            }
+        };
+    if excluded_by_filter(request.document_id.as_deref(), allowed_documents.as_ref()) {
+        return Ok(VectorSearchResponse {
+            success: true,
+            results: Vec::new(),
+            query_time_ms: start_time.elapsed().as_millis() as u64,
+            error: None,
+        });
+    }
+
     let engine_lock = vector_state.embedding_engine.lock().await;
     let engine = match engine_lock.as_ref() {
         Some(engine) => engine,
@@ -290,10 +317,24 @@ pub async fn search_vectors(
     };
 
     // Try keyword-based search first as a fallback/enhancement
-    let keyword_results = vector_state
-        .vector_store
-        .keyword_search(&request.query, request.max_results.unwrap_or(5))
-        .await;
+    let keyword_results = match &allowed_documents {
+        Some(document_ids) => {
+            vector_state
+                .vector_store
+                .keyword_search_in_documents(
+                    document_ids,
+                    &request.query,
+                    request.max_results.unwrap_or(5),
+                )
+                .await
+        }
+        None => {
+            vector_state
+                .vector_store
+                .keyword_search(&request.query, request.max_results.unwrap_or(5))
+                .await
+        }
+    };
 
     match keyword_results {
         Ok(results) => {
@@ -335,14 +376,20 @@ pub async fn search_vectors(
 
     // Perform vector search with safety limits
     let max_results = request.max_results.unwrap_or(5).min(MAX_SEARCH_RESULTS);
-    let search_results = match &request.document_id {
-        Some(doc_id) => {
+    let search_results = match (&request.document_id, &allowed_documents) {
+        (Some(doc_id), _) => {
             vector_state
                 .vector_store
                 .search_by_document(doc_id, &query_embedding, max_results)
                 .await
         }
-        None => {
+        (None, Some(document_ids)) => {
+            vector_state
+                .vector_store
+                .search_in_documents(document_ids, &query_embedding, max_results)
+                .await
+        }
+        (None, None) => {
             vector_state
                 .vector_store
                 .search(&query_embedding, max_results)
@@ -589,6 +636,7 @@ pub async fn semantic_search(
 #[tauri::command]
 pub async fn test_vector_search(
     vector_state: State<'_, VectorState>,
+    indexer_state: State<'_, DocumentIndexerState>,
     test_text: String,
 ) -> Result<String, String> {
     // Index the test text as a document
@@ -616,9 +664,10 @@ pub async fn test_vector_search(
         query: search_query.clone(),
         document_id: None,
         max_results: Some(3),
+        metadata_filter: None,
     };
 
-    let search_result = search_vectors(vector_state.clone(), search_request).await?;
+    let search_result = search_vectors(vector_state.clone(), indexer_state, search_request).await?;
     if !search_result.success {
         return Err(search_result.error.unwrap_or("Search failed".to_string()));
     }
@@ -638,6 +687,7 @@ pub async fn test_vector_search(
 #[tauri::command]
 pub async fn hybrid_search(
     vector_state: State<'_, VectorState>,
+    indexer_state: State<'_, DocumentIndexerState>,
     request: HybridSearchRequest,
 ) -> Result<HybridSearchResponse, String> {
     let start_time = std::time::Instant::now();
@@ -661,6 +711,22 @@ pub async fn hybrid_search(
         });
     }
 
+    let allowed_documents = documents_matching(&indexer_state, request.metadata_filter.as_ref())
+        .await
+        .map_err(|e| format!("Failed to apply metadata filter: {}", e))?;
+    if excluded_by_filter(request.document_id.as_deref(), allowed_documents.as_ref()) {
+        return Ok(HybridSearchResponse {
+            success: true,
+            results: Vec::new(),
+            keyword_results_count: 0,
+            vector_results_count: 0,
+            combined_results_count: 0,
+            query_time_ms: start_time.elapsed().as_millis() as u64,
+            search_strategy: "metadata_filter".to_string(),
+            error: None,
+        });
+    }
+
     let mut keyword_results = Vec::new();
     let mut vector_results = Vec::new();
     let mut search_strategy_parts = Vec::new();
@@ -672,6 +738,11 @@ pub async fn hybrid_search(
                 .vector_store
                 .keyword_search_by_document(doc_id, &request.query, max_results * 2)
                 .await
+        } else if let Some(document_ids) = &allowed_documents {
+            vector_state
+                .vector_store
+                .keyword_search_in_documents(document_ids, &request.query, max_results * 2)
+                .await
         } else {
             vector_state
                 .vector_store
@@ -700,6 +771,11 @@ pub async fn hybrid_search(
                             .vector_store
                             .search_by_document(doc_id, &query_embedding, max_results * 2)
                             .await
+                    } else if let Some(document_ids) = &allowed_documents {
+                        vector_state
+                            .vector_store
+                            .search_in_documents(document_ids, &query_embedding, max_results * 2)
+                            .await
                     } else {
                         vector_state
                             .vector_store
@@ -759,6 +835,30 @@ pub async fn hybrid_search(
     })
 }
 
+/// Resolve a metadata filter to the documents it allows, `None` when the search is unfiltered
+async fn documents_matching(
+    indexer_state: &DocumentIndexerState,
+    filter: Option<&MetadataFilter>,
+) -> Result<Option<HashSet<String>>, String> {
+    let Some(filter) = filter.filter(|filter| !filter.is_empty()) else {
+        return Ok(None);
+    };
+
+    let state = indexer_state.lock().await;
+    let indexer = state
+        .as_ref()
+        .ok_or_else(|| "Document indexer not initialized".to_string())?;
+    Ok(Some(indexer.metadata_store().matching_documents(filter)))
+}
+
+/// Whether a search scoped to one document is ruled out by the metadata filter
+fn excluded_by_filter(document_id: Option<&str>, allowed: Option<&HashSet<String>>) -> bool {
+    match (document_id, allowed) {
+        (Some(document_id), Some(allowed)) => !allowed.contains(document_id),
+        _ => false,
+    }
+}
+
 /// Combine keyword and vector search results using weighted scoring
 fn combine_hybrid_results(
     keyword_results: Vec<SearchResult>,
@@ -1,324 +1,293 @@
 // src-tauri/src/commands/workspace_backup_commands.rs
 //! Tauri commands for workspace backup and recovery operations
 
-use crate::filesystem::security::backup_manager::BackupManager;
 use crate::workspace::backup::{
-    IntegrityReport, WorkspaceBackupConfig, WorkspaceBackupManager, WorkspaceBackupMetadata,
-    WorkspaceBackupType, WorkspaceExportInfo, WorkspaceRecoveryInfo,
+    IntegrityReport, WorkspaceBackupConfig, WorkspaceBackupMetadata, WorkspaceBackupType,
+    WorkspaceExportInfo, WorkspaceRecoveryInfo,
 };
-use std::sync::{Arc, Mutex};
+use crate::workspace::backup_replication::ReplicaStatus;
+use crate::workspace::backup_scheduler::BackupScheduler;
+use crate::workspace::backup_secrets::BackupSecrets;
+use crate::workspace::bundle::{
+    inspect_bundle, BundleImport, BundleOptions, BundleSigner, BundleSummary,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
 use tauri::State;
 
-/// Global workspace backup manager
-#[allow(dead_code)]
-pub type GlobalWorkspaceBackupManager = Arc<Mutex<Option<WorkspaceBackupManager>>>;
-
-/// Initialize workspace backup manager
-#[tauri::command]
-#[allow(dead_code)]
-pub async fn init_workspace_backup_manager(
-    backup_manager: State<'_, GlobalWorkspaceBackupManager>,
-) -> Result<(), String> {
-    // Create backup manager - this would typically be created with the actual BackupManager
-    // For now, we'll just initialize with a placeholder
-    let mut manager_guard = backup_manager.lock().map_err(|e| e.to_string())?;
+/// Backup scheduler shared by every open workspace
+pub type WorkspaceBackupState = Arc<BackupScheduler>;
 
-    // In a real implementation, you would create the BackupManager here
-    // *manager_guard = Some(WorkspaceBackupManager::new(backup_manager)?);
+fn parse_backup_type(backup_type: &str) -> Result<WorkspaceBackupType, String> {
+    match backup_type {
+        "full" => Ok(WorkspaceBackupType::Full),
+        "metadata" => Ok(WorkspaceBackupType::MetadataOnly),
+        "incremental" => Ok(WorkspaceBackupType::Incremental),
+        "emergency" => Ok(WorkspaceBackupType::Emergency),
+        _ => Err("Invalid backup type".to_string()),
+    }
+}
 
-    // For testing purposes, mark as initialized
-    *manager_guard = None; // This would be Some(...) in real implementation
+/// Start scheduled backups of a workspace. Returns false when automatic
+/// backups are disabled.
+#[tauri::command]
+pub async fn start_workspace_backup_schedule(
+    workspace_path: String,
+    backups: State<'_, WorkspaceBackupState>,
+) -> Result<bool, String> {
+    Ok(backups.schedule(&PathBuf::from(workspace_path)).await)
+}
 
-    Ok(())
+/// Stop scheduled backups of a workspace
+#[tauri::command]
+pub async fn stop_workspace_backup_schedule(
+    workspace_path: String,
+    backups: State<'_, WorkspaceBackupState>,
+) -> Result<bool, String> {
+    Ok(backups.unschedule(&PathBuf::from(workspace_path)))
 }
 
 /// Create workspace backup
 #[tauri::command]
-#[allow(dead_code)]
 pub async fn create_workspace_backup(
     workspace_path: String,
     backup_type: String,
-    backup_manager: State<'_, GlobalWorkspaceBackupManager>,
+    backups: State<'_, WorkspaceBackupState>,
 ) -> Result<WorkspaceBackupMetadata, String> {
-    let manager_guard = backup_manager.lock().map_err(|e| e.to_string())?;
-
-    if let Some(manager) = manager_guard.as_ref() {
-        let backup_type = match backup_type.as_str() {
-            "full" => WorkspaceBackupType::Full,
-            "metadata" => WorkspaceBackupType::MetadataOnly,
-            "incremental" => WorkspaceBackupType::Incremental,
-            "emergency" => WorkspaceBackupType::Emergency,
-            _ => return Err("Invalid backup type".to_string()),
-        };
-
-        // This would work in a real implementation
-        // manager.create_backup(&PathBuf::from(workspace_path), backup_type).await.map_err(|e| e.to_string())
-
-        // For testing, return a mock backup metadata
-        Ok(create_mock_backup_metadata(workspace_path, backup_type))
-    } else {
-        Err("Backup manager not initialized".to_string())
-    }
+    let backup_type = parse_backup_type(&backup_type)?;
+    backups
+        .create_backup(&PathBuf::from(workspace_path), backup_type)
+        .await
+        .map_err(|e| e.to_string())
 }
 
-/// Restore workspace from backup
+/// Restore a workspace backup, from the workspace's own backups or, when
+/// `destination` names a replication destination, from the copy held there.
+/// Returns the snapshot taken of the workspace it overwrote, if any.
 #[tauri::command]
-#[allow(dead_code)]
 pub async fn restore_workspace_from_backup(
+    workspace_path: String,
     backup_id: String,
     restore_path: String,
     overwrite_existing: bool,
-    backup_manager: State<'_, GlobalWorkspaceBackupManager>,
-) -> Result<(), String> {
-    let manager_guard = backup_manager.lock().map_err(|e| e.to_string())?;
+    destination: Option<String>,
+    backups: State<'_, WorkspaceBackupState>,
+) -> Result<Option<WorkspaceBackupMetadata>, String> {
+    let workspace_path = PathBuf::from(workspace_path);
+    let restore_path = PathBuf::from(restore_path);
+    if let Some(destination) = destination {
+        return backups
+            .restore_from_replica(
+                &workspace_path,
+                &destination,
+                &backup_id,
+                &restore_path,
+                overwrite_existing,
+            )
+            .await
+            .map_err(|e| e.to_string());
+    }
 
-    if let Some(_manager) = manager_guard.as_ref() {
-        // In real implementation:
-        // let backup_metadata = manager.get_backup_metadata(&backup_id).await.map_err(|e| e.to_string())?;
-        // manager.restore_workspace(&backup_metadata, &PathBuf::from(restore_path), overwrite_existing).await.map_err(|e| e.to_string())
+    let backup_metadata = backups
+        .manager()
+        .lock()
+        .await
+        .find_backup(&workspace_path, &backup_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    backups
+        .restore(&backup_metadata, &restore_path, overwrite_existing)
+        .await
+        .map_err(|e| e.to_string())
+}
 
-        println!(
-            "Would restore backup {} to {} (overwrite: {})",
-            backup_id, restore_path, overwrite_existing
-        );
-        Ok(())
-    } else {
-        Err("Backup manager not initialized".to_string())
-    }
+/// Download a backup from every replication destination and check its content
+#[tauri::command]
+pub async fn verify_backup_replicas(
+    workspace_path: String,
+    backup_id: String,
+    backups: State<'_, WorkspaceBackupState>,
+) -> Result<Vec<ReplicaStatus>, String> {
+    let replicator = backups.manager().lock().await.replicator();
+    Ok(replicator
+        .verify(&PathBuf::from(workspace_path), &backup_id)
+        .await)
+}
+
+/// List the snapshots taken before risky operations, newest first
+#[tauri::command]
+pub async fn get_operation_checkpoints(
+    workspace_path: String,
+    backups: State<'_, WorkspaceBackupState>,
+) -> Result<Vec<WorkspaceBackupMetadata>, String> {
+    backups
+        .checkpoints(&PathBuf::from(workspace_path))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Roll a workspace back to the snapshot taken before an operation. Returns
+/// the snapshot taken just before rolling back, so the rollback can be undone.
+#[tauri::command]
+pub async fn rollback_workspace_operation(
+    workspace_path: String,
+    backup_id: String,
+    backups: State<'_, WorkspaceBackupState>,
+) -> Result<WorkspaceBackupMetadata, String> {
+    backups
+        .rollback(&PathBuf::from(workspace_path), &backup_id)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 /// Check workspace integrity
 #[tauri::command]
-#[allow(dead_code)]
 pub async fn check_workspace_integrity(
     workspace_path: String,
-    backup_manager: State<'_, GlobalWorkspaceBackupManager>,
+    backups: State<'_, WorkspaceBackupState>,
 ) -> Result<IntegrityReport, String> {
-    let manager_guard = backup_manager.lock().map_err(|e| e.to_string())?;
-
-    if let Some(_manager) = manager_guard.as_ref() {
-        // In real implementation:
-        // manager.check_integrity(&PathBuf::from(workspace_path)).await.map_err(|e| e.to_string())
-
-        // For testing, return a mock integrity report
-        Ok(create_mock_integrity_report(workspace_path))
-    } else {
-        Err("Backup manager not initialized".to_string())
-    }
+    backups
+        .manager()
+        .lock()
+        .await
+        .check_integrity(&PathBuf::from(workspace_path))
+        .await
+        .map_err(|e| e.to_string())
 }
 
 /// Get recovery information for workspace
 #[tauri::command]
-#[allow(dead_code)]
 pub async fn get_workspace_recovery_info(
     workspace_path: String,
-    backup_manager: State<'_, GlobalWorkspaceBackupManager>,
+    backups: State<'_, WorkspaceBackupState>,
 ) -> Result<WorkspaceRecoveryInfo, String> {
-    let manager_guard = backup_manager.lock().map_err(|e| e.to_string())?;
-
-    if let Some(_manager) = manager_guard.as_ref() {
-        // In real implementation:
-        // manager.get_recovery_info(&PathBuf::from(workspace_path)).await.map_err(|e| e.to_string())
-
-        // For testing, return mock recovery info
-        Ok(create_mock_recovery_info(workspace_path))
-    } else {
-        Err("Backup manager not initialized".to_string())
-    }
+    backups
+        .manager()
+        .lock()
+        .await
+        .get_recovery_info(&PathBuf::from(workspace_path))
+        .await
+        .map_err(|e| e.to_string())
 }
 
-/// Export workspace for migration
+/// Export workspace as a signed `.fiovana` bundle
 #[tauri::command]
-#[allow(dead_code)]
 pub async fn export_workspace(
     workspace_path: String,
     export_path: String,
-    include_files: bool,
-    backup_manager: State<'_, GlobalWorkspaceBackupManager>,
+    options: BundleOptions,
+    backups: State<'_, WorkspaceBackupState>,
 ) -> Result<WorkspaceExportInfo, String> {
-    let manager_guard = backup_manager.lock().map_err(|e| e.to_string())?;
-
-    if let Some(_manager) = manager_guard.as_ref() {
-        // In real implementation:
-        // manager.export_workspace(&PathBuf::from(workspace_path), &PathBuf::from(export_path), include_files).await.map_err(|e| e.to_string())
-
-        // For testing, return mock export info
-        Ok(create_mock_export_info(workspace_path, export_path, include_files))
-    } else {
-        Err("Backup manager not initialized".to_string())
-    }
+    backups
+        .manager()
+        .lock()
+        .await
+        .export_workspace(
+            &PathBuf::from(workspace_path),
+            &PathBuf::from(export_path),
+            &options,
+        )
+        .await
+        .map_err(|e| e.to_string())
 }
 
-/// Import workspace from export
+/// Import workspace from a `.fiovana` bundle
 #[tauri::command]
-#[allow(dead_code)]
 pub async fn import_workspace(
     export_path: String,
     import_path: String,
-    backup_manager: State<'_, GlobalWorkspaceBackupManager>,
-) -> Result<(), String> {
-    let manager_guard = backup_manager.lock().map_err(|e| e.to_string())?;
+    passphrase: Option<String>,
+    trusted_signers: Option<Vec<String>>,
+    backups: State<'_, WorkspaceBackupState>,
+) -> Result<BundleImport, String> {
+    backups
+        .manager()
+        .lock()
+        .await
+        .import_workspace(
+            &PathBuf::from(export_path),
+            &PathBuf::from(import_path),
+            passphrase,
+            trusted_signers.unwrap_or_default(),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
 
-    if let Some(_manager) = manager_guard.as_ref() {
-        // In real implementation:
-        // manager.import_workspace(&PathBuf::from(export_path), &PathBuf::from(import_path)).await.map_err(|e| e.to_string())
+/// Read what a bundle holds and who signed it without importing it
+#[tauri::command]
+pub async fn inspect_workspace_bundle(
+    bundle_path: String,
+    passphrase: Option<String>,
+) -> Result<BundleSummary, String> {
+    tokio::task::spawn_blocking(move || {
+        inspect_bundle(&PathBuf::from(bundle_path), passphrase.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
 
-        println!("Would import workspace from {} to {}", export_path, import_path);
-        Ok(())
-    } else {
-        Err("Backup manager not initialized".to_string())
-    }
+/// Fingerprint of this installation's bundle signing key, for sharing with
+/// people who import its bundles
+#[tauri::command]
+pub async fn get_bundle_signing_fingerprint() -> Result<String, String> {
+    let key_dir = BundleSigner::default_dir()
+        .ok_or_else(|| "No configuration directory for the bundle signing key".to_string())?;
+    tokio::task::spawn_blocking(move || BundleSigner::load_or_generate(&key_dir))
+        .await
+        .map_err(|e| e.to_string())?
+        .map(|signer| signer.fingerprint())
+        .map_err(|e| e.to_string())
+}
+
+/// Keep a replica passphrase or S3 secret access key in the backup secret
+/// store, returning the reference to put in the backup configuration
+#[tauri::command]
+pub async fn store_backup_secret(secret: String) -> Result<String, String> {
+    BackupSecrets::default()
+        .store(&secret)
+        .await
+        .map_err(|e| format!("{:#}", e))
 }
 
 /// Get backup configuration
 #[tauri::command]
-#[allow(dead_code)]
-pub async fn get_backup_config() -> Result<WorkspaceBackupConfig, String> {
-    Ok(WorkspaceBackupConfig::default())
+pub async fn get_backup_config(
+    backups: State<'_, WorkspaceBackupState>,
+) -> Result<WorkspaceBackupConfig, String> {
+    Ok(backups.config().await)
 }
 
-/// Update backup configuration
+/// Update backup configuration and restart schedules with it
 #[tauri::command]
-#[allow(dead_code)]
 pub async fn update_backup_config(
     config: WorkspaceBackupConfig,
-    backup_manager: State<'_, GlobalWorkspaceBackupManager>,
+    backups: State<'_, WorkspaceBackupState>,
 ) -> Result<(), String> {
-    let _manager_guard = backup_manager.lock().map_err(|e| e.to_string())?;
-
-    // In real implementation, you would update the manager's configuration
-    println!("Would update backup config: auto_backup={}, interval={}min",
-             config.auto_backup_enabled, config.backup_interval_minutes);
+    backups.update_config(config).await;
     Ok(())
 }
 
-// Helper functions for creating mock data during testing
-fn create_mock_backup_metadata(workspace_path: String, backup_type: WorkspaceBackupType) -> WorkspaceBackupMetadata {
-    use chrono::Utc;
-    use std::path::PathBuf;
-
-    WorkspaceBackupMetadata {
-        backup_id: format!("backup_{}", Utc::now().timestamp()),
-        workspace_name: "Mock Workspace".to_string(),
-        workspace_path: PathBuf::from(workspace_path),
-        backup_timestamp: Utc::now(),
-        backup_type,
-        backup_size: 1024 * 1024, // 1MB
-        file_count: 42,
-        checksum: "abc123def456".to_string(),
-        is_compressed: true,
-        backup_path: PathBuf::from("/tmp/mock_backup"),
-        recovery_tested: false,
-    }
-}
-
-fn create_mock_integrity_report(workspace_path: String) -> IntegrityReport {
-    use std::path::PathBuf;
-
-    let mut report = IntegrityReport::new(PathBuf::from(workspace_path));
-    report.health_score = 95;
-    report
-}
-
-fn create_mock_recovery_info(workspace_path: String) -> WorkspaceRecoveryInfo {
-    use crate::workspace::backup::{RecoveryOption, RecoveryType, RiskLevel};
-    use std::time::Duration;
-
-    let backup_metadata = create_mock_backup_metadata(workspace_path, WorkspaceBackupType::Full);
-
-    let recovery_options = vec![
-        RecoveryOption {
-            option_type: RecoveryType::FullRestore,
-            description: "Restore from latest backup".to_string(),
-            estimated_time: Duration::from_secs(300),
-            data_loss_risk: RiskLevel::Low,
-        },
-        RecoveryOption {
-            option_type: RecoveryType::MetadataRestore,
-            description: "Restore metadata only".to_string(),
-            estimated_time: Duration::from_secs(60),
-            data_loss_risk: RiskLevel::Medium,
-        },
-    ];
-
-    WorkspaceRecoveryInfo {
-        available_backups: vec![backup_metadata.clone()],
-        latest_backup: Some(backup_metadata),
-        recovery_options,
-    }
-}
-
-fn create_mock_export_info(workspace_path: String, export_path: String, include_files: bool) -> WorkspaceExportInfo {
-    use chrono::Utc;
-    use std::path::PathBuf;
-
-    WorkspaceExportInfo {
-        workspace_name: "Mock Workspace".to_string(),
-        export_timestamp: Utc::now(),
-        export_path: PathBuf::from(export_path),
-        total_size: if include_files { 5 * 1024 * 1024 } else { 1024 }, // 5MB or 1KB
-        file_count: if include_files { 100 } else { 2 },
-        includes_files: include_files,
-        checksum: "export123abc456".to_string(),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[tokio::test]
-    async fn test_backup_config() {
-        let config = get_backup_config().await.unwrap();
-        assert!(config.auto_backup_enabled);
-        assert_eq!(config.backup_interval_minutes, 60);
-        assert_eq!(config.max_backups, 10);
-    }
-
     #[test]
-    fn test_mock_backup_metadata() {
-        let metadata = create_mock_backup_metadata(
-            "/test/workspace".to_string(),
-            WorkspaceBackupType::Full,
-        );
-
-        assert_eq!(metadata.workspace_name, "Mock Workspace");
-        assert!(matches!(metadata.backup_type, WorkspaceBackupType::Full));
-        assert_eq!(metadata.file_count, 42);
+    fn test_parse_backup_type() {
+        assert!(matches!(
+            parse_backup_type("full"),
+            Ok(WorkspaceBackupType::Full)
+        ));
+        assert!(matches!(
+            parse_backup_type("metadata"),
+            Ok(WorkspaceBackupType::MetadataOnly)
+        ));
+        assert!(matches!(
+            parse_backup_type("emergency"),
+            Ok(WorkspaceBackupType::Emergency)
+        ));
+        assert!(parse_backup_type("weekly").is_err());
     }
-
-    #[test]
-    fn test_mock_integrity_report() {
-        let report = create_mock_integrity_report("/test/workspace".to_string());
-        assert_eq!(report.health_score, 95);
-        assert!(report.is_healthy());
-        assert!(!report.needs_attention());
-    }
-
-    #[test]
-    fn test_mock_recovery_info() {
-        let info = create_mock_recovery_info("/test/workspace".to_string());
-        assert_eq!(info.available_backups.len(), 1);
-        assert!(info.latest_backup.is_some());
-        assert_eq!(info.recovery_options.len(), 2);
-    }
-
-    #[test]
-    fn test_mock_export_info() {
-        let info_with_files = create_mock_export_info(
-            "/test/workspace".to_string(),
-            "/test/export".to_string(),
-            true,
-        );
-        assert!(info_with_files.includes_files);
-        assert_eq!(info_with_files.file_count, 100);
-
-        let info_metadata_only = create_mock_export_info(
-            "/test/workspace".to_string(),
-            "/test/export".to_string(),
-            false,
-        );
-        assert!(!info_metadata_only.includes_files);
-        assert_eq!(info_metadata_only.file_count, 2);
-    }
-}
\ No newline at end of file
+}
@@ -1,20 +1,59 @@
 // src-tauri/src/commands/workspace_commands.rs
 //! Tauri commands for workspace management
 
+use crate::commands::template_commands::TemplateManagerState;
+use crate::commands::workspace_backup_commands::WorkspaceBackupState;
+use crate::workspace::custom_templates::{
+    applied_template, template_id_for, CustomWorkspaceTemplate, SkippedTemplateFile,
+    TemplateSummary,
+};
 use crate::workspace::{
     CreateWorkspaceRequest, RecentWorkspace, UpdateRecentWorkspaceRequest, WorkspaceConfig,
     WorkspaceInfo, WorkspaceStats, WorkspaceTemplate, WorkspaceValidation,
 };
+use serde::{Deserialize, Serialize};
+use std::path::Path;
 use tauri::State;
 
+/// Request to capture an existing workspace as a custom template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportWorkspaceTemplateRequest {
+    pub workspace_path: String,
+    /// Template name, the workspace name by default
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Workspace-relative files to seed new workspaces with, the README by default
+    pub seed_documents: Option<Vec<String>>,
+    /// Output templates to carry along
+    #[serde(default)]
+    pub output_template_ids: Vec<String>,
+}
+
+/// A saved custom template and the files left out of it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedWorkspaceTemplate {
+    pub template: CustomWorkspaceTemplate,
+    pub skipped: Vec<SkippedTemplateFile>,
+}
+
+/// A workspace created from another one, with the saved template it was laid out from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceCopy {
+    pub workspace: WorkspaceInfo,
+    pub template: ExportedWorkspaceTemplate,
+}
+
 /// Create a new workspace
 #[tauri::command]
 pub async fn create_workspace(
     name: String,
     path: String,
     template: Option<String>,
+    template_version: Option<u32>,
     description: Option<String>,
     state: State<'_, crate::AppState>,
+    backups: State<'_, WorkspaceBackupState>,
+    templates: State<'_, TemplateManagerState>,
 ) -> Result<WorkspaceInfo, String> {
     let template = match template.as_deref() {
         Some("research") => WorkspaceTemplate::Research,
@@ -24,6 +63,17 @@ pub async fn create_workspace(
         Some(custom) => WorkspaceTemplate::Custom(custom.to_string()),
     };
 
+    let custom = match &template {
+        WorkspaceTemplate::Custom(id) => Some(
+            state
+                .workspace_manager
+                .template_store()
+                .load(id, template_version)
+                .map_err(|e| e.to_string())?,
+        ),
+        _ => None,
+    };
+
     let request = CreateWorkspaceRequest {
         name,
         path: std::path::PathBuf::from(path),
@@ -31,26 +81,243 @@ pub async fn create_workspace(
         description,
     };
 
-    state
+    let workspace = match &custom {
+        Some(custom) => {
+            let workspace = state
+                .workspace_manager
+                .create_workspace_from_template(request, custom)
+                .await
+                .map_err(|e| e.to_string())?;
+            install_output_templates(&templates, custom);
+            workspace
+        }
+        None => state
+            .workspace_manager
+            .create_workspace(request)
+            .await
+            .map_err(|e| e.to_string())?,
+    };
+
+    backups.schedule(&workspace.path).await;
+    Ok(workspace)
+}
+
+/// Create a workspace laid out like an existing one
+///
+/// The existing workspace's layout, settings, style profiles and the output
+/// templates it was created with are exported and saved as a custom template,
+/// exactly as `export_workspace_template` does, and the new workspace is
+/// created from that template.
+#[tauri::command]
+pub async fn create_workspace_from_existing(
+    source_path: String,
+    name: String,
+    path: String,
+    description: Option<String>,
+    state: State<'_, crate::AppState>,
+    backups: State<'_, WorkspaceBackupState>,
+    templates: State<'_, TemplateManagerState>,
+) -> Result<WorkspaceCopy, String> {
+    // Carry along the output templates the source was created with, installing
+    // any that are missing so the export can resolve them
+    let output_template_ids = match applied_template(Path::new(&source_path)) {
+        Some(applied) => {
+            install_output_templates(&templates, &applied);
+            applied
+                .output_templates
+                .iter()
+                .map(|output_template| output_template.id.clone())
+                .collect()
+        }
+        None => Vec::new(),
+    };
+    let exported = export_template(
+        &state,
+        &templates,
+        ExportWorkspaceTemplateRequest {
+            workspace_path: source_path,
+            name: None,
+            description: None,
+            seed_documents: None,
+            output_template_ids,
+        },
+    )
+    .await?;
+
+    let request = CreateWorkspaceRequest {
+        name,
+        path: std::path::PathBuf::from(path),
+        template: WorkspaceTemplate::Custom(exported.template.id.clone()),
+        description,
+    };
+    let workspace = state
         .workspace_manager
-        .create_workspace(request)
+        .create_workspace_from_template(request, &exported.template)
         .await
+        .map_err(|e| e.to_string())?;
+
+    backups.schedule(&workspace.path).await;
+    Ok(WorkspaceCopy {
+        workspace,
+        template: exported,
+    })
+}
+
+/// Save an existing workspace's layout as a new custom template version
+#[tauri::command]
+pub async fn export_workspace_template(
+    request: ExportWorkspaceTemplateRequest,
+    state: State<'_, crate::AppState>,
+    templates: State<'_, TemplateManagerState>,
+) -> Result<ExportedWorkspaceTemplate, String> {
+    export_template(&state, &templates, request).await
+}
+
+/// List user-defined workspace templates with their versions
+#[tauri::command]
+pub async fn list_custom_workspace_templates(
+    state: State<'_, crate::AppState>,
+) -> Result<Vec<TemplateSummary>, String> {
+    state
+        .workspace_manager
+        .template_store()
+        .list()
         .map_err(|e| e.to_string())
 }
 
+/// Get a version of a custom workspace template, the latest by default
+#[tauri::command]
+pub async fn get_custom_workspace_template(
+    id: String,
+    version: Option<u32>,
+    state: State<'_, crate::AppState>,
+) -> Result<CustomWorkspaceTemplate, String> {
+    state
+        .workspace_manager
+        .template_store()
+        .load(&id, version)
+        .map_err(|e| e.to_string())
+}
+
+/// Save a custom workspace template as its next version
+#[tauri::command]
+pub async fn save_custom_workspace_template(
+    template: CustomWorkspaceTemplate,
+    state: State<'_, crate::AppState>,
+) -> Result<CustomWorkspaceTemplate, String> {
+    state
+        .workspace_manager
+        .template_store()
+        .save(template)
+        .map_err(|e| e.to_string())
+}
+
+async fn export_template(
+    state: &crate::AppState,
+    templates: &TemplateManagerState,
+    request: ExportWorkspaceTemplateRequest,
+) -> Result<ExportedWorkspaceTemplate, String> {
+    let workspace = state
+        .workspace_manager
+        .load_workspace(Path::new(&request.workspace_path))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let output_templates = {
+        let manager = templates.lock().map_err(|e| format!("Lock error: {}", e))?;
+        request
+            .output_template_ids
+            .iter()
+            .map(|id| {
+                manager
+                    .get_template(id)
+                    .cloned()
+                    .ok_or_else(|| format!("Template not found: {}", id))
+            })
+            .collect::<Result<Vec<_>, String>>()?
+    };
+    let seed_documents = request
+        .seed_documents
+        .unwrap_or_else(|| default_seed_documents(&workspace));
+    let name = request.name.unwrap_or_else(|| workspace.name.clone());
+    let description = request
+        .description
+        .unwrap_or_else(|| format!("Exported from workspace {}", workspace.name));
+
+    let (template, skipped) = CustomWorkspaceTemplate::export(
+        &workspace,
+        &template_id_for(&name),
+        &name,
+        &description,
+        &seed_documents,
+        output_templates,
+    )
+    .map_err(|e| e.to_string())?;
+    let template = state
+        .workspace_manager
+        .template_store()
+        .save(template)
+        .map_err(|e| e.to_string())?;
+
+    Ok(ExportedWorkspaceTemplate { template, skipped })
+}
+
+/// Files new workspaces are seeded with when none are chosen: the README
+fn default_seed_documents(workspace: &WorkspaceInfo) -> Vec<String> {
+    ["README.md"]
+        .iter()
+        .filter(|seed| workspace.path.join(seed).is_file())
+        .map(|seed| seed.to_string())
+        .collect()
+}
+
+/// Make a custom template's output templates available for document generation
+///
+/// Output templates already installed under the same ID are left as they are.
+fn install_output_templates(templates: &TemplateManagerState, custom: &CustomWorkspaceTemplate) {
+    let mut manager = match templates.lock() {
+        Ok(manager) => manager,
+        Err(e) => {
+            tracing::warn!("Failed to install output templates: {}", e);
+            return;
+        }
+    };
+    for output_template in &custom.output_templates {
+        if manager.get_template(&output_template.id).is_some() {
+            tracing::debug!(
+                "Output template {} is already installed",
+                output_template.id
+            );
+            continue;
+        }
+        if let Err(e) = manager.create_template(output_template.clone()) {
+            tracing::warn!(
+                "Failed to install output template {}: {}",
+                output_template.id,
+                e
+            );
+        }
+    }
+}
+
 /// Load workspace information from a path
 #[tauri::command]
 pub async fn load_workspace(
     path: String,
     state: State<'_, crate::AppState>,
+    backups: State<'_, WorkspaceBackupState>,
 ) -> Result<WorkspaceInfo, String> {
     let workspace_path = std::path::PathBuf::from(path);
 
-    state
+    let workspace = state
         .workspace_manager
         .load_workspace(&workspace_path)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+
+    // Opening a workspace starts its scheduled backups
+    backups.schedule(&workspace_path).await;
+    Ok(workspace)
 }
 
 /// Check if a path contains a valid workspace
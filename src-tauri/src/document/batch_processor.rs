@@ -6,7 +6,9 @@
 use crate::document::{
     ContentHash, FileProcessor, FileValidationResult, ImportProgress, ProgressTracker,
 };
+use crate::filesystem::errors::SecurityError;
 use crate::filesystem::security::path_validator::PathValidator;
+use crate::filesystem::security::quarantine::{QuarantineManager, QuarantineReason};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
@@ -541,16 +543,49 @@ impl BatchProcessor {
             sleep(delay).await;
         }
 
-        // Validate path security first
-        let validated_path = validator
-            .validate_import_path(file_path)
-            .with_context(|| format!("Path validation failed for {}", file_path.display()))?;
+        // Validate path security first, quarantining files rejected as suspicious
+        let validated_path = match validator.validate_import_path(file_path) {
+            Ok(path) => path,
+            Err(e) => {
+                let quarantined = QuarantineReason::from_security_error(&e).and_then(|reason| {
+                    QuarantineManager::global().quarantine_rejected_import(
+                        file_path,
+                        reason,
+                        "batch_import",
+                    )
+                });
+                if let Some(record) = quarantined {
+                    return Err(SecurityError::FileQuarantined {
+                        path: record.original_path.display().to_string(),
+                    }
+                    .into());
+                }
+                return Err(e).with_context(|| {
+                    format!("Path validation failed for {}", file_path.display())
+                });
+            }
+        };
 
         // Validate file integrity and structure
         let validation_result = FileProcessor::validate_file(&validated_path)
             .with_context(|| format!("File validation failed for {}", validated_path.display()))?;
 
         if !validation_result.is_valid {
+            validation_result.report_threat(&validated_path);
+            let quarantined =
+                QuarantineReason::from_validation(&validation_result).and_then(|reason| {
+                    QuarantineManager::global().quarantine_rejected_import(
+                        &validated_path,
+                        reason,
+                        "batch_import",
+                    )
+                });
+            if let Some(record) = quarantined {
+                return Err(SecurityError::FileQuarantined {
+                    path: record.original_path.display().to_string(),
+                }
+                .into());
+            }
             anyhow::bail!("File validation failed: {}", validation_result.message);
         }
 
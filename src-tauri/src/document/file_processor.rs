@@ -9,6 +9,9 @@ use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 
 use crate::document::{DocxParser, EnhancedMetadata, MetadataExtractor, PdfParser};
+use crate::filesystem::security::audit_logger::SecurityAuditor;
+use crate::filesystem::security::content_scanner::{ContentScanResult, ContentScanner};
+use crate::filesystem::security::safe_mode::SafeModeManager;
 
 /// File corruption check result
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -652,6 +655,17 @@ impl FileProcessor {
             return Ok(FileValidationResult::corrupted(corruption_result));
         }
 
+        // Scan for macros, scripts and embedded payloads
+        match ContentScanner::default().scan_file(path) {
+            Ok(scan) if scan.has_threats() => {
+                let summary = scan.summary().join("; ");
+                tracing::warn!("☣️  Active content in {}: {}", path.display(), summary);
+                return Ok(FileValidationResult::threat(corruption_result, scan));
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Content scan failed for {}: {}", path.display(), e),
+        }
+
         tracing::info!("✅ File validation passed: {}", path.display());
         Ok(FileValidationResult::valid(corruption_result))
     }
@@ -1148,6 +1162,9 @@ pub struct FileValidationResult {
     pub status: ValidationStatus,
     /// Corruption check details
     pub corruption_check: Option<CorruptionCheckResult>,
+    /// Active content found in the file
+    #[serde(default)]
+    pub content_scan: Option<ContentScanResult>,
     /// Validation message
     pub message: String,
 }
@@ -1157,6 +1174,7 @@ pub enum ValidationStatus {
     Valid,
     Corrupted,
     Invalid,
+    Threat,
 }
 
 impl FileValidationResult {
@@ -1166,6 +1184,7 @@ impl FileValidationResult {
             is_valid: true,
             status: ValidationStatus::Valid,
             corruption_check: Some(corruption_check),
+            content_scan: None,
             message: "File validation passed".to_string(),
         }
     }
@@ -1176,16 +1195,55 @@ impl FileValidationResult {
             is_valid: false,
             status: ValidationStatus::Corrupted,
             corruption_check: Some(corruption_check),
+            content_scan: None,
             message: "File appears to be corrupted".to_string(),
         }
     }
 
+    /// Create a result for a file carrying active content
+    pub fn threat(
+        corruption_check: CorruptionCheckResult,
+        content_scan: ContentScanResult,
+    ) -> Self {
+        Self {
+            is_valid: false,
+            status: ValidationStatus::Threat,
+            corruption_check: Some(corruption_check),
+            message: format!(
+                "File contains active content: {}",
+                content_scan.summary().join("; ")
+            ),
+            content_scan: Some(content_scan),
+        }
+    }
+
+    /// Record active content found while importing a file
+    ///
+    /// Validation only describes the file; callers that act on it report the
+    /// threat so previews and pre-import checks do not count toward safe mode.
+    pub fn report_threat(&self, path: &Path) {
+        let Some(scan) = self.content_scan.as_ref().filter(|scan| scan.has_threats()) else {
+            return;
+        };
+        let summary = scan.summary().join("; ");
+        SecurityAuditor::log_security_violation(
+            "CONTENT_THREAT_DETECTED",
+            &summary,
+            Some(path),
+            Some("content_scan"),
+            "HIGH",
+            None,
+        );
+        SafeModeManager::global().report_threat(path, &summary);
+    }
+
     /// Create an invalid file result
     pub fn invalid(message: String) -> Self {
         Self {
             is_valid: false,
             status: ValidationStatus::Invalid,
             corruption_check: None,
+            content_scan: None,
             message,
         }
     }
@@ -2,12 +2,17 @@
 // Document indexing system for fast document retrieval
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+use crate::document::metadata_store::{
+    DocumentMetadataRecord, DocumentMetadataStore, MetadataFilter, MetadataUpdate,
+};
+use crate::document::review_workflow::DocumentStatus;
 use crate::document::{EnhancedMetadata, MetadataExtractor};
 
 /// Document index entry
@@ -121,6 +126,8 @@ pub struct SearchFilter {
     pub has_code: Option<bool>,
     /// Keyword filter
     pub must_contain_keywords: Option<Vec<String>>,
+    /// Tag, custom field, owner and review date filter
+    pub metadata: Option<MetadataFilter>,
 }
 
 /// Search result
@@ -149,6 +156,8 @@ pub struct DocumentIndexer {
     /// Metadata extractor
     #[allow(dead_code)]
     metadata_extractor: MetadataExtractor,
+    /// Tags and custom metadata, kept beside the index
+    metadata_store: DocumentMetadataStore,
     /// Current index version
     index_version: u32,
 }
@@ -159,12 +168,14 @@ impl DocumentIndexer {
     pub fn new(index_dir: PathBuf) -> Result<Self> {
         // Ensure index directory exists
         fs::create_dir_all(&index_dir).context("Failed to create index directory")?;
+        let metadata_store = DocumentMetadataStore::open(&index_dir)?;
 
         let mut indexer = Self {
             index_dir,
             index: HashMap::new(),
             keyword_index: HashMap::new(),
             metadata_extractor: MetadataExtractor,
+            metadata_store,
             index_version: 1,
         };
 
@@ -215,6 +226,11 @@ impl DocumentIndexer {
             index_version: self.index_version,
         };
 
+        // Reattach custom metadata, including after the file was renamed
+        if self.metadata_store.attach(&entry, &self.index) {
+            self.metadata_store.save()?;
+        }
+
         // Update keyword index
         self.update_keyword_index(&id, &keywords);
 
@@ -270,6 +286,47 @@ impl DocumentIndexer {
         Ok(results)
     }
 
+    /// Search titles, file names, tags and custom fields, never document content
+    ///
+    /// For workspaces that only share metadata, so a match cannot reveal what a
+    /// document says. Filters on content structure and keywords are ignored.
+    pub fn search_metadata(&self, query: &str, filter: Option<SearchFilter>) -> Vec<SearchResult> {
+        let query_terms = self.tokenize_query(query);
+        let filter = filter.map(|filter| SearchFilter {
+            has_images: None,
+            has_tables: None,
+            has_code: None,
+            must_contain_keywords: None,
+            ..filter
+        });
+
+        let mut results: Vec<SearchResult> = self
+            .index
+            .values()
+            .filter(|entry| {
+                filter
+                    .as_ref()
+                    .is_none_or(|filter| self.matches_filter(entry, filter))
+            })
+            .filter_map(|entry| {
+                let score = self.calculate_metadata_score(entry, &query_terms);
+                (score > 0.0).then(|| SearchResult {
+                    document: entry.clone(),
+                    score,
+                    matching_sections: Vec::new(),
+                    snippets: Vec::new(),
+                })
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        results
+    }
+
     /// Get document by ID
     pub fn get_document(&self, id: &str) -> Option<&DocumentIndexEntry> {
         self.index.get(id)
@@ -288,6 +345,12 @@ impl DocumentIndexer {
                 }
             }
 
+            // A document with the same content indexed before this one was
+            // pruned takes over its metadata
+            if self.metadata_store.reattach_removed(&entry, &self.index) {
+                self.metadata_store.save()?;
+            }
+
             // Persist changes
             self.save_index()?;
             Ok(true)
@@ -296,6 +359,95 @@ impl DocumentIndexer {
         }
     }
 
+    /// Get document by file path
+    pub fn get_document_by_path(&self, path: &Path) -> Option<&DocumentIndexEntry> {
+        self.index.values().find(|entry| entry.path == path)
+    }
+
+    /// Move an indexed document to a new path, re-keying it under the ID derived from that path
+    pub fn relocate_document(
+        &mut self,
+        from: &Path,
+        to: &Path,
+    ) -> Result<Option<DocumentRelocation>> {
+        let Some(old_id) = self
+            .get_document_by_path(from)
+            .map(|entry| entry.id.clone())
+        else {
+            return Ok(None);
+        };
+        let new_id = self.generate_document_id(to);
+        if new_id != old_id && self.index.contains_key(&new_id) {
+            anyhow::bail!("{} is already indexed", to.display());
+        }
+
+        let mut entry = self
+            .index
+            .remove(&old_id)
+            .context("Indexed document disappeared during relocation")?;
+        for keyword in &entry.keywords {
+            if let Some(doc_set) = self.keyword_index.get_mut(keyword) {
+                doc_set.remove(&old_id);
+            }
+        }
+
+        entry.id = new_id.clone();
+        entry.path = to.to_path_buf();
+        if let Some(file_name) = to.file_name() {
+            entry.metadata.basic.file_name = file_name.to_string_lossy().to_string();
+        }
+        self.update_keyword_index(&new_id, &entry.keywords);
+        self.index.insert(new_id.clone(), entry);
+        self.save_index()?;
+        if self.metadata_store.rekey(&old_id, &new_id, to) {
+            self.metadata_store.save()?;
+        }
+
+        Ok(Some(DocumentRelocation {
+            old_id,
+            new_id,
+            old_path: from.to_path_buf(),
+            new_path: to.to_path_buf(),
+        }))
+    }
+
+    /// Get the tags and custom metadata of a document
+    pub fn get_metadata(&self, id: &str) -> Option<&DocumentMetadataRecord> {
+        self.metadata_store.get(id)
+    }
+
+    /// Change the tags and custom metadata of an indexed document
+    pub fn update_metadata(
+        &mut self,
+        id: &str,
+        update: MetadataUpdate,
+    ) -> Result<DocumentMetadataRecord> {
+        let entry = self
+            .index
+            .get(id)
+            .with_context(|| format!("Document not indexed: {}", id))?;
+        self.metadata_store.update(entry, update)
+    }
+
+    /// Move an indexed document to a review state
+    pub fn set_document_status(
+        &mut self,
+        id: &str,
+        status: DocumentStatus,
+        review_date: Option<DateTime<Utc>>,
+    ) -> Result<DocumentMetadataRecord> {
+        let entry = self
+            .index
+            .get(id)
+            .with_context(|| format!("Document not indexed: {}", id))?;
+        self.metadata_store.set_status(entry, status, review_date)
+    }
+
+    /// Get the workspace metadata store
+    pub fn metadata_store(&self) -> &DocumentMetadataStore {
+        &self.metadata_store
+    }
+
     /// Get all indexed documents
     pub fn get_all_documents(&self) -> Vec<&DocumentIndexEntry> {
         self.index.values().collect()
@@ -697,6 +849,13 @@ impl DocumentIndexer {
             }
         }
 
+        // Custom metadata filter
+        if let Some(ref metadata) = filter.metadata {
+            if !metadata.matches(self.metadata_store.get(&entry.id)) {
+                return false;
+            }
+        }
+
         // Size filters
         if let Some(min_size) = filter.min_size {
             if entry.metadata.basic.file_size < min_size {
@@ -784,6 +943,51 @@ impl DocumentIndexer {
         (score / 10.0).min(1.0)
     }
 
+    fn calculate_metadata_score(&self, entry: &DocumentIndexEntry, query_terms: &[String]) -> f64 {
+        if query_terms.is_empty() {
+            return 0.0;
+        }
+
+        let title = entry.title.to_lowercase();
+        let file_name = entry
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let record = self.metadata_store.get(&entry.id);
+        let mut score = 0.0;
+        for term in query_terms {
+            let term = term.as_str();
+            let mut term_score = 0.0;
+            if title.contains(term) {
+                term_score += 3.0;
+            }
+            if file_name.contains(term) {
+                term_score += 1.0;
+            }
+            if let Some(record) = record {
+                if record
+                    .tags
+                    .iter()
+                    .any(|tag| tag.name.to_lowercase().contains(term))
+                {
+                    term_score += 2.0;
+                }
+                if record
+                    .fields
+                    .values()
+                    .any(|value| value.to_lowercase().contains(term))
+                {
+                    term_score += 1.0;
+                }
+            }
+            score += term_score / query_terms.len() as f64;
+        }
+
+        // Normalize score to 0-1 range
+        (score / 10.0).min(1.0)
+    }
+
     fn find_matching_sections(
         &self,
         entry: &DocumentIndexEntry,
@@ -862,6 +1066,15 @@ impl DocumentIndexer {
     }
 }
 
+/// An indexed document that moved, with the IDs it was stored under before and after
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentRelocation {
+    pub old_id: String,
+    pub new_id: String,
+    pub old_path: PathBuf,
+    pub new_path: PathBuf,
+}
+
 /// Index statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IndexStats {
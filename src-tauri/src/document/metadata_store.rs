@@ -0,0 +1,546 @@
+// src-tauri/src/document/metadata_store.rs
+// Workspace-level custom metadata for indexed documents
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::document::review_workflow::DocumentStatus;
+use crate::document::DocumentIndexEntry;
+use crate::filesystem::atomic_write::write_atomically;
+use crate::workspace::smart_organizer::TagType;
+
+/// Metadata database file, stored beside the document index
+pub const METADATA_STORE_FILE: &str = "document_metadata.json";
+
+/// A tag accepted for a document
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentTag {
+    pub name: String,
+    pub tag_type: TagType,
+}
+
+impl DocumentTag {
+    pub fn custom(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            tag_type: TagType::Custom,
+        }
+    }
+}
+
+/// Tags, custom fields, owners and review date of one document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentMetadataRecord {
+    pub document_id: String,
+    /// Content hash of the document when the record was last attached to it,
+    /// used to find the record again after the file is renamed
+    pub content_hash: String,
+    /// Last known path, for display only
+    pub path: PathBuf,
+    #[serde(default)]
+    pub tags: Vec<DocumentTag>,
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+    #[serde(default)]
+    pub owners: Vec<String>,
+    pub review_date: Option<DateTime<Utc>>,
+    /// Days between reviews of a controlled document
+    #[serde(default)]
+    pub review_interval_days: Option<u32>,
+    /// Review state; only controlled documents have one
+    #[serde(default)]
+    pub status: Option<DocumentStatus>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DocumentMetadataRecord {
+    fn new(entry: &DocumentIndexEntry) -> Self {
+        Self {
+            document_id: entry.id.clone(),
+            content_hash: entry.content_hash.clone(),
+            path: entry.path.clone(),
+            tags: Vec::new(),
+            fields: BTreeMap::new(),
+            owners: Vec::new(),
+            review_date: None,
+            review_interval_days: None,
+            status: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    pub fn has_tag(&self, name: &str) -> bool {
+        self.tags
+            .iter()
+            .any(|tag| tag.name.eq_ignore_ascii_case(name))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && self.fields.is_empty()
+            && self.owners.is_empty()
+            && self.review_date.is_none()
+            && self.review_interval_days.is_none()
+            && self.status.is_none()
+    }
+}
+
+/// A change to a document's metadata; fields left empty are not touched
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetadataUpdate {
+    #[serde(default)]
+    pub add_tags: Vec<DocumentTag>,
+    #[serde(default)]
+    pub remove_tags: Vec<String>,
+    #[serde(default)]
+    pub set_fields: BTreeMap<String, String>,
+    #[serde(default)]
+    pub remove_fields: Vec<String>,
+    /// Replaces the owner list when present
+    pub owners: Option<Vec<String>>,
+    /// Replaces the review date when present
+    pub review_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub clear_review_date: bool,
+    /// Replaces the review interval when present
+    pub review_interval_days: Option<u32>,
+    #[serde(default)]
+    pub clear_review_interval: bool,
+}
+
+/// Criteria on custom metadata, shared by index and vector searches
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetadataFilter {
+    /// Documents must carry every one of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Documents must have each field set to the given value
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+    /// Documents must be owned by this person
+    pub owner: Option<String>,
+    /// Documents must be due for review before this date
+    pub review_due_before: Option<DateTime<Utc>>,
+    /// Documents must be in this review state
+    pub status: Option<DocumentStatus>,
+}
+
+impl MetadataFilter {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && self.fields.is_empty()
+            && self.owner.is_none()
+            && self.review_due_before.is_none()
+            && self.status.is_none()
+    }
+
+    pub fn matches(&self, record: Option<&DocumentMetadataRecord>) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        let Some(record) = record else {
+            return false;
+        };
+
+        if !self.tags.iter().all(|tag| record.has_tag(tag)) {
+            return false;
+        }
+        if !self
+            .fields
+            .iter()
+            .all(|(key, value)| record.fields.get(key) == Some(value))
+        {
+            return false;
+        }
+        if let Some(owner) = &self.owner {
+            if !record.owners.iter().any(|o| o.eq_ignore_ascii_case(owner)) {
+                return false;
+            }
+        }
+        if let Some(due_before) = self.review_due_before {
+            match record.review_date {
+                Some(date) if date < due_before => {}
+                _ => return false,
+            }
+        }
+        if self.status.is_some() && record.status != self.status {
+            return false;
+        }
+
+        true
+    }
+}
+
+/// Custom metadata keyed by document ID rather than path
+#[derive(Debug, Clone)]
+pub struct DocumentMetadataStore {
+    path: PathBuf,
+    records: HashMap<String, DocumentMetadataRecord>,
+}
+
+impl DocumentMetadataStore {
+    /// Open the store kept in the given directory
+    pub fn open(dir: &Path) -> Result<Self> {
+        let path = dir.join(METADATA_STORE_FILE);
+        let records = if path.exists() {
+            let data = fs::read_to_string(&path).context("Failed to read metadata store")?;
+            let records: Vec<DocumentMetadataRecord> =
+                serde_json::from_str(&data).context("Failed to parse metadata store")?;
+            records
+                .into_iter()
+                .map(|record| (record.document_id.clone(), record))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self { path, records })
+    }
+
+    pub fn get(&self, document_id: &str) -> Option<&DocumentMetadataRecord> {
+        self.records.get(document_id)
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &DocumentMetadataRecord> {
+        self.records.values()
+    }
+
+    /// Apply an update to a document, creating its record if needed
+    pub fn update(
+        &mut self,
+        entry: &DocumentIndexEntry,
+        update: MetadataUpdate,
+    ) -> Result<DocumentMetadataRecord> {
+        let record = self
+            .records
+            .entry(entry.id.clone())
+            .or_insert_with(|| DocumentMetadataRecord::new(entry));
+
+        record.tags.retain(|tag| {
+            !update
+                .remove_tags
+                .iter()
+                .any(|r| r.eq_ignore_ascii_case(&tag.name))
+        });
+        for tag in update.add_tags {
+            if !tag.name.trim().is_empty() && !record.has_tag(&tag.name) {
+                record.tags.push(tag);
+            }
+        }
+        for key in &update.remove_fields {
+            record.fields.remove(key);
+        }
+        record.fields.extend(update.set_fields);
+        if let Some(owners) = update.owners {
+            record.owners = owners;
+        }
+        if update.clear_review_date {
+            record.review_date = None;
+        }
+        if let Some(review_date) = update.review_date {
+            record.review_date = Some(review_date);
+        }
+        if update.clear_review_interval {
+            record.review_interval_days = None;
+        }
+        if let Some(days) = update.review_interval_days {
+            record.review_interval_days = Some(days);
+        }
+        record.content_hash = entry.content_hash.clone();
+        record.path = entry.path.clone();
+        record.updated_at = Utc::now();
+
+        let record = record.clone();
+        if record.is_empty() {
+            self.records.remove(&entry.id);
+        }
+        self.save()?;
+        Ok(record)
+    }
+
+    /// Move a document to a review state, optionally scheduling its next review
+    ///
+    /// Transition rules are enforced by `review_workflow`, which is the only caller.
+    pub fn set_status(
+        &mut self,
+        entry: &DocumentIndexEntry,
+        status: DocumentStatus,
+        review_date: Option<DateTime<Utc>>,
+    ) -> Result<DocumentMetadataRecord> {
+        let record = self
+            .records
+            .entry(entry.id.clone())
+            .or_insert_with(|| DocumentMetadataRecord::new(entry));
+        record.status = Some(status);
+        if review_date.is_some() {
+            record.review_date = review_date;
+        }
+        record.content_hash = entry.content_hash.clone();
+        record.path = entry.path.clone();
+        record.updated_at = Utc::now();
+
+        let record = record.clone();
+        self.save()?;
+        Ok(record)
+    }
+
+    /// Keep a record attached to its document when the document is (re)indexed
+    ///
+    /// A document indexed under an ID with no record picks up an orphaned record
+    /// with the same content hash: one whose document is no longer indexed, or
+    /// is still indexed but gone from disk because the new path is where it was
+    /// renamed to. Returns whether anything changed.
+    pub fn attach(
+        &mut self,
+        entry: &DocumentIndexEntry,
+        indexed: &HashMap<String, DocumentIndexEntry>,
+    ) -> bool {
+        if let Some(record) = self.records.get_mut(&entry.id) {
+            let changed = record.content_hash != entry.content_hash || record.path != entry.path;
+            record.content_hash = entry.content_hash.clone();
+            record.path = entry.path.clone();
+            return changed;
+        }
+
+        let orphan = self
+            .records
+            .values()
+            .find(|record| {
+                record.content_hash == entry.content_hash
+                    && indexed
+                        .get(&record.document_id)
+                        .map_or(true, |stale| !stale.path.exists())
+            })
+            .map(|record| record.document_id.clone());
+        match orphan {
+            Some(old_id) => {
+                self.rekey(&old_id, &entry.id, &entry.path);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Hand the record of a document removed from the index to an indexed
+    /// document with the same content and no record of its own, which is what
+    /// a rename looks like when the new path was indexed first. Returns
+    /// whether a record moved.
+    pub fn reattach_removed(
+        &mut self,
+        removed: &DocumentIndexEntry,
+        indexed: &HashMap<String, DocumentIndexEntry>,
+    ) -> bool {
+        if !self.records.contains_key(&removed.id) {
+            return false;
+        }
+        let successor = indexed.values().find(|entry| {
+            entry.id != removed.id
+                && entry.content_hash == removed.content_hash
+                && !self.records.contains_key(&entry.id)
+        });
+        match successor {
+            Some(entry) => self.rekey(&removed.id, &entry.id, &entry.path),
+            None => false,
+        }
+    }
+
+    /// Move a record to the ID a document has after being moved
+    pub fn rekey(&mut self, old_id: &str, new_id: &str, new_path: &Path) -> bool {
+        let Some(mut record) = self.records.remove(old_id) else {
+            return false;
+        };
+        record.document_id = new_id.to_string();
+        record.path = new_path.to_path_buf();
+        self.records.insert(new_id.to_string(), record);
+        true
+    }
+
+    /// IDs of the documents matching a filter
+    pub fn matching_documents(&self, filter: &MetadataFilter) -> HashSet<String> {
+        self.records
+            .values()
+            .filter(|record| filter.matches(Some(record)))
+            .map(|record| record.document_id.clone())
+            .collect()
+    }
+
+    /// Every tag in use with the number of documents carrying it
+    pub fn tag_counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for tag in self.records.values().flat_map(|record| &record.tags) {
+            *counts.entry(tag.name.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let records: Vec<&DocumentMetadataRecord> = self.records.values().collect();
+        let data =
+            serde_json::to_string_pretty(&records).context("Failed to serialize metadata")?;
+
+        write_atomically(&self.path, data.as_bytes()).context("Failed to write metadata store")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::{DocumentIndexer, SearchFilter};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_metadata_follows_renamed_document() {
+        let temp_dir = TempDir::new().unwrap();
+        let original = temp_dir.path().join("contract.txt");
+        fs::write(&original, "Supplier agreement covering delivery terms.").unwrap();
+
+        let mut indexer = DocumentIndexer::new(temp_dir.path().join("index")).unwrap();
+        let entry = indexer.index_document(&original).await.unwrap();
+        indexer
+            .update_metadata(
+                &entry.id,
+                MetadataUpdate {
+                    add_tags: vec![DocumentTag::custom("legal")],
+                    set_fields: BTreeMap::from([("status".to_string(), "draft".to_string())]),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+
+        // Renamed outside the app: the old entry disappears, the new path is indexed
+        let renamed = temp_dir.path().join("supplier-contract.txt");
+        fs::rename(&original, &renamed).unwrap();
+        indexer.remove_document(&entry.id).unwrap();
+        let renamed_entry = indexer.index_document(&renamed).await.unwrap();
+
+        assert_ne!(renamed_entry.id, entry.id);
+        let record = indexer.get_metadata(&renamed_entry.id).unwrap();
+        assert!(record.has_tag("LEGAL"));
+        assert_eq!(record.path, renamed);
+        assert!(indexer.get_metadata(&entry.id).is_none());
+
+        // Survives reopening the index
+        let reopened = DocumentIndexer::new(temp_dir.path().join("index")).unwrap();
+        let record = reopened.get_metadata(&renamed_entry.id).unwrap();
+        assert_eq!(
+            record.fields.get("status").map(String::as_str),
+            Some("draft")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metadata_follows_document_indexed_before_the_old_path_is_pruned() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut indexer = DocumentIndexer::new(temp_dir.path().join("index")).unwrap();
+        let tag = |indexer: &mut DocumentIndexer, id: &str, name: &str| {
+            indexer
+                .update_metadata(
+                    id,
+                    MetadataUpdate {
+                        add_tags: vec![DocumentTag::custom(name)],
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+        };
+
+        // Renamed: the new path is indexed while the old entry is still present
+        let original = temp_dir.path().join("contract.txt");
+        fs::write(&original, "Supplier agreement covering delivery terms.").unwrap();
+        let entry = indexer.index_document(&original).await.unwrap();
+        tag(&mut indexer, &entry.id, "legal");
+        let renamed = temp_dir.path().join("supplier-contract.txt");
+        fs::rename(&original, &renamed).unwrap();
+        let renamed_entry = indexer.index_document(&renamed).await.unwrap();
+        indexer.remove_document(&entry.id).unwrap();
+        assert!(indexer
+            .get_metadata(&renamed_entry.id)
+            .unwrap()
+            .has_tag("legal"));
+
+        // Copied, then the original deleted: the copy takes over once the
+        // original leaves the index
+        let report = temp_dir.path().join("report.txt");
+        fs::write(&report, "Quarterly figures for the board.").unwrap();
+        let report_entry = indexer.index_document(&report).await.unwrap();
+        tag(&mut indexer, &report_entry.id, "finance");
+        let copy = temp_dir.path().join("report-copy.txt");
+        fs::copy(&report, &copy).unwrap();
+        let copy_entry = indexer.index_document(&copy).await.unwrap();
+        assert!(indexer.get_metadata(&copy_entry.id).is_none());
+        fs::remove_file(&report).unwrap();
+        indexer.remove_document(&report_entry.id).unwrap();
+        let record = indexer.get_metadata(&copy_entry.id).unwrap();
+        assert!(record.has_tag("finance"));
+        assert_eq!(record.path, copy);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut indexer = DocumentIndexer::new(temp_dir.path().join("index")).unwrap();
+
+        let mut ids = Vec::new();
+        for (name, status) in [("a.txt", "final"), ("b.txt", "draft")] {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, format!("Quarterly report {} version", status)).unwrap();
+            let entry = indexer.index_document(&path).await.unwrap();
+            indexer
+                .update_metadata(
+                    &entry.id,
+                    MetadataUpdate {
+                        add_tags: vec![DocumentTag::custom("report")],
+                        set_fields: BTreeMap::from([("status".to_string(), status.to_string())]),
+                        owners: Some(vec!["Dana".to_string()]),
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            ids.push(entry.id);
+        }
+
+        let filter = MetadataFilter {
+            tags: vec!["report".to_string()],
+            fields: BTreeMap::from([("status".to_string(), "final".to_string())]),
+            owner: Some("dana".to_string()),
+            review_due_before: None,
+            status: None,
+        };
+        let matching = indexer.metadata_store().matching_documents(&filter);
+        assert_eq!(matching, HashSet::from([ids[0].clone()]));
+        assert_eq!(
+            indexer.metadata_store().tag_counts().get("report"),
+            Some(&2)
+        );
+
+        let results = indexer
+            .search(
+                "quarterly",
+                Some(SearchFilter {
+                    metadata: Some(filter),
+                    ..Default::default()
+                }),
+            )
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].document.id, ids[0]);
+
+        // Removing the last piece of metadata drops the record
+        indexer
+            .update_metadata(
+                &ids[1],
+                MetadataUpdate {
+                    remove_tags: vec!["report".to_string()],
+                    remove_fields: vec!["status".to_string()],
+                    owners: Some(Vec::new()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert!(indexer.get_metadata(&ids[1]).is_none());
+    }
+}
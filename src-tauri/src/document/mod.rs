@@ -15,11 +15,13 @@ pub mod format_converters;
 pub mod import_errors;
 pub mod indexer;
 pub mod metadata_extractor;
+pub mod metadata_store;
 pub mod output_generator;
 pub mod pdf_parser;
 pub mod progress_persistence;
 pub mod progress_tracker;
 pub mod relationship_analyzer;
+pub mod review_workflow;
 pub mod structure_analyzer;
 pub mod style_analyzer;
 pub mod style_learner;
@@ -61,11 +63,15 @@ pub use format_converters::{
 };
 #[allow(unused_imports)]
 pub use indexer::{
-    DocumentIndexEntry, DocumentIndexer, IndexDocumentSection, IndexStats, SearchFilter,
-    SearchResult,
+    DocumentIndexEntry, DocumentIndexer, DocumentRelocation, IndexDocumentSection, IndexStats,
+    SearchFilter, SearchResult,
 };
 pub use metadata_extractor::*;
 #[allow(unused_imports)]
+pub use metadata_store::{
+    DocumentMetadataRecord, DocumentMetadataStore, DocumentTag, MetadataFilter, MetadataUpdate,
+};
+#[allow(unused_imports)]
 pub use output_generator::{
     GenerationSummary, OutputGenerationConfig, OutputGenerationResult, OutputGenerator,
     SourceContent, SourceContentType,
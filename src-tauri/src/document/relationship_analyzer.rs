@@ -191,6 +191,7 @@ pub struct RelationshipAnalysisMetadata {
 }
 
 /// Cross-document relationship analyzer
+#[derive(Clone)]
 pub struct RelationshipAnalyzer {
     config: RelationshipConfig,
     embedding_engine: Option<EmbeddingEngine>,
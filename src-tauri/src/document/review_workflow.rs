@@ -0,0 +1,396 @@
+// src-tauri/src/document/review_workflow.rs
+// Ownership, review schedule and approval states for controlled documents
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use crate::document::indexer::DocumentIndexer;
+use crate::document::metadata_store::{DocumentMetadataRecord, DocumentMetadataStore};
+use crate::filesystem::security::audit_logger::SecurityAuditor;
+use crate::workspace::usage_log::workspace_root_of;
+
+/// Folder that only approved documents may be moved into
+pub const APPROVED_DIR: &str = "outputs/approved";
+
+/// Review state of a controlled document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentStatus {
+    Draft,
+    InReview,
+    Approved,
+    Retired,
+}
+
+impl DocumentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DocumentStatus::Draft => "draft",
+            DocumentStatus::InReview => "in_review",
+            DocumentStatus::Approved => "approved",
+            DocumentStatus::Retired => "retired",
+        }
+    }
+
+    /// Whether a document may move from `current` to `next`
+    ///
+    /// A document enters control as a draft. Approval goes through review,
+    /// approved documents go back to review or draft to be revised, and any
+    /// state can be retired; a retired document can only be reopened as a draft.
+    pub fn can_transition(current: Option<DocumentStatus>, next: DocumentStatus) -> bool {
+        use DocumentStatus::*;
+        matches!(
+            (current, next),
+            (None, Draft)
+                | (Some(Draft), InReview)
+                | (Some(InReview), Approved)
+                | (Some(InReview), Draft)
+                | (Some(Approved), InReview)
+                | (Some(Approved), Draft)
+                | (Some(Draft | InReview | Approved), Retired)
+                | (Some(Retired), Draft)
+        )
+    }
+}
+
+/// A recorded change of review state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusTransition {
+    pub document_id: String,
+    pub path: PathBuf,
+    pub from: Option<DocumentStatus>,
+    pub to: DocumentStatus,
+    pub actor: String,
+    pub comment: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// A controlled document whose next review date has passed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverdueReview {
+    pub document_id: String,
+    pub path: PathBuf,
+    pub owners: Vec<String>,
+    pub status: DocumentStatus,
+    pub review_date: DateTime<Utc>,
+    pub days_overdue: i64,
+}
+
+/// Move a document to a new review state and record the transition in the audit log
+///
+/// Documents need an owner before they can go into review, and a review interval
+/// before they can be approved; approval schedules the next review that far ahead.
+pub fn transition_status(
+    indexer: &mut DocumentIndexer,
+    document_id: &str,
+    next: DocumentStatus,
+    actor: &str,
+    comment: Option<&str>,
+) -> Result<(DocumentMetadataRecord, StatusTransition)> {
+    let entry = indexer
+        .get_document(document_id)
+        .with_context(|| format!("Document not indexed: {}", document_id))?;
+    let path = entry.path.clone();
+    let record = indexer.get_metadata(document_id);
+    let current = record.and_then(|record| record.status);
+
+    if !DocumentStatus::can_transition(current, next) {
+        bail!(
+            "Cannot move {} from {} to {}",
+            path.display(),
+            current.map_or("uncontrolled", |status| status.as_str()),
+            next.as_str()
+        );
+    }
+
+    let has_owner = record.is_some_and(|record| !record.owners.is_empty());
+    if matches!(next, DocumentStatus::InReview | DocumentStatus::Approved) && !has_owner {
+        bail!(
+            "{} needs an owner before it can be {}",
+            path.display(),
+            if next == DocumentStatus::Approved {
+                "approved"
+            } else {
+                "reviewed"
+            }
+        );
+    }
+
+    let now = Utc::now();
+    let next_review = if next == DocumentStatus::Approved {
+        let days = record
+            .and_then(|record| record.review_interval_days)
+            .filter(|days| *days > 0)
+            .with_context(|| {
+                format!(
+                    "{} needs a review interval before it can be approved",
+                    path.display()
+                )
+            })?;
+        Some(now + Duration::days(i64::from(days)))
+    } else {
+        None
+    };
+
+    let record = indexer.set_document_status(document_id, next, next_review)?;
+    let transition = StatusTransition {
+        document_id: document_id.to_string(),
+        path,
+        from: current,
+        to: next,
+        actor: actor.to_string(),
+        comment: comment.map(str::to_string),
+        timestamp: now,
+    };
+    SecurityAuditor::log_document_status_change(
+        &transition.path,
+        document_id,
+        current.map(|status| status.as_str()),
+        next.as_str(),
+        actor,
+        comment,
+    );
+
+    Ok((record, transition))
+}
+
+/// Controlled documents past their review date, most overdue first
+pub fn overdue_reviews(store: &DocumentMetadataStore, now: DateTime<Utc>) -> Vec<OverdueReview> {
+    let mut overdue: Vec<OverdueReview> = store
+        .records()
+        .filter_map(|record| {
+            let status = record.status.filter(|s| *s != DocumentStatus::Retired)?;
+            let review_date = record.review_date.filter(|date| *date < now)?;
+            Some(OverdueReview {
+                document_id: record.document_id.clone(),
+                path: record.path.clone(),
+                owners: record.owners.clone(),
+                status,
+                review_date,
+                days_overdue: (now - review_date).num_days(),
+            })
+        })
+        .collect();
+    overdue.sort_by_key(|review| review.review_date);
+    overdue
+}
+
+/// Whether a path lies in the workspace's approved outputs folder
+///
+/// Both paths are resolved and compared without regard to case, so `..`
+/// segments, symlinks and case-insensitive filesystems cannot route a document
+/// around the approval gate.
+pub fn is_approved_location(workspace_path: &Path, path: &Path) -> bool {
+    let approved = fold_case(&resolve(&workspace_path.join(APPROVED_DIR)));
+    fold_case(&resolve(path)).starts_with(approved)
+}
+
+/// Whether putting a file at `destination` publishes it, i.e. the destination
+/// is the approved outputs folder of whichever workspace contains it
+pub fn is_publication(destination: &Path) -> bool {
+    let destination = resolve(destination);
+    workspace_root_of(&destination)
+        .is_some_and(|workspace_path| is_approved_location(&workspace_path, &destination))
+}
+
+/// Refuse to publish a document into `outputs/approved` unless it is approved
+pub fn ensure_publishable(indexer: &DocumentIndexer, path: &Path) -> Result<()> {
+    let status = indexer
+        .get_document_by_path(path)
+        .and_then(|entry| indexer.get_metadata(&entry.id))
+        .and_then(|record| record.status);
+    if status != Some(DocumentStatus::Approved) {
+        bail!(
+            "{} must be approved before it is moved to {} (current state: {})",
+            path.display(),
+            APPROVED_DIR,
+            status.map_or("uncontrolled", |status| status.as_str())
+        );
+    }
+    Ok(())
+}
+
+/// Drop `.` and `..` segments, then follow symlinks through the part of the
+/// path that exists
+fn resolve(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+
+    let mut missing = Vec::new();
+    let mut existing = normalized.as_path();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return missing
+                .iter()
+                .rev()
+                .fold(canonical, |path, name| path.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return normalized,
+        }
+    }
+}
+
+fn fold_case(path: &Path) -> PathBuf {
+    PathBuf::from(path.to_string_lossy().to_lowercase())
+}
+
+/// Remembers which overdue reviews were already raised so owners get one reminder a day
+#[derive(Debug, Default)]
+pub struct ReviewReminders {
+    notified: HashMap<String, NaiveDate>,
+}
+
+impl ReviewReminders {
+    /// Overdue reviews not yet raised today
+    pub fn due(&mut self, overdue: Vec<OverdueReview>, now: DateTime<Utc>) -> Vec<OverdueReview> {
+        let today = now.date_naive();
+        self.notified
+            .retain(|document_id, _| overdue.iter().any(|r| &r.document_id == document_id));
+        overdue
+            .into_iter()
+            .filter(|review| self.notified.insert(review.document_id.clone(), today) != Some(today))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::metadata_store::MetadataUpdate;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_review_workflow() {
+        let temp_dir = TempDir::new().unwrap();
+        let policy = temp_dir.path().join("retention-policy.md");
+        fs::write(
+            &policy,
+            "# Retention policy\n\nRecords are kept for seven years.",
+        )
+        .unwrap();
+
+        let mut indexer = DocumentIndexer::new(temp_dir.path().join("index")).unwrap();
+        let id = indexer.index_document(&policy).await.unwrap().id;
+
+        // Must enter control as a draft, and needs an owner to go into review
+        assert!(
+            transition_status(&mut indexer, &id, DocumentStatus::Approved, "sam", None).is_err()
+        );
+        transition_status(&mut indexer, &id, DocumentStatus::Draft, "sam", None).unwrap();
+        assert!(
+            transition_status(&mut indexer, &id, DocumentStatus::InReview, "sam", None).is_err()
+        );
+        assert!(ensure_publishable(&indexer, &policy).is_err());
+
+        indexer
+            .update_metadata(
+                &id,
+                MetadataUpdate {
+                    owners: Some(vec!["sam".to_string()]),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        transition_status(&mut indexer, &id, DocumentStatus::InReview, "sam", None).unwrap();
+
+        // Approval needs a review interval and schedules the next review
+        assert!(
+            transition_status(&mut indexer, &id, DocumentStatus::Approved, "lee", None).is_err()
+        );
+        indexer
+            .update_metadata(
+                &id,
+                MetadataUpdate {
+                    review_interval_days: Some(90),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        let (record, transition) = transition_status(
+            &mut indexer,
+            &id,
+            DocumentStatus::Approved,
+            "lee",
+            Some("Checked against legal requirements"),
+        )
+        .unwrap();
+        assert_eq!(transition.from, Some(DocumentStatus::InReview));
+        assert_eq!(record.status, Some(DocumentStatus::Approved));
+        let next_review = record.review_date.unwrap();
+        assert_eq!((next_review - Utc::now()).num_days(), 89);
+        ensure_publishable(&indexer, &policy).unwrap();
+
+        // Overdue once the review date passes, reminded once a day
+        let later = next_review + Duration::days(3);
+        let overdue = overdue_reviews(indexer.metadata_store(), later);
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(overdue[0].days_overdue, 3);
+        assert!(overdue_reviews(indexer.metadata_store(), Utc::now()).is_empty());
+
+        let mut reminders = ReviewReminders::default();
+        assert_eq!(reminders.due(overdue.clone(), later).len(), 1);
+        assert!(reminders.due(overdue.clone(), later).is_empty());
+        assert_eq!(reminders.due(overdue, later + Duration::days(1)).len(), 1);
+
+        // Retired documents are neither publishable nor reviewed
+        transition_status(&mut indexer, &id, DocumentStatus::Retired, "sam", None).unwrap();
+        assert!(ensure_publishable(&indexer, &policy).is_err());
+        assert!(overdue_reviews(indexer.metadata_store(), later).is_empty());
+        assert!(
+            transition_status(&mut indexer, &id, DocumentStatus::Approved, "sam", None).is_err()
+        );
+    }
+
+    #[test]
+    fn test_approved_location_is_resolved_before_matching() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join(APPROVED_DIR)).unwrap();
+        fs::create_dir_all(root.join("notes")).unwrap();
+
+        assert!(is_approved_location(
+            root,
+            &root.join(APPROVED_DIR).join("a.md")
+        ));
+        assert!(is_approved_location(
+            root,
+            &root.join("notes/../outputs/approved/a.md")
+        ));
+        assert!(is_approved_location(
+            root,
+            &root.join("Outputs/APPROVED/a.md")
+        ));
+        assert!(!is_approved_location(
+            root,
+            &root.join("outputs/approved/../drafts/a.md")
+        ));
+        assert!(!is_approved_location(root, &root.join("notes/a.md")));
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root.join(APPROVED_DIR), root.join("notes/shortcut"))
+                .unwrap();
+            assert!(is_approved_location(
+                root,
+                &root.join("notes/shortcut/a.md")
+            ));
+        }
+    }
+}
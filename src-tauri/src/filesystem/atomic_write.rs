@@ -0,0 +1,65 @@
+// src-tauri/src/filesystem/atomic_write.rs
+// Crash-safe file replacement shared by the stores that persist app state
+
+use anyhow::{Context, Result};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Write a file through a temporary sibling and rename it into place
+///
+/// Readers and a crash mid-write leave either the old or the new contents,
+/// never a truncated file. The temporary name is unique per call so
+/// concurrent writers of the same file do not clobber each other's data.
+pub(crate) fn write_atomically(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    let temp = temp_path(path);
+    let written = File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(data)?;
+            file.sync_all()
+        })
+        .with_context(|| format!("Failed to write {}", temp.display()))
+        .and_then(|_| {
+            fs::rename(&temp, path).with_context(|| format!("Failed to replace {}", path.display()))
+        });
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".tmp-{}", uuid::Uuid::new_v4()));
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_replaces_contents_without_leaving_temporary_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("state").join("session.json");
+
+        write_atomically(&path, b"first").unwrap();
+        write_atomically(&path, b"second").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        let entries: Vec<_> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, vec![std::ffi::OsString::from("session.json")]);
+    }
+}
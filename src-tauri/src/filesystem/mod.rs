@@ -1,5 +1,6 @@
 // src-tauri/src/filesystem/mod.rs
 
+pub mod atomic_write;
 pub mod errors;
 pub mod event_persistence;
 pub mod event_processor;
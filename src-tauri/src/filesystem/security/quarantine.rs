@@ -0,0 +1,553 @@
+// src-tauri/src/filesystem/security/quarantine.rs
+// Quarantine for imports rejected as suspicious, driven by the security config flags
+
+use crate::app_config::types::SecurityConfig as AppSecurityConfig;
+use crate::document::{ContentHash, CorruptionCheckResult, FileValidationResult};
+use crate::filesystem::errors::SecurityError;
+use crate::filesystem::security::audit_logger::{SecurityAuditor, SecurityLevel};
+use crate::filesystem::security::content_scanner::ContentFinding;
+use crate::filesystem::security::safe_mode::SafeModeManager;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Extension of quarantined file contents; the sidecar record sits next to it as `<id>.json`
+const PAYLOAD_EXTENSION: &str = "quarantined";
+
+/// Bytes of a quarantined file shown when it is inspected
+const INSPECTION_HEADER_LEN: usize = 64;
+
+static QUARANTINE_MANAGER: OnceCell<QuarantineManager> = OnceCell::new();
+
+/// Why a file was quarantined
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QuarantineReason {
+    /// The file's header does not match its extension
+    MagicNumberMismatch { details: String },
+    /// The file's MIME type is not allowed
+    BlockedMimeType { details: String },
+    /// The corruption check rejected the file
+    Corrupted {
+        detected_type: Option<String>,
+        expected_type: Option<String>,
+        details: Vec<String>,
+    },
+    /// The content scanner found macros, scripts or embedded payloads
+    ActiveContent { findings: Vec<ContentFinding> },
+}
+
+impl QuarantineReason {
+    /// Reason to quarantine a file rejected by path validation, if the rejection marks it as suspicious
+    pub fn from_security_error(error: &SecurityError) -> Option<Self> {
+        match error {
+            SecurityError::MagicNumberMismatch(details) => Some(Self::MagicNumberMismatch {
+                details: details.clone(),
+            }),
+            SecurityError::MimeTypeViolation(details) => Some(Self::BlockedMimeType {
+                details: details.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Reason to quarantine a file the corruption check rejected
+    pub fn from_corruption(check: &CorruptionCheckResult) -> Option<Self> {
+        check.is_corrupted.then(|| Self::Corrupted {
+            detected_type: check.detected_type.clone(),
+            expected_type: check.expected_type.clone(),
+            details: check.corruption_details.clone(),
+        })
+    }
+
+    /// Reason to quarantine a file that failed validation, from its content scan or corruption check
+    pub fn from_validation(result: &FileValidationResult) -> Option<Self> {
+        result
+            .content_scan
+            .as_ref()
+            .filter(|scan| scan.has_threats())
+            .map(|scan| Self::ActiveContent {
+                findings: scan.findings.clone(),
+            })
+            .or_else(|| {
+                result
+                    .corruption_check
+                    .as_ref()
+                    .and_then(Self::from_corruption)
+            })
+    }
+
+    /// Human-readable description of the reason
+    pub fn description(&self) -> String {
+        match self {
+            Self::MagicNumberMismatch { details } => format!("Magic number mismatch: {}", details),
+            Self::BlockedMimeType { details } => format!("Blocked MIME type: {}", details),
+            Self::Corrupted { details, .. } if details.is_empty() => {
+                "File appears to be corrupted".to_string()
+            }
+            Self::Corrupted { details, .. } => {
+                format!("File appears to be corrupted: {}", details.join("; "))
+            }
+            Self::ActiveContent { findings } => format!(
+                "Active content: {}",
+                findings
+                    .iter()
+                    .map(|finding| finding.details.as_str())
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+        }
+    }
+}
+
+/// Sidecar record kept next to every quarantined file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    pub id: String,
+    /// Where the file was when it was quarantined
+    pub original_path: PathBuf,
+    pub file_name: String,
+    pub reason: QuarantineReason,
+    /// SHA-256 of the file contents
+    pub sha256: String,
+    pub size: u64,
+    /// The operation that rejected the file, e.g. `import_file`
+    pub operation: String,
+    pub quarantined_at: DateTime<Utc>,
+    pub quarantined_by: String,
+}
+
+/// A quarantined file's record and what is on disk for it now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineInspection {
+    pub record: QuarantineRecord,
+    /// Whether the quarantined contents are still present
+    pub present: bool,
+    /// Whether the contents still hash to the recorded SHA-256
+    pub integrity_verified: bool,
+    pub detected_mime_type: Option<String>,
+    /// First bytes of the file, hex encoded
+    pub header_hex: String,
+}
+
+/// Moves suspicious files out of the way and manages them afterwards
+#[derive(Debug, Clone)]
+pub struct QuarantineManager {
+    directory: PathBuf,
+    enabled: bool,
+    quarantine_suspicious_files: bool,
+}
+
+impl QuarantineManager {
+    pub fn new(config: &AppSecurityConfig) -> Self {
+        Self {
+            directory: config.quarantine_directory.clone(),
+            enabled: config.enable_file_quarantine,
+            quarantine_suspicious_files: config.quarantine_suspicious_files,
+        }
+    }
+
+    /// Install the global quarantine manager from the loaded configuration
+    pub fn init(config: &AppSecurityConfig) -> Result<()> {
+        QUARANTINE_MANAGER
+            .set(Self::new(config))
+            .map_err(|_| anyhow!("Quarantine manager already initialized"))
+    }
+
+    /// The global quarantine manager, using the default configuration until `init` is called
+    pub fn global() -> &'static QuarantineManager {
+        QUARANTINE_MANAGER.get_or_init(|| Self::new(&AppSecurityConfig::default()))
+    }
+
+    /// Whether rejected imports are quarantined automatically
+    ///
+    /// Needs file quarantine enabled and both the app and safe mode configuration
+    /// asking for suspicious files to be quarantined.
+    pub fn quarantines_suspicious_files(&self) -> bool {
+        self.enabled
+            && self.quarantine_suspicious_files
+            && SafeModeManager::global()
+                .get_config()
+                .quarantine_suspicious_files
+    }
+
+    /// Quarantine a rejected import when automatic quarantine is enabled
+    ///
+    /// Failures are logged rather than returned so the original rejection still
+    /// reaches the caller.
+    pub fn quarantine_rejected_import(
+        &self,
+        path: &Path,
+        reason: QuarantineReason,
+        operation: &str,
+    ) -> Option<QuarantineRecord> {
+        if !self.quarantines_suspicious_files() || !path.is_file() {
+            return None;
+        }
+        match self.quarantine(path, reason, operation) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                tracing::error!("Failed to quarantine {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Move a file into quarantine and write its sidecar record
+    pub fn quarantine(
+        &self,
+        path: &Path,
+        reason: QuarantineReason,
+        operation: &str,
+    ) -> Result<QuarantineRecord> {
+        self.ensure_directory()?;
+        let hash = ContentHash::from_file(path)?;
+
+        let record = QuarantineRecord {
+            id: Uuid::new_v4().to_string(),
+            original_path: path.to_path_buf(),
+            file_name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            reason,
+            sha256: hash.hash,
+            size: hash.size,
+            operation: operation.to_string(),
+            quarantined_at: Utc::now(),
+            quarantined_by: SecurityAuditor::get_current_user(),
+        };
+
+        let payload = self.payload_path(&record.id);
+        move_file(path, &payload)?;
+        if let Err(e) = self.write_record(&record) {
+            // Put the file back rather than leave it without a record
+            let _ = move_file(&payload, path);
+            return Err(e);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&payload, fs::Permissions::from_mode(0o400))
+                .with_context(|| format!("Failed to restrict {}", payload.display()))?;
+        }
+
+        SecurityAuditor::log_quarantine_action(
+            "quarantine",
+            &record.original_path,
+            &record.id,
+            SecurityLevel::High,
+            serde_json::json!({
+                "reason": record.reason,
+                "sha256": record.sha256,
+                "size": record.size,
+                "origin_operation": record.operation,
+            }),
+        );
+        tracing::warn!(
+            "Quarantined {}: {}",
+            record.original_path.display(),
+            record.reason.description()
+        );
+        Ok(record)
+    }
+
+    /// Every quarantined file, newest first
+    pub fn list(&self) -> Result<Vec<QuarantineRecord>> {
+        let mut records = Vec::new();
+        if !self.directory.is_dir() {
+            return Ok(records);
+        }
+        for entry in fs::read_dir(&self.directory)
+            .with_context(|| format!("Failed to list {}", self.directory.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            match read_record(&path) {
+                Ok(record) => records.push(record),
+                Err(e) => tracing::warn!("Skipping quarantine record {}: {}", path.display(), e),
+            }
+        }
+        records.sort_by(|a, b| b.quarantined_at.cmp(&a.quarantined_at));
+        Ok(records)
+    }
+
+    /// A quarantined file's record, checked against the contents on disk
+    pub fn inspect(&self, id: &str) -> Result<QuarantineInspection> {
+        let record = self.record(id)?;
+        let payload = self.payload_path(id);
+
+        let inspection = if payload.is_file() {
+            let mut header = Vec::with_capacity(INSPECTION_HEADER_LEN);
+            fs::File::open(&payload)?
+                .take(INSPECTION_HEADER_LEN as u64)
+                .read_to_end(&mut header)?;
+            QuarantineInspection {
+                integrity_verified: ContentHash::from_file(&payload)?.hash == record.sha256,
+                present: true,
+                detected_mime_type: infer::get(&header).map(|kind| kind.mime_type().to_string()),
+                header_hex: hex::encode(&header),
+                record,
+            }
+        } else {
+            QuarantineInspection {
+                record,
+                present: false,
+                integrity_verified: false,
+                detected_mime_type: None,
+                header_hex: String::new(),
+            }
+        };
+
+        SecurityAuditor::log_quarantine_action(
+            "inspect",
+            &inspection.record.original_path,
+            id,
+            SecurityLevel::Low,
+            serde_json::json!({ "integrity_verified": inspection.integrity_verified }),
+        );
+        Ok(inspection)
+    }
+
+    /// Move a quarantined file back out, to its original location by default
+    ///
+    /// Refuses to overwrite an existing file or to release contents that no longer
+    /// match the recorded hash.
+    pub fn release(&self, id: &str, destination: Option<&Path>) -> Result<PathBuf> {
+        let record = self.record(id)?;
+        let payload = self.payload_path(id);
+        let destination = destination
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| record.original_path.clone());
+
+        if destination.exists() {
+            bail!("{} already exists", destination.display());
+        }
+        if ContentHash::from_file(&payload)?.hash != record.sha256 {
+            bail!(
+                "Quarantined file {} no longer matches its recorded hash",
+                id
+            );
+        }
+
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        move_file(&payload, &destination)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&destination, fs::Permissions::from_mode(0o644))
+                .with_context(|| format!("Failed to restore {}", destination.display()))?;
+        }
+        fs::remove_file(self.record_path(id))?;
+
+        SecurityAuditor::log_quarantine_action(
+            "release",
+            &destination,
+            id,
+            SecurityLevel::Medium,
+            serde_json::json!({
+                "reason": record.reason,
+                "sha256": record.sha256,
+                "original_path": record.original_path,
+            }),
+        );
+        Ok(destination)
+    }
+
+    /// Permanently delete a quarantined file and its record
+    pub fn delete(&self, id: &str) -> Result<QuarantineRecord> {
+        let record = self.record(id)?;
+        let payload = self.payload_path(id);
+        if payload.exists() {
+            fs::remove_file(&payload)
+                .with_context(|| format!("Failed to delete {}", payload.display()))?;
+        }
+        fs::remove_file(self.record_path(id))?;
+
+        SecurityAuditor::log_quarantine_action(
+            "delete",
+            &record.original_path,
+            id,
+            SecurityLevel::Medium,
+            serde_json::json!({ "sha256": record.sha256 }),
+        );
+        Ok(record)
+    }
+
+    /// The record of a quarantined file
+    pub fn record(&self, id: &str) -> Result<QuarantineRecord> {
+        // IDs come from the UI; only accept the UUIDs this manager hands out
+        Uuid::parse_str(id).map_err(|_| anyhow!("Invalid quarantine ID: {}", id))?;
+        let path = self.record_path(id);
+        if !path.is_file() {
+            bail!("No quarantined file with ID {}", id);
+        }
+        read_record(&path)
+    }
+
+    fn write_record(&self, record: &QuarantineRecord) -> Result<()> {
+        let path = self.record_path(&record.id);
+        fs::write(&path, serde_json::to_string_pretty(record)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    fn ensure_directory(&self) -> Result<()> {
+        fs::create_dir_all(&self.directory)
+            .with_context(|| format!("Failed to create {}", self.directory.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&self.directory, fs::Permissions::from_mode(0o700))
+                .with_context(|| format!("Failed to restrict {}", self.directory.display()))?;
+        }
+        Ok(())
+    }
+
+    fn payload_path(&self, id: &str) -> PathBuf {
+        self.directory.join(format!("{}.{}", id, PAYLOAD_EXTENSION))
+    }
+
+    fn record_path(&self, id: &str) -> PathBuf {
+        self.directory.join(format!("{}.json", id))
+    }
+}
+
+fn read_record(path: &Path) -> Result<QuarantineRecord> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Rename a file, copying it when source and target are on different filesystems
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)
+        .with_context(|| format!("Failed to move {} to {}", from.display(), to.display()))?;
+    fs::remove_file(from).with_context(|| format!("Failed to remove {}", from.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem::security::content_scanner::{ContentScanResult, ContentThreatKind};
+    use tempfile::TempDir;
+
+    fn manager(dir: &Path) -> QuarantineManager {
+        let config = AppSecurityConfig {
+            enable_file_quarantine: true,
+            quarantine_suspicious_files: true,
+            quarantine_directory: dir.join("quarantine"),
+            ..AppSecurityConfig::default()
+        };
+        QuarantineManager::new(&config)
+    }
+
+    #[test]
+    fn test_quarantine_inspect_release_and_delete() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = manager(temp_dir.path());
+
+        let suspicious = temp_dir.path().join("invoice.pdf");
+        fs::write(&suspicious, b"MZ\x90\x00not really a pdf").unwrap();
+        let reason = QuarantineReason::from_security_error(&SecurityError::MagicNumberMismatch(
+            "expected %PDF".to_string(),
+        ))
+        .unwrap();
+
+        let record = manager
+            .quarantine(&suspicious, reason.clone(), "import_file")
+            .unwrap();
+        assert!(!suspicious.exists());
+        assert_eq!(record.reason, reason);
+        assert_eq!(record.file_name, "invoice.pdf");
+        assert_eq!(manager.list().unwrap().len(), 1);
+
+        let inspection = manager.inspect(&record.id).unwrap();
+        assert!(inspection.present);
+        assert!(inspection.integrity_verified);
+        assert!(inspection.header_hex.starts_with("4d5a"));
+
+        // Released files go back where they came from
+        let released = manager.release(&record.id, None).unwrap();
+        assert_eq!(released, suspicious);
+        assert!(suspicious.is_file());
+        assert!(manager.list().unwrap().is_empty());
+
+        // A second quarantine can be deleted for good
+        let record = manager
+            .quarantine(&suspicious, reason, "process_dropped_files")
+            .unwrap();
+        fs::write(&suspicious, b"replacement").unwrap();
+        assert!(manager.release(&record.id, None).is_err());
+        manager.delete(&record.id).unwrap();
+        assert!(manager.list().unwrap().is_empty());
+        assert!(manager.inspect(&record.id).is_err());
+        assert!(manager.inspect("../../etc/passwd").is_err());
+    }
+
+    #[test]
+    fn test_only_suspicious_rejections_are_quarantined() {
+        assert!(
+            QuarantineReason::from_security_error(&SecurityError::PathTraversal {
+                path: "../secret".to_string(),
+            })
+            .is_none()
+        );
+        assert!(
+            QuarantineReason::from_corruption(&CorruptionCheckResult::clean(
+                Some("pdf".to_string()),
+                Some("pdf".to_string()),
+            ))
+            .is_none()
+        );
+
+        // Active content takes precedence over a clean corruption check
+        let scan = ContentScanResult {
+            scanned_format: Some("zip".to_string()),
+            findings: vec![ContentFinding {
+                kind: ContentThreatKind::VbaMacro,
+                location: "word/vbaProject.bin".to_string(),
+                details: "Document contains a VBA macro project".to_string(),
+            }],
+        };
+        let validation =
+            FileValidationResult::threat(CorruptionCheckResult::clean(None, None), scan);
+        assert!(matches!(
+            QuarantineReason::from_validation(&validation),
+            Some(QuarantineReason::ActiveContent { .. })
+        ));
+
+        let temp_dir = TempDir::new().unwrap();
+        let disabled = QuarantineManager::new(&AppSecurityConfig {
+            enable_file_quarantine: false,
+            quarantine_directory: temp_dir.path().join("quarantine"),
+            ..AppSecurityConfig::default()
+        });
+        let file = temp_dir.path().join("report.docx");
+        fs::write(&file, b"garbage").unwrap();
+        let reason = QuarantineReason::from_corruption(&CorruptionCheckResult::corrupted(
+            None,
+            Some("docx".to_string()),
+            vec!["Missing ZIP header".to_string()],
+            0.9,
+        ))
+        .unwrap();
+        assert!(disabled
+            .quarantine_rejected_import(&file, reason, "import_file")
+            .is_none());
+        assert!(file.exists());
+    }
+}
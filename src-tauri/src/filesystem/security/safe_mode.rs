@@ -187,6 +187,25 @@ impl SafeModeManager {
         Ok(())
     }
 
+    /// Switch to restricted mode after a threat is found, when `auto_enable_on_threats` is set
+    ///
+    /// Returns whether the level changed; modes that are already stricter are kept.
+    pub fn report_threat(&self, path: &std::path::Path, details: &str) -> bool {
+        let mut config = self.config.write().unwrap();
+        if !config.auto_enable_on_threats || !matches!(config.level, SafeModeLevel::Disabled) {
+            return false;
+        }
+        config.level = SafeModeLevel::Restricted;
+
+        log::warn!(
+            "Safe mode enabled after threat in {}: {}",
+            path.display(),
+            details
+        );
+
+        true
+    }
+
     pub fn is_file_allowed(&self, path: &std::path::Path) -> Result<bool> {
         let config = self.config.read().unwrap();
 
@@ -336,4 +355,33 @@ mod tests {
         assert!(manager.is_file_allowed(allowed_file.path()).unwrap());
         assert!(!manager.is_file_allowed(blocked_file.path()).unwrap());
     }
+
+    #[test]
+    fn test_threats_enable_safe_mode() {
+        let manager = SafeModeManager::new();
+        let path = std::path::Path::new("invoice.docm");
+        manager.set_level(SafeModeLevel::Disabled).unwrap();
+
+        manager.config.write().unwrap().auto_enable_on_threats = false;
+        assert!(!manager.report_threat(path, "VBA macro"));
+        assert!(matches!(
+            manager.get_config().level,
+            SafeModeLevel::Disabled
+        ));
+
+        manager.config.write().unwrap().auto_enable_on_threats = true;
+        assert!(manager.report_threat(path, "VBA macro"));
+        assert!(matches!(
+            manager.get_config().level,
+            SafeModeLevel::Restricted
+        ));
+
+        // Stricter levels are left alone
+        manager.set_level(SafeModeLevel::Paranoid).unwrap();
+        assert!(!manager.report_threat(path, "VBA macro"));
+        assert!(matches!(
+            manager.get_config().level,
+            SafeModeLevel::Paranoid
+        ));
+    }
 }
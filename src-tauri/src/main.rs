@@ -123,6 +123,14 @@ async fn main() {
     info!("Starting Fiovana application...");
     info!("Environment: {:?}", config_manager.environment());
 
+    // Initialize file quarantine from the security configuration
+    if let Some(security_config) = config_manager.get_security_config() {
+        if let Err(e) = filesystem::security::quarantine::QuarantineManager::init(&security_config)
+        {
+            tracing::warn!("Failed to initialize file quarantine: {}", e);
+        }
+    }
+
     // Initialize workspace manager
     let workspace_manager = match WorkspaceManager::new(Arc::clone(&config_manager)) {
         Ok(manager) => {
@@ -141,6 +149,21 @@ async fn main() {
             .expect("Failed to create workspace manager for commands"),
     ));
 
+    // Shared limit on concurrent workspace file system work
+    let workspace_resource_limiter =
+        Arc::new(workspace::performance::WorkspaceResourceLimiter::new());
+
+    // Initialize workspace backup scheduler
+    let workspace_backup_state: commands::workspace_backup_commands::WorkspaceBackupState =
+        Arc::new(workspace::backup_scheduler::BackupScheduler::new(
+            workspace::backup::WorkspaceBackupManager::new(Arc::new(
+                filesystem::security::backup_manager::BackupManager::new()
+                    .expect("Failed to create backup manager for workspace backups"),
+            ))
+            .expect("Failed to create workspace backup manager"),
+            Arc::clone(&workspace_resource_limiter),
+        ));
+
     // Initialize deduplication state
     let deduplication_state = commands::deduplication_commands::DeduplicationState::new();
 
@@ -195,9 +218,28 @@ async fn main() {
     let conversational_intelligence_state: commands::conversational_intelligence_commands::ConversationalIntelligenceState =
         std::sync::Arc::new(tokio::sync::Mutex::new(None));
 
-    // Initialize conversation context state
+    // Initialize conversation context state, resuming history of the most recent workspace
+    let mut conversation_context_manager = crate::ai::ConversationContextManager::new();
+    if let Some(recent) = workspace_manager
+        .get_recent_workspaces()
+        .await
+        .ok()
+        .and_then(|recent| recent.into_iter().max_by_key(|w| w.last_accessed))
+        .filter(|recent| recent.path.exists())
+    {
+        match crate::ai::ConversationHistoryStore::for_workspace(&recent.path)
+            .and_then(|store| conversation_context_manager.attach_history_store(store))
+        {
+            Ok(resumed) => info!(
+                "Resumed {} conversation sessions from {}",
+                resumed,
+                recent.path.display()
+            ),
+            Err(e) => tracing::warn!("Failed to resume conversation history: {}", e),
+        }
+    }
     let conversation_context_state: commands::conversation_context_commands::ConversationContextState =
-        std::sync::Arc::new(tokio::sync::Mutex::new(crate::ai::ConversationContextManager::new()));
+        std::sync::Arc::new(tokio::sync::Mutex::new(conversation_context_manager));
 
     // Initialize template manager state
     let template_manager_state: commands::template_commands::TemplateManagerState =
@@ -209,6 +251,10 @@ async fn main() {
     // Initialize collaboration server state
     let collaboration_server_state: commands::collaboration_commands::CollaborationServerState =
         std::sync::Arc::new(tokio::sync::RwLock::new(None));
+    let lan_discovery_state: commands::collaboration_commands::LanDiscoveryState =
+        std::sync::Arc::new(tokio::sync::Mutex::new(None));
+    let sync_agent_state: commands::collaboration_commands::SyncAgentState =
+        std::sync::Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new()));
 
     // Initialize document indexing service
     let (indexing_sender, indexing_receiver) =
@@ -237,6 +283,8 @@ async fn main() {
     tauri::Builder::default()
         .manage(app_state)
         .manage(deduplication_state)
+        .manage(workspace_backup_state)
+        .manage(workspace_resource_limiter)
         .manage(progress_state)
         .manage(ai_state)
         .manage(vector_state)
@@ -251,6 +299,8 @@ async fn main() {
         .manage(template_manager_state)
         .manage(content_adapter_state)
         .manage(collaboration_server_state)
+        .manage(lan_discovery_state)
+        .manage(sync_agent_state)
         .manage(workspace_manager_for_commands)
         .manage(ai_orchestrator_for_workspace)
         .manage(Arc::new(Mutex::new(None::<crate::ai::document_commands::DocumentCommandProcessor>)))
@@ -340,6 +390,11 @@ async fn main() {
             commands::get_workspace_config,
             commands::update_workspace_config,
             commands::get_workspace_templates,
+            commands::list_custom_workspace_templates,
+            commands::get_custom_workspace_template,
+            commands::save_custom_workspace_template,
+            commands::export_workspace_template,
+            commands::create_workspace_from_existing,
             commands::repair_workspace,
             // Recent workspace management commands
             commands::get_recent_workspaces,
@@ -347,6 +402,23 @@ async fn main() {
             commands::toggle_workspace_favorite,
             commands::remove_workspace_from_recent,
             commands::get_workspace_stats,
+            // Workspace backup commands
+            commands::start_workspace_backup_schedule,
+            commands::stop_workspace_backup_schedule,
+            commands::create_workspace_backup,
+            commands::restore_workspace_from_backup,
+            commands::get_operation_checkpoints,
+            commands::rollback_workspace_operation,
+            commands::verify_backup_replicas,
+            commands::check_workspace_integrity,
+            commands::get_workspace_recovery_info,
+            commands::export_workspace,
+            commands::import_workspace,
+            commands::inspect_workspace_bundle,
+            commands::get_bundle_signing_fingerprint,
+            commands::store_backup_secret,
+            commands::get_backup_config,
+            commands::update_backup_config,
             // Deduplication commands
             commands::deduplication_commands::initialize_deduplication,
             commands::deduplication_commands::deduplicate_file,
@@ -382,6 +454,8 @@ async fn main() {
             // AI integration commands
             commands::init_ai_system,
             commands::chat_with_ai,
+            commands::evaluate_intent_classification,
+            commands::reload_prompt_templates,
             commands::get_ai_status,
             commands::shutdown_ai_system,
             commands::restart_ai_system,
@@ -398,6 +472,20 @@ async fn main() {
             commands::get_all_documents,
             commands::get_document_details,
             commands::remove_document_from_indexer,
+            commands::get_document_metadata,
+            commands::update_document_metadata,
+            commands::get_document_tags,
+            commands::transition_document_status,
+            commands::get_overdue_reviews,
+            // File quarantine commands
+            commands::list_quarantined_files,
+            commands::inspect_quarantined_file,
+            commands::release_quarantined_file,
+            commands::delete_quarantined_file,
+            commands::federated_search,
+            commands::discover_cross_workspace_relationships,
+            commands::resolve_workspace_links,
+            commands::create_workspace_link,
             commands::clear_document_index,
             commands::get_saved_queries,
             commands::get_search_history,
@@ -517,6 +605,7 @@ async fn main() {
             // Style transfer commands
             commands::transfer_content_style,
             commands::apply_organizational_style,
+            commands::apply_style_to_document,
             commands::apply_custom_style,
             commands::preview_style_changes,
             commands::validate_style_config,
@@ -542,6 +631,10 @@ async fn main() {
             commands::get_active_entities,
             commands::export_conversation_session,
             commands::import_conversation_session,
+            commands::open_conversation_history,
+            commands::search_conversation_history,
+            commands::delete_conversation_history,
+            commands::apply_conversation_history_retention,
             commands::get_conversation_context_status,
             // Natural language operations commands
             commands::execute_natural_language_operation,
@@ -591,6 +684,11 @@ async fn main() {
             commands::get_organization_config,
             commands::update_organization_config,
             commands::execute_organization_action,
+            commands::preview_organization_plan,
+            commands::apply_organization_plan,
+            commands::undo_organization_plan,
+            commands::resume_organization_plan,
+            commands::list_organization_plans,
             commands::calculate_organization_score,
             // Knowledge gap analysis commands
             commands::analyze_knowledge_gaps_comprehensive,
@@ -622,7 +720,23 @@ async fn main() {
             commands::start_collaboration_server,
             commands::stop_collaboration_server,
             commands::get_collaboration_server_status,
+            commands::bind_collaboration_document,
+            commands::create_collaboration_token,
+            commands::set_collaboration_permission,
+            commands::revoke_collaboration_token,
             commands::get_collaboration_server_info,
+            commands::get_discovered_peers,
+            commands::stop_lan_discovery,
+            commands::get_collaboration_history,
+            commands::get_collaboration_changes,
+            commands::diff_collaboration_history,
+            commands::restore_collaboration_document,
+            commands::start_collaboration_sync,
+            commands::get_collaboration_sync_status,
+            commands::get_synced_document_text,
+            commands::update_synced_document,
+            commands::set_collaboration_sync_online,
+            commands::stop_collaboration_sync,
             // Text operation commands
             commands::execute_text_operation,
             commands::get_available_text_operations,
@@ -646,6 +760,26 @@ async fn main() {
             crate::notifications::set_global_emitter(emitter);
             info!("Notification system initialized");
 
+            // Remind owners about controlled documents that are overdue for review
+            let app_handle = app.handle().clone();
+            tokio::spawn(async move {
+                let mut reminders = document::review_workflow::ReviewReminders::default();
+                // First check once the indexer has had time to initialize
+                let mut ticks = tokio::time::interval_at(
+                    tokio::time::Instant::now() + std::time::Duration::from_secs(60),
+                    commands::REVIEW_CHECK_INTERVAL,
+                );
+                loop {
+                    ticks.tick().await;
+                    if let Some(indexer_state) = app_handle.try_state::<commands::document_indexing_commands::DocumentIndexerState>() {
+                        let raised = commands::notify_overdue_reviews(&indexer_state, &mut reminders).await;
+                        if raised > 0 {
+                            info!("Raised {} overdue document review reminder(s)", raised);
+                        }
+                    }
+                }
+            });
+
             // Auto-initialize document indexer on app load
             let app_handle = app.handle().clone();
             tokio::spawn(async move {
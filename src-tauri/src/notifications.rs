@@ -13,6 +13,7 @@ pub enum Notification {
     FileChange(FileChangeNotification),
     Conflict(ConflictNotification),
     Security(SecurityNotification),
+    Review(ReviewNotification),
 }
 
 /// File change notification
@@ -61,6 +62,21 @@ pub struct SecurityNotification {
     pub reason: String,
 }
 
+/// Controlled document review notification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewNotification {
+    pub id: String,
+    pub title: String,
+    pub message: String,
+    pub timestamp: u64,
+    pub duration: Option<u64>,
+    pub document_id: String,
+    pub file_path: String,
+    pub owners: Vec<String>,
+    pub review_date: u64,
+    pub days_overdue: i64,
+}
+
 /// Notification emitter for sending notifications to frontend
 pub struct NotificationEmitter {
     app_handle: tauri::AppHandle,
@@ -228,6 +244,52 @@ impl NotificationEmitter {
 
         self.emit(notification)
     }
+
+    /// Create and emit a notification for a document whose review is overdue
+    pub fn emit_review_overdue(
+        &self,
+        document_id: &str,
+        path: &std::path::Path,
+        owners: &[String],
+        review_date: chrono::DateTime<chrono::Utc>,
+        days_overdue: i64,
+    ) -> Result<(), tauri::Error> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| document_id.to_string());
+        let title = format!("Review overdue: {}", name);
+        let message = if owners.is_empty() {
+            format!("{} was due for review {} day(s) ago", name, days_overdue)
+        } else {
+            format!(
+                "{} was due for review {} day(s) ago (owner: {})",
+                name,
+                days_overdue,
+                owners.join(", ")
+            )
+        };
+
+        let notification = Notification::Review(ReviewNotification {
+            id: Uuid::new_v4().to_string(),
+            title,
+            message,
+            timestamp,
+            duration: None, // Stays until dismissed
+            document_id: document_id.to_string(),
+            file_path: path.to_string_lossy().to_string(),
+            owners: owners.to_vec(),
+            review_date: review_date.timestamp_millis() as u64,
+            days_overdue,
+        });
+
+        self.emit(notification)
+    }
 }
 
 // Import required modules
@@ -1,7 +1,7 @@
 // src-tauri/src/vector/mod.rs
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -852,6 +852,26 @@ impl VectorStore {
     }
 
     pub async fn search(&self, query_vector: &[f32], k: usize) -> Result<Vec<SearchResult>> {
+        self.search_matching(query_vector, k, None).await
+    }
+
+    /// Vector search restricted to the given documents, e.g. those matching a metadata filter
+    pub async fn search_in_documents(
+        &self,
+        document_ids: &HashSet<String>,
+        query_vector: &[f32],
+        k: usize,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_matching(query_vector, k, Some(document_ids))
+            .await
+    }
+
+    async fn search_matching(
+        &self,
+        query_vector: &[f32],
+        k: usize,
+        document_ids: Option<&HashSet<String>>,
+    ) -> Result<Vec<SearchResult>> {
         if query_vector.len() != self.dimension {
             return Err(anyhow!("Query vector dimension mismatch"));
         }
@@ -867,6 +887,9 @@ impl VectorStore {
 
         // Calculate cosine similarity for each stored vector
         for (chunk_id, embedding_record) in embeddings.iter() {
+            if !in_documents(&chunks, chunk_id, document_ids) {
+                continue;
+            }
             let similarity = cosine_similarity(query_vector, &embedding_record.embedding);
             similarities.push((chunk_id.clone(), similarity));
         }
@@ -964,6 +987,26 @@ impl VectorStore {
         &self,
         query: &str,
         max_results: usize,
+    ) -> Result<Vec<SearchResult>> {
+        self.keyword_search_matching(query, max_results, None).await
+    }
+
+    /// Keyword search restricted to the given documents, e.g. those matching a metadata filter
+    pub async fn keyword_search_in_documents(
+        &self,
+        document_ids: &HashSet<String>,
+        query: &str,
+        max_results: usize,
+    ) -> Result<Vec<SearchResult>> {
+        self.keyword_search_matching(query, max_results, Some(document_ids))
+            .await
+    }
+
+    async fn keyword_search_matching(
+        &self,
+        query: &str,
+        max_results: usize,
+        document_ids: Option<&HashSet<String>>,
     ) -> Result<Vec<SearchResult>> {
         let keyword_index = self.keyword_index.read().await;
         let chunks = self.chunks.read().await;
@@ -1002,7 +1045,10 @@ impl VectorStore {
         }
 
         // Sort by combined score
-        let mut results: Vec<(String, f64)> = combined_scores.into_iter().collect();
+        let mut results: Vec<(String, f64)> = combined_scores
+            .into_iter()
+            .filter(|(chunk_id, _)| in_documents(&chunks, chunk_id, document_ids))
+            .collect();
         results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
 
         // Convert to SearchResult objects
@@ -1109,6 +1155,51 @@ impl VectorStore {
         Ok(())
     }
 
+    /// Move a document's chunks and embeddings to a new document ID, returning how many moved
+    pub async fn rekey_document(&self, old_id: &str, new_id: &str) -> Result<usize> {
+        let mut doc_index = self.document_index.write().await;
+        let mut chunks = self.chunks.write().await;
+        let mut embeddings = self.embeddings.write().await;
+        let mut keyword_index = self.keyword_index.write().await;
+
+        if old_id == new_id {
+            return Ok(0);
+        }
+        if doc_index.contains_key(new_id) {
+            return Err(anyhow!("Document already has chunks: {}", new_id));
+        }
+        let Some(chunk_ids) = doc_index.remove(old_id) else {
+            return Ok(0);
+        };
+
+        let mut new_chunk_ids = Vec::with_capacity(chunk_ids.len());
+        for chunk_id in chunk_ids {
+            let Some(mut chunk) = chunks.remove(&chunk_id) else {
+                continue;
+            };
+            let new_chunk_id = match chunk_id.strip_prefix(old_id) {
+                Some(suffix) => format!("{}{}", new_id, suffix),
+                None => format!("{}:{}", new_id, chunk.chunk_index),
+            };
+
+            keyword_index.remove_chunk(&chunk_id);
+            keyword_index.add_chunk(&new_chunk_id, &chunk.content);
+            if let Some(mut embedding) = embeddings.remove(&chunk_id) {
+                embedding.chunk_id = new_chunk_id.clone();
+                embeddings.insert(new_chunk_id.clone(), embedding);
+            }
+
+            chunk.id = new_chunk_id.clone();
+            chunk.document_id = new_id.to_string();
+            chunks.insert(new_chunk_id.clone(), chunk);
+            new_chunk_ids.push(new_chunk_id);
+        }
+
+        let moved = new_chunk_ids.len();
+        doc_index.insert(new_id.to_string(), new_chunk_ids);
+        Ok(moved)
+    }
+
     pub async fn get_stats(&self) -> Result<VectorStoreStats> {
         let embeddings = self.embeddings.read().await;
         let chunks = self.chunks.read().await;
@@ -1149,6 +1240,20 @@ pub struct VectorStoreStats {
     pub memory_usage_estimate: usize,
 }
 
+// Helper function to check whether a chunk belongs to one of the allowed documents
+fn in_documents(
+    chunks: &HashMap<String, DocumentChunk>,
+    chunk_id: &str,
+    document_ids: Option<&HashSet<String>>,
+) -> bool {
+    match document_ids {
+        Some(ids) => chunks
+            .get(chunk_id)
+            .is_some_and(|chunk| ids.contains(&chunk.document_id)),
+        None => true,
+    }
+}
+
 // Helper function to calculate cosine similarity
 fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     let dot_product: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
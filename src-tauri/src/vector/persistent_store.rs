@@ -131,6 +131,14 @@ impl PersistentVectorStore {
         Ok(())
     }
 
+    pub async fn rekey_document(&self, old_id: &str, new_id: &str) -> Result<usize> {
+        let moved = self.inner_store.rekey_document(old_id, new_id).await?;
+        if moved > 0 {
+            self.mark_dirty().await;
+        }
+        Ok(moved)
+    }
+
     pub async fn get_stats(&self) -> Result<VectorStoreStats> {
         self.inner_store.get_stats().await
     }
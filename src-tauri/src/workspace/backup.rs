@@ -1,14 +1,32 @@
 // src-tauri/src/workspace/backup.rs
 //! Workspace backup and recovery system
 
+use super::backup_replication::{BackupDestination, BackupLocationInfo, Replica, ReplicaStatus};
+use super::backup_s3::S3Destination;
+use super::backup_secrets::BackupSecrets;
+use super::backup_store::{collect_files, BackupStore, SnapshotManifest, SNAPSHOTS_DIR};
+use super::bundle::{
+    create_bundle, extract_bundle, BundleComponent, BundleImport, BundleOptions, BundleSigner,
+};
+use super::custom_templates::expected_directories;
 use super::*;
+use crate::document::content_hasher::ContentHash;
 use crate::filesystem::security::backup_manager::BackupManager;
 use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError};
 use tokio::fs;
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+use tracing::warn;
+
+/// Locks on backup stores, by store root. Replication holds one while it reads
+/// blobs and prunes replicas, and retention while it deletes backups and
+/// blobs, so neither removes what the other is still using.
+static STORE_LOCKS: Lazy<Mutex<HashMap<PathBuf, Arc<AsyncMutex<()>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
 
 /// Workspace backup configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,16 +35,33 @@ pub struct WorkspaceBackupConfig {
     pub auto_backup_enabled: bool,
     /// Backup interval in minutes
     pub backup_interval_minutes: u32,
-    /// Maximum number of backups to keep
+    /// Maximum number of backups to keep, not counting emergency checkpoints
     pub max_backups: usize,
+    /// Maximum number of emergency checkpoints taken before risky operations
+    /// to keep, so a run of them cannot push scheduled backups out
+    #[serde(default = "default_max_emergency_checkpoints")]
+    pub max_emergency_checkpoints: usize,
     /// Backup retention policy in days
     pub retention_days: u32,
-    /// Compress backups
+    /// Compress stored file contents with zstd
     pub compress_backups: bool,
     /// Include workspace files in backup
     pub include_files: bool,
-    /// Backup locations
+    /// Directories backups are replicated to, such as an external drive or a
+    /// network mount
     pub backup_locations: Vec<PathBuf>,
+    /// S3-compatible buckets backups are replicated to
+    #[serde(default)]
+    pub s3_destinations: Vec<S3Destination>,
+    /// Reference, in the backup secret store, to the passphrase replicas are
+    /// encrypted with before they leave this machine. Required for S3
+    /// destinations.
+    #[serde(default)]
+    pub replica_passphrase_ref: Option<String>,
+    /// Key fingerprints of signers whose workspace bundles may be imported,
+    /// besides this machine's own signing key
+    #[serde(default)]
+    pub trusted_signers: Vec<String>,
 }
 
 impl Default for WorkspaceBackupConfig {
@@ -35,14 +70,64 @@ impl Default for WorkspaceBackupConfig {
             auto_backup_enabled: true,
             backup_interval_minutes: 60, // 1 hour
             max_backups: 10,
+            max_emergency_checkpoints: default_max_emergency_checkpoints(),
             retention_days: 30,
             compress_backups: true,
             include_files: false, // Only metadata by default
             backup_locations: vec![],
+            s3_destinations: vec![],
+            replica_passphrase_ref: None,
+            trusted_signers: vec![],
         }
     }
 }
 
+fn default_max_emergency_checkpoints() -> usize {
+    5
+}
+
+impl WorkspaceBackupConfig {
+    /// Every place backups are replicated to besides the workspace itself
+    pub fn destinations(&self) -> Vec<BackupDestination> {
+        self.backup_locations
+            .iter()
+            .cloned()
+            .map(BackupDestination::Directory)
+            .chain(
+                self.s3_destinations
+                    .iter()
+                    .cloned()
+                    .map(BackupDestination::S3),
+            )
+            .collect()
+    }
+
+    /// Which backups of a newest first list the retention policy keeps.
+    /// Emergency checkpoints and other backups are counted against their own
+    /// limits. The newest backup is always kept.
+    pub(crate) fn retained<'a>(
+        &self,
+        backups: impl IntoIterator<Item = &'a WorkspaceBackupMetadata>,
+    ) -> Vec<bool> {
+        let cutoff = Utc::now() - chrono::Duration::days(i64::from(self.retention_days));
+        let (mut regular, mut emergency) = (0, 0);
+        backups
+            .into_iter()
+            .enumerate()
+            .map(|(index, backup)| {
+                let (seen, limit) = match backup.backup_type {
+                    WorkspaceBackupType::Emergency => {
+                        (&mut emergency, self.max_emergency_checkpoints)
+                    }
+                    _ => (&mut regular, self.max_backups),
+                };
+                *seen += 1;
+                index == 0 || (*seen <= limit && backup.backup_timestamp >= cutoff)
+            })
+            .collect()
+    }
+}
+
 /// Workspace backup metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceBackupMetadata {
@@ -57,6 +142,12 @@ pub struct WorkspaceBackupMetadata {
     pub is_compressed: bool,
     pub backup_path: PathBuf,
     pub recovery_tested: bool,
+    /// Operation an emergency snapshot was taken before, for rolling it back
+    #[serde(default)]
+    pub operation: Option<RiskyOperation>,
+    /// Outcome of copying the backup to each replication destination
+    #[serde(default)]
+    pub replicas: Vec<ReplicaStatus>,
 }
 
 /// Types of workspace backups
@@ -72,12 +163,41 @@ pub enum WorkspaceBackupType {
     Emergency,
 }
 
+/// Operations that snapshot the workspace before they change it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskyOperation {
+    /// Bulk reorganisation of workspace files
+    Reorganization,
+    /// Deduplication garbage collection
+    GarbageCollection,
+    /// Restoring a backup or rolling back over the workspace
+    Restore,
+    /// Writing style-transferred content back to a document
+    StyleTransfer,
+}
+
+impl RiskyOperation {
+    pub fn description(&self) -> &'static str {
+        match self {
+            RiskyOperation::Reorganization => "workspace reorganisation",
+            RiskyOperation::GarbageCollection => "deduplication garbage collection",
+            RiskyOperation::Restore => "restore",
+            RiskyOperation::StyleTransfer => "style transfer",
+        }
+    }
+}
+
 /// Workspace recovery information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceRecoveryInfo {
     pub available_backups: Vec<WorkspaceBackupMetadata>,
     pub latest_backup: Option<WorkspaceBackupMetadata>,
     pub recovery_options: Vec<RecoveryOption>,
+    /// Backups at each replication destination, which survive the loss of the
+    /// disk holding the workspace
+    #[serde(default)]
+    pub locations: Vec<BackupLocationInfo>,
 }
 
 /// Recovery options for workspace
@@ -108,12 +228,16 @@ pub enum RiskLevel {
 use std::time::Duration;
 
 /// Workspace backup and recovery manager
+///
+/// Backups are content-addressed snapshots (see `backup_store`): each one is a
+/// manifest of file hashes, and file contents are stored once per backup
+/// location however many snapshots reference them.
 #[allow(dead_code)]
 pub struct WorkspaceBackupManager {
     #[allow(dead_code)]
     backup_manager: Arc<BackupManager>,
     config: WorkspaceBackupConfig,
-    backup_history: HashMap<PathBuf, Vec<WorkspaceBackupMetadata>>,
+    secrets: BackupSecrets,
 }
 
 #[allow(dead_code)]
@@ -123,7 +247,7 @@ impl WorkspaceBackupManager {
         Ok(Self {
             backup_manager,
             config: WorkspaceBackupConfig::default(),
-            backup_history: HashMap::new(),
+            secrets: BackupSecrets::default(),
         })
     }
 
@@ -135,15 +259,255 @@ impl WorkspaceBackupManager {
         Ok(Self {
             backup_manager,
             config,
-            backup_history: HashMap::new(),
+            secrets: BackupSecrets::default(),
         })
     }
 
-    /// Create automatic workspace backup
+    pub fn config(&self) -> &WorkspaceBackupConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: WorkspaceBackupConfig) {
+        self.config = config;
+    }
+
+    /// Use another secret store for replica passphrases and S3 keys
+    pub fn set_secrets(&mut self, secrets: BackupSecrets) {
+        self.secrets = secrets;
+    }
+
+    /// Replication with the current configuration, usable after releasing
+    /// whatever lock guards the manager
+    pub fn replicator(&self) -> BackupReplicator {
+        BackupReplicator {
+            config: self.config.clone(),
+            secrets: self.secrets.clone(),
+        }
+    }
+
+    /// Create automatic workspace backup and replicate it to every configured
+    /// destination. A destination that fails is recorded in the backup's
+    /// `replicas` rather than failing the backup.
     pub async fn create_backup(
         &mut self,
         workspace_path: &Path,
         backup_type: WorkspaceBackupType,
+    ) -> WorkspaceResult<WorkspaceBackupMetadata> {
+        let backup_metadata = self
+            .create_local_backup(workspace_path, backup_type)
+            .await?;
+        self.replicator().replicate(backup_metadata).await
+    }
+
+    /// Create a backup and apply the retention policy to the workspace's own
+    /// backup store, leaving replication to a `BackupReplicator`
+    pub async fn create_local_backup(
+        &mut self,
+        workspace_path: &Path,
+        backup_type: WorkspaceBackupType,
+    ) -> WorkspaceResult<WorkspaceBackupMetadata> {
+        let backup_metadata = self
+            .create_snapshot(workspace_path, backup_type, None)
+            .await?;
+
+        // Clean up old backups if needed
+        self.cleanup_old_backups(workspace_path).await?;
+        Ok(backup_metadata)
+    }
+
+    /// Download every blob of a backup from each destination holding it and
+    /// check it against its hash
+    pub async fn verify_replicas(
+        &self,
+        workspace_path: &Path,
+        backup_id: &str,
+    ) -> Vec<ReplicaStatus> {
+        self.replicator().verify(workspace_path, backup_id).await
+    }
+
+    /// Backups of the workspace held at each replication destination
+    pub async fn get_replica_backups(&self, workspace_path: &Path) -> Vec<BackupLocationInfo> {
+        let mut locations = Vec::new();
+        for destination in self.config.destinations() {
+            let outcome = async {
+                let passphrase = replica_passphrase(&self.config, &self.secrets).await?;
+                match Replica::open(
+                    &destination,
+                    workspace_path,
+                    passphrase.as_deref(),
+                    &self.secrets,
+                )
+                .await?
+                {
+                    Some(replica) => replica.backups().await,
+                    None => Ok(Vec::new()),
+                }
+            }
+            .await;
+            locations.push(match outcome {
+                Ok(backups) => BackupLocationInfo {
+                    destination: destination.label(),
+                    reachable: true,
+                    error: None,
+                    backups,
+                },
+                Err(e) => BackupLocationInfo {
+                    destination: destination.label(),
+                    reachable: false,
+                    error: Some(format!("{:#}", e)),
+                    backups: Vec::new(),
+                },
+            });
+        }
+        locations
+    }
+
+    /// Restore a backup held at a replication destination. The backup is
+    /// downloaded and verified into a staging store beside `restore_path`
+    /// first, so a failed download leaves the target untouched.
+    pub async fn restore_from_replica(
+        &self,
+        workspace_path: &Path,
+        destination: &str,
+        backup_id: &str,
+        restore_path: &Path,
+        overwrite_existing: bool,
+    ) -> WorkspaceResult<()> {
+        if restore_path.exists() && !overwrite_existing {
+            return Err(WorkspaceError::WorkspaceExists {
+                path: restore_path.to_path_buf(),
+            });
+        }
+        let destination = self
+            .config
+            .destinations()
+            .into_iter()
+            .find(|candidate| candidate.label() == destination)
+            .ok_or_else(|| WorkspaceError::Backup {
+                message: format!("Backup destination {} is not configured", destination),
+            })?;
+        let passphrase = replica_passphrase(&self.config, &self.secrets)
+            .await
+            .map_err(backup_error)?;
+        let replica = Replica::open(
+            &destination,
+            workspace_path,
+            passphrase.as_deref(),
+            &self.secrets,
+        )
+        .await
+        .map_err(backup_error)?
+        .ok_or_else(|| WorkspaceError::Backup {
+            message: format!("No backups of this workspace at {}", destination.label()),
+        })?;
+
+        let staging = restore_path.with_file_name(format!(
+            ".{}.replica-{}",
+            restore_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy(),
+            uuid::Uuid::new_v4()
+        ));
+        let store = BackupStore::new(&staging, self.config.compress_backups);
+        let restored = async {
+            let (mut backup_metadata, manifest) = replica
+                .fetch(backup_id, &store)
+                .await
+                .map_err(backup_error)?;
+            let (backup_path, checksum) =
+                run_blocking(move || store.write_manifest(&manifest)).await?;
+            backup_metadata.backup_path = backup_path;
+            backup_metadata.checksum = checksum;
+            self.restore_workspace(&backup_metadata, restore_path, overwrite_existing)
+                .await
+        }
+        .await;
+
+        let _ = fs::remove_dir_all(&staging).await;
+        restored
+    }
+
+    /// Take an emergency snapshot that `rollback_workspace` can return to
+    /// once `operation` has run
+    pub async fn create_pre_operation_snapshot(
+        &mut self,
+        workspace_path: &Path,
+        operation: RiskyOperation,
+    ) -> WorkspaceResult<WorkspaceBackupMetadata> {
+        let backup_metadata = self
+            .create_snapshot(
+                workspace_path,
+                WorkspaceBackupType::Emergency,
+                Some(operation),
+            )
+            .await?;
+        self.cleanup_old_backups(workspace_path).await?;
+        Ok(backup_metadata)
+    }
+
+    /// Return the workspace to a snapshot, deleting files created since. The
+    /// current state is snapshotted first so the rollback can itself be undone;
+    /// that snapshot is returned.
+    pub async fn rollback_workspace(
+        &mut self,
+        workspace_path: &Path,
+        backup_id: &str,
+    ) -> WorkspaceResult<WorkspaceBackupMetadata> {
+        let target = self.find_backup(workspace_path, backup_id).await?;
+        if matches!(target.backup_type, WorkspaceBackupType::MetadataOnly)
+            || target.backup_path.is_dir()
+        {
+            return Err(WorkspaceError::Backup {
+                message: format!("Backup {} does not capture workspace files", backup_id),
+            });
+        }
+        self.verify_backup_integrity(&target).await?;
+
+        // Retention runs only after the rollback so it cannot prune the target
+        let safety = self
+            .create_snapshot(
+                workspace_path,
+                WorkspaceBackupType::Emergency,
+                Some(RiskyOperation::Restore),
+            )
+            .await?;
+
+        let store = store_of(&self.config, &target)?;
+        let manifest_path = target.backup_path.clone();
+        let workspace = workspace_path.to_path_buf();
+        let exclude = vec![self.get_backup_location(workspace_path)?];
+        run_blocking(move || {
+            let manifest = store.load_manifest(&manifest_path)?;
+            store.restore(&manifest, &workspace)?;
+            store.remove_untracked(&manifest, &workspace, &exclude)
+        })
+        .await?;
+
+        self.cleanup_old_backups(workspace_path).await?;
+        Ok(safety)
+    }
+
+    /// Look up a backup of the workspace by id
+    pub async fn find_backup(
+        &self,
+        workspace_path: &Path,
+        backup_id: &str,
+    ) -> WorkspaceResult<WorkspaceBackupMetadata> {
+        self.get_available_backups(workspace_path)
+            .await?
+            .into_iter()
+            .find(|backup| backup.backup_id == backup_id)
+            .ok_or_else(|| WorkspaceError::Backup {
+                message: format!("Backup {} not found", backup_id),
+            })
+    }
+
+    async fn create_snapshot(
+        &self,
+        workspace_path: &Path,
+        backup_type: WorkspaceBackupType,
+        operation: Option<RiskyOperation>,
     ) -> WorkspaceResult<WorkspaceBackupMetadata> {
         // Validate workspace exists
         if !workspace_path.exists() {
@@ -174,33 +538,42 @@ impl WorkspaceBackupManager {
 
         // Determine backup location
         let backup_location = self.get_backup_location(workspace_path)?;
-        let backup_path = backup_location.join(format!("{}.backup", backup_id));
-
-        // Create backup directory
         fs::create_dir_all(&backup_location).await?;
 
-        // Perform backup based on type
-        let (backup_size, file_count) = match backup_type {
-            WorkspaceBackupType::Full => {
-                self.create_full_backup(workspace_path, &backup_path)
-                    .await?
-            }
-            WorkspaceBackupType::MetadataOnly => {
-                self.create_metadata_backup(workspace_path, &backup_path)
-                    .await?
-            }
-            WorkspaceBackupType::Incremental => {
-                self.create_incremental_backup(workspace_path, &backup_path)
-                    .await?
-            }
-            WorkspaceBackupType::Emergency => {
-                self.create_emergency_backup(workspace_path, &backup_path)
-                    .await?
+        // Incremental and emergency snapshots reuse the hashes of the latest
+        // snapshot for files that have not changed since; the others hash
+        // every file they include
+        let parent = match backup_type {
+            WorkspaceBackupType::Incremental | WorkspaceBackupType::Emergency => {
+                self.latest_snapshot(workspace_path).await?
             }
+            _ => None,
+        };
+        let paths = if matches!(backup_type, WorkspaceBackupType::MetadataOnly) {
+            [WORKSPACE_METADATA_FILE, WORKSPACE_CONFIG_FILE]
+                .iter()
+                .map(PathBuf::from)
+                .filter(|path| workspace_path.join(path).exists())
+                .collect()
+        } else {
+            let root = workspace_path.to_path_buf();
+            let exclude = vec![backup_location.clone()];
+            run_blocking(move || collect_files(&root, &exclude)).await?
         };
 
-        // Calculate checksum
-        let checksum = self.calculate_backup_checksum(&backup_path).await?;
+        let store = BackupStore::new(&backup_location, self.config.compress_backups);
+        let (manifest, backup_path, checksum) = {
+            let backup_id = backup_id.clone();
+            let source = workspace_path.to_path_buf();
+            run_blocking(move || {
+                let (manifest, _) = store.snapshot(&backup_id, &source, &paths, parent.as_ref())?;
+                let (path, checksum) = store.write_manifest(&manifest)?;
+                Ok((manifest, path, checksum))
+            })
+            .await?
+        };
+        let backup_size = manifest.total_size();
+        let file_count = manifest.files.len();
 
         // Get workspace name from metadata
         let workspace_name = self.get_workspace_name(workspace_path).await?;
@@ -218,19 +591,12 @@ impl WorkspaceBackupManager {
             is_compressed: self.config.compress_backups,
             backup_path,
             recovery_tested: false,
+            operation,
+            replicas: Vec::new(),
         };
 
         // Store backup metadata
-        self.store_backup_metadata(&backup_metadata).await?;
-
-        // Update backup history
-        self.backup_history
-            .entry(workspace_path.to_path_buf())
-            .or_default()
-            .push(backup_metadata.clone());
-
-        // Clean up old backups if needed
-        self.cleanup_old_backups(workspace_path).await?;
+        store_backup_metadata(&backup_metadata).await?;
 
         Ok(backup_metadata)
     }
@@ -263,24 +629,19 @@ impl WorkspaceBackupManager {
         // Create restore directory
         fs::create_dir_all(restore_path).await?;
 
-        // Perform restore based on backup type
-        match backup_metadata.backup_type {
-            WorkspaceBackupType::Full => {
-                self.restore_full_backup(backup_metadata, restore_path)
-                    .await?
-            }
-            WorkspaceBackupType::MetadataOnly => {
-                self.restore_metadata_backup(backup_metadata, restore_path)
-                    .await?
-            }
-            WorkspaceBackupType::Incremental => {
-                self.restore_incremental_backup(backup_metadata, restore_path)
-                    .await?
-            }
-            WorkspaceBackupType::Emergency => {
-                self.restore_emergency_backup(backup_metadata, restore_path)
-                    .await?
-            }
+        // Every snapshot type lists complete files, so they all restore alike
+        if backup_metadata.backup_path.is_dir() {
+            self.restore_directory_backup(backup_metadata, restore_path)
+                .await?;
+        } else {
+            let store = store_of(&self.config, backup_metadata)?;
+            let manifest_path = backup_metadata.backup_path.clone();
+            let target = restore_path.to_path_buf();
+            run_blocking(move || {
+                let manifest = store.load_manifest(&manifest_path)?;
+                store.restore(&manifest, &target)
+            })
+            .await?;
         }
 
         // Validate restored workspace
@@ -315,190 +676,123 @@ impl WorkspaceBackupManager {
     ) -> WorkspaceResult<WorkspaceRecoveryInfo> {
         let available_backups = self.get_available_backups(workspace_path).await?;
         let latest_backup = available_backups.first().cloned();
-        let recovery_options = self.generate_recovery_options(workspace_path, &available_backups);
+        let locations = self.get_replica_backups(workspace_path).await;
+        let recovery_options =
+            self.generate_recovery_options(workspace_path, &available_backups, &locations);
 
         Ok(WorkspaceRecoveryInfo {
             available_backups,
             latest_backup,
             recovery_options,
+            locations,
         })
     }
 
-    /// Export workspace for migration
+    /// Export the workspace as a signed single-file bundle
     pub async fn export_workspace(
         &self,
         workspace_path: &Path,
         export_path: &Path,
-        include_files: bool,
+        options: &BundleOptions,
     ) -> WorkspaceResult<WorkspaceExportInfo> {
-        // Create export directory
-        fs::create_dir_all(export_path).await?;
-
-        // Export metadata
-        let metadata_export_path = export_path.join("workspace_metadata.json");
-        self.export_workspace_metadata(workspace_path, &metadata_export_path)
-            .await?;
-
-        // Export configuration
-        let config_export_path = export_path.join("workspace_config.json");
-        self.export_workspace_config(workspace_path, &config_export_path)
-            .await?;
-
-        let mut total_size = 0;
-        let mut file_count = 0;
-
-        // Export files if requested
-        if include_files {
-            let files_export_path = export_path.join("workspace_files");
-            let (size, count) = self
-                .export_workspace_files(workspace_path, &files_export_path)
-                .await?;
-            total_size += size;
-            file_count += count;
+        if !workspace_path.join(WORKSPACE_METADATA_FILE).exists() {
+            return Err(WorkspaceError::InvalidWorkspace {
+                path: workspace_path.to_path_buf(),
+                reason: "Missing workspace metadata file".to_string(),
+            });
         }
 
-        // Create export manifest
-        let export_info = WorkspaceExportInfo {
-            workspace_name: self.get_workspace_name(workspace_path).await?,
-            export_timestamp: Utc::now(),
-            export_path: export_path.to_path_buf(),
-            total_size,
-            file_count,
-            includes_files: include_files,
-            checksum: self.calculate_export_checksum(export_path).await?,
+        let key_dir = BundleSigner::default_dir().ok_or_else(|| WorkspaceError::Backup {
+            message: "No configuration directory for the bundle signing key".to_string(),
+        })?;
+        let workspace_name = self.get_workspace_name(workspace_path).await?;
+        let summary = {
+            let workspace_path = workspace_path.to_path_buf();
+            let export_path = export_path.to_path_buf();
+            let workspace_name = workspace_name.clone();
+            let options = options.clone();
+            run_blocking(move || {
+                let signer = BundleSigner::load_or_generate(&key_dir)?;
+                create_bundle(
+                    &workspace_path,
+                    &export_path,
+                    &workspace_name,
+                    &options,
+                    &signer,
+                )
+            })
+            .await?
         };
 
-        let manifest_path = export_path.join("export_manifest.json");
-        let manifest_content = serde_json::to_string_pretty(&export_info)?;
-        fs::write(&manifest_path, manifest_content).await?;
-
-        Ok(export_info)
+        Ok(WorkspaceExportInfo {
+            workspace_name,
+            export_timestamp: summary.created_at,
+            export_path: export_path.to_path_buf(),
+            total_size: summary.total_size,
+            file_count: summary.file_count,
+            includes_files: options.include_files,
+            checksum: self.calculate_backup_checksum(export_path).await?,
+            signer_fingerprint: summary.signer_fingerprint,
+            encrypted: summary.encrypted,
+            components: summary.components,
+        })
     }
 
-    /// Import workspace from export
+    /// Import a workspace bundle into an empty directory, verifying its
+    /// signature and contents and remapping absolute paths to `import_path`.
+    /// The bundle must be signed by this machine, a configured trusted signer
+    /// or one of `trusted_signers`.
     pub async fn import_workspace(
         &self,
         export_path: &Path,
         import_path: &Path,
-    ) -> WorkspaceResult<()> {
-        // Validate export
-        let manifest_path = export_path.join("export_manifest.json");
-        if !manifest_path.exists() {
-            return Err(WorkspaceError::InvalidWorkspace {
-                path: export_path.to_path_buf(),
-                reason: "Missing export manifest".to_string(),
-            });
-        }
-
-        // Read and validate manifest
-        let manifest_content = fs::read_to_string(&manifest_path).await?;
-        let export_info: WorkspaceExportInfo = serde_json::from_str(&manifest_content)?;
-
-        // Verify export integrity
-        let calculated_checksum = self.calculate_export_checksum(export_path).await?;
-        if calculated_checksum != export_info.checksum {
-            return Err(WorkspaceError::InvalidWorkspace {
-                path: export_path.to_path_buf(),
-                reason: "Export checksum mismatch".to_string(),
-            });
-        }
-
-        // Create import directory
-        fs::create_dir_all(import_path).await?;
-
-        // Import metadata
-        let metadata_export_path = export_path.join("workspace_metadata.json");
-        self.import_workspace_metadata(&metadata_export_path, import_path)
-            .await?;
+        passphrase: Option<String>,
+        mut trusted_signers: Vec<String>,
+    ) -> WorkspaceResult<BundleImport> {
+        trusted_signers.extend(self.config.trusted_signers.iter().cloned());
+        let key_dir = BundleSigner::default_dir();
+        let export_path = export_path.to_path_buf();
+        let import_path = import_path.to_path_buf();
+        let imported = run_blocking(move || {
+            if let Some(key_dir) = key_dir {
+                trusted_signers.push(BundleSigner::load_or_generate(&key_dir)?.fingerprint());
+            }
+            extract_bundle(
+                &export_path,
+                &import_path,
+                passphrase.as_deref(),
+                &trusted_signers,
+            )
+        })
+        .await?;
 
-        // Import configuration
-        let config_export_path = export_path.join("workspace_config.json");
-        self.import_workspace_config(&config_export_path, import_path)
+        self.validate_restored_workspace(&imported.workspace_path)
             .await?;
-
-        // Import files if they exist
-        let files_export_path = export_path.join("workspace_files");
-        if files_export_path.exists() {
-            self.import_workspace_files(&files_export_path, import_path)
-                .await?;
-        }
-
-        Ok(())
+        Ok(imported)
     }
 
     // Private helper methods
-    async fn create_full_backup(
+    /// Manifest of the newest snapshot of the workspace that includes its files
+    async fn latest_snapshot(
         &self,
         workspace_path: &Path,
-        backup_path: &Path,
-    ) -> WorkspaceResult<(u64, usize)> {
-        // Implementation for full backup
-        let mut total_size = 0;
-        let mut file_count = 0;
-
-        // Create backup archive
-        // For now, just copy the directory structure
-        Self::copy_directory_recursive(
-            workspace_path,
-            backup_path,
-            &mut total_size,
-            &mut file_count,
-        )
-        .await?;
-
-        Ok((total_size, file_count))
-    }
-
-    async fn create_metadata_backup(
-        &self,
-        workspace_path: &Path,
-        backup_path: &Path,
-    ) -> WorkspaceResult<(u64, usize)> {
-        // Backup only metadata files
-        let metadata_files = vec![WORKSPACE_METADATA_FILE, WORKSPACE_CONFIG_FILE];
-
-        let mut total_size = 0;
-        let mut file_count = 0;
-
-        fs::create_dir_all(backup_path).await?;
-
-        for metadata_file in metadata_files {
-            let source = workspace_path.join(metadata_file);
-            if source.exists() {
-                let dest = backup_path.join(metadata_file);
-                if let Some(parent) = dest.parent() {
-                    fs::create_dir_all(parent).await?;
-                }
-                fs::copy(&source, &dest).await?;
-
-                if let Ok(metadata) = fs::metadata(&source).await {
-                    total_size += metadata.len();
-                    file_count += 1;
-                }
-            }
-        }
-
-        Ok((total_size, file_count))
-    }
+    ) -> WorkspaceResult<Option<SnapshotManifest>> {
+        let latest = self
+            .get_available_backups(workspace_path)
+            .await?
+            .into_iter()
+            .find(|backup| {
+                !matches!(backup.backup_type, WorkspaceBackupType::MetadataOnly)
+                    && backup.backup_path.is_file()
+            });
+        let Some(latest) = latest else {
+            return Ok(None);
+        };
 
-    async fn create_incremental_backup(
-        &self,
-        workspace_path: &Path,
-        backup_path: &Path,
-    ) -> WorkspaceResult<(u64, usize)> {
-        // For now, just do a metadata backup
-        // In a full implementation, this would compare with the last backup
-        self.create_metadata_backup(workspace_path, backup_path)
+        let store = store_of(&self.config, &latest)?;
+        run_blocking(move || store.load_manifest(&latest.backup_path))
             .await
-    }
-
-    async fn create_emergency_backup(
-        &self,
-        workspace_path: &Path,
-        backup_path: &Path,
-    ) -> WorkspaceResult<(u64, usize)> {
-        // Emergency backup is always a full backup
-        self.create_full_backup(workspace_path, backup_path).await
+            .map(Some)
     }
 
     fn copy_directory_recursive<'a>(
@@ -544,95 +838,103 @@ impl WorkspaceBackupManager {
         Ok(workspace_info.name)
     }
 
+    /// Backups are kept inside the workspace and replicated to the configured
+    /// destinations from there
     fn get_backup_location(&self, workspace_path: &Path) -> WorkspaceResult<PathBuf> {
-        if let Some(location) = self.config.backup_locations.first() {
-            Ok(location.clone())
-        } else {
-            // Default to .fiovana/backups directory
-            Ok(workspace_path.join(".fiovana/backups"))
-        }
+        Ok(workspace_path.join(".fiovana/backups"))
     }
 
+    /// SHA-256 of a snapshot manifest, which pins every file hash it lists
     async fn calculate_backup_checksum(&self, backup_path: &Path) -> WorkspaceResult<String> {
-        // Simple checksum calculation - in production you'd use a proper hash
-        let metadata = fs::metadata(backup_path).await?;
-        Ok(format!("{:x}", metadata.len()))
-    }
-
-    async fn store_backup_metadata(
-        &self,
-        metadata: &WorkspaceBackupMetadata,
-    ) -> WorkspaceResult<()> {
-        let backup_dir = metadata.backup_path.parent().unwrap();
-        let metadata_path = backup_dir.join(format!("{}.metadata", metadata.backup_id));
-        let content = serde_json::to_string_pretty(metadata)?;
-        fs::write(&metadata_path, content).await?;
-        Ok(())
+        let manifest_path = backup_path.to_path_buf();
+        run_blocking(move || ContentHash::from_file(&manifest_path))
+            .await
+            .map(|hash| hash.hash)
     }
 
+    /// Drop backups beyond the retention limits or older than `retention_days`,
+    /// always keeping the newest, then delete blobs no remaining snapshot references
     async fn cleanup_old_backups(&mut self, workspace_path: &Path) -> WorkspaceResult<()> {
-        if let Some(backups) = self.backup_history.get_mut(workspace_path) {
-            // Sort by timestamp, newest first
-            backups.sort_by(|a, b| b.backup_timestamp.cmp(&a.backup_timestamp));
-
-            // Remove excess backups
-            if backups.len() > self.config.max_backups {
-                let to_remove: Vec<_> = backups.drain(self.config.max_backups..).collect();
-
-                for backup in to_remove {
-                    // Delete backup file
-                    if backup.backup_path.exists() {
-                        let _ = fs::remove_dir_all(&backup.backup_path).await;
-                    }
+        let backup_location = self.get_backup_location(workspace_path)?;
+        let _store_lock = lock_store(&backup_location).await;
+        let backups = self.get_available_backups(workspace_path).await?;
+        let retained = self.config.retained(&backups);
+        let expired: Vec<_> = backups
+            .into_iter()
+            .zip(retained)
+            .filter(|(_, keep)| !keep)
+            .map(|(backup, _)| backup)
+            .collect();
+        if expired.is_empty() {
+            return Ok(());
+        }
 
-                    // Delete metadata file
-                    let metadata_path = backup
-                        .backup_path
-                        .parent()
-                        .unwrap()
-                        .join(format!("{}.metadata", backup.backup_id));
-                    if metadata_path.exists() {
-                        let _ = fs::remove_file(&metadata_path).await;
-                    }
+        for backup in &expired {
+            if backup.backup_path.is_dir() {
+                let _ = fs::remove_dir_all(&backup.backup_path).await;
+            } else if backup.backup_path.exists() {
+                let _ = fs::remove_file(&backup.backup_path).await;
+            }
+
+            if let Some(backup_dir) = backup.backup_path.parent() {
+                let metadata_path = backup_dir.join(format!("{}.metadata", backup.backup_id));
+                if metadata_path.exists() {
+                    let _ = fs::remove_file(&metadata_path).await;
                 }
             }
         }
 
+        let store = BackupStore::new(backup_location, self.config.compress_backups);
+        run_blocking(move || store.collect_garbage()).await?;
         Ok(())
     }
 
-    // Additional helper methods (simplified implementations)
+    /// Check the manifest against its recorded checksum and every blob it
+    /// references against its hash
     async fn verify_backup_integrity(
-        &self,
-        _backup_metadata: &WorkspaceBackupMetadata,
-    ) -> WorkspaceResult<()> {
-        // Implementation would verify checksums, file integrity, etc.
-        Ok(())
-    }
-
-    async fn restore_full_backup(
         &self,
         backup_metadata: &WorkspaceBackupMetadata,
-        restore_path: &Path,
     ) -> WorkspaceResult<()> {
-        // Copy backup to restore location
-        let mut total_size = 0;
-        let mut file_count = 0;
-        Self::copy_directory_recursive(
-            &backup_metadata.backup_path,
-            restore_path,
-            &mut total_size,
-            &mut file_count,
-        )
-        .await
+        // Plain directory copies from before snapshots carry no hashes
+        if backup_metadata.backup_path.is_dir() {
+            return Ok(());
+        }
+
+        let checksum = self
+            .calculate_backup_checksum(&backup_metadata.backup_path)
+            .await?;
+        if checksum != backup_metadata.checksum {
+            return Err(WorkspaceError::InvalidWorkspace {
+                path: backup_metadata.backup_path.clone(),
+                reason: "Backup manifest checksum mismatch".to_string(),
+            });
+        }
+
+        let store = store_of(&self.config, backup_metadata)?;
+        let manifest_path = backup_metadata.backup_path.clone();
+        let problems = run_blocking(move || {
+            let manifest = store.load_manifest(&manifest_path)?;
+            Ok(store.verify(&manifest))
+        })
+        .await?;
+        if !problems.is_empty() {
+            return Err(WorkspaceError::Backup {
+                message: format!(
+                    "Backup {} is damaged: {}",
+                    backup_metadata.backup_id,
+                    problems.join("; ")
+                ),
+            });
+        }
+        Ok(())
     }
 
-    async fn restore_metadata_backup(
+    /// Restore a backup made as a plain copy of the workspace directory
+    async fn restore_directory_backup(
         &self,
         backup_metadata: &WorkspaceBackupMetadata,
         restore_path: &Path,
     ) -> WorkspaceResult<()> {
-        // Copy only metadata files
         let mut total_size = 0;
         let mut file_count = 0;
         Self::copy_directory_recursive(
@@ -644,26 +946,6 @@ impl WorkspaceBackupManager {
         .await
     }
 
-    async fn restore_incremental_backup(
-        &self,
-        backup_metadata: &WorkspaceBackupMetadata,
-        restore_path: &Path,
-    ) -> WorkspaceResult<()> {
-        // For now, same as metadata restore
-        self.restore_metadata_backup(backup_metadata, restore_path)
-            .await
-    }
-
-    async fn restore_emergency_backup(
-        &self,
-        backup_metadata: &WorkspaceBackupMetadata,
-        restore_path: &Path,
-    ) -> WorkspaceResult<()> {
-        // Emergency restore is always full restore
-        self.restore_full_backup(backup_metadata, restore_path)
-            .await
-    }
-
     async fn validate_restored_workspace(&self, _workspace_path: &Path) -> WorkspaceResult<()> {
         // Implementation would validate workspace structure, metadata, etc.
         Ok(())
@@ -672,8 +954,8 @@ impl WorkspaceBackupManager {
     async fn check_workspace_structure(&self, workspace_path: &Path) -> Vec<String> {
         let mut issues = Vec::new();
 
-        for dir in WORKSPACE_DIRECTORIES {
-            let dir_path = workspace_path.join(dir);
+        for dir in expected_directories(workspace_path) {
+            let dir_path = workspace_path.join(&dir);
             if !dir_path.exists() {
                 issues.push(format!("Missing directory: {}", dir));
             }
@@ -720,22 +1002,46 @@ impl WorkspaceBackupManager {
         }
     }
 
-    async fn get_available_backups(
+    /// Backups of the workspace recorded at its backup location, newest first
+    pub async fn get_available_backups(
         &self,
         workspace_path: &Path,
     ) -> WorkspaceResult<Vec<WorkspaceBackupMetadata>> {
-        // Load backup history from filesystem
-        if let Some(backups) = self.backup_history.get(workspace_path) {
-            Ok(backups.clone())
-        } else {
-            Ok(Vec::new())
+        let backup_location = self.get_backup_location(workspace_path)?;
+        let mut backups = Vec::new();
+
+        // Snapshot metadata sits next to its manifest; directory copies from
+        // before snapshots kept theirs at the top of the location
+        for dir in [backup_location.join(SNAPSHOTS_DIR), backup_location] {
+            if !dir.is_dir() {
+                continue;
+            }
+            let mut entries = fs::read_dir(&dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if path.extension().and_then(|ext| ext.to_str()) != Some("metadata") {
+                    continue;
+                }
+                let Ok(content) = fs::read_to_string(&path).await else {
+                    continue;
+                };
+                if let Ok(metadata) = serde_json::from_str::<WorkspaceBackupMetadata>(&content) {
+                    if metadata.workspace_path == workspace_path {
+                        backups.push(metadata);
+                    }
+                }
+            }
         }
+
+        backups.sort_by(|a, b| b.backup_timestamp.cmp(&a.backup_timestamp));
+        Ok(backups)
     }
 
     fn generate_recovery_options(
         &self,
         _workspace_path: &Path,
         available_backups: &[WorkspaceBackupMetadata],
+        locations: &[BackupLocationInfo],
     ) -> Vec<RecoveryOption> {
         let mut options = Vec::new();
 
@@ -755,6 +1061,18 @@ impl WorkspaceBackupManager {
             });
         }
 
+        for location in locations
+            .iter()
+            .filter(|location| !location.backups.is_empty())
+        {
+            options.push(RecoveryOption {
+                option_type: RecoveryType::FullRestore,
+                description: format!("Restore from latest backup at {}", location.destination),
+                estimated_time: Duration::from_secs(600),
+                data_loss_risk: RiskLevel::Low,
+            });
+        }
+
         options.push(RecoveryOption {
             option_type: RecoveryType::RepairInPlace,
             description: "Repair workspace structure in place".to_string(),
@@ -764,95 +1082,10 @@ impl WorkspaceBackupManager {
 
         options
     }
-
-    // Export/Import helper methods (simplified)
-    async fn export_workspace_metadata(
-        &self,
-        workspace_path: &Path,
-        export_path: &Path,
-    ) -> WorkspaceResult<()> {
-        let metadata_path = workspace_path.join(WORKSPACE_METADATA_FILE);
-        fs::copy(&metadata_path, export_path).await?;
-        Ok(())
-    }
-
-    async fn export_workspace_config(
-        &self,
-        workspace_path: &Path,
-        export_path: &Path,
-    ) -> WorkspaceResult<()> {
-        let config_path = workspace_path.join(WORKSPACE_CONFIG_FILE);
-        if config_path.exists() {
-            fs::copy(&config_path, export_path).await?;
-        }
-        Ok(())
-    }
-
-    async fn export_workspace_files(
-        &self,
-        workspace_path: &Path,
-        export_path: &Path,
-    ) -> WorkspaceResult<(u64, usize)> {
-        let mut total_size = 0;
-        let mut file_count = 0;
-        Self::copy_directory_recursive(
-            workspace_path,
-            export_path,
-            &mut total_size,
-            &mut file_count,
-        )
-        .await?;
-        Ok((total_size, file_count))
-    }
-
-    async fn calculate_export_checksum(&self, export_path: &Path) -> WorkspaceResult<String> {
-        // Simple checksum - in production use proper hashing
-        let metadata = fs::metadata(export_path).await?;
-        Ok(format!("{:x}", metadata.len()))
-    }
-
-    async fn import_workspace_metadata(
-        &self,
-        metadata_path: &Path,
-        import_path: &Path,
-    ) -> WorkspaceResult<()> {
-        let dest = import_path.join(WORKSPACE_METADATA_FILE);
-        if let Some(parent) = dest.parent() {
-            fs::create_dir_all(parent).await?;
-        }
-        fs::copy(metadata_path, &dest).await?;
-        Ok(())
-    }
-
-    async fn import_workspace_config(
-        &self,
-        config_path: &Path,
-        import_path: &Path,
-    ) -> WorkspaceResult<()> {
-        if config_path.exists() {
-            let dest = import_path.join(WORKSPACE_CONFIG_FILE);
-            if let Some(parent) = dest.parent() {
-                fs::create_dir_all(parent).await?;
-            }
-            fs::copy(config_path, &dest).await?;
-        }
-        Ok(())
-    }
-
-    async fn import_workspace_files(
-        &self,
-        files_path: &Path,
-        import_path: &Path,
-    ) -> WorkspaceResult<()> {
-        let mut total_size = 0;
-        let mut file_count = 0;
-        Self::copy_directory_recursive(files_path, import_path, &mut total_size, &mut file_count)
-            .await
-    }
 }
 
 /// Workspace integrity report
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct IntegrityReport {
     pub workspace_path: PathBuf,
@@ -892,13 +1125,197 @@ pub struct WorkspaceExportInfo {
     pub total_size: u64,
     pub file_count: usize,
     pub includes_files: bool,
+    /// SHA-256 of the bundle file
     pub checksum: String,
+    pub signer_fingerprint: String,
+    pub encrypted: bool,
+    pub components: Vec<BundleComponent>,
+}
+
+/// Replication of finished backups with a fixed configuration. It does not
+/// borrow the manager, so uploads can run after the manager's lock is released.
+pub struct BackupReplicator {
+    config: WorkspaceBackupConfig,
+    secrets: BackupSecrets,
+}
+
+impl BackupReplicator {
+    /// Copy a backup to every configured destination and record how each went
+    /// in its `replicas`. A destination that fails is recorded rather than
+    /// failing the backup.
+    pub async fn replicate(
+        &self,
+        mut backup_metadata: WorkspaceBackupMetadata,
+    ) -> WorkspaceResult<WorkspaceBackupMetadata> {
+        if self.config.destinations().is_empty() {
+            return Ok(backup_metadata);
+        }
+
+        let store = store_of(&self.config, &backup_metadata)?;
+        let _store_lock = lock_store(store.root()).await;
+        // Retention may have removed the backup while replication waited
+        if !fs::try_exists(&backup_metadata.backup_path)
+            .await
+            .unwrap_or(false)
+        {
+            warn!(
+                "Backup {} was removed before it could be replicated",
+                backup_metadata.backup_id
+            );
+            return Ok(backup_metadata);
+        }
+        backup_metadata.replicas = self.push(&backup_metadata).await?;
+        store_backup_metadata(&backup_metadata).await?;
+        Ok(backup_metadata)
+    }
+
+    /// Download every blob of a backup from each destination holding it and
+    /// check it against its hash
+    pub async fn verify(&self, workspace_path: &Path, backup_id: &str) -> Vec<ReplicaStatus> {
+        let mut statuses = Vec::new();
+        for destination in self.config.destinations() {
+            let outcome = async {
+                let passphrase = replica_passphrase(&self.config, &self.secrets).await?;
+                let replica = Replica::open(
+                    &destination,
+                    workspace_path,
+                    passphrase.as_deref(),
+                    &self.secrets,
+                )
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("No backups of this workspace"))?;
+                replica.verify(backup_id, true).await
+            }
+            .await;
+            statuses.push(match outcome {
+                Ok(problems) => ReplicaStatus::new(destination.label(), 0, problems),
+                Err(e) => ReplicaStatus::failed(destination.label(), &e),
+            });
+        }
+        statuses
+    }
+
+    /// Upload a backup to every destination and prune each replica with the
+    /// retention policy
+    async fn push(
+        &self,
+        backup_metadata: &WorkspaceBackupMetadata,
+    ) -> WorkspaceResult<Vec<ReplicaStatus>> {
+        let store = store_of(&self.config, backup_metadata)?;
+        let manifest = {
+            let store = store.clone();
+            let manifest_path = backup_metadata.backup_path.clone();
+            run_blocking(move || store.load_manifest(&manifest_path)).await?
+        };
+
+        let mut statuses = Vec::new();
+        for destination in self.config.destinations() {
+            let outcome = async {
+                let passphrase = replica_passphrase(&self.config, &self.secrets).await?;
+                let replica = Replica::open_or_create(
+                    &destination,
+                    &backup_metadata.workspace_path,
+                    passphrase.as_deref(),
+                    &self.secrets,
+                )
+                .await?;
+                let uploaded = replica.push(&store, backup_metadata, &manifest).await?;
+                let problems = replica.verify(&backup_metadata.backup_id, false).await?;
+                replica.prune(&self.config).await?;
+                Ok::<_, anyhow::Error>((uploaded, problems))
+            }
+            .await;
+
+            let status = match outcome {
+                Ok((uploaded, problems)) => {
+                    ReplicaStatus::new(destination.label(), uploaded, problems)
+                }
+                Err(e) => ReplicaStatus::failed(destination.label(), &e),
+            };
+            if let Some(error) = &status.error {
+                warn!(
+                    "Failed to replicate backup {} to {}: {}",
+                    backup_metadata.backup_id, status.destination, error
+                );
+            }
+            statuses.push(status);
+        }
+        Ok(statuses)
+    }
+}
+
+/// The replica passphrase, looked up in the secret store
+async fn replica_passphrase(
+    config: &WorkspaceBackupConfig,
+    secrets: &BackupSecrets,
+) -> anyhow::Result<Option<String>> {
+    match &config.replica_passphrase_ref {
+        Some(reference) => secrets.resolve(reference).await.map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Store holding the blobs of a snapshot, whose manifest lives in its
+/// `snapshots/` directory
+fn store_of(
+    config: &WorkspaceBackupConfig,
+    backup_metadata: &WorkspaceBackupMetadata,
+) -> WorkspaceResult<BackupStore> {
+    backup_metadata
+        .backup_path
+        .parent()
+        .and_then(Path::parent)
+        .map(|root| BackupStore::new(root, config.compress_backups))
+        .ok_or_else(|| WorkspaceError::InvalidWorkspace {
+            path: backup_metadata.backup_path.clone(),
+            reason: "Backup is not inside a backup store".to_string(),
+        })
+}
+
+/// Hold the lock on the backup store at `root`
+async fn lock_store(root: &Path) -> OwnedMutexGuard<()> {
+    let lock = STORE_LOCKS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(root.to_path_buf())
+        .or_default()
+        .clone();
+    lock.lock_owned().await
+}
+
+async fn store_backup_metadata(metadata: &WorkspaceBackupMetadata) -> WorkspaceResult<()> {
+    let backup_dir = metadata.backup_path.parent().unwrap();
+    let metadata_path = backup_dir.join(format!("{}.metadata", metadata.backup_id));
+    let content = serde_json::to_string_pretty(metadata)?;
+    fs::write(&metadata_path, content).await?;
+    Ok(())
+}
+
+fn backup_error(error: anyhow::Error) -> WorkspaceError {
+    WorkspaceError::Backup {
+        message: format!("{:#}", error),
+    }
+}
+
+/// Run blocking backup store work off the async runtime
+async fn run_blocking<T, F>(work: F) -> WorkspaceResult<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> anyhow::Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| WorkspaceError::Backup {
+            message: e.to_string(),
+        })?
+        .map_err(backup_error)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::app_config::ConfigManager;
+    use crate::workspace::test_fixtures::create_workspace;
     use tempfile::TempDir;
 
     #[tokio::test]
@@ -913,6 +1330,130 @@ mod tests {
         assert_eq!(backup_config.backup_interval_minutes, 60);
     }
 
+    #[tokio::test]
+    async fn test_incremental_snapshots_restore_and_prune() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        create_workspace(
+            &workspace,
+            "Backups",
+            &[
+                ("docs/manual.md", "# Manual\n".repeat(200).as_bytes()),
+                ("docs/notes.txt", b"first draft"),
+            ],
+        );
+
+        let mut manager = WorkspaceBackupManager::with_config(
+            Arc::new(BackupManager::new().unwrap()),
+            WorkspaceBackupConfig {
+                max_backups: 2,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let full = manager
+            .create_backup(&workspace, WorkspaceBackupType::Full)
+            .await
+            .unwrap();
+        assert_eq!(full.file_count, 3);
+        assert_eq!(full.checksum.len(), 64);
+
+        fs::write(workspace.join("docs/notes.txt"), "second draft")
+            .await
+            .unwrap();
+        let incremental = manager
+            .create_backup(&workspace, WorkspaceBackupType::Incremental)
+            .await
+            .unwrap();
+        assert_eq!(incremental.file_count, 3);
+
+        // Only the edited file added a blob on top of the full snapshot
+        let store = BackupStore::new(workspace.join(".fiovana/backups"), true);
+        let manifest = store.load_manifest(&incremental.backup_path).unwrap();
+        assert_eq!(manifest.parent_id.as_deref(), Some(full.backup_id.as_str()));
+        let blobs = collect_files(&store.root().join("blobs"), &[]).unwrap();
+        assert_eq!(blobs.len(), 4);
+
+        let restored = temp_dir.path().join("restored");
+        manager
+            .restore_workspace(&incremental, &restored, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(restored.join("docs/notes.txt"))
+                .await
+                .unwrap(),
+            "second draft"
+        );
+        assert_eq!(
+            fs::read_to_string(restored.join("docs/manual.md"))
+                .await
+                .unwrap(),
+            "# Manual\n".repeat(200)
+        );
+
+        // A third snapshot pushes the full one out, and its only unshared blob with it
+        fs::write(workspace.join("docs/notes.txt"), "final")
+            .await
+            .unwrap();
+        manager
+            .create_backup(&workspace, WorkspaceBackupType::Incremental)
+            .await
+            .unwrap();
+        let backups = manager.get_available_backups(&workspace).await.unwrap();
+        assert_eq!(backups.len(), 2);
+        assert!(!full.backup_path.exists());
+        let blobs = collect_files(&store.root().join("blobs"), &[]).unwrap();
+        assert_eq!(blobs.len(), 4);
+
+        // Tampered manifests are refused
+        fs::write(&incremental.backup_path, "{}").await.unwrap();
+        assert!(manager
+            .restore_workspace(&incremental, &restored, true)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_emergency_checkpoints_do_not_evict_backups() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        create_workspace(&workspace, "Backups", &[("docs/notes.txt", b"draft")]);
+
+        let mut manager = WorkspaceBackupManager::with_config(
+            Arc::new(BackupManager::new().unwrap()),
+            WorkspaceBackupConfig {
+                max_backups: 2,
+                max_emergency_checkpoints: 1,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let full = manager
+            .create_backup(&workspace, WorkspaceBackupType::Full)
+            .await
+            .unwrap();
+        for _ in 0..3 {
+            manager
+                .create_pre_operation_snapshot(&workspace, RiskyOperation::Reorganization)
+                .await
+                .unwrap();
+        }
+
+        let backups = manager.get_available_backups(&workspace).await.unwrap();
+        assert_eq!(backups.len(), 2);
+        assert!(backups
+            .iter()
+            .any(|backup| backup.backup_id == full.backup_id));
+        assert_eq!(
+            backups
+                .iter()
+                .filter(|backup| matches!(backup.backup_type, WorkspaceBackupType::Emergency))
+                .count(),
+            1
+        );
+    }
+
     #[test]
     fn test_integrity_report() {
         let temp_path = PathBuf::from("/tmp/test");
@@ -0,0 +1,885 @@
+// src-tauri/src/workspace/backup_replication.rs
+//! Replication of workspace backups to other locations
+//!
+//! Every destination, a directory on another drive or network mount or an
+//! S3-compatible bucket, holds a replica of the workspace's backup store under
+//! `fiovana-backups/<workspace id>/`: `blobs/` with file contents and
+//! `snapshots/` with one object per snapshot holding its metadata and manifest.
+//! With a passphrase every object is compressed and AES-GCM encrypted before it
+//! leaves this machine, and blobs are named by a keyed hash of their content so
+//! the destination cannot tell which files they hold.
+
+use super::backup::{WorkspaceBackupConfig, WorkspaceBackupMetadata};
+use super::backup_s3::{S3Client, S3Destination};
+use super::backup_secrets::BackupSecrets;
+use super::backup_store::{BackupStore, SnapshotManifest};
+use super::bundle::{KeyDerivation, Sealer};
+use crate::filesystem::atomic_write::write_atomically;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::sync::Mutex as AsyncMutex;
+
+/// Directory or key prefix replicas live under at a destination
+pub const REPLICA_ROOT: &str = "fiovana-backups";
+
+/// File in a workspace holding the ID its replicas are stored under, so they
+/// follow the workspace when it is moved or renamed
+pub(crate) const WORKSPACE_ID_FILE: &str = ".fiovana/replica-id";
+
+/// Workspace IDs by path, kept with the backup secrets so a workspace
+/// recreated where a lost one was finds that one's replicas
+const KNOWN_WORKSPACES_FILE: &str = "replica-workspaces.json";
+
+static WORKSPACE_IDS_LOCK: Lazy<AsyncMutex<()>> = Lazy::new(|| AsyncMutex::new(()));
+
+const FORMAT_VERSION: u32 = 1;
+const HEADER_KEY: &str = "replica.json";
+const BLOBS_PREFIX: &str = "blobs/";
+const SNAPSHOTS_PREFIX: &str = "snapshots/";
+
+/// Sealed into the header so a wrong passphrase is told apart from damage
+const KEY_CHECK: &[u8] = b"fiovana backup replica";
+
+/// A place backups are replicated to
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackupDestination {
+    Directory(PathBuf),
+    S3(S3Destination),
+}
+
+impl BackupDestination {
+    /// Name shown for the destination and used to pick it for restores
+    pub fn label(&self) -> String {
+        match self {
+            Self::Directory(path) => path.display().to_string(),
+            Self::S3(destination) => destination.label(),
+        }
+    }
+
+    /// Whether backups may only leave this machine encrypted
+    fn requires_encryption(&self) -> bool {
+        matches!(self, Self::S3(_))
+    }
+}
+
+/// How replicating or verifying a backup at one destination went
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicaStatus {
+    pub destination: String,
+    pub checked_at: DateTime<Utc>,
+    /// Blobs uploaded because the destination did not have them yet
+    pub uploaded_blobs: usize,
+    pub verified: bool,
+    pub error: Option<String>,
+}
+
+impl ReplicaStatus {
+    pub(crate) fn new(destination: String, uploaded_blobs: usize, problems: Vec<String>) -> Self {
+        Self {
+            destination,
+            checked_at: Utc::now(),
+            uploaded_blobs,
+            verified: problems.is_empty(),
+            error: (!problems.is_empty()).then(|| problems.join("; ")),
+        }
+    }
+
+    pub(crate) fn failed(destination: String, error: &anyhow::Error) -> Self {
+        Self {
+            destination,
+            checked_at: Utc::now(),
+            uploaded_blobs: 0,
+            verified: false,
+            error: Some(format!("{:#}", error)),
+        }
+    }
+}
+
+/// Backups of a workspace held at one destination
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupLocationInfo {
+    pub destination: String,
+    pub reachable: bool,
+    pub error: Option<String>,
+    /// Newest first
+    pub backups: Vec<WorkspaceBackupMetadata>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ReplicaHeader {
+    format_version: u32,
+    encryption: Option<ReplicaEncryption>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ReplicaEncryption {
+    #[serde(flatten)]
+    derivation: KeyDerivation,
+    /// Hex of `KEY_CHECK` sealed with the replica key
+    key_check: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ReplicaSnapshot {
+    metadata: WorkspaceBackupMetadata,
+    manifest: SnapshotManifest,
+}
+
+/// The backups of one workspace at one destination
+pub struct Replica {
+    destination: String,
+    storage: Storage,
+    sealer: Sealer,
+}
+
+impl Replica {
+    /// Open the replica of a workspace, or `None` when the destination holds
+    /// no backups of it
+    pub async fn open(
+        destination: &BackupDestination,
+        workspace_path: &Path,
+        passphrase: Option<&str>,
+        secrets: &BackupSecrets,
+    ) -> Result<Option<Self>> {
+        let label = destination.label();
+        let workspace_id = workspace_id(workspace_path, secrets.dir()).await?;
+        let storage = Storage::new(destination, &workspace_id, secrets).await?;
+        let Some(header) = storage.get(HEADER_KEY).await? else {
+            return Ok(None);
+        };
+        let header: ReplicaHeader = serde_json::from_slice(&header)
+            .with_context(|| format!("Backups at {} have a damaged header", label))?;
+        if header.format_version > FORMAT_VERSION {
+            return Err(anyhow!(
+                "Backups at {} need a newer version of Fiovana",
+                label
+            ));
+        }
+
+        let sealer = match (&header.encryption, passphrase) {
+            (Some(encryption), Some(passphrase)) => {
+                let sealer = Sealer::for_passphrase(passphrase, &encryption.derivation)?;
+                let check =
+                    hex::decode(&encryption.key_check).context("Replica key check is malformed")?;
+                match sealer.open(HEADER_KEY, &check) {
+                    Ok(plain) if plain == KEY_CHECK => sealer,
+                    _ => return Err(anyhow!("Wrong replica passphrase for {}", label)),
+                }
+            }
+            (Some(_), None) => {
+                return Err(anyhow!(
+                    "Backups at {} are encrypted; a replica passphrase is required",
+                    label
+                ))
+            }
+            (None, _) => Sealer::plain(),
+        };
+
+        Ok(Some(Self {
+            destination: label,
+            storage,
+            sealer,
+        }))
+    }
+
+    /// Open the replica of a workspace for writing, setting it up on first use
+    pub async fn open_or_create(
+        destination: &BackupDestination,
+        workspace_path: &Path,
+        passphrase: Option<&str>,
+        secrets: &BackupSecrets,
+    ) -> Result<Self> {
+        if let Some(replica) = Self::open(destination, workspace_path, passphrase, secrets).await? {
+            if passphrase.is_some() && !replica.sealer.is_encrypted() {
+                return Err(anyhow!(
+                    "Backups at {} were replicated without encryption; clear the replica \
+                     passphrase or choose another location",
+                    replica.destination
+                ));
+            }
+            return Ok(replica);
+        }
+
+        let label = destination.label();
+        if destination.requires_encryption() && passphrase.is_none() {
+            return Err(anyhow!(
+                "{} only receives encrypted backups; set a replica passphrase",
+                label
+            ));
+        }
+
+        let (encryption, sealer) = match passphrase {
+            Some(passphrase) => {
+                let derivation = KeyDerivation::generate()?;
+                let sealer = Sealer::for_passphrase(passphrase, &derivation)?;
+                let encryption = ReplicaEncryption {
+                    derivation,
+                    key_check: hex::encode(sealer.seal(HEADER_KEY, KEY_CHECK)?),
+                };
+                (Some(encryption), sealer)
+            }
+            None => (None, Sealer::plain()),
+        };
+
+        let workspace_id = workspace_id(workspace_path, secrets.dir()).await?;
+        let storage = Storage::new(destination, &workspace_id, secrets).await?;
+        let header = ReplicaHeader {
+            format_version: FORMAT_VERSION,
+            encryption,
+        };
+        storage
+            .put(HEADER_KEY, serde_json::to_vec_pretty(&header)?)
+            .await?;
+
+        Ok(Self {
+            destination: label,
+            storage,
+            sealer,
+        })
+    }
+
+    /// Upload the blobs of `manifest` the replica lacks, then the snapshot
+    /// itself, so a snapshot is never visible before its content. Returns the
+    /// number of blobs uploaded.
+    pub async fn push(
+        &self,
+        store: &BackupStore,
+        metadata: &WorkspaceBackupMetadata,
+        manifest: &SnapshotManifest,
+    ) -> Result<usize> {
+        let present: HashSet<String> = self.storage.list(BLOBS_PREFIX).await?.into_iter().collect();
+
+        let mut uploaded = 0;
+        for hash in unique_hashes(manifest) {
+            let name = self.blob_key(hash);
+            if present.contains(&name) {
+                continue;
+            }
+            let content = {
+                let store = store.clone();
+                let hash = hash.to_string();
+                tokio::task::spawn_blocking(move || store.read_blob(&hash)).await??
+            };
+            self.storage.put(&name, self.seal(&name, &content)?).await?;
+            uploaded += 1;
+        }
+
+        let key = snapshot_key(&metadata.backup_id);
+        let snapshot = ReplicaSnapshot {
+            metadata: metadata.clone(),
+            manifest: manifest.clone(),
+        };
+        self.storage
+            .put(&key, self.seal(&key, &serde_json::to_vec(&snapshot)?)?)
+            .await?;
+        Ok(uploaded)
+    }
+
+    /// Problems with a replicated backup: the snapshot must read back and
+    /// every blob it needs must be present. With `deep`, every blob is also
+    /// downloaded and checked against its hash.
+    pub async fn verify(&self, backup_id: &str, deep: bool) -> Result<Vec<String>> {
+        let snapshot = self.load_snapshot(&snapshot_key(backup_id)).await?;
+        let present: HashSet<String> = self.storage.list(BLOBS_PREFIX).await?.into_iter().collect();
+
+        let mut problems = Vec::new();
+        let mut checked = HashSet::new();
+        for entry in &snapshot.manifest.files {
+            if !checked.insert(entry.hash.as_str()) {
+                continue;
+            }
+            let name = self.blob_key(&entry.hash);
+            if !present.contains(&name) {
+                problems.push(format!("{}: missing from {}", entry.path, self.destination));
+            } else if deep {
+                if let Err(e) = self.load_blob(&name, &entry.hash).await {
+                    problems.push(format!("{}: {}", entry.path, e));
+                }
+            }
+        }
+        Ok(problems)
+    }
+
+    /// Backups held at this destination, newest first
+    pub async fn backups(&self) -> Result<Vec<WorkspaceBackupMetadata>> {
+        Ok(self
+            .load_snapshots()
+            .await?
+            .into_iter()
+            .map(|snapshot| snapshot.metadata)
+            .collect())
+    }
+
+    /// Apply the retention policy to this replica, then delete blobs no
+    /// remaining snapshot needs. Returns the number of snapshots removed.
+    pub async fn prune(&self, config: &WorkspaceBackupConfig) -> Result<usize> {
+        let snapshots = self.load_snapshots().await?;
+        let retained = config.retained(snapshots.iter().map(|snapshot| &snapshot.metadata));
+        let (kept, expired): (Vec<_>, Vec<_>) = snapshots
+            .into_iter()
+            .zip(retained)
+            .partition(|(_, keep)| *keep);
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        for (snapshot, _) in &expired {
+            self.storage
+                .delete(&snapshot_key(&snapshot.metadata.backup_id))
+                .await?;
+        }
+
+        let referenced: HashSet<String> = kept
+            .iter()
+            .flat_map(|(snapshot, _)| unique_hashes(&snapshot.manifest))
+            .map(|hash| self.blob_key(hash))
+            .collect();
+        for name in self.storage.list(BLOBS_PREFIX).await? {
+            if !referenced.contains(&name) {
+                self.storage.delete(&name).await?;
+            }
+        }
+        Ok(expired.len())
+    }
+
+    /// Download a backup and the blobs it needs into `store`, checking each
+    /// against its hash
+    pub async fn fetch(
+        &self,
+        backup_id: &str,
+        store: &BackupStore,
+    ) -> Result<(WorkspaceBackupMetadata, SnapshotManifest)> {
+        let snapshot = self.load_snapshot(&snapshot_key(backup_id)).await?;
+        for hash in unique_hashes(&snapshot.manifest) {
+            let content = self.load_blob(&self.blob_key(hash), hash).await?;
+            let store = store.clone();
+            let hash = hash.to_string();
+            tokio::task::spawn_blocking(move || store.insert_blob(&hash, &content)).await??;
+        }
+        Ok((snapshot.metadata, snapshot.manifest))
+    }
+
+    async fn load_snapshots(&self) -> Result<Vec<ReplicaSnapshot>> {
+        let mut snapshots = Vec::new();
+        for key in self.storage.list(SNAPSHOTS_PREFIX).await? {
+            snapshots.push(self.load_snapshot(&key).await?);
+        }
+        snapshots.sort_by(|a, b| {
+            b.metadata
+                .backup_timestamp
+                .cmp(&a.metadata.backup_timestamp)
+        });
+        Ok(snapshots)
+    }
+
+    async fn load_snapshot(&self, key: &str) -> Result<ReplicaSnapshot> {
+        let sealed = self
+            .storage
+            .get(key)
+            .await?
+            .ok_or_else(|| anyhow!("{} not found at {}", key, self.destination))?;
+        serde_json::from_slice(&self.open_sealed(key, &sealed)?)
+            .with_context(|| format!("{} at {} is damaged", key, self.destination))
+    }
+
+    async fn load_blob(&self, name: &str, hash: &str) -> Result<Vec<u8>> {
+        let sealed = self
+            .storage
+            .get(name)
+            .await?
+            .ok_or_else(|| anyhow!("Blob {} is missing from {}", hash, self.destination))?;
+        let content = self.open_sealed(name, &sealed)?;
+        if hex::encode(Sha256::digest(&content)) != hash {
+            return Err(anyhow!("content does not match its hash"));
+        }
+        Ok(content)
+    }
+
+    fn blob_key(&self, hash: &str) -> String {
+        format!("{}{}", BLOBS_PREFIX, self.sealer.object_name(hash))
+    }
+
+    fn seal(&self, name: &str, plain: &[u8]) -> Result<Vec<u8>> {
+        self.sealer.seal(name, plain)
+    }
+
+    fn open_sealed(&self, name: &str, sealed: &[u8]) -> Result<Vec<u8>> {
+        self.sealer
+            .open(name, sealed)
+            .with_context(|| format!("{} at {} is damaged", name, self.destination))
+    }
+}
+
+/// Object storage at a destination, addressed by `/`-separated keys relative
+/// to the workspace's replica
+enum Storage {
+    Directory(PathBuf),
+    S3 {
+        client: Box<S3Client>,
+        prefix: String,
+    },
+}
+
+impl Storage {
+    async fn new(
+        destination: &BackupDestination,
+        workspace_id: &str,
+        secrets: &BackupSecrets,
+    ) -> Result<Self> {
+        match destination {
+            BackupDestination::Directory(path) => {
+                Ok(Self::Directory(path.join(REPLICA_ROOT).join(workspace_id)))
+            }
+            BackupDestination::S3(destination) => {
+                let mut prefix = destination.prefix.trim_matches('/').to_string();
+                if !prefix.is_empty() {
+                    prefix.push('/');
+                }
+                prefix.push_str(&format!("{}/{}/", REPLICA_ROOT, workspace_id));
+                let secret_access_key = secrets
+                    .resolve(&destination.secret_access_key_ref)
+                    .await
+                    .with_context(|| {
+                    format!("No secret access key for {}", destination.label())
+                })?;
+                Ok(Self::S3 {
+                    client: Box::new(S3Client::new(destination.clone(), secret_access_key)?),
+                    prefix,
+                })
+            }
+        }
+    }
+
+    async fn put(&self, key: &str, content: Vec<u8>) -> Result<()> {
+        match self {
+            Self::Directory(root) => {
+                let path = root.join(key);
+                tokio::task::spawn_blocking(move || write_atomically(&path, &content)).await?
+            }
+            Self::S3 { client, prefix } => client.put(&format!("{}{}", prefix, key), content).await,
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match self {
+            Self::Directory(root) => match fs::read(root.join(key)).await {
+                Ok(content) => Ok(Some(content)),
+                Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e).with_context(|| format!("Failed to read {}", key)),
+            },
+            Self::S3 { client, prefix } => client.get(&format!("{}{}", prefix, key)).await,
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match self {
+            Self::Directory(root) => match fs::remove_file(root.join(key)).await {
+                Err(e) if e.kind() != ErrorKind::NotFound => {
+                    Err(e).with_context(|| format!("Failed to delete {}", key))
+                }
+                _ => Ok(()),
+            },
+            Self::S3 { client, prefix } => client.delete(&format!("{}{}", prefix, key)).await,
+        }
+    }
+
+    /// Keys of the objects in the `prefix` directory
+    async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        match self {
+            Self::Directory(root) => {
+                let dir = root.join(prefix);
+                let mut entries = match fs::read_dir(&dir).await {
+                    Ok(entries) => entries,
+                    Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+                    Err(e) => {
+                        return Err(e).with_context(|| format!("Failed to list {}", dir.display()))
+                    }
+                };
+                let mut keys = Vec::new();
+                while let Some(entry) = entries.next_entry().await? {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    if !name.contains(".tmp-") {
+                        keys.push(format!("{}{}", prefix, name));
+                    }
+                }
+                keys.sort();
+                Ok(keys)
+            }
+            Self::S3 {
+                client,
+                prefix: root,
+            } => Ok(client
+                .list(&format!("{}{}", root, prefix))
+                .await?
+                .into_iter()
+                .filter_map(|key| key.strip_prefix(root.as_str()).map(str::to_string))
+                .collect()),
+        }
+    }
+}
+
+/// ID a workspace's replicas are stored under, read from the workspace. A
+/// workspace without one gets the ID last recorded for its path in
+/// `registry_dir`, or a new one.
+async fn workspace_id(workspace_path: &Path, registry_dir: Option<&Path>) -> Result<String> {
+    let _guard = WORKSPACE_IDS_LOCK.lock().await;
+    let registry_path = registry_dir.map(|dir| dir.join(KNOWN_WORKSPACES_FILE));
+    let mut known: BTreeMap<String, String> = match &registry_path {
+        Some(path) => match fs::read(path).await {
+            Ok(content) => serde_json::from_slice(&content).unwrap_or_default(),
+            Err(e) if e.kind() == ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        },
+        None => BTreeMap::new(),
+    };
+    let path_key = workspace_path.to_string_lossy().into_owned();
+
+    let id_path = workspace_path.join(WORKSPACE_ID_FILE);
+    let id = match fs::read_to_string(&id_path).await {
+        Ok(id) => id.trim().to_string(),
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            let id = known
+                .get(&path_key)
+                .cloned()
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+            if let Some(parent) = id_path.parent() {
+                fs::create_dir_all(parent)
+                    .await
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            fs::write(&id_path, &id)
+                .await
+                .with_context(|| format!("Failed to write {}", id_path.display()))?;
+            id
+        }
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", id_path.display())),
+    };
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(anyhow!("{} is damaged", id_path.display()));
+    }
+
+    if let Some(registry_path) = registry_path {
+        if known.get(&path_key) != Some(&id) {
+            known.insert(path_key, id.clone());
+            if let Some(parent) = registry_path.parent() {
+                fs::create_dir_all(parent)
+                    .await
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            fs::write(&registry_path, serde_json::to_vec_pretty(&known)?)
+                .await
+                .with_context(|| format!("Failed to write {}", registry_path.display()))?;
+        }
+    }
+    Ok(id)
+}
+
+fn snapshot_key(backup_id: &str) -> String {
+    format!("{}{}", SNAPSHOTS_PREFIX, backup_id)
+}
+
+fn unique_hashes(manifest: &SnapshotManifest) -> impl Iterator<Item = &str> {
+    let mut seen = HashSet::new();
+    manifest
+        .files
+        .iter()
+        .map(|entry| entry.hash.as_str())
+        .filter(move |hash| seen.insert(*hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem::security::backup_manager::BackupManager;
+    use crate::workspace::backup::{WorkspaceBackupManager, WorkspaceBackupType};
+    use crate::workspace::backup_s3::stand_in;
+    use crate::workspace::test_fixtures::create_workspace;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    const SECRET: &[u8] = b"Quarterly numbers nobody else should see";
+
+    fn manager(secrets: &BackupSecrets, config: WorkspaceBackupConfig) -> WorkspaceBackupManager {
+        let mut manager =
+            WorkspaceBackupManager::with_config(Arc::new(BackupManager::new().unwrap()), config)
+                .unwrap();
+        manager.set_secrets(secrets.clone());
+        manager
+    }
+
+    fn files_below(dir: &Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else {
+                    files.push(path);
+                }
+            }
+        }
+        files
+    }
+
+    #[tokio::test]
+    async fn test_directory_replica_survives_losing_the_workspace() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("disk").join("workspace");
+        let drive = temp_dir.path().join("drive");
+        let secrets = BackupSecrets::in_dir(&temp_dir.path().join("config"));
+        create_workspace(&workspace, "Replicated", &[("docs/report.txt", SECRET)]);
+
+        let config = WorkspaceBackupConfig {
+            backup_locations: vec![drive.clone()],
+            replica_passphrase_ref: Some(secrets.store("correct horse").await.unwrap()),
+            ..Default::default()
+        };
+        let mut manager = manager(&secrets, config.clone());
+        let backup = manager
+            .create_backup(&workspace, WorkspaceBackupType::Full)
+            .await
+            .unwrap();
+        assert_eq!(backup.replicas.len(), 1);
+        assert!(backup.replicas[0].verified, "{:?}", backup.replicas[0]);
+        assert_eq!(backup.replicas[0].uploaded_blobs, 2);
+
+        // Neither content nor content hashes reach the destination in the clear
+        let secret_hash = hex::encode(Sha256::digest(SECRET));
+        for file in files_below(&drive) {
+            let content = std::fs::read(&file).unwrap();
+            assert!(!content.windows(9).any(|window| window == &SECRET[..9]));
+            assert!(!file.to_string_lossy().contains(&secret_hash));
+        }
+
+        // The disk holding the workspace and its own backups fails
+        fs::remove_dir_all(temp_dir.path().join("disk"))
+            .await
+            .unwrap();
+        let info = manager.get_recovery_info(&workspace).await.unwrap();
+        assert!(info.available_backups.is_empty());
+        assert_eq!(info.locations.len(), 1);
+        assert_eq!(info.locations[0].backups.len(), 1);
+        assert!(info
+            .recovery_options
+            .iter()
+            .any(|option| option.description.contains(&info.locations[0].destination)));
+
+        manager
+            .restore_from_replica(
+                &workspace,
+                &drive.display().to_string(),
+                &backup.backup_id,
+                &workspace,
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            fs::read(workspace.join("docs/report.txt")).await.unwrap(),
+            SECRET
+        );
+        assert!(!files_below(temp_dir.path().join("disk").as_path())
+            .iter()
+            .any(|file| file.to_string_lossy().contains(".replica-")));
+
+        let wrong = manager_with_passphrase(&secrets, &config, "wrong horse").await;
+        let locations = wrong.get_replica_backups(&workspace).await;
+        assert!(!locations[0].reachable);
+        assert!(locations[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("Wrong replica passphrase"));
+    }
+
+    #[tokio::test]
+    async fn test_backups_pruned_before_replication_are_skipped() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        let drive = temp_dir.path().join("drive");
+        let secrets = BackupSecrets::in_dir(&temp_dir.path().join("config"));
+        create_workspace(&workspace, "Replicated", &[("docs/report.txt", SECRET)]);
+
+        let config = WorkspaceBackupConfig {
+            max_backups: 1,
+            backup_locations: vec![drive.clone()],
+            replica_passphrase_ref: Some(secrets.store("correct horse").await.unwrap()),
+            ..Default::default()
+        };
+        let mut manager = manager(&secrets, config);
+        let first = manager
+            .create_local_backup(&workspace, WorkspaceBackupType::Full)
+            .await
+            .unwrap();
+        let replicator = manager.replicator();
+
+        // A newer backup's retention removes the first while it waits to replicate
+        fs::write(workspace.join("docs/report.txt"), "revised")
+            .await
+            .unwrap();
+        manager
+            .create_local_backup(&workspace, WorkspaceBackupType::Full)
+            .await
+            .unwrap();
+        assert!(!first.backup_path.exists());
+
+        let replicated = replicator.replicate(first.clone()).await.unwrap();
+        assert!(replicated.replicas.is_empty());
+        assert!(!first
+            .backup_path
+            .with_file_name(format!("{}.metadata", first.backup_id))
+            .exists());
+        assert!(!drive.exists());
+    }
+
+    async fn manager_with_passphrase(
+        secrets: &BackupSecrets,
+        config: &WorkspaceBackupConfig,
+        passphrase: &str,
+    ) -> WorkspaceBackupManager {
+        manager(
+            secrets,
+            WorkspaceBackupConfig {
+                replica_passphrase_ref: Some(secrets.store(passphrase).await.unwrap()),
+                ..config.clone()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_replicas_follow_a_moved_workspace() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        let moved = temp_dir.path().join("renamed");
+        let drive = temp_dir.path().join("drive");
+        let secrets = BackupSecrets::in_dir(&temp_dir.path().join("config"));
+        create_workspace(&workspace, "Replicated", &[("docs/report.txt", SECRET)]);
+
+        let mut manager = manager(
+            &secrets,
+            WorkspaceBackupConfig {
+                backup_locations: vec![drive.clone()],
+                ..Default::default()
+            },
+        );
+        let backup = manager
+            .create_backup(&workspace, WorkspaceBackupType::Full)
+            .await
+            .unwrap();
+        assert!(backup.replicas[0].verified, "{:?}", backup.replicas[0]);
+
+        fs::rename(&workspace, &moved).await.unwrap();
+        let locations = manager.get_replica_backups(&moved).await;
+        assert_eq!(locations[0].backups.len(), 1);
+        assert_eq!(locations[0].backups[0].backup_id, backup.backup_id);
+    }
+
+    #[tokio::test]
+    async fn test_s3_replicas_are_pruned_and_verified() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        let secrets = BackupSecrets::in_dir(&temp_dir.path().join("config"));
+        create_workspace(&workspace, "Replicated", &[("docs/report.txt", SECRET)]);
+        let (address, objects) = stand_in::serve().await;
+        let bucket = S3Destination {
+            endpoint: format!("http://{}", address),
+            bucket: "backups".to_string(),
+            region: "us-east-1".to_string(),
+            prefix: "team".to_string(),
+            access_key_id: "minio".to_string(),
+            secret_access_key_ref: secrets.store("minio-secret").await.unwrap(),
+        };
+
+        // Unencrypted backups never leave for a bucket
+        let mut plain = manager(
+            &secrets,
+            WorkspaceBackupConfig {
+                s3_destinations: vec![bucket.clone()],
+                ..Default::default()
+            },
+        );
+        let backup = plain
+            .create_backup(&workspace, WorkspaceBackupType::Full)
+            .await
+            .unwrap();
+        assert!(backup.replicas[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("passphrase"));
+        assert!(objects.lock().unwrap().is_empty());
+
+        let mut manager = manager(
+            &secrets,
+            WorkspaceBackupConfig {
+                s3_destinations: vec![bucket.clone()],
+                replica_passphrase_ref: Some(secrets.store("correct horse").await.unwrap()),
+                max_backups: 2,
+                ..Default::default()
+            },
+        );
+        let mut latest = None;
+        for revision in 0..3 {
+            fs::write(
+                workspace.join("docs/draft.txt"),
+                format!("revision {}", revision),
+            )
+            .await
+            .unwrap();
+            let backup = manager
+                .create_backup(&workspace, WorkspaceBackupType::Incremental)
+                .await
+                .unwrap();
+            assert!(backup.replicas[0].verified, "{:?}", backup.replicas[0]);
+            latest = Some(backup);
+        }
+        let latest = latest.unwrap();
+
+        let locations = manager.get_replica_backups(&workspace).await;
+        assert_eq!(locations[0].destination, bucket.label());
+        assert_eq!(locations[0].backups.len(), 2);
+        assert_eq!(locations[0].backups[0].backup_id, latest.backup_id);
+        // Metadata, report and the two remaining drafts
+        let blobs = objects
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|key| key.contains("/blobs/"))
+            .count();
+        assert_eq!(blobs, 4);
+
+        let statuses = manager.verify_replicas(&workspace, &latest.backup_id).await;
+        assert!(statuses[0].verified, "{:?}", statuses[0]);
+
+        // Damage every blob; verification reports it and restore refuses
+        for (key, content) in objects.lock().unwrap().iter_mut() {
+            if key.contains("/blobs/") {
+                let last = content.len() - 1;
+                content[last] ^= 1;
+            }
+        }
+        let statuses = manager.verify_replicas(&workspace, &latest.backup_id).await;
+        assert!(!statuses[0].verified);
+        let target = temp_dir.path().join("restored");
+        assert!(manager
+            .restore_from_replica(
+                &workspace,
+                &bucket.label(),
+                &latest.backup_id,
+                &target,
+                false
+            )
+            .await
+            .is_err());
+        assert!(!target.exists());
+    }
+}
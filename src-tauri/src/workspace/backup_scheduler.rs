@@ -0,0 +1,416 @@
+// src-tauri/src/workspace/backup_scheduler.rs
+//! Scheduled workspace backups and snapshots taken before risky operations
+//!
+//! Every open workspace gets a background task that takes an incremental
+//! snapshot each `backup_interval_minutes`, skipping runs while the resource
+//! limiter asks for throttling. Operations that rewrite workspace files call
+//! `snapshot_before` first so they can be rolled back in one step.
+
+use super::backup::{
+    RiskyOperation, WorkspaceBackupConfig, WorkspaceBackupManager, WorkspaceBackupMetadata,
+    WorkspaceBackupType,
+};
+use super::performance::WorkspaceResourceLimiter;
+use super::{WorkspaceResult, WORKSPACE_METADATA_FILE};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Mutex as AsyncMutex;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, MissedTickBehavior};
+use tracing::{debug, info, warn};
+
+/// Runs periodic backups for open workspaces and guards risky operations
+pub struct BackupScheduler {
+    manager: Arc<AsyncMutex<WorkspaceBackupManager>>,
+    limiter: Arc<WorkspaceResourceLimiter>,
+    schedules: Mutex<HashMap<PathBuf, JoinHandle<()>>>,
+}
+
+impl BackupScheduler {
+    pub fn new(manager: WorkspaceBackupManager, limiter: Arc<WorkspaceResourceLimiter>) -> Self {
+        Self {
+            manager: Arc::new(AsyncMutex::new(manager)),
+            limiter,
+            schedules: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Shared backup manager, for operations without a scheduler wrapper
+    pub fn manager(&self) -> Arc<AsyncMutex<WorkspaceBackupManager>> {
+        Arc::clone(&self.manager)
+    }
+
+    pub async fn config(&self) -> WorkspaceBackupConfig {
+        self.manager.lock().await.config().clone()
+    }
+
+    /// Replace the backup configuration and restart schedules with it
+    pub async fn update_config(&self, config: WorkspaceBackupConfig) {
+        self.manager.lock().await.set_config(config.clone());
+
+        let workspaces = self.scheduled_workspaces();
+        for workspace_path in &workspaces {
+            self.unschedule(workspace_path);
+        }
+        if config.auto_backup_enabled {
+            for workspace_path in workspaces {
+                self.spawn_schedule(workspace_path, &config);
+            }
+        }
+    }
+
+    /// Start periodic backups of a workspace. Returns false when automatic
+    /// backups are disabled; scheduling an already scheduled workspace is a
+    /// no-op.
+    pub async fn schedule(&self, workspace_path: &Path) -> bool {
+        let config = self.config().await;
+        if !config.auto_backup_enabled {
+            return false;
+        }
+        if !self.is_scheduled(workspace_path) {
+            self.spawn_schedule(workspace_path.to_path_buf(), &config);
+        }
+        true
+    }
+
+    /// Stop periodic backups of a workspace
+    pub fn unschedule(&self, workspace_path: &Path) -> bool {
+        match self.lock_schedules().remove(workspace_path) {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_scheduled(&self, workspace_path: &Path) -> bool {
+        self.lock_schedules()
+            .get(workspace_path)
+            .is_some_and(|handle| !handle.is_finished())
+    }
+
+    pub fn scheduled_workspaces(&self) -> Vec<PathBuf> {
+        self.lock_schedules().keys().cloned().collect()
+    }
+
+    /// Take the backup a schedule tick would take. Returns `None` when the
+    /// resource limiter asks for throttling.
+    pub async fn run_scheduled_backup(
+        &self,
+        workspace_path: &Path,
+    ) -> WorkspaceResult<Option<WorkspaceBackupMetadata>> {
+        scheduled_backup(&self.manager, &self.limiter, workspace_path).await
+    }
+
+    pub async fn create_backup(
+        &self,
+        workspace_path: &Path,
+        backup_type: WorkspaceBackupType,
+    ) -> WorkspaceResult<WorkspaceBackupMetadata> {
+        let _permit = self.limiter.acquire_fs_permit().await;
+        create_and_replicate(&self.manager, workspace_path, backup_type).await
+    }
+
+    /// Snapshot a workspace before `operation` changes it. Safety snapshots
+    /// are never skipped for throttling, only queued behind other file work.
+    pub async fn snapshot_before(
+        &self,
+        workspace_path: &Path,
+        operation: RiskyOperation,
+    ) -> WorkspaceResult<WorkspaceBackupMetadata> {
+        let _permit = self.limiter.acquire_fs_permit().await;
+        let snapshot = self
+            .manager
+            .lock()
+            .await
+            .create_pre_operation_snapshot(workspace_path, operation)
+            .await?;
+        info!(
+            "Snapshot {} taken before {} of {}",
+            snapshot.backup_id,
+            operation.description(),
+            workspace_path.display()
+        );
+        Ok(snapshot)
+    }
+
+    /// Snapshots taken before operations on the workspace, newest first
+    pub async fn checkpoints(
+        &self,
+        workspace_path: &Path,
+    ) -> WorkspaceResult<Vec<WorkspaceBackupMetadata>> {
+        let backups = self
+            .manager
+            .lock()
+            .await
+            .get_available_backups(workspace_path)
+            .await?;
+        Ok(backups
+            .into_iter()
+            .filter(|backup| backup.operation.is_some())
+            .collect())
+    }
+
+    /// Return the workspace to the snapshot taken before an operation
+    pub async fn rollback(
+        &self,
+        workspace_path: &Path,
+        backup_id: &str,
+    ) -> WorkspaceResult<WorkspaceBackupMetadata> {
+        let _permit = self.limiter.acquire_fs_permit().await;
+        self.manager
+            .lock()
+            .await
+            .rollback_workspace(workspace_path, backup_id)
+            .await
+    }
+
+    /// Restore a backup to `restore_path`, snapshotting the workspace already
+    /// there first. Returns that snapshot, if one was taken.
+    pub async fn restore(
+        &self,
+        backup_metadata: &WorkspaceBackupMetadata,
+        restore_path: &Path,
+        overwrite_existing: bool,
+    ) -> WorkspaceResult<Option<WorkspaceBackupMetadata>> {
+        let snapshot = self
+            .snapshot_restore_target(restore_path, overwrite_existing)
+            .await?;
+
+        let _permit = self.limiter.acquire_fs_permit().await;
+        self.manager
+            .lock()
+            .await
+            .restore_workspace(backup_metadata, restore_path, overwrite_existing)
+            .await?;
+        Ok(snapshot)
+    }
+
+    /// Restore a backup held at a replication destination, snapshotting the
+    /// workspace already at `restore_path` first
+    pub async fn restore_from_replica(
+        &self,
+        workspace_path: &Path,
+        destination: &str,
+        backup_id: &str,
+        restore_path: &Path,
+        overwrite_existing: bool,
+    ) -> WorkspaceResult<Option<WorkspaceBackupMetadata>> {
+        let snapshot = self
+            .snapshot_restore_target(restore_path, overwrite_existing)
+            .await?;
+
+        let _permit = self.limiter.acquire_fs_permit().await;
+        self.manager
+            .lock()
+            .await
+            .restore_from_replica(
+                workspace_path,
+                destination,
+                backup_id,
+                restore_path,
+                overwrite_existing,
+            )
+            .await?;
+        Ok(snapshot)
+    }
+
+    async fn snapshot_restore_target(
+        &self,
+        restore_path: &Path,
+        overwrite_existing: bool,
+    ) -> WorkspaceResult<Option<WorkspaceBackupMetadata>> {
+        if overwrite_existing && restore_path.join(WORKSPACE_METADATA_FILE).exists() {
+            self.snapshot_before(restore_path, RiskyOperation::Restore)
+                .await
+                .map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn spawn_schedule(&self, workspace_path: PathBuf, config: &WorkspaceBackupConfig) {
+        let period = Duration::from_secs(u64::from(config.backup_interval_minutes.max(1)) * 60);
+        let manager = Arc::clone(&self.manager);
+        let limiter = Arc::clone(&self.limiter);
+        let path = workspace_path.clone();
+
+        let handle = tokio::spawn(async move {
+            let mut ticks = tokio::time::interval_at(Instant::now() + period, period);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                ticks.tick().await;
+                if !path.join(WORKSPACE_METADATA_FILE).exists() {
+                    info!(
+                        "Stopping scheduled backups of {}: workspace is gone",
+                        path.display()
+                    );
+                    break;
+                }
+                match scheduled_backup(&manager, &limiter, &path).await {
+                    Ok(Some(backup)) => debug!(
+                        "Scheduled backup {} of {} stored {} files",
+                        backup.backup_id,
+                        path.display(),
+                        backup.file_count
+                    ),
+                    Ok(None) => debug!(
+                        "Skipped scheduled backup of {}: resources are throttled",
+                        path.display()
+                    ),
+                    Err(e) => warn!("Scheduled backup of {} failed: {}", path.display(), e),
+                }
+            }
+        });
+
+        if let Some(previous) = self.lock_schedules().insert(workspace_path, handle) {
+            previous.abort();
+        }
+    }
+
+    fn lock_schedules(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, JoinHandle<()>>> {
+        self.schedules
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Drop for BackupScheduler {
+    fn drop(&mut self) {
+        for (_, handle) in self.lock_schedules().drain() {
+            handle.abort();
+        }
+    }
+}
+
+async fn scheduled_backup(
+    manager: &AsyncMutex<WorkspaceBackupManager>,
+    limiter: &WorkspaceResourceLimiter,
+    workspace_path: &Path,
+) -> WorkspaceResult<Option<WorkspaceBackupMetadata>> {
+    if limiter.should_throttle() {
+        return Ok(None);
+    }
+    let _permit = limiter.acquire_fs_permit().await;
+    create_and_replicate(manager, workspace_path, WorkspaceBackupType::Incremental)
+        .await
+        .map(Some)
+}
+
+/// Take a backup under the manager's lock, then replicate it with the lock
+/// released so slow destinations do not hold up other backup operations
+async fn create_and_replicate(
+    manager: &AsyncMutex<WorkspaceBackupManager>,
+    workspace_path: &Path,
+    backup_type: WorkspaceBackupType,
+) -> WorkspaceResult<WorkspaceBackupMetadata> {
+    let (backup_metadata, replicator) = {
+        let mut manager = manager.lock().await;
+        let backup_metadata = manager
+            .create_local_backup(workspace_path, backup_type)
+            .await?;
+        (backup_metadata, manager.replicator())
+    };
+    replicator.replicate(backup_metadata).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filesystem::security::backup_manager::BackupManager;
+    use crate::workspace::test_fixtures::create_workspace;
+    use tempfile::TempDir;
+    use tokio::fs;
+
+    fn scheduler() -> BackupScheduler {
+        let manager = WorkspaceBackupManager::new(Arc::new(BackupManager::new().unwrap())).unwrap();
+        BackupScheduler::new(manager, Arc::new(WorkspaceResourceLimiter::new()))
+    }
+
+    #[tokio::test]
+    async fn test_rollback_returns_to_the_pre_operation_snapshot() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        create_workspace(
+            &workspace,
+            "Scheduled",
+            &[("docs/plan.md", b"original plan")],
+        );
+        let scheduler = scheduler();
+
+        let checkpoint = scheduler
+            .snapshot_before(&workspace, RiskyOperation::Reorganization)
+            .await
+            .unwrap();
+        assert!(matches!(
+            checkpoint.backup_type,
+            WorkspaceBackupType::Emergency
+        ));
+
+        // The "reorganisation" moves and rewrites files
+        fs::create_dir_all(workspace.join("archive")).await.unwrap();
+        fs::rename(
+            workspace.join("docs/plan.md"),
+            workspace.join("archive/plan.md"),
+        )
+        .await
+        .unwrap();
+        fs::write(workspace.join("archive/plan.md"), "rewritten")
+            .await
+            .unwrap();
+
+        let undo = scheduler
+            .rollback(&workspace, &checkpoint.backup_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(workspace.join("docs/plan.md"))
+                .await
+                .unwrap(),
+            "original plan"
+        );
+        assert!(!workspace.join("archive/plan.md").exists());
+
+        // The rollback left its own checkpoint, so it can be undone too
+        let checkpoints = scheduler.checkpoints(&workspace).await.unwrap();
+        assert_eq!(checkpoints.len(), 2);
+        assert_eq!(checkpoints[0].backup_id, undo.backup_id);
+        assert_eq!(checkpoints[0].operation, Some(RiskyOperation::Restore));
+        assert_eq!(
+            checkpoints[1].operation,
+            Some(RiskyOperation::Reorganization)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_schedules_follow_the_configuration() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        create_workspace(
+            &workspace,
+            "Scheduled",
+            &[("docs/plan.md", b"original plan")],
+        );
+        let scheduler = scheduler();
+
+        assert!(scheduler.schedule(&workspace).await);
+        assert!(scheduler.is_scheduled(&workspace));
+        assert!(scheduler
+            .run_scheduled_backup(&workspace)
+            .await
+            .unwrap()
+            .is_some());
+
+        scheduler
+            .update_config(WorkspaceBackupConfig {
+                auto_backup_enabled: false,
+                ..Default::default()
+            })
+            .await;
+        assert!(!scheduler.is_scheduled(&workspace));
+        assert!(!scheduler.schedule(&workspace).await);
+    }
+}
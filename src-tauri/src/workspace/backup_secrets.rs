@@ -0,0 +1,123 @@
+// src-tauri/src/workspace/backup_secrets.rs
+//! Secrets backup replication needs, such as the replica passphrase and S3
+//! secret access keys
+//!
+//! The backup configuration only names a secret by reference. Values are
+//! encrypted with the installation's configuration key, as API keys are, and
+//! kept in a file under the configuration directory.
+
+use crate::app_config::encryption::ConfigEncryption;
+use crate::filesystem::atomic_write::write_atomically;
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::sync::Mutex as AsyncMutex;
+
+const SECRETS_FILE: &str = "backup-secrets.json";
+
+/// Serializes read-modify-write cycles on secret files
+static SECRETS_LOCK: Lazy<AsyncMutex<()>> = Lazy::new(|| AsyncMutex::new(()));
+
+/// Encrypted store of backup secrets, addressed by reference
+#[derive(Debug, Clone)]
+pub struct BackupSecrets {
+    path: Option<PathBuf>,
+}
+
+impl Default for BackupSecrets {
+    /// The store under this installation's configuration directory
+    fn default() -> Self {
+        Self {
+            path: dirs::config_dir().map(|dir| dir.join("fiovana").join(SECRETS_FILE)),
+        }
+    }
+}
+
+impl BackupSecrets {
+    /// A store kept in `dir`
+    #[cfg(test)]
+    pub fn in_dir(dir: &Path) -> Self {
+        Self {
+            path: Some(dir.join(SECRETS_FILE)),
+        }
+    }
+
+    /// Encrypt and keep a secret, returning the reference to put in the
+    /// backup configuration
+    pub async fn store(&self, value: &str) -> Result<String> {
+        let path = self.path()?;
+        let key = ConfigEncryption::get_encryption_key()?;
+        let reference = uuid::Uuid::new_v4().to_string();
+
+        let _guard = SECRETS_LOCK.lock().await;
+        let mut secrets = load(path).await?;
+        secrets.insert(
+            reference.clone(),
+            ConfigEncryption::encrypt_value(value, &key),
+        );
+        let data = serde_json::to_vec_pretty(&secrets)?;
+        let target = path.to_path_buf();
+        tokio::task::spawn_blocking(move || write_atomically(&target, &data)).await??;
+        Ok(reference)
+    }
+
+    /// Decrypt the secret behind a reference
+    pub async fn resolve(&self, reference: &str) -> Result<String> {
+        let path = self.path()?;
+        let encrypted = {
+            let _guard = SECRETS_LOCK.lock().await;
+            load(path).await?.remove(reference)
+        }
+        .ok_or_else(|| anyhow!("Backup secret {} is not stored on this machine", reference))?;
+        let key = ConfigEncryption::get_encryption_key()?;
+        ConfigEncryption::decrypt_value(&encrypted, &key)
+            .with_context(|| format!("Backup secret {} cannot be decrypted", reference))
+    }
+
+    /// Directory the store lives in, for other per-installation backup state
+    pub fn dir(&self) -> Option<&Path> {
+        self.path.as_deref().and_then(Path::parent)
+    }
+
+    fn path(&self) -> Result<&Path> {
+        self.path
+            .as_deref()
+            .ok_or_else(|| anyhow!("No configuration directory for backup secrets"))
+    }
+}
+
+async fn load(path: &Path) -> Result<BTreeMap<String, String>> {
+    match fs::read(path).await {
+        Ok(content) => serde_json::from_slice(&content)
+            .with_context(|| format!("Backup secrets at {} are damaged", path.display())),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_secrets_are_stored_encrypted_and_resolved_by_reference() {
+        let dir = TempDir::new().unwrap();
+        let secrets = BackupSecrets::in_dir(dir.path());
+
+        let passphrase = secrets.store("correct horse").await.unwrap();
+        let access_key = secrets.store("minio-secret").await.unwrap();
+        assert_ne!(passphrase, access_key);
+
+        let on_disk = std::fs::read_to_string(dir.path().join(SECRETS_FILE)).unwrap();
+        assert!(!on_disk.contains("correct horse"));
+        assert!(!on_disk.contains("minio-secret"));
+
+        assert_eq!(secrets.resolve(&passphrase).await.unwrap(), "correct horse");
+        assert_eq!(secrets.resolve(&access_key).await.unwrap(), "minio-secret");
+        assert!(secrets.resolve("missing").await.is_err());
+    }
+}
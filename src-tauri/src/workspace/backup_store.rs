@@ -0,0 +1,544 @@
+// src-tauri/src/workspace/backup_store.rs
+//! Content-addressed storage for workspace backup snapshots
+//!
+//! Every file is stored once as a blob named by its SHA-256, optionally zstd
+//! compressed, under `blobs/`. A snapshot is a manifest in `snapshots/` listing
+//! the path, hash and size of each file it contains, so an incremental snapshot
+//! only adds blobs for the files that changed since its parent.
+
+use crate::document::content_hasher::ContentHash;
+use crate::filesystem::atomic_write::write_atomically;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+pub const BLOBS_DIR: &str = "blobs";
+pub const SNAPSHOTS_DIR: &str = "snapshots";
+
+/// Extension of compressed blobs; blobs without it are stored as-is
+const COMPRESSED_EXTENSION: &str = "zst";
+
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// A file captured by a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    /// Path relative to the workspace root, `/`-separated
+    pub path: String,
+    /// SHA-256 of the file content, naming its blob
+    pub hash: String,
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+}
+
+/// Everything needed to rebuild a workspace as it was when the snapshot was taken
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub backup_id: String,
+    pub created_at: DateTime<Utc>,
+    /// Snapshot whose hashes were reused for unchanged files
+    pub parent_id: Option<String>,
+    pub files: Vec<SnapshotEntry>,
+}
+
+impl SnapshotManifest {
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|entry| entry.size).sum()
+    }
+}
+
+/// What taking a snapshot added to the store
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotStats {
+    pub new_blobs: usize,
+    /// Bytes written for new blobs, after compression
+    pub stored_bytes: u64,
+    /// Files whose hash was taken from the parent without reading them
+    pub reused_entries: usize,
+}
+
+/// Blob and manifest storage rooted at a backup location
+#[derive(Debug, Clone)]
+pub struct BackupStore {
+    root: PathBuf,
+    compress: bool,
+}
+
+impl BackupStore {
+    pub fn new(root: impl Into<PathBuf>, compress: bool) -> Self {
+        Self {
+            root: root.into(),
+            compress,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn manifest_path(&self, backup_id: &str) -> PathBuf {
+        self.root
+            .join(SNAPSHOTS_DIR)
+            .join(format!("{}.json", backup_id))
+    }
+
+    /// Capture `paths` (relative to `source`) into a new snapshot. Files whose
+    /// size and modification time match `parent` reuse its hash unread.
+    pub fn snapshot(
+        &self,
+        backup_id: &str,
+        source: &Path,
+        paths: &[PathBuf],
+        parent: Option<&SnapshotManifest>,
+    ) -> Result<(SnapshotManifest, SnapshotStats)> {
+        let previous: HashMap<&str, &SnapshotEntry> = parent
+            .map(|parent| {
+                parent
+                    .files
+                    .iter()
+                    .map(|entry| (entry.path.as_str(), entry))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut stats = SnapshotStats::default();
+        let mut files = Vec::with_capacity(paths.len());
+        for relative in paths {
+            let path = source.join(relative);
+            let metadata = fs::metadata(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+            let key = manifest_path_of(relative)?;
+
+            let unchanged = previous.get(key.as_str()).filter(|entry| {
+                entry.size == metadata.len()
+                    && entry.modified.is_some()
+                    && entry.modified == modified
+                    && self.blob_path(&entry.hash).is_some()
+            });
+            let hash = match unchanged {
+                Some(entry) => {
+                    stats.reused_entries += 1;
+                    entry.hash.clone()
+                }
+                None => {
+                    let hash = ContentHash::from_file(&path)?.hash;
+                    if self.blob_path(&hash).is_none() {
+                        stats.stored_bytes += self.store_blob(&path, &hash)?;
+                        stats.new_blobs += 1;
+                    }
+                    hash
+                }
+            };
+
+            files.push(SnapshotEntry {
+                path: key,
+                hash,
+                size: metadata.len(),
+                modified,
+            });
+        }
+
+        let manifest = SnapshotManifest {
+            backup_id: backup_id.to_string(),
+            created_at: Utc::now(),
+            parent_id: parent.map(|parent| parent.backup_id.clone()),
+            files,
+        };
+        Ok((manifest, stats))
+    }
+
+    /// Write a manifest and return its path and SHA-256
+    pub fn write_manifest(&self, manifest: &SnapshotManifest) -> Result<(PathBuf, String)> {
+        let path = self.manifest_path(&manifest.backup_id);
+        let bytes = serde_json::to_vec_pretty(manifest)?;
+        write_atomically(&path, &bytes)?;
+        Ok((path, ContentHash::from_bytes(&bytes, None).hash))
+    }
+
+    pub fn load_manifest(&self, path: &Path) -> Result<SnapshotManifest> {
+        let bytes = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_slice(&bytes)
+            .with_context(|| format!("Invalid snapshot manifest {}", path.display()))
+    }
+
+    /// Files of the snapshot whose blob is missing or no longer matches its hash
+    pub fn verify(&self, manifest: &SnapshotManifest) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut checked = HashSet::new();
+        for entry in &manifest.files {
+            if !checked.insert(entry.hash.as_str()) {
+                continue;
+            }
+            let outcome = self
+                .open_blob(&entry.hash)
+                .and_then(|reader| hash_reader(reader, &mut io::sink()));
+            match outcome {
+                Ok(hash) if hash == entry.hash => {}
+                Ok(_) => problems.push(format!("{}: content does not match its hash", entry.path)),
+                Err(e) => problems.push(format!("{}: {}", entry.path, e)),
+            }
+        }
+        problems
+    }
+
+    /// Write every file of the snapshot below `target`, checking each against
+    /// its hash before it replaces an existing file
+    pub fn restore(&self, manifest: &SnapshotManifest, target: &Path) -> Result<usize> {
+        for entry in &manifest.files {
+            let destination = target.join(checked_relative_path(&entry.path)?);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+
+            let blob = self.open_blob(&entry.hash)?;
+            let temp = temp_path(&destination);
+            let mut file = File::create(&temp)
+                .with_context(|| format!("Failed to create {}", temp.display()))?;
+            let hash = hash_reader(blob, &mut file);
+            drop(file);
+            match hash {
+                Ok(hash) if hash == entry.hash => fs::rename(&temp, &destination)
+                    .with_context(|| format!("Failed to restore {}", destination.display()))?,
+                outcome => {
+                    let _ = fs::remove_file(&temp);
+                    outcome?;
+                    return Err(anyhow!(
+                        "Backup copy of {} is corrupted: content does not match its hash",
+                        entry.path
+                    ));
+                }
+            }
+        }
+        Ok(manifest.files.len())
+    }
+
+    /// Delete files below `target` that `manifest` does not list, skipping
+    /// `exclude`, so a restore in place also undoes files created since
+    pub fn remove_untracked(
+        &self,
+        manifest: &SnapshotManifest,
+        target: &Path,
+        exclude: &[PathBuf],
+    ) -> Result<usize> {
+        let tracked: HashSet<&str> = manifest
+            .files
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect();
+
+        let mut removed = 0;
+        for relative in collect_files(target, exclude)? {
+            if !tracked.contains(manifest_path_of(&relative)?.as_str()) {
+                let path = target.join(&relative);
+                fs::remove_file(&path)
+                    .with_context(|| format!("Failed to remove {}", path.display()))?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Delete blobs that no remaining manifest references. Returns the number
+    /// of blobs removed and the bytes freed.
+    pub fn collect_garbage(&self) -> Result<(usize, u64)> {
+        let mut referenced = HashSet::new();
+        for path in list_dir(&self.root.join(SNAPSHOTS_DIR))? {
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let manifest = self.load_manifest(&path)?;
+            referenced.extend(manifest.files.into_iter().map(|entry| entry.hash));
+        }
+
+        let mut removed = 0;
+        let mut freed = 0;
+        for shard in list_dir(&self.root.join(BLOBS_DIR))? {
+            for blob in list_dir(&shard)? {
+                let name = blob
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or_default();
+                let hash = name
+                    .strip_suffix(&format!(".{}", COMPRESSED_EXTENSION))
+                    .unwrap_or(name);
+                if !referenced.contains(hash) {
+                    freed += fs::metadata(&blob).map(|m| m.len()).unwrap_or(0);
+                    fs::remove_file(&blob)
+                        .with_context(|| format!("Failed to remove {}", blob.display()))?;
+                    removed += 1;
+                }
+            }
+        }
+        Ok((removed, freed))
+    }
+
+    fn blob_base(&self, hash: &str) -> PathBuf {
+        self.root
+            .join(BLOBS_DIR)
+            .join(&hash[..2.min(hash.len())])
+            .join(hash)
+    }
+
+    /// Existing blob file for `hash`, compressed or not
+    fn blob_path(&self, hash: &str) -> Option<PathBuf> {
+        let base = self.blob_base(hash);
+        [base.with_extension(COMPRESSED_EXTENSION), base]
+            .into_iter()
+            .find(|path| path.is_file())
+    }
+
+    fn open_blob(&self, hash: &str) -> Result<Box<dyn Read>> {
+        let path = self
+            .blob_path(hash)
+            .ok_or_else(|| anyhow!("Blob {} is missing from the backup store", hash))?;
+        let file =
+            File::open(&path).with_context(|| format!("Failed to open {}", path.display()))?;
+        if path.extension().and_then(|ext| ext.to_str()) == Some(COMPRESSED_EXTENSION) {
+            Ok(Box::new(zstd::stream::read::Decoder::new(file)?))
+        } else {
+            Ok(Box::new(file))
+        }
+    }
+
+    /// Decompressed content of the blob for `hash`
+    pub(crate) fn read_blob(&self, hash: &str) -> Result<Vec<u8>> {
+        let mut content = Vec::new();
+        self.open_blob(hash)?.read_to_end(&mut content)?;
+        Ok(content)
+    }
+
+    /// Store `content` as the blob for `hash` unless the store already has it.
+    /// Content that does not match the hash is rejected.
+    pub(crate) fn insert_blob(&self, hash: &str, content: &[u8]) -> Result<()> {
+        if self.blob_path(hash).is_some() {
+            return Ok(());
+        }
+        self.store_verified(content, hash, || {
+            format!("Content for blob {} does not match its hash", hash)
+        })
+        .map(|_| ())
+    }
+
+    /// Copy `path` into the blob for `hash`, returning the bytes written. The
+    /// content is hashed again while copying so a file that changes in the
+    /// meantime is not stored under the wrong hash.
+    fn store_blob(&self, path: &Path, hash: &str) -> Result<u64> {
+        let source =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        self.store_verified(source, hash, || {
+            format!("{} changed while it was being backed up", path.display())
+        })
+    }
+
+    fn store_verified(
+        &self,
+        source: impl Read,
+        hash: &str,
+        mismatch: impl FnOnce() -> String,
+    ) -> Result<u64> {
+        let mut destination = self.blob_base(hash);
+        if self.compress {
+            destination.set_extension(COMPRESSED_EXTENSION);
+        }
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        let temp = temp_path(&destination);
+        match self.write_blob(source, &temp, hash, mismatch) {
+            Ok(written) => {
+                fs::rename(&temp, &destination)
+                    .with_context(|| format!("Failed to store {}", destination.display()))?;
+                Ok(written)
+            }
+            Err(e) => {
+                let _ = fs::remove_file(&temp);
+                Err(e)
+            }
+        }
+    }
+
+    fn write_blob(
+        &self,
+        source: impl Read,
+        temp: &Path,
+        hash: &str,
+        mismatch: impl FnOnce() -> String,
+    ) -> Result<u64> {
+        let mut output =
+            File::create(temp).with_context(|| format!("Failed to create {}", temp.display()))?;
+        let copied = if self.compress {
+            let mut encoder = zstd::stream::write::Encoder::new(&mut output, COMPRESSION_LEVEL)?;
+            let copied = hash_reader(source, &mut encoder)?;
+            encoder.finish()?;
+            copied
+        } else {
+            hash_reader(source, &mut output)?
+        };
+        if copied != hash {
+            return Err(anyhow!(mismatch()));
+        }
+        output.sync_all()?;
+        Ok(output.metadata()?.len())
+    }
+}
+
+/// Every file below `root` as paths relative to it, skipping `exclude` and symlinks
+pub fn collect_files(root: &Path, exclude: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for path in list_dir(&dir)? {
+            if exclude.iter().any(|excluded| path.starts_with(excluded)) {
+                continue;
+            }
+            let file_type = fs::symlink_metadata(&path)?.file_type();
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file() {
+                files.push(path.strip_prefix(root)?.to_path_buf());
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn list_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    fs::read_dir(dir)
+        .with_context(|| format!("Failed to list {}", dir.display()))?
+        .map(|entry| Ok(entry?.path()))
+        .collect()
+}
+
+/// Copy `reader` into `writer` and return the SHA-256 of what was copied
+pub(crate) fn hash_reader(mut reader: impl Read, writer: &mut impl io::Write) -> Result<String> {
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        writer.write_all(&buffer[..read])?;
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+pub(crate) fn manifest_path_of(relative: &Path) -> Result<String> {
+    let parts = relative
+        .components()
+        .map(|component| match component {
+            Component::Normal(part) => part
+                .to_str()
+                .ok_or_else(|| anyhow!("Path is not valid UTF-8: {}", relative.display())),
+            _ => Err(anyhow!(
+                "Unexpected path in workspace: {}",
+                relative.display()
+            )),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(parts.join("/"))
+}
+
+/// Reject manifest paths that would escape the restore target
+pub(crate) fn checked_relative_path(path: &str) -> Result<PathBuf> {
+    let relative = PathBuf::from(path);
+    if path.is_empty()
+        || !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(anyhow!("Unsafe path in manifest: {}", path));
+    }
+    Ok(relative)
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".tmp-{}", uuid::Uuid::new_v4()));
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_incremental_snapshots_share_blobs_and_restore_verified() {
+        let workspace = TempDir::new().unwrap();
+        let backups = TempDir::new().unwrap();
+        fs::create_dir_all(workspace.path().join("docs")).unwrap();
+        fs::write(workspace.path().join("docs/a.md"), "alpha").unwrap();
+        fs::write(workspace.path().join("docs/b.md"), "beta").unwrap();
+        fs::write(workspace.path().join("copy.md"), "alpha").unwrap();
+
+        let store = BackupStore::new(backups.path(), true);
+        let files = collect_files(workspace.path(), &[]).unwrap();
+        let (first, stats) = store
+            .snapshot("one", workspace.path(), &files, None)
+            .unwrap();
+        assert_eq!(first.files.len(), 3);
+        assert_eq!(stats.new_blobs, 2); // identical content is stored once
+        store.write_manifest(&first).unwrap();
+
+        // Only the changed file adds a blob
+        fs::write(workspace.path().join("docs/b.md"), "beta, revised").unwrap();
+        let files = collect_files(workspace.path(), &[]).unwrap();
+        let (second, stats) = store
+            .snapshot("two", workspace.path(), &files, Some(&first))
+            .unwrap();
+        assert_eq!(stats.new_blobs, 1);
+        assert_eq!(second.parent_id.as_deref(), Some("one"));
+        store.write_manifest(&second).unwrap();
+        assert!(store.verify(&second).is_empty());
+
+        let restored = TempDir::new().unwrap();
+        assert_eq!(store.restore(&first, restored.path()).unwrap(), 3);
+        assert_eq!(
+            fs::read_to_string(restored.path().join("docs/b.md")).unwrap(),
+            "beta"
+        );
+
+        // Dropping the first snapshot frees the blob only it referenced
+        fs::remove_file(store.manifest_path("one")).unwrap();
+        assert_eq!(store.collect_garbage().unwrap().0, 1);
+        assert!(store.verify(&second).is_empty());
+
+        // A corrupted blob fails verification and is never restored
+        let hash = &second
+            .files
+            .iter()
+            .find(|e| e.path == "copy.md")
+            .unwrap()
+            .hash;
+        fs::write(store.blob_path(hash).unwrap(), b"garbage").unwrap();
+        assert!(!store.verify(&second).is_empty());
+        assert!(store.restore(&second, restored.path()).is_err());
+        assert_eq!(
+            fs::read_to_string(restored.path().join("copy.md")).unwrap(),
+            "alpha"
+        );
+    }
+
+    #[test]
+    fn test_manifest_paths_cannot_escape_the_target() {
+        assert!(checked_relative_path("docs/a.md").is_ok());
+        assert!(checked_relative_path("../outside").is_err());
+        assert!(checked_relative_path("/etc/passwd").is_err());
+        assert!(checked_relative_path("").is_err());
+    }
+}
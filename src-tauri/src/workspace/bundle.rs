@@ -0,0 +1,890 @@
+// src-tauri/src/workspace/bundle.rs
+//! Single-file `.fiovana` workspace bundles
+//!
+//! A bundle is a zip archive holding:
+//! - `header.json`: format version, encryption parameters, and the signer's
+//!   Ed25519 public key with its signature over the manifest
+//! - `manifest`: workspace name, source path and every file with its SHA-256
+//! - `data/<n>`: contents of the n-th manifest file
+//!
+//! With a passphrase, the manifest and data entries are zstd-compressed and
+//! sealed with AES-256-GCM under a PBKDF2-derived key. The header stays
+//! readable so a locked bundle can still be identified.
+
+use super::backup_replication::WORKSPACE_ID_FILE;
+use super::backup_store::{checked_relative_path, collect_files, hash_reader, manifest_path_of};
+use super::{WORKSPACE_CONFIG_FILE, WORKSPACE_METADATA_FILE};
+use crate::ai::conversation_store::CONVERSATIONS_DIR;
+use crate::filesystem::atomic_write::write_atomically;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use ring::hmac;
+use ring::rand::{SecureRandom, SystemRandom};
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// File extension of workspace bundles
+pub const BUNDLE_EXTENSION: &str = "fiovana";
+
+const FORMAT_VERSION: u32 = 1;
+const HEADER_ENTRY: &str = "header.json";
+const MANIFEST_ENTRY: &str = "manifest";
+const DATA_DIR: &str = "data";
+const SIGNING_KEY_FILE: &str = "signing_key.pk8";
+
+const KDF: &str = "pbkdf2-hmac-sha256";
+const PBKDF2_ITERATIONS: u32 = 210_000;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Workspace state that never travels in a bundle
+const EXCLUDED_DIRS: &[&str] = &[".fiovana/backups", ".fiovana/cache"];
+
+const VECTOR_STORE_DIR: &str = ".fiovana/vector_store";
+pub(crate) const DOCUMENT_INDEX_DIR: &str = ".fiovana/index";
+pub(crate) const STYLE_PROFILES_DIR: &str = "intelligence/content-models";
+
+/// Parts of a workspace a bundle can carry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleComponent {
+    /// Workspace metadata and configuration, always included
+    Metadata,
+    /// Documents and everything else in the workspace
+    Files,
+    VectorStore,
+    DocumentIndex,
+    StyleProfiles,
+    ConversationHistory,
+}
+
+impl BundleComponent {
+    /// Component a workspace-relative manifest path belongs to
+    fn of(path: &str) -> Self {
+        let under = |dir: &str| {
+            path.strip_prefix(dir)
+                .is_some_and(|rest| rest.starts_with('/'))
+        };
+        if path == WORKSPACE_METADATA_FILE || path == WORKSPACE_CONFIG_FILE {
+            BundleComponent::Metadata
+        } else if under(VECTOR_STORE_DIR) {
+            BundleComponent::VectorStore
+        } else if under(DOCUMENT_INDEX_DIR) {
+            BundleComponent::DocumentIndex
+        } else if under(STYLE_PROFILES_DIR) {
+            BundleComponent::StyleProfiles
+        } else if under(CONVERSATIONS_DIR) {
+            BundleComponent::ConversationHistory
+        } else {
+            BundleComponent::Files
+        }
+    }
+}
+
+/// What to put in a bundle and how to protect it
+#[derive(Clone, Default, Deserialize)]
+pub struct BundleOptions {
+    pub include_files: bool,
+    #[serde(default)]
+    pub include_vector_store: bool,
+    #[serde(default)]
+    pub include_document_index: bool,
+    #[serde(default)]
+    pub include_style_profiles: bool,
+    #[serde(default)]
+    pub include_conversation_history: bool,
+    /// Encrypt the bundle with a key derived from this passphrase
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
+impl BundleOptions {
+    fn includes(&self, component: BundleComponent) -> bool {
+        match component {
+            BundleComponent::Metadata => true,
+            BundleComponent::Files => self.include_files,
+            BundleComponent::VectorStore => self.include_vector_store,
+            BundleComponent::DocumentIndex => self.include_document_index,
+            BundleComponent::StyleProfiles => self.include_style_profiles,
+            BundleComponent::ConversationHistory => self.include_conversation_history,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BundleHeader {
+    format_version: u32,
+    created_at: DateTime<Utc>,
+    encryption: Option<KeyDerivation>,
+    /// Hex Ed25519 public key of the signer
+    signer: String,
+    /// Hex signature over the plaintext manifest
+    signature: String,
+}
+
+/// How the key sealing a bundle or backup replica is derived from its passphrase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct KeyDerivation {
+    kdf: String,
+    iterations: u32,
+    salt: String,
+}
+
+impl KeyDerivation {
+    /// Parameters with a fresh random salt
+    pub(crate) fn generate() -> Result<Self> {
+        let mut salt = [0u8; SALT_LEN];
+        SystemRandom::new()
+            .fill(&mut salt)
+            .map_err(|_| anyhow!("Failed to generate salt"))?;
+        Ok(Self {
+            kdf: KDF.to_string(),
+            iterations: PBKDF2_ITERATIONS,
+            salt: hex::encode(salt),
+        })
+    }
+}
+
+/// Signed list of the files in a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub workspace_name: String,
+    /// Where the workspace lived when it was bundled, remapped on import
+    pub source_path: PathBuf,
+    pub created_at: DateTime<Utc>,
+    pub components: Vec<BundleComponent>,
+    pub files: Vec<BundleFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleFile {
+    /// Path relative to the workspace, `/`-separated
+    pub path: String,
+    pub component: BundleComponent,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// What a bundle holds and who signed it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleSummary {
+    pub workspace_name: String,
+    pub created_at: DateTime<Utc>,
+    pub signer_fingerprint: String,
+    pub encrypted: bool,
+    pub components: Vec<BundleComponent>,
+    pub file_count: usize,
+    pub total_size: u64,
+}
+
+/// Outcome of unpacking a bundle into a workspace directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleImport {
+    pub summary: BundleSummary,
+    pub workspace_path: PathBuf,
+    /// JSON files whose absolute paths were rewritten to the new location
+    pub remapped_files: usize,
+}
+
+/// Ed25519 key this installation signs bundles with
+pub struct BundleSigner {
+    key_pair: Ed25519KeyPair,
+}
+
+impl BundleSigner {
+    /// Default directory for the signing key
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("fiovana").join("bundles"))
+    }
+
+    /// Load the signing key from `dir`, generating one on first use
+    pub fn load_or_generate(dir: &Path) -> Result<Self> {
+        let key_path = dir.join(SIGNING_KEY_FILE);
+        if !key_path.exists() {
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+                .map_err(|_| anyhow!("Failed to generate bundle signing key"))?;
+            write_atomically(&key_path, pkcs8.as_ref())?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&key_path, fs::Permissions::from_mode(0o600))
+                    .with_context(|| format!("Failed to restrict {}", key_path.display()))?;
+            }
+        }
+
+        let pkcs8 = fs::read(&key_path)
+            .with_context(|| format!("Failed to read {}", key_path.display()))?;
+        let key_pair = Ed25519KeyPair::from_pkcs8(&pkcs8)
+            .map_err(|_| anyhow!("Invalid bundle signing key in {}", key_path.display()))?;
+        Ok(Self { key_pair })
+    }
+
+    /// Throwaway signing key that is never written to disk
+    pub fn ephemeral() -> Result<Self> {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .map_err(|_| anyhow!("Failed to generate bundle signing key"))?;
+        let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+            .map_err(|_| anyhow!("Failed to load bundle signing key"))?;
+        Ok(Self { key_pair })
+    }
+
+    /// SHA-256 of the public key, which recipients can pin as trusted
+    pub fn fingerprint(&self) -> String {
+        key_fingerprint(self.key_pair.public_key().as_ref())
+    }
+}
+
+/// Bundle the workspace into a single file at `bundle_path`
+pub fn create_bundle(
+    workspace_path: &Path,
+    bundle_path: &Path,
+    workspace_name: &str,
+    options: &BundleOptions,
+    signer: &BundleSigner,
+) -> Result<BundleSummary> {
+    let mut exclude: Vec<PathBuf> = EXCLUDED_DIRS
+        .iter()
+        .map(|dir| workspace_path.join(dir))
+        .collect();
+    exclude.push(workspace_path.join(WORKSPACE_ID_FILE));
+    exclude.push(bundle_path.to_path_buf());
+
+    let mut selected = Vec::new();
+    for relative in collect_files(workspace_path, &exclude)? {
+        let path = manifest_path_of(&relative)?;
+        let component = BundleComponent::of(&path);
+        if options.includes(component) {
+            selected.push((relative, path, component));
+        }
+    }
+
+    let (encryption, sealer) = match options.passphrase.as_deref() {
+        Some(passphrase) => {
+            let encryption = KeyDerivation::generate()?;
+            let sealer = Sealer::for_passphrase(passphrase, &encryption)?;
+            (Some(encryption), sealer)
+        }
+        None => (None, Sealer::plain()),
+    };
+
+    let temp = bundle_path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+    let written = write_bundle(
+        &temp,
+        workspace_path,
+        workspace_name,
+        selected,
+        encryption,
+        &sealer,
+        signer,
+    );
+
+    match written {
+        Ok(summary) => {
+            fs::rename(&temp, bundle_path)
+                .with_context(|| format!("Failed to write {}", bundle_path.display()))?;
+            Ok(summary)
+        }
+        Err(e) => {
+            let _ = fs::remove_file(&temp);
+            Err(e)
+        }
+    }
+}
+
+fn write_bundle(
+    temp: &Path,
+    workspace_path: &Path,
+    workspace_name: &str,
+    selected: Vec<(PathBuf, String, BundleComponent)>,
+    encryption: Option<KeyDerivation>,
+    sealer: &Sealer,
+    signer: &BundleSigner,
+) -> Result<BundleSummary> {
+    let mut writer = ZipWriter::new(
+        File::create(temp).with_context(|| format!("Failed to create {}", temp.display()))?,
+    );
+
+    // Data goes first and is hashed as it is copied, so the manifest
+    // describes exactly the bytes in the bundle
+    let mut files = Vec::with_capacity(selected.len());
+    for (index, (relative, path, component)) in selected.into_iter().enumerate() {
+        let source = workspace_path.join(&relative);
+        let entry = format!("{}/{}", DATA_DIR, index);
+        let (size, sha256) = if sealer.is_encrypted() {
+            let contents = fs::read(&source)
+                .with_context(|| format!("Failed to read {}", source.display()))?;
+            writer.start_file(entry.as_str(), stored())?;
+            writer.write_all(&sealer.seal(&entry, &contents)?)?;
+            (contents.len() as u64, sha256_hex(&contents))
+        } else {
+            let file = File::open(&source)
+                .with_context(|| format!("Failed to read {}", source.display()))?;
+            let size = file.metadata()?.len();
+            writer.start_file(
+                entry.as_str(),
+                deflated().large_file(size >= u64::from(u32::MAX)),
+            )?;
+            (size, hash_reader(file, &mut writer)?)
+        };
+        files.push(BundleFile {
+            path,
+            component,
+            size,
+            sha256,
+        });
+    }
+
+    let mut components: Vec<_> = files.iter().map(|file| file.component).collect();
+    components.sort();
+    components.dedup();
+    let manifest = BundleManifest {
+        workspace_name: workspace_name.to_string(),
+        source_path: workspace_path.to_path_buf(),
+        created_at: Utc::now(),
+        components,
+        files,
+    };
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest)?;
+    let manifest_options = if sealer.is_encrypted() {
+        stored()
+    } else {
+        deflated()
+    };
+    writer.start_file(MANIFEST_ENTRY, manifest_options)?;
+    writer.write_all(&sealer.seal(MANIFEST_ENTRY, &manifest_bytes)?)?;
+
+    let header = BundleHeader {
+        format_version: FORMAT_VERSION,
+        created_at: manifest.created_at,
+        encryption,
+        signer: hex::encode(signer.key_pair.public_key().as_ref()),
+        signature: hex::encode(signer.key_pair.sign(&manifest_bytes).as_ref()),
+    };
+    writer.start_file(HEADER_ENTRY, deflated())?;
+    writer.write_all(&serde_json::to_vec_pretty(&header)?)?;
+    writer.finish()?;
+
+    Ok(summarize(&header, &manifest))
+}
+
+/// Check a bundle's signature and describe what it holds without unpacking it
+pub fn inspect_bundle(bundle_path: &Path, passphrase: Option<&str>) -> Result<BundleSummary> {
+    let opened = OpenedBundle::open(bundle_path, passphrase)?;
+    Ok(summarize(&opened.header, &opened.manifest))
+}
+
+/// Unpack a bundle into `target`, which must not exist or be empty.
+///
+/// Every file is checked against the signed manifest before it is kept, and
+/// absolute paths of the original workspace inside JSON files are rewritten
+/// to `target`. Only bundles signed by one of `trusted_signers` are
+/// accepted, so an empty list refuses every bundle.
+pub fn extract_bundle(
+    bundle_path: &Path,
+    target: &Path,
+    passphrase: Option<&str>,
+    trusted_signers: &[String],
+) -> Result<BundleImport> {
+    let mut opened = OpenedBundle::open(bundle_path, passphrase)?;
+    let summary = summarize(&opened.header, &opened.manifest);
+    if !trusted_signers.contains(&summary.signer_fingerprint) {
+        return Err(anyhow!(
+            "Bundle is signed by {}, which is not a trusted signer",
+            summary.signer_fingerprint
+        ));
+    }
+    if target.exists() && fs::read_dir(target)?.next().is_some() {
+        return Err(anyhow!(
+            "Cannot import into {}: directory is not empty",
+            target.display()
+        ));
+    }
+
+    // Unpack next to the target and move it into place once complete
+    let mut staging_name = target.file_name().unwrap_or_default().to_os_string();
+    staging_name.push(format!(".import-{}", uuid::Uuid::new_v4()));
+    let staging = target.with_file_name(staging_name);
+    let unpacked = opened.unpack(&staging, target);
+
+    let remapped_files = match unpacked {
+        Ok(remapped) => remapped,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+    };
+    if target.exists() {
+        fs::remove_dir(target)
+            .with_context(|| format!("Failed to replace {}", target.display()))?;
+    }
+    fs::rename(&staging, target)
+        .with_context(|| format!("Failed to move import into {}", target.display()))?;
+
+    Ok(BundleImport {
+        summary,
+        workspace_path: target.to_path_buf(),
+        remapped_files,
+    })
+}
+
+struct OpenedBundle {
+    archive: ZipArchive<File>,
+    header: BundleHeader,
+    manifest: BundleManifest,
+    sealer: Sealer,
+}
+
+impl OpenedBundle {
+    fn open(bundle_path: &Path, passphrase: Option<&str>) -> Result<Self> {
+        let file = File::open(bundle_path)
+            .with_context(|| format!("Failed to open {}", bundle_path.display()))?;
+        let mut archive = ZipArchive::new(file).context("Not a workspace bundle")?;
+
+        let header: BundleHeader = serde_json::from_slice(&read_entry(&mut archive, HEADER_ENTRY)?)
+            .context("Bundle header is malformed")?;
+        if header.format_version > FORMAT_VERSION {
+            return Err(anyhow!(
+                "Bundle format {} is newer than this version supports",
+                header.format_version
+            ));
+        }
+
+        let sealer = match (&header.encryption, passphrase) {
+            (Some(encryption), Some(passphrase)) => Sealer::for_passphrase(passphrase, encryption)?,
+            (Some(_), None) => {
+                return Err(anyhow!("Bundle is encrypted; a passphrase is required"))
+            }
+            (None, _) => Sealer::plain(),
+        };
+        let manifest_bytes = sealer
+            .open(MANIFEST_ENTRY, &read_entry(&mut archive, MANIFEST_ENTRY)?)
+            .context("Wrong passphrase or damaged bundle")?;
+
+        let public_key = hex::decode(&header.signer).context("Bundle signer is malformed")?;
+        let signature = hex::decode(&header.signature).context("Bundle signature is malformed")?;
+        UnparsedPublicKey::new(&ED25519, &public_key)
+            .verify(&manifest_bytes, &signature)
+            .map_err(|_| anyhow!("Bundle manifest signature is invalid"))?;
+
+        let manifest: BundleManifest =
+            serde_json::from_slice(&manifest_bytes).context("Bundle manifest is malformed")?;
+        for file in &manifest.files {
+            checked_relative_path(&file.path)?;
+        }
+
+        Ok(Self {
+            archive,
+            header,
+            manifest,
+            sealer,
+        })
+    }
+
+    /// Write every file into `staging`, verified, then remap paths to `target`.
+    /// Returns the number of remapped files.
+    fn unpack(&mut self, staging: &Path, target: &Path) -> Result<usize> {
+        for (index, file) in self.manifest.files.iter().enumerate() {
+            let destination = staging.join(checked_relative_path(&file.path)?);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+
+            let entry = format!("{}/{}", DATA_DIR, index);
+            let sha256 = if self.sealer.is_encrypted() {
+                let contents = self
+                    .sealer
+                    .open(&entry, &read_entry(&mut self.archive, &entry)?)?;
+                fs::write(&destination, &contents)
+                    .with_context(|| format!("Failed to write {}", destination.display()))?;
+                sha256_hex(&contents)
+            } else {
+                let reader = self
+                    .archive
+                    .by_name(&entry)
+                    .with_context(|| format!("Bundle is missing {}", file.path))?;
+                let mut output = File::create(&destination)
+                    .with_context(|| format!("Failed to write {}", destination.display()))?;
+                hash_reader(reader, &mut output)?
+            };
+            if sha256 != file.sha256 {
+                return Err(anyhow!(
+                    "Bundle copy of {} does not match its signed hash",
+                    file.path
+                ));
+            }
+        }
+
+        let mut remapped = 0;
+        for file in &self.manifest.files {
+            if file.path.ends_with(".json")
+                && remap_paths(
+                    &staging.join(checked_relative_path(&file.path)?),
+                    &self.manifest.source_path,
+                    target,
+                )?
+            {
+                remapped += 1;
+            }
+        }
+        Ok(remapped)
+    }
+}
+
+/// Encrypts bundle entries and backup replica objects, or passes them
+/// through when there is no passphrase
+pub(crate) struct Sealer {
+    /// Object cipher and key for naming objects by keyed hash
+    keys: Option<(Aes256Gcm, hmac::Key)>,
+}
+
+impl Sealer {
+    pub(crate) fn plain() -> Self {
+        Self { keys: None }
+    }
+
+    pub(crate) fn for_passphrase(passphrase: &str, derivation: &KeyDerivation) -> Result<Self> {
+        if derivation.kdf != KDF {
+            return Err(anyhow!("Unsupported key derivation: {}", derivation.kdf));
+        }
+        let iterations = NonZeroU32::new(derivation.iterations)
+            .ok_or_else(|| anyhow!("Key derivation has no iterations"))?;
+        let salt = hex::decode(&derivation.salt).context("Key derivation salt is malformed")?;
+
+        // The first half is what a 32-byte derivation yields, so the cipher
+        // key does not depend on whether the naming key is needed
+        let mut key = [0u8; 64];
+        ring::pbkdf2::derive(
+            ring::pbkdf2::PBKDF2_HMAC_SHA256,
+            iterations,
+            &salt,
+            passphrase.as_bytes(),
+            &mut key,
+        );
+        let cipher = Aes256Gcm::new_from_slice(&key[..32])
+            .map_err(|_| anyhow!("Failed to initialise cipher"))?;
+        Ok(Self {
+            keys: Some((cipher, hmac::Key::new(hmac::HMAC_SHA256, &key[32..]))),
+        })
+    }
+
+    pub(crate) fn is_encrypted(&self) -> bool {
+        self.keys.is_some()
+    }
+
+    /// Name for content with SHA-256 `hash` that does not reveal the hash
+    /// when encrypted
+    pub(crate) fn object_name(&self, hash: &str) -> String {
+        match &self.keys {
+            Some((_, names)) => hex::encode(hmac::sign(names, hash.as_bytes()).as_ref()),
+            None => hash.to_string(),
+        }
+    }
+
+    /// Compress and encrypt `plain`, bound to its entry name so entries
+    /// cannot be swapped. The nonce is prepended to the ciphertext.
+    pub(crate) fn seal(&self, entry: &str, plain: &[u8]) -> Result<Vec<u8>> {
+        let Some((cipher, _)) = &self.keys else {
+            return Ok(plain.to_vec());
+        };
+        let compressed = zstd::encode_all(plain, COMPRESSION_LEVEL)?;
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| anyhow!("Failed to generate nonce"))?;
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &compressed,
+                    aad: entry.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("Failed to encrypt {}", entry))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend(ciphertext);
+        Ok(sealed)
+    }
+
+    pub(crate) fn open(&self, entry: &str, sealed: &[u8]) -> Result<Vec<u8>> {
+        let Some((cipher, _)) = &self.keys else {
+            return Ok(sealed.to_vec());
+        };
+        if sealed.len() < NONCE_LEN {
+            return Err(anyhow!("{} is truncated", entry));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let compressed = cipher
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: entry.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("Failed to decrypt {}", entry))?;
+        Ok(zstd::decode_all(compressed.as_slice())?)
+    }
+}
+
+fn summarize(header: &BundleHeader, manifest: &BundleManifest) -> BundleSummary {
+    BundleSummary {
+        workspace_name: manifest.workspace_name.clone(),
+        created_at: manifest.created_at,
+        signer_fingerprint: hex::decode(&header.signer)
+            .map(|key| key_fingerprint(&key))
+            .unwrap_or_default(),
+        encrypted: header.encryption.is_some(),
+        components: manifest.components.clone(),
+        file_count: manifest.files.len(),
+        total_size: manifest.files.iter().map(|file| file.size).sum(),
+    }
+}
+
+/// Rewrite absolute paths under `from` in a JSON file to live under `to`.
+/// Returns whether the file changed.
+fn remap_paths(file: &Path, from: &Path, to: &Path) -> Result<bool> {
+    // Non-UTF-8 content cannot hold JSON strings worth remapping
+    let Ok(text) = fs::read_to_string(file) else {
+        return Ok(false);
+    };
+    let from = json_string_body(from)?;
+    let to = json_string_body(to)?;
+    if from.is_empty() || from == to {
+        return Ok(false);
+    }
+
+    // Only whole path prefixes: `/work/ws` must not match `/work/ws2`
+    let mut remapped = String::with_capacity(text.len());
+    let mut last = 0;
+    for (index, _) in text.match_indices(&from) {
+        let next = text[index + from.len()..].chars().next();
+        if matches!(next, Some('"' | '/' | '\\')) {
+            remapped.push_str(&text[last..index]);
+            remapped.push_str(&to);
+            last = index + from.len();
+        }
+    }
+    if last == 0 {
+        return Ok(false);
+    }
+    remapped.push_str(&text[last..]);
+    write_atomically(file, remapped.as_bytes())?;
+    Ok(true)
+}
+
+/// A path as it appears between the quotes of a JSON string
+fn json_string_body(path: &Path) -> Result<String> {
+    let quoted = serde_json::to_string(&path.to_string_lossy())?;
+    Ok(quoted[1..quoted.len() - 1].to_string())
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>> {
+    let mut entry = archive
+        .by_name(name)
+        .with_context(|| format!("Bundle is missing {}", name))?;
+    let mut contents = Vec::new();
+    entry.read_to_end(&mut contents)?;
+    Ok(contents)
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn key_fingerprint(public_key: &[u8]) -> String {
+    sha256_hex(public_key)
+}
+
+fn deflated() -> SimpleFileOptions {
+    SimpleFileOptions::default().compression_method(CompressionMethod::Deflated)
+}
+
+/// Sealed entries are already compressed, and compressing ciphertext is futile
+fn stored() -> SimpleFileOptions {
+    SimpleFileOptions::default().compression_method(CompressionMethod::Stored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::test_fixtures::create_workspace;
+    use tempfile::TempDir;
+
+    /// Workspace with documents, derived data and backups, and a session
+    /// that refers to the workspace by absolute path
+    fn create_field_notes(path: &Path) {
+        let session = serde_json::json!({ "source": path.join("docs/report.md") }).to_string();
+        create_workspace(
+            path,
+            "Field Notes",
+            &[
+                ("docs/report.md", b"# Report"),
+                (".fiovana/vector_store/chunks.bin", b"vectors"),
+                (".fiovana/backups/snapshots/old.json", b"{}"),
+                (
+                    "intelligence/conversations/a.session.json",
+                    session.as_bytes(),
+                ),
+            ],
+        );
+    }
+
+    /// Rewrite one entry of a bundle, keeping the rest byte for byte
+    fn tamper(bundle: &Path, name: &str, edit: impl Fn(Vec<u8>) -> Vec<u8>) {
+        let mut archive = ZipArchive::new(File::open(bundle).unwrap()).unwrap();
+        let mut entries = Vec::new();
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index).unwrap();
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents).unwrap();
+            entries.push((entry.name().to_string(), contents));
+        }
+
+        let mut writer = ZipWriter::new(File::create(bundle).unwrap());
+        for (entry, contents) in entries {
+            writer.start_file(entry.as_str(), stored()).unwrap();
+            let contents = if entry == name {
+                edit(contents)
+            } else {
+                contents
+            };
+            writer.write_all(&contents).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_bundle_round_trip_remaps_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        create_field_notes(&workspace);
+        let signer = BundleSigner::ephemeral().unwrap();
+        let bundle = temp_dir.path().join("notes.fiovana");
+
+        let options = BundleOptions {
+            include_files: true,
+            include_conversation_history: true,
+            ..Default::default()
+        };
+        let created = create_bundle(&workspace, &bundle, "Field Notes", &options, &signer).unwrap();
+        assert_eq!(
+            created.components,
+            vec![
+                BundleComponent::Metadata,
+                BundleComponent::Files,
+                BundleComponent::ConversationHistory
+            ]
+        );
+        assert_eq!(created.file_count, 3);
+        assert!(!created.encrypted);
+
+        let target = temp_dir.path().join("imported");
+        let imported = extract_bundle(&bundle, &target, None, &[signer.fingerprint()]).unwrap();
+        assert_eq!(imported.summary.signer_fingerprint, signer.fingerprint());
+        assert_eq!(imported.remapped_files, 2);
+        assert_eq!(
+            fs::read_to_string(target.join("docs/report.md")).unwrap(),
+            "# Report"
+        );
+        assert!(!target.join(".fiovana/vector_store").exists());
+        assert!(!target.join(".fiovana/backups").exists());
+
+        let metadata: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(target.join(WORKSPACE_METADATA_FILE)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(metadata["path"], serde_json::json!(target));
+        let session =
+            fs::read_to_string(target.join("intelligence/conversations/a.session.json")).unwrap();
+        assert!(session.contains(&json_string_body(&target.join("docs")).unwrap()));
+
+        // Importing over a workspace is refused
+        assert!(extract_bundle(&bundle, &target, None, &[signer.fingerprint()]).is_err());
+    }
+
+    #[test]
+    fn test_encrypted_bundle_needs_the_passphrase() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        create_field_notes(&workspace);
+        let signer = BundleSigner::ephemeral().unwrap();
+        let bundle = temp_dir.path().join("secret.fiovana");
+
+        let options = BundleOptions {
+            include_files: true,
+            include_vector_store: true,
+            passphrase: Some("correct horse".to_string()),
+            ..Default::default()
+        };
+        create_bundle(&workspace, &bundle, "Field Notes", &options, &signer).unwrap();
+        let raw = fs::read(&bundle).unwrap();
+        assert!(!raw.windows(8).any(|window| window == b"# Report"));
+
+        assert!(inspect_bundle(&bundle, None).is_err());
+        assert!(inspect_bundle(&bundle, Some("wrong horse")).is_err());
+        let summary = inspect_bundle(&bundle, Some("correct horse")).unwrap();
+        assert!(summary.encrypted);
+        assert!(summary.components.contains(&BundleComponent::VectorStore));
+
+        let target = temp_dir.path().join("imported");
+        extract_bundle(
+            &bundle,
+            &target,
+            Some("correct horse"),
+            &[signer.fingerprint()],
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(target.join(".fiovana/vector_store/chunks.bin")).unwrap(),
+            "vectors"
+        );
+    }
+
+    #[test]
+    fn test_tampered_or_untrusted_bundles_are_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        create_field_notes(&workspace);
+        let signer = BundleSigner::ephemeral().unwrap();
+        let bundle = temp_dir.path().join("notes.fiovana");
+        let options = BundleOptions {
+            include_files: true,
+            ..Default::default()
+        };
+        create_bundle(&workspace, &bundle, "Field Notes", &options, &signer).unwrap();
+
+        let stranger = BundleSigner::ephemeral().unwrap();
+        let target = temp_dir.path().join("imported");
+        assert!(extract_bundle(&bundle, &target, None, &[stranger.fingerprint()]).is_err());
+        // Without any trusted signer nothing is accepted
+        assert!(extract_bundle(&bundle, &target, None, &[]).is_err());
+        assert!(!target.exists());
+        let trusted = [signer.fingerprint()];
+
+        // Swapping file contents breaks the signed hash
+        tamper(&bundle, "data/1", |_| b"# Forged".to_vec());
+        assert!(extract_bundle(&bundle, &target, None, &trusted).is_err());
+        assert!(!target.exists());
+
+        // Editing the manifest breaks the signature
+        tamper(&bundle, MANIFEST_ENTRY, |manifest| {
+            String::from_utf8(manifest)
+                .unwrap()
+                .replace("Field Notes", "Forged Notes")
+                .into_bytes()
+        });
+        assert!(inspect_bundle(&bundle, None).is_err());
+    }
+}
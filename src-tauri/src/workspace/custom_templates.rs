@@ -0,0 +1,615 @@
+// src-tauri/src/workspace/custom_templates.rs
+//! User-defined workspace templates stored as versioned YAML files
+//!
+//! Each template lives in `<templates dir>/<id>/v<version>.yaml` and describes a
+//! complete workspace: its directory tree, seed documents, import and AI settings,
+//! output templates and style profiles. Saving a template under an existing ID
+//! adds a new version, so earlier versions can still be used to create workspaces.
+
+use super::backup_store::{checked_relative_path, collect_files, manifest_path_of};
+use super::bundle::STYLE_PROFILES_DIR;
+use super::{ImportSettings, WorkspaceAISettings, WorkspaceInfo, WORKSPACE_DIRECTORIES};
+use crate::document::TemplateDefinition;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Newest template file format this build understands
+pub const TEMPLATE_FORMAT_VERSION: u32 = 1;
+
+/// Copy of the template a workspace was created from
+pub const APPLIED_TEMPLATE_FILE: &str = ".fiovana/template.yaml";
+
+/// Directories every workspace needs, whatever its template
+const REQUIRED_DIRECTORIES: &[&str] = &[".fiovana/cache"];
+
+/// IDs reserved for the built-in templates
+const BUILT_IN_TEMPLATES: &[&str] = &["basic", "research", "documentation", "collaboration"];
+
+/// Fiovana's per-workspace state, such as the index and the replica ID, which
+/// templates never carry
+const WORKSPACE_STATE_DIR: &str = ".fiovana";
+
+/// Seed documents and style profiles larger than this are not exported
+const MAX_TEMPLATE_FILE_SIZE: u64 = 1024 * 1024;
+
+/// A text file a template writes into new workspaces
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateFile {
+    /// Path relative to the workspace, `/`-separated
+    pub path: String,
+    pub content: String,
+}
+
+/// A workspace layout users can create workspaces from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomWorkspaceTemplate {
+    pub format_version: u32,
+    pub id: String,
+    /// Assigned when the template is saved
+    #[serde(default)]
+    pub version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub created: DateTime<Utc>,
+    /// Workspace-relative directories, replacing the standard layout
+    pub directories: Vec<String>,
+    #[serde(default)]
+    pub seed_documents: Vec<TemplateFile>,
+    pub import_settings: ImportSettings,
+    pub ai_settings: WorkspaceAISettings,
+    /// Output templates installed with the workspace
+    #[serde(default)]
+    pub output_templates: Vec<TemplateDefinition>,
+    /// Style profiles copied into the workspace's content models
+    #[serde(default)]
+    pub style_profiles: Vec<TemplateFile>,
+}
+
+/// A template with every version saved under its ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateSummary {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub latest_version: u32,
+    pub versions: Vec<u32>,
+}
+
+/// Files left out when exporting a template from a workspace, and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedTemplateFile {
+    pub path: String,
+    pub reason: String,
+}
+
+impl CustomWorkspaceTemplate {
+    /// Check the template is well-formed and only writes inside the workspace
+    pub fn validate(&self) -> Result<()> {
+        if self.format_version == 0 || self.format_version > TEMPLATE_FORMAT_VERSION {
+            bail!(
+                "Unsupported workspace template format version {}",
+                self.format_version
+            );
+        }
+        check_template_id(&self.id)?;
+        if self.name.trim().is_empty() {
+            bail!("Template name cannot be empty");
+        }
+        if self.import_settings.allowed_extensions.is_empty() {
+            bail!("At least one file extension must be allowed");
+        }
+
+        for directory in &self.directories {
+            checked_relative_path(directory)?;
+        }
+        for file in self.seed_documents.iter().chain(&self.style_profiles) {
+            checked_relative_path(&file.path)?;
+        }
+        Ok(())
+    }
+
+    /// Lay the template out in a new workspace directory
+    ///
+    /// Existing files are left alone. A copy of the template is kept in the
+    /// workspace so its layout can be checked and repaired later. If laying it
+    /// out fails, everything created so far is removed again.
+    pub fn apply(&self, workspace_path: &Path) -> Result<()> {
+        self.validate()?;
+
+        let mut created = Vec::new();
+        let result = self.lay_out(workspace_path, &mut created);
+        if result.is_err() {
+            for path in created.iter().rev() {
+                let removed = if path.is_dir() {
+                    fs::remove_dir(path)
+                } else {
+                    fs::remove_file(path)
+                };
+                if let Err(e) = removed {
+                    tracing::warn!("Failed to remove {}: {}", path.display(), e);
+                }
+            }
+        }
+        result
+    }
+
+    /// Write the template into `workspace_path`, recording each file and
+    /// directory created in `created`
+    fn lay_out(&self, workspace_path: &Path, created: &mut Vec<PathBuf>) -> Result<()> {
+        for directory in self.expected_directories() {
+            create_dirs(
+                &workspace_path.join(checked_relative_path(&directory)?),
+                created,
+            )?;
+        }
+
+        let files = self
+            .seed_documents
+            .iter()
+            .map(|seed| (Path::new(""), seed))
+            .chain(
+                self.style_profiles
+                    .iter()
+                    .map(|profile| (Path::new(STYLE_PROFILES_DIR), profile)),
+            );
+        for (base, file) in files {
+            let path = workspace_path
+                .join(base)
+                .join(checked_relative_path(&file.path)?);
+            if path.exists() {
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                create_dirs(parent, created)?;
+            }
+            fs::write(&path, &file.content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            created.push(path);
+        }
+
+        let applied = workspace_path.join(APPLIED_TEMPLATE_FILE);
+        if let Some(parent) = applied.parent() {
+            create_dirs(parent, created)?;
+        }
+        let replaced = applied.exists();
+        fs::write(&applied, serde_yaml::to_string(self)?)
+            .with_context(|| format!("Failed to write {}", applied.display()))?;
+        if !replaced {
+            created.push(applied);
+        }
+        Ok(())
+    }
+
+    /// Directories a workspace created from this template should have
+    pub fn expected_directories(&self) -> Vec<String> {
+        let mut directories: Vec<String> = REQUIRED_DIRECTORIES
+            .iter()
+            .map(|dir| dir.to_string())
+            .chain(self.directories.iter().cloned())
+            .collect();
+        directories.sort();
+        directories.dedup();
+        directories
+    }
+
+    /// Capture an existing workspace as a template
+    ///
+    /// The directory tree, settings and style profiles are taken from the
+    /// workspace; seed documents are the listed workspace files. Files that are
+    /// not UTF-8 text or are too large to carry in a template are skipped and reported.
+    pub fn export(
+        workspace: &WorkspaceInfo,
+        id: &str,
+        name: &str,
+        description: &str,
+        seed_documents: &[String],
+        output_templates: Vec<TemplateDefinition>,
+    ) -> Result<(Self, Vec<SkippedTemplateFile>)> {
+        let workspace_path = workspace.path.as_path();
+        let mut skipped = Vec::new();
+
+        let mut seeds = Vec::new();
+        for seed in seed_documents {
+            let relative = checked_relative_path(seed)?;
+            if relative.starts_with(WORKSPACE_STATE_DIR) {
+                skipped.push(SkippedTemplateFile {
+                    path: seed.clone(),
+                    reason: "Workspace state is not carried in templates".to_string(),
+                });
+                continue;
+            }
+            match read_template_file(&workspace_path.join(&relative)) {
+                Ok(content) => seeds.push(TemplateFile {
+                    path: manifest_path_of(&relative)?,
+                    content,
+                }),
+                Err(reason) => skipped.push(SkippedTemplateFile {
+                    path: seed.clone(),
+                    reason,
+                }),
+            }
+        }
+
+        let profiles_dir = workspace_path.join(STYLE_PROFILES_DIR);
+        let mut style_profiles = Vec::new();
+        for relative in collect_files(&profiles_dir, &[])? {
+            let path = manifest_path_of(&relative)?;
+            match read_template_file(&profiles_dir.join(&relative)) {
+                Ok(content) => style_profiles.push(TemplateFile { path, content }),
+                Err(reason) => skipped.push(SkippedTemplateFile {
+                    path: format!("{}/{}", STYLE_PROFILES_DIR, path),
+                    reason,
+                }),
+            }
+        }
+
+        let template = Self {
+            format_version: TEMPLATE_FORMAT_VERSION,
+            id: id.to_string(),
+            version: 0,
+            name: name.to_string(),
+            description: description.to_string(),
+            created: Utc::now(),
+            directories: collect_directories(workspace_path)?,
+            seed_documents: seeds,
+            import_settings: workspace.import_settings.clone(),
+            ai_settings: workspace.ai_settings.clone(),
+            output_templates,
+            style_profiles,
+        };
+        template.validate()?;
+        Ok((template, skipped))
+    }
+}
+
+/// Versioned workspace templates in a directory
+#[derive(Debug, Clone)]
+pub struct WorkspaceTemplateStore {
+    root: PathBuf,
+}
+
+impl WorkspaceTemplateStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Default directory for user-defined workspace templates
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("fiovana").join("workspace-templates"))
+    }
+
+    /// Save a template as the next version under its ID
+    pub fn save(&self, mut template: CustomWorkspaceTemplate) -> Result<CustomWorkspaceTemplate> {
+        template.validate()?;
+        template.version = self.versions(&template.id)?.last().map_or(1, |v| v + 1);
+        template.created = Utc::now();
+
+        let path = self.version_path(&template.id, template.version);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(&path, serde_yaml::to_string(&template)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(template)
+    }
+
+    /// Load a version of a template, the latest when none is given
+    pub fn load(&self, id: &str, version: Option<u32>) -> Result<CustomWorkspaceTemplate> {
+        // The ID names a directory in the store, so it is checked before use
+        check_template_id(id)?;
+        let version = match version {
+            Some(version) => version,
+            None => *self
+                .versions(id)?
+                .last()
+                .ok_or_else(|| anyhow!("Unknown workspace template: {}", id))?,
+        };
+        let path = self.version_path(id, version);
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Workspace template {} has no version {}", id, version))?;
+        let template: CustomWorkspaceTemplate = serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        template.validate()?;
+        if template.id != id {
+            bail!("{} holds template '{}'", path.display(), template.id);
+        }
+        Ok(template)
+    }
+
+    /// Every template in the store, by name
+    pub fn list(&self) -> Result<Vec<TemplateSummary>> {
+        let mut summaries = Vec::new();
+        if !self.root.is_dir() {
+            return Ok(summaries);
+        }
+        for entry in fs::read_dir(&self.root)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let id = entry.file_name().to_string_lossy().to_string();
+            match self.load(&id, None) {
+                Ok(latest) => summaries.push(TemplateSummary {
+                    versions: self.versions(&id)?,
+                    latest_version: latest.version,
+                    id,
+                    name: latest.name,
+                    description: latest.description,
+                }),
+                Err(e) => tracing::warn!("Skipping workspace template {}: {}", id, e),
+            }
+        }
+        summaries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(summaries)
+    }
+
+    fn versions(&self, id: &str) -> Result<Vec<u32>> {
+        let dir = self.root.join(id);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut versions: Vec<u32> = fs::read_dir(&dir)?
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name();
+                name.to_str()?
+                    .strip_prefix('v')?
+                    .strip_suffix(".yaml")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        versions.sort_unstable();
+        Ok(versions)
+    }
+
+    fn version_path(&self, id: &str, version: u32) -> PathBuf {
+        self.root.join(id).join(format!("v{}.yaml", version))
+    }
+}
+
+/// Template ID derived from a name, e.g. `Product Line A` becomes `product-line-a`
+pub fn template_id_for(name: &str) -> String {
+    let id = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    match id.as_str() {
+        "" => "workspace".to_string(),
+        built_in if BUILT_IN_TEMPLATES.contains(&built_in) => format!("{}-custom", built_in),
+        _ => id,
+    }
+}
+
+/// Directories a workspace is expected to have, from its template if it has one
+pub fn expected_directories(workspace_path: &Path) -> Vec<String> {
+    match applied_template(workspace_path) {
+        Some(template) => template.expected_directories(),
+        None => WORKSPACE_DIRECTORIES
+            .iter()
+            .map(|dir| dir.to_string())
+            .collect(),
+    }
+}
+
+/// The template a workspace was created from, if any
+pub fn applied_template(workspace_path: &Path) -> Option<CustomWorkspaceTemplate> {
+    let content = fs::read_to_string(workspace_path.join(APPLIED_TEMPLATE_FILE)).ok()?;
+    serde_yaml::from_str(&content).ok()
+}
+
+fn check_template_id(id: &str) -> Result<()> {
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        bail!(
+            "Template ID '{}' may only contain lowercase letters, digits, '-' and '_'",
+            id
+        );
+    }
+    if BUILT_IN_TEMPLATES.contains(&id) {
+        bail!("'{}' is the ID of a built-in template", id);
+    }
+    Ok(())
+}
+
+/// `create_dir_all` that records each directory it creates, outermost first
+fn create_dirs(path: &Path, created: &mut Vec<PathBuf>) -> Result<()> {
+    let missing: Vec<&Path> = path.ancestors().take_while(|dir| !dir.exists()).collect();
+    for dir in missing.into_iter().rev() {
+        fs::create_dir(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        created.push(dir.to_path_buf());
+    }
+    Ok(())
+}
+
+fn read_template_file(path: &Path) -> Result<String, String> {
+    let size = fs::metadata(path)
+        .map_err(|e| format!("Cannot read file: {}", e))?
+        .len();
+    if size > MAX_TEMPLATE_FILE_SIZE {
+        return Err(format!("Larger than {} KB", MAX_TEMPLATE_FILE_SIZE / 1024));
+    }
+    let bytes = fs::read(path).map_err(|e| format!("Cannot read file: {}", e))?;
+    String::from_utf8(bytes).map_err(|_| "Not a text file".to_string())
+}
+
+/// Every directory below the workspace root, leaving out Fiovana's own state
+fn collect_directories(workspace_path: &Path) -> Result<Vec<String>> {
+    let mut directories = Vec::new();
+    let mut pending = vec![workspace_path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("Failed to list {}", dir.display()))?
+        {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() || entry.file_name().to_string_lossy().starts_with('.')
+            {
+                continue;
+            }
+            let path = entry.path();
+            directories.push(manifest_path_of(path.strip_prefix(workspace_path)?)?);
+            pending.push(path);
+        }
+    }
+    directories.sort();
+    Ok(directories)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_export_save_and_apply_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("product-line");
+        for dir in ["specs/hardware", "specs/firmware", "outputs/release-notes"] {
+            fs::create_dir_all(source.join(dir)).unwrap();
+        }
+        fs::create_dir_all(source.join(".fiovana/index")).unwrap();
+        fs::create_dir_all(source.join(STYLE_PROFILES_DIR)).unwrap();
+        fs::write(source.join("README.md"), "# Product line\n").unwrap();
+        fs::write(
+            source.join("logo.png"),
+            [0x89, b'P', b'N', b'G', 0xff, 0xfe],
+        )
+        .unwrap();
+        fs::write(
+            source.join(STYLE_PROFILES_DIR).join("house-style.json"),
+            r#"{"tone":"formal"}"#,
+        )
+        .unwrap();
+
+        let workspace = WorkspaceInfo {
+            path: source.clone(),
+            name: "Product line".to_string(),
+            version: "1.1.2".to_string(),
+            created: Utc::now(),
+            last_modified: Utc::now(),
+            last_accessed: Utc::now(),
+            import_settings: ImportSettings::default(),
+            ai_settings: WorkspaceAISettings {
+                privacy_mode: true,
+                ..Default::default()
+            },
+            is_favorite: false,
+            access_count: 0,
+        };
+
+        let (template, skipped) = CustomWorkspaceTemplate::export(
+            &workspace,
+            &template_id_for("Product line"),
+            "Product line",
+            "Specs and release notes for one product line",
+            &["README.md".to_string(), "logo.png".to_string()],
+            Vec::new(),
+        )
+        .unwrap();
+        assert!(template.ai_settings.privacy_mode);
+        assert!(template.directories.contains(&"specs/firmware".to_string()));
+        assert!(!template
+            .directories
+            .iter()
+            .any(|dir| dir.starts_with(".fiovana")));
+        assert_eq!(template.seed_documents.len(), 1);
+        assert_eq!(template.style_profiles.len(), 1);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].path, "logo.png");
+
+        // Saving again under the same ID adds a version
+        let store = WorkspaceTemplateStore::new(temp_dir.path().join("templates"));
+        assert_eq!(store.save(template.clone()).unwrap().version, 1);
+        let mut revised = template.clone();
+        revised.directories.push("specs/compliance".to_string());
+        assert_eq!(store.save(revised).unwrap().version, 2);
+        let summaries = store.list().unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].versions, vec![1, 2]);
+
+        // Workspaces can be created from any version
+        let first = store.load("product-line", Some(1)).unwrap();
+        let target = temp_dir.path().join("new-product");
+        first.apply(&target).unwrap();
+        assert!(target.join("specs/hardware").is_dir());
+        assert!(!target.join("specs/compliance").exists());
+        assert!(!target.join("sources/imports").exists());
+        assert_eq!(
+            fs::read_to_string(target.join("README.md")).unwrap(),
+            "# Product line\n"
+        );
+        assert!(target
+            .join(STYLE_PROFILES_DIR)
+            .join("house-style.json")
+            .is_file());
+        assert_eq!(expected_directories(&target), first.expected_directories());
+        assert!(store
+            .load("product-line", None)
+            .unwrap()
+            .directories
+            .contains(&"specs/compliance".to_string()));
+
+        // Templates may not write outside the workspace or shadow built-in ones
+        let mut escaping = template.clone();
+        escaping.seed_documents[0].path = "../outside.md".to_string();
+        assert!(store.save(escaping).is_err());
+        let mut built_in = template;
+        built_in.id = "research".to_string();
+        assert!(store.save(built_in).is_err());
+        assert_eq!(template_id_for("Research"), "research-custom");
+        assert!(store.load("../product-line", None).is_err());
+    }
+
+    #[test]
+    fn test_failed_apply_removes_what_it_created() {
+        let temp_dir = TempDir::new().unwrap();
+        let template = CustomWorkspaceTemplate {
+            format_version: TEMPLATE_FORMAT_VERSION,
+            id: "notes".to_string(),
+            version: 1,
+            name: "Notes".to_string(),
+            description: String::new(),
+            created: Utc::now(),
+            directories: vec!["notes/daily".to_string()],
+            seed_documents: vec![
+                TemplateFile {
+                    path: "notes/README.md".to_string(),
+                    content: "# Notes\n".to_string(),
+                },
+                // A seed under a file cannot be written
+                TemplateFile {
+                    path: "notes/README.md/index.md".to_string(),
+                    content: String::new(),
+                },
+            ],
+            import_settings: ImportSettings::default(),
+            ai_settings: WorkspaceAISettings::default(),
+            output_templates: Vec::new(),
+            style_profiles: Vec::new(),
+        };
+
+        let target = temp_dir.path().join("new-workspace");
+        assert!(template.apply(&target).is_err());
+        assert!(!target.exists());
+
+        // A directory that was already there is kept, with only its own contents
+        let existing = temp_dir.path().join("existing");
+        fs::create_dir_all(&existing).unwrap();
+        fs::write(existing.join("keep.md"), "kept").unwrap();
+        assert!(template.apply(&existing).is_err());
+        let left: Vec<_> = fs::read_dir(&existing)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(left, vec!["keep.md".to_string()]);
+    }
+}
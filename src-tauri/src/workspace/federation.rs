@@ -0,0 +1,543 @@
+// src-tauri/src/workspace/federation.rs
+//! Federated search, relationship discovery and document links across workspaces
+//!
+//! Each workspace keeps its own document index. A federation opens the indexes of
+//! several known workspaces side by side and tags everything it returns with the
+//! workspace it came from. What a workspace shares is governed by its
+//! `federated_access` setting and by privacy mode: private workspaces only share
+//! titles and paths, never document content, and searches only match against
+//! what they share.
+
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+use super::bundle::DOCUMENT_INDEX_DIR;
+use super::{WorkspaceConfig, WorkspaceInfo};
+use crate::document::indexer::{DocumentIndexEntry, DocumentIndexer, SearchFilter, SearchResult};
+use crate::document::relationship_analyzer::{DocumentRelationship, RelationshipAnalyzer};
+
+/// Workspace custom setting holding its [`FederatedAccess`]
+pub const FEDERATED_ACCESS_SETTING: &str = "federated_access";
+
+/// Most documents compared for cross-workspace relationships, shared evenly
+/// between the member workspaces; every candidate is compared with every other
+const MAX_RELATIONSHIP_CANDIDATES: usize = 200;
+
+/// Matches links such as `[[workspace:Product A/sources/spec.md]]`
+static WORKSPACE_LINK_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\[\[workspace:([^\]/]+)/([^\]]+)\]\]").unwrap());
+
+/// What a workspace shares with searches started from other workspaces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FederatedAccess {
+    /// Left out of federated search, relationships and links
+    Hidden,
+    /// Titles and paths only, without content or snippets
+    MetadataOnly,
+    /// Search results with snippets, and content-based relationships
+    Full,
+}
+
+/// Identifies the workspace a federated result came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceTag {
+    pub path: PathBuf,
+    pub name: String,
+    pub access: FederatedAccess,
+}
+
+/// A selected workspace that was not searched, and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedWorkspace {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// A search result tagged with its workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederatedSearchResult {
+    pub workspace: WorkspaceTag,
+    pub result: SearchResult,
+}
+
+/// A relationship between documents in two different workspaces
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossWorkspaceRelationship {
+    pub workspace_a: WorkspaceTag,
+    pub workspace_b: WorkspaceTag,
+    pub path_a: PathBuf,
+    pub path_b: PathBuf,
+    pub relationship: DocumentRelationship,
+}
+
+/// Where a workspace link points
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedLink {
+    pub link: String,
+    pub workspace: WorkspaceTag,
+    pub path: PathBuf,
+    pub exists: bool,
+    pub document_id: Option<String>,
+    pub title: Option<String>,
+}
+
+struct FederatedWorkspace {
+    tag: WorkspaceTag,
+    indexer: DocumentIndexer,
+}
+
+/// The document indexes of several workspaces opened side by side
+pub struct Federation {
+    workspaces: Vec<FederatedWorkspace>,
+    skipped: Vec<SkippedWorkspace>,
+}
+
+impl Federation {
+    /// Open the selected workspaces, or every known workspace when none are selected
+    ///
+    /// Only workspaces the workspace manager knows about are opened; unknown paths,
+    /// hidden workspaces and workspaces without an index are reported as skipped.
+    pub async fn open(known: &[WorkspaceInfo], selected: &[PathBuf]) -> Self {
+        let mut federation = Self {
+            workspaces: Vec::new(),
+            skipped: Vec::new(),
+        };
+
+        let candidates: Vec<(PathBuf, Option<&WorkspaceInfo>)> = if selected.is_empty() {
+            known
+                .iter()
+                .map(|info| (info.path.clone(), Some(info)))
+                .collect()
+        } else {
+            selected
+                .iter()
+                .map(|path| {
+                    let wanted = canonical(path);
+                    let info = known.iter().find(|info| canonical(&info.path) == wanted);
+                    (path.clone(), info)
+                })
+                .collect()
+        };
+
+        for (path, info) in candidates {
+            let Some(info) = info else {
+                federation.skip(path, "Not a known workspace");
+                continue;
+            };
+            if federation
+                .workspaces
+                .iter()
+                .any(|member| canonical(&member.tag.path) == canonical(&info.path))
+            {
+                continue;
+            }
+
+            let access = federated_access(info).await;
+            if access == FederatedAccess::Hidden {
+                federation.skip(path, "Workspace does not take part in federated search");
+                continue;
+            }
+
+            let index_dir = info.path.join(DOCUMENT_INDEX_DIR);
+            if !index_dir.is_dir() {
+                federation.skip(path, "Workspace has not been indexed");
+                continue;
+            }
+            match DocumentIndexer::new(index_dir) {
+                Ok(indexer) => federation.workspaces.push(FederatedWorkspace {
+                    tag: WorkspaceTag {
+                        path: info.path.clone(),
+                        name: info.name.clone(),
+                        access,
+                    },
+                    indexer,
+                }),
+                Err(e) => federation.skip(path, &format!("Failed to open index: {}", e)),
+            }
+        }
+
+        federation
+    }
+
+    fn skip(&mut self, path: PathBuf, reason: &str) {
+        tracing::debug!("Skipping {} in federation: {}", path.display(), reason);
+        self.skipped.push(SkippedWorkspace {
+            path,
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Workspaces taking part in the federation
+    pub fn members(&self) -> Vec<WorkspaceTag> {
+        self.workspaces
+            .iter()
+            .map(|member| member.tag.clone())
+            .collect()
+    }
+
+    /// Selected workspaces that were left out
+    pub fn skipped(&self) -> &[SkippedWorkspace] {
+        &self.skipped
+    }
+
+    /// Search every member workspace and merge the results by relevance
+    ///
+    /// Workspaces that only share metadata are matched on titles, paths and
+    /// metadata rather than content.
+    pub fn search(
+        &self,
+        query: &str,
+        filter: Option<SearchFilter>,
+        max_results: usize,
+    ) -> Result<Vec<FederatedSearchResult>> {
+        let mut results = Vec::new();
+        for member in &self.workspaces {
+            let found = if member.tag.access == FederatedAccess::Full {
+                member
+                    .indexer
+                    .search(query, filter.clone())
+                    .with_context(|| format!("Search failed in {}", member.tag.name))?
+            } else {
+                let mut found = member.indexer.search_metadata(query, filter.clone());
+                found.iter_mut().for_each(redact_search_result);
+                found
+            };
+            results.extend(found.into_iter().map(|result| FederatedSearchResult {
+                workspace: member.tag.clone(),
+                result,
+            }));
+        }
+
+        results.sort_by(|a, b| {
+            b.result
+                .score
+                .partial_cmp(&a.result.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        results.truncate(max_results);
+        Ok(results)
+    }
+
+    /// Relationships between documents of different workspaces, strongest first
+    ///
+    /// Documents are only compared with documents from other workspaces, and only
+    /// workspaces that share their content take part. Each workspace contributes
+    /// its most recently modified documents, up to an equal share of
+    /// [`MAX_RELATIONSHIP_CANDIDATES`].
+    pub async fn cross_workspace_relationships(
+        &self,
+        analyzer: &RelationshipAnalyzer,
+        max_results: usize,
+    ) -> Vec<CrossWorkspaceRelationship> {
+        let members: Vec<&FederatedWorkspace> = self
+            .workspaces
+            .iter()
+            .filter(|member| member.tag.access == FederatedAccess::Full)
+            .collect();
+        let per_member = MAX_RELATIONSHIP_CANDIDATES / members.len().max(1);
+
+        let mut documents: Vec<(&WorkspaceTag, &DocumentIndexEntry)> = Vec::new();
+        for member in members {
+            let mut entries = member.indexer.get_all_documents();
+            if entries.len() > per_member {
+                tracing::debug!(
+                    "Comparing the {} most recent of {} documents in {}",
+                    per_member,
+                    entries.len(),
+                    member.tag.name
+                );
+                entries.sort_by_key(|entry| std::cmp::Reverse(entry.metadata.basic.modified));
+                entries.truncate(per_member);
+            }
+            documents.extend(entries.into_iter().map(|entry| (&member.tag, entry)));
+        }
+
+        let mut relationships = Vec::new();
+        for (i, (workspace_a, doc_a)) in documents.iter().enumerate() {
+            for (workspace_b, doc_b) in &documents[i + 1..] {
+                if workspace_a.path == workspace_b.path {
+                    continue;
+                }
+                match analyzer.analyze_document_pair(doc_a, doc_b).await {
+                    Ok(found) => relationships.extend(found.into_iter().map(|relationship| {
+                        CrossWorkspaceRelationship {
+                            workspace_a: (*workspace_a).clone(),
+                            workspace_b: (*workspace_b).clone(),
+                            path_a: doc_a.path.clone(),
+                            path_b: doc_b.path.clone(),
+                            relationship,
+                        }
+                    })),
+                    Err(e) => tracing::warn!(
+                        "Failed to compare {} with {}: {}",
+                        doc_a.path.display(),
+                        doc_b.path.display(),
+                        e
+                    ),
+                }
+            }
+        }
+
+        relationships.sort_by(|a, b| {
+            b.relationship
+                .score
+                .partial_cmp(&a.relationship.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        relationships.truncate(max_results);
+        relationships
+    }
+
+    /// Resolve a `[[workspace:<name>/<path>]]` link against the member workspaces
+    ///
+    /// The workspace is matched by name or folder name, ignoring case. The path is
+    /// relative to the workspace root and may not leave it.
+    pub fn resolve_link(&self, link: &str) -> Result<ResolvedLink> {
+        let captures = WORKSPACE_LINK_PATTERN
+            .captures(link.trim())
+            .with_context(|| format!("Not a workspace link: {}", link))?;
+        let name = captures[1].trim();
+        let relative = Path::new(captures[2].trim());
+
+        if relative
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            bail!("Link must stay inside its workspace: {}", link);
+        }
+
+        let member =
+            self.workspaces
+                .iter()
+                .find(|member| {
+                    member.tag.name.eq_ignore_ascii_case(name)
+                        || member.tag.path.file_name().is_some_and(|folder| {
+                            folder.to_string_lossy().eq_ignore_ascii_case(name)
+                        })
+                })
+                .with_context(|| format!("Workspace '{}' is not available", name))?;
+
+        let path = member.tag.path.join(relative);
+        let entry = member.indexer.get_document_by_path(&path);
+        Ok(ResolvedLink {
+            link: captures[0].to_string(),
+            workspace: member.tag.clone(),
+            exists: path.is_file(),
+            document_id: entry.map(|entry| entry.id.clone()),
+            title: entry.map(|entry| entry.title.clone()),
+            path,
+        })
+    }
+}
+
+/// Link to a document from any other workspace
+pub fn workspace_link(workspace: &WorkspaceInfo, document_path: &Path) -> Result<String> {
+    let relative = document_path
+        .strip_prefix(&workspace.path)
+        .with_context(|| {
+            format!(
+                "{} is not inside workspace {}",
+                document_path.display(),
+                workspace.name
+            )
+        })?;
+    Ok(format!(
+        "[[workspace:{}/{}]]",
+        workspace.name,
+        relative.to_string_lossy().replace('\\', "/")
+    ))
+}
+
+/// Workspace links found in a document's text
+pub fn extract_workspace_links(content: &str) -> Vec<String> {
+    WORKSPACE_LINK_PATTERN
+        .find_iter(content)
+        .map(|link| link.as_str().to_string())
+        .collect()
+}
+
+/// How much a workspace shares, from its settings and privacy mode
+async fn federated_access(info: &WorkspaceInfo) -> FederatedAccess {
+    let config = WorkspaceConfig::load_from_workspace(&info.path).await.ok();
+    let configured = config
+        .as_ref()
+        .and_then(|config| config.get_custom_setting(FEDERATED_ACCESS_SETTING))
+        .and_then(|value| serde_json::from_value(value.clone()).ok())
+        .unwrap_or(FederatedAccess::Full);
+    let private = info.ai_settings.privacy_mode
+        || config.is_some_and(|config| config.workspace.ai_settings.privacy_mode);
+
+    if private {
+        configured.min(FederatedAccess::MetadataOnly)
+    } else {
+        configured
+    }
+}
+
+/// Strip everything but a result's title, path and score
+fn redact_search_result(result: &mut SearchResult) {
+    let document = &mut result.document;
+    document.content.clear();
+    document.metadata.content.preview = None;
+    document.summary = None;
+    document.keywords.clear();
+    document.structure.sections.clear();
+    document.structure.toc = None;
+    result.matching_sections.clear();
+    result.snippets.clear();
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::relationship_analyzer::RelationshipConfig;
+    use crate::workspace::{ImportSettings, WorkspaceAISettings};
+    use chrono::Utc;
+    use std::fs;
+    use tempfile::TempDir;
+
+    async fn indexed_workspace(
+        root: &Path,
+        name: &str,
+        privacy_mode: bool,
+        documents: &[(&str, &str)],
+    ) -> WorkspaceInfo {
+        let path = root.join(name.to_lowercase().replace(' ', "-"));
+        let mut indexer = DocumentIndexer::new(path.join(DOCUMENT_INDEX_DIR)).unwrap();
+        for (relative, content) in documents {
+            let document = path.join(relative);
+            fs::create_dir_all(document.parent().unwrap()).unwrap();
+            fs::write(&document, content).unwrap();
+            indexer.index_document(&document).await.unwrap();
+        }
+
+        WorkspaceInfo {
+            path,
+            name: name.to_string(),
+            version: "1.1.2".to_string(),
+            created: Utc::now(),
+            last_modified: Utc::now(),
+            last_accessed: Utc::now(),
+            import_settings: ImportSettings::default(),
+            ai_settings: WorkspaceAISettings {
+                privacy_mode,
+                ..Default::default()
+            },
+            is_favorite: false,
+            access_count: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_federated_search_and_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let routers = indexed_workspace(
+            temp_dir.path(),
+            "Routers",
+            false,
+            &[
+                (
+                    "sources/firmware-update.md",
+                    "# Firmware update\n\nFlash the router firmware over the network. \
+                     See [[workspace:Switches/sources/firmware-rollback.md]] if the update fails.",
+                ),
+                ("sources/wiring.md", "# Wiring\n\nConnect the power cable."),
+            ],
+        )
+        .await;
+        let switches = indexed_workspace(
+            temp_dir.path(),
+            "Switches",
+            false,
+            &[(
+                "sources/firmware-rollback.md",
+                "# Firmware rollback\n\nRoll the switch firmware back over the network.",
+            )],
+        )
+        .await;
+        let secret = indexed_workspace(
+            temp_dir.path(),
+            "Secret",
+            true,
+            &[(
+                "sources/firmware-plans.md",
+                "# Firmware plans\n\nUnannounced firmware roadmap.",
+            )],
+        )
+        .await;
+        let known = vec![routers.clone(), switches.clone(), secret.clone()];
+
+        // Unknown paths are never searched
+        let outsider = temp_dir.path().join("outsider");
+        let federation = Federation::open(
+            &known,
+            &[routers.path.clone(), secret.path.clone(), outsider.clone()],
+        )
+        .await;
+        assert_eq!(federation.members().len(), 2);
+        assert_eq!(federation.skipped().len(), 1);
+        assert_eq!(federation.skipped()[0].path, outsider);
+
+        // Results are tagged by workspace, private ones without content
+        let federation = Federation::open(&known, &[]).await;
+        let results = federation.search("firmware", None, 10).unwrap();
+        assert_eq!(results.len(), 3);
+        let private = results
+            .iter()
+            .find(|found| found.workspace.name == "Secret")
+            .unwrap();
+        assert_eq!(private.workspace.access, FederatedAccess::MetadataOnly);
+        assert!(private.result.document.content.is_empty());
+        assert!(private.result.document.metadata.content.preview.is_none());
+        assert!(private.result.snippets.is_empty());
+        assert!(results
+            .iter()
+            .filter(|found| found.workspace.name != "Secret")
+            .all(|found| !found.result.document.content.is_empty()
+                && found.result.document.metadata.content.preview.is_some()));
+        assert_eq!(federation.search("firmware", None, 1).unwrap().len(), 1);
+
+        // Private workspaces are not matched on their content
+        assert!(federation.search("roadmap", None, 10).unwrap().is_empty());
+        assert_eq!(federation.search("plans", None, 10).unwrap().len(), 1);
+
+        // Links resolve across workspaces but not outside them
+        let update = fs::read_to_string(routers.path.join("sources/firmware-update.md")).unwrap();
+        let links = extract_workspace_links(&update);
+        assert_eq!(links.len(), 1);
+        let resolved = federation.resolve_link(&links[0]).unwrap();
+        assert_eq!(resolved.workspace.name, "Switches");
+        assert!(resolved.exists);
+        assert_eq!(resolved.title.as_deref(), Some("Firmware rollback"));
+        assert_eq!(workspace_link(&switches, &resolved.path).unwrap(), links[0]);
+        assert!(federation
+            .resolve_link("[[workspace:switches/../routers/sources/wiring.md]]")
+            .is_err());
+        assert!(federation
+            .resolve_link("[[workspace:Printers/manual.md]]")
+            .is_err());
+
+        // Relationships only pair documents from different, non-private workspaces
+        let analyzer = RelationshipAnalyzer::new(RelationshipConfig {
+            use_semantic_analysis: false,
+            ..Default::default()
+        });
+        let relationships = federation
+            .cross_workspace_relationships(&analyzer, 50)
+            .await;
+        assert!(!relationships.is_empty());
+        assert!(relationships.iter().all(|found| {
+            found.workspace_a.path != found.workspace_b.path
+                && found.workspace_a.access == FederatedAccess::Full
+                && found.workspace_b.access == FederatedAccess::Full
+        }));
+    }
+}
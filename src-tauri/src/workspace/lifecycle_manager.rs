@@ -6,18 +6,25 @@
 //! to maintain current and relevant documentation.
 
 use super::intelligence::WorkspaceIntelligence;
+use super::usage_log::{DocumentUsageHistory, UsageEventKind, UsageLog};
 use crate::ai::AIOrchestrator;
 use crate::document::indexer::DocumentIndexer;
 use crate::document::relationship_analyzer::RelationshipAnalyzer;
 use crate::document::{ContentClassifier, StructureAnalyzer};
 use anyhow::Result;
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+/// Length of the rolling windows compared to derive usage trends
+const TREND_WINDOW_DAYS: i64 = 30;
+
+/// Period over which usage frequency is averaged
+const FREQUENCY_WINDOW_DAYS: i64 = 90;
+
 /// Content lifecycle manager for tracking and managing document lifecycles
 pub struct LifecycleManager {
     #[allow(dead_code)]
@@ -241,8 +248,8 @@ pub struct DocumentUsageStats {
 /// Usage tracking system
 #[derive(Debug, Clone, Default)]
 pub struct UsageTracker {
-    /// Document access history
-    access_history: HashMap<PathBuf, Vec<DateTime<Utc>>>,
+    /// Persisted document access history of the analyzed workspace
+    usage_log: Option<UsageLog>,
     /// Usage statistics cache
     stats_cache: HashMap<PathBuf, DocumentUsageStats>,
     /// Last cache update
@@ -416,7 +423,8 @@ impl LifecycleManager {
         };
 
         // Update usage tracker with current documents
-        self.update_usage_tracking(&documents).await?;
+        self.update_usage_tracking(workspace_path, &documents)
+            .await?;
 
         // Analyze update needs
         if config.enable_update_recommendations {
@@ -462,14 +470,15 @@ impl LifecycleManager {
     /// Update usage tracking for documents
     async fn update_usage_tracking(
         &mut self,
+        workspace_path: &Path,
         documents: &[crate::document::indexer::DocumentIndexEntry],
     ) -> Result<()> {
         let now = Utc::now();
+        self.usage_tracker.usage_log = Some(UsageLog::open_async(workspace_path).await?);
 
-        // Update access history for tracked documents
+        self.usage_tracker.stats_cache.clear();
         for document in documents {
-            // Simulate usage tracking - in a real implementation, this would be based on actual access logs
-            let usage_stats = self.calculate_document_usage_stats(&document.path).await?;
+            let usage_stats = self.usage_tracker.stats_for(&document.path, now);
             self.usage_tracker
                 .stats_cache
                 .insert(document.path.clone(), usage_stats);
@@ -479,43 +488,13 @@ impl LifecycleManager {
         Ok(())
     }
 
-    /// Calculate usage statistics for a document
-    async fn calculate_document_usage_stats(&self, _path: &Path) -> Result<DocumentUsageStats> {
-        // In a real implementation, this would read from access logs, analytics, etc.
-        // For now, simulate realistic usage patterns
-        let now = Utc::now();
-        let access_count = fastrand::u32(0..=100);
-        let recent_access_count = fastrand::u32(0..=20);
-
-        let frequency = match access_count {
-            0 => UsageFrequency::Unused,
-            1..=5 => UsageFrequency::VeryLow,
-            6..=20 => UsageFrequency::Low,
-            21..=50 => UsageFrequency::Medium,
-            51..=80 => UsageFrequency::High,
-            _ => UsageFrequency::VeryHigh,
-        };
-
-        let trend = if recent_access_count > access_count / 3 {
-            UsageTrend::Increasing
-        } else if recent_access_count < access_count / 6 {
-            UsageTrend::Decreasing
-        } else {
-            UsageTrend::Stable
-        };
-
-        Ok(DocumentUsageStats {
-            access_count,
-            recent_access_count,
-            last_accessed: if access_count > 0 {
-                Some(now - Duration::days(fastrand::i64(1..=90)))
-            } else {
-                None
-            },
-            monthly_average: access_count as f32 / 12.0,
-            frequency,
-            trend,
-        })
+    /// Usage statistics for a document, from the cache filled during analysis
+    fn calculate_document_usage_stats(&self, path: &Path) -> DocumentUsageStats {
+        self.usage_tracker
+            .stats_cache
+            .get(path)
+            .cloned()
+            .unwrap_or_else(|| self.usage_tracker.stats_for(path, Utc::now()))
     }
 
     /// Analyze documents that need updates
@@ -577,7 +556,7 @@ impl LifecycleManager {
             }
 
             // Usage pattern analysis
-            let usage_stats = self.calculate_document_usage_stats(&document.path).await?;
+            let usage_stats = self.calculate_document_usage_stats(&document.path);
             let usage_frequency = usage_stats.frequency;
 
             // High-usage documents get higher priority for updates
@@ -635,7 +614,7 @@ impl LifecycleManager {
         let mut suggestions = Vec::new();
 
         for document in documents {
-            let usage_stats = self.calculate_document_usage_stats(&document.path).await?;
+            let usage_stats = self.calculate_document_usage_stats(&document.path);
 
             // Consider for archival if usage is very low and document is old
             if usage_stats.access_count < config.min_usage_threshold {
@@ -665,8 +644,8 @@ impl LifecycleManager {
                         document_path: document.path.clone(),
                         rationale,
                         confidence: 0.8,
-                        usage_stats,
-                        last_accessed: None, // Would be populated from real usage data
+                        usage_stats: usage_stats.clone(),
+                        last_accessed: usage_stats.last_accessed,
                         priority,
                         suggested_location: Some(PathBuf::from("archive/").join(&document.path)),
                         affected_dependencies: Vec::new(), // Would be calculated from relationship analysis
@@ -749,7 +728,7 @@ impl LifecycleManager {
         for document in documents {
             let modified_time = self.get_document_modified_time(document);
             let days_old = now.signed_duration_since(modified_time).num_days();
-            let usage_stats = self.calculate_document_usage_stats(&document.path).await?;
+            let usage_stats = self.calculate_document_usage_stats(&document.path);
 
             // Classify documents based on age and usage
             match (days_old, usage_stats.frequency) {
@@ -795,11 +774,9 @@ impl LifecycleManager {
         };
 
         let mut total_usage = 0u32;
-        let mut increasing_trend_count = 0;
-        let mut decreasing_trend_count = 0;
 
         for document in documents {
-            let usage_stats = self.calculate_document_usage_stats(&document.path).await?;
+            let usage_stats = self.calculate_document_usage_stats(&document.path);
             total_usage += usage_stats.access_count;
 
             match usage_stats.frequency {
@@ -808,12 +785,6 @@ impl LifecycleManager {
                 UsageFrequency::Low | UsageFrequency::VeryLow => patterns.low_usage_count += 1,
                 UsageFrequency::Unused => patterns.unused_count += 1,
             }
-
-            match usage_stats.trend {
-                UsageTrend::Increasing => increasing_trend_count += 1,
-                UsageTrend::Decreasing => decreasing_trend_count += 1,
-                _ => {}
-            }
         }
 
         patterns.average_usage = if documents.is_empty() {
@@ -822,13 +793,7 @@ impl LifecycleManager {
             total_usage as f32 / documents.len() as f32
         };
 
-        patterns.usage_trend = if increasing_trend_count > decreasing_trend_count * 2 {
-            UsageTrend::Increasing
-        } else if decreasing_trend_count > increasing_trend_count * 2 {
-            UsageTrend::Decreasing
-        } else {
-            UsageTrend::Stable
-        };
+        patterns.usage_trend = self.usage_tracker.workspace_trend(Utc::now());
 
         Ok(patterns)
     }
@@ -1122,19 +1087,24 @@ impl LifecycleManager {
     }
 
     /// Track document access for usage statistics
-    pub async fn track_document_access(&mut self, document_path: &str) -> Result<()> {
+    pub async fn track_document_access(
+        &mut self,
+        workspace_path: &Path,
+        document_path: &str,
+        kind: UsageEventKind,
+    ) -> Result<()> {
         let path = PathBuf::from(document_path);
         let now = Utc::now();
 
-        // Add access record to usage tracker
-        self.usage_tracker
-            .access_history
-            .entry(path.clone())
-            .or_default()
-            .push(now);
+        if self.usage_tracker.usage_log.is_none() {
+            self.usage_tracker.usage_log = Some(UsageLog::open_async(workspace_path).await?);
+        }
+        if let Some(usage_log) = self.usage_tracker.usage_log.as_mut() {
+            usage_log.record_async(&path, kind, now).await?;
+        }
 
         // Update statistics cache
-        let usage_stats = self.calculate_document_usage_stats(&path).await?;
+        let usage_stats = self.usage_tracker.stats_for(&path, now);
         self.usage_tracker.stats_cache.insert(path, usage_stats);
         self.usage_tracker.last_cache_update = Some(now);
 
@@ -1157,6 +1127,139 @@ impl LifecycleManager {
     }
 }
 
+impl UsageTracker {
+    /// Usage statistics of a document from its persisted history
+    fn stats_for(&self, path: &Path, now: DateTime<Utc>) -> DocumentUsageStats {
+        let history = self
+            .usage_log
+            .as_ref()
+            .and_then(|usage_log| usage_log.history(path));
+        let Some(history) = history else {
+            return DocumentUsageStats {
+                access_count: 0,
+                recent_access_count: 0,
+                last_accessed: None,
+                monthly_average: 0.0,
+                frequency: UsageFrequency::Unused,
+                trend: UsageTrend::Inactive,
+            };
+        };
+
+        let windows = UsageWindows::of([history], now);
+        let today = now.date_naive();
+        let access_count = history.total();
+
+        // Average over the months tracked so far, up to a year
+        let months_tracked = history
+            .first_day()
+            .map(|first| ((today - first).num_days() as f32 / 30.0).clamp(1.0, 12.0))
+            .unwrap_or(1.0);
+        let last_year = history.count_between(today - Duration::days(365), tomorrow(today));
+
+        DocumentUsageStats {
+            access_count,
+            recent_access_count: windows.recent,
+            last_accessed: history.last_accessed,
+            monthly_average: last_year as f32 / months_tracked,
+            frequency: classify_frequency(access_count, windows.frequency_window),
+            trend: windows.trend(),
+        }
+    }
+
+    /// Usage trend across every document in the workspace
+    fn workspace_trend(&self, now: DateTime<Utc>) -> UsageTrend {
+        match &self.usage_log {
+            Some(usage_log) => UsageWindows::of(usage_log.documents(), now).trend(),
+            None => UsageTrend::Inactive,
+        }
+    }
+}
+
+/// Usage counts over the rolling windows ending today
+struct UsageWindows {
+    /// Uses in the last `TREND_WINDOW_DAYS`
+    recent: u32,
+    /// Uses in the window before that
+    previous: u32,
+    /// Uses in the last `FREQUENCY_WINDOW_DAYS`
+    frequency_window: u32,
+    /// Distinct days with any use across both trend windows
+    active_days: usize,
+}
+
+impl UsageWindows {
+    fn of<'a>(
+        histories: impl IntoIterator<Item = &'a DocumentUsageHistory>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let end = tomorrow(now.date_naive());
+        let recent_start = end - Duration::days(TREND_WINDOW_DAYS);
+        let previous_start = recent_start - Duration::days(TREND_WINDOW_DAYS);
+        let frequency_start = end - Duration::days(FREQUENCY_WINDOW_DAYS);
+
+        let mut windows = Self {
+            recent: 0,
+            previous: 0,
+            frequency_window: 0,
+            active_days: 0,
+        };
+        let mut active_days = HashSet::new();
+        for history in histories {
+            windows.recent += history.count_between(recent_start, end);
+            windows.previous += history.count_between(previous_start, recent_start);
+            windows.frequency_window += history.count_between(frequency_start, end);
+            active_days.extend(
+                history
+                    .days
+                    .range(previous_start..end)
+                    .filter(|(_, usage)| usage.total() > 0)
+                    .map(|(day, _)| *day),
+            );
+        }
+        windows.active_days = active_days.len();
+        windows
+    }
+
+    /// Compare the latest window with the one before it
+    fn trend(&self) -> UsageTrend {
+        if self.recent == 0 && self.previous == 0 {
+            UsageTrend::Inactive
+        } else if self.active_days < 3 {
+            UsageTrend::Sporadic
+        } else if self.recent as f32 >= self.previous as f32 * 1.25 {
+            UsageTrend::Increasing
+        } else if (self.recent as f32) <= self.previous as f32 * 0.75 {
+            UsageTrend::Decreasing
+        } else {
+            UsageTrend::Stable
+        }
+    }
+}
+
+/// Classify how often a document is used from its average daily uses
+fn classify_frequency(access_count: u32, frequency_window: u32) -> UsageFrequency {
+    if access_count == 0 {
+        return UsageFrequency::Unused;
+    }
+
+    let per_day = frequency_window as f32 / FREQUENCY_WINDOW_DAYS as f32;
+    if per_day >= 3.0 {
+        UsageFrequency::VeryHigh
+    } else if per_day >= 1.0 {
+        UsageFrequency::High
+    } else if per_day >= 1.0 / 7.0 {
+        UsageFrequency::Medium
+    } else if per_day >= 1.0 / 30.0 {
+        UsageFrequency::Low
+    } else {
+        UsageFrequency::VeryLow
+    }
+}
+
+fn tomorrow(day: NaiveDate) -> NaiveDate {
+    day + Duration::days(1)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1200,6 +1303,58 @@ mod tests {
         assert_eq!(trend, UsageTrend::Increasing);
     }
 
+    #[test]
+    fn test_usage_trends_from_persisted_history() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let workspace = temp_dir.path();
+        let now = Utc::now();
+        let rising = workspace.join("rising.md");
+        let fading = workspace.join("fading.md");
+
+        let mut usage_log = UsageLog::open(workspace).unwrap();
+        for days_ago in [1, 3, 5, 8, 12, 20] {
+            usage_log
+                .record(
+                    &rising,
+                    UsageEventKind::Opened,
+                    now - Duration::days(days_ago),
+                )
+                .unwrap();
+        }
+        for days_ago in [35, 38, 41, 45, 50, 55, 2] {
+            usage_log
+                .record(
+                    &fading,
+                    UsageEventKind::Cited,
+                    now - Duration::days(days_ago),
+                )
+                .unwrap();
+        }
+
+        // Statistics come from the persisted log, not the instance that recorded them
+        let tracker = UsageTracker {
+            usage_log: Some(UsageLog::open(workspace).unwrap()),
+            ..Default::default()
+        };
+
+        let stats = tracker.stats_for(&rising, now);
+        assert_eq!(stats.access_count, 6);
+        assert_eq!(stats.recent_access_count, 6);
+        assert_eq!(stats.trend, UsageTrend::Increasing);
+        assert_eq!(stats.frequency, UsageFrequency::Low);
+
+        let stats = tracker.stats_for(&fading, now);
+        assert_eq!(stats.recent_access_count, 1);
+        assert_eq!(stats.trend, UsageTrend::Decreasing);
+        assert_eq!(stats.last_accessed, Some(now - Duration::days(2)));
+
+        let stats = tracker.stats_for(&workspace.join("unused.md"), now);
+        assert_eq!(stats.frequency, UsageFrequency::Unused);
+        assert_eq!(stats.trend, UsageTrend::Inactive);
+
+        assert_eq!(tracker.workspace_trend(now), UsageTrend::Stable);
+    }
+
     #[test]
     fn test_lifecycle_action_priority() {
         let priority = ActionPriority::Urgent;
@@ -1,6 +1,9 @@
 // src-tauri/src/workspace/manager.rs
 //! Workspace manager implementation
 
+use super::custom_templates::{
+    expected_directories, CustomWorkspaceTemplate, WorkspaceTemplateStore,
+};
 use super::*;
 use crate::app_config::ConfigManager;
 use crate::filesystem::PathValidator;
@@ -12,6 +15,7 @@ use tokio::fs;
 pub struct WorkspaceManager {
     config_manager: Arc<ConfigManager>,
     path_validator: PathValidator,
+    template_store: WorkspaceTemplateStore,
 }
 
 impl WorkspaceManager {
@@ -72,32 +76,43 @@ impl WorkspaceManager {
 
         let path_validator = PathValidator::new(filesystem_security_config, allowed_paths);
 
+        let template_store = WorkspaceTemplateStore::new(
+            WorkspaceTemplateStore::default_dir()
+                .unwrap_or_else(|| PathBuf::from("./workspace-templates")),
+        );
+
         Ok(Self {
             config_manager,
             path_validator,
+            template_store,
         })
     }
 
+    /// User-defined workspace templates
+    pub fn template_store(&self) -> &WorkspaceTemplateStore {
+        &self.template_store
+    }
+
     /// Create a new workspace at the specified path
     pub async fn create_workspace(
         &self,
         request: CreateWorkspaceRequest,
     ) -> WorkspaceResult<WorkspaceInfo> {
-        // Validate the workspace path
-        let workspace_path = self
-            .path_validator
-            .validate_workspace_path(&request.path)
-            .map_err(|e| WorkspaceError::PathValidation {
-                message: format!("Invalid workspace path: {}", e),
-            })?;
-
-        // Check if workspace already exists
-        if workspace_path.exists() && self.is_workspace(&workspace_path).await? {
-            return Err(WorkspaceError::WorkspaceExists {
-                path: workspace_path,
-            });
+        // Custom templates are defined on disk
+        if let WorkspaceTemplate::Custom(id) = &request.template {
+            let template =
+                self.template_store
+                    .load(id, None)
+                    .map_err(|e| WorkspaceError::Template {
+                        message: e.to_string(),
+                    })?;
+            return self
+                .create_workspace_from_template(request, &template)
+                .await;
         }
 
+        let workspace_path = self.new_workspace_path(&request.path).await?;
+
         // Create the workspace directory structure
         self.create_workspace_structure(&workspace_path, &request.template)
             .await?;
@@ -120,7 +135,7 @@ impl WorkspaceManager {
         self.save_workspace_metadata(&workspace_info).await?;
 
         // Create workspace-specific configuration
-        self.create_workspace_config(&workspace_path, &request.template)
+        self.create_workspace_config(&workspace_path, &request.template, None)
             .await?;
 
         println!(
@@ -132,6 +147,95 @@ impl WorkspaceManager {
         Ok(workspace_info)
     }
 
+    /// Create a new workspace laid out by a user-defined template
+    pub async fn create_workspace_from_template(
+        &self,
+        request: CreateWorkspaceRequest,
+        template: &CustomWorkspaceTemplate,
+    ) -> WorkspaceResult<WorkspaceInfo> {
+        let workspace_path = self.new_workspace_path(&request.path).await?;
+        let existed = workspace_path.exists();
+
+        let result = self
+            .lay_out_from_template(&request, &workspace_path, template)
+            .await;
+        match &result {
+            Ok(_) => tracing::info!(
+                "Created workspace '{}' from template {} v{} at: {}",
+                request.name,
+                template.id,
+                template.version,
+                workspace_path.display()
+            ),
+            // The template removes what it laid out itself; this catches the
+            // metadata and configuration written afterwards
+            Err(_) if !existed => {
+                if let Err(e) = fs::remove_dir_all(&workspace_path).await {
+                    tracing::warn!(
+                        "Failed to remove partly created workspace {}: {}",
+                        workspace_path.display(),
+                        e
+                    );
+                }
+            }
+            Err(_) => {}
+        }
+        result
+    }
+
+    async fn lay_out_from_template(
+        &self,
+        request: &CreateWorkspaceRequest,
+        workspace_path: &Path,
+        template: &CustomWorkspaceTemplate,
+    ) -> WorkspaceResult<WorkspaceInfo> {
+        template
+            .apply(workspace_path)
+            .map_err(|e| WorkspaceError::Template {
+                message: e.to_string(),
+            })?;
+
+        let workspace_info = WorkspaceInfo {
+            path: workspace_path.to_path_buf(),
+            name: request.name.clone(),
+            version: "1.1.2".to_string(),
+            created: Utc::now(),
+            last_modified: Utc::now(),
+            last_accessed: Utc::now(),
+            import_settings: template.import_settings.clone(),
+            ai_settings: template.ai_settings.clone(),
+            is_favorite: false,
+            access_count: 1,
+        };
+        self.save_workspace_metadata(&workspace_info).await?;
+
+        let workspace_template = WorkspaceTemplate::Custom(template.id.clone());
+        self.create_workspace_config(workspace_path, &workspace_template, Some(template))
+            .await?;
+
+        Ok(workspace_info)
+    }
+
+    /// Validate the path for a new workspace and make sure none exists there yet
+    async fn new_workspace_path(&self, path: &Path) -> WorkspaceResult<PathBuf> {
+        // Validate the workspace path
+        let workspace_path = self
+            .path_validator
+            .validate_workspace_path(path)
+            .map_err(|e| WorkspaceError::PathValidation {
+                message: format!("Invalid workspace path: {}", e),
+            })?;
+
+        // Check if workspace already exists
+        if workspace_path.exists() && self.is_workspace(&workspace_path).await? {
+            return Err(WorkspaceError::WorkspaceExists {
+                path: workspace_path,
+            });
+        }
+
+        Ok(workspace_path)
+    }
+
     /// Create the standard workspace directory structure
     async fn create_workspace_structure(
         &self,
@@ -358,6 +462,7 @@ This workspace is designed for creating, maintaining, and organizing documentati
         &self,
         workspace_path: &Path,
         template: &WorkspaceTemplate,
+        custom: Option<&CustomWorkspaceTemplate>,
     ) -> WorkspaceResult<()> {
         let config_path = workspace_path.join(WORKSPACE_CONFIG_FILE);
 
@@ -374,7 +479,11 @@ This workspace is designed for creating, maintaining, and organizing documentati
         };
 
         // Create workspace-specific overrides based on template
-        let workspace_config = WorkspaceConfig::from_base_config(base_config, template.clone());
+        let mut workspace_config = WorkspaceConfig::from_base_config(base_config, template.clone());
+        if let Some(custom) = custom {
+            workspace_config.workspace.import_settings = custom.import_settings.clone();
+            workspace_config.workspace.ai_settings = custom.ai_settings.clone();
+        }
 
         let config_json = serde_json::to_string_pretty(&workspace_config)?;
         fs::write(&config_path, config_json).await?;
@@ -433,11 +542,11 @@ This workspace is designed for creating, maintaining, and organizing documentati
             return Ok(validation);
         }
 
-        // Check for required directories
-        for dir_path in WORKSPACE_DIRECTORIES {
-            let full_path = path.join(dir_path);
+        // Check for required directories, laid out by the workspace's template
+        for dir_path in expected_directories(path) {
+            let full_path = path.join(&dir_path);
             if !full_path.exists() {
-                validation.missing_directories.push(dir_path.to_string());
+                validation.missing_directories.push(dir_path.clone());
                 validation
                     .warnings
                     .push(format!("Missing directory: {}", dir_path));
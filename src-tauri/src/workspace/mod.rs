@@ -10,14 +10,26 @@ use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
 pub mod backup;
+pub mod backup_replication;
+pub mod backup_s3;
+pub mod backup_scheduler;
+pub mod backup_secrets;
+pub mod backup_store;
+pub mod bundle;
 pub mod config;
+pub mod custom_templates;
+pub mod federation;
 pub mod intelligence;
 pub mod knowledge_analyzer;
 pub mod lifecycle_manager;
 pub mod manager;
+pub mod organization_plan;
 pub mod performance;
 pub mod smart_organizer;
+#[cfg(test)]
+pub(crate) mod test_fixtures;
 pub mod types;
+pub mod usage_log;
 pub mod workspace_analyzer;
 
 pub use config::*;
@@ -161,4 +173,10 @@ pub enum WorkspaceError {
 
     #[error("Workspace not found at: {path}")]
     WorkspaceNotFound { path: PathBuf },
+
+    #[error("Backup error: {message}")]
+    Backup { message: String },
+
+    #[error("Template error: {message}")]
+    Template { message: String },
 }
@@ -0,0 +1,1238 @@
+// src-tauri/src/workspace/organization_plan.rs
+//! Executable reorganization plans built from `SmartOrganizer` actions
+//!
+//! A plan is previewed as a dry-run diff of the workspace file tree and then applied as a
+//! unit. Every step is recorded in an undo journal under `.fiovana/organization`; a failing
+//! step rolls back the steps before it, and a finished plan can be undone later. A plan cut
+//! short by a crash keeps its journal, so it can be undone or resumed. Moving a
+//! document re-keys it in the document index and the vector store so search and
+//! relationship data follow the file. Only approved documents may be moved into
+//! `outputs/approved`.
+
+use super::smart_organizer::OrganizationAction;
+use crate::document::indexer::{DocumentIndexer, DocumentRelocation};
+use crate::document::metadata_store::{DocumentTag, MetadataUpdate};
+use crate::document::review_workflow::{ensure_publishable, is_approved_location};
+use crate::filesystem::atomic_write::write_atomically;
+use crate::vector::VectorStore;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tracing::warn;
+
+/// Directory holding plan journals, relative to the workspace root
+pub const PLAN_JOURNAL_DIR: &str = ".fiovana/organization";
+
+/// Folder archived documents are moved into
+const ARCHIVE_DIR: &str = "sources/archives";
+
+/// Folder redundant copies are moved into when duplicates are merged
+const DUPLICATES_DIR: &str = "sources/archives/duplicates";
+
+/// A concrete file-tree operation behind an organization action
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlannedOperation {
+    /// Create a folder and any missing parents
+    CreateFolder { path: PathBuf },
+    /// Move a document to a new path
+    Move { from: PathBuf, to: PathBuf },
+    /// Add tags to an indexed document
+    Tag {
+        document: PathBuf,
+        tags: Vec<DocumentTag>,
+    },
+    /// Set custom metadata fields on an indexed document
+    AddMetadata {
+        document: PathBuf,
+        fields: BTreeMap<String, String>,
+    },
+    /// Move a document into the workspace archive
+    Archive { document: PathBuf },
+    /// Keep the primary document and archive its duplicates
+    Merge {
+        primary: PathBuf,
+        duplicates: Vec<PathBuf>,
+    },
+}
+
+/// A single step of a plan once it has been resolved against the file tree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PlanChange {
+    CreateFolder {
+        path: PathBuf,
+    },
+    Move {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    Tag {
+        document: PathBuf,
+        tags: Vec<DocumentTag>,
+    },
+    AddMetadata {
+        document: PathBuf,
+        fields: BTreeMap<String, String>,
+    },
+}
+
+/// A resolved step and the action it came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedChange {
+    pub action_id: String,
+    #[serde(flatten)]
+    pub change: PlanChange,
+}
+
+/// Why part of a plan cannot be applied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanConflict {
+    pub action_id: String,
+    pub message: String,
+}
+
+/// Dry-run result of a plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanPreview {
+    pub workspace_path: PathBuf,
+    pub changes: Vec<PlannedChange>,
+    pub conflicts: Vec<PlanConflict>,
+    /// The changes as a file-tree diff, one line per change
+    pub diff: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PlanStatus {
+    Applying,
+    Applied,
+    RolledBack,
+    /// Left at `Applying` with no apply running, e.g. after a crash. Only
+    /// reported when listing plans; the plan can be undone or resumed.
+    Interrupted,
+}
+
+/// A completed step, with what is needed to reverse it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JournalEntry {
+    CreatedFolder {
+        path: PathBuf,
+    },
+    Moved {
+        from: PathBuf,
+        to: PathBuf,
+        relocation: Option<DocumentRelocation>,
+    },
+    Tagged {
+        document_id: String,
+        tags: Vec<String>,
+    },
+    MetadataSet {
+        document_id: String,
+        /// Field values before the change, `None` where the field was unset
+        previous: BTreeMap<String, Option<String>>,
+    },
+}
+
+/// Undo journal of a plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanJournal {
+    pub plan_id: String,
+    pub workspace_path: PathBuf,
+    pub action_ids: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub status: PlanStatus,
+    /// Every step of the plan, in the order they are applied
+    pub changes: Vec<PlannedChange>,
+    /// Completed steps; the first `entries.len()` changes have been applied
+    pub entries: Vec<JournalEntry>,
+}
+
+/// The stores a plan keeps in step with the files it moves
+pub struct PlanIndexes<'a> {
+    pub indexer: &'a mut DocumentIndexer,
+    pub vector_store: Option<&'a VectorStore>,
+}
+
+/// Resolve the selected actions against the workspace without touching it
+pub fn preview_plan(workspace_path: &Path, actions: &[OrganizationAction]) -> PlanPreview {
+    let mut tree = VirtualTree::new(workspace_path);
+    let mut changes = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for action in actions {
+        if action.operations.is_empty() {
+            conflicts.push(PlanConflict {
+                action_id: action.action_id.clone(),
+                message: "Action has no executable operations".to_string(),
+            });
+        }
+
+        for operation in &action.operations {
+            // Plan against a copy so a conflicting operation leaves no partial changes
+            let mut attempt = tree.clone();
+            let mut resolved = Vec::new();
+            match attempt.plan(operation, &mut resolved) {
+                Ok(()) => {
+                    tree = attempt;
+                    changes.extend(resolved.into_iter().map(|change| PlannedChange {
+                        action_id: action.action_id.clone(),
+                        change,
+                    }));
+                }
+                Err(message) => conflicts.push(PlanConflict {
+                    action_id: action.action_id.clone(),
+                    message,
+                }),
+            }
+        }
+    }
+
+    let diff = changes
+        .iter()
+        .map(|planned| describe_change(workspace_path, &planned.change))
+        .collect();
+
+    PlanPreview {
+        workspace_path: workspace_path.to_path_buf(),
+        changes,
+        conflicts,
+        diff,
+    }
+}
+
+/// Apply the selected actions as one unit, rolling back every step if any of them fails
+pub async fn apply_plan(
+    workspace_path: &Path,
+    actions: &[OrganizationAction],
+    indexes: &mut PlanIndexes<'_>,
+) -> Result<PlanJournal> {
+    let preview = preview_plan(workspace_path, actions);
+    if !preview.conflicts.is_empty() {
+        let messages: Vec<String> = preview
+            .conflicts
+            .iter()
+            .map(|conflict| format!("{}: {}", conflict.action_id, conflict.message))
+            .collect();
+        bail!("Plan has conflicts: {}", messages.join("; "));
+    }
+
+    let mut journal = PlanJournal {
+        plan_id: uuid::Uuid::new_v4().to_string(),
+        workspace_path: workspace_path.to_path_buf(),
+        action_ids: actions.iter().map(|a| a.action_id.clone()).collect(),
+        created_at: Utc::now(),
+        completed_at: None,
+        status: PlanStatus::Applying,
+        changes: preview.changes.clone(),
+        entries: Vec::new(),
+    };
+    journal.save()?;
+
+    run_steps(workspace_path, &mut journal, &preview.changes, indexes).await?;
+    Ok(journal)
+}
+
+/// Continue an interrupted plan from the first step its journal does not record
+pub async fn resume_plan(
+    workspace_path: &Path,
+    plan_id: &str,
+    indexes: &mut PlanIndexes<'_>,
+) -> Result<PlanJournal> {
+    let mut journal = PlanJournal::load(workspace_path, plan_id)?;
+    if journal.status != PlanStatus::Applying {
+        bail!("Plan {} was not interrupted", plan_id);
+    }
+
+    let remaining = journal
+        .changes
+        .get(journal.entries.len()..)
+        .unwrap_or_default()
+        .to_vec();
+    run_steps(workspace_path, &mut journal, &remaining, indexes).await?;
+    Ok(journal)
+}
+
+/// Apply `changes` in order, journaling each step, and roll back the whole
+/// plan if one of them fails
+async fn run_steps(
+    workspace_path: &Path,
+    journal: &mut PlanJournal,
+    changes: &[PlannedChange],
+    indexes: &mut PlanIndexes<'_>,
+) -> Result<()> {
+    for planned in changes {
+        let applied = match check_publication(workspace_path, &planned.change, indexes) {
+            Ok(()) => apply_change(&planned.change, indexes).await,
+            Err(e) => Err(e),
+        };
+        let step = match applied {
+            Ok(entry) => {
+                journal.entries.push(entry);
+                journal.save()
+            }
+            Err(e) => Err(e),
+        };
+
+        if let Err(error) = step {
+            let rollback = revert_entries(&journal.entries, indexes).await;
+            journal.status = PlanStatus::RolledBack;
+            journal.completed_at = Some(Utc::now());
+            journal.save()?;
+
+            return Err(match rollback {
+                Ok(()) => error.context(format!(
+                    "Plan {} failed at action {} and was rolled back",
+                    journal.plan_id, planned.action_id
+                )),
+                Err(rollback_error) => error.context(format!(
+                    "Plan {} failed at action {} and could not be fully rolled back: {:#}",
+                    journal.plan_id, planned.action_id, rollback_error
+                )),
+            });
+        }
+    }
+
+    journal.status = PlanStatus::Applied;
+    journal.completed_at = Some(Utc::now());
+    journal.save()
+}
+
+/// Roll back every step of a plan, refusing if the moved files are no longer where it left them
+pub async fn undo_plan(
+    workspace_path: &Path,
+    plan_id: &str,
+    indexes: &mut PlanIndexes<'_>,
+) -> Result<PlanJournal> {
+    let mut journal = PlanJournal::load(workspace_path, plan_id)?;
+    if journal.status == PlanStatus::RolledBack {
+        bail!("Plan {} has already been rolled back", plan_id);
+    }
+
+    // Walk the moves backwards over a simulated tree before touching anything
+    let mut present = HashSet::new();
+    let mut absent = HashSet::new();
+    for entry in journal.entries.iter().rev() {
+        if let JournalEntry::Moved { from, to, .. } = entry {
+            let exists = |path: &PathBuf| {
+                present.contains(path) || (!absent.contains(path) && path.exists())
+            };
+            if !exists(to) {
+                bail!("Cannot undo plan {}: {} is missing", plan_id, to.display());
+            }
+            if exists(from) {
+                bail!(
+                    "Cannot undo plan {}: {} has been recreated",
+                    plan_id,
+                    from.display()
+                );
+            }
+            // Moving a document back into the approved folder publishes it again
+            if is_approved_location(workspace_path, from) {
+                ensure_publishable(indexes.indexer, to)
+                    .with_context(|| format!("Cannot undo plan {}", plan_id))?;
+            }
+            present.remove(to);
+            absent.insert(to.clone());
+            absent.remove(from);
+            present.insert(from.clone());
+        }
+    }
+
+    let result = revert_entries(&journal.entries, indexes).await;
+    journal.status = PlanStatus::RolledBack;
+    journal.completed_at = Some(Utc::now());
+    journal.save()?;
+    result.with_context(|| format!("Plan {} was only partly rolled back", plan_id))?;
+
+    Ok(journal)
+}
+
+/// Journals of the plans applied to a workspace, newest first
+pub fn list_plans(workspace_path: &Path) -> Result<Vec<PlanJournal>> {
+    let dir = workspace_path.join(PLAN_JOURNAL_DIR);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut journals = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        match fs::read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| serde_json::from_str::<PlanJournal>(&data).map_err(Into::into))
+        {
+            Ok(journal) => journals.push(journal),
+            Err(e) => warn!("Skipping unreadable plan journal {}: {}", path.display(), e),
+        }
+    }
+
+    journals.sort_by_key(|journal| std::cmp::Reverse(journal.created_at));
+    Ok(journals)
+}
+
+impl PlanJournal {
+    fn path(workspace_path: &Path, plan_id: &str) -> Result<PathBuf> {
+        if plan_id.is_empty()
+            || !plan_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            bail!("Invalid plan ID: {}", plan_id);
+        }
+        Ok(workspace_path
+            .join(PLAN_JOURNAL_DIR)
+            .join(format!("{}.json", plan_id)))
+    }
+
+    fn load(workspace_path: &Path, plan_id: &str) -> Result<Self> {
+        let path = Self::path(workspace_path, plan_id)?;
+        let data = fs::read_to_string(&path)
+            .with_context(|| format!("No journal found for plan {}", plan_id))?;
+        serde_json::from_str(&data).context("Failed to parse plan journal")
+    }
+
+    fn save(&self) -> Result<()> {
+        let path = Self::path(&self.workspace_path, &self.plan_id)?;
+        // A crash mid-write must never leave the journal half-written
+        write_atomically(&path, &serde_json::to_vec_pretty(self)?)
+            .context("Failed to write plan journal")
+    }
+}
+
+/// Only approved documents may be moved into `outputs/approved`
+fn check_publication(
+    workspace_path: &Path,
+    change: &PlanChange,
+    indexes: &PlanIndexes<'_>,
+) -> Result<()> {
+    match change {
+        PlanChange::Move { from, to } if is_approved_location(workspace_path, to) => {
+            ensure_publishable(indexes.indexer, from)
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn apply_change(change: &PlanChange, indexes: &mut PlanIndexes<'_>) -> Result<JournalEntry> {
+    match change {
+        PlanChange::CreateFolder { path } => {
+            fs::create_dir(path)
+                .with_context(|| format!("Failed to create folder {}", path.display()))?;
+            Ok(JournalEntry::CreatedFolder { path: path.clone() })
+        }
+        PlanChange::Move { from, to } => {
+            if to.exists() {
+                bail!("{} already exists", to.display());
+            }
+            fs::rename(from, to).with_context(|| {
+                format!("Failed to move {} to {}", from.display(), to.display())
+            })?;
+
+            match relocate(indexes, from, to).await {
+                Ok(relocation) => Ok(JournalEntry::Moved {
+                    from: from.clone(),
+                    to: to.clone(),
+                    relocation,
+                }),
+                Err(e) => {
+                    if let Err(restore) = fs::rename(to, from) {
+                        warn!("Failed to move {} back: {}", to.display(), restore);
+                    }
+                    Err(e)
+                }
+            }
+        }
+        PlanChange::Tag { document, tags } => {
+            let document_id = indexed_id(indexes, document)?;
+            let record = indexes.indexer.get_metadata(&document_id);
+            let added: Vec<DocumentTag> = tags
+                .iter()
+                .filter(|tag| !record.is_some_and(|r| r.has_tag(&tag.name)))
+                .cloned()
+                .collect();
+
+            indexes.indexer.update_metadata(
+                &document_id,
+                MetadataUpdate {
+                    add_tags: added.clone(),
+                    ..Default::default()
+                },
+            )?;
+            Ok(JournalEntry::Tagged {
+                document_id,
+                tags: added.into_iter().map(|tag| tag.name).collect(),
+            })
+        }
+        PlanChange::AddMetadata { document, fields } => {
+            let document_id = indexed_id(indexes, document)?;
+            let record = indexes.indexer.get_metadata(&document_id);
+            let previous = fields
+                .keys()
+                .map(|key| {
+                    let value = record.and_then(|r| r.fields.get(key).cloned());
+                    (key.clone(), value)
+                })
+                .collect();
+
+            indexes.indexer.update_metadata(
+                &document_id,
+                MetadataUpdate {
+                    set_fields: fields.clone(),
+                    ..Default::default()
+                },
+            )?;
+            Ok(JournalEntry::MetadataSet {
+                document_id,
+                previous,
+            })
+        }
+    }
+}
+
+fn indexed_id(indexes: &PlanIndexes<'_>, document: &Path) -> Result<String> {
+    indexes
+        .indexer
+        .get_document_by_path(document)
+        .map(|entry| entry.id.clone())
+        .with_context(|| format!("{} is not indexed", document.display()))
+}
+
+async fn revert_entries(entries: &[JournalEntry], indexes: &mut PlanIndexes<'_>) -> Result<()> {
+    let mut failures = Vec::new();
+    for entry in entries.iter().rev() {
+        if let Err(e) = revert_entry(entry, indexes).await {
+            failures.push(format!("{:#}", e));
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        bail!(failures.join("; "))
+    }
+}
+
+async fn revert_entry(entry: &JournalEntry, indexes: &mut PlanIndexes<'_>) -> Result<()> {
+    match entry {
+        JournalEntry::CreatedFolder { path } => {
+            // A folder the user has since filled is left in place
+            if let Err(e) = fs::remove_dir(path) {
+                warn!("Leaving folder {} in place: {}", path.display(), e);
+            }
+        }
+        JournalEntry::Moved {
+            from,
+            to,
+            relocation,
+        } => {
+            if from.exists() {
+                bail!("{} exists again", from.display());
+            }
+            if let Some(parent) = from.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(to, from).with_context(|| {
+                format!("Failed to move {} back to {}", to.display(), from.display())
+            })?;
+            if relocation.is_some() {
+                relocate(indexes, to, from).await?;
+            }
+        }
+        JournalEntry::Tagged { document_id, tags } => {
+            if !tags.is_empty() {
+                indexes.indexer.update_metadata(
+                    document_id,
+                    MetadataUpdate {
+                        remove_tags: tags.clone(),
+                        ..Default::default()
+                    },
+                )?;
+            }
+        }
+        JournalEntry::MetadataSet {
+            document_id,
+            previous,
+        } => {
+            let mut update = MetadataUpdate::default();
+            for (key, value) in previous {
+                match value {
+                    Some(value) => {
+                        update.set_fields.insert(key.clone(), value.clone());
+                    }
+                    None => update.remove_fields.push(key.clone()),
+                }
+            }
+            indexes.indexer.update_metadata(document_id, update)?;
+        }
+    }
+    Ok(())
+}
+
+/// Follow a moved document in the index and vector store
+async fn relocate(
+    indexes: &mut PlanIndexes<'_>,
+    from: &Path,
+    to: &Path,
+) -> Result<Option<DocumentRelocation>> {
+    let Some(relocation) = indexes.indexer.relocate_document(from, to)? else {
+        return Ok(None);
+    };
+
+    if let Some(vector_store) = indexes.vector_store {
+        if let Err(e) = vector_store
+            .rekey_document(&relocation.old_id, &relocation.new_id)
+            .await
+        {
+            indexes.indexer.relocate_document(to, from)?;
+            return Err(e);
+        }
+    }
+    Ok(Some(relocation))
+}
+
+fn describe_change(workspace_path: &Path, change: &PlanChange) -> String {
+    let relative = |path: &Path| {
+        path.strip_prefix(workspace_path)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+
+    match change {
+        PlanChange::CreateFolder { path } => format!("+ {}/", relative(path)),
+        PlanChange::Move { from, to } => format!("~ {} -> {}", relative(from), relative(to)),
+        PlanChange::Tag { document, tags } => {
+            let names: Vec<&str> = tags.iter().map(|tag| tag.name.as_str()).collect();
+            format!("# {} [{}]", relative(document), names.join(", "))
+        }
+        PlanChange::AddMetadata { document, fields } => {
+            let pairs: Vec<String> = fields
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect();
+            format!("# {} {{{}}}", relative(document), pairs.join(", "))
+        }
+    }
+}
+
+/// The workspace file tree as it will look after the changes planned so far
+#[derive(Clone)]
+struct VirtualTree {
+    root: PathBuf,
+    folders: HashSet<PathBuf>,
+    files: HashSet<PathBuf>,
+    removed: HashSet<PathBuf>,
+    /// Where each document moved earlier in the plan now lives
+    moved: HashMap<PathBuf, PathBuf>,
+}
+
+impl VirtualTree {
+    fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            folders: HashSet::new(),
+            files: HashSet::new(),
+            removed: HashSet::new(),
+            moved: HashMap::new(),
+        }
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains(path) || (!self.removed.contains(path) && path.is_file())
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.folders.contains(path) || (!self.removed.contains(path) && path.is_dir())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.is_file(path) || self.is_dir(path)
+    }
+
+    fn plan(
+        &mut self,
+        operation: &PlannedOperation,
+        out: &mut Vec<PlanChange>,
+    ) -> Result<(), String> {
+        match operation {
+            PlannedOperation::CreateFolder { path } => {
+                let path = self.resolve(path)?;
+                self.create_folder(&path, out)
+            }
+            PlannedOperation::Move { from, to } => {
+                let from = self.current(from)?;
+                let to = self.resolve(to)?;
+                self.move_document(&from, &to, out)
+            }
+            PlannedOperation::Tag { document, tags } => {
+                let document = self.current(document)?;
+                if !self.is_file(&document) {
+                    return Err(format!("{} does not exist", document.display()));
+                }
+                let tags: Vec<DocumentTag> = tags
+                    .iter()
+                    .filter(|tag| !tag.name.trim().is_empty())
+                    .cloned()
+                    .collect();
+                if !tags.is_empty() {
+                    out.push(PlanChange::Tag { document, tags });
+                }
+                Ok(())
+            }
+            PlannedOperation::AddMetadata { document, fields } => {
+                let document = self.current(document)?;
+                if !self.is_file(&document) {
+                    return Err(format!("{} does not exist", document.display()));
+                }
+                if !fields.is_empty() {
+                    out.push(PlanChange::AddMetadata {
+                        document,
+                        fields: fields.clone(),
+                    });
+                }
+                Ok(())
+            }
+            PlannedOperation::Archive { document } => {
+                let document = self.current(document)?;
+                self.archive(&document, ARCHIVE_DIR, out)
+            }
+            PlannedOperation::Merge {
+                primary,
+                duplicates,
+            } => {
+                let primary = self.current(primary)?;
+                if !self.is_file(&primary) {
+                    return Err(format!("{} does not exist", primary.display()));
+                }
+                for duplicate in duplicates {
+                    let duplicate = self.current(duplicate)?;
+                    if duplicate != primary {
+                        self.archive(&duplicate, DUPLICATES_DIR, out)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Absolute path inside the workspace, rejecting anything that escapes it
+    fn resolve(&self, path: &Path) -> Result<PathBuf, String> {
+        if path.components().any(|c| matches!(c, Component::ParentDir)) {
+            return Err(format!("{} leaves the workspace", path.display()));
+        }
+        let path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.root.join(path)
+        };
+
+        let relative = path
+            .strip_prefix(&self.root)
+            .map_err(|_| format!("{} is outside the workspace", path.display()))?;
+        if relative.as_os_str().is_empty() || relative.starts_with(".fiovana") {
+            return Err(format!("{} cannot be reorganized", path.display()));
+        }
+        Ok(path)
+    }
+
+    /// Resolved path of a document, following any move planned earlier
+    fn current(&self, path: &Path) -> Result<PathBuf, String> {
+        let path = self.resolve(path)?;
+        Ok(self.moved.get(&path).cloned().unwrap_or(path))
+    }
+
+    fn create_folder(&mut self, path: &Path, out: &mut Vec<PlanChange>) -> Result<(), String> {
+        let mut missing = Vec::new();
+        let mut current = Some(path);
+        while let Some(dir) = current {
+            if dir == self.root || self.is_dir(dir) {
+                break;
+            }
+            if self.is_file(dir) {
+                return Err(format!("{} is a file, not a folder", dir.display()));
+            }
+            missing.push(dir.to_path_buf());
+            current = dir.parent();
+        }
+
+        for dir in missing.into_iter().rev() {
+            self.removed.remove(&dir);
+            self.folders.insert(dir.clone());
+            out.push(PlanChange::CreateFolder { path: dir });
+        }
+        Ok(())
+    }
+
+    fn move_document(
+        &mut self,
+        from: &Path,
+        to: &Path,
+        out: &mut Vec<PlanChange>,
+    ) -> Result<(), String> {
+        if from == to {
+            return Ok(());
+        }
+        if !self.is_file(from) {
+            return Err(format!("{} does not exist", from.display()));
+        }
+        if self.exists(to) {
+            return Err(format!("{} already exists", to.display()));
+        }
+        if let Some(parent) = to.parent() {
+            self.create_folder(parent, out)?;
+        }
+
+        self.files.remove(from);
+        self.removed.insert(from.to_path_buf());
+        self.removed.remove(to);
+        self.files.insert(to.to_path_buf());
+        for destination in self.moved.values_mut() {
+            if destination == from {
+                *destination = to.to_path_buf();
+            }
+        }
+        self.moved.insert(from.to_path_buf(), to.to_path_buf());
+
+        out.push(PlanChange::Move {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+        });
+        Ok(())
+    }
+
+    fn archive(
+        &mut self,
+        document: &Path,
+        folder: &str,
+        out: &mut Vec<PlanChange>,
+    ) -> Result<(), String> {
+        let folder = self.root.join(folder);
+        if document.parent() == Some(folder.as_path()) {
+            return Ok(());
+        }
+        let name = document
+            .file_name()
+            .ok_or_else(|| format!("{} is not a document", document.display()))?;
+
+        // Keep both copies when the archive already holds a file of the same name
+        let mut destination = folder.join(name);
+        let stem = Path::new(name)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let extension = Path::new(name)
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default();
+        let mut counter = 1;
+        while self.exists(&destination) {
+            destination = folder.join(format!("{}-{}{}", stem, counter, extension));
+            counter += 1;
+        }
+
+        self.move_document(document, &destination, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::{DocumentChunk, EmbeddingRecord};
+    use crate::workspace::smart_organizer::{
+        ActionPriority, ActionType, BenefitLevel, ImplementationEffort,
+    };
+    use tempfile::TempDir;
+
+    fn action(action_id: &str, operations: Vec<PlannedOperation>) -> OrganizationAction {
+        OrganizationAction {
+            action_id: action_id.to_string(),
+            action_type: ActionType::MoveDocuments,
+            priority: ActionPriority::Medium,
+            description: action_id.to_string(),
+            affected_documents: vec![],
+            implementation_steps: vec![],
+            estimated_time: ImplementationEffort::Minimal,
+            expected_benefit: BenefitLevel::Medium,
+            dependencies: vec![],
+            operations,
+        }
+    }
+
+    async fn indexed_workspace() -> (TempDir, DocumentIndexer, VectorStore) {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("notes")).unwrap();
+        fs::write(
+            root.join("notes/report.md"),
+            "# Report\n\nQuarterly figures.",
+        )
+        .unwrap();
+        fs::write(root.join("notes/draft.md"), "# Draft\n\nQuarterly figures.").unwrap();
+
+        let mut indexer = DocumentIndexer::new(root.join(".fiovana/index")).unwrap();
+        let vector_store = VectorStore::new(2);
+        for name in ["report.md", "draft.md"] {
+            let entry = indexer
+                .index_document(&root.join("notes").join(name))
+                .await
+                .unwrap();
+            let chunk = DocumentChunk {
+                id: format!("{}:0", entry.id),
+                document_id: entry.id.clone(),
+                content: entry.content.clone(),
+                chunk_index: 0,
+                start_char: 0,
+                end_char: entry.content.len(),
+                metadata: HashMap::new(),
+            };
+            let embedding = EmbeddingRecord {
+                chunk_id: chunk.id.clone(),
+                embedding: vec![1.0, 0.0],
+                timestamp: Utc::now(),
+            };
+            vector_store
+                .add_document_chunks(vec![chunk], vec![embedding])
+                .await
+                .unwrap();
+        }
+
+        (temp_dir, indexer, vector_store)
+    }
+
+    #[test]
+    fn test_preview_reports_diff_and_conflicts() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("a.md"), "a").unwrap();
+        fs::write(root.join("b.md"), "b").unwrap();
+
+        let actions = vec![
+            action(
+                "categorize_0",
+                vec![PlannedOperation::Move {
+                    from: root.join("a.md"),
+                    to: root.join("Reports/2024/a.md"),
+                }],
+            ),
+            action(
+                "dedupe_1",
+                vec![PlannedOperation::Merge {
+                    primary: root.join("a.md"),
+                    duplicates: vec![root.join("b.md")],
+                }],
+            ),
+            action(
+                "escape_2",
+                vec![PlannedOperation::Archive {
+                    document: PathBuf::from("../outside.md"),
+                }],
+            ),
+            action(
+                "missing_3",
+                vec![PlannedOperation::Tag {
+                    document: root.join("gone.md"),
+                    tags: vec![DocumentTag::custom("x")],
+                }],
+            ),
+        ];
+
+        let preview = preview_plan(root, &actions);
+        assert_eq!(
+            preview.diff,
+            vec![
+                "+ Reports/",
+                "+ Reports/2024/",
+                "~ a.md -> Reports/2024/a.md",
+                "+ sources/",
+                "+ sources/archives/",
+                "+ sources/archives/duplicates/",
+                "~ b.md -> sources/archives/duplicates/b.md",
+            ]
+        );
+        let conflicted: Vec<&str> = preview
+            .conflicts
+            .iter()
+            .map(|c| c.action_id.as_str())
+            .collect();
+        assert_eq!(conflicted, vec!["escape_2", "missing_3"]);
+
+        // Nothing on disk changed
+        assert!(root.join("a.md").exists());
+        assert!(!root.join("Reports").exists());
+    }
+
+    #[tokio::test]
+    async fn test_apply_updates_indexes_and_undo_restores() {
+        let (temp_dir, mut indexer, vector_store) = indexed_workspace().await;
+        let root = temp_dir.path();
+        let report = root.join("notes/report.md");
+        let draft = root.join("notes/draft.md");
+        let old_id = indexer.get_document_by_path(&report).unwrap().id.clone();
+
+        let actions = vec![
+            action(
+                "categorize_0",
+                vec![PlannedOperation::Move {
+                    from: report.clone(),
+                    to: root.join("Reports/report.md"),
+                }],
+            ),
+            action(
+                "tag_1",
+                vec![
+                    PlannedOperation::Tag {
+                        document: report.clone(),
+                        tags: vec![DocumentTag::custom("quarterly")],
+                    },
+                    PlannedOperation::AddMetadata {
+                        document: report.clone(),
+                        fields: BTreeMap::from([("status".to_string(), "final".to_string())]),
+                    },
+                ],
+            ),
+            action(
+                "archive_2",
+                vec![PlannedOperation::Archive {
+                    document: draft.clone(),
+                }],
+            ),
+        ];
+
+        let mut indexes = PlanIndexes {
+            indexer: &mut indexer,
+            vector_store: Some(&vector_store),
+        };
+        let journal = apply_plan(root, &actions, &mut indexes).await.unwrap();
+        assert_eq!(journal.status, PlanStatus::Applied);
+
+        let moved = root.join("Reports/report.md");
+        assert!(moved.exists() && !report.exists());
+        assert!(root.join("sources/archives/draft.md").exists());
+
+        let entry = indexer.get_document_by_path(&moved).unwrap().clone();
+        assert_ne!(entry.id, old_id);
+        let record = indexer.get_metadata(&entry.id).unwrap();
+        assert!(record.has_tag("quarterly"));
+        assert_eq!(record.fields["status"], "final");
+        assert!(indexer.get_document(&old_id).is_none());
+        let chunks = vector_store.get_document_chunks(&entry.id).await.unwrap();
+        assert_eq!(chunks[0].id, format!("{}:0", entry.id));
+        assert!(vector_store.get_document_chunks(&old_id).await.is_err());
+
+        let listed = list_plans(root).unwrap();
+        assert_eq!(listed[0].plan_id, journal.plan_id);
+
+        let mut indexes = PlanIndexes {
+            indexer: &mut indexer,
+            vector_store: Some(&vector_store),
+        };
+        let undone = undo_plan(root, &journal.plan_id, &mut indexes)
+            .await
+            .unwrap();
+        assert_eq!(undone.status, PlanStatus::RolledBack);
+
+        assert!(report.exists() && draft.exists());
+        assert!(!root.join("Reports").exists());
+        let restored = indexer.get_document(&old_id).unwrap();
+        assert_eq!(restored.path, report);
+        assert!(indexer.get_metadata(&old_id).is_none());
+        assert_eq!(
+            vector_store
+                .get_document_chunks(&old_id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        let mut indexes = PlanIndexes {
+            indexer: &mut indexer,
+            vector_store: Some(&vector_store),
+        };
+        assert!(undo_plan(root, &journal.plan_id, &mut indexes)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_step_rolls_back_plan() {
+        let (temp_dir, mut indexer, vector_store) = indexed_workspace().await;
+        let root = temp_dir.path();
+        let report = root.join("notes/report.md");
+        fs::write(root.join("notes/unindexed.md"), "not indexed").unwrap();
+
+        let actions = vec![
+            action(
+                "categorize_0",
+                vec![PlannedOperation::Move {
+                    from: report.clone(),
+                    to: root.join("Reports/report.md"),
+                }],
+            ),
+            action(
+                "tag_1",
+                vec![PlannedOperation::Tag {
+                    document: root.join("notes/unindexed.md"),
+                    tags: vec![DocumentTag::custom("orphan")],
+                }],
+            ),
+        ];
+
+        let mut indexes = PlanIndexes {
+            indexer: &mut indexer,
+            vector_store: Some(&vector_store),
+        };
+        let error = apply_plan(root, &actions, &mut indexes).await.unwrap_err();
+        assert!(format!("{:#}", error).contains("rolled back"));
+
+        assert!(report.exists());
+        assert!(!root.join("Reports").exists());
+        assert!(indexer.get_document_by_path(&report).is_some());
+        assert_eq!(list_plans(root).unwrap()[0].status, PlanStatus::RolledBack);
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_plan_resumes_from_last_recorded_step() {
+        let (temp_dir, mut indexer, vector_store) = indexed_workspace().await;
+        let root = temp_dir.path();
+        let report = root.join("notes/report.md");
+        let actions = vec![action(
+            "categorize_0",
+            vec![PlannedOperation::Move {
+                from: report.clone(),
+                to: root.join("Reports/report.md"),
+            }],
+        )];
+
+        // A crash right after creating the folder leaves the journal at Applying
+        let preview = preview_plan(root, &actions);
+        fs::create_dir(root.join("Reports")).unwrap();
+        let journal = PlanJournal {
+            plan_id: uuid::Uuid::new_v4().to_string(),
+            workspace_path: root.to_path_buf(),
+            action_ids: vec!["categorize_0".to_string()],
+            created_at: Utc::now(),
+            completed_at: None,
+            status: PlanStatus::Applying,
+            changes: preview.changes,
+            entries: vec![JournalEntry::CreatedFolder {
+                path: root.join("Reports"),
+            }],
+        };
+        journal.save().unwrap();
+
+        let mut indexes = PlanIndexes {
+            indexer: &mut indexer,
+            vector_store: Some(&vector_store),
+        };
+        let resumed = resume_plan(root, &journal.plan_id, &mut indexes)
+            .await
+            .unwrap();
+        assert_eq!(resumed.status, PlanStatus::Applied);
+        assert_eq!(resumed.entries.len(), 2);
+        assert!(root.join("Reports/report.md").exists() && !report.exists());
+        assert!(indexer
+            .get_document_by_path(&root.join("Reports/report.md"))
+            .is_some());
+
+        let mut indexes = PlanIndexes {
+            indexer: &mut indexer,
+            vector_store: Some(&vector_store),
+        };
+        assert!(resume_plan(root, &journal.plan_id, &mut indexes)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_only_approved_documents_are_published() {
+        use crate::document::review_workflow::{transition_status, DocumentStatus, APPROVED_DIR};
+
+        let (temp_dir, mut indexer, vector_store) = indexed_workspace().await;
+        let root = temp_dir.path();
+        let report = root.join("notes/report.md");
+        let published = root.join(APPROVED_DIR).join("report.md");
+        let publish = vec![action(
+            "publish_0",
+            vec![PlannedOperation::Move {
+                from: report.clone(),
+                to: published.clone(),
+            }],
+        )];
+
+        let mut indexes = PlanIndexes {
+            indexer: &mut indexer,
+            vector_store: Some(&vector_store),
+        };
+        let error = apply_plan(root, &publish, &mut indexes).await.unwrap_err();
+        assert!(format!("{:#}", error).contains("must be approved"));
+        assert!(report.exists() && !published.exists());
+
+        let id = indexer.get_document_by_path(&report).unwrap().id.clone();
+        indexer
+            .update_metadata(
+                &id,
+                MetadataUpdate {
+                    owners: Some(vec!["sam".to_string()]),
+                    review_interval_days: Some(180),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        for status in [
+            DocumentStatus::Draft,
+            DocumentStatus::InReview,
+            DocumentStatus::Approved,
+        ] {
+            transition_status(&mut indexer, &id, status, "sam", None).unwrap();
+        }
+
+        let mut indexes = PlanIndexes {
+            indexer: &mut indexer,
+            vector_store: Some(&vector_store),
+        };
+        apply_plan(root, &publish, &mut indexes).await.unwrap();
+        assert!(published.exists());
+        let entry = indexer.get_document_by_path(&published).unwrap();
+        assert_eq!(
+            indexer.get_metadata(&entry.id).unwrap().status,
+            Some(DocumentStatus::Approved)
+        );
+
+        // Undoing a withdrawal would publish the document again
+        let withdraw = vec![action(
+            "withdraw_0",
+            vec![PlannedOperation::Move {
+                from: published.clone(),
+                to: report.clone(),
+            }],
+        )];
+        let mut indexes = PlanIndexes {
+            indexer: &mut indexer,
+            vector_store: Some(&vector_store),
+        };
+        let plan_id = apply_plan(root, &withdraw, &mut indexes)
+            .await
+            .unwrap()
+            .plan_id;
+        let id = indexer.get_document_by_path(&report).unwrap().id.clone();
+        transition_status(&mut indexer, &id, DocumentStatus::Retired, "sam", None).unwrap();
+
+        let mut indexes = PlanIndexes {
+            indexer: &mut indexer,
+            vector_store: Some(&vector_store),
+        };
+        let error = undo_plan(root, &plan_id, &mut indexes).await.unwrap_err();
+        assert!(format!("{:#}", error).contains("must be approved"));
+        assert!(report.exists() && !published.exists());
+    }
+}
@@ -5,14 +5,17 @@
 //! relationships, content, and metadata to suggest optimal organizational structures.
 
 use super::intelligence::WorkspaceIntelligence;
+use super::organization_plan::PlannedOperation;
 use crate::ai::AIOrchestrator;
 use crate::document::indexer::DocumentIndexer;
+use crate::document::metadata_store::DocumentTag;
 use crate::document::relationship_analyzer::RelationshipAnalyzer;
 use crate::document::{ContentClassifier, StructureAnalyzer, StyleAnalyzer};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -278,6 +281,9 @@ pub struct OrganizationAction {
     pub estimated_time: ImplementationEffort,
     pub expected_benefit: BenefitLevel,
     pub dependencies: Vec<String>,
+    /// Concrete file-tree operations that carry out the action
+    #[serde(default)]
+    pub operations: Vec<PlannedOperation>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -451,7 +457,10 @@ impl SmartOrganizer {
         let mut suggestions = Vec::new();
 
         for document in &documents {
-            let current_tags = self.extract_current_tags(&document.path);
+            let current_tags = indexer
+                .get_metadata(&document.id)
+                .map(|record| record.tags.iter().map(|tag| tag.name.clone()).collect())
+                .unwrap_or_default();
             let suggested_tags = self.generate_tags_for_document(document, config).await?;
 
             if !suggested_tags.is_empty() {
@@ -665,8 +674,27 @@ impl SmartOrganizer {
         // High-impact categorization actions
         for suggestion in &analysis.categorization_suggestions {
             if suggestion.confidence > 0.8 {
+                let category_folder = suggestion
+                    .category_hierarchy
+                    .iter()
+                    .fold(analysis.workspace_path.clone(), |path, level| {
+                        path.join(level)
+                    });
+                let operations = match suggestion.document_path.file_name() {
+                    Some(name)
+                        if suggestion.document_path.parent() != Some(category_folder.as_path()) =>
+                    {
+                        vec![PlannedOperation::Move {
+                            from: suggestion.document_path.clone(),
+                            to: category_folder.join(name),
+                        }]
+                    }
+                    _ => vec![],
+                };
+
+                let affected_documents = vec![suggestion.document_path.clone()];
                 actions.push(OrganizationAction {
-                    action_id: format!("categorize_{}", actions.len()),
+                    action_id: action_id("categorize", &affected_documents, &operations),
                     action_type: ActionType::Categorize,
                     priority: ActionPriority::High,
                     description: format!(
@@ -674,7 +702,7 @@ impl SmartOrganizer {
                         suggestion.document_path.display(),
                         suggestion.suggested_category
                     ),
-                    affected_documents: vec![suggestion.document_path.clone()],
+                    affected_documents,
                     implementation_steps: vec![
                         "Review document content".to_string(),
                         "Confirm categorization".to_string(),
@@ -683,6 +711,51 @@ impl SmartOrganizer {
                     estimated_time: ImplementationEffort::Minimal,
                     expected_benefit: BenefitLevel::Medium,
                     dependencies: vec![],
+                    operations,
+                });
+            }
+        }
+
+        // Tagging actions
+        for suggestion in &analysis.tagging_suggestions {
+            let tags: Vec<DocumentTag> = suggestion
+                .suggested_tags
+                .iter()
+                .filter(|tag| tag.confidence > 0.8 && !suggestion.current_tags.contains(&tag.tag))
+                .map(|tag| DocumentTag {
+                    name: tag.tag.clone(),
+                    tag_type: tag.tag_type.clone(),
+                })
+                .collect();
+
+            if !tags.is_empty() {
+                let affected_documents = vec![suggestion.document_path.clone()];
+                let description = format!(
+                    "Tag {} with {}",
+                    suggestion.document_path.display(),
+                    tags.iter()
+                        .map(|tag| tag.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                let operations = vec![PlannedOperation::Tag {
+                    document: suggestion.document_path.clone(),
+                    tags,
+                }];
+                actions.push(OrganizationAction {
+                    action_id: action_id("tag", &affected_documents, &operations),
+                    action_type: ActionType::Tag,
+                    priority: ActionPriority::Low,
+                    description,
+                    affected_documents,
+                    implementation_steps: vec![
+                        "Review suggested tags".to_string(),
+                        "Apply tags to document".to_string(),
+                    ],
+                    estimated_time: ImplementationEffort::Minimal,
+                    expected_benefit: BenefitLevel::Low,
+                    dependencies: vec![],
+                    operations,
                 });
             }
         }
@@ -696,8 +769,9 @@ impl SmartOrganizer {
                     _ => ActionPriority::Low,
                 };
 
+                let operations = folder_operations(&suggestion.proposed_structure.folders);
                 actions.push(OrganizationAction {
-                    action_id: format!("restructure_{}", actions.len()),
+                    action_id: action_id("restructure", &suggestion.target_documents, &operations),
                     action_type: ActionType::Restructure,
                     priority,
                     description: format!(
@@ -713,6 +787,7 @@ impl SmartOrganizer {
                     estimated_time: suggestion.implementation_effort.clone(),
                     expected_benefit: BenefitLevel::High,
                     dependencies: vec![],
+                    operations,
                 });
             }
         }
@@ -720,8 +795,22 @@ impl SmartOrganizer {
         // Duplicate handling actions
         for suggestion in &analysis.duplicate_handling {
             if suggestion.similarity_score > 0.9 {
+                let primary = suggestion
+                    .canonical_document
+                    .clone()
+                    .or_else(|| suggestion.duplicate_group.first().cloned());
+                let operations = match primary {
+                    Some(primary) => duplicate_operations(
+                        &suggestion.recommended_action,
+                        &analysis.workspace_path,
+                        &primary,
+                        &suggestion.duplicate_group,
+                    ),
+                    None => vec![],
+                };
+
                 actions.push(OrganizationAction {
-                    action_id: format!("dedupe_{}", actions.len()),
+                    action_id: action_id("dedupe", &suggestion.duplicate_group, &operations),
                     action_type: ActionType::Merge,
                     priority: ActionPriority::Medium,
                     description: format!(
@@ -737,6 +826,7 @@ impl SmartOrganizer {
                     estimated_time: ImplementationEffort::Low,
                     expected_benefit: BenefitLevel::Medium,
                     dependencies: vec![],
+                    operations,
                 });
             }
         }
@@ -876,12 +966,6 @@ impl SmartOrganizer {
         }
     }
 
-    fn extract_current_tags(&self, _path: &Path) -> Vec<String> {
-        // Extract tags from metadata or filename
-        // For now, return empty - could be enhanced
-        vec![]
-    }
-
     async fn generate_tags_for_document(
         &self,
         document: &crate::document::indexer::DocumentIndexEntry,
@@ -1125,6 +1209,97 @@ impl SmartOrganizer {
     }
 }
 
+/// ID naming an action by its kind and what it changes, so the same suggestion
+/// keeps its ID from one analysis to the next
+fn action_id(
+    kind: &str,
+    affected_documents: &[PathBuf],
+    operations: &[PlannedOperation],
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(kind.as_bytes());
+    for document in affected_documents {
+        hasher.update([0]);
+        hasher.update(document.to_string_lossy().as_bytes());
+    }
+    hasher.update([0]);
+    hasher.update(serde_json::to_vec(operations).unwrap_or_default());
+    format!("{}_{}", kind, &hex::encode(hasher.finalize())[..16])
+}
+
+/// File-tree operations carrying out a duplicate handling recommendation
+fn duplicate_operations(
+    action: &DuplicateAction,
+    workspace_path: &Path,
+    primary: &Path,
+    group: &[PathBuf],
+) -> Vec<PlannedOperation> {
+    let primary_stem = primary
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let duplicates = group
+        .iter()
+        .filter(|document| document.as_path() != primary);
+
+    match action {
+        DuplicateAction::KeepPrimary => {
+            let tag = DocumentTag::custom(format!("duplicate-of-{}", primary_stem));
+            duplicates
+                .map(|document| PlannedOperation::Tag {
+                    document: document.clone(),
+                    tags: vec![tag.clone()],
+                })
+                .collect()
+        }
+        DuplicateAction::MergeContent => vec![PlannedOperation::Merge {
+            primary: primary.to_path_buf(),
+            duplicates: duplicates.cloned().collect(),
+        }],
+        DuplicateAction::CreateReference => {
+            let reference = primary
+                .strip_prefix(workspace_path)
+                .unwrap_or(primary)
+                .to_string_lossy()
+                .to_string();
+            duplicates
+                .map(|document| PlannedOperation::AddMetadata {
+                    document: document.clone(),
+                    fields: BTreeMap::from([("canonical_document".to_string(), reference.clone())]),
+                })
+                .collect()
+        }
+        DuplicateAction::ArchiveRedundant => duplicates
+            .map(|document| PlannedOperation::Archive {
+                document: document.clone(),
+            })
+            .collect(),
+        DuplicateAction::TagAsVersions => {
+            let tag = DocumentTag::custom(format!("versions-of-{}", primary_stem));
+            group
+                .iter()
+                .map(|document| PlannedOperation::Tag {
+                    document: document.clone(),
+                    tags: vec![tag.clone()],
+                })
+                .collect()
+        }
+    }
+}
+
+/// Folder creation operations for a proposed structure, parents before children
+fn folder_operations(folders: &[FolderNode]) -> Vec<PlannedOperation> {
+    folders
+        .iter()
+        .flat_map(|folder| {
+            std::iter::once(PlannedOperation::CreateFolder {
+                path: folder.path.clone(),
+            })
+            .chain(folder_operations(&folder.subfolders))
+        })
+        .collect()
+}
+
 /// Category classification result
 struct CategoryClassification {
     category: String,
@@ -1194,4 +1369,60 @@ mod tests {
         assert_ne!(by_topic, by_type);
         assert!(matches!(hybrid, OrganizationPrinciple::Hybrid(_)));
     }
+
+    #[test]
+    fn test_duplicate_actions_plan_their_own_operations() {
+        let root = Path::new("/workspace");
+        let primary = root.join("docs/a.md");
+        let copy = root.join("docs/b.md");
+        let group = vec![primary.clone(), copy.clone()];
+        let plan = |action: DuplicateAction| duplicate_operations(&action, root, &primary, &group);
+
+        match plan(DuplicateAction::KeepPrimary).as_slice() {
+            [PlannedOperation::Tag { document, tags }] => {
+                assert_eq!(document, &copy);
+                assert_eq!(tags[0].name, "duplicate-of-a");
+            }
+            other => panic!("unexpected operations: {:?}", other),
+        }
+        match plan(DuplicateAction::MergeContent).as_slice() {
+            [PlannedOperation::Merge {
+                primary: kept,
+                duplicates,
+            }] => {
+                assert_eq!(kept, &primary);
+                assert_eq!(duplicates, &vec![copy.clone()]);
+            }
+            other => panic!("unexpected operations: {:?}", other),
+        }
+        match plan(DuplicateAction::CreateReference).as_slice() {
+            [PlannedOperation::AddMetadata { document, fields }] => {
+                assert_eq!(document, &copy);
+                assert_eq!(fields["canonical_document"], "docs/a.md");
+            }
+            other => panic!("unexpected operations: {:?}", other),
+        }
+        assert!(matches!(
+            plan(DuplicateAction::ArchiveRedundant).as_slice(),
+            [PlannedOperation::Archive { document }] if *document == copy
+        ));
+        assert_eq!(plan(DuplicateAction::TagAsVersions).len(), 2);
+    }
+
+    #[test]
+    fn test_action_ids_follow_what_the_action_does() {
+        let document = vec![PathBuf::from("/workspace/a.md")];
+        let tag = |name: &str| {
+            vec![PlannedOperation::Tag {
+                document: document[0].clone(),
+                tags: vec![DocumentTag::custom(name)],
+            }]
+        };
+
+        let id = action_id("tag", &document, &tag("finance"));
+        assert!(id.starts_with("tag_"));
+        assert_eq!(id, action_id("tag", &document, &tag("finance")));
+        assert_ne!(id, action_id("tag", &document, &tag("legal")));
+        assert_ne!(id, action_id("dedupe", &document, &tag("finance")));
+    }
 }
@@ -0,0 +1,36 @@
+// src-tauri/src/workspace/test_fixtures.rs
+//! Workspaces on disk for tests of backups, replication and bundles
+
+use super::{ImportSettings, WorkspaceAISettings, WorkspaceInfo, WORKSPACE_METADATA_FILE};
+use chrono::Utc;
+use std::fs;
+use std::path::Path;
+
+/// Create a workspace named `name` at `path` holding `files`, given as
+/// workspace-relative paths and their contents
+pub fn create_workspace(path: &Path, name: &str, files: &[(&str, &[u8])]) {
+    let now = Utc::now();
+    let info = WorkspaceInfo {
+        path: path.to_path_buf(),
+        name: name.to_string(),
+        version: "1.0.0".to_string(),
+        created: now,
+        last_modified: now,
+        last_accessed: now,
+        import_settings: ImportSettings::default(),
+        ai_settings: WorkspaceAISettings::default(),
+        is_favorite: false,
+        access_count: 0,
+    };
+    fs::create_dir_all(path.join(".fiovana")).unwrap();
+    fs::write(
+        path.join(WORKSPACE_METADATA_FILE),
+        serde_json::to_string(&info).unwrap(),
+    )
+    .unwrap();
+    for (relative, contents) in files {
+        let file = path.join(relative);
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(file, contents).unwrap();
+    }
+}
@@ -0,0 +1,545 @@
+// src-tauri/src/workspace/usage_log.rs
+//! Persisted document usage events for lifecycle analysis
+//!
+//! Each workspace keeps an append-only log of usage events (opened in the
+//! editor, cited in an AI answer, returned by a search, exported). Once the log
+//! grows past a size threshold it is folded into per-document daily counts, so
+//! lifecycle analysis can look back over rolling windows without the log
+//! growing without bound. Each raw log starts with a sequence number; the
+//! counts record which log they folded, so a log left behind by a crash during
+//! compaction is not counted twice.
+
+use crate::filesystem::atomic_write::write_atomically;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::WORKSPACE_METADATA_FILE;
+
+/// Directory holding the usage log, relative to the workspace root
+pub const USAGE_DIR: &str = ".fiovana/usage";
+
+/// Append-only file of events not yet folded into daily counts
+const EVENTS_FILE: &str = "events.jsonl";
+
+/// Compacted per-document daily counts
+const DAILY_FILE: &str = "daily.json";
+
+/// Size at which the raw event log is folded into daily counts
+const COMPACTION_THRESHOLD_BYTES: u64 = 256 * 1024;
+
+/// Daily counts older than this are dropped during compaction
+const RETENTION_DAYS: i64 = 730;
+
+/// Serializes appends and compaction across concurrent commands. Only taken
+/// on blocking threads.
+static LOG_LOCK: Mutex<()> = Mutex::new(());
+
+/// First line of the raw event log
+#[derive(Debug, Serialize, Deserialize)]
+struct LogHeader {
+    sequence: u64,
+}
+
+/// How a document was used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageEventKind {
+    /// Opened in the editor
+    Opened,
+    /// Cited in an AI answer
+    Cited,
+    /// Returned by a document search
+    SearchResult,
+    /// Exported or converted to another format
+    Exported,
+}
+
+/// A single recorded use of a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageEvent {
+    /// Document path relative to the workspace root
+    pub path: PathBuf,
+    pub kind: UsageEventKind,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Usage counts of one document on one day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DailyUsage {
+    #[serde(default)]
+    pub opened: u32,
+    #[serde(default)]
+    pub cited: u32,
+    #[serde(default)]
+    pub search_results: u32,
+    #[serde(default)]
+    pub exported: u32,
+}
+
+impl DailyUsage {
+    fn add(&mut self, kind: UsageEventKind) {
+        let count = match kind {
+            UsageEventKind::Opened => &mut self.opened,
+            UsageEventKind::Cited => &mut self.cited,
+            UsageEventKind::SearchResult => &mut self.search_results,
+            UsageEventKind::Exported => &mut self.exported,
+        };
+        *count = count.saturating_add(1);
+    }
+
+    pub fn total(&self) -> u32 {
+        self.opened + self.cited + self.search_results + self.exported
+    }
+}
+
+/// Usage history of one document
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocumentUsageHistory {
+    pub last_accessed: Option<DateTime<Utc>>,
+    pub days: BTreeMap<NaiveDate, DailyUsage>,
+}
+
+impl DocumentUsageHistory {
+    fn add(&mut self, event: &UsageEvent) {
+        self.days
+            .entry(event.timestamp.date_naive())
+            .or_default()
+            .add(event.kind);
+        if self.last_accessed.is_none_or(|last| event.timestamp > last) {
+            self.last_accessed = Some(event.timestamp);
+        }
+    }
+
+    /// Total uses on days in `[from, to)`
+    pub fn count_between(&self, from: NaiveDate, to: NaiveDate) -> u32 {
+        self.days
+            .range(from..to)
+            .map(|(_, usage)| usage.total())
+            .sum()
+    }
+
+    /// Total uses over the retained history
+    pub fn total(&self) -> u32 {
+        self.days.values().map(DailyUsage::total).sum()
+    }
+
+    pub fn first_day(&self) -> Option<NaiveDate> {
+        self.days.keys().next().copied()
+    }
+}
+
+/// On-disk form of the compacted counts
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CompactedUsage {
+    /// Sequence number of the raw log last folded into these counts; a log
+    /// with that number is skipped on load
+    #[serde(default)]
+    compacted_log: Option<u64>,
+    documents: BTreeMap<PathBuf, DocumentUsageHistory>,
+}
+
+/// Usage history of every document in a workspace
+#[derive(Debug, Clone)]
+pub struct UsageLog {
+    workspace_path: PathBuf,
+    compacted_log: Option<u64>,
+    documents: HashMap<PathBuf, DocumentUsageHistory>,
+}
+
+impl UsageLog {
+    /// Load the compacted counts and any raw events recorded since
+    pub fn open(workspace_path: &Path) -> Result<Self> {
+        let _guard = LOG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        Self::load(workspace_path)
+    }
+
+    /// `open` on the blocking thread pool, for async callers
+    pub async fn open_async(workspace_path: &Path) -> Result<Self> {
+        let workspace_path = workspace_path.to_path_buf();
+        tokio::task::spawn_blocking(move || Self::open(&workspace_path)).await?
+    }
+
+    fn empty(workspace_path: &Path) -> Self {
+        Self {
+            workspace_path: workspace_path.to_path_buf(),
+            compacted_log: None,
+            documents: HashMap::new(),
+        }
+    }
+
+    fn load(workspace_path: &Path) -> Result<Self> {
+        let dir = workspace_path.join(USAGE_DIR);
+        let compacted = read_compacted(&dir)?;
+        let mut log = Self {
+            compacted_log: compacted.compacted_log,
+            documents: compacted.documents.into_iter().collect(),
+            ..Self::empty(workspace_path)
+        };
+
+        let events_path = dir.join(EVENTS_FILE);
+        if events_path.exists() {
+            let data = fs::read_to_string(&events_path).context("Failed to read usage log")?;
+            let sequence = data.lines().next().and_then(header_sequence);
+            // Left behind by a crash after its events were folded into the counts
+            if sequence.is_some() && sequence == log.compacted_log {
+                return Ok(log);
+            }
+            // A line cut short by a crash is skipped rather than failing the whole log
+            for event in data
+                .lines()
+                .filter_map(|line| serde_json::from_str::<UsageEvent>(line).ok())
+            {
+                log.add(&event);
+            }
+        }
+
+        Ok(log)
+    }
+
+    pub fn history(&self, document_path: &Path) -> Option<&DocumentUsageHistory> {
+        self.documents.get(&self.key(document_path))
+    }
+
+    pub fn documents(&self) -> impl Iterator<Item = &DocumentUsageHistory> {
+        self.documents.values()
+    }
+
+    /// `record` on the blocking thread pool, for async callers
+    pub async fn record_async(
+        &mut self,
+        document_path: &Path,
+        kind: UsageEventKind,
+        timestamp: DateTime<Utc>,
+    ) -> Result<()> {
+        let empty = Self::empty(&self.workspace_path);
+        let mut log = std::mem::replace(self, empty);
+        let document_path = document_path.to_path_buf();
+        let (log, result) = tokio::task::spawn_blocking(move || {
+            let result = log.record(&document_path, kind, timestamp);
+            (log, result)
+        })
+        .await?;
+        *self = log;
+        result
+    }
+
+    /// Record a use of a document and persist it
+    pub fn record(
+        &mut self,
+        document_path: &Path,
+        kind: UsageEventKind,
+        timestamp: DateTime<Utc>,
+    ) -> Result<()> {
+        let event = UsageEvent {
+            path: self.key(document_path),
+            kind,
+            timestamp,
+        };
+
+        let _guard = LOG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let log_size = append_events(&self.workspace_path, std::slice::from_ref(&event))?;
+        self.add(&event);
+        if log_size >= COMPACTION_THRESHOLD_BYTES {
+            // Reload first so events recorded elsewhere since opening are not dropped
+            *self = Self::load(&self.workspace_path)?;
+            self.compact(Utc::now())?;
+        }
+        Ok(())
+    }
+
+    /// Fold raw events into daily counts and drop days past the retention period
+    ///
+    /// Callers must hold `LOG_LOCK`.
+    fn compact(&mut self, now: DateTime<Utc>) -> Result<()> {
+        let cutoff = (now - Duration::days(RETENTION_DAYS)).date_naive();
+        for history in self.documents.values_mut() {
+            history.days.retain(|day, _| *day >= cutoff);
+        }
+        self.documents.retain(|_, history| !history.days.is_empty());
+
+        let dir = self.workspace_path.join(USAGE_DIR);
+        let folded_log = log_sequence(&dir.join(EVENTS_FILE))?.or(self.compacted_log);
+        let compacted = CompactedUsage {
+            compacted_log: folded_log,
+            documents: self
+                .documents
+                .iter()
+                .map(|(path, history)| (path.clone(), history.clone()))
+                .collect(),
+        };
+
+        fs::create_dir_all(&dir).context("Failed to create usage directory")?;
+        let data =
+            serde_json::to_string_pretty(&compacted).context("Failed to serialize usage counts")?;
+        write_atomically(&dir.join(DAILY_FILE), data.as_bytes())
+            .context("Failed to write usage counts")?;
+
+        // The counts now name the folded log, so a crash before it is replaced
+        // leaves a log that is skipped on load
+        start_log(&dir, folded_log.map_or(0, |sequence| sequence + 1))?;
+        self.compacted_log = folded_log;
+
+        tracing::debug!(
+            "Compacted usage log for {} ({} documents)",
+            self.workspace_path.display(),
+            self.documents.len()
+        );
+        Ok(())
+    }
+
+    fn add(&mut self, event: &UsageEvent) {
+        self.documents
+            .entry(event.path.clone())
+            .or_default()
+            .add(event);
+    }
+
+    /// Documents are keyed by workspace-relative path so the log survives moving the workspace
+    fn key(&self, document_path: &Path) -> PathBuf {
+        document_path
+            .strip_prefix(&self.workspace_path)
+            .unwrap_or(document_path)
+            .to_path_buf()
+    }
+}
+
+/// Record uses of documents in whichever workspaces contain them
+///
+/// Documents outside any workspace are ignored. The log is written on the
+/// blocking thread pool without holding up the caller, and failures are logged
+/// rather than returned so usage tracking never breaks the operation being
+/// tracked.
+pub fn record_document_usage<P: AsRef<Path>>(document_paths: &[P], kind: UsageEventKind) {
+    let document_paths: Vec<PathBuf> = document_paths
+        .iter()
+        .map(|path| path.as_ref().to_path_buf())
+        .collect();
+    let timestamp = Utc::now();
+    tokio::task::spawn_blocking(move || append_document_usage(&document_paths, kind, timestamp));
+}
+
+fn append_document_usage(
+    document_paths: &[PathBuf],
+    kind: UsageEventKind,
+    timestamp: DateTime<Utc>,
+) {
+    let mut by_workspace: HashMap<PathBuf, Vec<UsageEvent>> = HashMap::new();
+    for path in document_paths {
+        if let Some(workspace_path) = workspace_root_of(path) {
+            let relative = path
+                .strip_prefix(&workspace_path)
+                .unwrap_or(path)
+                .to_path_buf();
+            by_workspace
+                .entry(workspace_path)
+                .or_default()
+                .push(UsageEvent {
+                    path: relative,
+                    kind,
+                    timestamp,
+                });
+        }
+    }
+
+    let _guard = LOG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    for (workspace_path, events) in by_workspace {
+        let result = append_events(&workspace_path, &events).and_then(|log_size| {
+            if log_size >= COMPACTION_THRESHOLD_BYTES {
+                UsageLog::load(&workspace_path)?.compact(timestamp)?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            tracing::warn!(
+                "Failed to record document usage in {}: {}",
+                workspace_path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// The workspace containing a path, if any
+pub fn workspace_root_of(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join(WORKSPACE_METADATA_FILE).is_file())
+        .map(Path::to_path_buf)
+}
+
+fn read_compacted(dir: &Path) -> Result<CompactedUsage> {
+    let daily_path = dir.join(DAILY_FILE);
+    if !daily_path.exists() {
+        return Ok(CompactedUsage::default());
+    }
+    let data = fs::read_to_string(&daily_path).context("Failed to read usage counts")?;
+    serde_json::from_str(&data).context("Failed to parse usage counts")
+}
+
+fn header_sequence(line: &str) -> Option<u64> {
+    serde_json::from_str::<LogHeader>(line)
+        .ok()
+        .map(|header| header.sequence)
+}
+
+/// Sequence number of the raw log, if there is one with a header
+fn log_sequence(events_path: &Path) -> Result<Option<u64>> {
+    if !events_path.exists() {
+        return Ok(None);
+    }
+    let mut first_line = String::new();
+    BufReader::new(fs::File::open(events_path).context("Failed to open usage log")?)
+        .read_line(&mut first_line)
+        .context("Failed to read usage log")?;
+    Ok(header_sequence(&first_line))
+}
+
+/// Replace the raw log with an empty one numbered `sequence`
+fn start_log(dir: &Path, sequence: u64) -> Result<()> {
+    let mut header =
+        serde_json::to_string(&LogHeader { sequence }).context("Failed to serialize usage log")?;
+    header.push('\n');
+    write_atomically(&dir.join(EVENTS_FILE), header.as_bytes()).context("Failed to start usage log")
+}
+
+/// Append events to the raw log, returning its new size. Callers must hold `LOG_LOCK`.
+fn append_events(workspace_path: &Path, events: &[UsageEvent]) -> Result<u64> {
+    let dir = workspace_path.join(USAGE_DIR);
+    fs::create_dir_all(&dir).context("Failed to create usage directory")?;
+    if !dir.join(EVENTS_FILE).exists() {
+        let sequence = read_compacted(&dir)?
+            .compacted_log
+            .map_or(0, |sequence| sequence + 1);
+        start_log(&dir, sequence)?;
+    }
+
+    let mut lines = String::new();
+    for event in events {
+        lines.push_str(&serde_json::to_string(event).context("Failed to serialize usage event")?);
+        lines.push('\n');
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(EVENTS_FILE))
+        .context("Failed to open usage log")?;
+    file.write_all(lines.as_bytes())
+        .context("Failed to append to usage log")?;
+    Ok(file.metadata().map(|m| m.len()).unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn workspace() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join(".fiovana")).unwrap();
+        fs::write(temp_dir.path().join(WORKSPACE_METADATA_FILE), "{}").unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_usage_survives_reload_and_compaction() {
+        let temp_dir = workspace();
+        let document = temp_dir.path().join("sources/imports/plan.md");
+        let now = Utc::now();
+
+        let mut log = UsageLog::open(temp_dir.path()).unwrap();
+        log.record(&document, UsageEventKind::Opened, now - Duration::days(40))
+            .unwrap();
+        log.record(&document, UsageEventKind::Cited, now).unwrap();
+        append_document_usage(&[document.clone()], UsageEventKind::SearchResult, now);
+        append_document_usage(
+            &[PathBuf::from("/outside/any/workspace.md")],
+            UsageEventKind::Opened,
+            now,
+        );
+
+        let reloaded = UsageLog::open(temp_dir.path()).unwrap();
+        let history = reloaded.history(&document).unwrap();
+        assert_eq!(history.total(), 3);
+        assert_eq!(reloaded.documents().count(), 1);
+
+        // Compaction keeps the counts and starts a new raw log
+        let mut compacted = reloaded.clone();
+        compacted.compact(now).unwrap();
+        let events = fs::read_to_string(temp_dir.path().join(USAGE_DIR).join(EVENTS_FILE)).unwrap();
+        assert_eq!(events.lines().count(), 1);
+
+        let reloaded = UsageLog::open(temp_dir.path()).unwrap();
+        let history = reloaded.history(&document).unwrap();
+        assert_eq!(history.total(), 3);
+        let today = now.date_naive();
+        assert_eq!(
+            history.count_between(today - Duration::days(30), today + Duration::days(1)),
+            2
+        );
+    }
+
+    #[test]
+    fn test_compaction_is_tracked_by_log_not_timestamp() {
+        let temp_dir = workspace();
+        let document = temp_dir.path().join("notes.md");
+        let events_path = temp_dir.path().join(USAGE_DIR).join(EVENTS_FILE);
+        let now = Utc::now();
+
+        let mut log = UsageLog::open(temp_dir.path()).unwrap();
+        log.record(&document, UsageEventKind::Opened, now).unwrap();
+        let folded_log = fs::read_to_string(&events_path).unwrap();
+        log.compact(now).unwrap();
+
+        // An event stamped before the compaction still counts
+        log.record(&document, UsageEventKind::Cited, now - Duration::days(3))
+            .unwrap();
+        assert_eq!(
+            UsageLog::open(temp_dir.path())
+                .unwrap()
+                .history(&document)
+                .unwrap()
+                .total(),
+            2
+        );
+
+        // A folded log left behind by a crash is not counted again
+        fs::write(&events_path, folded_log).unwrap();
+        assert_eq!(
+            UsageLog::open(temp_dir.path())
+                .unwrap()
+                .history(&document)
+                .unwrap()
+                .total(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_compaction_drops_expired_days() {
+        let temp_dir = workspace();
+        let document = temp_dir.path().join("old.md");
+        let now = Utc::now();
+
+        let mut log = UsageLog::open(temp_dir.path()).unwrap();
+        log.record(
+            &document,
+            UsageEventKind::Exported,
+            now - Duration::days(RETENTION_DAYS + 1),
+        )
+        .unwrap();
+        log.compact(now).unwrap();
+
+        assert!(log.history(&document).is_none());
+        assert!(UsageLog::open(temp_dir.path())
+            .unwrap()
+            .history(&document)
+            .is_none());
+    }
+}
@@ -0,0 +1,94 @@
+// src-tauri/tests/collaboration_sync_tests.rs
+// Sync agents converging through a local collaboration server after a network partition
+
+use fiovana::collaboration::auth::CollaborationRole;
+use fiovana::collaboration::sync_agent::{ConnectionState, SyncAgent, SyncAgentConfig};
+use fiovana::collaboration::{CollaborationServer, ServerConfig};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+async fn eventually(description: &str, condition: impl Fn() -> bool) {
+    let waited = tokio::time::timeout(Duration::from_secs(10), async {
+        while !condition() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await;
+    assert!(waited.is_ok(), "Timed out waiting until {}", description);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_agents_converge_after_partition() {
+    let port = free_port();
+    let server = Arc::new(CollaborationServer::new(ServerConfig {
+        port,
+        ..Default::default()
+    }));
+    let running = Arc::clone(&server);
+    tokio::spawn(async move {
+        let _ = running.start().await;
+    });
+
+    let agent = |user_id: &str| {
+        let token = server.access_control().issue_token(
+            user_id,
+            user_id,
+            Some(CollaborationRole::Editor),
+            None,
+        );
+        SyncAgent::start(SyncAgentConfig {
+            server_url: format!("ws://127.0.0.1:{}", port),
+            token: token.token,
+            document_id: "procedure".to_string(),
+            certificate_fingerprint: None,
+            reconnect_delay: Duration::from_millis(50),
+            queue_dir: None,
+        })
+    };
+    let alice = agent("alice");
+    let bob = agent("bob");
+    let online = |agent: &SyncAgent| agent.status().state == ConnectionState::Online;
+    eventually("both agents are online", || online(&alice) && online(&bob)).await;
+
+    // Live edits reach the other replica through the server
+    assert!(alice.replace_text("Step 1: isolate the pump"));
+    eventually("bob receives alice's edit", || {
+        bob.text() == "Step 1: isolate the pump"
+    })
+    .await;
+
+    // Both sides keep editing during a partition
+    alice.set_online(false);
+    bob.set_online(false);
+    eventually("both agents are offline", || {
+        !online(&alice) && !online(&bob)
+    })
+    .await;
+
+    alice.replace_text("Step 1: isolate the pump\nStep 2: drain the line");
+    bob.replace_text("# Shutdown\nStep 1: isolate the pump");
+    assert_eq!(alice.status().pending_updates, 1);
+    assert_eq!(bob.status().pending_updates, 1);
+
+    // Reconnecting merges both directions without losing either edit
+    alice.set_online(true);
+    bob.set_online(true);
+    let expected = "# Shutdown\nStep 1: isolate the pump\nStep 2: drain the line";
+    eventually("the replicas converge", || {
+        alice.text() == expected && bob.text() == expected
+    })
+    .await;
+    assert_eq!(alice.status().pending_updates, 0);
+    assert_eq!(bob.status().pending_updates, 0);
+
+    let room = server.with_history("procedure", |history| history.text_at(history.latest_seq()));
+    assert_eq!(room.unwrap().unwrap(), expected);
+}
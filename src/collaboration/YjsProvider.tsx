@@ -8,6 +8,8 @@ export interface YjsProviderProps {
   documentId: string
   username?: string
   userColor?: string
+  /** Collaboration access token issued by `create_collaboration_token` */
+  accessToken?: string
   onSync?: (isSynced: boolean) => void
   onError?: (error: Error) => void
   onStatusChange?: (status: ProviderStatus) => void
@@ -31,6 +33,7 @@ const YjsProvider: React.FC<YjsProviderProps> = ({
   documentId,
   username = 'Anonymous',
   userColor = '#' + Math.floor(Math.random() * 16777215).toString(16),
+  accessToken,
   onSync,
   onError,
   onStatusChange,
@@ -128,6 +131,7 @@ const YjsProvider: React.FC<YjsProviderProps> = ({
         const provider = new WebsocketProvider(wsUrl, documentId, ydoc, {
           connect: true,
           awareness: new Awareness(ydoc),
+          params: accessToken ? { token: accessToken } : undefined,
         })
 
         // Set user info in awareness
@@ -222,7 +226,7 @@ const YjsProvider: React.FC<YjsProviderProps> = ({
         tryWebRTCFallback()
       }
     },
-    [documentId, username, userColor, onSync, onError, tryWebRTCFallback]
+    [documentId, username, userColor, accessToken, onSync, onError, tryWebRTCFallback]
   )
 
   useEffect(() => {
@@ -34,6 +34,8 @@ export interface UseYjsCollaborationOptions {
   editor: LexicalEditor | null
   username?: string
   userColor?: string
+  /** Collaboration access token issued by `create_collaboration_token` */
+  accessToken?: string
   enabled?: boolean
   onCursorChange?: (cursor: CursorPosition) => void
 }
@@ -50,6 +52,7 @@ export const useYjsCollaboration = ({
   editor,
   username = 'Anonymous',
   userColor = '#' + Math.floor(Math.random() * 16777215).toString(16),
+  accessToken,
   enabled = true,
 }: UseYjsCollaborationOptions) => {
   const [state, setState] = useState<CollaborationState>({
@@ -107,6 +110,7 @@ export const useYjsCollaboration = ({
 
       provider = new WebsocketProvider(wsUrl, documentId, ydoc, {
         connect: true,
+        params: accessToken ? { token: accessToken } : undefined,
       })
 
       // Set user info in awareness with proper structure
@@ -207,7 +211,7 @@ export const useYjsCollaboration = ({
       providerRef.current?.destroy()
       docRef.current?.destroy()
     }
-  }, [documentId, editor, username, userColor, enabled, updateUsers])
+  }, [documentId, editor, username, userColor, accessToken, enabled, updateUsers])
 
   // Method to update cursor position
   const updateCursorPosition = useCallback((cursor: CursorPosition) => {